@@ -7,6 +7,7 @@
 //! - `/actuator/info` - Application information
 //! - `/actuator/metrics` - Metrics endpoint
 //! - `/actuator/env` - Environment information
+//! - `/actuator/mappings` - Route mappings from [`nexus_router::RouteDescriptor`]s
 //! - `/actuator` - Actuator index
 //!
 //! # Example / 示例
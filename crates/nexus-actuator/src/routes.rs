@@ -9,6 +9,7 @@ use crate::health::{HealthCheck, SystemHealthIndicator};
 use crate::info::{AppInfo, InfoBuilder};
 use crate::metrics::MetricsRegistry;
 use nexus_http::{Body, Request, Response, StatusCode};
+use nexus_router::RouteDescriptor;
 use std::sync::Arc;
 
 /// Actuator routes
@@ -16,7 +17,7 @@ use std::sync::Arc;
 ///
 /// # Spring Equivalent / Spring 等价物
 ///
-/// Equivalent to Spring Boot Actuator with endpoints: /health, /info, /metrics
+/// Equivalent to Spring Boot Actuator with endpoints: /health, /info, /metrics, /env, /mappings
 ///
 /// # Example / 示例
 ///
@@ -61,6 +62,14 @@ pub struct Actuator {
     /// Environment collector
     /// 环境收集器
     env_collector: Arc<EnvironmentCollector>,
+
+    /// Enable mappings endpoint
+    /// 启用映射端点
+    enable_mappings: bool,
+
+    /// Route descriptors rendered by the mappings endpoint
+    /// 映射端点渲染的路由描述符
+    mappings: Arc<Vec<RouteDescriptor>>,
 }
 
 impl Actuator {
@@ -79,6 +88,8 @@ impl Actuator {
             enable_metrics: true,
             enable_env: true,
             env_collector: Arc::new(EnvironmentCollector::new()),
+            enable_mappings: true,
+            mappings: Arc::new(Vec::new()),
         }
     }
 
@@ -134,6 +145,27 @@ impl Actuator {
         self
     }
 
+    /// Enable or disable mappings endpoint
+    /// 启用或禁用映射端点
+    pub fn enable_mappings(mut self, enable: bool) -> Self {
+        self.enable_mappings = enable;
+        self
+    }
+
+    /// Set the routes listed by the mappings endpoint
+    /// 设置映射端点列出的路由
+    ///
+    /// # Example / 示例
+    ///
+    /// ```rust,no_run,ignore
+    /// let router = Router::new().get("/users/:id", get_user);
+    /// let actuator = Actuator::new().with_mappings(router.descriptors());
+    /// ```
+    pub fn with_mappings(mut self, mappings: Vec<RouteDescriptor>) -> Self {
+        self.mappings = Arc::new(mappings);
+        self
+    }
+
     /// Set the environment collector
     /// 设置环境收集器
     pub fn with_env_collector(mut self, collector: EnvironmentCollector) -> Self {
@@ -201,6 +233,14 @@ impl Actuator {
             links.insert("env".to_string(), env_link);
         }
 
+        if self.enable_mappings {
+            let mappings_link = serde_json::json!({
+                "href": "/actuator/mappings",
+                "templated": false
+            });
+            links.insert("mappings".to_string(), mappings_link);
+        }
+
         let body = serde_json::to_vec(&links).unwrap_or_default();
         Response::new(StatusCode::OK).with_body(Body::from(body))
     }
@@ -283,6 +323,18 @@ impl Actuator {
         Response::new(StatusCode::OK).with_body(Body::from(body))
     }
 
+    /// Handle the mappings request
+    /// 处理映射请求
+    pub fn handle_mappings(&self, _req: &Request) -> Response {
+        if !self.enable_mappings {
+            return Response::new(StatusCode::NOT_FOUND);
+        }
+
+        let mappings = serde_json::json!({ "mappings": &*self.mappings });
+        let body = serde_json::to_vec(&mappings).unwrap_or_default();
+        Response::new(StatusCode::OK).with_body(Body::from(body))
+    }
+
     /// Handle the specific property request (e.g., /env/some.property)
     /// 处理特定属性请求（例如 /env/some.property）
     pub fn handle_property(&self, key: &str, _req: &Request) -> Response {
@@ -326,6 +378,7 @@ pub fn handle_request(actuator: Arc<Actuator>, req: &Request) -> Response {
         "/info" => actuator.handle_info(req),
         "/metrics" => actuator.handle_metrics(req),
         "/env" => actuator.handle_env(req),
+        "/mappings" => actuator.handle_mappings(req),
         path if path.starts_with("/metrics/") => {
             let name = &path[10..]; // Remove "/metrics/"
             actuator.handle_metric(name, req)
@@ -359,6 +412,7 @@ mod tests {
         assert!(actuator.enable_info);
         assert!(actuator.enable_metrics);
         assert!(actuator.enable_env);
+        assert!(actuator.enable_mappings);
     }
 
     #[test]
@@ -381,4 +435,25 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[test]
+    fn test_handle_mappings() {
+        let router = nexus_router::Router::new()
+            .get("/users/{id:u64}", "User")
+            .with_metadata(nexus_router::RouteMetadata::new().tag("users"));
+        let actuator = Actuator::new().with_mappings(router.descriptors());
+        let request = Request::from_method_uri(nexus_http::Method::GET, "/actuator/mappings");
+
+        let response = handle_request(Arc::new(actuator), &request);
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(response.body().data()).unwrap();
+        let mapping = &body["mappings"][0];
+        assert_eq!(mapping["method"], "GET");
+        assert_eq!(mapping["pattern"], "/users/{id:u64}");
+        assert_eq!(mapping["params"][0]["kind"], serde_json::json!({ "type": "u64" }));
+        assert_eq!(mapping["metadata"]["tags"], serde_json::json!(["users"]));
+
+        let disabled = Actuator::new().enable_mappings(false);
+        assert_eq!(disabled.handle_mappings(&request).status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,141 @@
+//! I/O Driver Benchmarks
+//! I/O驱动基准测试
+//!
+//! # Equivalent to Spring Boot / 等价于 Spring Boot
+//!
+//! - Reactor Netty's epoll vs io_uring transports
+//!
+//! # Goals / 目标
+//!
+//! - Compare a readiness receive (epoll + read) with a completion receive (io_uring)
+//! - Measure the cost of kernel-selected buffers from a buffer ring
+//!
+//! Each iteration sends a small message over a Unix socket pair and receives it
+//! through the driver. Linux only; elsewhere the benchmark is empty.
+//! 每次迭代通过Unix套接字对发送一条小消息并经由驱动接收。仅限Linux；其他平台上基准测试为空。
+
+#![warn(missing_docs)]
+#![warn(unreachable_pub)]
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use criterion::Criterion;
+    use nexus_runtime::driver::epoll::EpollDriver;
+    use nexus_runtime::driver::iouring::IoUringDriver;
+    use nexus_runtime::driver::{CompletionEntry, Driver, Interest, SubmitEntry};
+    use std::hint::black_box;
+    use std::io::{Read, Write};
+    use std::os::fd::AsRawFd;
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    /// Message sent per iteration / 每次迭代发送的消息
+    const MESSAGE: &[u8] = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+
+    /// Wait for the next completion of a driver
+    /// 等待驱动的下一个完成事件
+    fn next_completion(driver: &impl Driver) -> CompletionEntry {
+        loop {
+            if let Some(completion) = driver.get_completion() {
+                let completion = *completion;
+                driver.advance_completion();
+                return completion;
+            }
+            driver.wait_timeout(Duration::from_millis(100)).unwrap();
+        }
+    }
+
+    /// Benchmark: epoll readiness followed by a read / epoll就绪通知后读取
+    pub(crate) fn bench_epoll_recv(c: &mut Criterion) {
+        let driver = EpollDriver::new().unwrap();
+        let (mut tx, mut rx) = UnixStream::pair().unwrap();
+        driver
+            .register(rx.as_raw_fd(), Interest::readable())
+            .unwrap();
+        let mut buf = [0u8; 512];
+
+        c.bench_function("driver_recv_epoll", |b| {
+            b.iter(|| {
+                tx.write_all(MESSAGE).unwrap();
+                next_completion(&driver);
+                black_box(rx.read(&mut buf).unwrap())
+            });
+        });
+    }
+
+    /// Benchmark: io_uring completion receive into a caller buffer
+    /// io_uring完成式接收到调用方缓冲区
+    pub(crate) fn bench_iouring_recv(c: &mut Criterion) {
+        let Ok(driver) = IoUringDriver::new() else {
+            return;
+        };
+        let (mut tx, rx) = UnixStream::pair().unwrap();
+        let mut buf = [0u8; 512];
+
+        c.bench_function("driver_recv_iouring", |b| {
+            b.iter(|| {
+                tx.write_all(MESSAGE).unwrap();
+                *driver.get_submission().unwrap() = unsafe {
+                    SubmitEntry::recv(rx.as_raw_fd(), buf.as_mut_ptr(), buf.len() as u32, 1)
+                };
+                driver.submit().unwrap();
+                black_box(next_completion(&driver).result)
+            });
+        });
+    }
+
+    /// Benchmark: io_uring receive into a buffer picked from a buffer ring
+    /// io_uring接收到从缓冲区环中选取的缓冲区
+    pub(crate) fn bench_iouring_recv_buf_ring(c: &mut Criterion) {
+        let Ok(driver) = IoUringDriver::new() else {
+            return;
+        };
+        let Ok(buf_ring) = driver.register_buf_ring(0, 64, 512) else {
+            return;
+        };
+        let (mut tx, rx) = UnixStream::pair().unwrap();
+
+        c.bench_function("driver_recv_iouring_buf_ring", |b| {
+            b.iter(|| {
+                tx.write_all(MESSAGE).unwrap();
+                let entry =
+                    unsafe { SubmitEntry::recv(rx.as_raw_fd(), std::ptr::null_mut(), 0, 1) };
+                *driver.get_submission().unwrap() = entry.with_buffer_group(0);
+                driver.submit().unwrap();
+                let completion = next_completion(&driver);
+                black_box(buf_ring.take(&completion).map(|buf| buf.len()))
+            });
+        });
+    }
+}
+
+/// Benchmark: receive paths of the available drivers / 可用驱动的接收路径
+fn bench_driver_recv(c: &mut Criterion) {
+    #[cfg(target_os = "linux")]
+    {
+        linux::bench_epoll_recv(c);
+        linux::bench_iouring_recv(c);
+        linux::bench_iouring_recv_buf_ring(c);
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = c;
+}
+
+/// Configure the criterion / 配置criterion
+fn configure_criterion() -> Criterion {
+    Criterion::default()
+        .measurement_time(Duration::from_secs(5))
+        .sample_size(100)
+        .warm_up_time(Duration::from_secs(1))
+}
+
+criterion_group! {
+    name = io_driver;
+    config = configure_criterion();
+    targets = bench_driver_recv,
+}
+
+criterion_main!(io_driver);
@@ -0,0 +1,1186 @@
+//! HTTP client and request builder
+//! HTTP 客户端和请求构建器
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use nexus_http::{Body, Method, Request, Response, StatusCode};
+use serde::Serialize;
+use url::Url;
+
+use crate::conn::Connection;
+use crate::encoding;
+use crate::error::{ClientError, ClientResult};
+use crate::interceptor::{Interceptor, Next};
+use crate::pool::{Pool, PoolKey};
+use crate::tls::{TlsConnector, TlsOptions};
+
+/// Default `User-Agent` header
+/// 默认的 `User-Agent` 头
+const USER_AGENT: &str = concat!("nexus-client/", env!("CARGO_PKG_VERSION"));
+
+/// Per-request timeout stored in the request extensions
+/// 存储在请求扩展中的单请求超时
+#[derive(Debug, Clone, Copy)]
+struct RequestTimeout(Duration);
+
+/// Client settings
+/// 客户端设置
+#[derive(Debug, Clone)]
+struct ClientConfig {
+    base_url: Option<Url>,
+    default_headers: http::HeaderMap,
+    connect_timeout: Duration,
+    timeout: Option<Duration>,
+    max_redirects: usize,
+    max_response_size: usize,
+    decompress: bool,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        let mut default_headers = http::HeaderMap::new();
+        default_headers
+            .insert(http::header::USER_AGENT, http::HeaderValue::from_static(USER_AGENT));
+        Self {
+            base_url: None,
+            default_headers,
+            connect_timeout: Duration::from_secs(10),
+            timeout: Some(Duration::from_secs(30)),
+            max_redirects: 10,
+            max_response_size: 10 * 1024 * 1024,
+            decompress: true,
+        }
+    }
+}
+
+/// Shared client state
+/// 共享的客户端状态
+pub(crate) struct ClientInner {
+    config: ClientConfig,
+    pool: Pool,
+    tls: TlsConnector,
+    pub(crate) interceptors: Vec<Arc<dyn Interceptor>>,
+}
+
+/// Asynchronous HTTP/1.1 client
+/// 异步 HTTP/1.1 客户端
+///
+/// Cloning is cheap: clones share the connection pool, configuration and
+/// interceptors. Responses are fully buffered, up to `max_response_size`.
+///
+/// 克隆开销很小：克隆之间共享连接池、配置和拦截器。响应会被完整缓冲，上限为 `max_response_size`。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// use nexus_client::{HttpClient, ResponseExt};
+///
+/// let client = HttpClient::builder()
+///     .base_url("http://127.0.0.1:8080/api/")
+///     .timeout(Duration::from_secs(5))
+///     .build()?;
+///
+/// let user: User = client.get("users/1").send().await?.error_for_status()?.json()?;
+/// ```
+#[derive(Clone)]
+pub struct HttpClient {
+    inner: Arc<ClientInner>,
+}
+
+impl HttpClient {
+    /// Create a client with the default configuration
+    /// 使用默认配置创建客户端
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the TLS backend cannot be initialised; use
+    /// [`HttpClient::builder`] to handle that error instead.
+    /// 如果TLS后端无法初始化则恐慌；使用 [`HttpClient::builder`] 可以处理该错误。
+    pub fn new() -> Self {
+        Self::builder()
+            .build()
+            .expect("default client configuration is valid")
+    }
+
+    /// Create a client builder
+    /// 创建客户端构建器
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Start a `GET` request
+    /// 开始一个 `GET` 请求
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    /// Start a `POST` request
+    /// 开始一个 `POST` 请求
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Start a `PUT` request
+    /// 开始一个 `PUT` 请求
+    pub fn put(&self, url: &str) -> RequestBuilder {
+        self.request(Method::PUT, url)
+    }
+
+    /// Start a `PATCH` request
+    /// 开始一个 `PATCH` 请求
+    pub fn patch(&self, url: &str) -> RequestBuilder {
+        self.request(Method::PATCH, url)
+    }
+
+    /// Start a `DELETE` request
+    /// 开始一个 `DELETE` 请求
+    pub fn delete(&self, url: &str) -> RequestBuilder {
+        self.request(Method::DELETE, url)
+    }
+
+    /// Start a `HEAD` request
+    /// 开始一个 `HEAD` 请求
+    pub fn head(&self, url: &str) -> RequestBuilder {
+        self.request(Method::HEAD, url)
+    }
+
+    /// Start a request with any method
+    /// 使用任意方法开始请求
+    ///
+    /// Relative URLs are resolved against the configured base URL.
+    /// 相对URL会基于配置的基础URL解析。
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let url = match &self.inner.config.base_url {
+            Some(base) => base.join(url),
+            None => Url::parse(url),
+        };
+        RequestBuilder {
+            client: self.clone(),
+            method,
+            url: url.map_err(ClientError::from),
+            headers: http::HeaderMap::new(),
+            body: Body::empty(),
+            timeout: None,
+        }
+    }
+
+    /// Send a request through the interceptors
+    /// 通过拦截器发送请求
+    ///
+    /// The request URI must be absolute.
+    /// 请求URI必须是绝对URI。
+    pub async fn execute(&self, request: Request) -> ClientResult<Response> {
+        Next::new(self.inner.clone()).call(request).await
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for HttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpClient")
+            .field("config", &self.inner.config)
+            .field("interceptors", &self.inner.interceptors.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientInner {
+    /// Send a request once the interceptors are done, following redirects
+    /// 在拦截器处理后发送请求，并跟随重定向
+    pub(crate) async fn dispatch(&self, request: Request) -> ClientResult<Response> {
+        let timeout = request
+            .extensions()
+            .get::<RequestTimeout>()
+            .map(|t| t.0)
+            .or(self.config.timeout);
+        match timeout {
+            Some(limit) => nexus_runtime::time::timeout(limit, self.follow_redirects(request))
+                .await
+                .map_err(|_| ClientError::Timeout(limit))?,
+            None => self.follow_redirects(request).await,
+        }
+    }
+
+    async fn follow_redirects(&self, mut request: Request) -> ClientResult<Response> {
+        self.apply_defaults(&mut request);
+        let mut redirects = 0;
+        loop {
+            let url = Url::parse(&request.uri())?;
+            let response = self.send(&url, &request).await?;
+
+            let Some(location) = redirect_location(&response, self.config.max_redirects) else {
+                return self.decode(&request, response);
+            };
+            let next = match url.join(location) {
+                Ok(next) if matches!(next.scheme(), "http" | "https") => next,
+                _ => return self.decode(&request, response),
+            };
+            let Some(next_request) =
+                redirect_request(request.clone(), response.status(), &url, &next)?
+            else {
+                return self.decode(&request, response);
+            };
+            if redirects == self.config.max_redirects {
+                return Err(ClientError::TooManyRedirects(self.config.max_redirects));
+            }
+            redirects += 1;
+            tracing::debug!(from = %url, to = %next, "following redirect");
+            request = next_request;
+        }
+    }
+
+    /// Add the default headers the request does not set itself
+    /// 添加请求自身未设置的默认头部
+    fn apply_defaults(&self, request: &mut Request) {
+        let headers = request.inner_mut().headers_mut();
+        for (name, value) in &self.config.default_headers {
+            if !headers.contains_key(name) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+        if self.config.decompress && !headers.contains_key(http::header::ACCEPT_ENCODING) {
+            headers.insert(
+                http::header::ACCEPT_ENCODING,
+                http::HeaderValue::from_static(encoding::ACCEPT_ENCODING),
+            );
+        }
+    }
+
+    fn decode(&self, request: &Request, response: Response) -> ClientResult<Response> {
+        if !self.config.decompress || request.inner().method() == http::Method::HEAD {
+            return Ok(response);
+        }
+        encoding::decode(response, self.config.max_response_size)
+    }
+
+    /// Send one request, reusing a pooled connection when possible
+    /// 发送一个请求，尽可能复用池化连接
+    ///
+    /// A pooled connection the server closed while it sat idle is detected by the
+    /// request failing before any response byte arrived; the request is then sent
+    /// once more on a new connection.
+    ///
+    /// 服务器在空闲期间关闭的池化连接，可通过请求在收到任何响应字节之前失败来检测；
+    /// 此时请求会在新连接上再发送一次。
+    async fn send(&self, url: &Url, request: &Request) -> ClientResult<Response> {
+        let key = PoolKey::from_url(url)?;
+        let keep_alive = self.pool.enabled();
+
+        if let Some(mut conn) = self.pool.checkout(&key) {
+            conn.mark_reused();
+            match conn.send(request, keep_alive).await {
+                Ok(response) => {
+                    self.release(key, conn);
+                    return Ok(response);
+                },
+                Err(e) if conn.is_stale() && !request.body().is_stream() => {
+                    tracing::debug!(error = %e, "pooled connection was closed, reconnecting");
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut conn = Connection::open(
+            &key,
+            self.config.connect_timeout,
+            &self.tls,
+            self.config.max_response_size,
+        )
+        .await?;
+        let response = conn.send(request, keep_alive).await?;
+        self.release(key, conn);
+        Ok(response)
+    }
+
+    fn release(&self, key: PoolKey, conn: Connection) {
+        if conn.is_reusable() {
+            self.pool.checkin(key, conn);
+        }
+    }
+}
+
+/// Target of a redirect response the client should follow
+/// 客户端应跟随的重定向响应的目标
+fn redirect_location(response: &Response, max_redirects: usize) -> Option<&str> {
+    if max_redirects == 0 {
+        return None;
+    }
+    match response.status() {
+        StatusCode::MOVED_PERMANENTLY
+        | StatusCode::FOUND
+        | StatusCode::SEE_OTHER
+        | StatusCode::TEMPORARY_REDIRECT
+        | StatusCode::PERMANENT_REDIRECT => response.header("location"),
+        _ => None,
+    }
+}
+
+/// Build the request sent to a redirect target
+/// 构建发送到重定向目标的请求
+///
+/// `303`, and `301`/`302` answering a `POST`, switch to a bodiless `GET`; `307`
+/// and `308` repeat the method and body, which is impossible for a streaming
+/// body (`Ok(None)`). Credentials are dropped when the origin changes.
+///
+/// `303`，以及回应 `POST` 的 `301`/`302`，会改为不带body的 `GET`；`307` 和 `308`
+/// 重复原方法和body，流式body无法重复（返回 `Ok(None)`）。源改变时会丢弃凭据。
+fn redirect_request(
+    request: Request,
+    status: StatusCode,
+    from: &Url,
+    to: &Url,
+) -> ClientResult<Option<Request>> {
+    let (mut parts, body) = request.into_inner().into_parts();
+
+    let to_get = status == StatusCode::SEE_OTHER && parts.method != http::Method::HEAD
+        || matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND)
+            && parts.method == http::Method::POST;
+    let body = if to_get {
+        parts.method = http::Method::GET;
+        for name in [
+            http::header::CONTENT_TYPE,
+            http::header::CONTENT_LENGTH,
+            http::header::TRANSFER_ENCODING,
+        ] {
+            parts.headers.remove(name);
+        }
+        Body::empty()
+    } else if body.is_stream() {
+        return Ok(None);
+    } else {
+        body
+    };
+
+    if from.origin() != to.origin() {
+        for name in [
+            http::header::AUTHORIZATION,
+            http::header::COOKIE,
+            http::header::PROXY_AUTHORIZATION,
+            http::header::HOST,
+        ] {
+            parts.headers.remove(name);
+        }
+    }
+
+    parts.uri = to
+        .as_str()
+        .parse()
+        .map_err(|e| ClientError::InvalidUrl(format!("{}: {}", to, e)))?;
+    Ok(Some(Request::new(http::Request::from_parts(parts, body))))
+}
+
+/// Builder for [`HttpClient`]
+/// [`HttpClient`] 的构建器
+///
+/// Errors (an invalid base URL or certificate) are reported by
+/// [`ClientBuilder::build`].
+/// 错误（无效的基础URL或证书）由 [`ClientBuilder::build`] 报告。
+pub struct ClientBuilder {
+    config: ClientConfig,
+    pool_max_idle_per_host: usize,
+    pool_idle_timeout: Option<Duration>,
+    tls: TlsOptions,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    error: Option<ClientError>,
+}
+
+impl ClientBuilder {
+    /// Create a builder with the default configuration
+    /// 使用默认配置创建构建器
+    pub fn new() -> Self {
+        Self {
+            config: ClientConfig::default(),
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            tls: TlsOptions::default(),
+            interceptors: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Resolve relative request URLs against `url`
+    /// 基于 `url` 解析相对请求URL
+    ///
+    /// As with any URL join, a base without a trailing `/` loses its last segment.
+    /// 与所有URL拼接一样，不以 `/` 结尾的基础URL会丢失其最后一段。
+    pub fn base_url(mut self, url: &str) -> Self {
+        match Url::parse(url) {
+            Ok(url) => self.config.base_url = Some(url),
+            Err(e) => self.fail(ClientError::InvalidUrl(format!("{}: {}", url, e))),
+        }
+        self
+    }
+
+    /// Add a header sent with every request that does not set it
+    /// 添加一个随每个未设置该头的请求发送的头部
+    pub fn default_header(mut self, name: &str, value: &str) -> Self {
+        match header_pair(name, value) {
+            Ok((name, value)) => {
+                self.config.default_headers.insert(name, value);
+            },
+            Err(e) => self.fail(e),
+        }
+        self
+    }
+
+    /// Set the `User-Agent` header
+    /// 设置 `User-Agent` 头
+    pub fn user_agent(self, user_agent: &str) -> Self {
+        self.default_header("user-agent", user_agent)
+    }
+
+    /// Set the time allowed to connect, TLS handshake included (default 10s)
+    /// 设置允许的连接时间，包括TLS握手（默认10秒）
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = timeout;
+        self
+    }
+
+    /// Set the time allowed for a whole call, redirects included (default 30s)
+    /// 设置整个调用允许的时间，包括重定向（默认30秒）
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Disable the overall request timeout
+    /// 禁用整体请求超时
+    pub fn no_timeout(mut self) -> Self {
+        self.config.timeout = None;
+        self
+    }
+
+    /// Set how many idle connections are kept per origin (default 32, 0 disables keep-alive)
+    /// 设置每个源保留的空闲连接数（默认32，0表示禁用keep-alive）
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = max;
+        self
+    }
+
+    /// Set how long an idle connection is kept (default 90s)
+    /// 设置空闲连接的保留时间（默认90秒）
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set how many redirects are followed (default 10, 0 returns redirects as-is)
+    /// 设置跟随的重定向次数（默认10，0表示原样返回重定向）
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        self.config.max_redirects = max;
+        self
+    }
+
+    /// Set the largest response accepted, after decompression (default 10 MiB)
+    /// 设置可接受的最大响应（解压后，默认10 MiB）
+    pub fn max_response_size(mut self, max: usize) -> Self {
+        self.config.max_response_size = max;
+        self
+    }
+
+    /// Ask for and decode `gzip`/`deflate` responses (default on)
+    /// 请求并解码 `gzip`/`deflate` 响应（默认开启）
+    pub fn decompress(mut self, enabled: bool) -> Self {
+        self.config.decompress = enabled;
+        self
+    }
+
+    /// Trust the CA certificates in `pem` in addition to the bundled roots
+    /// 除内置根证书外，信任 `pem` 中的CA证书
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> Self {
+        if let Err(e) = self.tls.add_roots(pem) {
+            self.fail(e);
+        }
+        self
+    }
+
+    /// Trust only the certificates added with [`ClientBuilder::add_root_certificate`]
+    /// 只信任通过 [`ClientBuilder::add_root_certificate`] 添加的证书
+    pub fn disable_builtin_roots(mut self) -> Self {
+        self.tls.no_builtin_roots = true;
+        self
+    }
+
+    /// Present a client certificate to servers that ask for one (mTLS)
+    /// 向请求证书的服务器出示客户端证书（mTLS）
+    pub fn identity(mut self, cert_pem: &[u8], key_pem: &[u8]) -> Self {
+        if let Err(e) = self.tls.set_identity(cert_pem, key_pem) {
+            self.fail(e);
+        }
+        self
+    }
+
+    /// Add an interceptor; the first one added is the outermost
+    /// 添加拦截器；最先添加的位于最外层
+    pub fn interceptor(mut self, interceptor: impl Interceptor) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Build the client
+    /// 构建客户端
+    pub fn build(self) -> ClientResult<HttpClient> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        Ok(HttpClient {
+            inner: Arc::new(ClientInner {
+                config: self.config,
+                pool: Pool::new(self.pool_max_idle_per_host, self.pool_idle_timeout),
+                tls: self.tls.build()?,
+                interceptors: self.interceptors,
+            }),
+        })
+    }
+
+    /// Remember the first configuration error
+    /// 记录第一个配置错误
+    fn fail(&mut self, e: ClientError) {
+        self.error.get_or_insert(e);
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("config", &self.config)
+            .field("pool_max_idle_per_host", &self.pool_max_idle_per_host)
+            .field("pool_idle_timeout", &self.pool_idle_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Fluent builder for a single request
+/// 单个请求的流式构建器
+///
+/// Equivalent to Spring's `WebClient.RequestHeadersSpec`.
+/// 等价于Spring的 `WebClient.RequestHeadersSpec`。
+#[derive(Debug)]
+pub struct RequestBuilder {
+    client: HttpClient,
+    method: Method,
+    url: ClientResult<Url>,
+    headers: http::HeaderMap,
+    body: Body,
+    timeout: Option<Duration>,
+}
+
+impl RequestBuilder {
+    /// Add a header, replacing any previous value
+    /// 添加头部，替换之前的值
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        match header_pair(name, value) {
+            Ok((name, value)) => {
+                self.headers.insert(name, value);
+            },
+            Err(e) => self.fail(e),
+        }
+        self
+    }
+
+    /// Append serialized query parameters to the URL
+    /// 将序列化后的查询参数追加到URL
+    pub fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        match serde_urlencoded::to_string(query) {
+            Ok(encoded) => {
+                if let Ok(url) = &mut self.url
+                    && !encoded.is_empty()
+                {
+                    let query = match url.query() {
+                        Some(existing) if !existing.is_empty() => {
+                            format!("{}&{}", existing, encoded)
+                        },
+                        _ => encoded,
+                    };
+                    url.set_query(Some(&query));
+                }
+            },
+            Err(e) => self.fail(ClientError::InvalidRequest(format!("Invalid query: {}", e))),
+        }
+        self
+    }
+
+    /// Send `Authorization: Bearer <token>`
+    /// 发送 `Authorization: Bearer <token>`
+    pub fn bearer_auth(self, token: &str) -> Self {
+        self.header("authorization", &format!("Bearer {}", token))
+    }
+
+    /// Send HTTP basic credentials
+    /// 发送HTTP基本认证凭据
+    pub fn basic_auth(self, username: &str, password: Option<&str>) -> Self {
+        let credentials = format!("{}:{}", username, password.unwrap_or_default());
+        let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+        self.header("authorization", &format!("Basic {}", encoded))
+    }
+
+    /// Set the request body
+    /// 设置请求body
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Send `value` as JSON
+    /// 以JSON发送 `value`
+    pub fn json<T: Serialize + ?Sized>(mut self, value: &T) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(json) => {
+                self.body = Body::from(json);
+                self.content_type("application/json")
+            },
+            Err(e) => {
+                self.fail(ClientError::InvalidRequest(format!("Invalid JSON body: {}", e)));
+                self
+            },
+        }
+    }
+
+    /// Send `value` as `application/x-www-form-urlencoded`
+    /// 以 `application/x-www-form-urlencoded` 发送 `value`
+    pub fn form<T: Serialize + ?Sized>(mut self, value: &T) -> Self {
+        match serde_urlencoded::to_string(value) {
+            Ok(form) => {
+                self.body = Body::from(form);
+                self.content_type("application/x-www-form-urlencoded")
+            },
+            Err(e) => {
+                self.fail(ClientError::InvalidRequest(format!("Invalid form body: {}", e)));
+                self
+            },
+        }
+    }
+
+    /// Override the client timeout for this request
+    /// 为此请求覆盖客户端超时
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Build the request without sending it
+    /// 构建请求但不发送
+    pub fn build(self) -> ClientResult<Request> {
+        let url = self.url?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ClientError::InvalidUrl(format!("Unsupported scheme: {}", url.scheme())));
+        }
+        let mut builder = http::Request::builder()
+            .method(http::Method::from(&self.method))
+            .uri(url.as_str());
+        if let Some(headers) = builder.headers_mut() {
+            *headers = self.headers;
+        }
+        let inner = builder
+            .body(self.body)
+            .map_err(|e| ClientError::InvalidRequest(e.to_string()))?;
+
+        let mut request = Request::new(inner);
+        if let Some(timeout) = self.timeout {
+            request.extensions_mut().insert(RequestTimeout(timeout));
+        }
+        Ok(request)
+    }
+
+    /// Build and send the request
+    /// 构建并发送请求
+    pub async fn send(self) -> ClientResult<Response> {
+        let client = self.client.clone();
+        let request = self.build()?;
+        client.execute(request).await
+    }
+
+    fn content_type(mut self, value: &'static str) -> Self {
+        if !self.headers.contains_key(http::header::CONTENT_TYPE) {
+            self.headers
+                .insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static(value));
+        }
+        self
+    }
+
+    /// Keep the first error, reported by [`RequestBuilder::build`]
+    /// 保留第一个错误，由 [`RequestBuilder::build`] 报告
+    fn fail(&mut self, e: ClientError) {
+        if self.url.is_ok() {
+            self.url = Err(e);
+        }
+    }
+}
+
+/// Validate a header name and value
+/// 校验头部名称和值
+fn header_pair(name: &str, value: &str) -> ClientResult<(http::HeaderName, http::HeaderValue)> {
+    let name = http::HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| ClientError::InvalidRequest(format!("Invalid header name: {}", name)))?;
+    let value = http::HeaderValue::from_str(value)
+        .map_err(|_| ClientError::InvalidRequest(format!("Invalid value for header {}", name)))?;
+    Ok((name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ext::ResponseExt;
+    use std::io::{Read, Write};
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::task::Poll;
+
+    use nexus_runtime::task::block_on;
+
+    /// A raw request received by [`TestServer`]
+    struct Received {
+        head: String,
+        body: Vec<u8>,
+    }
+
+    impl Received {
+        fn request_line(&self) -> &str {
+            self.head.lines().next().unwrap_or_default()
+        }
+
+        fn header(&self, name: &str) -> Option<&str> {
+            self.head.lines().skip(1).find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.eq_ignore_ascii_case(name).then(|| value.trim())
+            })
+        }
+    }
+
+    /// Scripted HTTP/1.1 server on a std thread; the handler returns the raw
+    /// response and whether to close the connection afterwards
+    struct TestServer {
+        addr: String,
+        requests: Arc<Mutex<Vec<Received>>>,
+        connections: Arc<AtomicUsize>,
+    }
+
+    impl TestServer {
+        fn start<F>(handler: F) -> Self
+        where
+            F: Fn(&Received) -> (Vec<u8>, bool) + Send + Sync + 'static,
+        {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let connections = Arc::new(AtomicUsize::new(0));
+            let handler = Arc::new(handler);
+
+            let (log, count) = (requests.clone(), connections.clone());
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else { return };
+                    count.fetch_add(1, Ordering::SeqCst);
+                    let (handler, log) = (handler.clone(), log.clone());
+                    std::thread::spawn(move || {
+                        while let Some(received) = read_request(&mut stream) {
+                            let (response, close) = handler(&received);
+                            log.lock().unwrap().push(received);
+                            if stream.write_all(&response).is_err() || close {
+                                return;
+                            }
+                        }
+                    });
+                }
+            });
+            Self {
+                addr,
+                requests,
+                connections,
+            }
+        }
+
+        fn url(&self, path: &str) -> String {
+            format!("http://{}{}", self.addr, path)
+        }
+
+        fn requests(&self) -> std::sync::MutexGuard<'_, Vec<Received>> {
+            self.requests.lock().unwrap()
+        }
+    }
+
+    fn read_request(stream: &mut std::net::TcpStream) -> Option<Received> {
+        let mut data = Vec::new();
+        let mut byte = [0u8; 1];
+        while !data.ends_with(b"\r\n\r\n") {
+            if stream.read(&mut byte).ok()? == 0 {
+                return None;
+            }
+            data.push(byte[0]);
+        }
+        let mut received = Received {
+            head: String::from_utf8(data).ok()?,
+            body: Vec::new(),
+        };
+        if let Some(len) = received.header("content-length") {
+            let mut body = vec![0u8; len.parse().ok()?];
+            stream.read_exact(&mut body).ok()?;
+            received.body = body;
+        } else if received.header("transfer-encoding").is_some() {
+            while !received.body.ends_with(b"0\r\n\r\n") {
+                stream.read_exact(&mut byte).ok()?;
+                received.body.push(byte[0]);
+            }
+        }
+        Some(received)
+    }
+
+    fn ok(body: &str) -> (Vec<u8>, bool) {
+        let response = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}", body.len(), body);
+        (response.into_bytes(), false)
+    }
+
+    fn redirect(status: u16, location: &str) -> (Vec<u8>, bool) {
+        let response = format!(
+            "HTTP/1.1 {} Redirect\r\nlocation: {}\r\ncontent-length: 0\r\n\r\n",
+            status, location
+        );
+        (response.into_bytes(), false)
+    }
+
+    #[test]
+    fn test_get_reuses_pooled_connection() {
+        let server = TestServer::start(|req| ok(req.request_line()));
+        let client = HttpClient::builder()
+            .base_url(&server.url("/api/"))
+            .build()
+            .unwrap();
+
+        let api = client.clone();
+        block_on(async move {
+            for page in 1..=3 {
+                let response = api
+                    .get("users")
+                    .query(&[("page", page)])
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let expected = format!("GET /api/users?page={} HTTP/1.1", page);
+                assert_eq!(response.text().unwrap(), expected);
+            }
+        });
+
+        assert_eq!(server.connections.load(Ordering::SeqCst), 1);
+        let key = PoolKey::from_url(&Url::parse(&server.url("/")).unwrap()).unwrap();
+        assert_eq!(client.inner.pool.idle_count(&key), 1);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("host"), Some(server.addr.as_str()));
+        assert_eq!(requests[0].header("user-agent"), Some(USER_AGENT));
+        assert_eq!(requests[0].header("accept-encoding"), Some("gzip, deflate"));
+    }
+
+    #[test]
+    fn test_post_json_and_gzip_response() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+
+        let server = TestServer::start(|req| {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&req.body).unwrap();
+            let body = encoder.finish().unwrap();
+            let mut response = format!(
+                "HTTP/1.1 201 Created\r\ncontent-type: application/json\r\n\
+                 content-encoding: gzip\r\ncontent-length: {}\r\n\r\n",
+                body.len()
+            )
+            .into_bytes();
+            response.extend_from_slice(&body);
+            (response, false)
+        });
+        let client = HttpClient::new();
+
+        let response = block_on(
+            client
+                .post(&server.url("/echo"))
+                .json(&serde_json::json!({"name": "nexus"}))
+                .send(),
+        )
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(response.header("content-encoding").is_none());
+        let echoed: serde_json::Value = response.json().unwrap();
+        assert_eq!(echoed["name"], "nexus");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("content-type"), Some("application/json"));
+        assert_eq!(requests[0].body, br#"{"name":"nexus"}"#);
+    }
+
+    #[test]
+    fn test_redirects() {
+        let other = TestServer::start(|req| ok(req.header("authorization").unwrap_or("none")));
+        let target = other.url("/elsewhere");
+        let server = TestServer::start(move |req| match req.request_line() {
+            "POST /form HTTP/1.1" => redirect(302, "/done"),
+            "PUT /moved HTTP/1.1" => redirect(307, "/done"),
+            "GET /away HTTP/1.1" => redirect(301, &target),
+            "GET /loop HTTP/1.1" => redirect(302, "/loop"),
+            line => ok(&format!("{} {}", line, String::from_utf8_lossy(&req.body))),
+        });
+        let client = HttpClient::new();
+        let base = server.url("");
+
+        block_on(async move {
+            let url = |path: &str| format!("{}{}", base, path);
+
+            // 302 after POST turns into a GET without a body
+            let response = client.post(&url("/form")).body("a=1").send().await.unwrap();
+            assert_eq!(response.text().unwrap(), "GET /done HTTP/1.1 ");
+
+            // 307 repeats the method and body
+            let response = client
+                .put(&url("/moved"))
+                .body("data")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.text().unwrap(), "PUT /done HTTP/1.1 data");
+
+            // Credentials are not sent to another origin
+            let response = client
+                .get(&url("/away"))
+                .bearer_auth("secret")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.text().unwrap(), "none");
+
+            let err = client.get(&url("/loop")).send().await.unwrap_err();
+            assert!(matches!(err, ClientError::TooManyRedirects(10)));
+        });
+
+        let client = HttpClient::builder().max_redirects(0).build().unwrap();
+        let response = block_on(client.get(&server.url("/loop")).send()).unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(response.header("location"), Some("/loop"));
+    }
+
+    #[test]
+    fn test_stale_pooled_connection_is_replaced() {
+        // Closes every connection after answering, without saying so
+        let server = TestServer::start(|_| {
+            let (response, _) = ok("fresh");
+            (response, true)
+        });
+        let client = HttpClient::new();
+        let url = server.url("/");
+
+        block_on(async move {
+            for _ in 0..2 {
+                let response = client.get(&url).send().await.unwrap();
+                assert_eq!(response.text().unwrap(), "fresh");
+                nexus_runtime::time::sleep(Duration::from_millis(50)).await;
+            }
+        });
+        assert_eq!(server.connections.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_timeouts_and_connect_errors() {
+        let server = TestServer::start(|_| {
+            std::thread::sleep(Duration::from_secs(2));
+            ok("late")
+        });
+        let client = HttpClient::builder()
+            .timeout(Duration::from_millis(200))
+            .build()
+            .unwrap();
+
+        let err = block_on(client.get(&server.url("/")).send()).unwrap_err();
+        assert!(err.is_timeout(), "got: {}", err);
+
+        let err = block_on(
+            client
+                .get(&server.url("/"))
+                .timeout(Duration::from_millis(50))
+                .send(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ClientError::Timeout(t) if t == Duration::from_millis(50)));
+
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", closed.local_addr().unwrap());
+        drop(closed);
+        let err = block_on(client.get(&url).send()).unwrap_err();
+        assert!(matches!(err, ClientError::Connect(_)), "got: {}", err);
+
+        assert!(matches!(
+            client.get("ftp://example.com/").build(),
+            Err(ClientError::InvalidUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_response_size_limit() {
+        let server = TestServer::start(|_| ok(&"x".repeat(4096)));
+        let client = HttpClient::builder()
+            .max_response_size(1024)
+            .build()
+            .unwrap();
+        let err = block_on(client.get(&server.url("/")).send()).unwrap_err();
+        assert!(matches!(err, ClientError::ResponseTooLarge(1024)), "got: {}", err);
+    }
+
+    #[test]
+    #[cfg(all(feature = "observability", feature = "resilience"))]
+    fn test_interceptors() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let server = TestServer::start(move |req| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                let response = b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\r\n";
+                return (response.to_vec(), false);
+            }
+            ok(&format!(
+                "{}|{}",
+                req.header("x-tenant").unwrap_or_default(),
+                req.header("traceparent").unwrap_or_default()
+            ))
+        });
+
+        let seen = Arc::new(AtomicBool::new(false));
+        let flag = seen.clone();
+        let client = HttpClient::builder()
+            .interceptor(move |mut request: Request, next: Next| -> crate::InterceptorFuture {
+                flag.store(true, Ordering::SeqCst);
+                request
+                    .inner_mut()
+                    .headers_mut()
+                    .insert("x-tenant", http::HeaderValue::from_static("acme"));
+                next.call(request)
+            })
+            .interceptor(crate::TracingInterceptor::new(nexus_observability::Tracer::new("test")))
+            .interceptor(crate::RetryInterceptor::new(
+                nexus_resilience::retry::RetryPolicy::new()
+                    .with_max_attempts(3)
+                    .with_initial_delay(Duration::from_millis(10)),
+            ))
+            .build()
+            .unwrap();
+
+        let response = block_on(client.get(&server.url("/")).send()).unwrap();
+        assert!(seen.load(Ordering::SeqCst));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        let text = response.text().unwrap();
+        let (tenant, traceparent) = text.split_once('|').unwrap();
+        assert_eq!(tenant, "acme");
+        assert!(traceparent.starts_with("00-"), "got: {}", traceparent);
+
+        // Both attempts carried the same trace context
+        let requests = server.requests();
+        assert_eq!(requests[0].header("traceparent"), requests[1].header("traceparent"));
+    }
+
+    #[test]
+    fn test_error_for_status() {
+        let server = TestServer::start(|_| {
+            let response = b"HTTP/1.1 404 Not Found\r\ncontent-length: 7\r\n\r\nmissing";
+            (response.to_vec(), false)
+        });
+        let response = block_on(HttpClient::new().get(&server.url("/")).send()).unwrap();
+        let err = response.error_for_status().unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+        assert!(matches!(err, ClientError::Status { body, .. } if body == "missing"));
+    }
+
+    #[test]
+    fn test_tls_with_client_certificate() {
+        const CA_PEM: &[u8] = include_bytes!("../../nexus-http/testdata/tls/ca.pem");
+        const SERVER_PEM: &[u8] = include_bytes!("../../nexus-http/testdata/tls/server.pem");
+        const SERVER_KEY: &[u8] = include_bytes!("../../nexus-http/testdata/tls/server.key");
+        const CLIENT_PEM: &[u8] = include_bytes!("../../nexus-http/testdata/tls/client.pem");
+        const CLIENT_KEY: &[u8] = include_bytes!("../../nexus-http/testdata/tls/client.key");
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let config = nexus_http::TlsConfig::from_pem(SERVER_PEM, SERVER_KEY)
+            .unwrap()
+            .require_client_auth(CA_PEM)
+            .unwrap();
+        let server = nexus_http::Server::bind(addr.to_string())
+            .tls(nexus_http::TlsAcceptor::new(config).unwrap());
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        std::thread::spawn(move || {
+            let signal = std::future::poll_fn(move |_| {
+                if flag.load(Ordering::Acquire) {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            });
+            let handler = |req: Request| async move {
+                let presented = req.peer_certificate().is_some();
+                Ok(Response::builder()
+                    .body(Body::from(format!("client certificate: {}", presented)))
+                    .unwrap())
+            };
+            let _ = block_on(server.run_with_shutdown(handler, signal));
+        });
+
+        let client = HttpClient::builder()
+            .add_root_certificate(CA_PEM)
+            .disable_builtin_roots()
+            .identity(CLIENT_PEM, CLIENT_KEY)
+            .build()
+            .unwrap();
+        let url = format!("https://localhost:{}/", addr.port());
+        let mut response = None;
+        for _ in 0..50 {
+            match block_on(client.get(&url).send()) {
+                Ok(r) => {
+                    response = Some(r);
+                    break;
+                },
+                Err(_) => std::thread::sleep(Duration::from_millis(20)),
+            }
+        }
+        assert_eq!(response.unwrap().text().unwrap(), "client certificate: true");
+
+        // A client that does not trust the test CA refuses the server
+        let untrusting = HttpClient::new();
+        let err = block_on(untrusting.get(&url).send()).unwrap_err();
+        assert!(matches!(err, ClientError::Tls(_)), "got: {}", err);
+
+        stop.store(true, Ordering::Release);
+    }
+
+    #[test]
+    fn test_builder_reports_invalid_configuration() {
+        assert!(matches!(
+            HttpClient::builder().base_url("not a url").build(),
+            Err(ClientError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            HttpClient::builder()
+                .add_root_certificate(b"garbage")
+                .build(),
+            Err(ClientError::Tls(_))
+        ));
+        assert!(matches!(
+            HttpClient::new()
+                .get("http://localhost/")
+                .header("bad header", "x")
+                .build(),
+            Err(ClientError::InvalidRequest(_))
+        ));
+    }
+}
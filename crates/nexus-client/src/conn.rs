@@ -0,0 +1,224 @@
+//! A single HTTP/1.1 connection
+//! 单个 HTTP/1.1 连接
+
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+use nexus_http::proto::{self, ConnectionContext, ResponseParser};
+use nexus_http::{Body, Request, Response};
+use nexus_runtime::io::TcpStream;
+
+use crate::error::{ClientError, ClientResult};
+use crate::pool::PoolKey;
+use crate::tls::{TlsConnector, TlsStream};
+
+/// Size of a single socket read
+/// 单次套接字读取的大小
+const READ_BUF_SIZE: usize = 16 * 1024;
+
+/// Plain or TLS transport
+/// 明文或TLS传输
+enum Transport {
+    Plain(TcpStream),
+    Tls(Box<TlsStream>),
+}
+
+/// An open connection to one origin
+/// 到某个源的已打开连接
+pub(crate) struct Connection {
+    transport: Transport,
+    parser: ResponseParser,
+    scratch: Vec<u8>,
+    /// Taken from the pool rather than freshly opened / 从连接池取出而非新打开
+    reused: bool,
+    /// Response bytes arrived for the current request / 当前请求已收到响应字节
+    received: bool,
+    /// The current request asked the server to close / 当前请求要求服务器关闭连接
+    close_requested: bool,
+}
+
+impl Connection {
+    /// Resolve the origin and connect, running the TLS handshake for `https`
+    /// 解析源并连接，对 `https` 执行TLS握手
+    ///
+    /// Host names are resolved with the system resolver, which blocks the calling
+    /// thread; IP literals are used directly.
+    ///
+    /// 主机名通过系统解析器解析，这会阻塞调用线程；IP字面量直接使用。
+    pub(crate) async fn open(
+        key: &PoolKey,
+        connect_timeout: Duration,
+        tls: &TlsConnector,
+        max_response_size: usize,
+    ) -> ClientResult<Self> {
+        let connect = async {
+            let tcp = connect_any(key).await?;
+            let transport = if key.https {
+                Transport::Tls(Box::new(tls.connect(&key.host, tcp).await?))
+            } else {
+                Transport::Plain(tcp)
+            };
+            Ok::<_, ClientError>(transport)
+        };
+        let transport = nexus_runtime::time::timeout(connect_timeout, connect)
+            .await
+            .map_err(|_| ClientError::Timeout(connect_timeout))??;
+
+        let mut ctx = ConnectionContext::new();
+        ctx.set_max_buffer_size(max_response_size);
+        Ok(Self {
+            transport,
+            parser: ResponseParser::with_context(ctx),
+            scratch: vec![0u8; READ_BUF_SIZE],
+            reused: false,
+            received: false,
+            close_requested: false,
+        })
+    }
+
+    /// Mark the connection as taken from the pool
+    /// 将连接标记为从连接池取出
+    pub(crate) fn mark_reused(&mut self) {
+        self.reused = true;
+    }
+
+    /// Whether the last exchange failed because the server had already closed this
+    /// pooled connection, so the request can safely go out on a new one
+    /// 上一次交换是否因服务器已关闭此池化连接而失败，从而可以安全地在新连接上发送请求
+    pub(crate) fn is_stale(&self) -> bool {
+        self.reused && !self.received
+    }
+
+    /// Whether the connection can carry another request
+    /// 连接是否可以承载下一个请求
+    pub(crate) fn is_reusable(&self) -> bool {
+        !self.close_requested
+            && self.parser.context().keep_alive()
+            && self.parser.buffered().is_empty()
+    }
+
+    /// Write `request` and read its response
+    /// 写出 `request` 并读取其响应
+    pub(crate) async fn send(
+        &mut self,
+        request: &Request,
+        keep_alive: bool,
+    ) -> ClientResult<Response> {
+        self.received = false;
+        self.close_requested = !keep_alive
+            || request
+                .header("connection")
+                .is_some_and(|v| v.to_ascii_lowercase().contains("close"));
+
+        let mut ctx = ConnectionContext::new();
+        ctx.set_keep_alive(keep_alive);
+        let head = proto::encode_request(request, &ctx)
+            .map_err(|e| ClientError::InvalidRequest(e.to_string()))?;
+        self.write_all(&head).await?;
+
+        if let Body::Stream(body) = request.body() {
+            let chunked = proto::is_request_chunked(request, &ctx);
+            while let Some(chunk) = std::future::poll_fn(|cx| body.poll_chunk(cx)).await {
+                let chunk = chunk.map_err(|e| {
+                    ClientError::InvalidRequest(format!("Request body failed: {}", e))
+                })?;
+                if chunk.is_empty() {
+                    continue;
+                }
+                if chunked {
+                    self.write_all(&proto::encode_chunk(&chunk)).await?;
+                } else {
+                    self.write_all(&chunk).await?;
+                }
+            }
+            if chunked {
+                self.write_all(proto::LAST_CHUNK).await?;
+            }
+        }
+
+        let head_request = request.inner().method() == http::Method::HEAD;
+        let max = self.parser.context().max_buffer_size();
+        loop {
+            if let Some(response) = self
+                .parser
+                .parse(head_request)
+                .map_err(|e| parse_error(e, max))?
+            {
+                return Ok(response);
+            }
+            let n = self.read().await?;
+            if n == 0 {
+                if let Some(response) = self
+                    .parser
+                    .finish(head_request)
+                    .map_err(|e| parse_error(e, max))?
+                {
+                    return Ok(response);
+                }
+                return Err(ClientError::Io(
+                    "Connection closed before the response was complete".to_string(),
+                ));
+            }
+            self.received = true;
+            let Self {
+                parser, scratch, ..
+            } = self;
+            parser
+                .feed(&scratch[..n])
+                .map_err(|e| parse_error(e, max))?;
+        }
+    }
+
+    async fn read(&mut self) -> ClientResult<usize> {
+        let result = match &mut self.transport {
+            Transport::Plain(tcp) => tcp.read(&mut self.scratch).await,
+            Transport::Tls(tls) => tls.read(&mut self.scratch).await,
+        };
+        result.map_err(io_error)
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> ClientResult<()> {
+        let result = match &mut self.transport {
+            Transport::Plain(tcp) => tcp.write_all(buf).await,
+            Transport::Tls(tls) => tls.write_all(buf).await,
+        };
+        result.map_err(io_error)
+    }
+}
+
+/// Connect to the first reachable address of the origin
+/// 连接到源的第一个可达地址
+async fn connect_any(key: &PoolKey) -> ClientResult<TcpStream> {
+    let addrs: Vec<SocketAddr> = match key.host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, key.port)],
+        Err(_) => (key.host.as_str(), key.port)
+            .to_socket_addrs()
+            .map_err(|e| ClientError::Connect(format!("Failed to resolve {}: {}", key.host, e)))?
+            .collect(),
+    };
+
+    let mut last_error = None;
+    for addr in addrs {
+        match TcpStream::connect(&addr.to_string()).await {
+            Ok(tcp) => return Ok(tcp),
+            Err(e) => last_error = Some(format!("{}: {}", addr, e)),
+        }
+    }
+    Err(ClientError::Connect(
+        last_error.unwrap_or_else(|| format!("No addresses found for {}", key.host)),
+    ))
+}
+
+fn io_error(e: io::Error) -> ClientError {
+    ClientError::Io(e.to_string())
+}
+
+/// Map a response parsing error
+/// 映射响应解析错误
+fn parse_error(e: nexus_http::Error, max: usize) -> ClientError {
+    match e {
+        nexus_http::Error::Custom(413, _) => ClientError::ResponseTooLarge(max),
+        e => ClientError::InvalidResponse(e.to_string()),
+    }
+}
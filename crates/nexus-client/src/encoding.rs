@@ -0,0 +1,131 @@
+//! Response content decoding
+//! 响应内容解码
+
+use std::io::Read;
+
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use nexus_http::{Body, HttpBody, Response};
+
+use crate::error::{ClientError, ClientResult};
+
+/// Value of the `Accept-Encoding` header sent when decompression is enabled
+/// 启用解压时发送的 `Accept-Encoding` 头的值
+pub(crate) const ACCEPT_ENCODING: &str = "gzip, deflate";
+
+/// Decompress a `gzip` or `deflate` encoded response body in place
+/// 就地解压 `gzip` 或 `deflate` 编码的响应body
+///
+/// `Content-Encoding` and `Content-Length` are removed once the body is decoded.
+/// Other encodings are left untouched.
+///
+/// 解码后会移除 `Content-Encoding` 和 `Content-Length`。其他编码保持不变。
+pub(crate) fn decode(mut response: Response, max_size: usize) -> ClientResult<Response> {
+    let Some(encoding) = response
+        .header("content-encoding")
+        .map(str::to_ascii_lowercase)
+    else {
+        return Ok(response);
+    };
+    let data = response.body().as_bytes().unwrap_or_default();
+    if data.is_empty() {
+        return Ok(response);
+    }
+
+    let decoded = match encoding.trim() {
+        "gzip" | "x-gzip" => read_limited(GzDecoder::new(data), max_size)?,
+        // Servers disagree on whether "deflate" is zlib-wrapped (RFC 9110) or raw
+        // 服务器对 "deflate" 是否带zlib包装（RFC 9110）或为原始数据存在分歧
+        "deflate" => match read_limited(ZlibDecoder::new(data), max_size) {
+            Err(ClientError::Decode(_)) => read_limited(DeflateDecoder::new(data), max_size)?,
+            other => other?,
+        },
+        _ => return Ok(response),
+    };
+
+    response.remove_header("content-encoding");
+    response.remove_header("content-length");
+    response.set_body(Body::from(decoded));
+    Ok(response)
+}
+
+/// Read a decoder to the end, failing once the output exceeds `max_size`
+/// 将解码器读到末尾，输出超过 `max_size` 时失败
+fn read_limited(decoder: impl Read, max_size: usize) -> ClientResult<Vec<u8>> {
+    let mut out = Vec::new();
+    decoder
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| ClientError::Decode(e.to_string()))?;
+    if out.len() > max_size {
+        return Err(ClientError::ResponseTooLarge(max_size));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+    use std::io::Write;
+
+    fn compressed(encoding: &str, data: &[u8]) -> Response {
+        let body = match encoding {
+            "gzip" => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            },
+            "zlib" => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            },
+            _ => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            },
+        };
+        let header = if encoding == "gzip" {
+            "gzip"
+        } else {
+            "deflate"
+        };
+        Response::builder()
+            .header("content-encoding", header)
+            .header("content-length", body.len().to_string())
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decode_gzip_and_deflate() {
+        let text = "hello hello hello hello".repeat(20);
+        for encoding in ["gzip", "zlib", "raw"] {
+            let response = decode(compressed(encoding, text.as_bytes()), 1 << 20).unwrap();
+            assert_eq!(response.body().as_bytes(), Some(text.as_bytes()), "{}", encoding);
+            assert!(response.header("content-encoding").is_none());
+            assert!(response.header("content-length").is_none());
+        }
+    }
+
+    #[test]
+    fn test_decode_limits_and_passthrough() {
+        let bomb = compressed("gzip", &vec![0u8; 1 << 20]);
+        assert!(matches!(decode(bomb, 4096), Err(ClientError::ResponseTooLarge(4096))));
+
+        let broken = Response::builder()
+            .header("content-encoding", "gzip")
+            .body(Body::from("not gzip"))
+            .unwrap();
+        assert!(matches!(decode(broken, 4096), Err(ClientError::Decode(_))));
+
+        let brotli = Response::builder()
+            .header("content-encoding", "br")
+            .body(Body::from("opaque"))
+            .unwrap();
+        let response = decode(brotli, 4096).unwrap();
+        assert_eq!(response.header("content-encoding"), Some("br"));
+    }
+}
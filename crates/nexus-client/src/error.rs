@@ -0,0 +1,141 @@
+//! Client error types
+//! 客户端错误类型
+
+use std::fmt;
+use std::time::Duration;
+
+use nexus_http::StatusCode;
+
+/// Client result type
+/// 客户端结果类型
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// HTTP client error
+/// HTTP 客户端错误
+///
+/// Equivalent to Spring's `WebClientRequestException` / `WebClientResponseException`.
+/// 等价于Spring的 `WebClientRequestException` / `WebClientResponseException`。
+#[derive(Debug, Clone)]
+pub enum ClientError {
+    /// The URL could not be parsed or is not an http(s) URL
+    /// URL无法解析或不是 http(s) URL
+    InvalidUrl(String),
+
+    /// The request could not be built
+    /// 无法构建请求
+    InvalidRequest(String),
+
+    /// Resolving or connecting to the host failed
+    /// 解析或连接主机失败
+    Connect(String),
+
+    /// The TLS handshake failed
+    /// TLS握手失败
+    Tls(String),
+
+    /// I/O error while the request was in flight
+    /// 请求进行中的I/O错误
+    Io(String),
+
+    /// The request did not complete in time
+    /// 请求未及时完成
+    Timeout(Duration),
+
+    /// The server sent a malformed response
+    /// 服务器发送了格式错误的响应
+    InvalidResponse(String),
+
+    /// The response exceeded `max_response_size`
+    /// 响应超过了 `max_response_size`
+    ResponseTooLarge(usize),
+
+    /// The redirect limit was reached
+    /// 达到了重定向次数上限
+    TooManyRedirects(usize),
+
+    /// The response body could not be decompressed or deserialized
+    /// 响应body无法解压或反序列化
+    Decode(String),
+
+    /// The server answered with an error status
+    /// 服务器返回了错误状态
+    ///
+    /// Returned by [`ResponseExt::error_for_status`](crate::ResponseExt::error_for_status).
+    /// 由 [`ResponseExt::error_for_status`](crate::ResponseExt::error_for_status) 返回。
+    Status {
+        /// Status code / 状态码
+        status: StatusCode,
+        /// Response body, lossily decoded / 响应body（有损解码）
+        body: String,
+    },
+}
+
+impl ClientError {
+    /// Whether the request may be retried: it never reached the server or the
+    /// connection broke while it was in flight
+    /// 请求是否可以重试：请求从未到达服务器，或连接在请求进行中断开
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ClientError::Connect(_) | ClientError::Io(_) | ClientError::Timeout(_))
+    }
+
+    /// Whether this is a timeout
+    /// 是否为超时
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, ClientError::Timeout(_))
+    }
+
+    /// Status code of a [`ClientError::Status`] error
+    /// [`ClientError::Status`] 错误的状态码
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::InvalidUrl(msg) => write!(f, "Invalid URL: {}", msg),
+            ClientError::InvalidRequest(msg) => write!(f, "Invalid request: {}", msg),
+            ClientError::Connect(msg) => write!(f, "Connection failed: {}", msg),
+            ClientError::Tls(msg) => write!(f, "TLS error: {}", msg),
+            ClientError::Io(msg) => write!(f, "I/O error: {}", msg),
+            ClientError::Timeout(after) => write!(f, "Request timed out after {:?}", after),
+            ClientError::InvalidResponse(msg) => write!(f, "Invalid response: {}", msg),
+            ClientError::ResponseTooLarge(max) => {
+                write!(f, "Response exceeds the limit of {} bytes", max)
+            },
+            ClientError::TooManyRedirects(max) => write!(f, "More than {} redirects", max),
+            ClientError::Decode(msg) => write!(f, "Failed to decode response: {}", msg),
+            ClientError::Status { status, .. } => {
+                write!(f, "HTTP status {}", status.as_u16())?;
+                if let Some(reason) = status.canonical_reason() {
+                    write!(f, " {}", reason)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<url::ParseError> for ClientError {
+    fn from(e: url::ParseError) -> Self {
+        ClientError::InvalidUrl(e.to_string())
+    }
+}
+
+impl From<ClientError> for nexus_http::Error {
+    fn from(e: ClientError) -> Self {
+        match e {
+            ClientError::Timeout(_) => nexus_http::Error::Timeout(e.to_string()),
+            ClientError::Connect(_) | ClientError::Tls(_) | ClientError::Io(_) => {
+                nexus_http::Error::Connection(e.to_string())
+            },
+            _ => nexus_http::Error::Internal(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Response helpers
+//! 响应辅助方法
+
+use bytes::Bytes;
+use nexus_http::{HttpBody, Response};
+use serde::de::DeserializeOwned;
+
+use crate::error::{ClientError, ClientResult};
+
+/// Convenience methods for responses returned by the client
+/// 客户端返回的响应的便捷方法
+///
+/// Equivalent to Spring's `WebClient.ResponseSpec` (`bodyToMono`, `onStatus`).
+/// 等价于Spring的 `WebClient.ResponseSpec`（`bodyToMono`、`onStatus`）。
+pub trait ResponseExt: Sized {
+    /// The response body
+    /// 响应body
+    fn bytes(&self) -> Bytes;
+
+    /// The response body as UTF-8 text
+    /// 以UTF-8文本形式返回响应body
+    fn text(&self) -> ClientResult<String>;
+
+    /// Deserialize the JSON response body
+    /// 反序列化JSON响应body
+    fn json<T: DeserializeOwned>(&self) -> ClientResult<T>;
+
+    /// Turn `4xx` and `5xx` responses into [`ClientError::Status`]
+    /// 将 `4xx` 和 `5xx` 响应转换为 [`ClientError::Status`]
+    fn error_for_status(self) -> ClientResult<Self>;
+}
+
+impl ResponseExt for Response {
+    fn bytes(&self) -> Bytes {
+        self.body().data().clone()
+    }
+
+    fn text(&self) -> ClientResult<String> {
+        let data = self.body().as_bytes().unwrap_or_default();
+        String::from_utf8(data.to_vec()).map_err(|e| ClientError::Decode(e.to_string()))
+    }
+
+    fn json<T: DeserializeOwned>(&self) -> ClientResult<T> {
+        let data = self.body().as_bytes().unwrap_or_default();
+        serde_json::from_slice(data).map_err(|e| ClientError::Decode(e.to_string()))
+    }
+
+    fn error_for_status(self) -> ClientResult<Self> {
+        let status = self.status();
+        if status.is_client_error() || status.is_server_error() {
+            let body = String::from_utf8_lossy(self.body().as_bytes().unwrap_or_default());
+            return Err(ClientError::Status {
+                status,
+                body: body.into_owned(),
+            });
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,207 @@
+//! Request interceptors
+//! 请求拦截器
+//!
+//! # Overview / 概述
+//!
+//! An [`Interceptor`] wraps every call made through an [`HttpClient`](crate::HttpClient):
+//! it may rewrite the request, short-circuit with its own response, or call
+//! [`Next::call`] any number of times. Interceptors run in registration order,
+//! the first one registered being the outermost; redirects are followed inside
+//! the chain, so interceptors see the original request and the final response.
+//!
+//! [`Interceptor`] 包装通过 [`HttpClient`](crate::HttpClient) 发出的每个调用：
+//! 它可以改写请求、直接返回自己的响应，或多次调用 [`Next::call`]。拦截器按注册顺序运行，
+//! 最先注册的位于最外层；重定向在链内部处理，因此拦截器看到的是原始请求和最终响应。
+//!
+//! # Equivalent to Spring / 等价于 Spring
+//!
+//! - `ExchangeFilterFunction` (WebClient)
+//! - `ClientHttpRequestInterceptor` (RestTemplate / RestClient)
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use nexus_http::{Request, Response};
+
+use crate::client::ClientInner;
+use crate::error::ClientResult;
+
+/// Boxed future returned by interceptors
+/// 拦截器返回的装箱future
+pub type InterceptorFuture = Pin<Box<dyn Future<Output = ClientResult<Response>> + Send>>;
+
+/// Hook around every request sent by a client
+/// 围绕客户端发出的每个请求的钩子
+pub trait Interceptor: Send + Sync + 'static {
+    /// Handle the request, usually by calling `next`
+    /// 处理请求，通常通过调用 `next`
+    fn intercept(&self, request: Request, next: Next) -> InterceptorFuture;
+}
+
+impl<F> Interceptor for F
+where
+    F: Fn(Request, Next) -> InterceptorFuture + Send + Sync + 'static,
+{
+    fn intercept(&self, request: Request, next: Next) -> InterceptorFuture {
+        self(request, next)
+    }
+}
+
+/// The rest of the interceptor chain
+/// 拦截器链的剩余部分
+#[derive(Clone)]
+pub struct Next {
+    client: Arc<ClientInner>,
+    index: usize,
+}
+
+impl Next {
+    /// Start of the chain
+    /// 链的起点
+    pub(crate) fn new(client: Arc<ClientInner>) -> Self {
+        Self { client, index: 0 }
+    }
+
+    /// Pass the request to the next interceptor, or send it
+    /// 将请求交给下一个拦截器，或将其发送
+    pub fn call(self, request: Request) -> InterceptorFuture {
+        match self.client.interceptors.get(self.index).cloned() {
+            Some(interceptor) => {
+                let next = Next {
+                    client: self.client,
+                    index: self.index + 1,
+                };
+                interceptor.intercept(request, next)
+            },
+            None => Box::pin(async move { self.client.dispatch(request).await }),
+        }
+    }
+}
+
+impl std::fmt::Debug for Next {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Next")
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Propagate W3C trace context (`traceparent`) on outgoing requests
+/// 在发出的请求上传播W3C追踪上下文（`traceparent`）
+///
+/// The context is taken from a [`TraceContext`](nexus_observability::TraceContext)
+/// in the request extensions when present, in which case a child span is sent;
+/// requests that already carry `traceparent` are left untouched and any other
+/// request starts a new trace.
+///
+/// 如果请求扩展中存在 [`TraceContext`](nexus_observability::TraceContext)，
+/// 则发送其子span；已带有 `traceparent` 的请求保持不变，其他请求开始新的追踪。
+#[cfg(feature = "observability")]
+#[derive(Debug)]
+pub struct TracingInterceptor {
+    tracer: nexus_observability::Tracer,
+}
+
+#[cfg(feature = "observability")]
+impl TracingInterceptor {
+    /// Create an interceptor injecting headers with `tracer`
+    /// 创建使用 `tracer` 注入头部的拦截器
+    pub fn new(tracer: nexus_observability::Tracer) -> Self {
+        Self { tracer }
+    }
+}
+
+#[cfg(feature = "observability")]
+impl Interceptor for TracingInterceptor {
+    fn intercept(&self, mut request: Request, next: Next) -> InterceptorFuture {
+        if !request.headers().contains_key("traceparent") {
+            let context = match request
+                .extensions()
+                .get::<nexus_observability::TraceContext>()
+            {
+                Some(parent) => parent.child(),
+                None => nexus_observability::TraceContext::new(),
+            };
+            let mut headers = Vec::new();
+            self.tracer.inject(&context, &mut headers);
+            for (name, value) in headers {
+                if let (Ok(name), Ok(value)) = (
+                    http::HeaderName::from_bytes(name.as_bytes()),
+                    http::HeaderValue::from_str(&value),
+                ) {
+                    request.inner_mut().headers_mut().insert(name, value);
+                }
+            }
+        }
+        next.call(request)
+    }
+}
+
+/// Retry idempotent requests on transport errors and `502`/`503`/`504` responses
+/// 在传输错误以及 `502`/`503`/`504` 响应时重试幂等请求
+///
+/// Attempts and backoff come from a
+/// [`RetryPolicy`](nexus_resilience::retry::RetryPolicy). `POST` and `PATCH`
+/// requests, and requests with a streaming body, are sent once.
+///
+/// 尝试次数和退避来自 [`RetryPolicy`](nexus_resilience::retry::RetryPolicy)。
+/// `POST`、`PATCH` 请求以及带流式body的请求只发送一次。
+#[cfg(feature = "resilience")]
+#[derive(Debug, Clone)]
+pub struct RetryInterceptor {
+    policy: nexus_resilience::retry::RetryPolicy,
+}
+
+#[cfg(feature = "resilience")]
+impl RetryInterceptor {
+    /// Create an interceptor retrying according to `policy`
+    /// 创建按 `policy` 重试的拦截器
+    pub fn new(policy: nexus_resilience::retry::RetryPolicy) -> Self {
+        Self { policy }
+    }
+}
+
+#[cfg(feature = "resilience")]
+impl Interceptor for RetryInterceptor {
+    fn intercept(&self, request: Request, next: Next) -> InterceptorFuture {
+        use nexus_http::StatusCode;
+
+        let idempotent = matches!(
+            *request.inner().method(),
+            http::Method::GET
+                | http::Method::HEAD
+                | http::Method::PUT
+                | http::Method::DELETE
+                | http::Method::OPTIONS
+                | http::Method::TRACE
+        );
+        if !idempotent || request.body().is_stream() {
+            return next.call(request);
+        }
+
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            let max_attempts = policy.max_attempts();
+            let mut attempt = 1;
+            loop {
+                let result = next.clone().call(request.clone()).await;
+                let retry = match &result {
+                    Ok(response) => matches!(
+                        response.status(),
+                        StatusCode::BAD_GATEWAY
+                            | StatusCode::SERVICE_UNAVAILABLE
+                            | StatusCode::GATEWAY_TIMEOUT
+                    ),
+                    Err(e) => e.is_retryable(),
+                };
+                if !retry || attempt >= max_attempts {
+                    return result;
+                }
+                tracing::debug!(attempt, uri = %request.uri(), "retrying request");
+                nexus_runtime::time::sleep(policy.calculate_delay(attempt)).await;
+                attempt += 1;
+            }
+        })
+    }
+}
@@ -0,0 +1,72 @@
+//! Nexus Client - Spring WebClient equivalent HTTP client
+//! Nexus Client - Spring WebClient 等价的HTTP客户端
+//!
+//! # Overview / 概述
+//!
+//! A native asynchronous HTTP/1.1 client running on `nexus-runtime`. It speaks the
+//! same [`Request`](nexus_http::Request) and [`Response`](nexus_http::Response)
+//! types as the server, encoded with the `nexus_http::proto` codec, and provides
+//! keep-alive connection pooling per origin, connect and request timeouts,
+//! redirects, `gzip`/`deflate` decoding, TLS with optional client certificates,
+//! and [`Interceptor`] hooks for cross-cutting concerns such as trace propagation
+//! and retries.
+//!
+//! 运行在 `nexus-runtime` 上的原生异步 HTTP/1.1 客户端。它使用与服务器相同的
+//! [`Request`](nexus_http::Request) 和 [`Response`](nexus_http::Response) 类型，
+//! 由 `nexus_http::proto` 编解码，并提供按源划分的 keep-alive 连接池、连接和请求超时、
+//! 重定向、`gzip`/`deflate` 解码、支持客户端证书的TLS，以及用于追踪传播和重试等
+//! 横切关注点的 [`Interceptor`] 钩子。
+//!
+//! # Equivalent to Spring / 等价于 Spring
+//!
+//! - `WebClient` / `RestClient` - [`HttpClient`]
+//! - `WebClient.Builder` - [`ClientBuilder`]
+//! - `ExchangeFilterFunction` - [`Interceptor`]
+//! - `WebClientResponseException` - [`ClientError`]
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_client::{HttpClient, ResponseExt, RetryInterceptor, TracingInterceptor};
+//! use nexus_observability::Tracer;
+//! use nexus_resilience::retry::RetryPolicy;
+//!
+//! let client = HttpClient::builder()
+//!     .base_url("https://api.example.com/v1/")
+//!     .interceptor(TracingInterceptor::new(Tracer::new("orders")))
+//!     .interceptor(RetryInterceptor::new(RetryPolicy::new().with_max_attempts(3)))
+//!     .build()?;
+//!
+//! let order: Order = client
+//!     .post("orders")
+//!     .bearer_auth(&token)
+//!     .json(&new_order)
+//!     .send()
+//!     .await?
+//!     .error_for_status()?
+//!     .json()?;
+//! ```
+
+#![warn(missing_docs)]
+#![warn(unreachable_pub)]
+
+mod client;
+mod conn;
+mod encoding;
+pub mod error;
+mod ext;
+pub mod interceptor;
+mod pool;
+mod tls;
+
+pub use client::{ClientBuilder, HttpClient, RequestBuilder};
+pub use error::{ClientError, ClientResult};
+pub use ext::ResponseExt;
+#[cfg(feature = "resilience")]
+pub use interceptor::RetryInterceptor;
+#[cfg(feature = "observability")]
+pub use interceptor::TracingInterceptor;
+pub use interceptor::{Interceptor, InterceptorFuture, Next};
+
+/// Version of the client module
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,142 @@
+//! Keep-alive connection pool
+//! Keep-alive 连接池
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use url::{Host, Url};
+
+use crate::conn::Connection;
+use crate::error::{ClientError, ClientResult};
+
+/// Origin a connection is bound to
+/// 连接所绑定的源
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PoolKey {
+    /// `https` rather than `http` / 是否为 `https` 而非 `http`
+    pub(crate) https: bool,
+    /// Host name or IP literal (without brackets) / 主机名或IP字面量（不含方括号）
+    pub(crate) host: String,
+    /// Port / 端口
+    pub(crate) port: u16,
+}
+
+impl PoolKey {
+    /// Origin of an `http` or `https` URL
+    /// `http` 或 `https` URL 的源
+    pub(crate) fn from_url(url: &Url) -> ClientResult<Self> {
+        let https = match url.scheme() {
+            "http" => false,
+            "https" => true,
+            other => return Err(ClientError::InvalidUrl(format!("Unsupported scheme: {}", other))),
+        };
+        let host = match url.host() {
+            Some(Host::Domain(domain)) => domain.to_string(),
+            Some(Host::Ipv4(ip)) => ip.to_string(),
+            Some(Host::Ipv6(ip)) => ip.to_string(),
+            None => return Err(ClientError::InvalidUrl(format!("Missing host: {}", url))),
+        };
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| ClientError::InvalidUrl(format!("Missing port: {}", url)))?;
+        Ok(Self { https, host, port })
+    }
+}
+
+/// An idle connection and when it was returned
+/// 空闲连接及其归还时间
+struct Idle {
+    conn: Connection,
+    since: Instant,
+}
+
+/// Idle connections grouped by origin
+/// 按源分组的空闲连接
+pub(crate) struct Pool {
+    idle: Mutex<HashMap<PoolKey, Vec<Idle>>>,
+    max_idle_per_host: usize,
+    idle_timeout: Option<Duration>,
+}
+
+impl Pool {
+    /// Create an empty pool
+    /// 创建空的连接池
+    pub(crate) fn new(max_idle_per_host: usize, idle_timeout: Option<Duration>) -> Self {
+        Self {
+            idle: Mutex::new(HashMap::new()),
+            max_idle_per_host,
+            idle_timeout,
+        }
+    }
+
+    /// Whether connections are kept at all
+    /// 是否保留连接
+    pub(crate) fn enabled(&self) -> bool {
+        self.max_idle_per_host > 0
+    }
+
+    /// Take the most recently used idle connection to `key`, dropping expired ones
+    /// 取出到 `key` 的最近使用的空闲连接，并丢弃已过期的连接
+    pub(crate) fn checkout(&self, key: &PoolKey) -> Option<Connection> {
+        let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+        let conns = idle.get_mut(key)?;
+        if let Some(timeout) = self.idle_timeout {
+            conns.retain(|entry| entry.since.elapsed() < timeout);
+        }
+        let conn = conns.pop().map(|entry| entry.conn);
+        if conns.is_empty() {
+            idle.remove(key);
+        }
+        conn
+    }
+
+    /// Return a connection that can carry another request
+    /// 归还可以承载下一个请求的连接
+    pub(crate) fn checkin(&self, key: PoolKey, conn: Connection) {
+        if !self.enabled() {
+            return;
+        }
+        let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+        let conns = idle.entry(key).or_default();
+        conns.push(Idle {
+            conn,
+            since: Instant::now(),
+        });
+        if conns.len() > self.max_idle_per_host {
+            conns.remove(0);
+        }
+    }
+
+    /// Number of idle connections to `key`
+    /// 到 `key` 的空闲连接数
+    #[cfg(test)]
+    pub(crate) fn idle_count(&self, key: &PoolKey) -> usize {
+        let idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+        idle.get(key).map_or(0, Vec::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_key_from_url() {
+        let key = PoolKey::from_url(&Url::parse("https://Example.com/a").unwrap()).unwrap();
+        assert_eq!(
+            key,
+            PoolKey {
+                https: true,
+                host: "example.com".to_string(),
+                port: 443
+            }
+        );
+
+        let key = PoolKey::from_url(&Url::parse("http://[::1]:8080/").unwrap()).unwrap();
+        assert_eq!(key.host, "::1");
+        assert_eq!(key.port, 8080);
+
+        assert!(PoolKey::from_url(&Url::parse("ftp://example.com/").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! Client-side TLS
+//! 客户端 TLS
+
+use std::fmt;
+use std::io::{self, Read as _, Write as _};
+use std::sync::Arc;
+
+use bytes::{Buf, BytesMut};
+use nexus_runtime::io::TcpStream;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConnection, RootCertStore};
+
+use crate::error::{ClientError, ClientResult};
+
+/// Size of a single socket read
+/// 单次套接字读取的大小
+const READ_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Largest amount of plaintext encrypted before it is flushed
+/// 刷新之前加密的最大明文量
+const WRITE_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Trust anchors and client identity used for `https` URLs
+/// 用于 `https` URL 的信任锚和客户端身份
+#[derive(Default)]
+pub(crate) struct TlsOptions {
+    /// Additional trusted CA certificates / 额外信任的CA证书
+    pub(crate) roots: Vec<CertificateDer<'static>>,
+    /// Skip the bundled Mozilla roots / 不使用内置的Mozilla根证书
+    pub(crate) no_builtin_roots: bool,
+    /// Client certificate chain and key for mTLS / 用于mTLS的客户端证书链和私钥
+    pub(crate) identity: Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>,
+}
+
+impl TlsOptions {
+    /// Parse and add the PEM certificates in `pem` as trust anchors
+    /// 解析 `pem` 中的PEM证书并添加为信任锚
+    pub(crate) fn add_roots(&mut self, pem: &[u8]) -> ClientResult<()> {
+        let mut certs = parse_certificates(pem)?;
+        self.roots.append(&mut certs);
+        Ok(())
+    }
+
+    /// Set the client certificate presented to servers asking for one
+    /// 设置向请求证书的服务器出示的客户端证书
+    pub(crate) fn set_identity(&mut self, cert_pem: &[u8], key_pem: &[u8]) -> ClientResult<()> {
+        let chain = parse_certificates(cert_pem)?;
+        let key = PrivateKeyDer::from_pem_slice(key_pem)
+            .map_err(|e| ClientError::Tls(format!("Invalid private key: {}", e)))?;
+        self.identity = Some((chain, key));
+        Ok(())
+    }
+
+    /// Build the connector
+    /// 构建连接器
+    pub(crate) fn build(self) -> ClientResult<TlsConnector> {
+        let mut roots = RootCertStore::empty();
+        if !self.no_builtin_roots {
+            roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        }
+        for cert in self.roots {
+            roots
+                .add(cert)
+                .map_err(|e| ClientError::Tls(format!("Invalid root certificate: {}", e)))?;
+        }
+
+        let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+        let builder = rustls::ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| ClientError::Tls(format!("Invalid TLS configuration: {}", e)))?
+            .with_root_certificates(roots);
+        let mut config = match self.identity {
+            Some((chain, key)) => builder
+                .with_client_auth_cert(chain, key)
+                .map_err(|e| ClientError::Tls(format!("Invalid client certificate: {}", e)))?,
+            None => builder.with_no_client_auth(),
+        };
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+        Ok(TlsConnector {
+            config: Arc::new(config),
+        })
+    }
+}
+
+/// Parse every certificate in a PEM file, failing if there is none
+/// 解析PEM文件中的所有证书，没有证书时失败
+fn parse_certificates(pem: &[u8]) -> ClientResult<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_slice_iter(pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ClientError::Tls(format!("Invalid certificate: {}", e)))?;
+    if certs.is_empty() {
+        return Err(ClientError::Tls("No certificate found in PEM data".to_string()));
+    }
+    Ok(certs)
+}
+
+/// Performs client TLS handshakes
+/// 执行客户端TLS握手
+#[derive(Clone)]
+pub(crate) struct TlsConnector {
+    config: Arc<rustls::ClientConfig>,
+}
+
+impl TlsConnector {
+    /// Run the handshake for `host` over an established TCP connection
+    /// 在已建立的TCP连接上为 `host` 执行握手
+    pub(crate) async fn connect(&self, host: &str, tcp: TcpStream) -> ClientResult<TlsStream> {
+        let name = ServerName::try_from(host.to_string())
+            .map_err(|_| ClientError::Tls(format!("Invalid server name: {}", host)))?;
+        let conn = ClientConnection::new(self.config.clone(), name)
+            .map_err(|e| ClientError::Tls(e.to_string()))?;
+        let mut stream = TlsStream {
+            tcp,
+            conn,
+            incoming: BytesMut::new(),
+            scratch: vec![0u8; READ_CHUNK_SIZE],
+            outgoing: Vec::new(),
+        };
+
+        let handshake_error = |e: io::Error| ClientError::Tls(e.to_string());
+        while stream.conn.is_handshaking() {
+            stream.flush().await.map_err(handshake_error)?;
+            if !stream.conn.is_handshaking() {
+                break;
+            }
+            if !stream.read_tls().await.map_err(handshake_error)? {
+                return Err(ClientError::Tls("connection closed during TLS handshake".to_string()));
+            }
+        }
+        stream.flush().await.map_err(handshake_error)?;
+        Ok(stream)
+    }
+}
+
+impl fmt::Debug for TlsConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConnector").finish_non_exhaustive()
+    }
+}
+
+/// A client-side TLS connection over a TCP stream
+/// 基于TCP流的客户端TLS连接
+pub(crate) struct TlsStream {
+    tcp: TcpStream,
+    conn: ClientConnection,
+    /// Ciphertext read from the socket but not yet fed to rustls / 已从套接字读取但尚未交给rustls的密文
+    incoming: BytesMut,
+    /// Socket read buffer / 套接字读取缓冲区
+    scratch: Vec<u8>,
+    /// Ciphertext waiting to be written / 等待写出的密文
+    outgoing: Vec<u8>,
+}
+
+impl TlsStream {
+    /// Read decrypted bytes, returning 0 once the server has closed the connection
+    /// 读取解密后的字节，服务器关闭连接后返回0
+    pub(crate) async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.conn.reader().read(buf) {
+                Ok(n) => return Ok(n),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {},
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(0),
+                Err(e) => return Err(e),
+            }
+            if !self.read_tls().await? {
+                return Ok(0);
+            }
+        }
+    }
+
+    /// Encrypt and write all bytes
+    /// 加密并写入所有字节
+    pub(crate) async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        for chunk in buf.chunks(WRITE_CHUNK_SIZE) {
+            self.conn.writer().write_all(chunk)?;
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Feed more ciphertext to rustls, returning false at end of stream
+    /// 向rustls提供更多密文，到达流末尾时返回false
+    async fn read_tls(&mut self) -> io::Result<bool> {
+        if self.incoming.is_empty() {
+            let n = self.tcp.read(&mut self.scratch).await?;
+            if n == 0 {
+                return Ok(false);
+            }
+            self.incoming.extend_from_slice(&self.scratch[..n]);
+        }
+
+        let mut input: &[u8] = &self.incoming;
+        let consumed = self.conn.read_tls(&mut input)?;
+        self.incoming.advance(consumed);
+
+        if let Err(e) = self.conn.process_new_packets() {
+            let _ = self.flush().await;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        Ok(true)
+    }
+
+    /// Write out all pending ciphertext
+    /// 写出所有待发送的密文
+    async fn flush(&mut self) -> io::Result<()> {
+        while self.conn.wants_write() {
+            self.outgoing.clear();
+            self.conn.write_tls(&mut self.outgoing)?;
+            self.tcp.write_all(&self.outgoing).await?;
+        }
+        Ok(())
+    }
+}
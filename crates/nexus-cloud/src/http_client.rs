@@ -0,0 +1,773 @@
+//! Declarative HTTP client module
+//! 声明式HTTP客户端模块
+//!
+//! # Equivalent to Spring Cloud / 等价于 Spring Cloud
+//!
+//! - `@FeignClient` - `#[feign_client]` in `nexus-macros`, backed by [`FeignClient`]
+//! - `Feign.Builder` - [`FeignClientBuilder`]
+//! - `ErrorDecoder` - [`ErrorDecoder`]
+//! - `FeignException` - [`FeignError`]
+//!
+//! `#[feign_client]` turns a trait into a `<Trait>Impl` struct whose methods build a
+//! [`FeignRequest`] from the mapping attributes and run it through a [`FeignClient`].
+//! The client resolves the target from a fixed URL or from [`ServiceDiscovery`] and a
+//! [`LoadBalancer`], and wraps every call in the configured retry policy and named
+//! circuit breaker.
+//!
+//! `#[feign_client]` 将trait转换为 `<Trait>Impl` 结构体，其方法根据映射属性构建
+//! [`FeignRequest`]，并通过 [`FeignClient`] 执行。客户端从固定URL或
+//! [`ServiceDiscovery`] 与 [`LoadBalancer`] 解析目标，并用配置的重试策略和命名断路器
+//! 包装每次调用。
+//!
+//! # Spring Equivalent / Spring等价物
+//!
+//! ```java
+//! @FeignClient(name = "user-service", path = "/api")
+//! public interface UserClient {
+//!     @GetMapping("/users/{id}")
+//!     User getUser(@PathVariable("id") Long id);
+//! }
+//! ```
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_cloud::http_client::FeignError;
+//! use nexus_macros::feign_client;
+//!
+//! #[feign_client(name = "user-service", path = "/api", circuit_breaker = "users")]
+//! #[feign_retry(max_attempts = 3)]
+//! pub trait UserClient {
+//!     #[feign_get("/users/{id}")]
+//!     async fn get_user(&self, #[feign_path] id: u64) -> Result<User, FeignError>;
+//!
+//!     #[feign_post("/users")]
+//!     #[feign_timeout(2000)]
+//!     async fn create_user(&self, #[feign_body] user: &NewUser) -> Result<User, FeignError>;
+//! }
+//!
+//! let users = UserClientImpl::new(UserClientImpl::builder().discovery(discovery).build()?);
+//! let user = users.get_user(42).await?;
+//! ```
+
+use std::fmt::{self, Write};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use nexus_client::{ClientError, HttpClient, ResponseExt};
+use nexus_http::{HttpBody, Response, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::circuit_breaker::{CircuitBreakerError, CircuitBreakerRegistry};
+use crate::discovery::{ServiceDiscovery, ServiceInstance};
+use crate::load_balancer::{LoadBalancer, RoundRobinLoadBalancer};
+
+pub use nexus_http::Method;
+pub use nexus_resilience::retry::RetryPolicy;
+
+/// Feign client result type
+/// Feign客户端结果类型
+pub type FeignResult<T> = Result<T, FeignError>;
+
+/// Feign client error
+/// Feign客户端错误
+///
+/// Equivalent to Spring Cloud OpenFeign's `FeignException`.
+/// 等价于Spring Cloud OpenFeign的 `FeignException`。
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum FeignError {
+    /// The client is missing a target or is otherwise misconfigured
+    /// 客户端缺少目标或配置错误
+    #[error("Invalid Feign client configuration: {0}")]
+    Config(String),
+
+    /// Service discovery returned no healthy instance
+    /// 服务发现没有返回健康的实例
+    #[error("No available instance of service '{0}'")]
+    NoInstance(String),
+
+    /// The named circuit breaker rejected the call
+    /// 命名断路器拒绝了调用
+    #[error("Circuit breaker '{0}' is open")]
+    CircuitOpen(String),
+
+    /// An argument could not be encoded into the request
+    /// 参数无法编码到请求中
+    #[error("Failed to encode request: {0}")]
+    Encode(String),
+
+    /// The response body could not be deserialized
+    /// 响应body无法反序列化
+    #[error("Failed to decode response: {0}")]
+    Decode(String),
+
+    /// The server answered with an error status
+    /// 服务器返回了错误状态
+    #[error("{method_key} failed with status {}: {body}", .status.as_u16())]
+    Status {
+        /// Calling method, e.g. `UserClient#get_user` / 调用方法，例如 `UserClient#get_user`
+        method_key: String,
+        /// Response status / 响应状态
+        status: StatusCode,
+        /// Response body / 响应body
+        body: String,
+    },
+
+    /// The underlying HTTP exchange failed
+    /// 底层HTTP交换失败
+    #[error(transparent)]
+    Client(#[from] ClientError),
+}
+
+impl FeignError {
+    /// Whether the call may succeed when tried again
+    /// 重试时调用是否可能成功
+    ///
+    /// Connection failures, timeouts, missing instances and `502`/`503`/`504`
+    /// responses are retryable.
+    /// 连接失败、超时、缺少实例以及 `502`/`503`/`504` 响应可以重试。
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FeignError::Client(e) => e.is_retryable(),
+            FeignError::NoInstance(_) => true,
+            FeignError::Status { status, .. } => matches!(status.as_u16(), 502..=504),
+            _ => false,
+        }
+    }
+
+    /// Status code of a [`FeignError::Status`] error
+    /// [`FeignError::Status`] 错误的状态码
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            FeignError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+/// Turns error responses into [`FeignError`]s
+/// 将错误响应转换为 [`FeignError`]
+///
+/// Equivalent to Spring Cloud OpenFeign's `ErrorDecoder`.
+/// 等价于Spring Cloud OpenFeign的 `ErrorDecoder`。
+pub trait ErrorDecoder: Send + Sync + 'static {
+    /// Decode a `4xx` or `5xx` response returned to `method_key`
+    /// 解码返回给 `method_key` 的 `4xx` 或 `5xx` 响应
+    fn decode(&self, method_key: &str, response: &Response) -> FeignError;
+}
+
+/// Error decoder producing [`FeignError::Status`]
+/// 生成 [`FeignError::Status`] 的错误解码器
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultErrorDecoder;
+
+impl ErrorDecoder for DefaultErrorDecoder {
+    fn decode(&self, method_key: &str, response: &Response) -> FeignError {
+        FeignError::Status {
+            method_key: method_key.to_string(),
+            status: response.status(),
+            body: response.text().unwrap_or_default(),
+        }
+    }
+}
+
+/// A request built by a generated Feign method
+/// 由生成的Feign方法构建的请求
+///
+/// Argument encoding errors are kept and reported by [`FeignClient::execute`].
+/// 参数编码错误会被保留，并由 [`FeignClient::execute`] 报告。
+#[derive(Debug, Clone)]
+pub struct FeignRequest {
+    method: Method,
+    method_key: String,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    circuit_breaker: Option<String>,
+    error: Option<FeignError>,
+}
+
+impl FeignRequest {
+    /// Start a request for a path template such as `/users/{id}`
+    /// 为 `/users/{id}` 这样的路径模板开始请求
+    pub fn new(method: Method, method_key: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            method,
+            method_key: method_key.into(),
+            path: path.into(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            timeout: None,
+            retry: None,
+            circuit_breaker: None,
+            error: None,
+        }
+    }
+
+    /// Fill the `{name}` placeholder with a percent-encoded value
+    /// 用百分号编码的值填充 `{name}` 占位符
+    pub fn path_param<T: Serialize + ?Sized>(mut self, name: &str, value: &T) -> Self {
+        match param_values(value).as_deref() {
+            Ok([value]) => {
+                let placeholder = format!("{{{}}}", name);
+                self.path = self.path.replace(&placeholder, &encode_segment(value));
+            },
+            Ok(_) => self.fail(FeignError::Encode(format!(
+                "Path variable '{}' must be a single value",
+                name
+            ))),
+            Err(e) => self.fail(e.clone()),
+        }
+        self
+    }
+
+    /// Add a query parameter; `None` is skipped and sequences repeat the name
+    /// 添加查询参数；`None` 会被跳过，序列会重复该名称
+    pub fn query<T: Serialize + ?Sized>(mut self, name: &str, value: &T) -> Self {
+        match param_values(value) {
+            Ok(values) => {
+                self.query
+                    .extend(values.into_iter().map(|value| (name.to_string(), value)));
+            },
+            Err(e) => self.fail(e),
+        }
+        self
+    }
+
+    /// Add a header; `None` is skipped and sequences are comma-joined
+    /// 添加头部；`None` 会被跳过，序列以逗号连接
+    pub fn header<T: Serialize + ?Sized>(mut self, name: &str, value: &T) -> Self {
+        match param_values(value) {
+            Ok(values) if values.is_empty() => {},
+            Ok(values) => self.headers.push((name.to_string(), values.join(", "))),
+            Err(e) => self.fail(e),
+        }
+        self
+    }
+
+    /// Serialize `value` as the JSON request body
+    /// 将 `value` 序列化为JSON请求body
+    pub fn json<T: Serialize + ?Sized>(mut self, value: &T) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(body) => self.body = Some(body),
+            Err(e) => self.fail(FeignError::Encode(e.to_string())),
+        }
+        self
+    }
+
+    /// Override the client timeout for this method
+    /// 为此方法覆盖客户端超时
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Override the client retry policy for this method
+    /// 为此方法覆盖客户端重试策略
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Override the client circuit breaker for this method
+    /// 为此方法覆盖客户端断路器
+    pub fn circuit_breaker(mut self, name: impl Into<String>) -> Self {
+        self.circuit_breaker = Some(name.into());
+        self
+    }
+
+    /// Method key used in errors, e.g. `UserClient#get_user`
+    /// 错误中使用的方法键，例如 `UserClient#get_user`
+    pub fn method_key(&self) -> &str {
+        &self.method_key
+    }
+
+    /// Keep the first error
+    /// 保留第一个错误
+    fn fail(&mut self, e: FeignError) {
+        if self.error.is_none() {
+            self.error = Some(e);
+        }
+    }
+}
+
+/// Where requests are sent
+/// 请求发送的目标
+enum Target {
+    /// A fixed base URL / 固定的基础URL
+    Url(String),
+    /// Instances of a discovered service / 被发现服务的实例
+    Discovery {
+        discovery: Arc<dyn ServiceDiscovery>,
+        balancer: Arc<dyn ChooseInstance>,
+    },
+}
+
+/// Object-safe view of a [`LoadBalancer`]
+/// [`LoadBalancer`] 的对象安全视图
+trait ChooseInstance: Send + Sync {
+    fn choose<'a>(
+        &'a self,
+        instances: &'a [ServiceInstance],
+    ) -> Pin<Box<dyn Future<Output = Option<ServiceInstance>> + Send + 'a>>;
+}
+
+impl<L: LoadBalancer> ChooseInstance for L {
+    fn choose<'a>(
+        &'a self,
+        instances: &'a [ServiceInstance],
+    ) -> Pin<Box<dyn Future<Output = Option<ServiceInstance>> + Send + 'a>> {
+        Box::pin(LoadBalancer::choose(self, instances))
+    }
+}
+
+struct FeignInner {
+    name: String,
+    target: Target,
+    path: String,
+    http: HttpClient,
+    timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    circuit_breaker: Option<String>,
+    breakers: Arc<CircuitBreakerRegistry>,
+    error_decoder: Arc<dyn ErrorDecoder>,
+}
+
+/// Runtime behind the structs generated by `#[feign_client]`
+/// `#[feign_client]` 生成的结构体背后的运行时
+///
+/// Equivalent to the proxy Spring Cloud OpenFeign creates for a `@FeignClient`.
+/// 等价于Spring Cloud OpenFeign为 `@FeignClient` 创建的代理。
+#[derive(Clone)]
+pub struct FeignClient {
+    inner: Arc<FeignInner>,
+}
+
+impl FeignClient {
+    /// Start building a client for the service `name`
+    /// 开始为服务 `name` 构建客户端
+    pub fn builder(name: impl Into<String>) -> FeignClientBuilder {
+        FeignClientBuilder::new(name)
+    }
+
+    /// Service name of this client
+    /// 此客户端的服务名称
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Send a request and return the successful response
+    /// 发送请求并返回成功的响应
+    ///
+    /// Each attempt resolves a target, runs through the circuit breaker and counts
+    /// transport errors and `5xx` responses as failures. Retryable errors are retried
+    /// according to the request or client retry policy. `4xx` and `5xx` responses are
+    /// turned into errors by the [`ErrorDecoder`].
+    ///
+    /// 每次尝试都会解析目标、经过断路器，并将传输错误和 `5xx` 响应记为失败。
+    /// 可重试的错误会按请求或客户端的重试策略重试。`4xx` 和 `5xx` 响应由
+    /// [`ErrorDecoder`] 转换为错误。
+    pub async fn execute(&self, mut request: FeignRequest) -> FeignResult<Response> {
+        if let Some(e) = request.error.take() {
+            return Err(e);
+        }
+        let policy = request.retry.clone().or_else(|| self.inner.retry.clone());
+        let max_attempts = policy.as_ref().map_or(1, |p| p.max_attempts().max(1));
+        let request = Arc::new(request);
+
+        let mut attempt = 0;
+        loop {
+            match self.attempt(request.clone()).await {
+                Err(e) if e.is_retryable() && attempt + 1 < max_attempts => {
+                    attempt += 1;
+                    let delay = policy
+                        .as_ref()
+                        .map_or(Duration::ZERO, |p| p.calculate_delay(attempt));
+                    tracing::debug!(
+                        method = %request.method_key,
+                        attempt,
+                        error = %e,
+                        "Retrying Feign call"
+                    );
+                    if !delay.is_zero() {
+                        nexus_runtime::time::sleep(delay).await;
+                    }
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Deserialize a JSON response body; an empty body decodes as `null`
+    /// 反序列化JSON响应body；空body按 `null` 解码
+    pub fn decode<T: DeserializeOwned>(&self, response: &Response) -> FeignResult<T> {
+        let data = response.body().as_bytes().unwrap_or_default();
+        let data = if data.is_empty() {
+            b"null".as_slice()
+        } else {
+            data
+        };
+        serde_json::from_slice(data).map_err(|e| FeignError::Decode(e.to_string()))
+    }
+
+    async fn attempt(&self, request: Arc<FeignRequest>) -> FeignResult<Response> {
+        let breaker = request
+            .circuit_breaker
+            .as_deref()
+            .or(self.inner.circuit_breaker.as_deref());
+        let call: BoxFuture<'static, _> = Box::pin(Self::send(self.inner.clone(), request.clone()));
+
+        let response = match breaker {
+            Some(name) => {
+                let breaker = self.inner.breakers.get(name).await;
+                breaker.execute(move || call).await.map_err(|e| match e {
+                    CircuitBreakerError::Open(name) => FeignError::CircuitOpen(name),
+                    CircuitBreakerError::Failed { error, .. } => error,
+                })?
+            },
+            None => call.await?,
+        };
+
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(self
+                .inner
+                .error_decoder
+                .decode(&request.method_key, &response))
+        }
+    }
+
+    /// One HTTP exchange; `5xx` responses are errors so the circuit breaker sees them
+    /// 一次HTTP交换；`5xx` 响应视为错误，以便断路器能看到
+    async fn send(inner: Arc<FeignInner>, request: Arc<FeignRequest>) -> FeignResult<Response> {
+        let base = inner.resolve().await?;
+        let url = format!("{}{}{}", base.trim_end_matches('/'), inner.path, request.path);
+
+        let mut builder = inner
+            .http
+            .request(request.method, &url)
+            .query(&request.query);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = &request.body {
+            if !request
+                .headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            {
+                builder = builder.header("content-type", "application/json");
+            }
+            builder = builder.body(body.clone());
+        }
+        if let Some(timeout) = request.timeout.or(inner.timeout) {
+            builder = builder.timeout(timeout);
+        }
+
+        let response = builder.send().await?;
+        if response.status().is_server_error() {
+            return Err(inner.error_decoder.decode(&request.method_key, &response));
+        }
+        Ok(response)
+    }
+}
+
+impl FeignInner {
+    /// Base URL for the next attempt
+    /// 下一次尝试的基础URL
+    async fn resolve(&self) -> FeignResult<String> {
+        match &self.target {
+            Target::Url(url) => Ok(url.clone()),
+            Target::Discovery {
+                discovery,
+                balancer,
+            } => {
+                let instances: Vec<_> = discovery
+                    .get_instances(&self.name)
+                    .await
+                    .into_iter()
+                    .filter(ServiceInstance::is_healthy)
+                    .collect();
+                balancer
+                    .choose(&instances)
+                    .await
+                    .map(|instance| instance.uri().to_string())
+                    .ok_or_else(|| FeignError::NoInstance(self.name.clone()))
+            },
+        }
+    }
+}
+
+impl fmt::Debug for FeignClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let target = match &self.inner.target {
+            Target::Url(url) => url.as_str(),
+            Target::Discovery { .. } => "<discovery>",
+        };
+        f.debug_struct("FeignClient")
+            .field("name", &self.inner.name)
+            .field("target", &target)
+            .field("path", &self.inner.path)
+            .field("circuit_breaker", &self.inner.circuit_breaker)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builder for [`FeignClient`]
+/// [`FeignClient`] 的构建器
+///
+/// Equivalent to Spring Cloud OpenFeign's `Feign.Builder`.
+/// 等价于Spring Cloud OpenFeign的 `Feign.Builder`。
+pub struct FeignClientBuilder {
+    name: String,
+    url: Option<String>,
+    path: String,
+    discovery: Option<Arc<dyn ServiceDiscovery>>,
+    balancer: Arc<dyn ChooseInstance>,
+    http: Option<HttpClient>,
+    timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    circuit_breaker: Option<String>,
+    breakers: Option<Arc<CircuitBreakerRegistry>>,
+    error_decoder: Arc<dyn ErrorDecoder>,
+}
+
+impl FeignClientBuilder {
+    /// Create a builder for the service `name`
+    /// 为服务 `name` 创建构建器
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: None,
+            path: String::new(),
+            discovery: None,
+            balancer: Arc::new(RoundRobinLoadBalancer::new()),
+            http: None,
+            timeout: None,
+            retry: None,
+            circuit_breaker: None,
+            breakers: None,
+            error_decoder: Arc::new(DefaultErrorDecoder),
+        }
+    }
+
+    /// Send requests to a fixed base URL instead of discovered instances
+    /// 将请求发送到固定的基础URL，而不是被发现的实例
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Path prefix for every method
+    /// 每个方法的路径前缀
+    pub fn path(mut self, path: &str) -> Self {
+        let path = path.trim_end_matches('/');
+        self.path = if path.is_empty() || path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{}", path)
+        };
+        self
+    }
+
+    /// Resolve instances of the service through `discovery`
+    /// 通过 `discovery` 解析服务实例
+    pub fn discovery(mut self, discovery: Arc<dyn ServiceDiscovery>) -> Self {
+        self.discovery = Some(discovery);
+        self
+    }
+
+    /// Choose among discovered instances with `balancer` (round-robin by default)
+    /// 使用 `balancer` 在被发现的实例中选择（默认轮询）
+    pub fn load_balancer(mut self, balancer: impl LoadBalancer + 'static) -> Self {
+        self.balancer = Arc::new(balancer);
+        self
+    }
+
+    /// Use a configured HTTP client, sharing its pool and interceptors
+    /// 使用已配置的HTTP客户端，共享其连接池和拦截器
+    pub fn http_client(mut self, client: HttpClient) -> Self {
+        self.http = Some(client);
+        self
+    }
+
+    /// Default timeout for every method
+    /// 每个方法的默认超时
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Default retry policy for every method
+    /// 每个方法的默认重试策略
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Wrap every method in the named circuit breaker
+    /// 用命名断路器包装每个方法
+    pub fn circuit_breaker(mut self, name: impl Into<String>) -> Self {
+        self.circuit_breaker = Some(name.into());
+        self
+    }
+
+    /// Look circuit breakers up in `registry` instead of the shared one
+    /// 在 `registry` 而不是共享注册表中查找断路器
+    ///
+    /// Clients using the same registry and breaker name share its state.
+    /// 使用相同注册表和断路器名称的客户端共享其状态。
+    pub fn circuit_breakers(mut self, registry: Arc<CircuitBreakerRegistry>) -> Self {
+        self.breakers = Some(registry);
+        self
+    }
+
+    /// Turn error responses into errors with `decoder`
+    /// 使用 `decoder` 将错误响应转换为错误
+    pub fn error_decoder(mut self, decoder: impl ErrorDecoder) -> Self {
+        self.error_decoder = Arc::new(decoder);
+        self
+    }
+
+    /// Build the client
+    /// 构建客户端
+    pub fn build(self) -> FeignResult<FeignClient> {
+        let target = match (self.url, self.discovery) {
+            (Some(url), _) => Target::Url(url),
+            (None, Some(discovery)) => Target::Discovery {
+                discovery,
+                balancer: self.balancer,
+            },
+            (None, None) => {
+                return Err(FeignError::Config(format!(
+                    "Feign client '{}' needs a url or a service discovery",
+                    self.name
+                )));
+            },
+        };
+        let http = match self.http {
+            Some(http) => http,
+            None => HttpClient::builder().build()?,
+        };
+
+        Ok(FeignClient {
+            inner: Arc::new(FeignInner {
+                name: self.name,
+                target,
+                path: self.path,
+                http,
+                timeout: self.timeout,
+                retry: self.retry,
+                circuit_breaker: self.circuit_breaker,
+                breakers: self.breakers.unwrap_or_else(shared_breakers),
+                error_decoder: self.error_decoder,
+            }),
+        })
+    }
+}
+
+impl fmt::Debug for FeignClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeignClientBuilder")
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .field("path", &self.path)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Registry shared by clients that do not configure their own
+/// 未配置自己注册表的客户端共享的注册表
+fn shared_breakers() -> Arc<CircuitBreakerRegistry> {
+    static SHARED: OnceLock<Arc<CircuitBreakerRegistry>> = OnceLock::new();
+    SHARED
+        .get_or_init(|| Arc::new(CircuitBreakerRegistry::new()))
+        .clone()
+}
+
+/// Flatten an argument into string values
+/// 将参数展开为字符串值
+fn param_values<T: Serialize + ?Sized>(value: &T) -> FeignResult<Vec<String>> {
+    let value = serde_json::to_value(value).map_err(|e| FeignError::Encode(e.to_string()))?;
+    Ok(match value {
+        Value::Array(items) => items.into_iter().filter_map(scalar).collect(),
+        value => scalar(value).into_iter().collect(),
+    })
+}
+
+fn scalar(value: Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s),
+        value => Some(value.to_string()),
+    }
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+/// 对除RFC 3986非保留字符外的所有字符进行百分号编码
+fn encode_segment(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{:02X}", byte);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_encoding() {
+        let request = FeignRequest::new(Method::GET, "Api#find", "/users/{id}/files/{name}")
+            .path_param("id", &42)
+            .path_param("name", "a b/c")
+            .query("tag", &vec!["x", "y"])
+            .query("page", &Option::<u32>::None)
+            .header("x-ids", &[1, 2]);
+
+        assert!(request.error.is_none());
+        assert_eq!(request.path, "/users/42/files/a%20b%2Fc");
+        assert_eq!(
+            request.query,
+            vec![
+                ("tag".to_string(), "x".to_string()),
+                ("tag".to_string(), "y".to_string())
+            ]
+        );
+        assert_eq!(request.headers, vec![("x-ids".to_string(), "1, 2".to_string())]);
+
+        let request = FeignRequest::new(Method::GET, "Api#find", "/users/{id}")
+            .path_param("id", &Option::<u32>::None);
+        assert!(matches!(request.error, Some(FeignError::Encode(_))));
+    }
+
+    #[test]
+    fn test_builder_requires_target() {
+        let err = FeignClient::builder("users").build().unwrap_err();
+        assert!(matches!(err, FeignError::Config(_)));
+
+        let client = FeignClient::builder("users")
+            .url("http://127.0.0.1:1")
+            .path("api/")
+            .build()
+            .unwrap();
+        assert_eq!(client.name(), "users");
+        assert_eq!(client.inner.path, "/api");
+    }
+}
@@ -26,12 +26,14 @@ pub mod circuit_breaker;
 pub mod config;
 pub mod discovery;
 pub mod gateway;
+pub mod http_client;
 pub mod load_balancer;
 
 pub use circuit_breaker::{CircuitBreaker, CircuitState};
 pub use config::{ConfigClient, ConfigServerClient, RemoteConfigSource};
 pub use discovery::{ServiceDiscovery, ServiceInstance, ServiceRegistry};
 pub use gateway::{Gateway, GatewayFilter, GatewayRoute};
+pub use http_client::{FeignClient, FeignClientBuilder, FeignError};
 pub use load_balancer::{LoadBalancer, RoundRobinLoadBalancer};
 
 /// Re-exports of commonly used types
@@ -7,8 +7,6 @@
 //! - Ribbon / Spring Cloud LoadBalancer
 //! - Client-side load balancing
 
-#![allow(async_fn_in_trait)]
-
 use crate::ServiceInstance;
 use rand::prelude::{IndexedRandom, Rng};
 use std::sync::Arc;
@@ -37,7 +35,14 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 pub trait LoadBalancer: Send + Sync {
     /// Choose an instance from the list
     /// 从列表中选择实例
-    async fn choose(&self, instances: &[ServiceInstance]) -> Option<ServiceInstance>;
+    ///
+    /// Implementations may be written as `async fn`; the returned future must be
+    /// `Send` so that balanced calls can run on spawned tasks.
+    /// 实现可以写成 `async fn`；返回的 future 必须是 `Send`，以便负载均衡的调用可以在派生任务上运行。
+    fn choose(
+        &self,
+        instances: &[ServiceInstance],
+    ) -> impl Future<Output = Option<ServiceInstance>> + Send;
 }
 
 /// Round-robin load balancer
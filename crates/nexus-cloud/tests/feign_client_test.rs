@@ -0,0 +1,300 @@
+//! Tests for #[feign_client] generated clients
+//! #[feign_client] 生成的客户端的测试
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use nexus_cloud::circuit_breaker::CircuitBreakerRegistry;
+use nexus_cloud::discovery::{InMemoryServiceRegistry, SimpleDiscoveryClient};
+use nexus_cloud::http_client::{ErrorDecoder, FeignError};
+use nexus_cloud::{FeignClient, ServiceInstance};
+use nexus_http::Response;
+use nexus_macros::feign_client;
+use nexus_runtime::task::block_on;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct NewUser<'a> {
+    name: &'a str,
+}
+
+/// Request line, lowercase headers and body of a received request
+/// 收到的请求的请求行、小写头部和body
+#[derive(Debug, Clone)]
+struct Received {
+    line: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Received {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// One-connection-per-request HTTP server answering from a script
+/// 每个请求一个连接、按脚本应答的HTTP服务器
+struct TestServer {
+    port: u16,
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+impl TestServer {
+    fn start(handler: impl Fn(&Received, usize) -> (u16, String) + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut headers = Vec::new();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    let header = header.trim_end();
+                    if header.is_empty() {
+                        break;
+                    }
+                    let (name, value) = header.split_once(':').unwrap();
+                    headers.push((name.to_ascii_lowercase(), value.trim().to_string()));
+                }
+                let len = headers
+                    .iter()
+                    .find(|(n, _)| n == "content-length")
+                    .map_or(0, |(_, v)| v.parse().unwrap());
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).unwrap();
+
+                let request = Received {
+                    line: line.trim_end().to_string(),
+                    headers,
+                    body: String::from_utf8(body).unwrap(),
+                };
+                let index = {
+                    let mut log = log.lock().unwrap();
+                    log.push(request.clone());
+                    log.len() - 1
+                };
+                let (status, body) = handler(&request, index);
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        Self { port, received }
+    }
+
+    fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+#[feign_client(name = "users", path = "/api")]
+trait UserClient {
+    /// Look a user up
+    #[feign_get("/users/{id}")]
+    async fn get_user(&self, #[feign_path] id: u64) -> Result<User, FeignError>;
+
+    #[feign_get("/users")]
+    async fn search(
+        &self,
+        #[feign_query] name: &str,
+        #[feign_query("page_size")] size: Option<u32>,
+        #[feign_header("X-Request-Id")] request_id: &str,
+    ) -> Result<Vec<User>, FeignError>;
+
+    #[feign_post("/users")]
+    #[feign_timeout(secs = 5)]
+    async fn create_user(&self, #[feign_body] user: &NewUser<'_>) -> Result<User, FeignError>;
+
+    #[feign_delete("/users/{user_id}/files/{file}")]
+    async fn delete_file(&self, user_id: u64, file: &str) -> Result<(), FeignError>;
+}
+
+#[test]
+fn test_generated_methods_build_requests() {
+    let server = TestServer::start(|request, _| match request.line.as_str() {
+        "GET /api/users/7 HTTP/1.1" => (200, r#"{"id":7,"name":"ada"}"#.to_string()),
+        line if line.starts_with("GET /api/users?") => {
+            (200, r#"[{"id":1,"name":"ada lovelace"}]"#.to_string())
+        },
+        "POST /api/users HTTP/1.1" => (201, r#"{"id":2,"name":"grace"}"#.to_string()),
+        line if line.starts_with("DELETE ") => (204, String::new()),
+        _ => (404, "no such user".to_string()),
+    });
+    let url = server.url();
+
+    block_on(async move {
+        let users = UserClientImpl::new(UserClientImpl::builder().url(url).build().unwrap());
+
+        let user = users.get_user(7).await.unwrap();
+        assert_eq!(
+            user,
+            User {
+                id: 7,
+                name: "ada".to_string()
+            }
+        );
+
+        let found = users.search("ada lovelace", None, "req-1").await.unwrap();
+        assert_eq!(found.len(), 1);
+
+        let created = users.create_user(&NewUser { name: "grace" }).await.unwrap();
+        assert_eq!(created.id, 2);
+
+        users.delete_file(3, "a b.txt").await.unwrap();
+
+        let err = users.get_user(8).await.unwrap_err();
+        assert_eq!(err.status().map(nexus_http::StatusCode::as_u16), Some(404));
+        assert!(err.to_string().contains("UserClient#get_user"));
+    });
+
+    let received = server.received();
+    assert_eq!(received[1].line, "GET /api/users?name=ada+lovelace HTTP/1.1");
+    assert_eq!(received[1].header("x-request-id"), Some("req-1"));
+    assert_eq!(received[2].body, r#"{"name":"grace"}"#);
+    assert_eq!(received[2].header("content-type"), Some("application/json"));
+    assert_eq!(received[3].line, "DELETE /api/users/3/files/a%20b.txt HTTP/1.1");
+}
+
+#[feign_client(name = "inventory")]
+#[feign_retry(max_attempts = 3, delay = 1)]
+trait InventoryClient {
+    #[feign_get("/stock/{sku}")]
+    async fn stock(&self, #[feign_path] sku: &str) -> Result<u32, FeignError>;
+}
+
+#[test]
+fn test_discovery_and_retry() {
+    let server = TestServer::start(|_, index| match index {
+        0 => (503, "warming up".to_string()),
+        _ => (200, "12".to_string()),
+    });
+    let port = server.port;
+
+    block_on(async move {
+        let registry = Arc::new(InMemoryServiceRegistry::new());
+        registry
+            .register_service(ServiceInstance::new("inventory", "inventory-1", "127.0.0.1", port))
+            .await
+            .unwrap();
+        let discovery = Arc::new(SimpleDiscoveryClient::new(registry));
+
+        let client = InventoryClientImpl::builder()
+            .discovery(discovery)
+            .build()
+            .unwrap();
+        let inventory = InventoryClientImpl::from(client);
+        assert_eq!(inventory.stock("sku-1").await.unwrap(), 12);
+
+        // Nothing is registered for this service
+        let empty = Arc::new(SimpleDiscoveryClient::new(Arc::new(InMemoryServiceRegistry::new())));
+        let client = FeignClient::builder("inventory")
+            .discovery(empty)
+            .build()
+            .unwrap();
+        let err = InventoryClientImpl::new(client)
+            .stock("sku-1")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FeignError::NoInstance(name) if name == "inventory"));
+    });
+
+    assert_eq!(server.received().len(), 2);
+}
+
+/// Application error built from Feign errors
+#[derive(Debug)]
+enum PaymentError {
+    Declined(String),
+    Unavailable(FeignError),
+}
+
+impl From<FeignError> for PaymentError {
+    fn from(e: FeignError) -> Self {
+        match e {
+            FeignError::Status { status, body, .. } if status.as_u16() == 402 => {
+                PaymentError::Declined(body)
+            },
+            e => PaymentError::Unavailable(e),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PaymentErrorDecoder;
+
+impl ErrorDecoder for PaymentErrorDecoder {
+    fn decode(&self, method_key: &str, response: &Response) -> FeignError {
+        FeignError::Status {
+            method_key: method_key.to_string(),
+            status: response.status(),
+            body: "decoded".to_string(),
+        }
+    }
+}
+
+#[feign_client(name = "payments", circuit_breaker = "payments-test")]
+#[feign_error_decoder(PaymentErrorDecoder)]
+trait PaymentClient {
+    #[feign_post("/charges")]
+    async fn charge(&self, #[feign_body] cents: u64) -> Result<String, PaymentError>;
+}
+
+#[test]
+fn test_error_decoder_and_circuit_breaker() {
+    let server = TestServer::start(|request, _| match request.body.as_str() {
+        "1" => (402, "insufficient funds".to_string()),
+        _ => (500, "boom".to_string()),
+    });
+    let url = server.url();
+
+    block_on(async move {
+        let client = PaymentClientImpl::builder()
+            .url(url)
+            .circuit_breakers(Arc::new(CircuitBreakerRegistry::new()))
+            .build()
+            .unwrap();
+        let payments = PaymentClientImpl::new(client);
+
+        let err = payments.charge(1).await.unwrap_err();
+        assert!(matches!(err, PaymentError::Declined(body) if body == "decoded"));
+
+        // Client errors do not count against the breaker; five server errors open it
+        for _ in 0..5 {
+            let err = payments.charge(2).await.unwrap_err();
+            assert!(matches!(err, PaymentError::Unavailable(FeignError::Status { .. })));
+        }
+        let err = payments.charge(2).await.unwrap_err();
+        assert!(matches!(
+            err,
+            PaymentError::Unavailable(FeignError::CircuitOpen(name)) if name == "payments-test"
+        ));
+    });
+
+    assert_eq!(server.received().len(), 6);
+}
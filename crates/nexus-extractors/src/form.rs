@@ -26,7 +26,6 @@
 //! ```
 
 use crate::{ExtractorError, ExtractorFuture, FromRequest, Request};
-use nexus_http::HttpBody;
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -101,7 +100,9 @@ where
     T: for<'de> Deserialize<'de> + Send + 'static,
 {
     fn from_request(req: &Request) -> ExtractorFuture<Self> {
-        let body_bytes = req.body().as_bytes().map(|b| b.to_vec());
+        // A streamed body is read in full once the future runs
+        // 流式body在future运行时被完整读取
+        let body = req.body().clone();
         let content_type = req.header("content-type").unwrap_or("").to_string();
 
         Box::pin(async move {
@@ -116,11 +117,11 @@ where
                 )));
             }
 
-            let body = body_bytes.ok_or_else(|| {
-                ExtractorError::Invalid("Request body is not available".to_string())
+            let body = body.collect().await.map_err(|e| {
+                ExtractorError::Invalid(format!("Request body is not available: {}", e))
             })?;
 
-            let body_str = String::from_utf8(body)
+            let body_str = String::from_utf8(body.to_vec())
                 .map_err(|_| ExtractorError::Invalid("Invalid UTF-8 in body".to_string()))?;
 
             // Parse form data
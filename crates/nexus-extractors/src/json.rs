@@ -26,7 +26,6 @@
 //! ```
 
 use crate::{ExtractorError, ExtractorFuture, FromRequest, Request};
-use nexus_http::HttpBody;
 use serde::Deserialize;
 
 /// JSON body extractor
@@ -100,7 +99,9 @@ where
     T: for<'de> Deserialize<'de> + Send + 'static,
 {
     fn from_request(req: &Request) -> ExtractorFuture<Self> {
-        let body_bytes = req.body().as_bytes().map(|b| b.to_vec());
+        // A streamed body is read in full once the future runs
+        // 流式body在future运行时被完整读取
+        let body = req.body().clone();
         let content_type = req.header("content-type").unwrap_or("").to_string();
 
         Box::pin(async move {
@@ -115,8 +116,8 @@ where
                 )));
             }
 
-            let body = body_bytes.ok_or_else(|| {
-                ExtractorError::Invalid("Request body is not available".to_string())
+            let body = body.collect().await.map_err(|e| {
+                ExtractorError::Invalid(format!("Request body is not available: {}", e))
             })?;
 
             // Parse JSON
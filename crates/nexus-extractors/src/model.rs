@@ -44,7 +44,6 @@
 
 use crate::form::{parse_form_data, url_decode};
 use crate::{ExtractorError, ExtractorFuture, FromRequest, Request};
-use nexus_http::HttpBody;
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -131,7 +130,7 @@ where
         let query_params = parse_query_params(&uri);
 
         // Extract form data from body (if present)
-        let body_bytes = req.body().as_bytes().map(|b| b.to_vec());
+        let body = req.body().clone();
         let content_type = req.header("content-type").unwrap_or("").to_string();
         let has_form_body = content_type.starts_with("application/x-www-form-urlencoded");
 
@@ -140,8 +139,8 @@ where
 
             // Merge form data if present (form data takes precedence)
             if has_form_body {
-                if let Some(body) = body_bytes {
-                    let body_str = String::from_utf8(body).map_err(|_| {
+                if let Ok(body) = body.collect().await {
+                    let body_str = String::from_utf8(body.to_vec()).map_err(|_| {
                         ExtractorError::Invalid("Invalid UTF-8 in body".to_string())
                     })?;
 
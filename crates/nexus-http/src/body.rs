@@ -8,9 +8,12 @@
 #![warn(missing_docs)]
 #![warn(unreachable_pub)]
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
 use http_body::Frame;
+use nexus_runtime::fs::File;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
 use std::task::{Context, Poll};
 
 /// HTTP Body trait
@@ -150,22 +153,427 @@ impl HttpBody for EmptyBody {
     }
 }
 
-/// Body type alias using FullBody by default
-/// 默认使用FullBody的Body类型别名
-pub type Body = FullBody;
+/// Boxed stream of body chunks
+/// 装箱的body数据块流
+type BoxChunkStream = Pin<Box<dyn Stream<Item = Result<Bytes, Error>> + Send>>;
+
+/// Size of each chunk read from a file body / 从文件body读取的每个数据块的大小
+const FILE_CHUNK: usize = 64 * 1024;
+
+/// Streaming body produced chunk by chunk
+/// 逐块产生的流式body
+///
+/// The underlying stream is shared between clones, so a streaming body can only be
+/// consumed once: whichever clone polls first receives the data.
+///
+/// 底层流在克隆之间共享，因此流式body只能被消费一次：先轮询的克隆获得数据。
+#[derive(Clone)]
+pub struct StreamBody {
+    stream: Arc<Mutex<Option<BoxChunkStream>>>,
+    /// File and length the stream reads, if built by [`StreamBody::from_file`]
+    /// 流读取的文件及长度（如果由 [`StreamBody::from_file`] 构建）
+    file: Option<(Arc<File>, u64)>,
+    size_hint: Option<u64>,
+}
+
+impl StreamBody {
+    /// Create a streaming body from a stream of chunks
+    /// 从数据块流创建流式body
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, Error>> + Send + 'static,
+    {
+        Self {
+            stream: Arc::new(Mutex::new(Some(Box::pin(stream)))),
+            file: None,
+            size_hint: None,
+        }
+    }
+
+    /// Create a body of the first `len` bytes of `file`
+    /// 创建由 `file` 前 `len` 字节组成的body
+    ///
+    /// The server sends it with `sendfile(2)` over plain TCP connections; elsewhere it
+    /// is read in chunks like any other stream. The body ends early if the file is
+    /// shorter than `len`.
+    ///
+    /// 服务器在明文TCP连接上使用 `sendfile(2)` 发送它；在其他情况下，它像其他流一样被
+    /// 分块读取。如果文件短于 `len`，body会提前结束。
+    pub fn from_file(file: File, len: u64) -> Self {
+        let file = Arc::new(file);
+        let reader = file.clone();
+        let chunks = futures::stream::unfold(0, move |pos| {
+            let file = reader.clone();
+            async move {
+                if pos >= len {
+                    return None;
+                }
+                let want = usize::try_from(len - pos)
+                    .unwrap_or(usize::MAX)
+                    .min(FILE_CHUNK);
+                let mut buf = vec![0u8; want];
+                match file.read_at(&mut buf, pos).await {
+                    // The file is shorter than `len` / 文件短于 `len`
+                    Ok(0) => None,
+                    Ok(n) => {
+                        buf.truncate(n);
+                        Some((Ok(Bytes::from(buf)), pos + n as u64))
+                    },
+                    Err(e) => {
+                        let e = Error::internal(format!("Failed to read file: {}", e));
+                        Some((Err(e), len))
+                    },
+                }
+            }
+        });
+        let mut body = Self::new(chunks).with_size_hint(len);
+        body.file = Some((file, len));
+        body
+    }
+
+    /// Set the exact length of the body if known in advance
+    /// 如果预先知道，设置body的确切长度
+    pub fn with_size_hint(mut self, len: u64) -> Self {
+        self.size_hint = Some(len);
+        self
+    }
+
+    /// Take the file of a body built by [`StreamBody::from_file`] to send it directly
+    /// 取出由 [`StreamBody::from_file`] 构建的body的文件以便直接发送
+    ///
+    /// Consumes the body like reading the stream would; `None` if it has no file or
+    /// was already consumed.
+    /// 与读取流一样消费body；没有文件或已被消费时返回 `None`。
+    pub(crate) fn take_file(&self) -> Option<(Arc<File>, u64)> {
+        let (file, len) = self.file.as_ref()?;
+        let mut guard = self.stream.lock().unwrap_or_else(PoisonError::into_inner);
+        guard.take().map(|_| (file.clone(), *len))
+    }
+
+    /// Poll the next chunk of the stream
+    /// 轮询流的下一个数据块
+    pub fn poll_chunk(&self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, Error>>> {
+        let mut guard = self.stream.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(stream) = guard.as_mut() else {
+            return Poll::Ready(None);
+        };
+        match stream.as_mut().poll_next(cx) {
+            Poll::Ready(None) => {
+                *guard = None;
+                Poll::Ready(None)
+            },
+            other => other,
+        }
+    }
+}
+
+impl std::fmt::Debug for StreamBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamBody")
+            .field("size_hint", &self.size_hint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl http_body::Body for StreamBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Error>>> {
+        self.poll_chunk(cx).map(|chunk| chunk.map(|r| r.map(Frame::data)))
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        match self.size_hint {
+            Some(len) => http_body::SizeHint::with_exact(len),
+            None => http_body::SizeHint::default(),
+        }
+    }
+}
+
+impl HttpBody for StreamBody {
+    fn as_bytes(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+/// Request or response body
+/// 请求或响应body
+///
+/// Bodies are either fully buffered in memory or streamed chunk by chunk.
+/// Extractors read buffered bodies through [`HttpBody::as_bytes`]; streaming bodies
+/// must be read with [`Body::collect`] or polled as an [`http_body::Body`].
+///
+/// body要么完全缓冲在内存中，要么逐块流式传输。
+/// 提取器通过 [`HttpBody::as_bytes`] 读取缓冲的body；流式body必须通过
+/// [`Body::collect`] 读取或作为 [`http_body::Body`] 轮询。
+#[derive(Debug, Clone)]
+pub enum Body {
+    /// Complete in-memory body
+    /// 完整的内存body
+    Full(FullBody),
+    /// Streaming body
+    /// 流式body
+    Stream(StreamBody),
+}
 
 impl Body {
     /// Create an empty body
     /// 创建空body
     pub fn empty() -> Self {
-        FullBody::new(Bytes::new())
+        Body::Full(FullBody::new(Bytes::new()))
     }
 
     /// Create a body from bytes
     /// 从字节创建body
     pub fn from_bytes(data: Bytes) -> Self {
-        FullBody::new(data)
+        Body::Full(FullBody::new(data))
+    }
+
+    /// Create a streaming body from a stream of chunks
+    /// 从数据块流创建流式body
+    pub fn from_stream<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, Error>> + Send + 'static,
+    {
+        Body::Stream(StreamBody::new(stream))
+    }
+
+    /// Create a streaming body from an infallible stream of chunks
+    /// 从不会失败的数据块流创建流式body
+    ///
+    /// The server writes such bodies with chunked transfer-encoding unless the
+    /// response carries a `Content-Length` header.
+    ///
+    /// 除非响应带有 `Content-Length` 头，否则服务器使用分块传输编码写出此类body。
+    pub fn wrap_stream<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Bytes> + Send + 'static,
+    {
+        Body::from_stream(stream.map(Ok))
+    }
+
+    /// Create a streaming body of the first `len` bytes of `file`
+    /// 创建由 `file` 前 `len` 字节组成的流式body
+    ///
+    /// See [`StreamBody::from_file`] / 参见 [`StreamBody::from_file`]
+    pub fn from_file(file: File, len: u64) -> Self {
+        Body::Stream(StreamBody::from_file(file, len))
+    }
+
+    /// Check whether this is a streaming body
+    /// 检查是否为流式body
+    pub fn is_stream(&self) -> bool {
+        matches!(self, Body::Stream(_))
+    }
+
+    /// Get the buffered body data (empty for streaming bodies)
+    /// 获取缓冲的body数据（流式body为空）
+    pub fn data(&self) -> &Bytes {
+        static EMPTY: Bytes = Bytes::new();
+        match self {
+            Body::Full(full) => full.data(),
+            Body::Stream(_) => &EMPTY,
+        }
+    }
+
+    /// Read the whole body into memory
+    /// 将整个body读入内存
+    pub async fn collect(self) -> Result<Bytes, Error> {
+        match self {
+            Body::Full(full) => Ok(full.data),
+            Body::Stream(stream) => {
+                let mut buf = BytesMut::new();
+                while let Some(chunk) = std::future::poll_fn(|cx| stream.poll_chunk(cx)).await {
+                    buf.extend_from_slice(&chunk?);
+                }
+                Ok(buf.freeze())
+            },
+        }
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl http_body::Body for Body {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Error>>> {
+        match self.get_mut() {
+            Body::Full(full) => Pin::new(full).poll_frame(cx),
+            Body::Stream(stream) => Pin::new(stream).poll_frame(cx),
+        }
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        match self {
+            Body::Full(full) => full.size_hint(),
+            Body::Stream(stream) => stream.size_hint(),
+        }
+    }
+}
+
+impl HttpBody for Body {
+    fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Body::Full(full) => full.as_bytes(),
+            Body::Stream(stream) => stream.as_bytes(),
+        }
+    }
+}
+
+impl From<FullBody> for Body {
+    fn from(body: FullBody) -> Self {
+        Body::Full(body)
+    }
+}
+
+impl From<StreamBody> for Body {
+    fn from(body: StreamBody) -> Self {
+        Body::Stream(body)
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(data: Bytes) -> Self {
+        Body::Full(FullBody::from(data))
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(data: Vec<u8>) -> Self {
+        Body::Full(FullBody::from(data))
+    }
+}
+
+impl From<&'static [u8]> for Body {
+    fn from(data: &'static [u8]) -> Self {
+        Body::Full(FullBody::from(data))
+    }
+}
+
+impl From<String> for Body {
+    fn from(data: String) -> Self {
+        Body::Full(FullBody::from(data))
+    }
+}
+
+impl From<&'static str> for Body {
+    fn from(data: &'static str) -> Self {
+        Body::Full(FullBody::from(data))
+    }
+}
+
+/// Trailer fields received after a chunked body
+/// 分块body之后收到的尾部字段
+///
+/// Stored in the request extensions by the server. For a body that is still
+/// being streamed the fields are filled in once the body has been read to the
+/// end, so clones share one slot.
+///
+/// 由服务器存储在请求扩展中。对于仍在流式传输的body，字段会在body读取完毕后
+/// 填入，因此克隆共享同一个槽位。
+#[derive(Debug, Clone, Default)]
+pub struct Trailers(Arc<OnceLock<http::HeaderMap>>);
+
+impl Trailers {
+    /// Create trailers that have already been received
+    /// 创建已收到的尾部字段
+    pub fn new(fields: http::HeaderMap) -> Self {
+        Self(Arc::new(OnceLock::from(fields)))
+    }
+
+    /// Create a slot for trailers that arrive at the end of a streamed body
+    /// 为在流式body结束时到达的尾部字段创建槽位
+    pub(crate) fn pending() -> Self {
+        Self::default()
+    }
+
+    /// Fill in the trailers of a streamed body; later calls are ignored
+    /// 填入流式body的尾部字段；之后的调用将被忽略
+    pub(crate) fn set(&self, fields: http::HeaderMap) {
+        let _ = self.0.set(fields);
+    }
+
+    /// Get the trailer fields, or `None` until the body has been read to the end
+    /// 获取尾部字段，body读取完毕之前返回 `None`
+    pub fn get(&self) -> Option<&http::HeaderMap> {
+        self.0.get()
     }
 }
 
 use super::error::Error;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_body_as_bytes() {
+        let body = Body::from("hello");
+        assert!(!body.is_stream());
+        assert_eq!(body.as_bytes(), Some(&b"hello"[..]));
+        assert_eq!(body.data(), &Bytes::from_static(b"hello"));
+    }
+
+    #[test]
+    fn test_stream_body_collect() {
+        let chunks = vec![Ok(Bytes::from_static(b"hel")), Ok(Bytes::from_static(b"lo"))];
+        let body = Body::from_stream(futures::stream::iter(chunks));
+        assert!(body.is_stream());
+        assert!(body.as_bytes().is_none());
+
+        let data = futures::executor::block_on(body.collect()).unwrap();
+        assert_eq!(data, Bytes::from_static(b"hello"));
+    }
+
+    #[test]
+    fn test_file_body() {
+        let path = std::env::temp_dir().join(format!("nexus-body-{}", std::process::id()));
+        let mut contents: Vec<u8> = (0..FILE_CHUNK as u32 + 10)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(&path, &contents).unwrap();
+        let open = || futures::executor::block_on(File::open(&path)).unwrap();
+
+        contents.truncate(contents.len() - 4);
+        let body = Body::from_file(open(), contents.len() as u64);
+        let data = futures::executor::block_on(body.clone().collect()).unwrap();
+        assert_eq!(data, contents);
+        let Body::Stream(stream) = body else {
+            unreachable!()
+        };
+        assert!(stream.take_file().is_none());
+
+        let Body::Stream(stream) = Body::from_file(open(), 100) else {
+            unreachable!()
+        };
+        assert_eq!(stream.take_file().map(|(_, len)| len), Some(100));
+        let rest = futures::executor::block_on(Body::Stream(stream).collect()).unwrap();
+        assert!(rest.is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_stream_body_shared_between_clones() {
+        let chunks = vec![Ok(Bytes::from_static(b"once"))];
+        let body = Body::from_stream(futures::stream::iter(chunks));
+        let clone = body.clone();
+
+        let first = futures::executor::block_on(body.collect()).unwrap();
+        let second = futures::executor::block_on(clone.collect()).unwrap();
+        assert_eq!(first, Bytes::from_static(b"once"));
+        assert!(second.is_empty());
+    }
+}
@@ -0,0 +1,1465 @@
+//! HTTP/2 server connection driver
+//! HTTP/2 服务器连接驱动
+//!
+//! One task owns the socket: it decodes frames, enforces flow control and writes
+//! everything that goes out. Each request runs on its own task and reports its
+//! response back over a channel, so slow handlers never block other streams.
+//! Streaming bodies are pulled only while the stream has room in its send queue,
+//! which propagates the client's flow-control windows back to the producer.
+//!
+//! 一个任务拥有套接字：它解码帧、执行流控制并写出所有数据。每个请求在自己的任务上
+//! 运行，并通过通道回报其响应，因此慢速处理器不会阻塞其他流。流式body只有在流的发送
+//! 队列有空间时才会被拉取，从而将客户端的流控制窗口反向传播给生产者。
+
+use std::collections::{BTreeMap, VecDeque};
+use std::future::poll_fn;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+use bytes::{Buf, Bytes, BytesMut};
+use futures::StreamExt;
+use futures::channel::mpsc::{UnboundedSender, unbounded};
+use http::header::{HOST, HeaderName, HeaderValue};
+use nexus_runtime::select::{SelectTwoOutput, select_two};
+use nexus_runtime::task::spawn;
+use nexus_runtime::time::{Duration, Instant, timeout, timeout_at};
+
+use super::flow::{DEFAULT_WINDOW_SIZE, FlowWindow, MAX_WINDOW_SIZE};
+use super::frame::{self, Frame, PREFACE};
+use super::hpack::{self, HeaderField};
+use super::{ErrorCode, Http2Config, Http2Error, SettingsParameter, StreamId};
+use crate::server::{ServerState, ServerStream, error_response};
+use crate::{Body, Error, HttpService, Request, Trailers};
+
+/// Size of the socket read buffer
+/// 套接字读缓冲区的大小
+const READ_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Response bytes a stream may queue before its producer is paused
+/// 流在其生产者被暂停之前可以排队的响应字节数
+const MAX_QUEUED_PER_STREAM: usize = 64 * 1024;
+
+/// Connection-specific headers, which are not allowed in HTTP/2
+/// 连接特定的头，在HTTP/2中不允许
+const CONNECTION_HEADERS: [&str; 5] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Settings the server connection runs with
+/// 服务器连接运行时使用的设置
+#[derive(Debug, Clone)]
+pub(crate) struct ServeOptions {
+    /// HTTP/2 protocol settings / HTTP/2协议设置
+    pub(crate) config: Http2Config,
+    /// Time allowed for a handler / 处理器允许的时间
+    pub(crate) request_timeout: Duration,
+    /// Idle time before the connection is closed / 连接关闭前的空闲时间
+    pub(crate) keep_alive_timeout: Duration,
+    /// Largest request body accepted / 接受的最大请求body
+    pub(crate) max_body_size: usize,
+}
+
+/// An `Upgrade: h2c` request that becomes stream 1
+/// 成为流1的 `Upgrade: h2c` 请求
+pub(crate) struct Upgrade {
+    /// The upgraded request / 被升级的请求
+    pub(crate) request: Request,
+    /// Decoded `HTTP2-Settings` payload / 解码后的 `HTTP2-Settings` 负载
+    pub(crate) settings: Vec<u8>,
+}
+
+/// Serve an HTTP/2 connection until it is closed
+/// 服务HTTP/2连接直到其关闭
+///
+/// `buffered` holds bytes already read from the socket; with prior knowledge it
+/// starts with the connection preface.
+/// `buffered` 保存已经从套接字读取的字节；在先验知识模式下它以连接前言开头。
+pub(crate) async fn serve_connection<S>(
+    mut stream: ServerStream,
+    peer_addr: SocketAddr,
+    service: Arc<S>,
+    options: ServeOptions,
+    state: Arc<ServerState>,
+    buffered: &[u8],
+    upgrade: Option<Upgrade>,
+) where
+    S: HttpService + 'static,
+{
+    let (events, mut receiver) = unbounded();
+    let request_timeout = options.request_timeout;
+    let keep_alive_timeout = options.keep_alive_timeout;
+    let mut conn = Connection::new(options, events);
+    conn.read_buf.extend_from_slice(buffered);
+    conn.start();
+
+    if let Some(upgrade) = upgrade {
+        let settings = frame::decode_settings(&upgrade.settings)
+            .and_then(|params| conn.apply_settings(&params));
+        if let Err(e) = settings {
+            conn.fail(&e);
+        }
+        conn.open_upgraded(upgrade.request);
+    }
+
+    tracing::debug!("HTTP/2 connection from {}", peer_addr);
+
+    let peer_certificate = stream.peer_certificate();
+    let mut read_buf = vec![0u8; READ_BUFFER_SIZE];
+    let mut last_activity = Instant::now();
+
+    loop {
+        if let Err(e) = conn.process_input() {
+            tracing::debug!("HTTP/2 connection error from {}: {}", peer_addr, e);
+            conn.fail(&e);
+        }
+
+        for (stream_id, mut request, shared) in conn.ready.drain(..) {
+            if let Some(certificate) = &peer_certificate {
+                request.extensions_mut().insert(certificate.clone());
+            }
+            let service = service.clone();
+            let events = conn.events.clone();
+            spawn(respond(stream_id, request, service, request_timeout, shared, events));
+        }
+
+        // Stop taking new streams on shutdown and let the open ones finish
+        // 关闭时停止接受新流，并让已打开的流完成
+        if state.is_shutting_down() {
+            conn.go_away(ErrorCode::NoError);
+        }
+
+        conn.flush_data();
+        if !conn.out.is_empty() {
+            let out = conn.out.split().freeze();
+            let write = Box::pin(stream.write_all(&out));
+            let forced = state.wait_until(ServerState::is_force_closed);
+            match select_two(write, forced).await {
+                SelectTwoOutput::First(Ok(())) => {},
+                SelectTwoOutput::First(Err(e)) => {
+                    tracing::error!("Write error to {}: {}", peer_addr, e);
+                    break;
+                },
+                SelectTwoOutput::Second(()) => break,
+            }
+        }
+
+        if conn.closing || conn.is_finished() || state.is_force_closed() {
+            break;
+        }
+
+        let read = timeout_at(last_activity + keep_alive_timeout, stream.read(&mut read_buf));
+        let goaway_sent = conn.goaway_sent;
+        let interrupted = state
+            .wait_until(move |s| s.is_force_closed() || (s.is_shutting_down() && !goaway_sent));
+
+        match select_two(read, select_two(receiver.next(), interrupted)).await {
+            SelectTwoOutput::First(Ok(Ok(0))) => {
+                tracing::debug!("HTTP/2 connection closed by {}", peer_addr);
+                break;
+            },
+            SelectTwoOutput::First(Ok(Ok(n))) => {
+                conn.read_buf.extend_from_slice(&read_buf[..n]);
+                last_activity = Instant::now();
+            },
+            SelectTwoOutput::First(Ok(Err(e))) => {
+                tracing::error!("Read error from {}: {}", peer_addr, e);
+                break;
+            },
+            SelectTwoOutput::First(Err(_elapsed)) => {
+                if conn.streams.is_empty() {
+                    tracing::debug!("Keep-alive timeout for {}", peer_addr);
+                    conn.go_away(ErrorCode::NoError);
+                    conn.closing = true;
+                }
+                last_activity = Instant::now();
+            },
+            SelectTwoOutput::Second(SelectTwoOutput::First(Some(event))) => {
+                conn.on_event(event);
+                while let Ok(event) = receiver.try_recv() {
+                    conn.on_event(event);
+                }
+                last_activity = Instant::now();
+            },
+            SelectTwoOutput::Second(SelectTwoOutput::First(None)) => break,
+            SelectTwoOutput::Second(SelectTwoOutput::Second(())) => {},
+        }
+    }
+
+    // Stop the producers of streams that never finished
+    // 停止未完成的流的生产者
+    for stream in conn.streams.values() {
+        stream.shared.cancel();
+    }
+    stream.close().await;
+}
+
+/// Run a request and send its response back to the connection
+/// 执行请求并将其响应发送回连接
+async fn respond<S>(
+    stream_id: StreamId,
+    request: Request,
+    service: Arc<S>,
+    request_timeout: Duration,
+    shared: Arc<StreamShared>,
+    events: UnboundedSender<StreamEvent>,
+) where
+    S: HttpService + 'static,
+{
+    let response = match timeout(request_timeout, Box::pin(service.call(request))).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => {
+            tracing::error!("Handler error on HTTP/2 stream {}: {}", stream_id.get(), e);
+            error_response(&e)
+        },
+        Err(_elapsed) => error_response(&Error::Timeout("Request handling timed out".to_string())),
+    };
+
+    let status = response.status().as_u16();
+    let mut headers: Vec<(String, String)> = response
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.clone()))
+        .filter(|(name, _)| !CONNECTION_HEADERS.contains(&name.as_str()))
+        .collect();
+    let has_content_length = headers.iter().any(|(name, _)| name == "content-length");
+
+    let send = |event| events.unbounded_send(event).is_ok();
+
+    match response.into_body() {
+        Body::Full(full) => {
+            let data = full.data().clone();
+            if !has_content_length && (!data.is_empty() || status != 204) {
+                headers.push(("content-length".to_string(), data.len().to_string()));
+            }
+            let end_stream = data.is_empty();
+            if !send(StreamEvent::Head {
+                stream_id,
+                status,
+                headers,
+                end_stream,
+            }) || end_stream
+            {
+                return;
+            }
+            shared.queued.fetch_add(data.len(), Ordering::AcqRel);
+            send(StreamEvent::Data {
+                stream_id,
+                data,
+                end_stream: true,
+            });
+        },
+        Body::Stream(body) => {
+            if !send(StreamEvent::Head {
+                stream_id,
+                status,
+                headers,
+                end_stream: false,
+            }) {
+                return;
+            }
+            loop {
+                if !poll_fn(|cx| shared.poll_capacity(cx)).await {
+                    return;
+                }
+                let event = match poll_fn(|cx| body.poll_chunk(cx)).await {
+                    Some(Ok(chunk)) if chunk.is_empty() => continue,
+                    Some(Ok(chunk)) => {
+                        shared.queued.fetch_add(chunk.len(), Ordering::AcqRel);
+                        StreamEvent::Data {
+                            stream_id,
+                            data: chunk,
+                            end_stream: false,
+                        }
+                    },
+                    Some(Err(e)) => {
+                        tracing::error!("Error streaming HTTP/2 stream {}: {}", stream_id.get(), e);
+                        send(StreamEvent::Reset {
+                            stream_id,
+                            error_code: ErrorCode::InternalError,
+                        });
+                        return;
+                    },
+                    None => {
+                        send(StreamEvent::Data {
+                            stream_id,
+                            data: Bytes::new(),
+                            end_stream: true,
+                        });
+                        return;
+                    },
+                };
+                if !send(event) {
+                    return;
+                }
+            }
+        },
+    }
+}
+
+/// Message from a request task to the connection
+/// 从请求任务发送到连接的消息
+#[derive(Debug)]
+enum StreamEvent {
+    /// Response head / 响应头部
+    Head {
+        stream_id: StreamId,
+        status: u16,
+        headers: Vec<(String, String)>,
+        end_stream: bool,
+    },
+    /// Response body data / 响应body数据
+    Data {
+        stream_id: StreamId,
+        data: Bytes,
+        end_stream: bool,
+    },
+    /// Abort the stream / 中止流
+    Reset {
+        stream_id: StreamId,
+        error_code: ErrorCode,
+    },
+}
+
+/// State shared between a stream and its request task
+/// 流与其请求任务之间共享的状态
+#[derive(Debug, Default)]
+struct StreamShared {
+    /// Response bytes handed to the connection but not yet written
+    /// 已交给连接但尚未写出的响应字节
+    queued: AtomicUsize,
+    /// Set when the stream is reset or the connection is gone
+    /// 当流被重置或连接断开时设置
+    cancelled: AtomicBool,
+    /// Task waiting for queue space / 等待队列空间的任务
+    waker: Mutex<Option<Waker>>,
+}
+
+impl StreamShared {
+    /// Ready with `true` once there is room for more data, `false` if cancelled
+    /// 有空间容纳更多数据时返回 `true`，被取消时返回 `false`
+    fn poll_capacity(&self, cx: &mut Context<'_>) -> Poll<bool> {
+        let check = || {
+            if self.cancelled.load(Ordering::Acquire) {
+                Some(false)
+            } else if self.queued.load(Ordering::Acquire) < MAX_QUEUED_PER_STREAM {
+                Some(true)
+            } else {
+                None
+            }
+        };
+        if let Some(ready) = check() {
+            return Poll::Ready(ready);
+        }
+        *self.waker.lock().unwrap_or_else(PoisonError::into_inner) = Some(cx.waker().clone());
+        match check() {
+            Some(ready) => Poll::Ready(ready),
+            None => Poll::Pending,
+        }
+    }
+
+    fn release(&self, len: usize) {
+        self.queued.fetch_sub(len, Ordering::AcqRel);
+        self.wake();
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        self.wake();
+    }
+
+    fn wake(&self) {
+        let waker = self
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A request that is still being received
+/// 仍在接收中的请求
+struct PendingRequest {
+    head: http::Request<()>,
+    body: BytesMut,
+    trailers: Option<http::HeaderMap>,
+}
+
+/// Server-side state of one stream
+/// 单个流的服务器端状态
+struct H2Stream {
+    shared: Arc<StreamShared>,
+    send_window: FlowWindow,
+    recv_window: FlowWindow,
+    /// Request being received, taken once dispatched
+    /// 正在接收的请求，分派后被取走
+    request: Option<PendingRequest>,
+    /// The client finished sending / 客户端已完成发送
+    remote_closed: bool,
+    /// The response finished / 响应已完成
+    local_closed: bool,
+    /// Response data waiting for window / 等待窗口的响应数据
+    pending: VecDeque<Bytes>,
+    /// The response ends after `pending` / 响应在 `pending` 之后结束
+    pending_end: bool,
+}
+
+impl H2Stream {
+    fn new(send_window: u32, recv_window: u32) -> Self {
+        Self {
+            shared: Arc::new(StreamShared::default()),
+            send_window: FlowWindow::new(send_window),
+            recv_window: FlowWindow::new(recv_window),
+            request: None,
+            remote_closed: false,
+            local_closed: false,
+            pending: VecDeque::new(),
+            pending_end: false,
+        }
+    }
+}
+
+/// Protocol state of a server connection
+/// 服务器连接的协议状态
+struct Connection {
+    options: ServeOptions,
+    decoder: hpack::Decoder,
+    encoder: hpack::Encoder,
+    streams: BTreeMap<u32, H2Stream>,
+    /// Connection-level send window / 连接级发送窗口
+    send_window: FlowWindow,
+    /// Connection-level receive window / 连接级接收窗口
+    recv_window: FlowWindow,
+    /// Peer's SETTINGS_INITIAL_WINDOW_SIZE / 对端的 SETTINGS_INITIAL_WINDOW_SIZE
+    peer_initial_window: u32,
+    /// Peer's SETTINGS_MAX_FRAME_SIZE / 对端的 SETTINGS_MAX_FRAME_SIZE
+    peer_max_frame_size: u32,
+    /// Our SETTINGS were acknowledged / 我们的SETTINGS已被确认
+    settings_acked: bool,
+    /// The first frame (SETTINGS) was received / 已收到第一个帧（SETTINGS）
+    settings_received: bool,
+    /// Highest stream the client opened / 客户端打开的最大流
+    last_stream_id: u32,
+    /// Header block being continued: stream, END_STREAM, fragments so far
+    /// 正在继续的头块：流、END_STREAM、目前的片段
+    continuation: Option<(StreamId, bool, BytesMut)>,
+    goaway_sent: bool,
+    goaway_received: bool,
+    /// Close once the output is flushed / 输出刷新后关闭
+    closing: bool,
+    preface_received: bool,
+    read_buf: BytesMut,
+    out: BytesMut,
+    /// Requests ready to be handed to the service / 准备交给服务的请求
+    ready: Vec<(StreamId, Request, Arc<StreamShared>)>,
+    events: UnboundedSender<StreamEvent>,
+}
+
+impl Connection {
+    fn new(options: ServeOptions, events: UnboundedSender<StreamEvent>) -> Self {
+        let header_table_size = options.config.header_table_size as usize;
+        Self {
+            options,
+            decoder: hpack::Decoder::new(header_table_size),
+            encoder: hpack::Encoder::new(),
+            streams: BTreeMap::new(),
+            send_window: FlowWindow::new(DEFAULT_WINDOW_SIZE),
+            recv_window: FlowWindow::new(DEFAULT_WINDOW_SIZE),
+            peer_initial_window: DEFAULT_WINDOW_SIZE,
+            peer_max_frame_size: frame::DEFAULT_MAX_FRAME_SIZE,
+            settings_acked: false,
+            settings_received: false,
+            last_stream_id: 0,
+            continuation: None,
+            goaway_sent: false,
+            goaway_received: false,
+            closing: false,
+            preface_received: false,
+            read_buf: BytesMut::new(),
+            out: BytesMut::new(),
+            ready: Vec::new(),
+            events,
+        }
+    }
+
+    /// Queue the server preface and open the connection window
+    /// 排队服务器前言并打开连接窗口
+    fn start(&mut self) {
+        let config = &self.options.config;
+        frame::settings(&[
+            (SettingsParameter::HeaderTableSize, config.header_table_size),
+            (SettingsParameter::EnablePush, 0),
+            (SettingsParameter::MaxConcurrentStreams, config.max_concurrent_streams),
+            (SettingsParameter::InitialWindowSize, self.local_window()),
+            (SettingsParameter::MaxFrameSize, config.max_frame_size),
+            (SettingsParameter::MaxHeaderListSize, config.max_header_list_size),
+        ])
+        .encode(&mut self.out);
+
+        let target = self.connection_window();
+        if target > DEFAULT_WINDOW_SIZE {
+            Frame::WindowUpdate {
+                stream_id: StreamId::CONNECTION,
+                increment: target - DEFAULT_WINDOW_SIZE,
+            }
+            .encode(&mut self.out);
+            self.recv_window = FlowWindow::new(target);
+        }
+    }
+
+    /// Receive window advertised for streams
+    /// 为流通告的接收窗口
+    fn local_window(&self) -> u32 {
+        self.options.config.initial_window_size.min(MAX_WINDOW_SIZE)
+    }
+
+    /// Until our SETTINGS are acknowledged the client may still use the default window
+    /// 在我们的SETTINGS被确认之前，客户端可能仍使用默认窗口
+    fn stream_recv_window(&self) -> u32 {
+        if self.settings_acked {
+            self.local_window()
+        } else {
+            self.local_window().max(DEFAULT_WINDOW_SIZE)
+        }
+    }
+
+    fn connection_window(&self) -> u32 {
+        self.local_window().max(DEFAULT_WINDOW_SIZE)
+    }
+
+    /// Treat stream 1 as carrying the upgraded HTTP/1.1 request
+    /// 将流1视为携带被升级的HTTP/1.1请求
+    fn open_upgraded(&mut self, mut request: Request) {
+        *request.inner_mut().version_mut() = http::Version::HTTP_2;
+        let mut stream = H2Stream::new(self.peer_initial_window, self.stream_recv_window());
+        stream.remote_closed = true;
+        self.ready
+            .push((StreamId::new(1), request, stream.shared.clone()));
+        self.streams.insert(1, stream);
+        self.last_stream_id = 1;
+    }
+
+    /// Report a connection error and close after flushing
+    /// 报告连接错误并在刷新后关闭
+    fn fail(&mut self, error: &Http2Error) {
+        self.go_away(error.error_code());
+        self.closing = true;
+    }
+
+    fn go_away(&mut self, error_code: ErrorCode) {
+        if self.goaway_sent {
+            return;
+        }
+        self.goaway_sent = true;
+        Frame::GoAway {
+            last_stream_id: StreamId::new(self.last_stream_id),
+            error_code,
+            debug_data: Bytes::new(),
+        }
+        .encode(&mut self.out);
+    }
+
+    /// All work is done once either side sent GOAWAY and no stream is open
+    /// 一旦任一方发送了GOAWAY且没有打开的流，所有工作即完成
+    fn is_finished(&self) -> bool {
+        (self.goaway_sent || self.goaway_received) && self.streams.is_empty()
+    }
+
+    /// Decode and handle every complete frame in the read buffer
+    /// 解码并处理读缓冲区中的每个完整帧
+    fn process_input(&mut self) -> Result<(), Http2Error> {
+        if self.closing {
+            return Ok(());
+        }
+        if !self.preface_received {
+            let n = self.read_buf.len().min(PREFACE.len());
+            if self.read_buf[..n] != PREFACE[..n] {
+                return Err(Http2Error::ProtocolError("invalid connection preface".to_string()));
+            }
+            if n < PREFACE.len() {
+                return Ok(());
+            }
+            self.read_buf.advance(PREFACE.len());
+            self.preface_received = true;
+        }
+
+        while let Some((frame, used)) =
+            Frame::decode(&self.read_buf, self.options.config.max_frame_size)?
+        {
+            self.read_buf.advance(used);
+            self.handle_frame(frame)?;
+        }
+        Ok(())
+    }
+
+    fn handle_frame(&mut self, frame: Frame) -> Result<(), Http2Error> {
+        if !self.settings_received {
+            if !matches!(frame, Frame::Settings { ack: false, .. }) {
+                return Err(Http2Error::ProtocolError("expected SETTINGS first".to_string()));
+            }
+            self.settings_received = true;
+        }
+        if let Some((expected, ..)) = &self.continuation
+            && !matches!(&frame, Frame::Continuation { stream_id, .. } if stream_id == expected)
+        {
+            return Err(Http2Error::ProtocolError("expected CONTINUATION".to_string()));
+        }
+
+        match frame {
+            Frame::Settings { ack: true, .. } => self.settings_acked = true,
+            Frame::Settings { ack: false, params } => {
+                self.apply_settings(&params)?;
+                Frame::Settings {
+                    ack: true,
+                    params: Vec::new(),
+                }
+                .encode(&mut self.out);
+            },
+            Frame::Ping {
+                ack: false,
+                payload,
+            } => {
+                Frame::Ping { ack: true, payload }.encode(&mut self.out);
+            },
+            Frame::Headers {
+                stream_id,
+                block,
+                end_stream,
+                end_headers,
+            } => {
+                if end_headers {
+                    self.on_header_block(stream_id, &block, end_stream)?;
+                } else {
+                    self.continue_headers(stream_id, end_stream, BytesMut::from(&block[..]))?;
+                }
+            },
+            Frame::Continuation {
+                stream_id,
+                block,
+                end_headers,
+            } => {
+                let Some((_, end_stream, mut fragments)) = self.continuation.take() else {
+                    return Err(Http2Error::ProtocolError("unexpected CONTINUATION".to_string()));
+                };
+                fragments.extend_from_slice(&block);
+                if end_headers {
+                    self.on_header_block(stream_id, &fragments, end_stream)?;
+                } else {
+                    self.continue_headers(stream_id, end_stream, fragments)?;
+                }
+            },
+            Frame::Data {
+                stream_id,
+                data,
+                end_stream,
+                flow_len,
+            } => self.on_data(stream_id, &data, end_stream, flow_len)?,
+            Frame::WindowUpdate {
+                stream_id,
+                increment,
+            } => self.on_window_update(stream_id, increment)?,
+            Frame::RstStream { stream_id, .. } => {
+                if stream_id.get() > self.last_stream_id {
+                    return Err(Http2Error::ProtocolError("RST_STREAM on idle stream".to_string()));
+                }
+                self.remove_stream(stream_id.get());
+            },
+            Frame::GoAway { .. } => self.goaway_received = true,
+            Frame::PushPromise { .. } => {
+                return Err(Http2Error::ProtocolError("client sent PUSH_PROMISE".to_string()));
+            },
+            Frame::Ping { ack: true, .. } | Frame::Priority { .. } | Frame::Unknown { .. } => {},
+        }
+        Ok(())
+    }
+
+    /// Buffer a header block fragment, bounding the total size
+    /// 缓冲头块片段，限制其总大小
+    fn continue_headers(
+        &mut self,
+        stream_id: StreamId,
+        end_stream: bool,
+        fragments: BytesMut,
+    ) -> Result<(), Http2Error> {
+        let limit = (self.options.config.max_header_list_size as usize).saturating_mul(2);
+        if fragments.len() > limit.max(frame::DEFAULT_MAX_FRAME_SIZE as usize) {
+            return Err(Http2Error::StreamError {
+                stream_id,
+                error_code: ErrorCode::EnhanceYourCalm,
+                message: "header block too large".to_string(),
+            });
+        }
+        self.continuation = Some((stream_id, end_stream, fragments));
+        Ok(())
+    }
+
+    fn apply_settings(&mut self, params: &[(u16, u32)]) -> Result<(), Http2Error> {
+        for &(id, value) in params {
+            match SettingsParameter::from_u16(id) {
+                Some(SettingsParameter::EnablePush) if value > 1 => {
+                    return Err(Http2Error::SettingsError("invalid ENABLE_PUSH".to_string()));
+                },
+                Some(SettingsParameter::InitialWindowSize) => {
+                    if value > MAX_WINDOW_SIZE {
+                        return Err(Http2Error::FlowControlError(
+                            "INITIAL_WINDOW_SIZE too large".to_string(),
+                        ));
+                    }
+                    let delta = i64::from(value) - i64::from(self.peer_initial_window);
+                    for stream in self.streams.values_mut() {
+                        stream.send_window.adjust(delta)?;
+                    }
+                    self.peer_initial_window = value;
+                },
+                Some(SettingsParameter::MaxFrameSize) => {
+                    if !(frame::DEFAULT_MAX_FRAME_SIZE..=16_777_215).contains(&value) {
+                        return Err(Http2Error::SettingsError(
+                            "invalid MAX_FRAME_SIZE".to_string(),
+                        ));
+                    }
+                    self.peer_max_frame_size = value;
+                },
+                // The encoder does not use the dynamic table and push is never used
+                // 编码器不使用动态表，也从不使用推送
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    fn on_header_block(
+        &mut self,
+        stream_id: StreamId,
+        block: &[u8],
+        end_stream: bool,
+    ) -> Result<(), Http2Error> {
+        // Always decode to keep the HPACK state in sync
+        // 始终解码以保持HPACK状态同步
+        let fields = self.decoder.decode(block)?;
+        let id = stream_id.get();
+
+        if let Some(stream) = self.streams.get_mut(&id) {
+            // Trailers end the request body
+            // 尾部字段结束请求body
+            let trailers = match stream.request.as_mut() {
+                Some(pending) if !stream.remote_closed && end_stream => pending,
+                _ => {
+                    self.reset(stream_id, ErrorCode::ProtocolError);
+                    return Ok(());
+                },
+            };
+            match header_map(&fields) {
+                Ok(map) => {
+                    trailers.trailers = Some(map);
+                    stream.remote_closed = true;
+                    self.dispatch(id);
+                },
+                Err(_) => self.reset(stream_id, ErrorCode::ProtocolError),
+            }
+            return Ok(());
+        }
+
+        if id.is_multiple_of(2) || id <= self.last_stream_id {
+            return Err(Http2Error::ProtocolError(format!("unexpected HEADERS on stream {}", id)));
+        }
+        if self.goaway_sent {
+            // Streams opened after GOAWAY are ignored
+            // GOAWAY之后打开的流被忽略
+            return Ok(());
+        }
+        self.last_stream_id = id;
+
+        if self.streams.len() >= self.options.config.max_concurrent_streams as usize {
+            self.send_reset(stream_id, ErrorCode::RefusedStream);
+            return Ok(());
+        }
+
+        let mut stream = H2Stream::new(self.peer_initial_window, self.stream_recv_window());
+        stream.remote_closed = end_stream;
+        self.streams.insert(id, stream);
+
+        let list_size: usize = fields.iter().map(|(n, v)| n.len() + v.len() + 32).sum();
+        if list_size > self.options.config.max_header_list_size as usize {
+            self.respond_error(id, 431);
+            return Ok(());
+        }
+
+        match request_head(&fields) {
+            Ok(head) => {
+                if let Some(stream) = self.streams.get_mut(&id) {
+                    stream.request = Some(PendingRequest {
+                        head,
+                        body: BytesMut::new(),
+                        trailers: None,
+                    });
+                }
+                if end_stream {
+                    self.dispatch(id);
+                }
+            },
+            Err(reason) => {
+                tracing::debug!("Malformed HTTP/2 request on stream {}: {}", id, reason);
+                self.reset(stream_id, ErrorCode::ProtocolError);
+            },
+        }
+        Ok(())
+    }
+
+    fn on_data(
+        &mut self,
+        stream_id: StreamId,
+        data: &[u8],
+        end_stream: bool,
+        flow_len: u32,
+    ) -> Result<(), Http2Error> {
+        let flow_len = flow_len as usize;
+        self.recv_window.consume(flow_len)?;
+        if let Some(increment) = self.recv_window.refill(self.connection_window()) {
+            Frame::WindowUpdate {
+                stream_id: StreamId::CONNECTION,
+                increment,
+            }
+            .encode(&mut self.out);
+        }
+
+        let id = stream_id.get();
+        let target = self.stream_recv_window();
+        let max_body_size = self.options.max_body_size;
+        let Some(stream) = self.streams.get_mut(&id) else {
+            if id > self.last_stream_id {
+                return Err(Http2Error::ProtocolError("DATA on idle stream".to_string()));
+            }
+            self.send_reset(stream_id, ErrorCode::StreamClosed);
+            return Ok(());
+        };
+
+        if stream.remote_closed {
+            self.reset(stream_id, ErrorCode::StreamClosed);
+            return Ok(());
+        }
+        if stream.recv_window.consume(flow_len).is_err() {
+            self.reset(stream_id, ErrorCode::FlowControlError);
+            return Ok(());
+        }
+
+        let too_large = stream
+            .request
+            .as_ref()
+            .is_some_and(|pending| pending.body.len() + data.len() > max_body_size);
+        if let Some(pending) = stream.request.as_mut() {
+            pending.body.extend_from_slice(data);
+        }
+
+        if end_stream {
+            stream.remote_closed = true;
+        } else if let Some(increment) = stream.recv_window.refill(target) {
+            Frame::WindowUpdate {
+                stream_id,
+                increment,
+            }
+            .encode(&mut self.out);
+        }
+
+        if too_large {
+            self.respond_error(id, 413);
+        } else if end_stream {
+            self.dispatch(id);
+        }
+        Ok(())
+    }
+
+    fn on_window_update(&mut self, stream_id: StreamId, increment: u32) -> Result<(), Http2Error> {
+        if stream_id.is_connection() {
+            if increment == 0 {
+                return Err(Http2Error::ProtocolError("zero WINDOW_UPDATE".to_string()));
+            }
+            return self.send_window.increase(increment);
+        }
+
+        let Some(stream) = self.streams.get_mut(&stream_id.get()) else {
+            return Ok(());
+        };
+        if increment == 0 {
+            self.reset(stream_id, ErrorCode::ProtocolError);
+        } else if stream.send_window.increase(increment).is_err() {
+            self.reset(stream_id, ErrorCode::FlowControlError);
+        }
+        Ok(())
+    }
+
+    /// Hand a fully received request to the service
+    /// 将完整接收的请求交给服务
+    fn dispatch(&mut self, id: u32) {
+        let Some(stream) = self.streams.get_mut(&id) else {
+            return;
+        };
+        let Some(pending) = stream.request.take() else {
+            return;
+        };
+
+        let (parts, ()) = pending.head.into_parts();
+        let body = Body::from(pending.body.freeze());
+        let mut request = Request::new(http::Request::from_parts(parts, body));
+        if let Some(trailers) = pending.trailers.filter(|t| !t.is_empty()) {
+            request.extensions_mut().insert(Trailers::new(trailers));
+        }
+        self.ready
+            .push((StreamId::new(id), request, stream.shared.clone()));
+    }
+
+    /// Answer a stream with an empty error response, without calling the service
+    /// 以空的错误响应回复流，而不调用服务
+    fn respond_error(&mut self, id: u32, status: u16) {
+        let stream_id = StreamId::new(id);
+        self.encode_head(stream_id, status, &[], true);
+        let remote_closed = self.streams.get(&id).is_some_and(|s| s.remote_closed);
+        if remote_closed {
+            self.remove_stream(id);
+        } else {
+            // Tell the client to stop sending the rest of the request
+            // 告诉客户端停止发送请求的剩余部分
+            self.reset(stream_id, ErrorCode::NoError);
+        }
+    }
+
+    fn on_event(&mut self, event: StreamEvent) {
+        match event {
+            StreamEvent::Head {
+                stream_id,
+                status,
+                headers,
+                end_stream,
+            } => {
+                if !self.streams.contains_key(&stream_id.get()) {
+                    return;
+                }
+                self.encode_head(stream_id, status, &headers, end_stream);
+                if end_stream && let Some(stream) = self.streams.get_mut(&stream_id.get()) {
+                    stream.local_closed = true;
+                }
+            },
+            StreamEvent::Data {
+                stream_id,
+                data,
+                end_stream,
+            } => {
+                if let Some(stream) = self.streams.get_mut(&stream_id.get()) {
+                    if !data.is_empty() {
+                        stream.pending.push_back(data);
+                    }
+                    stream.pending_end = end_stream;
+                }
+            },
+            StreamEvent::Reset {
+                stream_id,
+                error_code,
+            } => {
+                if self.streams.contains_key(&stream_id.get()) {
+                    self.reset(stream_id, error_code);
+                }
+            },
+        }
+    }
+
+    fn encode_head(
+        &mut self,
+        stream_id: StreamId,
+        status: u16,
+        headers: &[(String, String)],
+        end_stream: bool,
+    ) {
+        let status = status.to_string();
+        let fields = std::iter::once((&b":status"[..], status.as_bytes())).chain(
+            headers
+                .iter()
+                .map(|(name, value)| (name.as_bytes(), value.as_bytes())),
+        );
+        let mut block = Vec::new();
+        self.encoder.encode(fields, &mut block);
+        frame::encode_headers(
+            &mut self.out,
+            stream_id,
+            Bytes::from(block),
+            end_stream,
+            self.peer_max_frame_size,
+        );
+    }
+
+    /// Write queued response data as far as the flow-control windows allow
+    /// 在流控制窗口允许的范围内写出排队的响应数据
+    fn flush_data(&mut self) {
+        let max_frame_size = self.peer_max_frame_size as usize;
+        let mut finished = Vec::new();
+
+        for (&id, stream) in &mut self.streams {
+            while let Some(chunk) = stream.pending.front_mut() {
+                let len = chunk
+                    .len()
+                    .min(max_frame_size)
+                    .min(self.send_window.available())
+                    .min(stream.send_window.available());
+                if len == 0
+                    || self.send_window.consume(len).is_err()
+                    || stream.send_window.consume(len).is_err()
+                {
+                    break;
+                }
+
+                let data = chunk.split_to(len);
+                if chunk.is_empty() {
+                    stream.pending.pop_front();
+                }
+                let end_stream = stream.pending.is_empty() && stream.pending_end;
+                Frame::Data {
+                    stream_id: StreamId::new(id),
+                    data,
+                    end_stream,
+                    flow_len: len as u32,
+                }
+                .encode(&mut self.out);
+                stream.shared.release(len);
+                stream.local_closed |= end_stream;
+            }
+
+            if stream.pending.is_empty() && stream.pending_end && !stream.local_closed {
+                Frame::Data {
+                    stream_id: StreamId::new(id),
+                    data: Bytes::new(),
+                    end_stream: true,
+                    flow_len: 0,
+                }
+                .encode(&mut self.out);
+                stream.local_closed = true;
+            }
+
+            if stream.local_closed && stream.remote_closed {
+                finished.push(id);
+            }
+        }
+
+        for id in finished {
+            self.remove_stream(id);
+        }
+    }
+
+    /// Reset a stream and forget it
+    /// 重置流并将其遗忘
+    fn reset(&mut self, stream_id: StreamId, error_code: ErrorCode) {
+        self.send_reset(stream_id, error_code);
+        self.remove_stream(stream_id.get());
+    }
+
+    fn send_reset(&mut self, stream_id: StreamId, error_code: ErrorCode) {
+        Frame::RstStream {
+            stream_id,
+            error_code,
+        }
+        .encode(&mut self.out);
+    }
+
+    fn remove_stream(&mut self, id: u32) {
+        if let Some(stream) = self.streams.remove(&id) {
+            stream.shared.cancel();
+        }
+    }
+}
+
+fn is_connection_header(name: &[u8]) -> bool {
+    CONNECTION_HEADERS.iter().any(|h| h.as_bytes() == name)
+}
+
+/// Build the request head from decoded header fields (RFC 9113 Section 8.3)
+/// 从解码后的头字段构建请求头部（RFC 9113 第8.3节）
+fn request_head(fields: &[HeaderField]) -> Result<http::Request<()>, &'static str> {
+    let mut method = None;
+    let mut path = None;
+    let mut scheme = None;
+    let mut authority = None;
+    let mut regular = Vec::with_capacity(fields.len());
+
+    for (name, value) in fields {
+        if let Some(pseudo) = name.strip_prefix(b":") {
+            if !regular.is_empty() {
+                return Err("pseudo-header after regular header");
+            }
+            let slot = match pseudo {
+                b"method" => &mut method,
+                b"path" => &mut path,
+                b"scheme" => &mut scheme,
+                b"authority" => &mut authority,
+                _ => return Err("unknown pseudo-header"),
+            };
+            if slot.replace(value.clone()).is_some() {
+                return Err("duplicate pseudo-header");
+            }
+        } else {
+            regular.push((name.clone(), value.clone()));
+        }
+    }
+
+    let mut headers = header_map(&regular)?;
+    let method = method.ok_or("missing :method")?;
+    let path = path.filter(|p| !p.is_empty()).ok_or("missing :path")?;
+    scheme.ok_or("missing :scheme")?;
+    if let Some(authority) = authority
+        && !headers.contains_key(HOST)
+    {
+        let value = HeaderValue::from_maybe_shared(authority).map_err(|_| "invalid :authority")?;
+        headers.insert(HOST, value);
+    }
+
+    let mut head = http::Request::builder()
+        .method(http::Method::from_bytes(&method).map_err(|_| "invalid :method")?)
+        .uri(http::Uri::from_maybe_shared(path).map_err(|_| "invalid :path")?)
+        .version(http::Version::HTTP_2)
+        .body(())
+        .map_err(|_| "invalid request")?;
+    *head.headers_mut() = headers;
+    Ok(head)
+}
+
+/// Convert regular header fields to a header map, rejecting malformed ones
+/// 将常规头字段转换为头映射，拒绝格式错误的字段
+fn header_map(fields: &[HeaderField]) -> Result<http::HeaderMap, &'static str> {
+    let mut headers = http::HeaderMap::with_capacity(fields.len());
+    for (name, value) in fields {
+        if name.starts_with(b":") {
+            return Err("unexpected pseudo-header");
+        }
+        if name.iter().any(u8::is_ascii_uppercase) {
+            return Err("uppercase header name");
+        }
+        if is_connection_header(name) {
+            return Err("connection-specific header");
+        }
+        if &name[..] == b"te" && &value[..] != b"trailers" {
+            return Err("invalid te header");
+        }
+        let name = HeaderName::from_bytes(name).map_err(|_| "invalid header name")?;
+        let value = HeaderValue::from_bytes(value).map_err(|_| "invalid header value")?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &'static str, value: &'static str) -> HeaderField {
+        (Bytes::from_static(name.as_bytes()), Bytes::from_static(value.as_bytes()))
+    }
+
+    fn options() -> ServeOptions {
+        ServeOptions {
+            config: Http2Config::default(),
+            request_timeout: Duration::from_secs(30),
+            keep_alive_timeout: Duration::from_secs(60),
+            max_body_size: 1024,
+        }
+    }
+
+    fn connection() -> Connection {
+        let (events, _receiver) = unbounded();
+        let mut conn = Connection::new(options(), events);
+        conn.read_buf.extend_from_slice(PREFACE);
+        Frame::Settings {
+            ack: false,
+            params: Vec::new(),
+        }
+        .encode(&mut conn.read_buf);
+        conn.process_input().unwrap();
+        conn.out.clear();
+        conn
+    }
+
+    fn headers(conn: &mut Connection, id: u32, fields: &[(&[u8], &[u8])], end_stream: bool) {
+        let mut block = Vec::new();
+        hpack::Encoder::new().encode(fields.iter().copied(), &mut block);
+        Frame::Headers {
+            stream_id: StreamId::new(id),
+            block: Bytes::from(block),
+            end_stream,
+            end_headers: true,
+        }
+        .encode(&mut conn.read_buf);
+    }
+
+    fn output_frames(conn: &mut Connection) -> Vec<Frame> {
+        let out = conn.out.split();
+        let mut frames = Vec::new();
+        let mut offset = 0;
+        while let Some((frame, used)) = Frame::decode(&out[offset..], 1 << 24).unwrap() {
+            frames.push(frame);
+            offset += used;
+        }
+        frames
+    }
+
+    const GET: [(&[u8], &[u8]); 4] = [
+        (b":method", b"GET"),
+        (b":scheme", b"http"),
+        (b":path", b"/items?page=2"),
+        (b":authority", b"example.com"),
+    ];
+
+    #[test]
+    fn test_request_head() {
+        let fields = [
+            field(":method", "POST"),
+            field(":scheme", "http"),
+            field(":path", "/users"),
+            field(":authority", "example.com"),
+            field("content-type", "application/json"),
+        ];
+        let head = request_head(&fields).unwrap();
+        assert_eq!(head.method(), http::Method::POST);
+        assert_eq!(head.uri().path(), "/users");
+        assert_eq!(head.version(), http::Version::HTTP_2);
+        assert_eq!(head.headers()["host"], "example.com");
+        assert_eq!(head.headers()["content-type"], "application/json");
+    }
+
+    #[test]
+    fn test_malformed_request_head() {
+        let missing_path = [field(":method", "GET"), field(":scheme", "http")];
+        assert!(request_head(&missing_path).is_err());
+
+        let late_pseudo = [
+            field(":method", "GET"),
+            field("accept", "*/*"),
+            field(":path", "/"),
+            field(":scheme", "http"),
+        ];
+        assert!(request_head(&late_pseudo).is_err());
+
+        let connection = [
+            field(":method", "GET"),
+            field(":scheme", "http"),
+            field(":path", "/"),
+            field("connection", "keep-alive"),
+        ];
+        assert!(request_head(&connection).is_err());
+
+        let uppercase = [field("Accept", "*/*")];
+        assert!(header_map(&uppercase).is_err());
+    }
+
+    #[test]
+    fn test_preface_and_settings_ack() {
+        let conn = &mut connection();
+        Frame::Settings {
+            ack: false,
+            params: vec![(SettingsParameter::MaxFrameSize.as_u16(), 32_768)],
+        }
+        .encode(&mut conn.read_buf);
+        Frame::Ping {
+            ack: false,
+            payload: *b"pingpong",
+        }
+        .encode(&mut conn.read_buf);
+        conn.process_input().unwrap();
+
+        assert_eq!(conn.peer_max_frame_size, 32_768);
+        let frames = output_frames(conn);
+        assert_eq!(
+            frames,
+            [
+                Frame::Settings {
+                    ack: true,
+                    params: Vec::new()
+                },
+                Frame::Ping {
+                    ack: true,
+                    payload: *b"pingpong"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_preface() {
+        let (events, _receiver) = unbounded();
+        let mut conn = Connection::new(options(), events);
+        conn.read_buf.extend_from_slice(b"GET / HTTP/1.1\r\n\r\n");
+        assert!(conn.process_input().is_err());
+    }
+
+    #[test]
+    fn test_request_dispatch() {
+        let conn = &mut connection();
+        headers(conn, 1, &GET, true);
+        conn.process_input().unwrap();
+
+        assert_eq!(conn.ready.len(), 1);
+        let (stream_id, request, _) = conn.ready.pop().unwrap();
+        assert_eq!(stream_id.get(), 1);
+        assert_eq!(request.path(), "/items");
+        assert_eq!(request.param("page"), Some("2"));
+        assert_eq!(request.header("host"), Some("example.com"));
+    }
+
+    #[test]
+    fn test_request_body_and_trailers() {
+        let conn = &mut connection();
+        let mut post = GET;
+        post[0] = (b":method", b"POST");
+        headers(conn, 3, &post, false);
+        Frame::Data {
+            stream_id: StreamId::new(3),
+            data: Bytes::from_static(b"hello"),
+            end_stream: false,
+            flow_len: 5,
+        }
+        .encode(&mut conn.read_buf);
+        headers(conn, 3, &[(b"x-checksum", b"abc")], true);
+        conn.process_input().unwrap();
+
+        let (_, request, _) = conn.ready.pop().unwrap();
+        assert_eq!(&request.body().data()[..], b"hello");
+        assert_eq!(request.trailers().unwrap()["x-checksum"], "abc");
+    }
+
+    #[test]
+    fn test_body_too_large() {
+        let conn = &mut connection();
+        headers(conn, 1, &GET, false);
+        Frame::Data {
+            stream_id: StreamId::new(1),
+            data: Bytes::from(vec![0; 2048]),
+            end_stream: true,
+            flow_len: 2048,
+        }
+        .encode(&mut conn.read_buf);
+        conn.process_input().unwrap();
+
+        assert!(conn.ready.is_empty());
+        let frames = output_frames(conn);
+        let Frame::Headers { block, .. } = &frames[0] else {
+            panic!("expected HEADERS, got {:?}", frames);
+        };
+        let fields = hpack::Decoder::new(4096).decode(block).unwrap();
+        assert_eq!(fields[0], field(":status", "413"));
+        assert!(conn.streams.is_empty());
+    }
+
+    #[test]
+    fn test_refuses_excess_streams() {
+        let (events, _receiver) = unbounded();
+        let mut opts = options();
+        opts.config = Http2Config::new().with_max_streams(1);
+        let conn = &mut Connection::new(opts, events);
+        conn.read_buf.extend_from_slice(PREFACE);
+        frame::settings(&[]).encode(&mut conn.read_buf);
+        headers(conn, 1, &GET, false);
+        headers(conn, 3, &GET, false);
+        conn.process_input().unwrap();
+
+        assert!(output_frames(conn).contains(&Frame::RstStream {
+            stream_id: StreamId::new(3),
+            error_code: ErrorCode::RefusedStream
+        }));
+        assert_eq!(conn.streams.len(), 1);
+    }
+
+    #[test]
+    fn test_response_respects_flow_control() {
+        let conn = &mut connection();
+        Frame::Settings {
+            ack: false,
+            params: vec![(SettingsParameter::InitialWindowSize.as_u16(), 10)],
+        }
+        .encode(&mut conn.read_buf);
+        headers(conn, 1, &GET, true);
+        conn.process_input().unwrap();
+        conn.out.clear();
+
+        let stream_id = StreamId::new(1);
+        conn.on_event(StreamEvent::Head {
+            stream_id,
+            status: 200,
+            headers: Vec::new(),
+            end_stream: false,
+        });
+        conn.on_event(StreamEvent::Data {
+            stream_id,
+            data: Bytes::from_static(b"0123456789abcdef"),
+            end_stream: true,
+        });
+        conn.flush_data();
+
+        let frames = output_frames(conn);
+        assert_eq!(frames.len(), 2);
+        assert!(matches!(
+            &frames[1],
+            Frame::Data { data, end_stream: false, .. } if &data[..] == b"0123456789"
+        ));
+
+        Frame::WindowUpdate {
+            stream_id,
+            increment: 100,
+        }
+        .encode(&mut conn.read_buf);
+        conn.process_input().unwrap();
+        conn.flush_data();
+
+        let frames = output_frames(conn);
+        assert!(matches!(
+            &frames[..],
+            [Frame::Data { data, end_stream: true, .. }] if &data[..] == b"abcdef"
+        ));
+        assert!(conn.streams.is_empty());
+    }
+
+    #[test]
+    fn test_connection_window_refilled_after_reset() {
+        let conn = &mut connection();
+        headers(conn, 1, &GET, false);
+        conn.process_input().unwrap();
+        for _ in 0..5 {
+            Frame::Data {
+                stream_id: StreamId::new(1),
+                data: Bytes::from(vec![0; 16_384]),
+                end_stream: false,
+                flow_len: 16_384,
+            }
+            .encode(&mut conn.read_buf);
+        }
+        // The stream is reset for exceeding the 1 KiB body limit; the connection
+        // window is still enforced and refilled
+        assert!(conn.process_input().is_ok());
+        assert!(conn.streams.is_empty());
+    }
+
+    #[test]
+    fn test_shutdown_goaway() {
+        let conn = &mut connection();
+        headers(conn, 1, &GET, true);
+        conn.process_input().unwrap();
+        conn.go_away(ErrorCode::NoError);
+        headers(conn, 3, &GET, true);
+        conn.process_input().unwrap();
+
+        // Stream 3 arrived after GOAWAY and is ignored
+        assert_eq!(conn.ready.len(), 1);
+        assert_eq!(
+            output_frames(conn),
+            [Frame::GoAway {
+                last_stream_id: StreamId::new(1),
+                error_code: ErrorCode::NoError,
+                debug_data: Bytes::new(),
+            }]
+        );
+        assert!(!conn.is_finished());
+        conn.remove_stream(1);
+        assert!(conn.is_finished());
+    }
+}
@@ -0,0 +1,114 @@
+//! HTTP/2 flow-control windows (RFC 9113 Section 5.2)
+//! HTTP/2 流控制窗口（RFC 9113 第5.2节）
+
+use super::Http2Error;
+
+/// Largest legal flow-control window
+/// 最大的合法流控制窗口
+pub const MAX_WINDOW_SIZE: u32 = (1 << 31) - 1;
+
+/// Initial window size every connection and stream starts with
+/// 每个连接和流开始时的初始窗口大小
+pub const DEFAULT_WINDOW_SIZE: u32 = 65_535;
+
+/// A flow-control window
+/// 流控制窗口
+///
+/// The window may become negative when SETTINGS_INITIAL_WINDOW_SIZE shrinks while
+/// data is in flight.
+/// 当数据在传输中而 SETTINGS_INITIAL_WINDOW_SIZE 缩小时，窗口可能变为负数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlowWindow(i64);
+
+impl FlowWindow {
+    /// Create a window of the given size
+    /// 创建给定大小的窗口
+    pub fn new(size: u32) -> Self {
+        Self(i64::from(size))
+    }
+
+    /// Bytes that may currently be sent (zero when negative)
+    /// 当前可发送的字节数（为负时为零）
+    pub fn available(&self) -> usize {
+        usize::try_from(self.0).unwrap_or(0)
+    }
+
+    /// Consume `len` bytes, failing if the window is exceeded
+    /// 消耗 `len` 字节，如果超出窗口则失败
+    pub fn consume(&mut self, len: usize) -> Result<(), Http2Error> {
+        let len = len as i64;
+        if len > self.0 {
+            return Err(Http2Error::FlowControlError(format!(
+                "{} bytes exceed the window of {}",
+                len, self.0
+            )));
+        }
+        self.0 -= len;
+        Ok(())
+    }
+
+    /// Apply a WINDOW_UPDATE increment
+    /// 应用 WINDOW_UPDATE 增量
+    pub fn increase(&mut self, increment: u32) -> Result<(), Http2Error> {
+        self.adjust(i64::from(increment))
+    }
+
+    /// Apply a change of SETTINGS_INITIAL_WINDOW_SIZE
+    /// 应用 SETTINGS_INITIAL_WINDOW_SIZE 的变化
+    pub fn adjust(&mut self, delta: i64) -> Result<(), Http2Error> {
+        let size = self.0 + delta;
+        if size > i64::from(MAX_WINDOW_SIZE) {
+            return Err(Http2Error::FlowControlError("window size overflow".to_string()));
+        }
+        self.0 = size;
+        Ok(())
+    }
+
+    /// How much to add to bring the window back to `target`, once it fell below half
+    /// 当窗口低于一半时，需要增加多少才能恢复到 `target`
+    pub fn refill(&mut self, target: u32) -> Option<u32> {
+        let target = i64::from(target);
+        if self.0 >= target / 2 {
+            return None;
+        }
+        let increment = target - self.0;
+        self.0 = target;
+        u32::try_from(increment).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consume_and_increase() {
+        let mut window = FlowWindow::new(100);
+        window.consume(60).unwrap();
+        assert_eq!(window.available(), 40);
+        assert!(window.consume(41).is_err());
+
+        window.increase(10).unwrap();
+        assert_eq!(window.available(), 50);
+        assert!(window.increase(MAX_WINDOW_SIZE).is_err());
+    }
+
+    #[test]
+    fn test_adjust_can_go_negative() {
+        let mut window = FlowWindow::new(DEFAULT_WINDOW_SIZE);
+        window.consume(60_000).unwrap();
+        window.adjust(-(DEFAULT_WINDOW_SIZE as i64)).unwrap();
+        assert_eq!(window.available(), 0);
+        assert_eq!(window, FlowWindow(-60_000));
+    }
+
+    #[test]
+    fn test_refill_below_half() {
+        let mut window = FlowWindow::new(1000);
+        window.consume(400).unwrap();
+        assert_eq!(window.refill(1000), None);
+        window.consume(200).unwrap();
+        assert_eq!(window.refill(1000), Some(600));
+        assert_eq!(window.available(), 1000);
+    }
+}
@@ -0,0 +1,663 @@
+//! HTTP/2 frame codec (RFC 9113 Section 4 and 6)
+//! HTTP/2 帧编解码（RFC 9113 第4节和第6节）
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{ErrorCode, FrameType, Http2Error, SettingsParameter, StreamId};
+
+/// Connection preface sent by the client
+/// 客户端发送的连接前言
+pub const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// Length of the fixed frame header
+/// 固定帧头的长度
+pub const FRAME_HEADER_LEN: usize = 9;
+
+/// Default SETTINGS_MAX_FRAME_SIZE
+/// 默认的 SETTINGS_MAX_FRAME_SIZE
+pub const DEFAULT_MAX_FRAME_SIZE: u32 = 16_384;
+
+/// END_STREAM flag (DATA, HEADERS)
+/// END_STREAM 标志（DATA、HEADERS）
+pub const FLAG_END_STREAM: u8 = 0x1;
+
+/// ACK flag (SETTINGS, PING)
+/// ACK 标志（SETTINGS、PING）
+pub const FLAG_ACK: u8 = 0x1;
+
+/// END_HEADERS flag (HEADERS, CONTINUATION)
+/// END_HEADERS 标志（HEADERS、CONTINUATION）
+pub const FLAG_END_HEADERS: u8 = 0x4;
+
+/// PADDED flag (DATA, HEADERS)
+/// PADDED 标志（DATA、HEADERS）
+pub const FLAG_PADDED: u8 = 0x8;
+
+/// PRIORITY flag (HEADERS)
+/// PRIORITY 标志（HEADERS）
+pub const FLAG_PRIORITY: u8 = 0x20;
+
+/// A decoded HTTP/2 frame
+/// 解码后的HTTP/2帧
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// DATA frame; `flow_len` is the full payload length charged to flow control
+    /// DATA帧；`flow_len` 是计入流控制的完整负载长度
+    Data {
+        /// Stream ID / 流ID
+        stream_id: StreamId,
+        /// Payload without padding / 去除填充的负载
+        data: Bytes,
+        /// END_STREAM flag / END_STREAM 标志
+        end_stream: bool,
+        /// Flow-controlled length / 受流控制的长度
+        flow_len: u32,
+    },
+
+    /// HEADERS frame carrying a header block fragment
+    /// 携带头块片段的HEADERS帧
+    Headers {
+        /// Stream ID / 流ID
+        stream_id: StreamId,
+        /// Header block fragment / 头块片段
+        block: Bytes,
+        /// END_STREAM flag / END_STREAM 标志
+        end_stream: bool,
+        /// END_HEADERS flag / END_HEADERS 标志
+        end_headers: bool,
+    },
+
+    /// PRIORITY frame (contents are ignored)
+    /// PRIORITY帧（内容被忽略）
+    Priority {
+        /// Stream ID / 流ID
+        stream_id: StreamId,
+    },
+
+    /// RST_STREAM frame
+    /// RST_STREAM帧
+    RstStream {
+        /// Stream ID / 流ID
+        stream_id: StreamId,
+        /// Error code / 错误码
+        error_code: ErrorCode,
+    },
+
+    /// SETTINGS frame
+    /// SETTINGS帧
+    Settings {
+        /// ACK flag / ACK 标志
+        ack: bool,
+        /// Parameters as (identifier, value); unknown identifiers are kept
+        /// 参数为（标识符，值）；保留未知标识符
+        params: Vec<(u16, u32)>,
+    },
+
+    /// PUSH_PROMISE frame
+    /// PUSH_PROMISE帧
+    PushPromise {
+        /// Stream ID / 流ID
+        stream_id: StreamId,
+    },
+
+    /// PING frame
+    /// PING帧
+    Ping {
+        /// ACK flag / ACK 标志
+        ack: bool,
+        /// Opaque data / 不透明数据
+        payload: [u8; 8],
+    },
+
+    /// GOAWAY frame
+    /// GOAWAY帧
+    GoAway {
+        /// Last processed stream / 最后处理的流
+        last_stream_id: StreamId,
+        /// Error code / 错误码
+        error_code: ErrorCode,
+        /// Additional debug data / 附加调试数据
+        debug_data: Bytes,
+    },
+
+    /// WINDOW_UPDATE frame
+    /// WINDOW_UPDATE帧
+    WindowUpdate {
+        /// Stream ID / 流ID
+        stream_id: StreamId,
+        /// Window size increment / 窗口大小增量
+        increment: u32,
+    },
+
+    /// CONTINUATION frame
+    /// CONTINUATION帧
+    Continuation {
+        /// Stream ID / 流ID
+        stream_id: StreamId,
+        /// Header block fragment / 头块片段
+        block: Bytes,
+        /// END_HEADERS flag / END_HEADERS 标志
+        end_headers: bool,
+    },
+
+    /// Frame of an unknown type, which must be ignored
+    /// 未知类型的帧，必须忽略
+    Unknown {
+        /// Frame type / 帧类型
+        kind: u8,
+        /// Stream ID / 流ID
+        stream_id: StreamId,
+    },
+}
+
+impl Frame {
+    /// Decode one frame from the front of `buf`
+    /// 从 `buf` 的开头解码一个帧
+    ///
+    /// Returns `Ok(None)` until the whole frame is buffered, otherwise the frame and
+    /// the number of bytes it used. Frames larger than `max_frame_size` are rejected.
+    ///
+    /// 在整个帧被缓冲之前返回 `Ok(None)`，否则返回帧及其使用的字节数。
+    /// 大于 `max_frame_size` 的帧被拒绝。
+    pub fn decode(buf: &[u8], max_frame_size: u32) -> Result<Option<(Frame, usize)>, Http2Error> {
+        if buf.len() < FRAME_HEADER_LEN {
+            return Ok(None);
+        }
+
+        let len = u32::from_be_bytes([0, buf[0], buf[1], buf[2]]);
+        if len > max_frame_size {
+            return Err(Http2Error::InvalidFrame(format!(
+                "frame of {} bytes exceeds the maximum of {}",
+                len, max_frame_size
+            )));
+        }
+        let total = FRAME_HEADER_LEN + len as usize;
+        if buf.len() < total {
+            return Ok(None);
+        }
+
+        let kind = buf[3];
+        let flags = buf[4];
+        let stream_id =
+            StreamId::new(u32::from_be_bytes([buf[5], buf[6], buf[7], buf[8]]) & 0x7fff_ffff);
+        let payload = Bytes::copy_from_slice(&buf[FRAME_HEADER_LEN..total]);
+
+        let Some(frame_type) = FrameType::from_byte(kind) else {
+            return Ok(Some((Frame::Unknown { kind, stream_id }, total)));
+        };
+        let frame = decode_payload(frame_type, flags, stream_id, payload)?;
+        Ok(Some((frame, total)))
+    }
+
+    /// Append the encoded frame to `dst`
+    /// 将编码后的帧追加到 `dst`
+    pub fn encode(&self, dst: &mut BytesMut) {
+        match self {
+            Frame::Data {
+                stream_id,
+                data,
+                end_stream,
+                ..
+            } => {
+                let flags = if *end_stream { FLAG_END_STREAM } else { 0 };
+                put_header(dst, data.len(), FrameType::Data, flags, *stream_id);
+                dst.put_slice(data);
+            },
+            Frame::Headers {
+                stream_id,
+                block,
+                end_stream,
+                end_headers,
+            } => {
+                let mut flags = if *end_stream { FLAG_END_STREAM } else { 0 };
+                if *end_headers {
+                    flags |= FLAG_END_HEADERS;
+                }
+                put_header(dst, block.len(), FrameType::Headers, flags, *stream_id);
+                dst.put_slice(block);
+            },
+            Frame::Priority { stream_id } => {
+                put_header(dst, 5, FrameType::Priority, 0, *stream_id);
+                dst.put_u32(0);
+                dst.put_u8(15);
+            },
+            Frame::RstStream {
+                stream_id,
+                error_code,
+            } => {
+                put_header(dst, 4, FrameType::RstStream, 0, *stream_id);
+                dst.put_u32(error_code.as_u32());
+            },
+            Frame::Settings { ack, params } => {
+                let flags = if *ack { FLAG_ACK } else { 0 };
+                put_header(dst, params.len() * 6, FrameType::Settings, flags, StreamId::CONNECTION);
+                for &(id, value) in params {
+                    dst.put_u16(id);
+                    dst.put_u32(value);
+                }
+            },
+            Frame::PushPromise { stream_id } => {
+                put_header(dst, 4, FrameType::PushPromise, FLAG_END_HEADERS, *stream_id);
+                dst.put_u32(0);
+            },
+            Frame::Ping { ack, payload } => {
+                let flags = if *ack { FLAG_ACK } else { 0 };
+                put_header(dst, 8, FrameType::Ping, flags, StreamId::CONNECTION);
+                dst.put_slice(payload);
+            },
+            Frame::GoAway {
+                last_stream_id,
+                error_code,
+                debug_data,
+            } => {
+                let len = 8 + debug_data.len();
+                put_header(dst, len, FrameType::GoAway, 0, StreamId::CONNECTION);
+                dst.put_u32(last_stream_id.get());
+                dst.put_u32(error_code.as_u32());
+                dst.put_slice(debug_data);
+            },
+            Frame::WindowUpdate {
+                stream_id,
+                increment,
+            } => {
+                put_header(dst, 4, FrameType::WindowUpdate, 0, *stream_id);
+                dst.put_u32(*increment);
+            },
+            Frame::Continuation {
+                stream_id,
+                block,
+                end_headers,
+            } => {
+                let flags = if *end_headers { FLAG_END_HEADERS } else { 0 };
+                put_header(dst, block.len(), FrameType::Continuation, flags, *stream_id);
+                dst.put_slice(block);
+            },
+            Frame::Unknown { .. } => {},
+        }
+    }
+}
+
+/// Encode a header block as HEADERS plus CONTINUATION frames of at most `max_frame_size`
+/// 将头块编码为不超过 `max_frame_size` 的HEADERS加CONTINUATION帧
+pub fn encode_headers(
+    dst: &mut BytesMut,
+    stream_id: StreamId,
+    block: Bytes,
+    end_stream: bool,
+    max_frame_size: u32,
+) {
+    let max = max_frame_size as usize;
+    let mut rest = block;
+    let first = rest.split_to(rest.len().min(max));
+    Frame::Headers {
+        stream_id,
+        block: first,
+        end_stream,
+        end_headers: rest.is_empty(),
+    }
+    .encode(dst);
+
+    while !rest.is_empty() {
+        let block = rest.split_to(rest.len().min(max));
+        Frame::Continuation {
+            stream_id,
+            block,
+            end_headers: rest.is_empty(),
+        }
+        .encode(dst);
+    }
+}
+
+fn put_header(dst: &mut BytesMut, len: usize, kind: FrameType, flags: u8, stream_id: StreamId) {
+    dst.reserve(FRAME_HEADER_LEN + len);
+    dst.put_uint(len as u64, 3);
+    dst.put_u8(kind.as_byte());
+    dst.put_u8(flags);
+    dst.put_u32(stream_id.get());
+}
+
+fn decode_payload(
+    frame_type: FrameType,
+    flags: u8,
+    stream_id: StreamId,
+    mut payload: Bytes,
+) -> Result<Frame, Http2Error> {
+    let flow_len = payload.len() as u32;
+    let require_stream = |name: &str| {
+        if stream_id.is_connection() {
+            Err(Http2Error::ProtocolError(format!("{} frame on stream 0", name)))
+        } else {
+            Ok(())
+        }
+    };
+    let require_connection = |name: &str| {
+        if stream_id.is_connection() {
+            Ok(())
+        } else {
+            Err(Http2Error::ProtocolError(format!(
+                "{} frame on stream {}",
+                name,
+                stream_id.get()
+            )))
+        }
+    };
+    let require_len = |name: &str, len: usize, payload: &Bytes| {
+        if payload.len() == len {
+            Ok(())
+        } else {
+            Err(Http2Error::InvalidFrame(format!("{} frame must be {} bytes", name, len)))
+        }
+    };
+
+    let frame = match frame_type {
+        FrameType::Data => {
+            require_stream("DATA")?;
+            strip_padding(flags, &mut payload)?;
+            Frame::Data {
+                stream_id,
+                data: payload,
+                end_stream: flags & FLAG_END_STREAM != 0,
+                flow_len,
+            }
+        },
+        FrameType::Headers => {
+            require_stream("HEADERS")?;
+            strip_padding(flags, &mut payload)?;
+            if flags & FLAG_PRIORITY != 0 {
+                if payload.len() < 5 {
+                    return Err(Http2Error::InvalidFrame("HEADERS priority truncated".into()));
+                }
+                payload.advance(5);
+            }
+            Frame::Headers {
+                stream_id,
+                block: payload,
+                end_stream: flags & FLAG_END_STREAM != 0,
+                end_headers: flags & FLAG_END_HEADERS != 0,
+            }
+        },
+        FrameType::Priority => {
+            require_stream("PRIORITY")?;
+            require_len("PRIORITY", 5, &payload)?;
+            Frame::Priority { stream_id }
+        },
+        FrameType::RstStream => {
+            require_stream("RST_STREAM")?;
+            require_len("RST_STREAM", 4, &payload)?;
+            Frame::RstStream {
+                stream_id,
+                error_code: decode_error_code(payload.get_u32()),
+            }
+        },
+        FrameType::Settings => {
+            require_connection("SETTINGS")?;
+            let ack = flags & FLAG_ACK != 0;
+            if ack && !payload.is_empty() {
+                return Err(Http2Error::InvalidFrame("SETTINGS ack with payload".into()));
+            }
+            Frame::Settings {
+                ack,
+                params: decode_settings(&payload)?,
+            }
+        },
+        FrameType::PushPromise => {
+            require_stream("PUSH_PROMISE")?;
+            Frame::PushPromise { stream_id }
+        },
+        FrameType::Ping => {
+            require_connection("PING")?;
+            require_len("PING", 8, &payload)?;
+            let mut data = [0u8; 8];
+            payload.copy_to_slice(&mut data);
+            Frame::Ping {
+                ack: flags & FLAG_ACK != 0,
+                payload: data,
+            }
+        },
+        FrameType::GoAway => {
+            require_connection("GOAWAY")?;
+            if payload.len() < 8 {
+                return Err(Http2Error::InvalidFrame("GOAWAY frame truncated".into()));
+            }
+            Frame::GoAway {
+                last_stream_id: StreamId::new(payload.get_u32() & 0x7fff_ffff),
+                error_code: decode_error_code(payload.get_u32()),
+                debug_data: payload,
+            }
+        },
+        FrameType::WindowUpdate => {
+            require_len("WINDOW_UPDATE", 4, &payload)?;
+            Frame::WindowUpdate {
+                stream_id,
+                increment: payload.get_u32() & 0x7fff_ffff,
+            }
+        },
+        FrameType::Continuation => {
+            require_stream("CONTINUATION")?;
+            Frame::Continuation {
+                stream_id,
+                block: payload,
+                end_headers: flags & FLAG_END_HEADERS != 0,
+            }
+        },
+    };
+    Ok(frame)
+}
+
+/// Remove the pad length byte and trailing padding from a padded payload
+/// 从填充的负载中移除填充长度字节和尾部填充
+fn strip_padding(flags: u8, payload: &mut Bytes) -> Result<(), Http2Error> {
+    if flags & FLAG_PADDED == 0 {
+        return Ok(());
+    }
+    if payload.is_empty() {
+        return Err(Http2Error::InvalidFrame("padded frame without pad length".into()));
+    }
+    let pad = payload.get_u8() as usize;
+    if pad > payload.len() {
+        return Err(Http2Error::ProtocolError("padding exceeds the frame payload".into()));
+    }
+    payload.truncate(payload.len() - pad);
+    Ok(())
+}
+
+/// Unknown error codes must not trigger special behaviour and are treated as internal errors
+/// 未知错误码不得触发特殊行为，按内部错误处理
+fn decode_error_code(code: u32) -> ErrorCode {
+    ErrorCode::from_u32(code).unwrap_or(ErrorCode::InternalError)
+}
+
+/// Decode a SETTINGS payload, as also carried by the `HTTP2-Settings` upgrade header
+/// 解码SETTINGS负载，`HTTP2-Settings` 升级头中携带的也是此格式
+pub fn decode_settings(mut payload: &[u8]) -> Result<Vec<(u16, u32)>, Http2Error> {
+    if !payload.len().is_multiple_of(6) {
+        return Err(Http2Error::InvalidFrame("SETTINGS length not a multiple of 6".into()));
+    }
+    let mut params = Vec::with_capacity(payload.len() / 6);
+    while payload.has_remaining() {
+        params.push((payload.get_u16(), payload.get_u32()));
+    }
+    Ok(params)
+}
+
+/// Encode SETTINGS parameters from typed identifiers
+/// 从类型化标识符编码SETTINGS参数
+pub fn settings(params: &[(SettingsParameter, u32)]) -> Frame {
+    Frame::Settings {
+        ack: false,
+        params: params
+            .iter()
+            .map(|&(id, value)| (id.as_u16(), value))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(frame: &Frame) -> Frame {
+        let mut buf = BytesMut::new();
+        frame.encode(&mut buf);
+        let (decoded, used) = Frame::decode(&buf, DEFAULT_MAX_FRAME_SIZE)
+            .unwrap()
+            .unwrap();
+        assert_eq!(used, buf.len());
+        decoded
+    }
+
+    #[test]
+    fn test_frame_roundtrip() {
+        let frames = [
+            Frame::Headers {
+                stream_id: StreamId::new(1),
+                block: Bytes::from_static(b"\x82\x86"),
+                end_stream: true,
+                end_headers: true,
+            },
+            Frame::RstStream {
+                stream_id: StreamId::new(3),
+                error_code: ErrorCode::Cancel,
+            },
+            settings(&[(SettingsParameter::MaxConcurrentStreams, 100)]),
+            Frame::Ping {
+                ack: true,
+                payload: *b"12345678",
+            },
+            Frame::GoAway {
+                last_stream_id: StreamId::new(7),
+                error_code: ErrorCode::NoError,
+                debug_data: Bytes::from_static(b"bye"),
+            },
+            Frame::WindowUpdate {
+                stream_id: StreamId::CONNECTION,
+                increment: 1024,
+            },
+        ];
+        for frame in frames {
+            assert_eq!(&roundtrip(&frame), &frame);
+        }
+
+        let data = roundtrip(&Frame::Data {
+            stream_id: StreamId::new(1),
+            data: Bytes::from_static(b"hello"),
+            end_stream: false,
+            flow_len: 5,
+        });
+        assert!(matches!(data, Frame::Data { flow_len: 5, .. }));
+    }
+
+    #[test]
+    fn test_decode_incomplete_and_oversized() {
+        let mut buf = BytesMut::new();
+        Frame::Ping {
+            ack: false,
+            payload: [0; 8],
+        }
+        .encode(&mut buf);
+        assert_eq!(Frame::decode(&buf[..5], DEFAULT_MAX_FRAME_SIZE).unwrap(), None);
+        assert_eq!(Frame::decode(&buf[..12], DEFAULT_MAX_FRAME_SIZE).unwrap(), None);
+
+        let header = [0x00, 0x40, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1];
+        assert!(matches!(
+            Frame::decode(&header, DEFAULT_MAX_FRAME_SIZE),
+            Err(Http2Error::InvalidFrame(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_padded_data() {
+        // 5 data bytes plus pad length byte and 2 bytes padding
+        let buf = [
+            0,
+            0,
+            8,
+            0x0,
+            FLAG_PADDED | FLAG_END_STREAM,
+            0,
+            0,
+            0,
+            1,
+            2,
+            b'h',
+            b'e',
+            b'l',
+            b'l',
+            b'o',
+            0,
+            0,
+        ];
+        let (frame, _) = Frame::decode(&buf, DEFAULT_MAX_FRAME_SIZE)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            frame,
+            Frame::Data {
+                stream_id: StreamId::new(1),
+                data: Bytes::from_static(b"hello"),
+                end_stream: true,
+                flow_len: 8,
+            }
+        );
+
+        let bad = [0, 0, 2, 0x0, FLAG_PADDED, 0, 0, 0, 1, 5, b'x'];
+        assert!(Frame::decode(&bad, DEFAULT_MAX_FRAME_SIZE).is_err());
+    }
+
+    #[test]
+    fn test_stream_id_rules() {
+        let ping_on_stream = [0, 0, 8, 0x6, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(matches!(
+            Frame::decode(&ping_on_stream, DEFAULT_MAX_FRAME_SIZE),
+            Err(Http2Error::ProtocolError(_))
+        ));
+
+        let data_on_zero = [0, 0, 1, 0x0, 0, 0, 0, 0, 0, b'x'];
+        assert!(Frame::decode(&data_on_zero, DEFAULT_MAX_FRAME_SIZE).is_err());
+
+        let unknown = [0, 0, 1, 0xfa, 0, 0, 0, 0, 1, b'x'];
+        let (frame, used) = Frame::decode(&unknown, DEFAULT_MAX_FRAME_SIZE)
+            .unwrap()
+            .unwrap();
+        assert_eq!(used, 10);
+        assert!(matches!(frame, Frame::Unknown { kind: 0xfa, .. }));
+    }
+
+    #[test]
+    fn test_encode_headers_splits_continuation() {
+        let mut buf = BytesMut::new();
+        let block = Bytes::from(vec![0x82; 40_000]);
+        encode_headers(&mut buf, StreamId::new(1), block, false, DEFAULT_MAX_FRAME_SIZE);
+
+        let mut offset = 0;
+        let mut frames = Vec::new();
+        while let Some((frame, used)) =
+            Frame::decode(&buf[offset..], DEFAULT_MAX_FRAME_SIZE).unwrap()
+        {
+            frames.push(frame);
+            offset += used;
+        }
+        assert_eq!(frames.len(), 3);
+        assert!(matches!(
+            frames[0],
+            Frame::Headers {
+                end_headers: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            frames[1],
+            Frame::Continuation {
+                end_headers: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            frames[2],
+            Frame::Continuation {
+                end_headers: true,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,512 @@
+//! HPACK header compression (RFC 7541)
+//! HPACK 头压缩（RFC 7541）
+//!
+//! The decoder implements the full specification: static and dynamic tables,
+//! table size updates and Huffman-coded strings. The encoder only indexes the
+//! static table and emits literals without indexing, which keeps the peer's
+//! decoder state untouched and needs no table size negotiation.
+//!
+//! 解码器实现完整规范：静态表和动态表、表大小更新以及霍夫曼编码字符串。
+//! 编码器只索引静态表并发出不索引的字面量，这样不会改变对端解码器的状态，
+//! 也无需协商表大小。
+
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+
+use super::{Http2Error, huffman};
+
+/// Per-entry overhead counted against the table size
+/// 每个条目计入表大小的额外开销
+const ENTRY_OVERHEAD: usize = 32;
+
+/// The static table (RFC 7541 Appendix A), 1-indexed
+/// 静态表（RFC 7541 附录A），索引从1开始
+const STATIC_TABLE: [(&str, &str); 61] = [
+    (":authority", ""),
+    (":method", "GET"),
+    (":method", "POST"),
+    (":path", "/"),
+    (":path", "/index.html"),
+    (":scheme", "http"),
+    (":scheme", "https"),
+    (":status", "200"),
+    (":status", "204"),
+    (":status", "206"),
+    (":status", "304"),
+    (":status", "400"),
+    (":status", "404"),
+    (":status", "500"),
+    ("accept-charset", ""),
+    ("accept-encoding", "gzip, deflate"),
+    ("accept-language", ""),
+    ("accept-ranges", ""),
+    ("accept", ""),
+    ("access-control-allow-origin", ""),
+    ("age", ""),
+    ("allow", ""),
+    ("authorization", ""),
+    ("cache-control", ""),
+    ("content-disposition", ""),
+    ("content-encoding", ""),
+    ("content-language", ""),
+    ("content-length", ""),
+    ("content-location", ""),
+    ("content-range", ""),
+    ("content-type", ""),
+    ("cookie", ""),
+    ("date", ""),
+    ("etag", ""),
+    ("expect", ""),
+    ("expires", ""),
+    ("from", ""),
+    ("host", ""),
+    ("if-match", ""),
+    ("if-modified-since", ""),
+    ("if-none-match", ""),
+    ("if-range", ""),
+    ("if-unmodified-since", ""),
+    ("last-modified", ""),
+    ("link", ""),
+    ("location", ""),
+    ("max-forwards", ""),
+    ("proxy-authenticate", ""),
+    ("proxy-authorization", ""),
+    ("range", ""),
+    ("referer", ""),
+    ("refresh", ""),
+    ("retry-after", ""),
+    ("server", ""),
+    ("set-cookie", ""),
+    ("strict-transport-security", ""),
+    ("transfer-encoding", ""),
+    ("user-agent", ""),
+    ("vary", ""),
+    ("via", ""),
+    ("www-authenticate", ""),
+];
+
+/// A decoded header field
+/// 解码后的头字段
+pub type HeaderField = (Bytes, Bytes);
+
+/// HPACK decoder
+/// HPACK 解码器
+///
+/// One decoder exists per connection; header blocks must be decoded in the order
+/// they were received.
+/// 每个连接一个解码器；头块必须按接收顺序解码。
+#[derive(Debug)]
+pub struct Decoder {
+    /// Dynamic table, newest entry first
+    /// 动态表，最新条目在前
+    dynamic: VecDeque<HeaderField>,
+
+    /// Current size of the dynamic table
+    /// 动态表的当前大小
+    size: usize,
+
+    /// Current maximum size set by the encoder
+    /// 编码器设置的当前最大大小
+    max_size: usize,
+
+    /// Upper bound advertised in SETTINGS_HEADER_TABLE_SIZE
+    /// 在 SETTINGS_HEADER_TABLE_SIZE 中通告的上限
+    max_allowed: usize,
+}
+
+impl Decoder {
+    /// Create a decoder allowing a dynamic table of up to `max_size` bytes
+    /// 创建允许最多 `max_size` 字节动态表的解码器
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            dynamic: VecDeque::new(),
+            size: 0,
+            max_size,
+            max_allowed: max_size,
+        }
+    }
+
+    /// Decode a complete header block
+    /// 解码完整的头块
+    pub fn decode(&mut self, mut src: &[u8]) -> Result<Vec<HeaderField>, Http2Error> {
+        let mut fields = Vec::new();
+        let mut first = true;
+
+        while let Some(&byte) = src.first() {
+            if byte & 0x80 != 0 {
+                // Indexed header field
+                // 索引头字段
+                let index = decode_int(&mut src, 7)?;
+                fields.push(self.get(index)?);
+            } else if byte & 0xc0 == 0x40 {
+                // Literal with incremental indexing
+                // 带增量索引的字面量
+                let field = self.decode_literal(&mut src, 6)?;
+                self.insert(field.clone());
+                fields.push(field);
+            } else if byte & 0xe0 == 0x20 {
+                // Dynamic table size update, only allowed at the start of a block
+                // 动态表大小更新，只允许出现在块的开头
+                if !first {
+                    return Err(compression_error("table size update after header field"));
+                }
+                let size = decode_int(&mut src, 5)?;
+                if size > self.max_allowed {
+                    return Err(compression_error("table size update exceeds the limit"));
+                }
+                self.max_size = size;
+                self.evict(0);
+                continue;
+            } else {
+                // Literal without indexing or never indexed
+                // 不索引或永不索引的字面量
+                fields.push(self.decode_literal(&mut src, 4)?);
+            }
+            first = false;
+        }
+
+        Ok(fields)
+    }
+
+    /// Look up an index in the static and dynamic tables
+    /// 在静态表和动态表中查找索引
+    fn get(&self, index: usize) -> Result<HeaderField, Http2Error> {
+        match index {
+            0 => Err(compression_error("index 0")),
+            1..=61 => {
+                let (name, value) = STATIC_TABLE[index - 1];
+                Ok((Bytes::from_static(name.as_bytes()), Bytes::from_static(value.as_bytes())))
+            },
+            _ => self
+                .dynamic
+                .get(index - 62)
+                .cloned()
+                .ok_or_else(|| compression_error("index out of range")),
+        }
+    }
+
+    fn decode_literal(&self, src: &mut &[u8], prefix: u8) -> Result<HeaderField, Http2Error> {
+        let index = decode_int(src, prefix)?;
+        let name = if index == 0 {
+            decode_string(src)?
+        } else {
+            self.get(index)?.0
+        };
+        let value = decode_string(src)?;
+        Ok((name, value))
+    }
+
+    fn insert(&mut self, field: HeaderField) {
+        let entry_size = field.0.len() + field.1.len() + ENTRY_OVERHEAD;
+        if entry_size > self.max_size {
+            // An entry larger than the table empties it
+            // 大于表的条目会清空该表
+            self.dynamic.clear();
+            self.size = 0;
+            return;
+        }
+        self.evict(entry_size);
+        self.size += entry_size;
+        self.dynamic.push_front(field);
+    }
+
+    /// Evict entries until `additional` bytes fit
+    /// 驱逐条目直到能容纳 `additional` 字节
+    fn evict(&mut self, additional: usize) {
+        while self.size + additional > self.max_size {
+            match self.dynamic.pop_back() {
+                Some((name, value)) => self.size -= name.len() + value.len() + ENTRY_OVERHEAD,
+                None => break,
+            }
+        }
+    }
+}
+
+/// HPACK encoder
+/// HPACK 编码器
+#[derive(Debug, Default)]
+pub struct Encoder;
+
+impl Encoder {
+    /// Create an encoder
+    /// 创建编码器
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Encode header fields into a header block
+    /// 将头字段编码为头块
+    pub fn encode<'a, I>(&mut self, fields: I, dst: &mut Vec<u8>)
+    where
+        I: IntoIterator<Item = (&'a [u8], &'a [u8])>,
+    {
+        for (name, value) in fields {
+            match static_index(name, value) {
+                StaticMatch::Field(index) => encode_int(dst, index, 7, 0x80),
+                StaticMatch::Name(index) => {
+                    // Literal without indexing, indexed name
+                    // 不索引的字面量，索引名称
+                    encode_int(dst, index, 4, 0x00);
+                    encode_string(dst, value);
+                },
+                StaticMatch::None => {
+                    // Literal without indexing, new name
+                    // 不索引的字面量，新名称
+                    dst.push(0x00);
+                    encode_string(dst, name);
+                    encode_string(dst, value);
+                },
+            }
+        }
+    }
+}
+
+/// Result of looking a header up in the static table
+/// 在静态表中查找头的结果
+enum StaticMatch {
+    Field(usize),
+    Name(usize),
+    None,
+}
+
+fn static_index(name: &[u8], value: &[u8]) -> StaticMatch {
+    let mut found = StaticMatch::None;
+    for (i, &(n, v)) in STATIC_TABLE.iter().enumerate() {
+        if n.as_bytes() != name {
+            continue;
+        }
+        if v.as_bytes() == value {
+            return StaticMatch::Field(i + 1);
+        }
+        if matches!(found, StaticMatch::None) {
+            found = StaticMatch::Name(i + 1);
+        }
+    }
+    found
+}
+
+/// Decode a prefixed integer (RFC 7541 Section 5.1)
+/// 解码带前缀的整数（RFC 7541 第5.1节）
+fn decode_int(src: &mut &[u8], prefix: u8) -> Result<usize, Http2Error> {
+    let mask = (1u8 << prefix) - 1;
+    let (&first, rest) = src
+        .split_first()
+        .ok_or_else(|| compression_error("truncated integer"))?;
+    *src = rest;
+
+    let mut value = usize::from(first & mask);
+    if value < usize::from(mask) {
+        return Ok(value);
+    }
+
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = src
+            .split_first()
+            .ok_or_else(|| compression_error("truncated integer"))?;
+        *src = rest;
+        if shift > 28 {
+            return Err(compression_error("integer overflow"));
+        }
+        value += usize::from(byte & 0x7f) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+}
+
+/// Encode a prefixed integer with the given leading bits
+/// 使用给定的前导位编码带前缀的整数
+fn encode_int(dst: &mut Vec<u8>, mut value: usize, prefix: u8, flags: u8) {
+    let mask = (1usize << prefix) - 1;
+    if value < mask {
+        dst.push(flags | value as u8);
+        return;
+    }
+    dst.push(flags | mask as u8);
+    value -= mask;
+    while value >= 0x80 {
+        dst.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    dst.push(value as u8);
+}
+
+/// Decode a string literal, Huffman-coded or raw
+/// 解码字符串字面量（霍夫曼编码或原始）
+fn decode_string(src: &mut &[u8]) -> Result<Bytes, Http2Error> {
+    let huffman = src.first().is_some_and(|b| b & 0x80 != 0);
+    let len = decode_int(src, 7)?;
+    if src.len() < len {
+        return Err(compression_error("truncated string"));
+    }
+    let (data, rest) = src.split_at(len);
+    *src = rest;
+
+    if huffman {
+        Ok(Bytes::from(huffman::decode(data)?))
+    } else {
+        Ok(Bytes::copy_from_slice(data))
+    }
+}
+
+/// Encode a string literal, Huffman-coding it when that is shorter
+/// 编码字符串字面量，当霍夫曼编码更短时使用它
+fn encode_string(dst: &mut Vec<u8>, value: &[u8]) {
+    let huffman_len = huffman::encoded_len(value);
+    if huffman_len < value.len() {
+        encode_int(dst, huffman_len, 7, 0x80);
+        huffman::encode(value, dst);
+    } else {
+        encode_int(dst, value.len(), 7, 0x00);
+        dst.extend_from_slice(value);
+    }
+}
+
+fn compression_error(msg: &str) -> Http2Error {
+    Http2Error::CompressionError(format!("HPACK: {}", msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        let s: String = s.split_whitespace().collect();
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn fields(decoded: &[HeaderField]) -> Vec<(&str, &str)> {
+        decoded
+            .iter()
+            .map(|(n, v)| (std::str::from_utf8(n).unwrap(), std::str::from_utf8(v).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_integer_roundtrip() {
+        // RFC 7541 C.1.2: 1337 with a 5-bit prefix
+        let mut out = Vec::new();
+        encode_int(&mut out, 1337, 5, 0);
+        assert_eq!(out, [0x1f, 0x9a, 0x0a]);
+        assert_eq!(decode_int(&mut &out[..], 5).unwrap(), 1337);
+
+        assert!(decode_int(&mut &[0x1f, 0x9a][..], 5).is_err());
+        assert!(decode_int(&mut &[0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01][..], 7).is_err());
+    }
+
+    #[test]
+    fn test_rfc7541_c3_requests_without_huffman() {
+        let mut decoder = Decoder::new(4096);
+
+        let first = decoder
+            .decode(&hex("828684410f7777772e6578616d706c652e636f6d"))
+            .unwrap();
+        assert_eq!(
+            fields(&first),
+            [
+                (":method", "GET"),
+                (":scheme", "http"),
+                (":path", "/"),
+                (":authority", "www.example.com")
+            ]
+        );
+        assert_eq!(decoder.size, 57);
+
+        let second = decoder
+            .decode(&hex("828684be58086e6f2d6361636865"))
+            .unwrap();
+        assert_eq!(fields(&second)[3], (":authority", "www.example.com"));
+        assert_eq!(fields(&second)[4], ("cache-control", "no-cache"));
+        assert_eq!(decoder.size, 110);
+
+        let third = decoder
+            .decode(&hex("828785bf400a637573746f6d2d6b65790c637573746f6d2d76616c7565"))
+            .unwrap();
+        assert_eq!(fields(&third)[1], (":scheme", "https"));
+        assert_eq!(fields(&third)[2], (":path", "/index.html"));
+        assert_eq!(fields(&third)[4], ("custom-key", "custom-value"));
+        assert_eq!(decoder.size, 164);
+    }
+
+    #[test]
+    fn test_rfc7541_c4_requests_with_huffman() {
+        let mut decoder = Decoder::new(4096);
+
+        let first = decoder
+            .decode(&hex("828684418cf1e3c2e5f23a6ba0ab90f4ff"))
+            .unwrap();
+        assert_eq!(fields(&first)[3], (":authority", "www.example.com"));
+
+        let second = decoder.decode(&hex("828684be5886a8eb10649cbf")).unwrap();
+        assert_eq!(fields(&second)[4], ("cache-control", "no-cache"));
+
+        let third = decoder
+            .decode(&hex("828785bf408825a849e95ba97d7f8925a849e95bb8e8b4bf"))
+            .unwrap();
+        assert_eq!(fields(&third)[4], ("custom-key", "custom-value"));
+        assert_eq!(decoder.size, 164);
+    }
+
+    #[test]
+    fn test_eviction_and_size_update() {
+        // RFC 7541 C.5.1 with a 256-byte table
+        let mut decoder = Decoder::new(256);
+        decoder
+            .decode(&hex(
+                "4803333032580770726976617465611d4d6f6e2c203231204f637420323031332032303a31333a323120474d546e1768747470733a2f2f7777772e6578616d706c652e636f6d",
+            ))
+            .unwrap();
+        assert_eq!(decoder.size, 222);
+
+        // C.5.2 evicts ":status: 302"
+        let second = decoder.decode(&hex("4803333037c1c0bf")).unwrap();
+        assert_eq!(fields(&second)[0], (":status", "307"));
+        assert_eq!(decoder.size, 222);
+        assert_eq!(decoder.dynamic.len(), 4);
+
+        // A size update to zero empties the table
+        decoder.decode(&[0x20]).unwrap();
+        assert_eq!(decoder.size, 0);
+        assert!(decoder.decode(&[0x3f, 0xe1, 0x1f]).is_err());
+        assert!(decoder.decode(&[0x82, 0x20]).is_err());
+    }
+
+    #[test]
+    fn test_encoder_roundtrip() {
+        let headers: [(&[u8], &[u8]); 4] = [
+            (b":status", b"200"),
+            (b":status", b"201"),
+            (b"content-type", b"text/plain"),
+            (b"x-request-id", b"abc123"),
+        ];
+        let mut block = Vec::new();
+        Encoder::new().encode(headers, &mut block);
+
+        // ":status: 200" is a single indexed byte
+        assert_eq!(block[0], 0x88);
+
+        let decoded = Decoder::new(4096).decode(&block).unwrap();
+        assert_eq!(
+            fields(&decoded),
+            [
+                (":status", "200"),
+                (":status", "201"),
+                ("content-type", "text/plain"),
+                ("x-request-id", "abc123")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_index() {
+        let mut decoder = Decoder::new(4096);
+        assert!(decoder.decode(&[0x80]).is_err());
+        assert!(decoder.decode(&[0xbe]).is_err());
+    }
+}
@@ -25,16 +25,27 @@
 //! # Example / 示例
 //!
 //! ```rust,no_run,ignore
-//! use nexus_http::http2::{Http2Config, ServerConnection};
+//! use nexus_http::Server;
+//! use nexus_http::http2::Http2Config;
 //!
 //! let config = Http2Config::new()
 //!     .with_max_streams(1000)
 //!     .with_header_table_size(4096);
+//!
+//! // Serve h2c (prior knowledge and `Upgrade: h2c`) next to HTTP/1.1
+//! // 在 HTTP/1.1 之外提供 h2c（先验知识和 `Upgrade: h2c`）
+//! Server::bind("0.0.0.0:8080").http2(config).run(handler).await?;
 //! ```
 
 #![warn(missing_docs)]
 #![warn(unreachable_pub)]
 
+pub(crate) mod connection;
+pub mod flow;
+pub mod frame;
+pub mod hpack;
+mod huffman;
+
 use crate::Error;
 
 /// HTTP/2 frame types
@@ -173,9 +184,9 @@ pub enum ErrorCode {
     /// 流已取消
     Cancel = 0x8,
 
-    /// Stream state error
-    /// 流状态错误
-    StreamStateError = 0x9,
+    /// Header compression state could not be maintained
+    /// 无法维护头压缩状态
+    CompressionError = 0x9,
 
     /// Error processing TLS
     /// 处理TLS错误
@@ -208,7 +219,7 @@ impl ErrorCode {
             0x6 => Some(Self::FrameSizeError),
             0x7 => Some(Self::RefusedStream),
             0x8 => Some(Self::Cancel),
-            0x9 => Some(Self::StreamStateError),
+            0x9 => Some(Self::CompressionError),
             0xa => Some(Self::ConnectError),
             0xb => Some(Self::EnhanceYourCalm),
             0xc => Some(Self::InadequateSecurity),
@@ -236,7 +247,7 @@ impl ErrorCode {
             Self::FrameSizeError => "Frame size error",
             Self::RefusedStream => "Stream not processed",
             Self::Cancel => "Stream cancelled",
-            Self::StreamStateError => "Stream state error",
+            Self::CompressionError => "Compression state not updated",
             Self::ConnectError => "Error processing TLS",
             Self::EnhanceYourCalm => "Stream limit exceeded",
             Self::InadequateSecurity => "Negotiated parameters not adequate",
@@ -626,11 +637,30 @@ pub enum Http2Error {
         message: String,
     },
 
+    /// Header compression error
+    /// 头压缩错误
+    CompressionError(String),
+
     /// IO error
     /// IO错误
     IoError(String),
 }
 
+impl Http2Error {
+    /// Error code to report in RST_STREAM or GOAWAY
+    /// 在 RST_STREAM 或 GOAWAY 中报告的错误码
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidFrame(_) => ErrorCode::FrameSizeError,
+            Self::ProtocolError(_) | Self::SettingsError(_) => ErrorCode::ProtocolError,
+            Self::FlowControlError(_) => ErrorCode::FlowControlError,
+            Self::StreamError { error_code, .. } => *error_code,
+            Self::CompressionError(_) => ErrorCode::CompressionError,
+            Self::IoError(_) => ErrorCode::InternalError,
+        }
+    }
+}
+
 impl std::fmt::Display for Http2Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -651,6 +681,7 @@ impl std::fmt::Display for Http2Error {
                     message
                 )
             },
+            Self::CompressionError(msg) => write!(f, "Compression error: {}", msg),
             Self::IoError(msg) => write!(f, "IO error: {}", msg),
         }
     }
@@ -786,6 +817,16 @@ mod tests {
         assert!(err.to_string().contains("Stream 1"));
     }
 
+    #[test]
+    fn test_http2_error_code() {
+        let err = Http2Error::InvalidFrame("too large".to_string());
+        assert_eq!(err.error_code(), ErrorCode::FrameSizeError);
+
+        let err = Http2Error::CompressionError("bad index".to_string());
+        assert_eq!(err.error_code(), ErrorCode::CompressionError);
+        assert!(err.to_string().contains("Compression"));
+    }
+
     #[test]
     fn test_frame_size_clamp() {
         let config = Http2Config::new().with_max_frame_size(1000);
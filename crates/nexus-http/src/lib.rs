@@ -39,6 +39,7 @@ pub mod api_response;
 pub mod body;
 pub mod builder;
 pub mod conn;
+pub mod csv;
 pub mod error;
 pub mod exception;
 pub mod ext;
@@ -52,6 +53,9 @@ pub mod server;
 pub mod service;
 pub mod sse;
 pub mod status;
+pub mod testing;
+pub mod tls;
+mod upgrade;
 pub mod validation;
 pub mod websocket;
 
@@ -59,9 +63,10 @@ pub mod websocket;
 // 重新导出以便使用
 pub use api_response::{IntoApiResponse, PageResponse, ResultCode};
 pub use api_response::ApiResponse;
-pub use body::{Body, EmptyBody, FullBody, HttpBody};
+pub use body::{Body, EmptyBody, FullBody, HttpBody, StreamBody, Trailers};
 pub use builder::{Uri, UriBuilder};
 pub use conn::{Connection, ConnectionState};
+pub use csv::CsvExport;
 pub use error::{Error, Result};
 pub use exception::{
     ApplicationException, ErrorResponse, ExceptionHandlerRegistry, FieldError,
@@ -79,8 +84,10 @@ pub use request::Request;
 pub use response::{BodyBuilder, Response};
 pub use server::Server;
 pub use service::HttpService;
-pub use sse::{Event, Sse, SseKeepAlive};
+pub use sse::{Event, Sse, SseKeepAlive, SseStream};
 pub use status::StatusCode;
+pub use testing::{TestClient, TestRequest, TestResponse};
+pub use tls::{PeerCertificate, TlsAcceptor, TlsConfig};
 pub use multipart::{
     FileSizeLimits, FromMultipart, MultipartFile, MultipartData, MultipartForm,
     media_type_for_extension, validate_content_type, validate_extension,
@@ -320,10 +327,7 @@ impl FromRequest for () {
 
 impl FromRequest for String {
     async fn from_request(req: &Request) -> Result<Self> {
-        let body = req
-            .body()
-            .as_bytes()
-            .ok_or_else(|| Error::InvalidRequest("Request body is not text".to_string()))?;
+        let body = req.body().clone().collect().await?;
 
         String::from_utf8(body.to_vec())
             .map_err(|_| Error::InvalidRequest("Invalid UTF-8 in body".to_string()))
@@ -332,22 +336,15 @@ impl FromRequest for String {
 
 impl FromRequest for Vec<u8> {
     async fn from_request(req: &Request) -> Result<Self> {
-        Ok(req
-            .body()
-            .as_bytes()
-            .map(|b| b.to_vec())
-            .unwrap_or_default())
+        Ok(req.body().clone().collect().await?.to_vec())
     }
 }
 
 impl<T: serde::de::DeserializeOwned> FromRequest for Json<T> {
     async fn from_request(req: &Request) -> Result<Self> {
-        let body = req
-            .body()
-            .as_bytes()
-            .ok_or_else(|| Error::InvalidRequest("Request body is not available".to_string()))?;
+        let body = req.body().clone().collect().await?;
 
-        serde_json::from_slice(body)
+        serde_json::from_slice(&body)
             .map(Json)
             .map_err(|e| Error::InvalidRequest(format!("Invalid JSON: {}", e)))
     }
@@ -1,9 +1,10 @@
 //! HTTP/1.1 protocol implementation
 //! HTTP/1.1 协议实现
 //!
-//! This module provides HTTP/1.1 parsing and serialization using httparse.
+//! This module provides HTTP/1.1 parsing and serialization using httparse, for
+//! both the server side (requests in, responses out) and the client side.
 //!
-//! 此模块使用 httparse 提供 HTTP/1.1 解析和序列化。
+//! 此模块使用 httparse 提供 HTTP/1.1 解析和序列化，同时支持服务端（读入请求、写出响应）和客户端。
 //!
 //! # Equivalent to Spring Boot / 等价于 Spring Boot
 //!
@@ -18,8 +19,14 @@ mod request;
 mod response;
 
 pub use context::{ConnectionContext, HttpVersion};
-pub use request::{RequestParser, parse_request};
-pub use response::{ResponseEncoder, encode_response};
+pub use request::{
+    BodyFrame, RequestParser, decode_chunked, encode_request, encode_request_head,
+    is_request_chunked, parse_request,
+};
+pub use response::{
+    LAST_CHUNK, ResponseEncoder, ResponseParser, encode_chunk, encode_head, encode_response,
+    is_chunked, parse_response,
+};
 
 /// Maximum header size (8KB)
 /// 最大头部大小 (8KB)
@@ -28,3 +35,7 @@ pub const MAX_HEADER_SIZE: usize = 8 * 1024;
 /// Maximum buffer size for reading
 /// 读取的最大缓冲区大小
 pub const MAX_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Interim response sent to clients waiting on `Expect: 100-continue`
+/// 发送给等待 `Expect: 100-continue` 的客户端的临时响应
+pub const CONTINUE_RESPONSE: &[u8] = b"HTTP/1.1 100 Continue\r\n\r\n";
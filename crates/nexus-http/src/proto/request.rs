@@ -2,7 +2,7 @@
 //! HTTP/1.1 请求解析
 
 use super::context::{ConnectionContext, HttpVersion};
-use crate::{Body, Error, Request, Result};
+use crate::{Body, Error, HttpBody, Request, Result, body::Trailers};
 use bytes::{Buf, Bytes, BytesMut};
 use httparse::Request as HttparseRequest;
 
@@ -30,11 +30,87 @@ use httparse::Request as HttparseRequest;
 /// let (request, used) = parse_request(data, &ctx)?;
 /// ```
 pub fn parse_request(data: &[u8], ctx: &ConnectionContext) -> Result<(Request, usize)> {
-    let mut headers = [httparse::EMPTY_HEADER; 64];
-    let mut req = HttparseRequest::new(&mut headers);
+    let head = parse_head(data)?;
+
+    // Read the body according to its framing
+    // 根据body的分帧方式读取body
+    let body_data = &data[head.len..];
+    let (body, trailers, body_len) = match head.framing {
+        BodyFraming::Empty => (Bytes::new(), None, 0),
+        BodyFraming::Length(len) => {
+            if len > ctx.max_buffer_size() {
+                return Err(Error::Custom(413, "Request body too large".to_string()));
+            }
+            if body_data.len() < len {
+                return Err(Error::IncompleteRequest);
+            }
+            (Bytes::copy_from_slice(&body_data[..len]), None, len)
+        },
+        BodyFraming::Chunked => {
+            let (body, trailers, used) = decode_chunked(body_data)?;
+            (body, Some(trailers), used)
+        },
+    };
 
-    // Parse the headers
-    let result = req.parse(data).map_err(|e| match e {
+    let head_len = head.len;
+    let mut request = build_request(head, Body::from(body))?;
+    if let Some(trailers) = trailers {
+        request.extensions_mut().insert(Trailers::new(trailers));
+    }
+
+    Ok((request, head_len + body_len))
+}
+
+/// Build a request from its parsed head and body
+/// 由解析后的请求头和body构建请求
+fn build_request(head: RequestHead, body: Body) -> Result<Request> {
+    let mut http_builder = http::Request::builder()
+        .method(head.method.as_str())
+        .uri(head.path.as_str())
+        .version(match head.version {
+            HttpVersion::Http10 => http::Version::HTTP_10,
+            HttpVersion::Http11 => http::Version::HTTP_11,
+        });
+    if let Some(headers) = http_builder.headers_mut() {
+        *headers = head.headers;
+    }
+
+    let http_request = http_builder
+        .body(body)
+        .map_err(|e| Error::InvalidRequest(format!("Failed to build request: {}", e)))?;
+
+    Ok(Request::new(http_request))
+}
+
+/// How the body of a request is delimited
+/// 请求body的分隔方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyFraming {
+    /// No body
+    Empty,
+    /// Body of a fixed length (Content-Length)
+    Length(usize),
+    /// Chunked transfer-encoding
+    Chunked,
+}
+
+/// Parsed request line and headers
+/// 解析后的请求行和头部
+struct RequestHead {
+    method: String,
+    path: String,
+    version: HttpVersion,
+    headers: http::HeaderMap,
+    framing: BodyFraming,
+    expect_continue: bool,
+    /// Length of the head in bytes, including the blank line
+    len: usize,
+}
+
+/// Map an httparse error to a request error
+/// 将 httparse 错误映射为请求错误
+fn map_httparse_error(e: httparse::Error) -> Error {
+    match e {
         httparse::Error::HeaderName => Error::InvalidRequest("Invalid header name".to_string()),
         httparse::Error::HeaderValue => Error::InvalidRequest("Invalid header value".to_string()),
         httparse::Error::NewLine => Error::InvalidRequest("Invalid newline".to_string()),
@@ -42,9 +118,17 @@ pub fn parse_request(data: &[u8], ctx: &ConnectionContext) -> Result<(Request, u
         httparse::Error::Token => Error::InvalidRequest("Invalid token".to_string()),
         httparse::Error::Version => Error::InvalidRequest("Invalid HTTP version".to_string()),
         httparse::Error::TooManyHeaders => Error::InvalidRequest("Too many headers".to_string()),
-    })?;
+    }
+}
+
+/// Parse the request line and headers, and work out the body framing
+/// 解析请求行和头部，并确定body的分帧方式
+fn parse_head(data: &[u8]) -> Result<RequestHead> {
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut req = HttparseRequest::new(&mut headers);
 
-    let bytes_used = match result {
+    // Parse the headers
+    let len = match req.parse(data).map_err(map_httparse_error)? {
         httparse::Status::Complete(n) => n,
         httparse::Status::Partial => {
             return Err(Error::IncompleteRequest);
@@ -68,84 +152,343 @@ pub fn parse_request(data: &[u8], ctx: &ConnectionContext) -> Result<(Request, u
         None => HttpVersion::Http11,
     };
 
-    // Build the http::Request
-    let mut http_builder = http::Request::builder().method(method).uri(path);
-
-    // Add headers
+    let mut header_map = http::HeaderMap::with_capacity(req.headers.len());
     for header in req.headers.iter() {
-        http_builder = http_builder.header(header.name, header.value);
+        let name = http::HeaderName::from_bytes(header.name.as_bytes())
+            .map_err(|_| Error::InvalidRequest("Invalid header name".to_string()))?;
+        let value = http::HeaderValue::from_bytes(header.value)
+            .map_err(|_| Error::InvalidRequest("Invalid header value".to_string()))?;
+        header_map.append(name, value);
     }
 
-    // Build the request with empty body for now (body will be read separately)
-    let http_request = http_builder
-        .body(Body::empty())
-        .map_err(|e| Error::InvalidRequest(format!("Failed to build request: {}", e)))?;
+    let framing = body_framing(&header_map)?;
 
-    // Convert to our Request type
-    let mut request = Request::new(http_request);
-
-    // Extract the body if present
-    if bytes_used < data.len() {
-        let body_data = &data[bytes_used..];
-        if !body_data.is_empty() {
-            // Update the request with the actual body
-            let content_length = request
-                .header("content-length")
-                .and_then(|v| v.parse::<usize>().ok())
-                .unwrap_or(body_data.len());
-
-            let actual_body_len = body_data.len().min(content_length);
-            let body = Body::from(Bytes::copy_from_slice(&body_data[..actual_body_len]));
-
-            // Rebuild with body
-            let mut http_builder = http::Request::builder()
-                .method(request.inner().method().clone())
-                .uri(request.inner().uri().clone());
-
-            for (name, value) in request.inner().headers().iter() {
-                http_builder = http_builder.header(name, value);
-            }
+    let expect_continue = match header_map.get(http::header::EXPECT) {
+        // Expectations are an HTTP/1.1 feature; HTTP/1.0 servers must ignore them
+        // Expect 是 HTTP/1.1 特性；HTTP/1.0 服务器必须忽略它
+        Some(_) if version == HttpVersion::Http10 => false,
+        Some(value) if value.as_bytes().eq_ignore_ascii_case(b"100-continue") => true,
+        Some(_) => return Err(Error::Custom(417, "Expectation Failed".to_string())),
+        None => false,
+    };
 
-            let http_request = http_builder.body(body).map_err(|e| {
-                Error::InvalidRequest(format!("Failed to build request with body: {}", e))
-            })?;
+    Ok(RequestHead {
+        method: method.to_string(),
+        path: path.to_string(),
+        version,
+        headers: header_map,
+        framing,
+        expect_continue,
+        len,
+    })
+}
 
-            request = Request::new(http_request);
+/// Determine how the request body is delimited (RFC 9112 section 6.3)
+/// 确定请求body的分隔方式（RFC 9112 第 6.3 节）
+fn body_framing(headers: &http::HeaderMap) -> Result<BodyFraming> {
+    let mut transfer_encodings = headers
+        .get_all(http::header::TRANSFER_ENCODING)
+        .iter()
+        .peekable();
+    if transfer_encodings.peek().is_some() {
+        // A message with both headers could be used for request smuggling
+        // 同时带有两个头部的消息可能被用于请求走私
+        if headers.contains_key(http::header::CONTENT_LENGTH) {
+            return Err(Error::InvalidRequest(
+                "Both Transfer-Encoding and Content-Length present".to_string(),
+            ));
         }
+        let last = transfer_encodings
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .last();
+        return match last {
+            Some(coding) if coding.eq_ignore_ascii_case("chunked") => Ok(BodyFraming::Chunked),
+            _ => Err(Error::Custom(501, "Unsupported transfer encoding".to_string())),
+        };
     }
 
-    // Update context based on headers
-    let mut updated_ctx = ctx.clone();
-    updated_ctx.set_version(version);
-    updated_ctx.update_keep_alive_from_header(
-        req.headers
-            .iter()
-            .find(|h| h.name.eq_ignore_ascii_case("connection"))
-            .map(|h| std::str::from_utf8(h.value).unwrap_or("")),
-    );
-    // Store context in request extensions if needed
+    let mut length: Option<usize> = None;
+    for value in headers.get_all(http::header::CONTENT_LENGTH) {
+        let parsed = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .ok_or_else(|| Error::InvalidRequest("Invalid Content-Length".to_string()))?;
+        if length.is_some_and(|l| l != parsed) {
+            return Err(Error::InvalidRequest("Conflicting Content-Length".to_string()));
+        }
+        length = Some(parsed);
+    }
 
-    Ok((request, bytes_used))
+    Ok(match length {
+        Some(0) | None => BodyFraming::Empty,
+        Some(len) => BodyFraming::Length(len),
+    })
+}
+
+/// Decode a chunked body, returning the data, the trailer fields and the bytes consumed
+/// 解码分块body，返回数据、尾部字段和消耗的字节数
+///
+/// Returns [`Error::IncompleteRequest`] until the terminating chunk and trailers
+/// have been received. Chunk extensions are ignored.
+///
+/// 在收到终止块和尾部之前返回 [`Error::IncompleteRequest`]。块扩展将被忽略。
+pub fn decode_chunked(data: &[u8]) -> Result<(Bytes, http::HeaderMap, usize)> {
+    let mut body = BytesMut::new();
+    let mut pos = 0;
+
+    loop {
+        // Chunk size line: hex size, optional ";ext", CRLF
+        // 块大小行：十六进制大小，可选的 ";ext"，CRLF
+        let line_end = find_crlf(&data[pos..]).ok_or(Error::IncompleteRequest)?;
+        let size = parse_chunk_size(&data[pos..pos + line_end])?;
+        pos += line_end + 2;
+
+        if size == 0 {
+            break;
+        }
+
+        // The size comes from the peer, so every offset derived from it is checked
+        // 大小来自对端，因此由它得出的每个偏移量都需检查
+        let chunk_end = pos.checked_add(size).ok_or_else(invalid_chunk_size)?;
+        let next = chunk_end.checked_add(2).ok_or_else(invalid_chunk_size)?;
+        if data.len() < next {
+            return Err(Error::IncompleteRequest);
+        }
+        if &data[chunk_end..next] != b"\r\n" {
+            return Err(Error::InvalidRequest("Missing CRLF after chunk".to_string()));
+        }
+        body.extend_from_slice(&data[pos..chunk_end]);
+        pos = next;
+    }
+
+    let (trailers, used) = parse_trailers(&data[pos..])?.ok_or(Error::IncompleteRequest)?;
+
+    Ok((body.freeze(), trailers, pos + used))
+}
+
+/// Parse the trailer section after the last chunk, terminated by an empty line
+/// 解析最后一个块之后的尾部部分，以空行结束
+///
+/// Returns `Ok(None)` until the whole section has been received.
+/// 在收到整个部分之前返回 `Ok(None)`。
+fn parse_trailers(data: &[u8]) -> Result<Option<(http::HeaderMap, usize)>> {
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let (used, fields) =
+        match httparse::parse_headers(data, &mut headers).map_err(map_httparse_error)? {
+            httparse::Status::Complete(parsed) => parsed,
+            httparse::Status::Partial => return Ok(None),
+        };
+
+    let mut trailers = http::HeaderMap::new();
+    for field in fields {
+        let name = http::HeaderName::from_bytes(field.name.as_bytes())
+            .map_err(|_| Error::InvalidRequest("Invalid trailer name".to_string()))?;
+        let value = http::HeaderValue::from_bytes(field.value)
+            .map_err(|_| Error::InvalidRequest("Invalid trailer value".to_string()))?;
+        trailers.append(name, value);
+    }
+
+    Ok(Some((trailers, used)))
+}
+
+/// Parse the hex size at the start of a chunk size line, ignoring chunk extensions
+/// 解析块大小行开头的十六进制大小，忽略块扩展
+fn parse_chunk_size(line: &[u8]) -> Result<usize> {
+    let size = line
+        .split(|&b| b == b';')
+        .next()
+        .unwrap_or_default()
+        .trim_ascii();
+    // `from_str_radix` alone would also accept a leading sign
+    // 仅靠 `from_str_radix` 还会接受前导符号
+    if size.is_empty() || !size.iter().all(u8::is_ascii_hexdigit) {
+        return Err(invalid_chunk_size());
+    }
+    let size = std::str::from_utf8(size).map_err(|_| invalid_chunk_size())?;
+    usize::from_str_radix(size, 16).map_err(|_| invalid_chunk_size())
+}
+
+/// Error for a malformed or out-of-range chunk size
+/// 格式错误或超出范围的块大小错误
+fn invalid_chunk_size() -> Error {
+    Error::InvalidRequest("Invalid chunk size".to_string())
+}
+
+/// Find the position of the next CRLF
+/// 查找下一个 CRLF 的位置
+fn find_crlf(data: &[u8]) -> Option<usize> {
+    data.windows(2).position(|w| w == b"\r\n")
+}
+
+/// Encode the request line and headers of an outgoing HTTP/1.1 request
+/// 编码发出的 HTTP/1.1 请求的请求行和头部
+///
+/// The request target is written in origin-form and a `host` header is derived
+/// from the URI authority when missing. A streaming body without a
+/// `Content-Length` header gets `transfer-encoding: chunked`; its data must then
+/// be written with [`super::encode_chunk`] followed by [`super::LAST_CHUNK`].
+///
+/// 请求目标以 origin-form 写出，缺少 `host` 头时从 URI 的 authority 推导。
+/// 没有 `Content-Length` 头的流式body会得到 `transfer-encoding: chunked`；
+/// 其数据随后必须用 [`super::encode_chunk`] 写出，并以 [`super::LAST_CHUNK`] 结尾。
+pub fn encode_request_head(request: &Request, ctx: &ConnectionContext) -> Result<Bytes> {
+    let inner = request.inner();
+    let target = inner.uri().path_and_query().map_or("/", |pq| pq.as_str());
+    let target = if target.is_empty() { "/" } else { target };
+
+    let mut buffer = BytesMut::with_capacity(1024);
+    buffer.extend_from_slice(inner.method().as_str().as_bytes());
+    buffer.extend_from_slice(b" ");
+    buffer.extend_from_slice(target.as_bytes());
+    buffer.extend_from_slice(b" ");
+    buffer.extend_from_slice(ctx.version().as_str().as_bytes());
+    buffer.extend_from_slice(b"\r\n");
+
+    let headers = inner.headers();
+    if !headers.contains_key(http::header::HOST) {
+        let authority = inner
+            .uri()
+            .authority()
+            .ok_or_else(|| Error::InvalidRequest("Missing Host header".to_string()))?;
+        write_field(&mut buffer, "host", authority.as_str().as_bytes());
+    }
+    for (name, value) in headers {
+        write_field(&mut buffer, name.as_str(), value.as_bytes());
+    }
+
+    // Framing headers: streaming bodies are chunked, buffered bodies get a length
+    // 分帧头：流式body分块传输，缓冲的body带长度
+    let framed = headers.contains_key(http::header::CONTENT_LENGTH)
+        || headers.contains_key(http::header::TRANSFER_ENCODING);
+    if !framed {
+        if is_request_chunked(request, ctx) {
+            write_field(&mut buffer, "transfer-encoding", b"chunked");
+        } else {
+            let len = request.body().as_bytes().map_or(0, <[u8]>::len);
+            let expects_body = matches!(
+                *inner.method(),
+                http::Method::POST | http::Method::PUT | http::Method::PATCH
+            );
+            if len > 0 || expects_body {
+                write_field(&mut buffer, "content-length", len.to_string().as_bytes());
+            }
+        }
+    }
+
+    if !headers.contains_key(http::header::CONNECTION) {
+        match (ctx.keep_alive(), ctx.version()) {
+            (false, HttpVersion::Http11) => write_field(&mut buffer, "connection", b"close"),
+            (true, HttpVersion::Http10) => write_field(&mut buffer, "connection", b"keep-alive"),
+            _ => {},
+        }
+    }
+
+    buffer.extend_from_slice(b"\r\n");
+    Ok(buffer.freeze())
+}
+
+/// Encode an outgoing HTTP/1.1 request, including a buffered body
+/// 编码发出的 HTTP/1.1 请求，包括缓冲的body
+///
+/// Only the head is returned for streaming bodies.
+/// 对于流式body只返回头部。
+pub fn encode_request(request: &Request, ctx: &ConnectionContext) -> Result<Bytes> {
+    let head = encode_request_head(request, ctx)?;
+    match request.body().as_bytes() {
+        Some(body) if !body.is_empty() => {
+            let mut result = BytesMut::with_capacity(head.len() + body.len());
+            result.extend_from_slice(&head);
+            result.extend_from_slice(body);
+            Ok(result.freeze())
+        },
+        _ => Ok(head),
+    }
+}
+
+/// Check whether the body of a request is written with chunked transfer-encoding
+/// 检查请求body是否以分块传输编码写出
+pub fn is_request_chunked(request: &Request, ctx: &ConnectionContext) -> bool {
+    if !request.body().is_stream() {
+        return false;
+    }
+    let headers = request.headers();
+    if let Some(te) = headers.get(http::header::TRANSFER_ENCODING) {
+        return te
+            .to_str()
+            .is_ok_and(|te| te.to_ascii_lowercase().contains("chunked"));
+    }
+    !headers.contains_key(http::header::CONTENT_LENGTH) && ctx.version() == HttpVersion::Http11
+}
+
+/// Append one `name: value` header line
+/// 追加一行 `name: value` 头部
+fn write_field(buffer: &mut BytesMut, name: &str, value: &[u8]) {
+    buffer.extend_from_slice(name.as_bytes());
+    buffer.extend_from_slice(b": ");
+    buffer.extend_from_slice(value);
+    buffer.extend_from_slice(b"\r\n");
+}
+
+/// A piece of a streamed request body, produced by [`RequestParser::decode_body`]
+/// 流式请求body的一部分，由 [`RequestParser::decode_body`] 产生
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BodyFrame {
+    /// Body data
+    /// body数据
+    Data(Bytes),
+    /// End of the body, with the trailer fields of a chunked body
+    /// body结束，附带分块body的尾部字段
+    End(http::HeaderMap),
+}
+
+/// Progress through a request body that is being streamed
+/// 流式读取请求body的进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyState {
+    /// Bytes left of a Content-Length body
+    Length(usize),
+    /// Waiting for a chunk size line
+    ChunkSize,
+    /// Bytes left of the current chunk
+    ChunkData(usize),
+    /// Waiting for the CRLF that ends a chunk
+    ChunkEnd,
+    /// Waiting for the trailer section after the last chunk
+    Trailers,
 }
 
 /// HTTP request parser with state
 /// 带状态的 HTTP 请求解析器
+///
+/// A body that arrives together with its head is handed over in the request.
+/// Otherwise [`parse`](Self::parse) returns the request with an empty body and
+/// the rest is read frame by frame with [`decode_body`](Self::decode_body), so
+/// the buffer limit applies to a head or chunk header rather than to the body.
+///
+/// 与请求头一起到达的body会随请求一并交出。否则 [`parse`](Self::parse)
+/// 返回body为空的请求，其余部分通过 [`decode_body`](Self::decode_body)
+/// 逐帧读取，因此缓冲区限制作用于请求头或块头，而不是整个body。
 #[derive(Debug)]
 pub struct RequestParser {
     /// Buffer for incoming data
     buffer: BytesMut,
     /// Connection context
     ctx: ConnectionContext,
+    /// Body being streamed, `None` while waiting for a request head
+    body: Option<BodyState>,
+    /// Whether the client waits for `100 Continue` before sending the body
+    expect_continue: bool,
 }
 
 impl RequestParser {
     /// Create a new request parser
     /// 创建新的请求解析器
     pub fn new() -> Self {
-        Self {
-            buffer: BytesMut::with_capacity(8192),
-            ctx: ConnectionContext::new(),
-        }
+        Self::with_context(ConnectionContext::new())
     }
 
     /// Create a new request parser with custom context
@@ -154,11 +497,16 @@ impl RequestParser {
         Self {
             buffer: BytesMut::with_capacity(ctx.max_buffer_size()),
             ctx,
+            body: None,
+            expect_continue: false,
         }
     }
 
     /// Feed data to the parser
     /// 向解析器提供数据
+    ///
+    /// Fails once more than the buffer limit is waiting to be parsed.
+    /// 当等待解析的数据超过缓冲区限制时失败。
     pub fn feed(&mut self, data: &[u8]) -> Result<()> {
         if self.buffer.len() + data.len() > self.ctx.max_buffer_size() {
             return Err(Error::InvalidRequest("Buffer overflow".to_string()));
@@ -170,28 +518,162 @@ impl RequestParser {
     /// Try to parse a request from the buffered data
     /// 尝试从缓冲数据解析请求
     ///
-    /// Returns `Ok(None)` if more data is needed.
+    /// Returns `Ok(None)` if more data is needed, or while the body of the
+    /// previous request is still being read. When the body has not fully arrived
+    /// the request carries an empty body and [`reading_body`](Self::reading_body)
+    /// turns `true`.
+    ///
+    /// 需要更多数据或上一个请求的body仍在读取时返回 `Ok(None)`。当body尚未
+    /// 完全到达时，请求携带空body且 [`reading_body`](Self::reading_body) 变为 `true`。
     pub fn parse(&mut self) -> Result<Option<(Request, usize)>> {
-        match parse_request(&self.buffer, &self.ctx) {
-            Ok((req, used)) => {
-                self.buffer.advance(used);
-                Ok(Some((req, used)))
+        if self.body.is_some() {
+            return Ok(None);
+        }
+        let head = match parse_head(&self.buffer) {
+            Ok(head) => head,
+            Err(Error::IncompleteRequest) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        // A body that is already buffered is handed over in one piece
+        // 已缓冲的body一次性交出
+        let body_data = &self.buffer[head.len..];
+        let buffered = match head.framing {
+            BodyFraming::Empty => Some((Bytes::new(), None, 0)),
+            BodyFraming::Length(len) if body_data.len() >= len => {
+                Some((Bytes::copy_from_slice(&body_data[..len]), None, len))
             },
-            Err(Error::IncompleteRequest) => Ok(None),
-            Err(e) => Err(e),
+            BodyFraming::Length(_) => None,
+            BodyFraming::Chunked => match decode_chunked(body_data) {
+                Ok((body, trailers, used)) => Some((body, Some(trailers), used)),
+                Err(Error::IncompleteRequest) => None,
+                Err(e) => return Err(e),
+            },
+        };
+
+        let head_len = head.len;
+        let framing = head.framing;
+        let expect_continue = head.expect_continue;
+        let (request, used) = if let Some((body, trailers, body_len)) = buffered {
+            let mut request = build_request(head, Body::from(body))?;
+            if let Some(trailers) = trailers {
+                request.extensions_mut().insert(Trailers::new(trailers));
+            }
+            (request, head_len + body_len)
+        } else {
+            self.body = Some(match framing {
+                BodyFraming::Length(len) => BodyState::Length(len),
+                _ => BodyState::ChunkSize,
+            });
+            self.expect_continue = expect_continue && self.buffer.len() == head_len;
+            (build_request(head, Body::empty())?, head_len)
+        };
+        self.buffer.advance(used);
+        Ok(Some((request, used)))
+    }
+
+    /// Check whether the body of the last parsed request is still being read
+    /// 检查上一个已解析请求的body是否仍在读取
+    pub fn reading_body(&self) -> bool {
+        self.body.is_some()
+    }
+
+    /// Decode the next frame of the body being read from the buffered data
+    /// 从缓冲数据解码正在读取的body的下一帧
+    ///
+    /// Returns `Ok(None)` if more data is needed. Data is handed over as soon as it
+    /// is buffered, even part way through a chunk. After [`BodyFrame::End`] the
+    /// parser goes back to parsing request heads.
+    ///
+    /// 需要更多数据时返回 `Ok(None)`。数据一经缓冲即交出，即使处于块的中间。
+    /// 在 [`BodyFrame::End`] 之后解析器恢复解析请求头。
+    pub fn decode_body(&mut self) -> Result<Option<BodyFrame>> {
+        loop {
+            let Some(state) = self.body else {
+                return Ok(None);
+            };
+            match state {
+                BodyState::Length(0) => {
+                    self.body = None;
+                    return Ok(Some(BodyFrame::End(http::HeaderMap::new())));
+                },
+                BodyState::Length(left) | BodyState::ChunkData(left) => {
+                    if self.buffer.is_empty() {
+                        return Ok(None);
+                    }
+                    let data = self.buffer.split_to(left.min(self.buffer.len())).freeze();
+                    let left = left - data.len();
+                    self.body = Some(match state {
+                        BodyState::Length(_) => BodyState::Length(left),
+                        _ if left == 0 => BodyState::ChunkEnd,
+                        _ => BodyState::ChunkData(left),
+                    });
+                    return Ok(Some(BodyFrame::Data(data)));
+                },
+                BodyState::ChunkSize => {
+                    let Some(line_end) = find_crlf(&self.buffer) else {
+                        return Ok(None);
+                    };
+                    let size = parse_chunk_size(&self.buffer[..line_end])?;
+                    self.buffer.advance(line_end + 2);
+                    self.body = Some(match size {
+                        0 => BodyState::Trailers,
+                        size => BodyState::ChunkData(size),
+                    });
+                },
+                BodyState::ChunkEnd => {
+                    if self.buffer.len() < 2 {
+                        return Ok(None);
+                    }
+                    if &self.buffer[..2] != b"\r\n" {
+                        return Err(Error::InvalidRequest("Missing CRLF after chunk".to_string()));
+                    }
+                    self.buffer.advance(2);
+                    self.body = Some(BodyState::ChunkSize);
+                },
+                BodyState::Trailers => {
+                    let Some((trailers, used)) = parse_trailers(&self.buffer)? else {
+                        return Ok(None);
+                    };
+                    self.buffer.advance(used);
+                    self.body = None;
+                    return Ok(Some(BodyFrame::End(trailers)));
+                },
+            }
         }
     }
 
+    /// Check whether the client is waiting for `100 Continue` before sending the body
+    /// 检查客户端是否在发送body之前等待 `100 Continue`
+    ///
+    /// Returns `true` at most once per request, while the body of a request
+    /// carrying `Expect: 100-continue` is being read and none of it has arrived.
+    /// The caller should then write [`super::CONTINUE_RESPONSE`].
+    ///
+    /// 每个请求最多返回一次 `true`：当正在读取携带 `Expect: 100-continue`
+    /// 的请求body且其尚未到达时。调用方随后应写入 [`super::CONTINUE_RESPONSE`]。
+    pub fn take_expect_continue(&mut self) -> bool {
+        std::mem::take(&mut self.expect_continue) && self.buffer.is_empty()
+    }
+
     /// Get the current buffer length
     /// 获取当前缓冲区长度
     pub fn buffered_len(&self) -> usize {
         self.buffer.len()
     }
 
+    /// Get the bytes buffered but not yet parsed
+    /// 获取已缓冲但尚未解析的字节
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
     /// Clear the buffer
     /// 清空缓冲区
     pub fn clear(&mut self) {
         self.buffer.clear();
+        self.body = None;
+        self.expect_continue = false;
     }
 
     /// Get the connection context
@@ -244,4 +726,277 @@ mod tests {
         let result = parse_request(data, &ctx);
         assert!(matches!(result, Err(Error::IncompleteRequest)));
     }
+
+    #[test]
+    fn test_parse_waits_for_full_body() {
+        let data = b"POST /api HTTP/1.1\r\nContent-Length: 5\r\n\r\nhel";
+        let ctx = ConnectionContext::new();
+        let result = parse_request(data, &ctx);
+        assert!(matches!(result, Err(Error::IncompleteRequest)));
+    }
+
+    #[test]
+    fn test_parse_leaves_pipelined_request() {
+        let data = b"POST /a HTTP/1.1\r\nContent-Length: 2\r\n\r\nokGET /b HTTP/1.1\r\n\r\n";
+        let ctx = ConnectionContext::new();
+        let (req, used) = parse_request(data, &ctx).unwrap();
+        assert_eq!(req.body().as_bytes(), Some(&b"ok"[..]));
+        assert!(data[used..].starts_with(b"GET /b"));
+    }
+
+    #[test]
+    fn test_parse_chunked_body() {
+        let data = b"POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+                     5\r\nhello\r\n7;ext=1\r\n, world\r\n0\r\n\r\n";
+        let ctx = ConnectionContext::new();
+        let (req, used) = parse_request(data, &ctx).unwrap();
+        assert_eq!(used, data.len());
+        assert_eq!(req.body().as_bytes(), Some(&b"hello, world"[..]));
+        assert!(req.trailers().is_none());
+    }
+
+    #[test]
+    fn test_parse_chunked_trailers() {
+        let data = b"POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+                     3\r\nabc\r\n0\r\nChecksum: 42\r\n\r\n";
+        let ctx = ConnectionContext::new();
+        let (req, used) = parse_request(data, &ctx).unwrap();
+        assert_eq!(used, data.len());
+        assert_eq!(req.body().as_bytes(), Some(&b"abc"[..]));
+        let trailers = req.trailers().unwrap();
+        assert_eq!(trailers.get("checksum").unwrap(), "42");
+    }
+
+    #[test]
+    fn test_parse_chunked_incomplete() {
+        let ctx = ConnectionContext::new();
+        for data in [
+            &b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhel"[..],
+            &b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n"[..],
+            &b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0\r\nA: b\r\n"[..],
+        ] {
+            let result = parse_request(data, &ctx);
+            assert!(matches!(result, Err(Error::IncompleteRequest)));
+        }
+    }
+
+    #[test]
+    fn test_parse_chunked_invalid_size() {
+        let ctx = ConnectionContext::new();
+        for size in [
+            "zz",
+            "+5",
+            "-1",
+            "",
+            "FFFFFFFFFFFFFFED",
+            "1FFFFFFFFFFFFFFFF",
+        ] {
+            let data = format!("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{size}\r\nx");
+            let result = parse_request(data.as_bytes(), &ctx);
+            assert!(matches!(result, Err(Error::InvalidRequest(_))), "{}", size);
+        }
+    }
+
+    #[test]
+    fn test_parse_chunked_size_overflowing_crlf() {
+        // A chunk ending exactly at `usize::MAX` of the body, leaving no room for its CRLF
+        let head = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let size = usize::MAX - format!("{:x}\r\n", usize::MAX).len();
+        let data = format!("{head}{size:x}\r\nx");
+        let result = parse_request(data.as_bytes(), &ConnectionContext::new());
+        assert!(matches!(result, Err(Error::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn test_reject_transfer_encoding_with_content_length() {
+        let data = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nContent-Length: 3\r\n\r\n";
+        let ctx = ConnectionContext::new();
+        let result = parse_request(data, &ctx);
+        assert!(matches!(result, Err(Error::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn test_reject_body_over_buffer_limit() {
+        let data = b"POST / HTTP/1.1\r\nContent-Length: 1000000\r\n\r\n";
+        let ctx = ConnectionContext::new();
+        let result = parse_request(data, &ctx);
+        assert!(matches!(result, Err(Error::Custom(413, _))));
+    }
+
+    #[test]
+    fn test_expect_continue() {
+        let mut parser = RequestParser::new();
+        parser
+            .feed(b"PUT /file HTTP/1.1\r\nExpect: 100-continue\r\nContent-Length: 4\r\n\r\n")
+            .unwrap();
+        let (req, _) = parser.parse().unwrap().unwrap();
+        assert!(req.body().as_bytes().unwrap().is_empty());
+        assert!(parser.reading_body());
+        assert!(parser.take_expect_continue());
+        // Only once per request
+        assert!(!parser.take_expect_continue());
+
+        parser.feed(b"data").unwrap();
+        assert_eq!(
+            parser.decode_body().unwrap(),
+            Some(BodyFrame::Data(Bytes::from_static(b"data")))
+        );
+        assert_eq!(parser.decode_body().unwrap(), Some(BodyFrame::End(http::HeaderMap::new())));
+        assert!(!parser.reading_body());
+    }
+
+    /// Feed `parts` one by one, collecting the decoded body and its trailers
+    fn decode_in_parts(parser: &mut RequestParser, parts: &[&[u8]]) -> (Vec<u8>, http::HeaderMap) {
+        let mut body = Vec::new();
+        for part in parts {
+            parser.feed(part).unwrap();
+            while let Some(frame) = parser.decode_body().unwrap() {
+                match frame {
+                    BodyFrame::Data(data) => body.extend_from_slice(&data),
+                    BodyFrame::End(trailers) => return (body, trailers),
+                }
+            }
+        }
+        panic!("body did not end");
+    }
+
+    #[test]
+    fn test_stream_length_body() {
+        let mut parser = RequestParser::new();
+        parser
+            .feed(b"POST /a HTTP/1.1\r\nContent-Length: 10\r\n\r\nhel")
+            .unwrap();
+        let (req, used) = parser.parse().unwrap().unwrap();
+        assert_eq!(req.path(), "/a");
+        assert_eq!(used, 40);
+        assert!(parser.parse().unwrap().is_none());
+
+        let (body, trailers) = decode_in_parts(&mut parser, &[b"", b"lo, ", b"worldGET /b"]);
+        assert_eq!(body, b"hello, wor");
+        assert!(trailers.is_empty());
+        // The rest belongs to the next request
+        assert_eq!(parser.buffered(), b"ldGET /b");
+    }
+
+    #[test]
+    fn test_stream_chunked_body() {
+        let mut parser = RequestParser::new();
+        parser
+            .feed(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhe")
+            .unwrap();
+        let (req, _) = parser.parse().unwrap().unwrap();
+        assert!(req.trailers().is_none());
+        assert!(parser.reading_body());
+
+        let parts: [&[u8]; 6] = [
+            b"llo\r",
+            b"\n7;ext=1\r\n, wo",
+            b"rld\r\n0",
+            b"\r\nChecksum",
+            b": 42\r\n",
+            b"\r\n",
+        ];
+        let (body, trailers) = decode_in_parts(&mut parser, &parts);
+        assert_eq!(body, b"hello, world");
+        assert_eq!(trailers.get("checksum").unwrap(), "42");
+        assert!(!parser.reading_body());
+    }
+
+    #[test]
+    fn test_stream_body_larger_than_buffer() {
+        let mut ctx = ConnectionContext::new();
+        ctx.set_max_buffer_size(128);
+        let mut parser = RequestParser::with_context(ctx);
+        parser
+            .feed(b"PUT / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n")
+            .unwrap();
+        parser.parse().unwrap().unwrap();
+
+        // A single chunk many times the buffer limit, fed a buffer at a time
+        let mut parts = vec![b"4000\r\n".to_vec()];
+        parts.extend(std::iter::repeat_n(vec![b'x'; 128], 128));
+        parts.push(b"\r\n0\r\n\r\n".to_vec());
+        let parts: Vec<&[u8]> = parts.iter().map(Vec::as_slice).collect();
+        let (body, _) = decode_in_parts(&mut parser, &parts);
+        assert_eq!(body.len(), 0x4000);
+    }
+
+    #[test]
+    fn test_stream_chunked_invalid() {
+        let mut parser = RequestParser::new();
+        parser
+            .feed(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n2\r\na")
+            .unwrap();
+        parser.parse().unwrap().unwrap();
+        parser.feed(b"bXY").unwrap();
+        assert!(matches!(parser.decode_body(), Ok(Some(BodyFrame::Data(_)))));
+        assert!(matches!(parser.decode_body(), Err(Error::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn test_unsupported_expectation() {
+        let data = b"PUT / HTTP/1.1\r\nExpect: something-else\r\nContent-Length: 1\r\n\r\nx";
+        let ctx = ConnectionContext::new();
+        let result = parse_request(data, &ctx);
+        assert!(matches!(result, Err(Error::Custom(417, _))));
+    }
+
+    #[test]
+    fn test_encode_request_head() {
+        let request = Request::new(
+            http::Request::builder()
+                .method("POST")
+                .uri("http://example.com:8080/api/users?page=2")
+                .header("content-type", "application/json")
+                .body(Body::from("{}"))
+                .unwrap(),
+        );
+        let bytes = encode_request(&request, &ConnectionContext::new()).unwrap();
+        let text = std::str::from_utf8(&bytes).unwrap();
+
+        assert!(text.starts_with("POST /api/users?page=2 HTTP/1.1\r\nhost: example.com:8080\r\n"));
+        assert!(text.contains("content-type: application/json\r\n"));
+        assert!(text.contains("content-length: 2\r\n"));
+        assert!(!text.contains("connection"));
+        assert!(text.ends_with("\r\n\r\n{}"));
+
+        // The encoded request parses back
+        let (parsed, used) = parse_request(&bytes, &ConnectionContext::new()).unwrap();
+        assert_eq!(used, bytes.len());
+        assert_eq!(parsed.path(), "/api/users");
+        assert_eq!(parsed.body().as_bytes(), Some(&b"{}"[..]));
+    }
+
+    #[test]
+    fn test_encode_request_framing() {
+        let mut ctx = ConnectionContext::new();
+        ctx.set_keep_alive(false);
+        let get = Request::new(
+            http::Request::builder()
+                .uri("/")
+                .header("host", "localhost")
+                .body(Body::empty())
+                .unwrap(),
+        );
+        let text = String::from_utf8(encode_request(&get, &ctx).unwrap().to_vec()).unwrap();
+        assert_eq!(text, "GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n");
+
+        let body = Body::wrap_stream(futures::stream::iter(vec![Bytes::from_static(b"a")]));
+        let put = Request::new(
+            http::Request::builder()
+                .method("PUT")
+                .uri("http://localhost/upload")
+                .body(body)
+                .unwrap(),
+        );
+        let ctx = ConnectionContext::new();
+        assert!(is_request_chunked(&put, &ctx));
+        let text = String::from_utf8(encode_request(&put, &ctx).unwrap().to_vec()).unwrap();
+        assert!(text.contains("transfer-encoding: chunked\r\n"));
+        assert!(!text.contains("content-length"));
+
+        // Without a Host header or an absolute URI there is nothing to send
+        let bare = Request::from_method_uri(Method::GET, "/");
+        assert!(encode_request_head(&bare, &ctx).is_err());
+    }
 }
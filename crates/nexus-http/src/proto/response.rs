@@ -1,9 +1,10 @@
-//! HTTP/1.1 response encoding
-//! HTTP/1.1 响应编码
+//! HTTP/1.1 response encoding and parsing
+//! HTTP/1.1 响应编码和解析
 
-use super::context::ConnectionContext;
-use crate::{HttpBody, Response, Result, StatusCode};
-use bytes::Bytes;
+use super::context::{ConnectionContext, HttpVersion};
+use super::request::decode_chunked;
+use crate::{Body, Error, HttpBody, Response, Result, StatusCode};
+use bytes::{Buf, Bytes, BytesMut};
 use std::fmt::Write;
 
 /// Encode an HTTP/1.1 response to bytes
@@ -16,7 +17,7 @@ use std::fmt::Write;
 ///
 /// # Returns / 返回
 ///
-/// * `Ok(Bytes)` - The encoded response bytes
+/// * `Ok(Bytes)` - The encoded response bytes (only the head for streaming bodies)
 /// * `Err(Error)` - Encoding error
 ///
 /// # Example / 示例
@@ -35,14 +36,35 @@ use std::fmt::Write;
 /// let bytes = encode_response(&response, &ctx)?;
 /// ```
 pub fn encode_response(response: &Response, ctx: &ConnectionContext) -> Result<Bytes> {
+    let head = encode_head(response, ctx)?;
+    match response.body().as_bytes() {
+        Some(body_data) if !body_data.is_empty() => {
+            let mut result = BytesMut::with_capacity(head.len() + body_data.len());
+            result.extend_from_slice(&head);
+            result.extend_from_slice(body_data);
+            Ok(result.freeze())
+        },
+        _ => Ok(head),
+    }
+}
+
+/// Encode the status line and headers of an HTTP/1.1 response
+/// 编码 HTTP/1.1 响应的状态行和头部
+///
+/// A streaming body without a `Content-Length` header gets
+/// `transfer-encoding: chunked` on HTTP/1.1; its data must then be written with
+/// [`encode_chunk`] followed by [`LAST_CHUNK`].
+///
+/// 没有 `Content-Length` 头的流式body在 HTTP/1.1 上会得到 `transfer-encoding: chunked`；
+/// 其数据随后必须用 [`encode_chunk`] 写出，并以 [`LAST_CHUNK`] 结尾。
+pub fn encode_head(response: &Response, ctx: &ConnectionContext) -> Result<Bytes> {
     let mut buffer = String::with_capacity(4096);
 
     // Status line: HTTP/1.1 200 OK
     let status = response.status();
     let reason = status.canonical_reason().unwrap_or("Unknown");
-    writeln!(buffer, "{} {} {}\r", ctx.version().as_str(), status.as_u16(), reason).map_err(
-        |e| crate::Error::InvalidResponse(format!("Failed to write status line: {}", e)),
-    )?;
+    writeln!(buffer, "{} {} {}\r", ctx.version().as_str(), status.as_u16(), reason)
+        .map_err(|e| Error::InvalidResponse(format!("Failed to write status line: {}", e)))?;
 
     // Add default headers
     let mut has_content_length = false;
@@ -66,59 +88,104 @@ pub fn encode_response(response: &Response, ctx: &ConnectionContext) -> Result<B
         }
 
         writeln!(buffer, "{}: {}\r", name_str, value_str)
-            .map_err(|_| crate::Error::InvalidResponse("Failed to write header".to_string()))?;
+            .map_err(|_| Error::InvalidResponse("Failed to write header".to_string()))?;
     }
 
-    // Add Content-Length if not present and we have a body
-    if !has_content_length && !has_transfer_encoding {
-        let body_len = response.body().as_bytes().map(|b| b.len()).unwrap_or(0);
-        if body_len > 0 || !matches!(status, StatusCode::NO_CONTENT) {
-            writeln!(buffer, "content-length: {}\r", body_len).map_err(|_| {
-                crate::Error::InvalidResponse("Failed to write content-length".to_string())
-            })?;
+    // Informational responses (e.g. `101 Switching Protocols`) never carry a body
+    // 信息响应（例如 `101 Switching Protocols`）从不携带body
+    let informational = status.is_informational();
+
+    // Add framing headers if not present: streaming bodies are chunked on HTTP/1.1
+    // and delimited by closing the connection on HTTP/1.0
+    // 如果不存在则添加分帧头：流式body在 HTTP/1.1 上分块，在 HTTP/1.0 上以关闭连接分隔
+    if !has_content_length && !has_transfer_encoding && !informational {
+        if response.body().is_stream() {
+            if is_chunked(response, ctx) {
+                writeln!(buffer, "transfer-encoding: chunked\r").map_err(|_| {
+                    Error::InvalidResponse("Failed to write transfer-encoding".to_string())
+                })?;
+            }
+        } else {
+            let body_len = response.body().as_bytes().map(|b| b.len()).unwrap_or(0);
+            if body_len > 0 || !matches!(status, StatusCode::NO_CONTENT) {
+                writeln!(buffer, "content-length: {}\r", body_len).map_err(|_| {
+                    Error::InvalidResponse("Failed to write content-length".to_string())
+                })?;
+            }
         }
     }
 
     // Add Content-Type if not present
-    if !has_content_type {
+    if !has_content_type && !informational {
         let content_type = response
             .headers()
             .get("content-type")
             .map(|v| v.as_str())
             .unwrap_or("text/plain");
-        writeln!(buffer, "content-type: {}\r", content_type).map_err(|_| {
-            crate::Error::InvalidResponse("Failed to write content-type".to_string())
-        })?;
+        writeln!(buffer, "content-type: {}\r", content_type)
+            .map_err(|_| Error::InvalidResponse("Failed to write content-type".to_string()))?;
     }
 
     // Add Connection header if not present
     if !has_connection {
         if ctx.keep_alive() {
             writeln!(buffer, "connection: keep-alive\r").map_err(|_| {
-                crate::Error::InvalidResponse("Failed to write connection header".to_string())
+                Error::InvalidResponse("Failed to write connection header".to_string())
             })?;
         } else {
             writeln!(buffer, "connection: close\r").map_err(|_| {
-                crate::Error::InvalidResponse("Failed to write connection header".to_string())
+                Error::InvalidResponse("Failed to write connection header".to_string())
             })?;
         }
     }
 
     // End of headers
-    write!(buffer, "\r").map_err(|_| {
-        crate::Error::InvalidResponse("Failed to write header terminator".to_string())
-    })?;
+    write!(buffer, "\r\n")
+        .map_err(|_| Error::InvalidResponse("Failed to write header terminator".to_string()))?;
+
+    Ok(Bytes::from(buffer))
+}
 
-    // Convert to bytes and add body
-    let mut result = Bytes::copy_from_slice(buffer.as_bytes());
-    if let Some(body_data) = response.body().as_bytes() {
-        let body_bytes = Bytes::copy_from_slice(body_data);
-        result = Bytes::copy_from_slice(&[&result[..], &body_bytes[..]].concat());
+/// Check whether the body of a response is written with chunked transfer-encoding
+/// 检查响应body是否以分块传输编码写出
+pub fn is_chunked(response: &Response, ctx: &ConnectionContext) -> bool {
+    if !response.body().is_stream() {
+        return false;
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    };
+    if let Some(te) = header("transfer-encoding") {
+        return te.to_ascii_lowercase().contains("chunked");
     }
+    header("content-length").is_none() && ctx.version() == HttpVersion::Http11
+}
 
-    Ok(result)
+/// Encode one chunk of a chunked body
+/// 编码分块body的一个数据块
+///
+/// Empty chunks would terminate the body and are encoded as nothing.
+/// 空数据块会终止body，因此编码为空。
+pub fn encode_chunk(data: &[u8]) -> Bytes {
+    if data.is_empty() {
+        return Bytes::new();
+    }
+    let size = format!("{:x}\r\n", data.len());
+    let mut chunk = BytesMut::with_capacity(size.len() + data.len() + 2);
+    chunk.extend_from_slice(size.as_bytes());
+    chunk.extend_from_slice(data);
+    chunk.extend_from_slice(b"\r\n");
+    chunk.freeze()
 }
 
+/// Terminating chunk of a chunked body (no trailers)
+/// 分块body的终止块（无尾部）
+pub const LAST_CHUNK: &[u8] = b"0\r\n\r\n";
+
 /// HTTP response encoder with state
 /// 带状态的 HTTP 响应编码器
 #[derive(Debug)]
@@ -148,6 +215,12 @@ impl ResponseEncoder {
         encode_response(response, &self.ctx)
     }
 
+    /// Check whether the response body is written with chunked transfer-encoding
+    /// 检查响应body是否以分块传输编码写出
+    pub fn is_chunked(&self, response: &Response) -> bool {
+        is_chunked(response, &self.ctx)
+    }
+
     /// Get the connection context
     /// 获取连接上下文
     pub fn context(&self) -> &ConnectionContext {
@@ -167,6 +240,223 @@ impl Default for ResponseEncoder {
     }
 }
 
+/// Parse an HTTP/1.1 response from bytes
+/// 从字节解析 HTTP/1.1 响应
+///
+/// Interim `1xx` responses other than `101 Switching Protocols` are skipped.
+/// Set `head_request` when answering a `HEAD` request, whose response never
+/// carries a body. Returns [`Error::IncompleteRequest`] until the whole response
+/// is buffered; responses delimited by closing the connection are only complete
+/// once [`ResponseParser::finish`] is called.
+///
+/// 除 `101 Switching Protocols` 之外的 `1xx` 临时响应会被跳过。
+/// 回应 `HEAD` 请求时设置 `head_request`，其响应从不携带body。
+/// 在整个响应缓冲完成之前返回 [`Error::IncompleteRequest`]；以关闭连接分隔的响应
+/// 只有在调用 [`ResponseParser::finish`] 后才算完整。
+pub fn parse_response(
+    data: &[u8],
+    ctx: &ConnectionContext,
+    head_request: bool,
+) -> Result<(Response, usize)> {
+    parse_response_inner(data, ctx, head_request, false).map(|(response, used, _)| (response, used))
+}
+
+/// Parse a response, treating the end of `data` as end of stream when `eof` is set
+/// 解析响应，设置 `eof` 时将 `data` 的结尾视为流的结尾
+///
+/// Also returns whether the connection may be reused afterwards.
+/// 同时返回连接之后是否可以复用。
+fn parse_response_inner(
+    data: &[u8],
+    ctx: &ConnectionContext,
+    head_request: bool,
+    eof: bool,
+) -> Result<(Response, usize, bool)> {
+    let mut offset = 0;
+    loop {
+        let mut headers = [httparse::EMPTY_HEADER; 64];
+        let mut res = httparse::Response::new(&mut headers);
+        let len = match res.parse(&data[offset..]).map_err(map_httparse_error)? {
+            httparse::Status::Complete(n) => n,
+            httparse::Status::Partial => return Err(Error::IncompleteRequest),
+        };
+
+        let code = res
+            .code
+            .ok_or_else(|| Error::InvalidResponse("Missing status code".to_string()))?;
+        let status = StatusCode::from_u16(code);
+        if status.is_informational() && status != StatusCode::SWITCHING_PROTOCOLS {
+            offset += len;
+            continue;
+        }
+        let version = match res.version {
+            Some(0) => HttpVersion::Http10,
+            _ => HttpVersion::Http11,
+        };
+
+        let mut response = Response::new(status);
+        for header in res.headers.iter() {
+            let name = header.name.to_ascii_lowercase();
+            let value = std::str::from_utf8(header.value)
+                .map_err(|_| Error::InvalidResponse("Invalid header value".to_string()))?
+                .trim();
+            let value = match response.header(&name) {
+                Some(previous) => format!("{}, {}", previous, value),
+                None => value.to_string(),
+            };
+            response.insert_header(name, value);
+        }
+
+        let mut conn_ctx = ctx.clone();
+        conn_ctx.set_version(version);
+        conn_ctx.update_keep_alive_from_header(response.header("connection"));
+
+        let body_data = &data[offset + len..];
+        let bodiless = head_request
+            || status.is_informational()
+            || status == StatusCode::NO_CONTENT
+            || status == StatusCode::NOT_MODIFIED;
+        let (body, body_len, keep_alive) = if bodiless {
+            (Bytes::new(), 0, conn_ctx.keep_alive())
+        } else if let Some(te) = response.header("transfer-encoding") {
+            if !te.to_ascii_lowercase().trim_end().ends_with("chunked") {
+                return Err(Error::InvalidResponse("Unsupported transfer encoding".to_string()));
+            }
+            let (body, _trailers, used) = decode_chunked(body_data).map_err(|e| match e {
+                Error::IncompleteRequest => e,
+                e => Error::InvalidResponse(e.to_string()),
+            })?;
+            (body, used, conn_ctx.keep_alive())
+        } else if let Some(length) = response.header("content-length") {
+            let length = length
+                .split(',')
+                .next()
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .ok_or_else(|| Error::InvalidResponse("Invalid Content-Length".to_string()))?;
+            if length > ctx.max_buffer_size() {
+                return Err(Error::Custom(413, "Response body too large".to_string()));
+            }
+            if body_data.len() < length {
+                return Err(Error::IncompleteRequest);
+            }
+            (Bytes::copy_from_slice(&body_data[..length]), length, conn_ctx.keep_alive())
+        } else if eof {
+            // Delimited by closing the connection
+            // 以关闭连接分隔
+            (Bytes::copy_from_slice(body_data), body_data.len(), false)
+        } else {
+            return Err(Error::IncompleteRequest);
+        };
+
+        response.set_body(Body::from(body));
+        return Ok((response, offset + len + body_len, keep_alive));
+    }
+}
+
+/// Map an httparse error to a response error
+/// 将 httparse 错误映射为响应错误
+fn map_httparse_error(e: httparse::Error) -> Error {
+    Error::InvalidResponse(format!("Malformed response: {}", e))
+}
+
+/// HTTP response parser with state, used by clients
+/// 带状态的 HTTP 响应解析器，供客户端使用
+///
+/// After a response has been parsed, [`ResponseParser::context`] tells whether the
+/// connection may carry another request.
+///
+/// 解析响应后，[`ResponseParser::context`] 表明连接是否可以承载下一个请求。
+#[derive(Debug)]
+pub struct ResponseParser {
+    /// Buffer for incoming data
+    buffer: BytesMut,
+    /// Connection context
+    ctx: ConnectionContext,
+}
+
+impl ResponseParser {
+    /// Create a new response parser
+    /// 创建新的响应解析器
+    pub fn new() -> Self {
+        Self::with_context(ConnectionContext::new())
+    }
+
+    /// Create a new response parser with custom context
+    /// 使用自定义上下文创建新的响应解析器
+    ///
+    /// `max_buffer_size` bounds the whole response, head and body included; larger
+    /// responses fail with `Error::Custom(413, _)`.
+    /// `max_buffer_size` 限制整个响应，包括头部和body；更大的响应以 `Error::Custom(413, _)` 失败。
+    pub fn with_context(ctx: ConnectionContext) -> Self {
+        Self {
+            buffer: BytesMut::with_capacity(8192),
+            ctx,
+        }
+    }
+
+    /// Feed data to the parser
+    /// 向解析器提供数据
+    pub fn feed(&mut self, data: &[u8]) -> Result<()> {
+        if self.buffer.len() + data.len() > self.ctx.max_buffer_size() {
+            return Err(Error::Custom(413, "Response too large".to_string()));
+        }
+        self.buffer.extend_from_slice(data);
+        Ok(())
+    }
+
+    /// Try to parse a response from the buffered data
+    /// 尝试从缓冲数据解析响应
+    ///
+    /// Returns `Ok(None)` if more data is needed.
+    pub fn parse(&mut self, head_request: bool) -> Result<Option<Response>> {
+        self.parse_buffered(head_request, false)
+    }
+
+    /// Parse the final response once the peer has closed the connection
+    /// 在对端关闭连接后解析最终响应
+    ///
+    /// Returns `Ok(None)` if the buffered data does not hold a whole response.
+    pub fn finish(&mut self, head_request: bool) -> Result<Option<Response>> {
+        self.parse_buffered(head_request, true)
+    }
+
+    fn parse_buffered(&mut self, head_request: bool, eof: bool) -> Result<Option<Response>> {
+        match parse_response_inner(&self.buffer, &self.ctx, head_request, eof) {
+            Ok((response, used, keep_alive)) => {
+                self.buffer.advance(used);
+                self.ctx.set_keep_alive(keep_alive);
+                Ok(Some(response))
+            },
+            Err(Error::IncompleteRequest) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the bytes buffered but not yet parsed
+    /// 获取已缓冲但尚未解析的字节
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Get the connection context
+    /// 获取连接上下文
+    pub fn context(&self) -> &ConnectionContext {
+        &self.ctx
+    }
+
+    /// Get mutable reference to the connection context
+    /// 获取连接上下文的可变引用
+    pub fn context_mut(&mut self) -> &mut ConnectionContext {
+        &mut self.ctx
+    }
+}
+
+impl Default for ResponseParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,7 +475,7 @@ mod tests {
 
         assert!(str_data.starts_with("HTTP/1.1 200 OK\r\n"));
         assert!(str_data.contains("content-length: 11"));
-        assert!(str_data.ends_with("Hello World"));
+        assert!(str_data.ends_with("\r\n\r\nHello World"));
     }
 
     #[test]
@@ -216,4 +506,141 @@ mod tests {
 
         assert!(str_data.contains("x-custom-header: custom-value"));
     }
+
+    #[test]
+    fn test_encode_streaming_response_is_chunked() {
+        let body = Body::wrap_stream(futures::stream::iter(vec![Bytes::from_static(b"a")]));
+        let response = Response::builder()
+            .status(StatusCode::OK)
+            .body(body)
+            .unwrap();
+
+        let ctx = ConnectionContext::new();
+        assert!(is_chunked(&response, &ctx));
+        let bytes = encode_response(&response, &ctx).unwrap();
+        let str_data = std::str::from_utf8(&bytes).unwrap();
+
+        assert!(str_data.contains("transfer-encoding: chunked\r\n"));
+        assert!(!str_data.contains("content-length"));
+        assert!(str_data.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_encode_switching_protocols_has_no_body_headers() {
+        let response = Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header("connection", "Upgrade")
+            .header("upgrade", "websocket")
+            .body(Body::empty())
+            .unwrap();
+
+        let bytes = encode_response(&response, &ConnectionContext::new()).unwrap();
+        let str_data = std::str::from_utf8(&bytes).unwrap();
+
+        assert!(str_data.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+        assert!(!str_data.contains("content-length"));
+        assert!(!str_data.contains("content-type"));
+        assert!(!str_data.contains("keep-alive"));
+    }
+
+    #[test]
+    fn test_streaming_response_with_length_is_not_chunked() {
+        let body = Body::wrap_stream(futures::stream::iter(vec![Bytes::from_static(b"abc")]));
+        let response = Response::builder()
+            .status(StatusCode::OK)
+            .header("content-length", "3")
+            .body(body)
+            .unwrap();
+
+        let ctx = ConnectionContext::new();
+        assert!(!is_chunked(&response, &ctx));
+
+        let mut http10 = ConnectionContext::new();
+        http10.set_version(HttpVersion::Http10);
+        let response = Response::builder()
+            .body(Body::wrap_stream(futures::stream::empty()))
+            .unwrap();
+        assert!(!is_chunked(&response, &http10));
+    }
+
+    #[test]
+    fn test_encode_chunk() {
+        assert_eq!(&encode_chunk(b"hello world, chunk")[..], b"12\r\nhello world, chunk\r\n");
+        assert!(encode_chunk(b"").is_empty());
+    }
+
+    #[test]
+    fn test_parse_response_with_length() {
+        let data = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nSet-Cookie: a=1\r\n\
+                     Set-Cookie: b=2\r\nContent-Length: 5\r\n\r\nhelloHTTP/1.1";
+        let (response, used) = parse_response(data, &ConnectionContext::new(), false).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.header("content-type"), Some("text/plain"));
+        assert_eq!(response.header("set-cookie"), Some("a=1, b=2"));
+        assert_eq!(response.body().as_bytes(), Some(&b"hello"[..]));
+        assert_eq!(&data[used..], b"HTTP/1.1");
+
+        let partial = &data[..data.len() - 10];
+        let result = parse_response(partial, &ConnectionContext::new(), false);
+        assert!(matches!(result, Err(Error::IncompleteRequest)));
+    }
+
+    #[test]
+    fn test_parse_response_chunked_and_interim() {
+        let data = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 201 Created\r\n\
+                     Transfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n";
+        let (response, used) = parse_response(data, &ConnectionContext::new(), false).unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.body().as_bytes(), Some(&b"abc"[..]));
+        assert_eq!(used, data.len());
+    }
+
+    #[test]
+    fn test_parse_response_without_body() {
+        let head = b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n";
+        let (response, used) = parse_response(head, &ConnectionContext::new(), true).unwrap();
+        assert!(response.body().as_bytes().unwrap().is_empty());
+        assert_eq!(used, head.len());
+
+        let data = b"HTTP/1.1 304 Not Modified\r\nETag: \"x\"\r\n\r\n";
+        let (response, _) = parse_response(data, &ConnectionContext::new(), false).unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[test]
+    fn test_response_parser_close_delimited() {
+        let mut parser = ResponseParser::new();
+        parser
+            .feed(b"HTTP/1.0 200 OK\r\n\r\nstreamed until ")
+            .unwrap();
+        assert!(parser.parse(false).unwrap().is_none());
+        parser.feed(b"close").unwrap();
+        let response = parser.finish(false).unwrap().unwrap();
+        assert_eq!(response.body().as_bytes(), Some(&b"streamed until close"[..]));
+        assert!(!parser.context().keep_alive());
+        assert!(parser.buffered().is_empty());
+    }
+
+    #[test]
+    fn test_response_parser_keep_alive_and_limits() {
+        let mut parser = ResponseParser::new();
+        parser.feed(b"HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+        parser.parse(false).unwrap().unwrap();
+        assert!(parser.context().keep_alive());
+
+        parser
+            .feed(b"HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        parser.parse(false).unwrap().unwrap();
+        assert!(!parser.context().keep_alive());
+
+        let mut ctx = ConnectionContext::new();
+        ctx.set_max_buffer_size(64);
+        let mut parser = ResponseParser::with_context(ctx);
+        parser
+            .feed(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\n")
+            .unwrap();
+        assert!(matches!(parser.parse(false), Err(Error::Custom(413, _))));
+        assert!(matches!(parser.feed(&[0u8; 64]), Err(Error::Custom(413, _))));
+    }
 }
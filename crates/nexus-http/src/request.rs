@@ -9,9 +9,10 @@
 #![warn(unreachable_pub)]
 
 use super::{
-    body::Body,
+    body::{Body, Trailers},
     error::{Error, Result},
     method::Method,
+    tls::PeerCertificate,
 };
 use http::request::Parts;
 use std::collections::HashMap;
@@ -124,6 +125,28 @@ impl Request {
         self.inner.body()
     }
 
+    /// Get the trailer fields sent after a chunked request body
+    /// 获取分块请求体之后发送的尾部字段
+    ///
+    /// For a streamed body this is `None` until the body has been read to the end.
+    /// 对于流式body，在body读取完毕之前为 `None`。
+    pub fn trailers(&self) -> Option<&http::HeaderMap> {
+        self.inner
+            .extensions()
+            .get::<Trailers>()
+            .and_then(Trailers::get)
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Get the certificate the client authenticated with over TLS
+    /// 获取客户端通过TLS认证所用的证书
+    ///
+    /// Only set when the server verifies client certificates (mTLS).
+    /// 仅当服务器校验客户端证书（mTLS）时设置。
+    pub fn peer_certificate(&self) -> Option<&PeerCertificate> {
+        self.inner.extensions().get::<PeerCertificate>()
+    }
+
     /// Get a header value
     /// 获取header值
     pub fn header(&self, name: &str) -> Option<&str> {
@@ -12,6 +12,7 @@ use super::{
     body::Body,
     error::Result,
     status::StatusCode,
+    upgrade::OnUpgrade,
 };
 use std::collections::HashMap;
 
@@ -22,6 +23,7 @@ pub struct Response {
     status: StatusCode,
     headers: HashMap<String, String>,
     body: Body,
+    upgrade: Option<OnUpgrade>,
 }
 
 impl Response {
@@ -32,6 +34,7 @@ impl Response {
             status,
             headers: HashMap::new(),
             body: Body::empty(),
+            upgrade: None,
         }
     }
 
@@ -102,6 +105,18 @@ impl Response {
         self.headers.remove(name.as_ref());
     }
 
+    /// Attach the callback taking over the connection after a `101` response
+    /// 附加在 `101` 响应之后接管连接的回调
+    pub(crate) fn set_upgrade(&mut self, upgrade: OnUpgrade) {
+        self.upgrade = Some(upgrade);
+    }
+
+    /// Take the connection takeover callback, if any
+    /// 取出连接接管回调（如果有）
+    pub(crate) fn take_upgrade(&mut self) -> Option<OnUpgrade> {
+        self.upgrade.take()
+    }
+
     /// Create a JSON response
     /// 创建JSON响应
     pub fn json<T: serde::Serialize>(value: &T) -> Self {
@@ -155,6 +170,7 @@ impl ResponseBuilder {
             status: self.status.unwrap_or_default(),
             headers: self.headers,
             body: self.body.unwrap_or_default(),
+            upgrade: None,
         })
     }
 }
@@ -247,6 +263,7 @@ impl BodyBuilder {
             status: self.status,
             headers: self.headers,
             body: body.into(),
+            upgrade: None,
         }
     }
 
@@ -270,6 +287,7 @@ impl BodyBuilder {
                     h
                 },
                 body: Body::from(bytes),
+                upgrade: None,
             },
             Err(_) => Response::internal_server_error()
                 .with_body(Body::from("{\"error\":\"Failed to serialize response\"}")),
@@ -287,6 +305,7 @@ impl BodyBuilder {
                 h
             },
             body: Body::from(text.into()),
+            upgrade: None,
         }
     }
 
@@ -301,6 +320,7 @@ impl BodyBuilder {
                 h
             },
             body: Body::from(html.into()),
+            upgrade: None,
         }
     }
 }
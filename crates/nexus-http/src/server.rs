@@ -5,40 +5,117 @@
 //!
 //! - Tomcat, Jetty, Undertow embedded servers
 //! - server.port, server.address configuration
+//!
+//! Besides a TCP address a server can listen on a Unix domain socket
+//! ([`Server::bind_unix`]) or on a socket inherited from a service manager or a
+//! previous process ([`Server::from_fd`], [`Server::from_listen_fds`]).
+//!
+//! 除TCP地址外，服务器还可以监听Unix域套接字（[`Server::bind_unix`]），
+//! 或从服务管理器、上一个进程继承的套接字（[`Server::from_fd`]、[`Server::from_listen_fds`]）。
 
 #![warn(missing_docs)]
 #![warn(unreachable_pub)]
 
 use super::{
-    HttpService, Response,
+    Body, HttpService, Request, Response, StatusCode, StreamBody, Trailers,
     error::{Error, Result},
+    http2::{
+        Http2Config,
+        connection::{ServeOptions, Upgrade, serve_connection},
+        frame::PREFACE,
+    },
     proto,
+    testing::MemoryStream,
+    tls::{PeerCertificate, TlsAcceptor, TlsStream},
+    upgrade::Upgraded,
 };
-use nexus_runtime::io::{TcpListener, TcpStream};
+use base64::Engine as _;
+use bytes::Bytes;
+use futures::SinkExt;
+use futures::channel::mpsc;
+use nexus_runtime::io::{
+    ListenFd, ReadFuture, TcpListener, TcpStream, UnixListener, UnixStream, WriteAllFuture,
+    listen_fds,
+};
+use nexus_runtime::select::{SelectTwoOutput, select_two};
 use nexus_runtime::task::spawn;
-use std::net::SocketAddr;
-use std::sync::Arc;
+use nexus_runtime::time::{Duration, Instant, timeout, timeout_at};
+use std::future::Future;
+use std::net::{Shutdown, SocketAddr};
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::os::unix::fs::FileTypeExt;
+use std::path::PathBuf;
+use std::pin::{Pin, pin};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
 
 /// HTTP Server
 /// HTTP服务器
 #[derive(Clone)]
 pub struct Server {
     addr: SocketAddr,
+    listen: Listen,
     config: ServerConfig,
 }
 
+/// Where a server accepts connections
+/// 服务器接受连接的位置
+#[derive(Clone)]
+enum Listen {
+    /// TCP on the server address / 服务器地址上的TCP
+    Tcp,
+    /// Unix domain socket at a path / 路径上的Unix域套接字
+    Unix(PathBuf),
+    /// Already listening socket, TCP or Unix / 已在监听的套接字，TCP或Unix
+    Fd(Arc<OwnedFd>),
+}
+
+/// A bound listening socket
+/// 已绑定的监听套接字
+enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+impl Listener {
+    /// Accept the next connection
+    /// 接受下一个连接
+    ///
+    /// Unix domain peers have no IP address and are reported as `0.0.0.0:0`.
+    /// Unix域对端没有IP地址，报告为 `0.0.0.0:0`。
+    async fn accept(&mut self) -> std::io::Result<(ServerStream, SocketAddr)> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, peer_addr) = listener.accept().await?;
+                Ok((ServerStream::Plain(stream), peer_addr))
+            },
+            Listener::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+                Ok((ServerStream::Unix(stream), SocketAddr::from(([0, 0, 0, 0], 0))))
+            },
+        }
+    }
+}
+
 /// Server configuration
 /// 服务器配置
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
-    /// Maximum connections
+    /// Maximum concurrent connections (0 = unlimited)
     max_connections: usize,
-    /// Request timeout in seconds
+    /// Request timeout in seconds (reading the request plus running the handler)
     request_timeout: u64,
-    /// Keep-alive timeout in seconds
+    /// Keep-alive timeout in seconds (idle time between requests)
     keep_alive_timeout: u64,
     /// Maximum buffer size for reading
     max_buffer_size: usize,
+    /// Graceful shutdown drain timeout in seconds
+    shutdown_timeout: u64,
+    /// HTTP/2 cleartext (h2c) settings, `None` when HTTP/2 is disabled
+    http2: Option<Http2Config>,
+    /// TLS acceptor, `None` for plain TCP
+    tls: Option<TlsAcceptor>,
 }
 
 impl Default for ServerConfig {
@@ -48,10 +125,51 @@ impl Default for ServerConfig {
             request_timeout: 30,
             keep_alive_timeout: 60,
             max_buffer_size: 64 * 1024,
+            shutdown_timeout: 30,
+            http2: None,
+            tls: None,
         }
     }
 }
 
+impl ServerConfig {
+    /// Get the maximum number of concurrent connections
+    /// 获取最大并发连接数
+    pub fn max_connections(&self) -> usize {
+        self.max_connections
+    }
+
+    /// Get the request timeout
+    /// 获取请求超时时间
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout)
+    }
+
+    /// Get the keep-alive timeout
+    /// 获取keep-alive超时时间
+    pub fn keep_alive_timeout(&self) -> Duration {
+        Duration::from_secs(self.keep_alive_timeout)
+    }
+
+    /// Get the graceful shutdown drain timeout
+    /// 获取优雅关闭的排空超时时间
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout)
+    }
+
+    /// Get the HTTP/2 settings, if HTTP/2 is enabled
+    /// 获取HTTP/2设置（如果启用了HTTP/2）
+    pub fn http2(&self) -> Option<&Http2Config> {
+        self.http2.as_ref()
+    }
+
+    /// Get the TLS acceptor, if TLS is enabled
+    /// 获取TLS接受器（如果启用了TLS）
+    pub fn tls(&self) -> Option<&TlsAcceptor> {
+        self.tls.as_ref()
+    }
+}
+
 impl Server {
     /// Create a new server with default address (127.0.0.1:8080)
     /// 使用默认地址创建新服务器 (127.0.0.1:8080)
@@ -74,10 +192,52 @@ impl Server {
 
         Self {
             addr,
+            listen: Listen::Tcp,
             config: ServerConfig::default(),
         }
     }
 
+    /// Create a new server listening on a Unix domain socket
+    /// 创建监听Unix域套接字的新服务器
+    ///
+    /// A stale socket file left at `path` by a previous run is replaced, and the
+    /// file is removed again once the server has shut down.
+    /// 上次运行遗留在 `path` 的套接字文件会被替换，服务器关闭后会再次删除该文件。
+    pub fn bind_unix(path: impl Into<PathBuf>) -> Self {
+        Self {
+            listen: Listen::Unix(path.into()),
+            ..Self::new()
+        }
+    }
+
+    /// Create a new server accepting on an already listening socket
+    /// 创建在已监听的套接字上接受连接的新服务器
+    ///
+    /// The socket may be TCP or a Unix domain socket, e.g. one received from a
+    /// previous process during a zero-downtime restart.
+    /// 套接字可以是TCP或Unix域套接字，例如在零停机重启期间从上一个进程接收的套接字。
+    pub fn from_fd(fd: OwnedFd) -> Self {
+        Self {
+            listen: Listen::Fd(Arc::new(fd)),
+            ..Self::new()
+        }
+    }
+
+    /// Create a new server on the first socket passed through `LISTEN_FDS`
+    /// 在通过 `LISTEN_FDS` 传入的第一个套接字上创建新服务器
+    ///
+    /// Supports systemd socket activation. Fails when no socket was passed; use
+    /// [`nexus_runtime::io::listen_fds`] and [`Server::from_fd`] to serve on several.
+    /// 支持systemd套接字激活。未传入套接字时失败；如需在多个套接字上提供服务，
+    /// 请使用 [`nexus_runtime::io::listen_fds`] 和 [`Server::from_fd`]。
+    pub fn from_listen_fds() -> Result<Self> {
+        let fd = listen_fds()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Io("No socket passed through LISTEN_FDS".to_string()))?;
+        Ok(Self::from_fd(fd.into_owned_fd()))
+    }
+
     /// Set the maximum connections
     /// 设置最大连接数
     pub fn max_connections(mut self, max: usize) -> Self {
@@ -99,6 +259,37 @@ impl Server {
         self
     }
 
+    /// Set the graceful shutdown drain timeout in seconds
+    /// 设置优雅关闭的排空超时时间（秒）
+    pub fn shutdown_timeout(mut self, timeout: u64) -> Self {
+        self.config.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Enable cleartext HTTP/2 (h2c) next to HTTP/1.1
+    /// 在 HTTP/1.1 之外启用明文HTTP/2（h2c）
+    ///
+    /// Clients may start with the HTTP/2 connection preface (prior knowledge) or
+    /// upgrade an HTTP/1.1 request with `Upgrade: h2c`.
+    /// 客户端可以以HTTP/2连接前言开始（先验知识），或使用 `Upgrade: h2c` 升级HTTP/1.1请求。
+    pub fn http2(mut self, config: Http2Config) -> Self {
+        self.config.http2 = Some(config);
+        self
+    }
+
+    /// Terminate TLS on every connection
+    /// 在每个连接上终止TLS
+    ///
+    /// Keep a clone of the acceptor to swap the certificate at runtime with
+    /// [`TlsAcceptor::reload`]. Connections negotiating `h2` through ALPN are
+    /// served as HTTP/2.
+    /// 保留接受器的克隆即可通过 [`TlsAcceptor::reload`] 在运行时替换证书。
+    /// 通过ALPN协商为 `h2` 的连接以HTTP/2提供服务。
+    pub fn tls(mut self, acceptor: TlsAcceptor) -> Self {
+        self.config.tls = Some(acceptor);
+        self
+    }
+
     /// Run the server with the given service
     /// 使用给定的服务运行服务器
     ///
@@ -118,34 +309,140 @@ impl Server {
     where
         S: HttpService + Clone + 'static,
     {
-        tracing::info!("Starting HTTP server on {}", self.addr);
+        self.run_with_shutdown(service, std::future::pending()).await
+    }
+
+    /// Run the server until the shutdown signal completes, then shut down gracefully
+    /// 运行服务器直到关闭信号完成，然后优雅关闭
+    ///
+    /// When `signal` resolves the server stops accepting new connections, lets
+    /// in-flight requests finish (answering them with `connection: close`) and
+    /// closes idle keep-alive connections. Connections still busy after the
+    /// shutdown timeout are closed forcibly.
+    ///
+    /// 当 `signal` 完成时，服务器停止接受新连接，让进行中的请求完成
+    /// （以 `connection: close` 响应），并关闭空闲的keep-alive连接。
+    /// 超过关闭超时时间仍在忙碌的连接将被强制关闭。
+    ///
+    /// # Example / 示例
+    ///
+    /// ```rust,no_run,ignore
+    /// use nexus_http::Server;
+    ///
+    /// Server::bind("0.0.0.0:8080")
+    ///     .shutdown_timeout(10)
+    ///     .run_with_shutdown(handler, async {
+    ///         stop_signal.await;
+    ///     })
+    ///     .await?;
+    /// ```
+    pub async fn run_with_shutdown<S, F>(self, service: S, signal: F) -> Result<()>
+    where
+        S: HttpService + Clone + 'static,
+        F: Future<Output = ()>,
+    {
+        let listen = self.listen_label();
+        tracing::info!("Starting HTTP server on {}", listen);
 
         // Bind the listener
-        let mut listener = TcpListener::bind(&self.addr.to_string())
+        let mut listener = self
+            .open_listener()
             .await
-            .map_err(|e| Error::Io(format!("Failed to bind to {}: {}", self.addr, e)))?;
+            .map_err(|e| Error::Io(format!("Failed to bind to {}: {}", listen, e)))?;
+        if matches!(listener, Listener::Unix(_)) && self.config.tls.is_some() {
+            return Err(Error::Io(format!("TLS is not supported on {}", listen)));
+        }
 
-        tracing::info!("HTTP server listening on {}", self.addr);
+        tracing::info!("HTTP server listening on {}", listen);
 
         let service = Arc::new(service);
         let config = self.config.clone();
+        let state = Arc::new(ServerState::new());
+        let mut signal = pin!(signal);
 
         // Accept connections loop
         loop {
-            match listener.accept().await {
-                Ok((stream, peer_addr)) => {
+            // Apply back-pressure while the connection limit is reached
+            // 达到连接上限时施加背压
+            let capacity = state.wait_until(|s| s.has_capacity(config.max_connections));
+            if let SelectTwoOutput::Second(()) = select_two(capacity, signal.as_mut()).await {
+                break;
+            }
+
+            match select_two(Box::pin(listener.accept()), signal.as_mut()).await {
+                SelectTwoOutput::First(Ok((stream, peer_addr))) => {
                     let service = service.clone();
-                    spawn(handle_connection(stream, peer_addr, service, config.clone()));
+                    let guard = ConnectionGuard::new(state.clone());
+                    spawn(handle_connection(stream, peer_addr, service, config.clone(), guard));
                 },
-                Err(e) => {
+                SelectTwoOutput::First(Err(e)) => {
                     tracing::error!("Error accepting connection: {}", e);
                 },
+                SelectTwoOutput::Second(()) => break,
             }
         }
+
+        // Stop accepting and drain
+        // 停止接受并排空
+        drop(listener);
+        state.begin_shutdown();
+        tracing::info!(
+            "HTTP server on {} shutting down, draining {} connection(s)",
+            listen,
+            state.active_connections()
+        );
+
+        let drained = state.wait_until(|s| s.active_connections() == 0);
+        if timeout(config.shutdown_timeout(), drained).await.is_err() {
+            tracing::warn!(
+                "Shutdown timeout elapsed, forcing {} connection(s) closed",
+                state.active_connections()
+            );
+            state.force_close();
+        }
+
+        if let Listen::Unix(path) = &self.listen {
+            let _ = std::fs::remove_file(path);
+        }
+
+        tracing::info!("HTTP server on {} stopped", listen);
+        Ok(())
+    }
+
+    /// Bind or adopt the listening socket
+    /// 绑定或接管监听套接字
+    async fn open_listener(&self) -> std::io::Result<Listener> {
+        match &self.listen {
+            Listen::Tcp => Ok(Listener::Tcp(TcpListener::bind(&self.addr.to_string()).await?)),
+            Listen::Unix(path) => {
+                if std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+                    std::fs::remove_file(path)?;
+                }
+                Ok(Listener::Unix(UnixListener::bind(path)?))
+            },
+            Listen::Fd(fd) => {
+                let fd = ListenFd::from(fd.try_clone()?);
+                if fd.is_unix()? {
+                    Ok(Listener::Unix(fd.into_unix_listener()?))
+                } else {
+                    Ok(Listener::Tcp(fd.into_tcp_listener()?))
+                }
+            },
+        }
     }
 
-    /// Get the bound address
-    /// 获取绑定地址
+    /// Describe where the server listens, for log messages
+    /// 描述服务器的监听位置，用于日志消息
+    fn listen_label(&self) -> String {
+        match &self.listen {
+            Listen::Tcp => self.addr.to_string(),
+            Listen::Unix(path) => format!("unix:{}", path.display()),
+            Listen::Fd(fd) => format!("fd {}", fd.as_raw_fd()),
+        }
+    }
+
+    /// Get the bound TCP address (unused for Unix sockets and inherited sockets)
+    /// 获取绑定的TCP地址（Unix套接字和继承的套接字不使用）
     pub fn addr(&self) -> &SocketAddr {
         &self.addr
     }
@@ -163,102 +460,863 @@ impl Default for Server {
     }
 }
 
+/// State shared between the accept loop and its connections
+/// 接受循环与其连接之间共享的状态
+pub(crate) struct ServerState {
+    /// Number of open connections / 打开的连接数
+    active_connections: AtomicUsize,
+    /// Set once shutdown has started / 关闭开始后设置
+    shutting_down: AtomicBool,
+    /// Set once the drain deadline has passed / 排空截止时间过后设置
+    force_close: AtomicBool,
+    /// Tasks waiting for a state change / 等待状态变化的任务
+    waiters: Mutex<Vec<Waker>>,
+}
+
+impl ServerState {
+    pub(crate) fn new() -> Self {
+        Self {
+            active_connections: AtomicUsize::new(0),
+            shutting_down: AtomicBool::new(false),
+            force_close: AtomicBool::new(false),
+            waiters: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn active_connections(&self) -> usize {
+        self.active_connections.load(Ordering::Acquire)
+    }
+
+    fn has_capacity(&self, max_connections: usize) -> bool {
+        max_connections == 0 || self.active_connections() < max_connections
+    }
+
+    pub(crate) fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn is_force_closed(&self) -> bool {
+        self.force_close.load(Ordering::Acquire)
+    }
+
+    fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::Release);
+        self.notify();
+    }
+
+    fn force_close(&self) {
+        self.force_close.store(true, Ordering::Release);
+        self.notify();
+    }
+
+    fn notify(&self) {
+        let mut guard = self.waiters.lock().unwrap_or_else(PoisonError::into_inner);
+        let waiters = std::mem::take(&mut *guard);
+        drop(guard);
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+
+    /// Wait until `condition` holds
+    /// 等待直到 `condition` 成立
+    pub(crate) fn wait_until<C>(&self, condition: C) -> WaitUntil<'_, C>
+    where
+        C: Fn(&ServerState) -> bool,
+    {
+        WaitUntil {
+            state: self,
+            condition,
+        }
+    }
+}
+
+/// Future that resolves once a condition on the server state holds
+/// 服务器状态满足条件时完成的future
+pub(crate) struct WaitUntil<'a, C> {
+    state: &'a ServerState,
+    condition: C,
+}
+
+impl<C> Future for WaitUntil<'_, C>
+where
+    C: Fn(&ServerState) -> bool + Unpin,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if (self.condition)(self.state) {
+            return Poll::Ready(());
+        }
+
+        self.state
+            .waiters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(cx.waker().clone());
+
+        // Re-check to avoid missing a notification raced with registration
+        // 再次检查以避免错过与注册竞争的通知
+        if (self.condition)(self.state) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Keeps a connection counted as active until dropped
+/// 在释放之前保持连接被计为活动
+struct ConnectionGuard {
+    state: Arc<ServerState>,
+}
+
+impl ConnectionGuard {
+    fn new(state: Arc<ServerState>) -> Self {
+        state.active_connections.fetch_add(1, Ordering::AcqRel);
+        Self { state }
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.state.active_connections.fetch_sub(1, Ordering::AcqRel);
+        self.state.notify();
+    }
+}
+
+/// An accepted connection, in plain text or TLS
+/// 已接受的连接，明文或TLS
+pub(crate) enum ServerStream {
+    /// Plain TCP / 明文TCP
+    Plain(TcpStream),
+    /// Unix domain socket / Unix域套接字
+    Unix(UnixStream),
+    /// TLS over TCP / 基于TCP的TLS
+    Tls(Box<TlsStream>),
+    /// In-memory stream of a test client / 测试客户端的内存流
+    Memory(MemoryStream),
+}
+
+impl ServerStream {
+    /// Read some bytes, returning 0 once the peer has closed the connection
+    /// 读取一些字节，对端关闭连接后返回0
+    pub(crate) fn read<'a>(
+        &'a mut self,
+        buf: &'a mut [u8],
+    ) -> StreamFuture<'a, ReadFuture<'a, 'a>> {
+        match self {
+            ServerStream::Plain(tcp) => StreamFuture::Plain(tcp.read(buf)),
+            ServerStream::Unix(unix) => StreamFuture::Plain(unix.read(buf)),
+            ServerStream::Tls(tls) => StreamFuture::Boxed(Box::pin(tls.read(buf))),
+            ServerStream::Memory(memory) => StreamFuture::Boxed(Box::pin(memory.read(buf))),
+        }
+    }
+
+    /// Write all bytes
+    /// 写入所有字节
+    pub(crate) fn write_all<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+    ) -> StreamFuture<'a, WriteAllFuture<'a, 'a>> {
+        match self {
+            ServerStream::Plain(tcp) => StreamFuture::Plain(tcp.write_all(buf)),
+            ServerStream::Unix(unix) => StreamFuture::Plain(unix.write_all(buf)),
+            ServerStream::Tls(tls) => StreamFuture::Boxed(Box::pin(tls.write_all(buf))),
+            ServerStream::Memory(memory) => StreamFuture::Boxed(Box::pin(memory.write_all(buf))),
+        }
+    }
+
+    /// Shut down the socket without a TLS `close_notify`
+    /// 关闭套接字，不发送TLS `close_notify`
+    pub(crate) fn shutdown(&self, how: Shutdown) -> std::io::Result<()> {
+        match self {
+            ServerStream::Plain(tcp) => tcp.shutdown(how),
+            ServerStream::Unix(unix) => unix.shutdown(how),
+            ServerStream::Tls(tls) => tls.tcp().shutdown(how),
+            ServerStream::Memory(memory) => memory.shutdown(how),
+        }
+    }
+
+    /// Close the connection cleanly, sending `close_notify` over TLS
+    /// 干净地关闭连接，TLS连接会发送 `close_notify`
+    pub(crate) async fn close(&mut self) {
+        if let ServerStream::Tls(tls) = self
+            && timeout(CLOSE_NOTIFY_TIMEOUT, Box::pin(tls.close())).await.is_err()
+        {
+            tracing::debug!("Timed out sending TLS close_notify");
+        }
+    }
+
+    /// Verified client certificate of a TLS connection
+    /// TLS连接中经过校验的客户端证书
+    pub(crate) fn peer_certificate(&self) -> Option<PeerCertificate> {
+        match self {
+            ServerStream::Plain(_) | ServerStream::Unix(_) | ServerStream::Memory(_) => None,
+            ServerStream::Tls(tls) => tls.peer_certificate(),
+        }
+    }
+}
+
+/// Future of a [`ServerStream`] operation, `Unpin` so it can be selected on
+/// [`ServerStream`] 操作的future，实现了 `Unpin` 以便参与select
+pub(crate) enum StreamFuture<'a, F: Future> {
+    /// Plain TCP or Unix socket operation / 明文TCP或Unix套接字操作
+    Plain(F),
+    /// TLS or in-memory operation / TLS或内存操作
+    Boxed(Pin<Box<dyn Future<Output = F::Output> + Send + 'a>>),
+}
+
+impl<F: Future + Unpin> Future for StreamFuture<'_, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        match self.get_mut() {
+            StreamFuture::Plain(future) => Pin::new(future).poll(cx),
+            StreamFuture::Boxed(future) => future.as_mut().poll(cx),
+        }
+    }
+}
+
+/// Time allowed for sending the TLS `close_notify` alert
+/// 发送TLS `close_notify` 警报允许的时间
+const CLOSE_NOTIFY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Check whether the client asked to close the connection after this request
+/// 检查客户端是否要求在此请求后关闭连接
+fn wants_close(request: &Request) -> bool {
+    request
+        .header("connection")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("close"))
+}
+
+/// Build the response sent for a failed request
+/// 构建请求失败时发送的响应
+pub(crate) fn error_response(e: &Error) -> Response {
+    let status = StatusCode::from_u16(e.status_code());
+    Response::builder()
+        .status(status)
+        .body(Body::from(e.to_string()))
+        .unwrap_or_else(|_| Response::new(status))
+}
+
+/// Build the context used to encode a response
+/// 构建用于编码响应的上下文
+fn response_context(version: proto::HttpVersion, keep_alive: bool) -> proto::ConnectionContext {
+    let mut ctx = proto::ConnectionContext::new();
+    ctx.set_version(version);
+    ctx.set_keep_alive(keep_alive);
+    ctx
+}
+
+/// Encode and write a response, returning false if the connection is broken
+/// 编码并写入响应，如果连接已断开则返回false
+///
+/// Only the head is written for streaming bodies; see [`write_stream_body`].
+/// 对于流式body只写入头部；参见 [`write_stream_body`]。
+async fn write_response(
+    stream: &mut ServerStream,
+    peer_addr: SocketAddr,
+    response: &Response,
+    ctx: proto::ConnectionContext,
+) -> bool {
+    let encoder = proto::ResponseEncoder::with_context(ctx);
+
+    match encoder.encode(response) {
+        Ok(bytes) => {
+            if let Err(e) = stream.write_all(&bytes).await {
+                tracing::error!("Write error to {}: {}", peer_addr, e);
+                return false;
+            }
+            true
+        },
+        Err(e) => {
+            tracing::error!("Encode error from {}: {}", peer_addr, e);
+            false
+        },
+    }
+}
+
+/// Write an error response and mark the connection for closing
+/// 写入错误响应并标记连接将关闭
+async fn write_error(stream: &mut ServerStream, peer_addr: SocketAddr, e: &Error) {
+    let ctx = response_context(proto::HttpVersion::Http11, false);
+    write_response(stream, peer_addr, &error_response(e), ctx).await;
+}
+
+/// Write a streaming body, optionally with chunked framing
+/// 写入流式body，可选使用分块分帧
+///
+/// Each chunk is fully written before the next one is pulled from the stream, so a
+/// slow client slows down the producer instead of growing a buffer.
+///
+/// 每个数据块完全写出后才从流中拉取下一个，因此慢速客户端会减慢生产者，而不是使缓冲区增长。
+///
+/// File bodies go to plain TCP connections with `sendfile(2)`, without passing
+/// through user space.
+/// 文件body通过 `sendfile(2)` 发送到明文TCP连接，无需经过用户空间。
+async fn write_stream_body(
+    stream: &mut ServerStream,
+    body: &StreamBody,
+    chunked: bool,
+) -> std::io::Result<()> {
+    if !chunked
+        && let ServerStream::Plain(tcp) = stream
+        && let Some((file, len)) = body.take_file()
+    {
+        let sent = nexus_runtime::fs::sendfile(&file, tcp, 0, len).await?;
+        if sent < len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "file ended before the announced length",
+            ));
+        }
+        return Ok(());
+    }
+    while let Some(chunk) = std::future::poll_fn(|cx| body.poll_chunk(cx)).await {
+        let chunk = chunk.map_err(|e| std::io::Error::other(e.to_string()))?;
+        if chunk.is_empty() {
+            continue;
+        }
+        if chunked {
+            stream.write_all(&proto::encode_chunk(&chunk)).await?;
+        } else {
+            stream.write_all(&chunk).await?;
+        }
+    }
+    if chunked {
+        stream.write_all(proto::LAST_CHUNK).await?;
+    }
+    Ok(())
+}
+
+/// Check whether the buffered bytes start the HTTP/2 connection preface
+/// 检查缓冲的字节是否以HTTP/2连接前言开头
+fn starts_preface(buffered: &[u8]) -> bool {
+    let n = buffered.len().min(PREFACE.len());
+    n > 0 && buffered[..n] == PREFACE[..n]
+}
+
+/// Extract the decoded `HTTP2-Settings` of an `Upgrade: h2c` request
+/// 提取 `Upgrade: h2c` 请求中解码后的 `HTTP2-Settings`
+fn h2c_upgrade(request: &Request) -> Option<Vec<u8>> {
+    let upgrade = request.header("upgrade")?;
+    if !upgrade.split(',').any(|p| p.trim().eq_ignore_ascii_case("h2c")) {
+        return None;
+    }
+    let settings = request.header("http2-settings")?;
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(settings.trim().trim_end_matches('='))
+        .ok()
+}
+
+/// Hand the connection over to the HTTP/2 driver
+/// 将连接移交给HTTP/2驱动
+async fn serve_http2<S>(
+    stream: ServerStream,
+    peer_addr: SocketAddr,
+    service: Arc<S>,
+    config: &ServerConfig,
+    state: Arc<ServerState>,
+    buffered: &[u8],
+    upgrade: Option<Upgrade>,
+) where
+    S: HttpService + 'static,
+{
+    let options = ServeOptions {
+        config: config.http2.clone().unwrap_or_default(),
+        request_timeout: config.request_timeout(),
+        keep_alive_timeout: config.keep_alive_timeout(),
+        max_body_size: config.max_buffer_size,
+    };
+    serve_connection(stream, peer_addr, service, options, state, buffered, upgrade).await;
+}
+
+/// Check whether a response declares its body length
+/// 检查响应是否声明了body长度
+fn has_content_length(response: &Response) -> bool {
+    response
+        .headers()
+        .keys()
+        .any(|k| k.eq_ignore_ascii_case("content-length"))
+}
+
+/// Body chunks buffered between the connection and the handler reading them
+/// 在连接与读取它们的处理器之间缓冲的body数据块数
+const REQUEST_BODY_CHUNKS: usize = 4;
+
+/// Connection side of a request body streamed to the handler
+/// 流式传给处理器的请求body的连接端
+struct BodyFeed {
+    /// Sender of body chunks, `None` once the body ended or its reader is gone
+    sender: Option<mpsc::Sender<Result<Bytes>>>,
+    /// Where the trailer fields go once the body has been read
+    trailers: Trailers,
+}
+
+impl BodyFeed {
+    /// Give `request` a body that is fed as it is read off the connection
+    /// 为 `request` 设置一个随着从连接读取而填充的body
+    fn attach(request: &mut Request) -> Self {
+        let (sender, receiver) = mpsc::channel(REQUEST_BODY_CHUNKS);
+        let trailers = Trailers::pending();
+        *request.inner_mut().body_mut() = Body::from_stream(receiver);
+        request.extensions_mut().insert(trailers.clone());
+        Self {
+            sender: Some(sender),
+            trailers,
+        }
+    }
+
+    /// Check whether the body still has a reader
+    /// 检查body是否仍有读取方
+    fn wanted(&self) -> bool {
+        self.sender.as_ref().is_some_and(|s| !s.is_closed())
+    }
+
+    /// Pass on a chunk, returning false if nobody reads the body anymore
+    /// 传递一个数据块，如果已无人读取body则返回false
+    async fn send(&mut self, data: Bytes) -> bool {
+        let Some(sender) = self.sender.as_mut() else {
+            return false;
+        };
+        if sender.send(Ok(data)).await.is_ok() {
+            return true;
+        }
+        self.sender = None;
+        false
+    }
+
+    /// End the body, making the trailers visible before the stream ends
+    /// 结束body，在流结束之前使尾部字段可见
+    fn finish(&mut self, trailers: http::HeaderMap) {
+        self.trailers.set(trailers);
+        self.sender = None;
+    }
+
+    /// End the body with an error
+    /// 以错误结束body
+    async fn fail(&mut self, e: Error) {
+        if let Some(sender) = self.sender.as_mut() {
+            let _ = sender.send(Err(e)).await;
+        }
+        self.sender = None;
+    }
+}
+
+impl Drop for BodyFeed {
+    fn drop(&mut self) {
+        // A body abandoned half way must not look complete to a late reader
+        // 中途放弃的body不能让之后的读取方误以为已完整
+        if let Some(sender) = self.sender.as_mut() {
+            let _ = sender.try_send(Err(Error::IncompleteRequest));
+        }
+    }
+}
+
+/// Read the rest of a request body off the connection and hand it to `feed`
+/// 从连接读取请求body的剩余部分并交给 `feed`
+///
+/// Chunks are passed on as they are decoded, so the connection holds at most one
+/// read buffer of the body and a handler that reads slowly slows down the client.
+/// Once the body has no reader the rest is skipped, up to `skip_limit` bytes.
+/// Returns whether the body was read to the end.
+///
+/// 数据块解码后立即传递，因此连接最多持有一个读缓冲区大小的body，读取缓慢的处理器会
+/// 减慢客户端。body没有读取方后，剩余部分会被跳过，最多 `skip_limit` 字节。
+/// 返回body是否已读取完毕。
+async fn read_body(
+    stream: &mut ServerStream,
+    parser: &mut proto::RequestParser,
+    read_buf: &mut [u8],
+    feed: &mut BodyFeed,
+    skip_limit: usize,
+) -> bool {
+    let mut skipped = 0usize;
+    loop {
+        let frame = match parser.decode_body() {
+            Ok(frame) => frame,
+            Err(e) => {
+                feed.fail(e).await;
+                return false;
+            },
+        };
+        match frame {
+            Some(proto::BodyFrame::Data(data)) => {
+                let len = data.len();
+                if !feed.send(data).await {
+                    skipped += len;
+                    if skipped > skip_limit {
+                        return false;
+                    }
+                }
+            },
+            Some(proto::BodyFrame::End(trailers)) => {
+                feed.finish(trailers);
+                return true;
+            },
+            None => {
+                // The client holds back the body until we agree to receive it, which
+                // is pointless once nobody reads it
+                // 客户端在我们同意接收之前暂不发送body；无人读取时则无需同意
+                if parser.take_expect_continue() {
+                    if !feed.wanted() {
+                        return false;
+                    }
+                    if let Err(e) = stream.write_all(proto::CONTINUE_RESPONSE).await {
+                        feed.fail(Error::Io(e.to_string())).await;
+                        return false;
+                    }
+                }
+                let room = read_room(parser, read_buf);
+                match stream.read(&mut read_buf[..room]).await {
+                    Ok(0) => {
+                        feed.fail(Error::IncompleteRequest).await;
+                        return false;
+                    },
+                    Ok(n) => {
+                        if let Err(e) = parser.feed(&read_buf[..n]) {
+                            feed.fail(e).await;
+                            return false;
+                        }
+                    },
+                    Err(e) => {
+                        feed.fail(Error::Io(e.to_string())).await;
+                        return false;
+                    },
+                }
+            },
+        }
+    }
+}
+
+/// Number of bytes to read next without overflowing the parser's buffer
+/// 下一次读取的字节数，不会使解析器的缓冲区溢出
+///
+/// At least one byte is read, so a full buffer surfaces as a parse error.
+/// 至少读取一个字节，因此缓冲区已满时会以解析错误的形式体现。
+fn read_room(parser: &proto::RequestParser, read_buf: &[u8]) -> usize {
+    read_buf
+        .len()
+        .saturating_sub(parser.buffered_len())
+        .clamp(1, read_buf.len())
+}
+
+/// Run a handler while the rest of its request body is read off the connection
+/// 在从连接读取请求body剩余部分的同时运行处理器
+///
+/// The body stops being read as soon as the handler finishes. Returns the
+/// handler's output and whether the body was read to the end.
+///
+/// 处理器结束后立即停止读取body。返回处理器的输出以及body是否已读取完毕。
+async fn call_reading_body<F, B>(call: F, body: Option<B>) -> (F::Output, bool)
+where
+    F: Future,
+    B: Future<Output = bool>,
+{
+    let Some(body) = body else {
+        return (call.await, true);
+    };
+    let mut call = pin!(call);
+    let mut body = pin!(body);
+    let mut body_read = None;
+    std::future::poll_fn(|cx| {
+        if body_read.is_none()
+            && let Poll::Ready(read) = body.as_mut().poll(cx)
+        {
+            body_read = Some(read);
+        }
+        call.as_mut()
+            .poll(cx)
+            .map(|output| (output, body_read.unwrap_or(false)))
+    })
+    .await
+}
+
+/// Response accepting an `Upgrade: h2c` request
+/// 接受 `Upgrade: h2c` 请求的响应
+const SWITCHING_TO_H2C: &[u8] =
+    b"HTTP/1.1 101 Switching Protocols\r\nconnection: Upgrade\r\nupgrade: h2c\r\n\r\n";
+
 /// Handle a single connection
 /// 处理单个连接
+///
+/// Idle time between requests is bounded by the keep-alive timeout; reading a
+/// request and running its handler is bounded by the request timeout.
+/// 请求之间的空闲时间受keep-alive超时限制；读取请求和执行处理器受请求超时限制。
 async fn handle_connection<S>(
-    mut stream: TcpStream,
+    stream: ServerStream,
     peer_addr: SocketAddr,
     service: Arc<S>,
     config: ServerConfig,
+    guard: ConnectionGuard,
 ) where
     S: HttpService + 'static,
 {
-    let mut parser = proto::RequestParser::new();
-    let encoder = proto::ResponseEncoder::new();
+    let state = guard.state.clone();
+    let mut stream = match (stream, config.tls.as_ref()) {
+        (ServerStream::Plain(stream), Some(acceptor)) => {
+            let handshake = timeout(config.request_timeout(), Box::pin(acceptor.accept(stream)));
+            let forced = state.wait_until(ServerState::is_force_closed);
+            match select_two(handshake, forced).await {
+                SelectTwoOutput::First(Ok(Ok(tls))) => ServerStream::Tls(Box::new(tls)),
+                SelectTwoOutput::First(Ok(Err(e))) => {
+                    tracing::debug!("TLS handshake with {} failed: {}", peer_addr, e);
+                    return;
+                },
+                SelectTwoOutput::First(Err(_elapsed)) => {
+                    tracing::debug!("TLS handshake timeout for {}", peer_addr);
+                    return;
+                },
+                SelectTwoOutput::Second(()) => return,
+            }
+        },
+        (stream, _) => stream,
+    };
+
+    // The client certificate applies to every request on the connection
+    // 客户端证书适用于该连接上的每个请求
+    let peer_certificate = stream.peer_certificate();
+
+    // HTTP/2 negotiated through ALPN needs no preface sniffing
+    // 通过ALPN协商的HTTP/2无需嗅探前言
+    let alpn_h2 = matches!(&stream,
+        ServerStream::Tls(tls) if tls.alpn_protocol() == Some(b"h2".as_slice()));
+    if alpn_h2 {
+        serve_http2(stream, peer_addr, service, &config, state, &[], None).await;
+        return;
+    }
+
+    let mut ctx = proto::ConnectionContext::new();
+    ctx.set_max_buffer_size(config.max_buffer_size);
+    let mut parser = proto::RequestParser::with_context(ctx);
+    let mut read_buf = vec![0u8; config.max_buffer_size];
+    // When the first byte of the current request arrived
+    // 当前请求的第一个字节到达的时间
+    let mut request_started: Option<Instant> = None;
 
     tracing::debug!("New connection from {}", peer_addr);
 
     loop {
-        // Read data from the stream
-        let mut read_buf = vec![0u8; config.max_buffer_size];
-        match stream.read(&mut read_buf).await {
-            Ok(0) => {
-                // Connection closed by peer
-                tracing::debug!("Connection closed by {}", peer_addr);
+        // HTTP/2 with prior knowledge starts with the connection preface
+        // 使用先验知识的HTTP/2以连接前言开头
+        let h2_preface = config.http2.is_some() && starts_preface(parser.buffered());
+        if h2_preface && parser.buffered_len() >= PREFACE.len() {
+            let buffered = parser.buffered();
+            serve_http2(stream, peer_addr, service, &config, state, buffered, None).await;
+            return;
+        }
+
+        // Try to parse a request from what is already buffered, unless it may still
+        // turn out to be the HTTP/2 preface
+        // 尝试从已缓冲的数据中解析请求，除非它仍可能是HTTP/2前言
+        let parsed = if h2_preface { Ok(None) } else { parser.parse() };
+        let request = match parsed {
+            Ok(Some((request, _used))) => Some(request),
+            Ok(None) => None,
+            Err(e) => {
+                tracing::error!("Parse error from {}: {}", peer_addr, e);
+                write_error(&mut stream, peer_addr, &e).await;
                 break;
             },
-            Ok(n) => {
-                // Feed data to parser
-                if let Err(e) = parser.feed(&read_buf[..n]) {
-                    tracing::error!("Parse error from {}: {}", peer_addr, e);
-                    break;
-                }
+        };
 
-                // Try to parse request(s)
-                loop {
-                    match parser.parse() {
-                        Ok(Some((request, _used))) => {
-                            tracing::debug!(
-                                "Request from {}: {} {}",
-                                peer_addr,
-                                request.method(),
-                                request.path()
-                            );
-
-                            // Handle the request
-                            let response = match service.call(request).await {
-                                Ok(resp) => resp,
-                                Err(e) => {
-                                    tracing::error!("Handler error from {}: {}", peer_addr, e);
-                                    // Return error response
-                                    let status = crate::StatusCode::from_u16(e.status_code());
-                                    Response::builder()
-                                        .status(status)
-                                        .body(crate::Body::from(e.to_string()))
-                                        .unwrap()
-                                },
-                            };
-
-                            // Encode response
-                            match encoder.encode(&response) {
-                                Ok(bytes) => {
-                                    if let Err(e) = stream.write_all(&bytes).await {
-                                        tracing::error!("Write error to {}: {}", peer_addr, e);
-                                        break;
-                                    }
-                                },
-                                Err(e) => {
-                                    tracing::error!("Encode error from {}: {}", peer_addr, e);
-                                    break;
-                                },
-                            }
+        let Some(mut request) = request else {
+            // Need more data: idle connections wait for the keep-alive timeout and
+            // are closed on shutdown, partial requests wait for the request deadline
+            // 需要更多数据：空闲连接等待keep-alive超时并在关闭时关闭，
+            // 部分请求等待请求截止时间
+            let idle = parser.buffered_len() == 0;
+            if idle && state.is_shutting_down() {
+                tracing::debug!("Closing idle connection from {} (shutdown)", peer_addr);
+                break;
+            }
 
-                            // Check if we should keep the connection alive
-                            if !encoder.context().keep_alive() {
-                                tracing::debug!(
-                                    "Closing connection from {} (no keep-alive)",
-                                    peer_addr
-                                );
-                                return;
-                            }
-                        },
-                        Ok(None) => {
-                            // Need more data
-                            break;
-                        },
-                        Err(e) => {
-                            tracing::error!("Parse error from {}: {}", peer_addr, e);
-                            break;
-                        },
+            let deadline = match request_started {
+                Some(started) if !idle => started + config.request_timeout(),
+                _ => Instant::now() + config.keep_alive_timeout(),
+            };
+
+            let room = read_room(&parser, &read_buf);
+            let read = timeout_at(deadline, stream.read(&mut read_buf[..room]));
+            let interrupted = state.wait_until(|s| {
+                s.is_force_closed() || (idle && s.is_shutting_down())
+            });
+
+            match select_two(read, interrupted).await {
+                SelectTwoOutput::First(Ok(Ok(0))) => {
+                    // Connection closed by peer
+                    tracing::debug!("Connection closed by {}", peer_addr);
+                    break;
+                },
+                SelectTwoOutput::First(Ok(Ok(n))) => {
+                    if request_started.is_none() {
+                        request_started = Some(Instant::now());
                     }
-                }
-            },
-            Err(e) => {
-                tracing::error!("Read error from {}: {}", peer_addr, e);
+                    if let Err(e) = parser.feed(&read_buf[..n]) {
+                        tracing::error!("Parse error from {}: {}", peer_addr, e);
+                        write_error(&mut stream, peer_addr, &e).await;
+                        break;
+                    }
+                },
+                SelectTwoOutput::First(Ok(Err(e))) => {
+                    tracing::error!("Read error from {}: {}", peer_addr, e);
+                    break;
+                },
+                SelectTwoOutput::First(Err(_elapsed)) => {
+                    if idle {
+                        tracing::debug!("Keep-alive timeout for {}", peer_addr);
+                    } else {
+                        tracing::warn!("Request timeout reading from {}", peer_addr);
+                        let e = Error::Timeout("Request not received in time".to_string());
+                        write_error(&mut stream, peer_addr, &e).await;
+                    }
+                    break;
+                },
+                SelectTwoOutput::Second(()) => {
+                    tracing::debug!("Closing connection from {} (shutdown)", peer_addr);
+                    break;
+                },
+            }
+            continue;
+        };
+
+        tracing::debug!("Request from {}: {} {}", peer_addr, request.method(), request.path());
+        if let Some(certificate) = &peer_certificate {
+            request.extensions_mut().insert(certificate.clone());
+        }
+
+        // `Upgrade: h2c` turns the request into stream 1 of an HTTP/2 connection
+        // `Upgrade: h2c` 将请求变为HTTP/2连接的流1
+        if config.http2.is_some()
+            && !state.is_shutting_down()
+            && !parser.reading_body()
+            && let Some(settings) = h2c_upgrade(&request)
+        {
+            if let Err(e) = stream.write_all(SWITCHING_TO_H2C).await {
+                tracing::error!("Write error to {}: {}", peer_addr, e);
+                break;
+            }
+            let upgrade = Some(Upgrade { request, settings });
+            let buffered = parser.buffered();
+            serve_http2(stream, peer_addr, service, &config, state, buffered, upgrade).await;
+            return;
+        }
+
+        let deadline = request_started.unwrap_or_else(Instant::now) + config.request_timeout();
+        request_started = None;
+        let client_close = wants_close(&request);
+        let version = if request.inner().version() == http::Version::HTTP_10 {
+            proto::HttpVersion::Http10
+        } else {
+            proto::HttpVersion::Http11
+        };
+
+        // A body that has not fully arrived is streamed to the handler as it is read
+        // 尚未完全到达的body在读取时流式传给处理器
+        let mut body_feed = parser.reading_body().then(|| BodyFeed::attach(&mut request));
+
+        // Handle the request, bounded by the request deadline and forced shutdown
+        // 处理请求，受请求截止时间和强制关闭限制
+        let call = timeout_at(deadline, Box::pin(service.call(request)));
+        let reading = body_feed
+            .as_mut()
+            .map(|feed| read_body(&mut stream, &mut parser, &mut read_buf, feed, usize::MAX));
+        let serve = Box::pin(call_reading_body(call, reading));
+        let forced = state.wait_until(ServerState::is_force_closed);
+        let (result, mut body_read) = match select_two(serve, forced).await {
+            SelectTwoOutput::First(output) => output,
+            SelectTwoOutput::Second(()) => {
+                tracing::debug!("Aborting request from {} (forced shutdown)", peer_addr);
                 break;
             },
+        };
+        let mut response = match result {
+            Ok(Ok(resp)) => resp,
+            Ok(Err(e)) => {
+                tracing::error!("Handler error from {}: {}", peer_addr, e);
+                error_response(&e)
+            },
+            Err(_elapsed) => {
+                tracing::warn!("Request timeout handling request from {}", peer_addr);
+                error_response(&Error::Timeout("Request handling timed out".to_string()))
+            },
+        };
+
+        // Skip what the handler left of the body so the next request can be parsed;
+        // a remainder too large to skip closes the connection instead
+        // 跳过处理器未读取的body部分以便解析下一个请求；剩余部分过大时改为关闭连接
+        if let Some(feed) = body_feed.as_mut()
+            && !body_read
+        {
+            let skip = read_body(&mut stream, &mut parser, &mut read_buf, feed,
+                config.max_buffer_size);
+            let skip = timeout_at(deadline, Box::pin(skip));
+            let forced = state.wait_until(ServerState::is_force_closed);
+            body_read = matches!(select_two(skip, forced).await, SelectTwoOutput::First(Ok(true)));
+        }
+        drop(body_feed);
+
+        // Finish the connection after this response when draining, or when the body
+        // is a stream that can only be delimited by closing the connection
+        // 排空时在此响应后结束连接，或者当body是只能通过关闭连接来分隔的流时
+        let mut ctx = response_context(version, true);
+        let chunked = proto::is_chunked(&response, &ctx);
+        let close_delimited =
+            response.body().is_stream() && !chunked && !has_content_length(&response);
+        let keep_alive =
+            !client_close && !state.is_shutting_down() && !close_delimited && body_read;
+        ctx.set_keep_alive(keep_alive);
+        if !write_response(&mut stream, peer_addr, &response, ctx).await {
+            break;
+        }
+
+        // After `101 Switching Protocols` the connection belongs to the handler's
+        // takeover callback; without one nothing else can be spoken on it
+        // `101 Switching Protocols` 之后连接归处理器的接管回调所有；没有回调时无法再使用该连接
+        if response.status() == StatusCode::SWITCHING_PROTOCOLS {
+            if let Some(on_upgrade) = response.take_upgrade().and_then(|u| u.take()) {
+                tracing::debug!("Connection from {} upgraded", peer_addr);
+                let upgraded = Upgraded {
+                    stream,
+                    buffered: Bytes::copy_from_slice(parser.buffered()),
+                    state: state.clone(),
+                };
+                let run = on_upgrade(upgraded);
+                let forced = state.wait_until(ServerState::is_force_closed);
+                if let SelectTwoOutput::Second(()) = select_two(run, forced).await {
+                    tracing::debug!("Aborting upgraded connection from {} (forced shutdown)",
+                        peer_addr);
+                }
+                return;
+            }
+            break;
+        }
+
+        if let Body::Stream(body) = response.body() {
+            let write = Box::pin(write_stream_body(&mut stream, body, chunked));
+            let forced = state.wait_until(ServerState::is_force_closed);
+            match select_two(write, forced).await {
+                SelectTwoOutput::First(Ok(())) => {},
+                SelectTwoOutput::First(Err(e)) => {
+                    tracing::error!("Error streaming response to {}: {}", peer_addr, e);
+                    break;
+                },
+                SelectTwoOutput::Second(()) => {
+                    tracing::debug!("Aborting response to {} (forced shutdown)", peer_addr);
+                    break;
+                },
+            }
+        }
+
+        if !keep_alive {
+            tracing::debug!("Closing connection from {} (no keep-alive)", peer_addr);
+            break;
         }
     }
+
+    stream.close().await;
+    drop(guard);
 }
 
 /// Builder for creating servers
@@ -304,6 +1362,27 @@ impl ServerBuilder {
         self
     }
 
+    /// Set the graceful shutdown drain timeout
+    /// 设置优雅关闭的排空超时时间
+    pub fn shutdown_timeout(mut self, timeout: u64) -> Self {
+        self.config.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Enable cleartext HTTP/2 (h2c)
+    /// 启用明文HTTP/2（h2c）
+    pub fn http2(mut self, config: Http2Config) -> Self {
+        self.config.http2 = Some(config);
+        self
+    }
+
+    /// Terminate TLS with the given acceptor
+    /// 使用给定的接受器终止TLS
+    pub fn tls(mut self, acceptor: TlsAcceptor) -> Self {
+        self.config.tls = Some(acceptor);
+        self
+    }
+
     /// Build the server
     /// 构建服务器
     pub fn build(self) -> Server {
@@ -311,6 +1390,7 @@ impl ServerBuilder {
             addr: self
                 .addr
                 .unwrap_or_else(|| SocketAddr::from(([127, 0, 0, 1], 8080))),
+            listen: Listen::Tcp,
             config: self.config,
         }
     }
@@ -351,4 +1431,783 @@ mod tests {
         assert_eq!(server.config().max_connections, 1000);
         assert_eq!(server.config().request_timeout, 60);
     }
+
+    #[test]
+    fn test_server_config_durations() {
+        let server = Server::new().keep_alive_timeout(5).shutdown_timeout(7);
+        assert_eq!(server.config().keep_alive_timeout(), Duration::from_secs(5));
+        assert_eq!(server.config().shutdown_timeout(), Duration::from_secs(7));
+        assert_eq!(server.config().request_timeout(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_server_state_capacity() {
+        let state = Arc::new(ServerState::new());
+        assert!(state.has_capacity(1));
+
+        let guard = ConnectionGuard::new(state.clone());
+        assert_eq!(state.active_connections(), 1);
+        assert!(!state.has_capacity(1));
+        assert!(state.has_capacity(0));
+
+        drop(guard);
+        assert_eq!(state.active_connections(), 0);
+        assert!(state.has_capacity(1));
+    }
+
+    mod live {
+        use super::*;
+        use crate::http2::frame::{self, Frame};
+        use crate::http2::{ErrorCode, StreamId, hpack};
+        use crate::websocket::{Message, WebSocketConfig, WebSocketUpgrade};
+        use crate::tls::TlsConfig;
+        use nexus_runtime::sync::Notify;
+        use std::io::{Read, Write};
+        use std::sync::mpsc;
+
+        /// Pick a free local port
+        fn free_addr() -> String {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().to_string()
+        }
+
+        /// Start a server on a background thread, returning a shutdown trigger and
+        /// a receiver that fires when `run_with_shutdown` returns
+        fn start(server: Server) -> (Arc<Notify>, mpsc::Receiver<Result<()>>) {
+            start_with(server, |_req: Request| async {
+                nexus_runtime::time::sleep(Duration::from_millis(300)).await;
+                Ok(Response::builder().body(Body::from("done")).unwrap())
+            })
+        }
+
+        /// Start a server with a custom handler
+        fn start_with<S>(
+            server: Server,
+            handler: S,
+        ) -> (Arc<Notify>, mpsc::Receiver<Result<()>>)
+        where
+            S: HttpService + Clone + 'static,
+        {
+            let stop = Arc::new(Notify::new());
+            let (tx, rx) = mpsc::channel();
+            let notify = stop.clone();
+            std::thread::spawn(move || {
+                let signal = async move { notify.notified().await };
+                let result =
+                    nexus_runtime::task::block_on(server.run_with_shutdown(handler, signal));
+                let _ = tx.send(result);
+            });
+            (stop, rx)
+        }
+
+        fn connect(addr: &str) -> std::net::TcpStream {
+            for _ in 0..100 {
+                if let Ok(stream) = std::net::TcpStream::connect(addr) {
+                    stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+                    return stream;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            panic!("server did not start on {}", addr);
+        }
+
+        fn read_to_close(stream: &mut impl Read) -> String {
+            let mut out = Vec::new();
+            let _ = stream.read_to_end(&mut out);
+            String::from_utf8_lossy(&out).into_owned()
+        }
+
+        /// Read a response head, leaving whatever follows it unread
+        fn read_head(stream: &mut impl Read) -> String {
+            let mut head = Vec::new();
+            let mut byte = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") {
+                stream.read_exact(&mut byte).unwrap();
+                head.push(byte[0]);
+            }
+            String::from_utf8(head).unwrap()
+        }
+
+        #[test]
+        fn test_graceful_shutdown_drains_in_flight_request() {
+            let addr = free_addr();
+            let (stop, done) = start(Server::bind(addr.clone()).shutdown_timeout(5));
+
+            let mut client = connect(&addr);
+            client.write_all(b"GET / HTTP/1.1\r\nHost: test\r\n\r\n").unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+            stop.notify_one();
+
+            let response = read_to_close(&mut client);
+            assert!(response.starts_with("HTTP/1.1 200 OK"), "got: {}", response);
+            assert!(response.contains("connection: close"));
+            assert!(response.ends_with("done"));
+
+            let result = done.recv_timeout(Duration::from_secs(10)).unwrap();
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn test_idle_connection_closed_after_keep_alive_timeout() {
+            let addr = free_addr();
+            let (stop, _done) = start(Server::bind(addr.clone()).keep_alive_timeout(1));
+
+            let mut client = connect(&addr);
+            let started = Instant::now();
+            let response = read_to_close(&mut client);
+            assert!(response.is_empty());
+            assert!(started.elapsed() >= Duration::from_millis(900));
+            assert!(started.elapsed() < Duration::from_secs(5));
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_slow_request_cut_off_with_408() {
+            let addr = free_addr();
+            let (stop, _done) = start(Server::bind(addr.clone()).request_timeout(1));
+
+            let mut client = connect(&addr);
+            client.write_all(b"GET / HTTP/1.1\r\nHost: te").unwrap();
+            let response = read_to_close(&mut client);
+            assert!(response.starts_with("HTTP/1.1 408"), "got: {}", response);
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_expect_continue_with_chunked_body() {
+            let addr = free_addr();
+            let (stop, _done) = start(Server::bind(addr.clone()));
+
+            let mut client = connect(&addr);
+            client
+                .write_all(
+                    b"POST / HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\
+                      Expect: 100-continue\r\nTransfer-Encoding: chunked\r\n\r\n",
+                )
+                .unwrap();
+            let mut interim = [0u8; proto::CONTINUE_RESPONSE.len()];
+            client.read_exact(&mut interim).unwrap();
+            assert_eq!(&interim[..], proto::CONTINUE_RESPONSE);
+
+            client.write_all(b"4\r\nbody\r\n0\r\n\r\n").unwrap();
+            let response = read_to_close(&mut client);
+            assert!(response.starts_with("HTTP/1.1 200 OK"), "got: {}", response);
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_request_body_larger_than_buffer_is_streamed() {
+            let addr = free_addr();
+            let (stop, _done) = start_with(Server::bind(addr.clone()), |req: Request| async move {
+                let body = req.body().clone().collect().await?;
+                let checksum = req
+                    .trailers()
+                    .and_then(|t| t.get("checksum"))
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("-")
+                    .to_string();
+                Ok(Response::builder()
+                    .body(Body::from(format!("{} {}", body.len(), checksum)))
+                    .unwrap())
+            });
+
+            // Both uploads are far beyond the 64 KiB buffer limit
+            let data = vec![b'x'; 1 << 20];
+            let mut client = connect(&addr);
+            let mut writer = client.try_clone().unwrap();
+            let upload = std::thread::spawn(move || {
+                let head = format!("PUT / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", data.len());
+                writer.write_all(head.as_bytes()).unwrap();
+                writer.write_all(&data).unwrap();
+
+                writer
+                    .write_all(b"PUT / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n")
+                    .unwrap();
+                for chunk in data.chunks(60_000) {
+                    writer.write_all(&proto::encode_chunk(chunk)).unwrap();
+                }
+                writer.write_all(b"0\r\nChecksum: abc\r\n\r\n").unwrap();
+                writer.write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+            });
+
+            let response = read_to_close(&mut client);
+            upload.join().unwrap();
+            assert!(response.contains("\r\n\r\n1048576 -HTTP/1.1"), "got: {}", response);
+            assert!(response.contains("\r\n\r\n1048576 abcHTTP/1.1"), "got: {}", response);
+            assert!(response.ends_with("\r\n\r\n0 -"), "got: {}", response);
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_unread_request_body_is_skipped() {
+            let addr = free_addr();
+            let (stop, _done) = start_with(Server::bind(addr.clone()), |req: Request| async move {
+                Ok(Response::builder().body(Body::from(req.path().to_string())).unwrap())
+            });
+
+            // The first body arrives after the head, so the handler never sees it
+            let mut client = connect(&addr);
+            client.write_all(b"POST /a HTTP/1.1\r\nContent-Length: 5\r\n\r\n").unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            client
+                .write_all(b"helloGET /b HTTP/1.1\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            let response = read_to_close(&mut client);
+            assert!(response.contains("\r\n\r\n/aHTTP/1.1 200"), "got: {}", response);
+            assert!(response.ends_with("\r\n\r\n/b"), "got: {}", response);
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_streaming_response_is_chunked() {
+            let addr = free_addr();
+            let (stop, _done) = start_with(Server::bind(addr.clone()), |_req: Request| async {
+                let chunks = ["one", "two", "three"].map(|s| Bytes::from_static(s.as_bytes()));
+                let body = Body::wrap_stream(futures::stream::iter(chunks));
+                Ok(Response::builder().body(body).unwrap())
+            });
+
+            let mut client = connect(&addr);
+            client.write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+            let response = read_to_close(&mut client);
+            assert!(response.contains("transfer-encoding: chunked\r\n"), "got: {}", response);
+            let body = "3\r\none\r\n3\r\ntwo\r\n5\r\nthree\r\n0\r\n\r\n";
+            assert!(response.ends_with(&format!("\r\n\r\n{}", body)), "got: {}", response);
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_file_response_is_sent_whole() {
+            let path = std::env::temp_dir().join(format!("nexus-http-file-{}", std::process::id()));
+            std::fs::write(&path, "file contents").unwrap();
+            let addr = free_addr();
+            let file_path = path.clone();
+            let (stop, _done) = start_with(Server::bind(addr.clone()), move |_req: Request| {
+                let file_path = file_path.clone();
+                async move {
+                    let file = nexus_runtime::fs::File::open(&file_path).await.unwrap();
+                    Ok(Response::builder()
+                        .header("content-length", "13")
+                        .body(Body::from_file(file, 13))
+                        .unwrap())
+                }
+            });
+
+            let mut client = connect(&addr);
+            client.write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+            let response = read_to_close(&mut client);
+            assert!(!response.contains("transfer-encoding"), "got: {}", response);
+            assert!(response.ends_with("\r\n\r\nfile contents"), "got: {}", response);
+
+            stop.notify_one();
+            std::fs::remove_file(path).unwrap();
+        }
+
+        /// Connect to a Unix socket once the server has created it
+        fn connect_unix(path: &std::path::Path) -> std::os::unix::net::UnixStream {
+            for _ in 0..100 {
+                if let Ok(stream) = std::os::unix::net::UnixStream::connect(path) {
+                    stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+                    return stream;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            panic!("server did not start on {}", path.display());
+        }
+
+        #[test]
+        fn test_unix_socket_replaces_stale_file_and_cleans_up() {
+            let dir = std::env::temp_dir().join(format!("nexus-http-unix-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("server.sock");
+            // A socket file left behind by a crashed run
+            drop(std::os::unix::net::UnixListener::bind(&path));
+            assert!(path.exists());
+
+            let (stop, done) = start(Server::bind_unix(&path));
+            let mut client = connect_unix(&path);
+            client.write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+            let response = read_to_close(&mut client);
+            assert!(response.starts_with("HTTP/1.1 200 OK"), "got: {}", response);
+            assert!(response.ends_with("done"));
+
+            stop.notify_one();
+            assert!(done.recv_timeout(Duration::from_secs(10)).unwrap().is_ok());
+            assert!(!path.exists());
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+
+        #[test]
+        fn test_inherited_tcp_fd() {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+            let (stop, _done) = start(Server::from_fd(listener.into()));
+
+            let mut client = connect(&addr);
+            client.write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+            let response = read_to_close(&mut client);
+            assert!(response.starts_with("HTTP/1.1 200 OK"), "got: {}", response);
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_tls_rejected_on_unix_socket() {
+            let dir = std::env::temp_dir().join(format!("nexus-http-tls-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let listener = std::os::unix::net::UnixListener::bind(dir.join("tls.sock")).unwrap();
+            let config = TlsConfig::from_pem(SERVER_PEM, SERVER_KEY).unwrap();
+            let server = Server::from_fd(listener.into()).tls(TlsAcceptor::new(config).unwrap());
+            let result = nexus_runtime::task::block_on(server.run(|_req: Request| async {
+                Ok(Response::new(StatusCode::OK))
+            }));
+            assert!(result.is_err());
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+
+        /// Read HTTP/2 frames until `done` returns true for one of them
+        fn read_frames(
+            stream: &mut impl Read,
+            buf: &mut Vec<u8>,
+            done: impl Fn(&Frame) -> bool,
+        ) -> Vec<Frame> {
+            let mut frames = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                while let Some((frame, used)) = Frame::decode(buf, 1 << 24).unwrap() {
+                    buf.drain(..used);
+                    let finished = done(&frame);
+                    frames.push(frame);
+                    if finished {
+                        return frames;
+                    }
+                }
+                let n = stream.read(&mut chunk).unwrap();
+                assert!(n > 0, "connection closed after {:?}", frames);
+                buf.extend_from_slice(&chunk[..n]);
+            }
+        }
+
+        fn end_of_stream(frame: &Frame) -> bool {
+            matches!(frame, Frame::Data { end_stream: true, .. })
+        }
+
+        fn client_preface() -> Vec<u8> {
+            let mut out = bytes::BytesMut::from(PREFACE);
+            Frame::Settings {
+                ack: false,
+                params: Vec::new(),
+            }
+            .encode(&mut out);
+            out.to_vec()
+        }
+
+        fn status_and_body(frames: &[Frame]) -> (String, Vec<u8>) {
+            let mut status = String::new();
+            let mut body = Vec::new();
+            for frame in frames {
+                match frame {
+                    Frame::Headers { block, .. } => {
+                        let fields = hpack::Decoder::new(4096).decode(block).unwrap();
+                        status = String::from_utf8(fields[0].1.to_vec()).unwrap();
+                    },
+                    Frame::Data { data, .. } => body.extend_from_slice(data),
+                    _ => {},
+                }
+            }
+            (status, body)
+        }
+
+        #[test]
+        fn test_http2_prior_knowledge() {
+            let addr = free_addr();
+            let (stop, done) = start(Server::bind(addr.clone()).http2(Http2Config::new()));
+
+            let mut client = connect(&addr);
+            let mut request = bytes::BytesMut::from(&client_preface()[..]);
+            for id in [1, 3] {
+                let mut block = Vec::new();
+                hpack::Encoder::new().encode(
+                    [
+                        (&b":method"[..], &b"GET"[..]),
+                        (b":scheme", b"http"),
+                        (b":path", b"/"),
+                        (b":authority", b"test"),
+                    ],
+                    &mut block,
+                );
+                frame::encode_headers(&mut request, StreamId::new(id), block.into(), true, 16_384);
+            }
+            client.write_all(&request).unwrap();
+
+            // Both streams are handled concurrently on one connection
+            let mut buf = Vec::new();
+            let mut frames = read_frames(&mut client, &mut buf, end_of_stream);
+            frames.extend(read_frames(&mut client, &mut buf, end_of_stream));
+            assert!(matches!(frames[0], Frame::Settings { ack: false, .. }));
+            for id in [1, 3] {
+                let stream: Vec<Frame> = frames
+                    .iter()
+                    .filter(|f| match f {
+                        Frame::Headers { stream_id, .. } | Frame::Data { stream_id, .. } => {
+                            stream_id.get() == id
+                        },
+                        _ => false,
+                    })
+                    .cloned()
+                    .collect();
+                assert_eq!(status_and_body(&stream), ("200".to_string(), b"done".to_vec()));
+            }
+
+            // Shutdown announces GOAWAY and closes the connection
+            stop.notify_one();
+            let goaway = read_frames(&mut client, &mut buf, |f| matches!(f, Frame::GoAway { .. }));
+            assert!(matches!(
+                goaway.last(),
+                Some(Frame::GoAway { last_stream_id, error_code: ErrorCode::NoError, .. })
+                    if last_stream_id.get() == 3
+            ));
+            assert!(done.recv_timeout(Duration::from_secs(10)).unwrap().is_ok());
+        }
+
+        #[test]
+        fn test_http2_upgrade_from_http1() {
+            let addr = free_addr();
+            let (stop, _done) = start(Server::bind(addr.clone()).http2(Http2Config::new()));
+
+            let mut client = connect(&addr);
+            client
+                .write_all(
+                    b"GET / HTTP/1.1\r\nHost: test\r\nConnection: Upgrade, HTTP2-Settings\r\n\
+                      Upgrade: h2c\r\nHTTP2-Settings: AAMAAABkAAQAAP__\r\n\r\n",
+                )
+                .unwrap();
+
+            let head = read_head(&mut client);
+            assert!(head.starts_with("HTTP/1.1 101 Switching Protocols"), "got: {}", head);
+            assert!(head.contains("upgrade: h2c"));
+
+            // The upgraded request is answered on stream 1
+            client.write_all(&client_preface()).unwrap();
+            let frames = read_frames(&mut client, &mut Vec::new(), end_of_stream);
+            assert_eq!(status_and_body(&frames), ("200".to_string(), b"done".to_vec()));
+            assert!(frames.iter().any(|f| matches!(
+                f,
+                Frame::Headers { stream_id, .. } if stream_id.get() == 1
+            )));
+
+            stop.notify_one();
+        }
+
+        const WS_HANDSHAKE: &str = "GET /ws HTTP/1.1\r\nHost: test\r\nUpgrade: websocket\r\n\
+                                    Connection: Upgrade\r\nSec-WebSocket-Version: 13\r\n\
+                                    Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n";
+
+        /// Start a server whose `/ws` handler echoes data messages
+        fn start_echo(
+            config: WebSocketConfig,
+        ) -> (String, Arc<Notify>, mpsc::Receiver<Result<()>>) {
+            let addr = free_addr();
+            let (stop, done) = start_with(Server::bind(addr.clone()), move |req: Request| {
+                let config = config.clone();
+                async move {
+                    let upgrade = WebSocketUpgrade::from_request(&req)?.with_config(config);
+                    Ok(upgrade.on_upgrade(|mut ws| async move {
+                        while let Ok(Some(message)) = ws.recv().await {
+                            if matches!(message, Message::Text(_) | Message::Binary(_)) {
+                                let _ = ws.send(message).await;
+                            }
+                        }
+                    }))
+                }
+            });
+            (addr, stop, done)
+        }
+
+        /// Send a masked client frame
+        fn send_frame(stream: &mut std::net::TcpStream, first_byte: u8, payload: &[u8]) {
+            let mut out = vec![first_byte];
+            if payload.len() < 126 {
+                out.push(0x80 | payload.len() as u8);
+            } else {
+                out.push(0x80 | 126);
+                out.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+            }
+            let mask = [0x12, 0x34, 0x56, 0x78];
+            out.extend_from_slice(&mask);
+            out.extend(payload.iter().zip(mask.iter().cycle()).map(|(b, k)| b ^ k));
+            stream.write_all(&out).unwrap();
+        }
+
+        /// Read an unmasked server frame as its first byte and payload
+        fn recv_frame(stream: &mut std::net::TcpStream) -> (u8, Vec<u8>) {
+            let mut header = [0u8; 2];
+            stream.read_exact(&mut header).unwrap();
+            assert_eq!(header[1] & 0x80, 0, "server frames are unmasked");
+            let len = match header[1] & 0x7F {
+                126 => {
+                    let mut len = [0u8; 2];
+                    stream.read_exact(&mut len).unwrap();
+                    usize::from(u16::from_be_bytes(len))
+                },
+                127 => panic!("unexpected 64-bit length"),
+                len => usize::from(len),
+            };
+            let mut payload = vec![0u8; len];
+            stream.read_exact(&mut payload).unwrap();
+            (header[0], payload)
+        }
+
+        #[test]
+        fn test_websocket_echo_with_fragmentation() {
+            let (addr, stop, _done) =
+                start_echo(WebSocketConfig::new().max_frame_size(16).ping_interval(0));
+
+            let mut client = connect(&addr);
+            client.write_all(format!("{}\r\n", WS_HANDSHAKE).as_bytes()).unwrap();
+            let head = read_head(&mut client);
+            assert!(head.starts_with("HTTP/1.1 101 Switching Protocols"), "got: {}", head);
+            assert!(head.contains("sec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+            assert!(!head.contains("content-length"));
+
+            // A fragmented text message with a ping in between
+            send_frame(&mut client, 0x01, b"Hello, ");
+            send_frame(&mut client, 0x89, b"are you there?");
+            send_frame(&mut client, 0x80, b"fragmented world");
+            assert_eq!(recv_frame(&mut client), (0x8A, b"are you there?".to_vec()));
+
+            // The echo is split into frames of at most 16 bytes
+            let mut echo = Vec::new();
+            let mut opcodes = Vec::new();
+            loop {
+                let (first, payload) = recv_frame(&mut client);
+                assert!(payload.len() <= 16);
+                opcodes.push(first & 0x0F);
+                echo.extend_from_slice(&payload);
+                if first & 0x80 != 0 {
+                    break;
+                }
+            }
+            assert_eq!(echo, b"Hello, fragmented world");
+            assert_eq!(opcodes, [0x1, 0x0]);
+
+            // A frame over the limit closes the connection with 1009
+            send_frame(&mut client, 0x82, &[0u8; 17]);
+            let (first, payload) = recv_frame(&mut client);
+            assert_eq!(first, 0x88);
+            assert_eq!(&payload[..2], &1009u16.to_be_bytes());
+            assert_eq!(read_to_close(&mut client), "");
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_websocket_permessage_deflate() {
+            let (addr, stop, _done) = start_echo(
+                WebSocketConfig::new()
+                    .max_message_size(64)
+                    .ping_interval(0)
+                    .with_compression(true),
+            );
+
+            let mut client = connect(&addr);
+            let request = format!(
+                "{}Sec-WebSocket-Extensions: permessage-deflate; client_max_window_bits\r\n\r\n",
+                WS_HANDSHAKE
+            );
+            client.write_all(request.as_bytes()).unwrap();
+            let head = read_head(&mut client);
+            assert!(
+                head.contains("sec-websocket-extensions: permessage-deflate\r\n"),
+                "got: {}",
+                head
+            );
+
+            // RFC 7692 Section 7.2.3.1: "Hello" compressed, with RSV1 set
+            send_frame(&mut client, 0xC1, &[0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00]);
+            let (first, payload) = recv_frame(&mut client);
+            assert_eq!(first, 0xC1);
+            let mut inflate = flate2::Decompress::new(false);
+            let mut text = Vec::with_capacity(64);
+            let input = [&payload[..], &[0x00, 0x00, 0xFF, 0xFF]].concat();
+            inflate.decompress_vec(&input, &mut text, flate2::FlushDecompress::Sync).unwrap();
+            assert_eq!(text, b"Hello");
+
+            // Messages inflating past max_message_size close with 1009
+            let mut deflate = flate2::Compress::new(flate2::Compression::default(), false);
+            let mut bomb = Vec::with_capacity(1024);
+            deflate
+                .compress_vec(&[b'a'; 4096], &mut bomb, flate2::FlushCompress::Sync)
+                .unwrap();
+            bomb.truncate(bomb.len() - 4);
+            send_frame(&mut client, 0xC2, &bomb);
+            let (first, payload) = recv_frame(&mut client);
+            assert_eq!(first, 0x88);
+            assert_eq!(&payload[..2], &1009u16.to_be_bytes());
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_websocket_ping_and_going_away_on_shutdown() {
+            let (addr, stop, done) = start_echo(WebSocketConfig::new().ping_interval(1));
+
+            let mut client = connect(&addr);
+            client.write_all(format!("{}\r\n", WS_HANDSHAKE).as_bytes()).unwrap();
+            read_head(&mut client);
+
+            // The server pings on its own once the interval passes
+            let (first, _) = recv_frame(&mut client);
+            assert_eq!(first, 0x89);
+            send_frame(&mut client, 0x8A, b"");
+
+            // Shutdown announces 1001, and the closing handshake lets the server stop
+            stop.notify_one();
+            let (first, payload) = recv_frame(&mut client);
+            assert_eq!(first, 0x88);
+            assert_eq!(&payload[..2], &1001u16.to_be_bytes());
+            send_frame(&mut client, 0x88, &1001u16.to_be_bytes());
+            assert_eq!(read_to_close(&mut client), "");
+            assert!(done.recv_timeout(Duration::from_secs(10)).unwrap().is_ok());
+        }
+
+        const CA_PEM: &[u8] = include_bytes!("../testdata/tls/ca.pem");
+        const SERVER_PEM: &[u8] = include_bytes!("../testdata/tls/server.pem");
+        const SERVER_KEY: &[u8] = include_bytes!("../testdata/tls/server.key");
+        const SERVER2_PEM: &[u8] = include_bytes!("../testdata/tls/server2.pem");
+        const SERVER2_KEY: &[u8] = include_bytes!("../testdata/tls/server2.key");
+        const CLIENT_PEM: &[u8] = include_bytes!("../testdata/tls/client.pem");
+        const CLIENT_KEY: &[u8] = include_bytes!("../testdata/tls/client.key");
+
+        type TlsClient = rustls::StreamOwned<rustls::ClientConnection, std::net::TcpStream>;
+
+        fn der(pem: &[u8]) -> Vec<u8> {
+            use rustls::pki_types::pem::PemObject;
+            rustls::pki_types::CertificateDer::from_pem_slice(pem).unwrap().to_vec()
+        }
+
+        /// Connect a rustls client trusting the test CA
+        fn tls_connect(addr: &str, alpn: &[&str], client_cert: bool) -> TlsClient {
+            use rustls::pki_types::pem::PemObject;
+            use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+
+            let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+            let mut roots = rustls::RootCertStore::empty();
+            roots.add(CertificateDer::from(der(CA_PEM))).unwrap();
+            let builder = rustls::ClientConfig::builder_with_provider(provider)
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(roots);
+            let mut config = if client_cert {
+                let chain = vec![CertificateDer::from(der(CLIENT_PEM))];
+                let key = PrivateKeyDer::from_pem_slice(CLIENT_KEY).unwrap();
+                builder.with_client_auth_cert(chain, key).unwrap()
+            } else {
+                builder.with_no_client_auth()
+            };
+            config.alpn_protocols = alpn.iter().map(|p| p.as_bytes().to_vec()).collect();
+
+            let name = ServerName::try_from("localhost").unwrap();
+            let conn = rustls::ClientConnection::new(Arc::new(config), name).unwrap();
+            rustls::StreamOwned::new(conn, connect(addr))
+        }
+
+        fn tls_get(client: &mut TlsClient) -> Vec<u8> {
+            client
+                .write_all(b"GET / HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            let mut response = Vec::new();
+            // A clean close_notify ends the response without an error
+            client.read_to_end(&mut response).unwrap();
+            response
+        }
+
+        #[test]
+        fn test_tls_http1_and_certificate_reload() {
+            let addr = free_addr();
+            let config = TlsConfig::from_pem(SERVER_PEM, SERVER_KEY).unwrap();
+            let acceptor = TlsAcceptor::new(config).unwrap();
+            let (stop, done) = start(Server::bind(addr.clone()).tls(acceptor.clone()));
+
+            let mut client = tls_connect(&addr, &["http/1.1"], false);
+            let response = tls_get(&mut client);
+            assert!(response.starts_with(b"HTTP/1.1 200 OK\r\n"));
+            assert!(response.ends_with(b"done"));
+            assert_eq!(client.conn.alpn_protocol(), Some(&b"http/1.1"[..]));
+            assert_eq!(client.conn.peer_certificates().unwrap()[0].as_ref(), der(SERVER_PEM));
+
+            // New connections get the new certificate without restarting the listener
+            let renewed = TlsConfig::from_pem(SERVER2_PEM, SERVER2_KEY).unwrap();
+            acceptor.reload(renewed).unwrap();
+            let mut client = tls_connect(&addr, &[], false);
+            assert!(tls_get(&mut client).ends_with(b"done"));
+            assert_eq!(client.conn.peer_certificates().unwrap()[0].as_ref(), der(SERVER2_PEM));
+
+            stop.notify_one();
+            assert!(done.recv_timeout(Duration::from_secs(10)).unwrap().is_ok());
+        }
+
+        #[test]
+        fn test_tls_alpn_h2() {
+            let addr = free_addr();
+            let config = TlsConfig::from_pem(SERVER_PEM, SERVER_KEY).unwrap();
+            let server = Server::bind(addr.clone()).tls(TlsAcceptor::new(config).unwrap());
+            let (stop, _done) = start(server);
+
+            let mut client = tls_connect(&addr, &["h2", "http/1.1"], false);
+            let mut request = bytes::BytesMut::from(&client_preface()[..]);
+            let mut block = Vec::new();
+            hpack::Encoder::new().encode(
+                [
+                    (&b":method"[..], &b"GET"[..]),
+                    (b":scheme", b"https"),
+                    (b":path", b"/"),
+                    (b":authority", b"localhost"),
+                ],
+                &mut block,
+            );
+            frame::encode_headers(&mut request, StreamId::new(1), block.into(), true, 16_384);
+            client.write_all(&request).unwrap();
+
+            let frames = read_frames(&mut client, &mut Vec::new(), end_of_stream);
+            assert_eq!(client.conn.alpn_protocol(), Some(&b"h2"[..]));
+            assert_eq!(status_and_body(&frames), ("200".to_string(), b"done".to_vec()));
+
+            stop.notify_one();
+        }
+
+        #[test]
+        fn test_tls_client_certificate_on_request() {
+            let addr = free_addr();
+            let config = TlsConfig::from_pem(SERVER_PEM, SERVER_KEY)
+                .unwrap()
+                .require_client_auth(CA_PEM)
+                .unwrap();
+            let server = Server::bind(addr.clone()).tls(TlsAcceptor::new(config).unwrap());
+            let (stop, _done) = start_with(server, |req: Request| async move {
+                let der = req.peer_certificate().map(|c| c.der().to_vec()).unwrap_or_default();
+                Ok(Response::builder().body(Body::from(der)).unwrap())
+            });
+
+            let mut client = tls_connect(&addr, &[], true);
+            let response = tls_get(&mut client);
+            assert!(response.starts_with(b"HTTP/1.1 200 OK\r\n"));
+            assert!(response.ends_with(&der(CLIENT_PEM)));
+
+            // Without a certificate the handshake is rejected
+            let mut client = tls_connect(&addr, &[], false);
+            let _ = client.write_all(b"GET / HTTP/1.1\r\nHost: test\r\n\r\n");
+            let mut response = Vec::new();
+            assert!(client.read_to_end(&mut response).is_err());
+            assert!(response.is_empty());
+
+            stop.notify_one();
+        }
+    }
 }
@@ -33,9 +33,16 @@
 #![warn(unreachable_pub)]
 
 use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::time::Duration;
 
-use crate::{Body, Response, StatusCode};
+use bytes::Bytes;
+use futures::Stream;
+use nexus_runtime::time::{Sleep, sleep};
+
+use crate::{Body, Response, StatusCode, response::ResponseBuilder};
 
 /// Server-Sent Event
 /// 服务器发送事件
@@ -59,6 +66,10 @@ pub struct Event {
     /// Retry interval in milliseconds (optional)
     /// 重试间隔毫秒数（可选）
     retry: Option<u64>,
+
+    /// Comment line, ignored by clients (optional)
+    /// 注释行，客户端会忽略（可选）
+    comment: Option<String>,
 }
 
 impl Event {
@@ -66,10 +77,8 @@ impl Event {
     /// 创建带有数据的新事件
     pub fn data(data: impl Into<String>) -> Self {
         Self {
-            id: None,
-            event: None,
             data: vec![data.into()],
-            retry: None,
+            ..Self::default()
         }
     }
 
@@ -77,10 +86,8 @@ impl Event {
     /// 创建注释事件（客户端不会处理）
     pub fn comment(comment: impl Into<String>) -> Self {
         Self {
-            id: None,
-            event: None,
-            data: vec![format!(":{}", comment.into())],
-            retry: None,
+            comment: Some(comment.into()),
+            ..Self::default()
         }
     }
 
@@ -117,6 +124,12 @@ impl Event {
     pub fn to_sse_format(&self) -> String {
         let mut output = String::new();
 
+        if let Some(ref comment) = self.comment {
+            output.push(':');
+            output.push_str(comment);
+            output.push('\n');
+        }
+
         if let Some(ref id) = self.id {
             output.push_str("id: ");
             output.push_str(id);
@@ -231,12 +244,7 @@ impl Sse {
     /// Convert to HTTP response
     /// 转换为HTTP响应
     pub fn into_response(self) -> Response {
-        let mut builder = Response::builder()
-            .status(StatusCode::OK)
-            .header("content-type", "text/event-stream; charset=utf-8")
-            .header("cache-control", "no-cache, no-transform")
-            .header("connection", "keep-alive")
-            .header("x-accel-buffering", "no"); // Disable nginx buffering
+        let mut builder = event_stream_builder();
 
         if let Some(retry) = self.retry {
             builder = builder.header("retry", retry.to_string());
@@ -256,6 +264,30 @@ impl Sse {
         builder.body(Body::from(body)).unwrap()
     }
 
+    /// Create a streaming SSE response that sends events as they are produced
+    /// 创建流式SSE响应，在事件产生时发送
+    ///
+    /// # Example / 示例
+    ///
+    /// ```rust,no_run,ignore
+    /// use futures::stream;
+    /// use nexus_http::sse::{Event, Sse, SseKeepAlive};
+    ///
+    /// let events = stream::iter((0..3).map(|i| Event::data(i.to_string()).id(i.to_string())));
+    /// let response = Sse::stream(events)
+    ///     .keep_alive(SseKeepAlive::default())
+    ///     .into_response();
+    /// ```
+    pub fn stream<S>(events: S) -> SseStream<S>
+    where
+        S: Stream<Item = Event> + Send + 'static,
+    {
+        SseStream {
+            events,
+            keep_alive: None,
+        }
+    }
+
     /// Create a simple event (shorthand)
     /// 创建简单事件（简写）
     pub fn event(data: impl Into<String>) -> Event {
@@ -287,6 +319,120 @@ impl From<Sse> for Response {
     }
 }
 
+/// Response builder with the headers shared by all SSE responses
+/// 带有所有SSE响应共享头部的响应构建器
+fn event_stream_builder() -> ResponseBuilder {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "text/event-stream; charset=utf-8")
+        .header("cache-control", "no-cache, no-transform")
+        .header("connection", "keep-alive")
+        .header("x-accel-buffering", "no") // Disable nginx buffering
+}
+
+/// Streaming SSE response
+/// 流式SSE响应
+///
+/// Created by [`Sse::stream`]. Each event is written to the client as soon as the
+/// stream yields it; with [`SseStream::keep_alive`], a comment is sent whenever the
+/// stream stays idle for the keep-alive interval.
+///
+/// 由 [`Sse::stream`] 创建。流产生的每个事件都会立即写给客户端；设置
+/// [`SseStream::keep_alive`] 后，流空闲达到保活间隔时会发送一条注释。
+pub struct SseStream<S> {
+    /// Events to send
+    /// 要发送的事件
+    events: S,
+
+    /// Keep-alive configuration
+    /// 保活配置
+    keep_alive: Option<SseKeepAlive>,
+}
+
+impl<S> SseStream<S>
+where
+    S: Stream<Item = Event> + Send + 'static,
+{
+    /// Send keep-alive comments while the stream is idle
+    /// 在流空闲时发送保活注释
+    pub fn keep_alive(mut self, keep_alive: SseKeepAlive) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    /// Convert to a streaming HTTP response
+    /// 转换为流式HTTP响应
+    pub fn into_response(self) -> Response {
+        let keep_alive = self.keep_alive.map(|keep_alive| KeepAliveTimer {
+            event: Bytes::from(keep_alive.to_event().to_sse_format()),
+            interval: keep_alive.interval(),
+            sleep: sleep(keep_alive.interval()),
+        });
+        let stream = EventStream {
+            events: Box::pin(self.events),
+            keep_alive,
+        };
+
+        event_stream_builder()
+            .body(Body::wrap_stream(stream))
+            .unwrap_or_else(|_| Response::new(StatusCode::INTERNAL_SERVER_ERROR))
+    }
+}
+
+impl<S> From<SseStream<S>> for Response
+where
+    S: Stream<Item = Event> + Send + 'static,
+{
+    fn from(sse: SseStream<S>) -> Self {
+        sse.into_response()
+    }
+}
+
+/// Timer that fires keep-alive comments
+/// 触发保活注释的定时器
+struct KeepAliveTimer {
+    event: Bytes,
+    interval: Duration,
+    sleep: Sleep,
+}
+
+/// Serializes events and interleaves keep-alive comments
+/// 序列化事件并穿插保活注释
+struct EventStream {
+    events: Pin<Box<dyn Stream<Item = Event> + Send>>,
+    keep_alive: Option<KeepAliveTimer>,
+}
+
+impl Stream for EventStream {
+    type Item = Bytes;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Bytes>> {
+        let this = &mut *self;
+
+        match this.events.as_mut().poll_next(cx) {
+            Poll::Ready(Some(event)) => {
+                // Any traffic keeps the connection alive
+                // 任何流量都能保持连接活跃
+                if let Some(timer) = this.keep_alive.as_mut() {
+                    timer.sleep = sleep(timer.interval);
+                }
+                return Poll::Ready(Some(Bytes::from(event.to_sse_format())));
+            },
+            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Pending => {},
+        }
+
+        if let Some(timer) = this.keep_alive.as_mut() {
+            if Pin::new(&mut timer.sleep).poll(cx).is_ready() {
+                timer.sleep = sleep(timer.interval);
+                return Poll::Ready(Some(timer.event.clone()));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
 /// Keep-alive interval for SSE connections
 /// SSE连接的保活间隔
 ///
@@ -439,4 +585,33 @@ mod tests {
         let output = event.to_sse_format();
         assert!(output.contains(":ping\n"));
     }
+
+    #[test]
+    fn test_sse_stream_writes_events() {
+        let events = futures::stream::iter(vec![Event::data("a").id("1"), Event::data("b")]);
+        let response = Sse::stream(events).into_response();
+        assert_eq!(
+            response.header("content-type"),
+            Some("text/event-stream; charset=utf-8")
+        );
+        assert!(response.body().is_stream());
+
+        let body = futures::executor::block_on(response.into_body().collect()).unwrap();
+        assert_eq!(&body[..], b"id: 1\ndata: a\n\ndata: b\n\n");
+    }
+
+    #[test]
+    fn test_sse_stream_sends_keep_alive_when_idle() {
+        let response = Sse::stream(futures::stream::pending())
+            .keep_alive(SseKeepAlive::new(Duration::from_millis(20)).with_comment("ping"))
+            .into_response();
+
+        let chunk = nexus_runtime::task::block_on(async move {
+            let Body::Stream(body) = response.into_body() else {
+                panic!("expected a streaming body");
+            };
+            std::future::poll_fn(|cx| body.poll_chunk(cx)).await
+        });
+        assert_eq!(&chunk.unwrap().unwrap()[..], b":ping\n\n");
+    }
 }
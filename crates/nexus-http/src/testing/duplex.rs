@@ -0,0 +1,140 @@
+//! In-memory duplex byte stream
+//! 内存中的双工字节流
+//!
+//! Stands in for a socket when an upgraded connection is driven by a
+//! [`TestClient`](super::TestClient) instead of a real server.
+//!
+//! 当升级后的连接由 [`TestClient`](super::TestClient) 而不是真实服务器驱动时，
+//! 用于替代套接字。
+
+use std::collections::VecDeque;
+use std::future::{Ready, poll_fn, ready};
+use std::io;
+use std::net::Shutdown;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Poll, Waker};
+
+/// One direction of a duplex stream
+/// 双工流的一个方向
+#[derive(Default)]
+struct Pipe {
+    /// Written bytes not yet read / 已写入但尚未读取的字节
+    buf: VecDeque<u8>,
+    /// Set once either end shut this direction down / 任一端关闭此方向后设置
+    closed: bool,
+    /// Reader waiting for data / 等待数据的读取者
+    waker: Option<Waker>,
+}
+
+impl Pipe {
+    fn close(pipe: &Mutex<Pipe>) {
+        let mut pipe = pipe.lock().unwrap_or_else(PoisonError::into_inner);
+        pipe.closed = true;
+        if let Some(waker) = pipe.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// One end of an in-memory duplex stream
+/// 内存双工流的一端
+pub(crate) struct MemoryStream {
+    /// Bytes written by the other end / 另一端写入的字节
+    read: Arc<Mutex<Pipe>>,
+    /// Bytes written by this end / 此端写入的字节
+    write: Arc<Mutex<Pipe>>,
+}
+
+/// Create a connected pair of in-memory streams
+/// 创建一对相互连接的内存流
+pub(crate) fn duplex() -> (MemoryStream, MemoryStream) {
+    let a = Arc::new(Mutex::new(Pipe::default()));
+    let b = Arc::new(Mutex::new(Pipe::default()));
+    (
+        MemoryStream {
+            read: a.clone(),
+            write: b.clone(),
+        },
+        MemoryStream { read: b, write: a },
+    )
+}
+
+impl MemoryStream {
+    /// Read some bytes, returning 0 once the other end has closed
+    /// 读取一些字节，另一端关闭后返回0
+    pub(crate) async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        poll_fn(|cx| {
+            let mut pipe = self.read.lock().unwrap_or_else(PoisonError::into_inner);
+            if !pipe.buf.is_empty() || buf.is_empty() {
+                let n = buf.len().min(pipe.buf.len());
+                for (dst, src) in buf.iter_mut().zip(pipe.buf.drain(..n)) {
+                    *dst = src;
+                }
+                return Poll::Ready(Ok(n));
+            }
+            if pipe.closed {
+                return Poll::Ready(Ok(0));
+            }
+            pipe.waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Write all bytes
+    /// 写入所有字节
+    ///
+    /// The buffer is unbounded, so this completes at once.
+    /// 缓冲区没有上限，因此会立即完成。
+    pub(crate) fn write_all(&mut self, buf: &[u8]) -> Ready<io::Result<()>> {
+        let mut pipe = self.write.lock().unwrap_or_else(PoisonError::into_inner);
+        if pipe.closed {
+            return ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        pipe.buf.extend(buf);
+        if let Some(waker) = pipe.waker.take() {
+            waker.wake();
+        }
+        ready(Ok(()))
+    }
+
+    /// Shut down one or both directions
+    /// 关闭一个或两个方向
+    pub(crate) fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        if matches!(how, Shutdown::Read | Shutdown::Both) {
+            Pipe::close(&self.read);
+        }
+        if matches!(how, Shutdown::Write | Shutdown::Both) {
+            Pipe::close(&self.write);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MemoryStream {
+    fn drop(&mut self) {
+        let _ = self.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nexus_runtime::task::block_on;
+
+    #[test]
+    fn test_duplex_transfers_and_closes() {
+        block_on(async {
+            let (mut a, mut b) = duplex();
+            a.write_all(b"hello").await.unwrap();
+            let mut buf = [0u8; 3];
+            assert_eq!(b.read(&mut buf).await.unwrap(), 3);
+            assert_eq!(&buf, b"hel");
+            assert_eq!(b.read(&mut buf).await.unwrap(), 2);
+
+            drop(a);
+            assert_eq!(b.read(&mut buf).await.unwrap(), 0);
+            assert!(b.write_all(b"x").await.is_err());
+        });
+    }
+}
@@ -0,0 +1,1102 @@
+//! In-process test client
+//! 进程内测试客户端
+//!
+//! # Overview / 概述
+//!
+//! [`TestClient`] drives any [`HttpService`] — a `nexus_router::Router` with its
+//! middleware stack and state, or a plain handler function — by calling it directly,
+//! so no port is bound and no bytes go over a socket. Requests are built fluently,
+//! cookies set by responses are kept in a [`CookieJar`] and sent back on later
+//! requests, and [`TestResponse`] offers JSON helpers and assertions. Streaming
+//! responses can be read event by event with [`TestResponse::sse`], and WebSocket
+//! handlers are connected over an in-memory stream with [`TestRequest::websocket`].
+//!
+//! [`TestClient`] 通过直接调用来驱动任何 [`HttpService`]——带有中间件栈和状态的
+//! `nexus_router::Router`，或普通的处理函数——因此不会绑定端口，也不会有字节经过套接字。
+//! 请求以流式API构建，响应设置的cookie保存在 [`CookieJar`] 中并在后续请求中发回，
+//! [`TestResponse`] 提供JSON辅助方法和断言。流式响应可以通过 [`TestResponse::sse`]
+//! 逐个事件读取，WebSocket处理器则通过 [`TestRequest::websocket`] 经内存流连接。
+//!
+//! # Equivalent to Spring Boot / 等价于 Spring Boot
+//!
+//! - MockMvc
+//! - WebTestClient
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_http::testing::TestClient;
+//! use nexus_http::StatusCode;
+//!
+//! let client = TestClient::new(router);
+//!
+//! client
+//!     .post("/login")
+//!     .form(&[("user", "ada"), ("password", "secret")])
+//!     .await
+//!     .assert_status(StatusCode::OK);
+//!
+//! // The session cookie from the login response is sent automatically
+//! // 登录响应中的会话cookie会自动发送
+//! let user: User = client.get("/me").await.assert_success().json();
+//! ```
+
+#![warn(missing_docs)]
+#![warn(unreachable_pub)]
+
+mod duplex;
+
+pub(crate) use duplex::{MemoryStream, duplex};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::{Future, IntoFuture, poll_fn};
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use base64::Engine as _;
+use bytes::{Bytes, BytesMut};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::server::{ServerState, ServerStream, error_response};
+use crate::upgrade::Upgraded;
+use crate::websocket::accept_key;
+use crate::{Body, HttpService, Method, Request, Response, StatusCode, WebSocket};
+
+/// In-process client for an [`HttpService`]
+/// [`HttpService`] 的进程内客户端
+///
+/// Requests borrow the client, so one client can be shared by a whole test and its
+/// cookie jar carries state such as sessions from one request to the next.
+///
+/// 请求借用客户端，因此一个客户端可以在整个测试中共享，其cookie罐会把会话等状态
+/// 从一个请求带到下一个请求。
+pub struct TestClient<S> {
+    /// Service under test / 被测服务
+    service: S,
+    /// Headers sent with every request / 每个请求都发送的头部
+    headers: Vec<(String, String)>,
+    /// Cookies set by responses / 响应设置的cookie
+    cookies: Mutex<CookieJar>,
+}
+
+impl<S: HttpService> TestClient<S> {
+    /// Create a client for a service
+    /// 为服务创建客户端
+    pub fn new(service: S) -> Self {
+        Self {
+            service,
+            headers: Vec::new(),
+            cookies: Mutex::new(CookieJar::new()),
+        }
+    }
+
+    /// Send a header with every request, unless a request sets it itself
+    /// 在每个请求中发送一个头部，除非请求自己设置了该头部
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Get the service under test
+    /// 获取被测服务
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+
+    /// Start a request with any method
+    /// 以任意方法开始一个请求
+    pub fn request(&self, method: Method, uri: impl Into<String>) -> TestRequest<'_, S> {
+        TestRequest {
+            client: self,
+            method,
+            uri: uri.into(),
+            headers: Vec::new(),
+            cookies: Vec::new(),
+            body: Body::empty(),
+        }
+    }
+
+    /// Start a GET request
+    /// 开始GET请求
+    pub fn get(&self, uri: impl Into<String>) -> TestRequest<'_, S> {
+        self.request(Method::GET, uri)
+    }
+
+    /// Start a POST request
+    /// 开始POST请求
+    pub fn post(&self, uri: impl Into<String>) -> TestRequest<'_, S> {
+        self.request(Method::POST, uri)
+    }
+
+    /// Start a PUT request
+    /// 开始PUT请求
+    pub fn put(&self, uri: impl Into<String>) -> TestRequest<'_, S> {
+        self.request(Method::PUT, uri)
+    }
+
+    /// Start a PATCH request
+    /// 开始PATCH请求
+    pub fn patch(&self, uri: impl Into<String>) -> TestRequest<'_, S> {
+        self.request(Method::PATCH, uri)
+    }
+
+    /// Start a DELETE request
+    /// 开始DELETE请求
+    pub fn delete(&self, uri: impl Into<String>) -> TestRequest<'_, S> {
+        self.request(Method::DELETE, uri)
+    }
+
+    /// Start a HEAD request
+    /// 开始HEAD请求
+    pub fn head(&self, uri: impl Into<String>) -> TestRequest<'_, S> {
+        self.request(Method::HEAD, uri)
+    }
+
+    /// Start an OPTIONS request
+    /// 开始OPTIONS请求
+    pub fn options(&self, uri: impl Into<String>) -> TestRequest<'_, S> {
+        self.request(Method::OPTIONS, uri)
+    }
+
+    /// Get the value of a cookie in the jar
+    /// 获取cookie罐中某个cookie的值
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.jar().get(name).map(str::to_string)
+    }
+
+    /// Put a cookie into the jar, as if a response had set it for `/`
+    /// 将cookie放入cookie罐，如同某个响应为 `/` 设置了它
+    pub fn set_cookie(&self, name: impl Into<String>, value: impl Into<String>) {
+        self.jar().insert(name, value);
+    }
+
+    /// Snapshot of the cookie jar
+    /// cookie罐的快照
+    pub fn cookies(&self) -> CookieJar {
+        self.jar().clone()
+    }
+
+    /// Remove all cookies
+    /// 移除所有cookie
+    pub fn clear_cookies(&self) {
+        self.jar().clear();
+    }
+
+    fn jar(&self) -> MutexGuard<'_, CookieJar> {
+        self.cookies.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Call the service, turning errors into responses as the server does
+    /// 调用服务，像服务器那样将错误转换为响应
+    async fn call(&self, request: Request) -> Response {
+        let response = match self.service.call(request).await {
+            Ok(response) => response,
+            Err(e) => error_response(&e),
+        };
+        self.jar().store(&response);
+        response
+    }
+}
+
+impl<S> fmt::Debug for TestClient<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestClient")
+            .field("headers", &self.headers)
+            .field("cookies", &self.cookies)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Request being built by a [`TestClient`]
+/// 由 [`TestClient`] 构建的请求
+///
+/// Awaiting the request sends it; see [`send`](Self::send).
+/// await该请求即发送它；参见 [`send`](Self::send)。
+#[must_use = "requests do nothing until sent or awaited"]
+pub struct TestRequest<'a, S> {
+    client: &'a TestClient<S>,
+    method: Method,
+    uri: String,
+    headers: Vec<(String, String)>,
+    cookies: Vec<(String, String)>,
+    body: Body,
+}
+
+impl<S: HttpService> TestRequest<'_, S> {
+    /// Add a header, replacing a client-wide header of the same name
+    /// 添加头部，替换客户端级别的同名头部
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Append query parameters, e.g. `&[("page", 2)]` or a serializable struct
+    /// 追加查询参数，例如 `&[("page", 2)]` 或可序列化的结构体
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the value cannot be URL-encoded.
+    /// 如果值无法进行URL编码则恐慌。
+    #[track_caller]
+    pub fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        let encoded = serde_urlencoded::to_string(query)
+            .unwrap_or_else(|e| panic!("cannot encode query for {}: {}", self.uri, e));
+        if !encoded.is_empty() {
+            self.uri.push(if self.uri.contains('?') { '&' } else { '?' });
+            self.uri.push_str(&encoded);
+        }
+        self
+    }
+
+    /// Send a JSON body
+    /// 发送JSON body
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the value cannot be serialized.
+    /// 如果值无法序列化则恐慌。
+    #[track_caller]
+    pub fn json<T: Serialize + ?Sized>(self, value: &T) -> Self {
+        let body = serde_json::to_vec(value)
+            .unwrap_or_else(|e| panic!("cannot serialize JSON for {}: {}", self.uri, e));
+        self.content_type("application/json").body(body)
+    }
+
+    /// Send a URL-encoded form body
+    /// 发送URL编码的表单body
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the value cannot be URL-encoded.
+    /// 如果值无法进行URL编码则恐慌。
+    #[track_caller]
+    pub fn form<T: Serialize + ?Sized>(self, value: &T) -> Self {
+        let body = serde_urlencoded::to_string(value)
+            .unwrap_or_else(|e| panic!("cannot encode form for {}: {}", self.uri, e));
+        self.content_type("application/x-www-form-urlencoded")
+            .body(body)
+    }
+
+    /// Send a plain text body
+    /// 发送纯文本body
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.content_type("text/plain; charset=utf-8")
+            .body(text.into())
+    }
+
+    /// Set the body as is
+    /// 原样设置body
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Send a cookie with this request only, next to those in the jar
+    /// 仅在此请求中发送一个cookie，与cookie罐中的cookie一起发送
+    pub fn cookie(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.cookies.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send an `Authorization: Bearer` header
+    /// 发送 `Authorization: Bearer` 头部
+    pub fn bearer_auth(self, token: impl fmt::Display) -> Self {
+        self.header("authorization", format!("Bearer {}", token))
+    }
+
+    /// Send an `Authorization: Basic` header
+    /// 发送 `Authorization: Basic` 头部
+    pub fn basic_auth(self, username: &str, password: &str) -> Self {
+        let credentials = base64::engine::general_purpose::STANDARD
+            .encode(format!("{}:{}", username, password));
+        self.header("authorization", format!("Basic {}", credentials))
+    }
+
+    /// Call the service and capture its response
+    /// 调用服务并捕获其响应
+    ///
+    /// A handler error becomes the same error response the server would send, and
+    /// cookies set by the response are stored in the client's jar.
+    /// 处理器错误会变成与服务器发送的相同的错误响应，响应设置的cookie会保存到客户端的cookie罐中。
+    pub async fn send(self) -> TestResponse {
+        let client = self.client;
+        let response = client.call(self.into_request()).await;
+        TestResponse { response }
+    }
+
+    /// Perform a WebSocket handshake and connect to the handler in memory
+    /// 执行WebSocket握手并在内存中连接到处理器
+    ///
+    /// On success the handler's `on_upgrade` callback runs on a spawned task and the
+    /// returned [`WebSocket`] is the client end of the connection: it masks what it
+    /// sends and answers the server's pings. Compression is never offered. If the
+    /// service does not accept the upgrade, its response is returned as the error.
+    ///
+    /// 成功时处理器的 `on_upgrade` 回调会在新生成的任务上运行，返回的 [`WebSocket`]
+    /// 是连接的客户端一端：它会对发送的内容加掩码并应答服务器的ping。不会提供压缩。
+    /// 如果服务不接受升级，其响应会作为错误返回。
+    pub async fn websocket(mut self) -> Result<WebSocket, TestResponse> {
+        let mut key = [0u8; 16];
+        key[..8].copy_from_slice(&random().to_ne_bytes());
+        key[8..].copy_from_slice(&random().to_ne_bytes());
+        let key = base64::engine::general_purpose::STANDARD.encode(key);
+
+        self.headers
+            .retain(|(name, _)| !name.eq_ignore_ascii_case("sec-websocket-extensions"));
+        let client = self.client;
+        let request = self
+            .header("connection", "Upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", key.clone())
+            .into_request();
+        let mut response = client.call(request).await;
+
+        let accepted = response.status() == StatusCode::SWITCHING_PROTOCOLS
+            && find_header(response.headers(), "sec-websocket-accept")
+                == Some(accept_key(&key).as_str());
+        let on_upgrade = match response.take_upgrade().and_then(|u| u.take()) {
+            Some(on_upgrade) if accepted => on_upgrade,
+            _ => return Err(TestResponse { response }),
+        };
+
+        let (server_end, client_end) = duplex();
+        let state = Arc::new(ServerState::new());
+        nexus_runtime::task::spawn(on_upgrade(Upgraded {
+            stream: ServerStream::Memory(server_end),
+            buffered: Bytes::new(),
+            state: state.clone(),
+        }));
+        let protocol =
+            find_header(response.headers(), "sec-websocket-protocol").map(str::to_string);
+        let upgraded = Upgraded {
+            stream: ServerStream::Memory(client_end),
+            buffered: Bytes::new(),
+            state,
+        };
+        Ok(WebSocket::client(upgraded, protocol))
+    }
+
+    fn content_type(self, content_type: &str) -> Self {
+        if self.has_header("content-type") {
+            self
+        } else {
+            self.header("content-type", content_type)
+        }
+    }
+
+    fn has_header(&self, name: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|(n, _)| n.eq_ignore_ascii_case(name))
+    }
+
+    /// Assemble the request with client-wide headers and cookies
+    /// 组装带有客户端级别头部和cookie的请求
+    #[track_caller]
+    fn into_request(self) -> Request {
+        let client = self.client;
+        let mut builder = http::Request::builder()
+            .method(http::Method::from(&self.method))
+            .uri(&self.uri);
+        let defaults = client
+            .headers
+            .iter()
+            .filter(|(name, _)| !self.has_header(name));
+        for (name, value) in defaults.chain(&self.headers) {
+            builder = builder.header(name, value);
+        }
+        if !self.has_header("host") {
+            builder = builder.header("host", "localhost");
+        }
+        let len = self.body.data().len();
+        if len > 0 && !self.has_header("content-length") {
+            builder = builder.header("content-length", len);
+        }
+
+        let path = self.uri.split(['?', '#']).next().unwrap_or("/");
+        let mut cookies = client.jar().matching(path);
+        cookies.retain(|(name, _)| !self.cookies.iter().any(|(n, _)| n == name));
+        cookies.extend(self.cookies.iter().cloned());
+        if !cookies.is_empty() {
+            let header = cookies
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join("; ");
+            builder = builder.header("cookie", header);
+        }
+
+        let inner = builder
+            .body(self.body)
+            .unwrap_or_else(|e| panic!("invalid test request {} {}: {}", self.method, self.uri, e));
+        Request::new(inner)
+    }
+}
+
+impl<'a, S: HttpService + 'a> IntoFuture for TestRequest<'a, S> {
+    type Output = TestResponse;
+    type IntoFuture = Pin<Box<dyn Future<Output = TestResponse> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.send())
+    }
+}
+
+impl<S> fmt::Debug for TestRequest<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestRequest")
+            .field("method", &self.method)
+            .field("uri", &self.uri)
+            .field("headers", &self.headers)
+            .field("cookies", &self.cookies)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Response captured by a [`TestClient`]
+/// 由 [`TestClient`] 捕获的响应
+///
+/// Assertions panic with the status and body of the response on failure and return
+/// `&Self`, so they can be chained.
+/// 断言失败时会带着响应的状态和body恐慌，并返回 `&Self` 以便链式调用。
+#[derive(Debug)]
+pub struct TestResponse {
+    response: Response,
+}
+
+impl TestResponse {
+    /// Get the status code
+    /// 获取状态码
+    pub fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    /// Get a header value, ignoring the case of the name
+    /// 获取头部值，忽略名称大小写
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(self.response.headers(), name)
+    }
+
+    /// Get all headers
+    /// 获取所有头部
+    pub fn headers(&self) -> &HashMap<String, String> {
+        self.response.headers()
+    }
+
+    /// Get the body bytes
+    /// 获取body字节
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics for a streaming body; use [`collect`](Self::collect) or
+    /// [`sse`](Self::sse) first.
+    /// 对于流式body会恐慌；请先使用 [`collect`](Self::collect) 或 [`sse`](Self::sse)。
+    #[track_caller]
+    pub fn bytes(&self) -> &Bytes {
+        assert!(
+            !self.response.body().is_stream(),
+            "response body is a stream; call collect() or sse() first"
+        );
+        self.response.body().data()
+    }
+
+    /// Get the body as text, replacing invalid UTF-8
+    /// 以文本形式获取body，替换无效的UTF-8
+    #[track_caller]
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(self.bytes()).into_owned()
+    }
+
+    /// Deserialize the body as JSON
+    /// 将body反序列化为JSON
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the body is not valid JSON for `T`.
+    /// 如果body不是 `T` 的有效JSON则恐慌。
+    #[track_caller]
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(self.bytes())
+            .unwrap_or_else(|e| panic!("response is not the expected JSON ({}): {}", e, self))
+    }
+
+    /// Read a streaming body to the end so the body helpers can be used
+    /// 将流式body读取到末尾，以便使用body辅助方法
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the stream fails.
+    /// 如果流失败则恐慌。
+    pub async fn collect(mut self) -> Self {
+        let body = self.response.take_body();
+        let bytes = body
+            .collect()
+            .await
+            .unwrap_or_else(|e| panic!("response body stream failed: {}", e));
+        self.response.set_body(Body::from(bytes));
+        self
+    }
+
+    /// Read the body as a stream of Server-Sent Events
+    /// 将body作为服务器发送事件流读取
+    pub fn sse(mut self) -> SseEvents {
+        SseEvents {
+            body: self.response.take_body(),
+            buf: BytesMut::new(),
+            done: false,
+        }
+    }
+
+    /// Get the underlying response
+    /// 获取底层响应
+    pub fn into_response(self) -> Response {
+        self.response
+    }
+
+    /// Assert the status code
+    /// 断言状态码
+    #[track_caller]
+    pub fn assert_status(&self, status: StatusCode) -> &Self {
+        assert_eq!(self.status(), status, "unexpected status: {}", self);
+        self
+    }
+
+    /// Assert a `2xx` status code
+    /// 断言 `2xx` 状态码
+    #[track_caller]
+    pub fn assert_success(&self) -> &Self {
+        assert!(self.status().is_success(), "expected a success status: {}", self);
+        self
+    }
+
+    /// Assert a header value
+    /// 断言头部值
+    #[track_caller]
+    pub fn assert_header(&self, name: &str, value: &str) -> &Self {
+        assert_eq!(self.header(name), Some(value), "unexpected {} header: {}", name, self);
+        self
+    }
+
+    /// Assert that a header is absent
+    /// 断言头部不存在
+    #[track_caller]
+    pub fn assert_no_header(&self, name: &str) -> &Self {
+        assert_eq!(self.header(name), None, "unexpected {} header: {}", name, self);
+        self
+    }
+
+    /// Assert the body text
+    /// 断言body文本
+    #[track_caller]
+    pub fn assert_body(&self, body: &str) -> &Self {
+        assert_eq!(self.text(), body, "unexpected body: {}", self);
+        self
+    }
+
+    /// Assert that the body text contains a string
+    /// 断言body文本包含某个字符串
+    #[track_caller]
+    pub fn assert_body_contains(&self, needle: &str) -> &Self {
+        assert!(self.text().contains(needle), "body does not contain {:?}: {}", needle, self);
+        self
+    }
+
+    /// Assert that the body is JSON equal to `expected`
+    /// 断言body是与 `expected` 相等的JSON
+    ///
+    /// Values are compared as JSON, so key order and formatting do not matter.
+    /// 值按JSON比较，因此键顺序和格式无关紧要。
+    #[track_caller]
+    pub fn assert_json<T: Serialize + ?Sized>(&self, expected: &T) -> &Self {
+        let expected = serde_json::to_value(expected)
+            .unwrap_or_else(|e| panic!("cannot serialize expected JSON: {}", e));
+        let actual: serde_json::Value = self.json();
+        assert_eq!(actual, expected, "unexpected JSON body: {}", self);
+        self
+    }
+}
+
+impl fmt::Display for TestResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.status())?;
+        let body = self.response.body();
+        if body.is_stream() {
+            write!(f, " <streaming body>")
+        } else if !body.data().is_empty() {
+            write!(f, " {}", String::from_utf8_lossy(body.data()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Server-Sent Events read from a [`TestResponse`]
+/// 从 [`TestResponse`] 读取的服务器发送事件
+pub struct SseEvents {
+    body: Body,
+    buf: BytesMut,
+    done: bool,
+}
+
+impl SseEvents {
+    /// Wait for the next event, `None` once the stream ends
+    /// 等待下一个事件，流结束后返回 `None`
+    ///
+    /// Comment-only blocks such as keep-alives are skipped.
+    /// 仅包含注释的块（例如保活）会被跳过。
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the stream fails.
+    /// 如果流失败则恐慌。
+    pub async fn next_event(&mut self) -> Option<ReceivedEvent> {
+        loop {
+            while let Some(block) = self.next_block() {
+                if let Some(event) = ReceivedEvent::parse(&block) {
+                    return Some(event);
+                }
+            }
+            if !self.fill().await {
+                return None;
+            }
+        }
+    }
+
+    /// Wait for up to `n` events
+    /// 等待最多 `n` 个事件
+    pub async fn take(&mut self, n: usize) -> Vec<ReceivedEvent> {
+        let mut events = Vec::with_capacity(n);
+        while events.len() < n {
+            match self.next_event().await {
+                Some(event) => events.push(event),
+                None => break,
+            }
+        }
+        events
+    }
+
+    /// Split the next blank-line terminated block off the buffer
+    /// 从缓冲区拆分出下一个以空行结束的块
+    fn next_block(&mut self) -> Option<String> {
+        let lf = self.buf.windows(2).position(|w| w == b"\n\n").map(|i| (i, 2));
+        let crlf = self
+            .buf
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .map(|i| (i, 4));
+        let (end, len) = [lf, crlf].into_iter().flatten().min()?;
+        let mut block = self.buf.split_to(end + len);
+        block.truncate(end);
+        Some(String::from_utf8_lossy(&block).into_owned())
+    }
+
+    /// Read the next chunk, returning false once the body is exhausted
+    /// 读取下一个数据块，body耗尽后返回false
+    async fn fill(&mut self) -> bool {
+        if self.done {
+            return false;
+        }
+        let chunk = match &self.body {
+            Body::Full(full) => {
+                self.done = true;
+                Some(Ok(full.data().clone()))
+            },
+            Body::Stream(stream) => poll_fn(|cx| stream.poll_chunk(cx)).await,
+        };
+        match chunk {
+            Some(Ok(bytes)) => {
+                self.buf.extend_from_slice(&bytes);
+                true
+            },
+            Some(Err(e)) => panic!("event stream failed: {}", e),
+            None => {
+                self.done = true;
+                false
+            },
+        }
+    }
+}
+
+impl fmt::Debug for SseEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SseEvents")
+            .field("buffered", &self.buf.len())
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A Server-Sent Event as received by a client
+/// 客户端收到的服务器发送事件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReceivedEvent {
+    /// Event ID / 事件ID
+    pub id: Option<String>,
+    /// Event name / 事件名称
+    pub event: Option<String>,
+    /// Data lines joined with `\n` / 以 `\n` 连接的数据行
+    pub data: String,
+    /// Reconnection time in milliseconds / 重连时间（毫秒）
+    pub retry: Option<u64>,
+}
+
+impl ReceivedEvent {
+    /// Deserialize the data as JSON
+    /// 将数据反序列化为JSON
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the data is not valid JSON for `T`.
+    /// 如果数据不是 `T` 的有效JSON则恐慌。
+    #[track_caller]
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_str(&self.data)
+            .unwrap_or_else(|e| panic!("event data is not the expected JSON ({}): {}", e, self.data))
+    }
+
+    /// Parse one event block, `None` if it only holds comments
+    /// 解析一个事件块，如果只包含注释则返回 `None`
+    fn parse(block: &str) -> Option<Self> {
+        let mut event = ReceivedEvent::default();
+        let mut data = Vec::new();
+        let mut any = false;
+        for line in block.lines() {
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "data" => data.push(value),
+                "event" => event.event = Some(value.to_string()),
+                "id" => event.id = Some(value.to_string()),
+                "retry" => event.retry = value.parse().ok(),
+                _ => continue,
+            }
+            any = true;
+        }
+        event.data = data.join("\n");
+        any.then_some(event)
+    }
+}
+
+/// Cookies kept by a [`TestClient`]
+/// [`TestClient`] 保存的cookie
+///
+/// Cookies are scoped by name and path only: domains are ignored since there is a
+/// single service, and `Max-Age=0` is the only expiry honoured.
+/// cookie只按名称和路径区分：由于只有一个服务，域会被忽略，且只遵循 `Max-Age=0` 这一种过期方式。
+#[derive(Debug, Clone, Default)]
+pub struct CookieJar {
+    /// Name, value and path of each cookie / 每个cookie的名称、值和路径
+    cookies: Vec<(String, String, String)>,
+}
+
+impl CookieJar {
+    /// Create an empty jar
+    /// 创建空的cookie罐
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the value of a cookie, whatever its path
+    /// 获取cookie的值，无论其路径
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.cookies
+            .iter()
+            .find(|(n, _, _)| n == name)
+            .map(|(_, value, _)| value.as_str())
+    }
+
+    /// Add or replace a cookie for path `/`
+    /// 为路径 `/` 添加或替换cookie
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.put(name.into(), value.into(), "/".to_string());
+    }
+
+    /// Remove a cookie on every path
+    /// 在所有路径上移除cookie
+    pub fn remove(&mut self, name: &str) {
+        self.cookies.retain(|(n, _, _)| n != name);
+    }
+
+    /// Remove all cookies
+    /// 移除所有cookie
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+
+    /// Number of cookies
+    /// cookie数量
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    /// Check if the jar is empty
+    /// 检查cookie罐是否为空
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+
+    /// Iterate over cookie names and values
+    /// 遍历cookie名称和值
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.cookies
+            .iter()
+            .map(|(name, value, _)| (name.as_str(), value.as_str()))
+    }
+
+    fn put(&mut self, name: String, value: String, path: String) {
+        self.cookies.retain(|(n, _, p)| !(*n == name && *p == path));
+        self.cookies.push((name, value, path));
+    }
+
+    /// Cookies to send for a request path
+    /// 对某个请求路径应发送的cookie
+    fn matching(&self, path: &str) -> Vec<(String, String)> {
+        self.cookies
+            .iter()
+            .filter(|(_, _, prefix)| path_matches(path, prefix))
+            .map(|(name, value, _)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Store the cookies set by a response
+    /// 保存响应设置的cookie
+    fn store(&mut self, response: &Response) {
+        let headers = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("set-cookie"));
+        for (_, value) in headers {
+            for set_cookie in split_set_cookie(value) {
+                self.store_one(set_cookie);
+            }
+        }
+    }
+
+    fn store_one(&mut self, set_cookie: &str) {
+        let mut parts = set_cookie.split(';').map(str::trim);
+        let Some((name, value)) = parts.next().and_then(|pair| pair.split_once('=')) else {
+            return;
+        };
+        let (name, value) = (name.trim().to_string(), value.trim().to_string());
+        let mut path = "/".to_string();
+        let mut expired = false;
+        for attribute in parts {
+            let (key, val) = attribute.split_once('=').unwrap_or((attribute, ""));
+            if key.eq_ignore_ascii_case("path") && val.starts_with('/') {
+                path = val.to_string();
+            } else if key.eq_ignore_ascii_case("max-age") {
+                expired = val.parse::<i64>().is_ok_and(|age| age <= 0);
+            }
+        }
+        if expired {
+            self.cookies.retain(|(n, _, p)| !(*n == name && *p == path));
+        } else {
+            self.put(name, value, path);
+        }
+    }
+}
+
+/// Check whether a request path falls under a cookie path (RFC 6265 Section 5.1.4)
+/// 检查请求路径是否位于cookie路径之下（RFC 6265 第5.1.4节）
+fn path_matches(path: &str, prefix: &str) -> bool {
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || prefix.ends_with('/') || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Split `Set-Cookie` values that were joined with commas
+/// 拆分以逗号连接的 `Set-Cookie` 值
+///
+/// A comma only starts a new cookie when a `name=` follows it, which keeps the
+/// comma inside `Expires=Wed, 21 Oct 2015 ...` intact.
+/// 只有当逗号后面跟着 `name=` 时才开始一个新cookie，这样 `Expires=Wed, 21 Oct 2015 ...`
+/// 中的逗号会保持原样。
+fn split_set_cookie(value: &str) -> Vec<&str> {
+    let mut cookies = Vec::new();
+    let mut start = 0;
+    for (i, _) in value.match_indices(',') {
+        let next = &value[i + 1..];
+        let pair = next.split([';', ',']).next().unwrap_or("");
+        if pair.contains('=') {
+            cookies.push(value[start..i].trim());
+            start = i + 1;
+        }
+    }
+    cookies.push(value[start..].trim());
+    cookies.retain(|cookie| !cookie.is_empty());
+    cookies
+}
+
+/// Find a header ignoring the case of its name
+/// 查找头部，忽略名称大小写
+fn find_header<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// A fresh random number
+/// 新的随机数
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::websocket::{Message, WebSocketUpgrade};
+    use crate::{Error, Event, Result, Sse};
+    use nexus_runtime::task::block_on;
+
+    async fn app(req: Request) -> Result<Response> {
+        match (req.method(), req.path()) {
+            (Method::GET, "/hello") => Ok(Response::build_ok()
+                .header("X-Query", req.param("name").unwrap_or("none"))
+                .text(format!("hello {}", req.header("cookie").unwrap_or("stranger")))),
+            (Method::POST, "/echo") => {
+                let body = req.body().clone().collect().await?;
+                let content_type = req.header("content-type").unwrap_or("").to_string();
+                Ok(Response::build_ok()
+                    .content_type(content_type)
+                    .body(body))
+            },
+            (Method::POST, "/login") => Ok(Response::build_ok()
+                .header("Set-Cookie", "session=abc; Path=/; HttpOnly")
+                .text("welcome")),
+            (Method::POST, "/logout") => Ok(Response::build_ok()
+                .header("set-cookie", "session=; Max-Age=0; Path=/")
+                .text("bye")),
+            (Method::GET, "/events") => {
+                let events = vec![
+                    Event::data("one").id("1"),
+                    Event::comment("keep-alive"),
+                    Event::data("{\"n\":2}").event("update"),
+                ];
+                Ok(Sse::stream(futures::stream::iter(events)).into_response())
+            },
+            (Method::GET, "/ws") => match WebSocketUpgrade::from_request(&req) {
+                Ok(upgrade) => Ok(upgrade.on_upgrade(|mut ws| async move {
+                    while let Ok(Some(message)) = ws.recv().await {
+                        if let Message::Text(text) = message {
+                            let _ = ws.send(Message::text(text.to_uppercase())).await;
+                        }
+                    }
+                })),
+                Err(e) => Err(Error::bad_request(e.to_string())),
+            },
+            _ => Err(Error::NotFound(req.path().to_string())),
+        }
+    }
+
+    #[test]
+    fn test_requests_and_assertions() {
+        block_on(async {
+            let client = TestClient::new(app);
+
+            client
+                .get("/hello")
+                .query(&[("name", "ada")])
+                .await
+                .assert_status(StatusCode::OK)
+                .assert_header("x-query", "ada")
+                .assert_body("hello stranger");
+
+            let echoed = client
+                .post("/echo")
+                .json(&serde_json::json!({"id": 7}))
+                .await;
+            echoed
+                .assert_header("content-type", "application/json")
+                .assert_json(&serde_json::json!({"id": 7}));
+            let value: serde_json::Value = echoed.json();
+            assert_eq!(value["id"], 7);
+
+            client
+                .post("/echo")
+                .form(&[("a", "1 2")])
+                .await
+                .assert_body("a=1+2");
+
+            client
+                .get("/missing")
+                .await
+                .assert_status(StatusCode::NOT_FOUND)
+                .assert_body_contains("/missing");
+        });
+    }
+
+    #[test]
+    fn test_cookie_jar() {
+        block_on(async {
+            let client = TestClient::new(app);
+            client.post("/login").await.assert_success();
+            assert_eq!(client.cookie("session").as_deref(), Some("abc"));
+
+            client.get("/hello").await.assert_body("hello session=abc");
+            client
+                .get("/hello")
+                .cookie("theme", "dark")
+                .await
+                .assert_body("hello session=abc; theme=dark");
+
+            client.post("/logout").await;
+            assert!(client.cookies().is_empty());
+            client.get("/hello").await.assert_body("hello stranger");
+        });
+    }
+
+    #[test]
+    fn test_set_cookie_parsing() {
+        assert_eq!(
+            split_set_cookie("a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT, b=2; Path=/api"),
+            vec!["a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT", "b=2; Path=/api"]
+        );
+        let mut jar = CookieJar::new();
+        jar.store_one("b=2; Path=/api");
+        assert_eq!(jar.matching("/api/users"), vec![("b".to_string(), "2".to_string())]);
+        assert!(jar.matching("/apiary").is_empty());
+    }
+
+    #[test]
+    fn test_sse() {
+        block_on(async {
+            let client = TestClient::new(app);
+            let response = client.get("/events").await;
+            response.assert_header("content-type", "text/event-stream; charset=utf-8");
+
+            let mut events = response.sse();
+            let first = events.next_event().await.unwrap();
+            assert_eq!(first.id.as_deref(), Some("1"));
+            assert_eq!(first.data, "one");
+
+            let second = events.next_event().await.unwrap();
+            assert_eq!(second.event.as_deref(), Some("update"));
+            assert_eq!(second.json::<serde_json::Value>()["n"], 2);
+            assert!(events.next_event().await.is_none());
+        });
+    }
+
+    #[test]
+    fn test_websocket() {
+        block_on(async {
+            let client = TestClient::new(app);
+            let mut ws = client.get("/ws").websocket().await.unwrap();
+            ws.send(Message::text("hi")).await.unwrap();
+            assert_eq!(ws.recv().await.unwrap(), Some(Message::text("HI")));
+            ws.close(None).await.unwrap();
+            assert!(!ws.is_open());
+
+            let rejected = client.get("/hello").websocket().await.unwrap_err();
+            rejected.assert_status(StatusCode::OK);
+        });
+    }
+}
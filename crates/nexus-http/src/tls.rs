@@ -0,0 +1,516 @@
+//! TLS termination
+//! TLS 终止
+//!
+//! # Overview / 概述
+//!
+//! [`TlsConfig`] describes the server certificate, the optional client-certificate
+//! verification (mTLS) and the ALPN protocols. A [`TlsAcceptor`] built from it is
+//! handed to [`ServerBuilder::tls`](crate::server::ServerBuilder::tls); keeping a
+//! clone of the acceptor lets the certificate be replaced at runtime with
+//! [`TlsAcceptor::reload`], which affects every connection accepted afterwards.
+//!
+//! [`TlsConfig`] 描述服务器证书、可选的客户端证书校验（mTLS）以及ALPN协议。由其构建的
+//! [`TlsAcceptor`] 交给 [`ServerBuilder::tls`](crate::server::ServerBuilder::tls)；
+//! 保留接受器的克隆即可在运行时通过 [`TlsAcceptor::reload`] 替换证书，
+//! 之后接受的每个连接都会使用新证书。
+//!
+//! # Equivalent to Spring Boot / 等价于 Spring Boot
+//!
+//! - server.ssl.certificate, server.ssl.certificate-private-key
+//! - server.ssl.client-auth=need|want, server.ssl.trust-certificate
+//! - SSL bundle reloading (spring.ssl.bundle.*.reload-on-update)
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_http::server::ServerBuilder;
+//! use nexus_http::tls::{TlsAcceptor, TlsConfig};
+//!
+//! let config = TlsConfig::from_pem_files("cert.pem", "key.pem")?
+//!     .require_client_auth(&std::fs::read("ca.pem")?)?;
+//! let acceptor = TlsAcceptor::new(config)?;
+//!
+//! let server = ServerBuilder::new()
+//!     .bind(([0, 0, 0, 0], 8443))
+//!     .tls(acceptor.clone())
+//!     .build();
+//!
+//! // Later, after the certificate was renewed on disk
+//! acceptor.reload(TlsConfig::from_pem_files("cert.pem", "key.pem")?)?;
+//! ```
+
+#![warn(missing_docs)]
+#![warn(unreachable_pub)]
+
+use std::fmt;
+use std::io::{self, Read as _, Write as _};
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use bytes::{Buf, Bytes, BytesMut};
+use nexus_runtime::io::TcpStream;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConnection};
+
+use crate::error::{Error, Result};
+
+/// ALPN protocols advertised by default, in order of preference
+/// 默认通告的ALPN协议，按优先顺序排列
+const DEFAULT_ALPN: [&str; 2] = ["h2", "http/1.1"];
+
+/// Size of a single socket read
+/// 单次套接字读取的大小
+const READ_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Largest amount of plaintext encrypted before it is flushed
+/// 刷新之前加密的最大明文量
+const WRITE_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Client certificate policy
+/// 客户端证书策略
+#[derive(Clone)]
+enum ClientAuth {
+    /// No client certificate is asked for / 不请求客户端证书
+    None,
+    /// A certificate is asked for, and verified when presented / 请求证书，提供时进行校验
+    Optional(RootCertStore),
+    /// Every client must present a valid certificate / 每个客户端都必须提供有效证书
+    Required(RootCertStore),
+}
+
+/// TLS settings of a server
+/// 服务器的TLS设置
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// use nexus_http::tls::TlsConfig;
+///
+/// let config = TlsConfig::from_pem(CERT_PEM, KEY_PEM)?
+///     .request_client_auth(CA_PEM)?
+///     .alpn_protocols(&["http/1.1"]);
+/// ```
+pub struct TlsConfig {
+    cert_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    client_auth: ClientAuth,
+    alpn: Vec<Vec<u8>>,
+}
+
+impl TlsConfig {
+    /// Create a configuration from a PEM certificate chain and a PEM private key
+    /// 从PEM证书链和PEM私钥创建配置
+    ///
+    /// The chain starts with the server certificate, followed by its intermediates.
+    /// The key may be PKCS#8, PKCS#1 (RSA) or SEC1 (EC).
+    ///
+    /// 证书链以服务器证书开头，后跟其中间证书。私钥可以是PKCS#8、PKCS#1（RSA）或SEC1（EC）。
+    pub fn from_pem(cert_chain: &[u8], key: &[u8]) -> Result<Self> {
+        let cert_chain = parse_certificates(cert_chain, "certificate chain")?;
+        let key = PrivateKeyDer::from_pem_slice(key)
+            .map_err(|e| Error::Internal(format!("Invalid TLS private key: {}", e)))?;
+
+        Ok(Self {
+            cert_chain,
+            key,
+            client_auth: ClientAuth::None,
+            alpn: DEFAULT_ALPN.iter().map(|p| p.as_bytes().to_vec()).collect(),
+        })
+    }
+
+    /// Create a configuration from PEM files
+    /// 从PEM文件创建配置
+    pub fn from_pem_files(cert_chain: impl AsRef<Path>, key: impl AsRef<Path>) -> Result<Self> {
+        Self::from_pem(&read_file(cert_chain.as_ref())?, &read_file(key.as_ref())?)
+    }
+
+    /// Require a client certificate issued by one of the PEM CA certificates (mTLS)
+    /// 要求由PEM CA证书之一签发的客户端证书（mTLS）
+    ///
+    /// Handshakes without a valid certificate fail.
+    /// 没有有效证书的握手将失败。
+    pub fn require_client_auth(mut self, ca_certs: &[u8]) -> Result<Self> {
+        self.client_auth = ClientAuth::Required(root_store(ca_certs)?);
+        Ok(self)
+    }
+
+    /// Ask for a client certificate issued by one of the PEM CA certificates
+    /// 请求由PEM CA证书之一签发的客户端证书
+    ///
+    /// Clients may connect without one; a certificate that is presented must be valid.
+    /// 客户端可以不提供证书连接；但提供的证书必须有效。
+    pub fn request_client_auth(mut self, ca_certs: &[u8]) -> Result<Self> {
+        self.client_auth = ClientAuth::Optional(root_store(ca_certs)?);
+        Ok(self)
+    }
+
+    /// Set the ALPN protocols to advertise, in order of preference
+    /// 设置要通告的ALPN协议，按优先顺序排列
+    ///
+    /// Defaults to `h2` and `http/1.1`. Connections negotiating `h2` are served
+    /// as HTTP/2; an empty list disables ALPN.
+    /// 默认为 `h2` 和 `http/1.1`。协商为 `h2` 的连接以HTTP/2提供服务；空列表禁用ALPN。
+    pub fn alpn_protocols(mut self, protocols: &[&str]) -> Self {
+        self.alpn = protocols.iter().map(|p| p.as_bytes().to_vec()).collect();
+        self
+    }
+
+    /// Build the rustls server configuration
+    /// 构建rustls服务器配置
+    fn into_server_config(self) -> Result<Arc<rustls::ServerConfig>> {
+        let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+        let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| Error::Internal(format!("Invalid TLS configuration: {}", e)))?;
+
+        let builder = match self.client_auth {
+            ClientAuth::None => builder.with_no_client_auth(),
+            ClientAuth::Optional(roots) => {
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                        .allow_unauthenticated()
+                        .build()
+                        .map_err(|e| Error::Internal(format!("Invalid client CA: {}", e)))?;
+                builder.with_client_cert_verifier(verifier)
+            },
+            ClientAuth::Required(roots) => {
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                        .build()
+                        .map_err(|e| Error::Internal(format!("Invalid client CA: {}", e)))?;
+                builder.with_client_cert_verifier(verifier)
+            },
+        };
+
+        let mut config = builder
+            .with_single_cert(self.cert_chain, self.key)
+            .map_err(|e| Error::Internal(format!("Invalid TLS certificate: {}", e)))?;
+        config.alpn_protocols = self.alpn;
+        Ok(Arc::new(config))
+    }
+}
+
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let client_auth = match self.client_auth {
+            ClientAuth::None => "none",
+            ClientAuth::Optional(_) => "optional",
+            ClientAuth::Required(_) => "required",
+        };
+        let alpn: Vec<_> = self
+            .alpn
+            .iter()
+            .map(|p| String::from_utf8_lossy(p))
+            .collect();
+        f.debug_struct("TlsConfig")
+            .field("certificates", &self.cert_chain.len())
+            .field("client_auth", &client_auth)
+            .field("alpn", &alpn)
+            .finish_non_exhaustive()
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| Error::Io(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+fn parse_certificates(pem: &[u8], what: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_slice_iter(pem)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::Internal(format!("Invalid TLS {}: {}", what, e)))?;
+    if certs.is_empty() {
+        return Err(Error::Internal(format!("No certificates found in TLS {}", what)));
+    }
+    Ok(certs)
+}
+
+fn root_store(pem: &[u8]) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for cert in parse_certificates(pem, "client CA")? {
+        roots
+            .add(cert)
+            .map_err(|e| Error::Internal(format!("Invalid client CA: {}", e)))?;
+    }
+    Ok(roots)
+}
+
+/// Accepts TLS connections with a certificate that can be swapped at runtime
+/// 使用可在运行时替换的证书接受TLS连接
+///
+/// Clones share the configuration, so reloading through any clone affects the
+/// server holding another one.
+/// 克隆共享同一配置，因此通过任一克隆重新加载都会影响持有另一个克隆的服务器。
+#[derive(Clone)]
+pub struct TlsAcceptor {
+    config: Arc<RwLock<Arc<rustls::ServerConfig>>>,
+}
+
+impl TlsAcceptor {
+    /// Create an acceptor, validating the certificate and key
+    /// 创建接受器，并校验证书和私钥
+    pub fn new(config: TlsConfig) -> Result<Self> {
+        Ok(Self {
+            config: Arc::new(RwLock::new(config.into_server_config()?)),
+        })
+    }
+
+    /// Replace the configuration used for new connections
+    /// 替换用于新连接的配置
+    ///
+    /// Established connections keep the configuration they were accepted with.
+    /// On error the current configuration stays in place.
+    /// 已建立的连接保留其被接受时的配置。出错时当前配置保持不变。
+    pub fn reload(&self, config: TlsConfig) -> Result<()> {
+        let config = config.into_server_config()?;
+        *self.config.write().unwrap_or_else(PoisonError::into_inner) = config;
+        tracing::info!("TLS configuration reloaded");
+        Ok(())
+    }
+
+    fn current(&self) -> Arc<rustls::ServerConfig> {
+        self.config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Run the server side of the handshake on an accepted connection
+    /// 在已接受的连接上执行握手的服务器端
+    pub(crate) async fn accept(&self, tcp: TcpStream) -> io::Result<TlsStream> {
+        let conn = ServerConnection::new(self.current()).map_err(io::Error::other)?;
+        let mut stream = TlsStream {
+            tcp,
+            conn,
+            incoming: BytesMut::new(),
+            scratch: vec![0u8; READ_CHUNK_SIZE],
+            outgoing: Vec::new(),
+        };
+
+        while stream.conn.is_handshaking() {
+            stream.flush().await?;
+            if !stream.conn.is_handshaking() {
+                break;
+            }
+            if !stream.read_tls().await? {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed during TLS handshake",
+                ));
+            }
+        }
+        // Last handshake flight and session tickets
+        // 最后一轮握手消息和会话票据
+        stream.flush().await?;
+        Ok(stream)
+    }
+}
+
+impl fmt::Debug for TlsAcceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsAcceptor").finish_non_exhaustive()
+    }
+}
+
+/// Certificate chain presented by a TLS client
+/// TLS客户端提供的证书链
+///
+/// Available through [`Request::peer_certificate`](crate::Request::peer_certificate)
+/// when the server verifies client certificates.
+/// 当服务器校验客户端证书时，可通过 [`Request::peer_certificate`](crate::Request::peer_certificate) 获取。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerCertificate {
+    chain: Vec<Bytes>,
+}
+
+impl PeerCertificate {
+    /// Create from DER certificates, client certificate first
+    /// 从DER证书创建，客户端证书在前
+    ///
+    /// Returns `None` for an empty chain.
+    /// 链为空时返回 `None`。
+    pub fn new(chain: Vec<Bytes>) -> Option<Self> {
+        if chain.is_empty() {
+            None
+        } else {
+            Some(Self { chain })
+        }
+    }
+
+    /// DER encoding of the client certificate
+    /// 客户端证书的DER编码
+    pub fn der(&self) -> &[u8] {
+        &self.chain[0]
+    }
+
+    /// The whole chain as sent by the client, client certificate first
+    /// 客户端发送的完整证书链，客户端证书在前
+    pub fn chain(&self) -> &[Bytes] {
+        &self.chain
+    }
+}
+
+/// A server-side TLS connection over a TCP stream
+/// 基于TCP流的服务器端TLS连接
+pub(crate) struct TlsStream {
+    tcp: TcpStream,
+    conn: ServerConnection,
+    /// Ciphertext read from the socket but not yet fed to rustls / 已从套接字读取但尚未交给rustls的密文
+    incoming: BytesMut,
+    /// Socket read buffer / 套接字读取缓冲区
+    scratch: Vec<u8>,
+    /// Ciphertext waiting to be written / 等待写出的密文
+    outgoing: Vec<u8>,
+}
+
+impl TlsStream {
+    /// Read decrypted bytes, returning 0 once the peer has closed the connection
+    /// 读取解密后的字节，对端关闭连接后返回0
+    ///
+    /// Cancelling the returned future loses no data.
+    /// 取消返回的future不会丢失数据。
+    pub(crate) async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.conn.reader().read(buf) {
+                Ok(n) => return Ok(n),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {},
+                // Closed without close_notify, which HTTP clients commonly do
+                // 未发送close_notify就关闭，HTTP客户端通常如此
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(0),
+                Err(e) => return Err(e),
+            }
+            if !self.read_tls().await? {
+                return Ok(0);
+            }
+        }
+    }
+
+    /// Encrypt and write all bytes
+    /// 加密并写入所有字节
+    pub(crate) async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        for chunk in buf.chunks(WRITE_CHUNK_SIZE) {
+            self.conn.writer().write_all(chunk)?;
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Send `close_notify` and shut down the write side
+    /// 发送 `close_notify` 并关闭写入端
+    pub(crate) async fn close(&mut self) -> io::Result<()> {
+        self.conn.send_close_notify();
+        self.flush().await?;
+        self.tcp.shutdown(std::net::Shutdown::Write)
+    }
+
+    /// The underlying socket
+    /// 底层套接字
+    pub(crate) fn tcp(&self) -> &TcpStream {
+        &self.tcp
+    }
+
+    /// ALPN protocol agreed during the handshake
+    /// 握手期间协商的ALPN协议
+    pub(crate) fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.conn.alpn_protocol()
+    }
+
+    /// Verified client certificate chain, if one was presented
+    /// 经过校验的客户端证书链（如果提供了）
+    pub(crate) fn peer_certificate(&self) -> Option<PeerCertificate> {
+        let certs = self.conn.peer_certificates()?;
+        PeerCertificate::new(certs.iter().map(|c| Bytes::copy_from_slice(c)).collect())
+    }
+
+    /// Feed more ciphertext to rustls, returning false at end of stream
+    /// 向rustls提供更多密文，到达流末尾时返回false
+    ///
+    /// The only await point is the socket read, so cancelling loses nothing.
+    /// 唯一的等待点是套接字读取，因此取消不会丢失任何数据。
+    async fn read_tls(&mut self) -> io::Result<bool> {
+        if self.incoming.is_empty() {
+            let n = self.tcp.read(&mut self.scratch).await?;
+            if n == 0 {
+                return Ok(false);
+            }
+            self.incoming.extend_from_slice(&self.scratch[..n]);
+        }
+
+        let mut input: &[u8] = &self.incoming;
+        let consumed = self.conn.read_tls(&mut input)?;
+        self.incoming.advance(consumed);
+
+        if let Err(e) = self.conn.process_new_packets() {
+            // Best effort: tell the peer why the connection is dropped
+            // 尽力而为：告知对端连接被丢弃的原因
+            let _ = self.flush().await;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+        Ok(true)
+    }
+
+    /// Write out all pending ciphertext
+    /// 写出所有待发送的密文
+    async fn flush(&mut self) -> io::Result<()> {
+        while self.conn.wants_write() {
+            self.outgoing.clear();
+            self.conn.write_tls(&mut self.outgoing)?;
+            self.tcp.write_all(&self.outgoing).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA_PEM: &str = include_str!("../testdata/tls/ca.pem");
+    const SERVER_PEM: &str = include_str!("../testdata/tls/server.pem");
+    const SERVER_KEY: &str = include_str!("../testdata/tls/server.key");
+
+    #[test]
+    fn test_from_pem() {
+        let config = TlsConfig::from_pem(SERVER_PEM.as_bytes(), SERVER_KEY.as_bytes()).unwrap();
+        assert_eq!(config.cert_chain.len(), 1);
+        assert_eq!(config.alpn, vec![b"h2".to_vec(), b"http/1.1".to_vec()]);
+        assert!(TlsAcceptor::new(config).is_ok());
+
+        let config = TlsConfig::from_pem(SERVER_PEM.as_bytes(), SERVER_KEY.as_bytes())
+            .unwrap()
+            .require_client_auth(CA_PEM.as_bytes())
+            .unwrap()
+            .alpn_protocols(&["http/1.1"]);
+        assert_eq!(config.alpn, vec![b"http/1.1".to_vec()]);
+        assert!(TlsAcceptor::new(config).is_ok());
+    }
+
+    #[test]
+    fn test_from_pem_rejects_invalid_input() {
+        // Key given where the certificate is expected
+        assert!(TlsConfig::from_pem(SERVER_KEY.as_bytes(), SERVER_KEY.as_bytes()).is_err());
+        assert!(TlsConfig::from_pem(SERVER_PEM.as_bytes(), SERVER_PEM.as_bytes()).is_err());
+        assert!(TlsConfig::from_pem(b"", b"").is_err());
+
+        let config = TlsConfig::from_pem(SERVER_PEM.as_bytes(), SERVER_KEY.as_bytes()).unwrap();
+        assert!(config.request_client_auth(b"not a certificate").is_err());
+    }
+
+    #[test]
+    fn test_reload_keeps_current_config_on_error() {
+        let config = TlsConfig::from_pem(SERVER_PEM.as_bytes(), SERVER_KEY.as_bytes()).unwrap();
+        let acceptor = TlsAcceptor::new(config).unwrap();
+        let before = acceptor.current();
+
+        // Certificate and key that do not belong together
+        let server2 = include_str!("../testdata/tls/server2.pem");
+        let mismatched = TlsConfig::from_pem(server2.as_bytes(), SERVER_KEY.as_bytes()).unwrap();
+        assert!(acceptor.reload(mismatched).is_err());
+        assert!(Arc::ptr_eq(&before, &acceptor.current()));
+
+        let config = TlsConfig::from_pem(SERVER_PEM.as_bytes(), SERVER_KEY.as_bytes()).unwrap();
+        acceptor.clone().reload(config).unwrap();
+        assert!(!Arc::ptr_eq(&before, &acceptor.current()));
+    }
+}
@@ -0,0 +1,67 @@
+//! Connection takeover after `101 Switching Protocols`
+//! `101 Switching Protocols` 之后的连接接管
+//!
+//! # Overview / 概述
+//!
+//! A handler accepting a protocol upgrade attaches an [`OnUpgrade`] callback to its
+//! `101` response. Once the response head is written, the HTTP/1.1 server stops
+//! reading requests and hands the raw connection to the callback.
+//!
+//! 接受协议升级的处理器会在其 `101` 响应上附加 [`OnUpgrade`] 回调。响应头写出后，
+//! HTTP/1.1 服务器停止读取请求，并将原始连接交给该回调。
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use bytes::Bytes;
+use crate::server::{ServerState, ServerStream};
+
+/// A connection handed over by the server
+/// 服务器移交的连接
+pub(crate) struct Upgraded {
+    /// The underlying socket / 底层套接字
+    pub(crate) stream: ServerStream,
+    /// Bytes read past the end of the upgrade request / 在升级请求之后已读取的字节
+    pub(crate) buffered: Bytes,
+    /// Shared server state, for shutdown notifications / 共享服务器状态，用于关闭通知
+    pub(crate) state: Arc<ServerState>,
+}
+
+/// Future driving an upgraded connection
+/// 驱动已升级连接的future
+pub(crate) type UpgradeFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+type UpgradeFn = Box<dyn FnOnce(Upgraded) -> UpgradeFuture + Send>;
+
+/// Callback taking over the connection once the `101` response is sent
+/// 发送 `101` 响应后接管连接的回调
+///
+/// Cloning shares the callback; only the first [`take`](Self::take) gets it.
+/// 克隆会共享回调；只有第一次 [`take`](Self::take) 能取得它。
+#[derive(Clone)]
+pub(crate) struct OnUpgrade(Arc<Mutex<Option<UpgradeFn>>>);
+
+impl OnUpgrade {
+    /// Wrap a takeover callback
+    /// 包装接管回调
+    pub(crate) fn new<F>(callback: F) -> Self
+    where
+        F: FnOnce(Upgraded) -> UpgradeFuture + Send + 'static,
+    {
+        Self(Arc::new(Mutex::new(Some(Box::new(callback)))))
+    }
+
+    /// Take the callback, leaving nothing behind
+    /// 取出回调，不留下任何内容
+    pub(crate) fn take(&self) -> Option<UpgradeFn> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).take()
+    }
+}
+
+impl fmt::Debug for OnUpgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnUpgrade").finish_non_exhaustive()
+    }
+}
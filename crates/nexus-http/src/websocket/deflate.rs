@@ -0,0 +1,245 @@
+//! permessage-deflate extension (RFC 7692)
+//! permessage-deflate 扩展（RFC 7692）
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+
+use super::WebSocketError;
+
+/// Extension token
+/// 扩展标识
+const EXTENSION: &str = "permessage-deflate";
+
+/// Tail removed from every compressed message and restored before inflating
+/// 从每个压缩消息中移除、并在解压前恢复的尾部
+const TAIL: [u8; 4] = [0x00, 0x00, 0xFF, 0xFF];
+
+/// The only LZ77 window size the deflate backend supports
+/// 压缩后端唯一支持的LZ77窗口大小
+const WINDOW_BITS: u8 = 15;
+
+/// Negotiated permessage-deflate parameters
+/// 协商后的 permessage-deflate 参数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DeflateConfig {
+    /// Reset the compressor after every message / 每条消息后重置压缩器
+    server_no_context_takeover: bool,
+    /// The client resets its compressor after every message / 客户端在每条消息后重置其压缩器
+    client_no_context_takeover: bool,
+}
+
+impl DeflateConfig {
+    /// Pick the first acceptable offer from `Sec-WebSocket-Extensions`
+    /// 从 `Sec-WebSocket-Extensions` 中选择第一个可接受的提议
+    ///
+    /// Offers asking for a server window smaller than 15 bits are declined.
+    /// 请求小于15位服务器窗口的提议会被拒绝。
+    pub(crate) fn negotiate(header: &str) -> Option<Self> {
+        header.split(',').find_map(Self::accept_offer)
+    }
+
+    fn accept_offer(offer: &str) -> Option<Self> {
+        let mut params = offer.split(';').map(str::trim);
+        if !params.next()?.eq_ignore_ascii_case(EXTENSION) {
+            return None;
+        }
+
+        let mut config = Self::default();
+        let mut seen = Vec::new();
+        for param in params {
+            let (name, value) = match param.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (param, None),
+            };
+            let name = name.to_ascii_lowercase();
+            if seen.contains(&name) {
+                return None;
+            }
+            match (name.as_str(), value) {
+                ("server_no_context_takeover", None) => config.server_no_context_takeover = true,
+                ("client_no_context_takeover", None) => config.client_no_context_takeover = true,
+                ("server_max_window_bits", Some(bits)) => {
+                    if window_bits(bits)? != WINDOW_BITS {
+                        return None;
+                    }
+                },
+                ("client_max_window_bits", None) => {},
+                ("client_max_window_bits", Some(bits)) => {
+                    window_bits(bits)?;
+                },
+                _ => return None,
+            }
+            seen.push(name);
+        }
+        Some(config)
+    }
+
+    /// Value of the `Sec-WebSocket-Extensions` response header
+    /// `Sec-WebSocket-Extensions` 响应头的值
+    pub(crate) fn response_header(self) -> String {
+        let mut value = EXTENSION.to_string();
+        if self.server_no_context_takeover {
+            value.push_str("; server_no_context_takeover");
+        }
+        if self.client_no_context_takeover {
+            value.push_str("; client_no_context_takeover");
+        }
+        value
+    }
+}
+
+fn window_bits(value: &str) -> Option<u8> {
+    value.parse().ok().filter(|bits| (8..=15).contains(bits))
+}
+
+/// Per-connection compression state
+/// 每个连接的压缩状态
+pub(crate) struct Deflate {
+    config: DeflateConfig,
+    compress: Compress,
+    decompress: Decompress,
+}
+
+impl Deflate {
+    /// Create the compression state for a negotiated configuration
+    /// 为协商后的配置创建压缩状态
+    pub(crate) fn new(config: DeflateConfig) -> Self {
+        Self {
+            config,
+            compress: Compress::new(Compression::default(), false),
+            decompress: Decompress::new(false),
+        }
+    }
+
+    /// Compress one outgoing message
+    /// 压缩一条发出的消息
+    pub(crate) fn compress(&mut self, data: &[u8]) -> Result<Vec<u8>, WebSocketError> {
+        let mut out = Vec::with_capacity(data.len() / 2 + 64);
+        let mut input = data;
+        loop {
+            if out.capacity() - out.len() < 64 {
+                out.reserve(out.capacity().max(64));
+            }
+            let before = self.compress.total_in();
+            self.compress
+                .compress_vec(input, &mut out, FlushCompress::Sync)
+                .map_err(|e| WebSocketError::Other(format!("deflate failed: {}", e)))?;
+            let consumed = usize::try_from(self.compress.total_in() - before).unwrap_or(0);
+            input = input.get(consumed..).unwrap_or_default();
+            if input.is_empty() && out.len() < out.capacity() {
+                break;
+            }
+        }
+
+        if out.ends_with(&TAIL) {
+            out.truncate(out.len() - TAIL.len());
+        }
+        if self.config.server_no_context_takeover {
+            self.compress.reset();
+        }
+        Ok(out)
+    }
+
+    /// Inflate one incoming message, failing once it grows past `max_size` (0 = no limit)
+    /// 解压一条收到的消息，一旦超过 `max_size`（0 = 无限制）即失败
+    pub(crate) fn decompress(
+        &mut self,
+        data: &[u8],
+        max_size: usize,
+    ) -> Result<Vec<u8>, WebSocketError> {
+        let mut input = Vec::with_capacity(data.len() + TAIL.len());
+        input.extend_from_slice(data);
+        input.extend_from_slice(&TAIL);
+
+        let mut out = Vec::with_capacity((data.len() * 2).max(64));
+        let mut pos = 0;
+        loop {
+            if out.capacity() - out.len() < 64 {
+                out.reserve(out.capacity());
+            }
+            let (before_in, before_out) = (self.decompress.total_in(), out.len());
+            let status = self
+                .decompress
+                .decompress_vec(&input[pos..], &mut out, FlushDecompress::Sync)
+                .map_err(|e| WebSocketError::InvalidPayload(format!("inflate failed: {}", e)))?;
+            pos += usize::try_from(self.decompress.total_in() - before_in).unwrap_or(0);
+
+            if max_size > 0 && out.len() > max_size {
+                return Err(WebSocketError::MessageTooLarge {
+                    size: out.len(),
+                    max: max_size,
+                });
+            }
+            let done = pos == input.len() && out.len() < out.capacity();
+            if done || status == Status::StreamEnd {
+                break;
+            }
+            if status == Status::BufError && out.len() == before_out {
+                return Err(WebSocketError::InvalidPayload("truncated deflate data".to_string()));
+            }
+        }
+
+        if self.config.client_no_context_takeover {
+            self.decompress.reset(false);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_offers() {
+        let config =
+            DeflateConfig::negotiate("permessage-deflate; client_max_window_bits").unwrap();
+        assert_eq!(config.response_header(), "permessage-deflate");
+
+        let config = DeflateConfig::negotiate(
+            "permessage-deflate; server_max_window_bits=10, \
+             permessage-deflate; server_no_context_takeover",
+        )
+        .unwrap();
+        assert_eq!(config.response_header(), "permessage-deflate; server_no_context_takeover");
+
+        assert!(DeflateConfig::negotiate("x-webkit-deflate-frame").is_none());
+        assert!(DeflateConfig::negotiate("permessage-deflate; unknown").is_none());
+        assert!(
+            DeflateConfig::negotiate("permessage-deflate; client_max_window_bits=16").is_none()
+        );
+    }
+
+    #[test]
+    fn test_rfc_7692_hello() {
+        // RFC 7692 Section 7.2.3.1: "Hello" compressed into a single block
+        let mut deflate = Deflate::new(DeflateConfig::default());
+        let data = [0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00];
+        assert_eq!(deflate.decompress(&data, 0).unwrap(), b"Hello");
+    }
+
+    #[test]
+    fn test_roundtrip_with_context_takeover() {
+        let mut server = Deflate::new(DeflateConfig::default());
+        let mut client = Deflate::new(DeflateConfig::default());
+        let message = "the quick brown fox jumps over the lazy dog ".repeat(100);
+
+        let first = server.compress(message.as_bytes()).unwrap();
+        let second = server.compress(message.as_bytes()).unwrap();
+        assert!(first.len() < message.len());
+        // The second message refers back to the first one
+        assert!(second.len() < first.len());
+
+        assert_eq!(client.decompress(&first, 0).unwrap(), message.as_bytes());
+        assert_eq!(client.decompress(&second, 0).unwrap(), message.as_bytes());
+    }
+
+    #[test]
+    fn test_decompress_enforces_max_size() {
+        let mut server = Deflate::new(DeflateConfig::default());
+        let bomb = server.compress(&vec![0u8; 1 << 20]).unwrap();
+
+        let mut client = Deflate::new(DeflateConfig::default());
+        let err = client.decompress(&bomb, 4096).unwrap_err();
+        assert!(matches!(err, WebSocketError::MessageTooLarge { max: 4096, .. }));
+    }
+}
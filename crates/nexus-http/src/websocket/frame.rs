@@ -0,0 +1,288 @@
+//! WebSocket frame codec (RFC 6455 Section 5)
+//! WebSocket 帧编解码（RFC 6455 第5节）
+
+use bytes::{Buf, BytesMut};
+
+use super::WebSocketError;
+
+/// Largest payload of a control frame
+/// 控制帧的最大负载
+pub(crate) const MAX_CONTROL_PAYLOAD: usize = 125;
+
+/// Frame opcode
+/// 帧操作码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OpCode {
+    /// Continuation of a fragmented message / 分片消息的后续帧
+    Continuation,
+    /// First frame of a text message / 文本消息的首帧
+    Text,
+    /// First frame of a binary message / 二进制消息的首帧
+    Binary,
+    /// Connection close / 连接关闭
+    Close,
+    /// Ping / Ping
+    Ping,
+    /// Pong / Pong
+    Pong,
+}
+
+impl OpCode {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0x0 => Some(OpCode::Continuation),
+            0x1 => Some(OpCode::Text),
+            0x2 => Some(OpCode::Binary),
+            0x8 => Some(OpCode::Close),
+            0x9 => Some(OpCode::Ping),
+            0xA => Some(OpCode::Pong),
+            _ => None,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            OpCode::Continuation => 0x0,
+            OpCode::Text => 0x1,
+            OpCode::Binary => 0x2,
+            OpCode::Close => 0x8,
+            OpCode::Ping => 0x9,
+            OpCode::Pong => 0xA,
+        }
+    }
+
+    /// Whether this is a control opcode
+    /// 是否为控制操作码
+    pub(crate) fn is_control(self) -> bool {
+        matches!(self, OpCode::Close | OpCode::Ping | OpCode::Pong)
+    }
+}
+
+/// A single WebSocket frame with its payload unmasked
+/// 负载已去掩码的单个WebSocket帧
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Frame {
+    /// Last frame of the message / 消息的最后一帧
+    pub(crate) fin: bool,
+    /// RSV1, set on compressed messages / RSV1，在压缩消息上设置
+    pub(crate) rsv1: bool,
+    /// Opcode / 操作码
+    pub(crate) opcode: OpCode,
+    /// Payload / 负载
+    pub(crate) payload: Vec<u8>,
+}
+
+impl Frame {
+    /// Create a frame
+    /// 创建帧
+    pub(crate) fn new(fin: bool, rsv1: bool, opcode: OpCode, payload: Vec<u8>) -> Self {
+        Self {
+            fin,
+            rsv1,
+            opcode,
+            payload,
+        }
+    }
+
+    /// Decode one frame from the front of `buf`
+    /// 从 `buf` 开头解码一个帧
+    ///
+    /// Returns `Ok(None)` until the whole frame is buffered. Frames from clients must
+    /// be masked (`masked = true`) and frames from servers must not. The payload
+    /// length is checked against `max_frame_size` (0 = no limit) before any of it is
+    /// buffered.
+    ///
+    /// 在整个帧缓冲完成之前返回 `Ok(None)`。来自客户端的帧必须带掩码（`masked = true`），
+    /// 来自服务器的帧则不能带掩码。负载长度在缓冲之前就会与 `max_frame_size`（0 = 无限制）比较。
+    pub(crate) fn decode(
+        buf: &mut BytesMut,
+        masked: bool,
+        max_frame_size: usize,
+    ) -> Result<Option<Frame>, WebSocketError> {
+        if buf.len() < 2 {
+            return Ok(None);
+        }
+        let (b0, b1) = (buf[0], buf[1]);
+
+        let fin = b0 & 0x80 != 0;
+        let rsv1 = b0 & 0x40 != 0;
+        if b0 & 0x30 != 0 {
+            return Err(WebSocketError::Protocol("reserved bits set".to_string()));
+        }
+        let opcode = OpCode::from_u8(b0 & 0x0F)
+            .ok_or_else(|| WebSocketError::Protocol(format!("reserved opcode {:#x}", b0 & 0x0F)))?;
+        if (b1 & 0x80 != 0) != masked {
+            let expected = if masked { "masked" } else { "unmasked" };
+            return Err(WebSocketError::Protocol(format!("frames must be {}", expected)));
+        }
+
+        let (len, mut offset) = match b1 & 0x7F {
+            126 if buf.len() < 4 => return Ok(None),
+            126 => (u64::from(u16::from_be_bytes([buf[2], buf[3]])), 4),
+            127 if buf.len() < 10 => return Ok(None),
+            127 => {
+                let mut len = [0u8; 8];
+                len.copy_from_slice(&buf[2..10]);
+                (u64::from_be_bytes(len), 10)
+            },
+            len => (u64::from(len), 2),
+        };
+        if len >> 63 != 0 {
+            return Err(WebSocketError::Protocol("payload length overflow".to_string()));
+        }
+
+        if opcode.is_control() {
+            if !fin {
+                return Err(WebSocketError::Protocol("fragmented control frame".to_string()));
+            }
+            if len > MAX_CONTROL_PAYLOAD as u64 {
+                return Err(WebSocketError::Protocol("control frame too large".to_string()));
+            }
+        }
+        let len = usize::try_from(len).unwrap_or(usize::MAX);
+        if max_frame_size > 0 && len > max_frame_size {
+            return Err(WebSocketError::FrameTooLarge {
+                size: len,
+                max: max_frame_size,
+            });
+        }
+
+        let mask_len = if masked { 4 } else { 0 };
+        if buf.len() < offset + mask_len + len {
+            buf.reserve(offset + mask_len + len - buf.len());
+            return Ok(None);
+        }
+        let mut mask = [0u8; 4];
+        if masked {
+            mask.copy_from_slice(&buf[offset..offset + 4]);
+            offset += 4;
+        }
+
+        buf.advance(offset);
+        let mut payload = buf.split_to(len).to_vec();
+        if masked {
+            apply_mask(&mut payload, mask);
+        }
+        Ok(Some(Frame::new(fin, rsv1, opcode, payload)))
+    }
+
+    /// Encode the frame, masking the payload when a key is given
+    /// 编码帧，给定密钥时对负载加掩码
+    pub(crate) fn encode(&self, dst: &mut Vec<u8>, mask: Option<[u8; 4]>) {
+        let mut b0 = self.opcode.as_u8();
+        if self.fin {
+            b0 |= 0x80;
+        }
+        if self.rsv1 {
+            b0 |= 0x40;
+        }
+        dst.push(b0);
+
+        let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+        let len = self.payload.len();
+        if len < 126 {
+            dst.push(mask_bit | len as u8);
+        } else if let Ok(len) = u16::try_from(len) {
+            dst.push(mask_bit | 126);
+            dst.extend_from_slice(&len.to_be_bytes());
+        } else {
+            dst.push(mask_bit | 127);
+            dst.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+
+        let start = dst.len();
+        if let Some(mask) = mask {
+            dst.extend_from_slice(&mask);
+            dst.extend_from_slice(&self.payload);
+            apply_mask(&mut dst[start + 4..], mask);
+        } else {
+            dst.extend_from_slice(&self.payload);
+        }
+    }
+}
+
+/// XOR `buf` with the masking key (RFC 6455 Section 5.3)
+/// 用掩码密钥对 `buf` 进行异或（RFC 6455 第5.3节）
+pub(crate) fn apply_mask(buf: &mut [u8], mask: [u8; 4]) {
+    for (byte, key) in buf.iter_mut().zip(mask.iter().cycle()) {
+        *byte ^= key;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(bytes: &[u8], masked: bool) -> Result<Option<Frame>, WebSocketError> {
+        Frame::decode(&mut BytesMut::from(bytes), masked, 0)
+    }
+
+    #[test]
+    fn test_decode_rfc_examples() {
+        // RFC 6455 Section 5.7: unmasked and masked "Hello"
+        let frame = decode_all(&[0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f], false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame, Frame::new(true, false, OpCode::Text, b"Hello".to_vec()));
+
+        let masked = [
+            0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+        ];
+        let frame = decode_all(&masked, true).unwrap().unwrap();
+        assert_eq!(frame.payload, b"Hello");
+
+        // Fragmented "Hel" + "lo"
+        let first = decode_all(&[0x01, 0x03, 0x48, 0x65, 0x6c], false)
+            .unwrap()
+            .unwrap();
+        assert!(!first.fin);
+        assert_eq!(first.opcode, OpCode::Text);
+        let last = decode_all(&[0x80, 0x02, 0x6c, 0x6f], false)
+            .unwrap()
+            .unwrap();
+        assert!(last.fin);
+        assert_eq!(last.opcode, OpCode::Continuation);
+    }
+
+    #[test]
+    fn test_encode_roundtrip_lengths() {
+        for len in [0, 125, 126, 65_535, 65_536] {
+            let frame = Frame::new(true, len == 126, OpCode::Binary, vec![7u8; len]);
+            let mut out = Vec::new();
+            frame.encode(&mut out, Some([1, 2, 3, 4]));
+
+            let mut buf = BytesMut::from(&out[..out.len() - 1]);
+            assert_eq!(Frame::decode(&mut buf, true, 0).unwrap(), None);
+            buf.extend_from_slice(&out[out.len() - 1..]);
+            assert_eq!(Frame::decode(&mut buf, true, 0).unwrap(), Some(frame));
+            assert!(buf.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_frames() {
+        // Unmasked client frame
+        assert!(decode_all(&[0x81, 0x00], true).is_err());
+        // RSV2 set
+        assert!(decode_all(&[0xA1, 0x80, 0, 0, 0, 0], true).is_err());
+        // Reserved opcode
+        assert!(decode_all(&[0x83, 0x80, 0, 0, 0, 0], true).is_err());
+        // Fragmented ping
+        assert!(decode_all(&[0x09, 0x80, 0, 0, 0, 0], true).is_err());
+        // Oversized ping
+        assert!(decode_all(&[0x89, 0xFE, 0x00, 0x7E], true).is_err());
+    }
+
+    #[test]
+    fn test_decode_enforces_max_frame_size() {
+        let mut buf = BytesMut::from(&[0x82, 0xFE, 0x01, 0x00][..]);
+        let err = Frame::decode(&mut buf, true, 255).unwrap_err();
+        assert!(matches!(
+            err,
+            WebSocketError::FrameTooLarge {
+                size: 256,
+                max: 255
+            }
+        ));
+    }
+}
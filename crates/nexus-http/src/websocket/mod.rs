@@ -0,0 +1,1441 @@
+//! WebSocket support
+//! WebSocket支持
+//!
+//! # Overview / 概述
+//!
+//! WebSocket provides full-duplex communication over a single TCP connection.
+//! A handler validates the handshake with [`WebSocketUpgrade::from_request`] and
+//! returns [`WebSocketUpgrade::on_upgrade`]; once the `101 Switching Protocols`
+//! response is written, the server hands the connection to the callback as a
+//! [`WebSocket`].
+//!
+//! WebSocket 通过单个TCP连接提供全双工通信。处理器使用 [`WebSocketUpgrade::from_request`]
+//! 校验握手并返回 [`WebSocketUpgrade::on_upgrade`]；`101 Switching Protocols` 响应写出后，
+//! 服务器将连接作为 [`WebSocket`] 交给回调。
+//!
+//! # Equivalent to Spring Boot / 等价于 Spring Boot
+//!
+//! - WebSocketHandler
+//! - @EnableWebSocket
+//! - WebSocketSession
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_http::websocket::{Message, WebSocketConfig, WebSocketUpgrade};
+//!
+//! async fn ws_handler(req: Request) -> Result<Response, Error> {
+//!     let upgrade = WebSocketUpgrade::from_request(&req)?
+//!         .with_config(WebSocketConfig::new().with_compression(true));
+//!
+//!     Ok(upgrade.on_upgrade(|mut ws| async move {
+//!         // Send a message
+//!         let _ = ws.send(Message::text("Hello, WebSocket!")).await;
+//!
+//!         // Receive messages loop
+//!         while let Ok(Some(msg)) = ws.recv().await {
+//!             match msg {
+//!                 Message::Text(text) => {
+//!                     let _ = ws.send(Message::text(format!("Echo: {}", text))).await;
+//!                 }
+//!                 Message::Close(_) => break,
+//!                 _ => {}
+//!             }
+//!         }
+//!     }))
+//! }
+//! ```
+
+#![warn(missing_docs)]
+#![warn(unreachable_pub)]
+
+mod deflate;
+mod frame;
+
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::net::Shutdown;
+use std::sync::Arc;
+
+use base64::Engine as _;
+use bytes::BytesMut;
+use nexus_runtime::select::{SelectTwoOutput, select_two};
+use nexus_runtime::time::{Duration, Instant, timeout_at};
+use sha1::{Digest, Sha1};
+
+use crate::server::{ServerState, ServerStream};
+use crate::upgrade::{OnUpgrade, Upgraded};
+use crate::{Error, Method, Request, Response, StatusCode};
+
+use deflate::{Deflate, DeflateConfig};
+use frame::{Frame, MAX_CONTROL_PAYLOAD, OpCode};
+
+/// GUID appended to the client key to compute `Sec-WebSocket-Accept`
+/// 附加到客户端密钥以计算 `Sec-WebSocket-Accept` 的GUID
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Size of a single socket read
+/// 单次套接字读取的大小
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// How long to wait for data when no ping or pong is due
+/// 没有到期的ping或pong时等待数据的时长
+const IDLE_WAKEUP: Duration = Duration::from_secs(60);
+
+/// Compute `Sec-WebSocket-Accept` for a `Sec-WebSocket-Key` (RFC 6455 Section 4.2.2)
+/// 为 `Sec-WebSocket-Key` 计算 `Sec-WebSocket-Accept`（RFC 6455 第4.2.2节）
+pub fn accept_key(key: &str) -> String {
+    let mut sha1 = Sha1::new();
+    sha1.update(key.trim().as_bytes());
+    sha1.update(ACCEPT_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(sha1.finalize())
+}
+
+/// WebSocket message
+/// WebSocket消息
+///
+/// Represents a WebSocket message that can be sent or received.
+/// 表示可以发送或接收的WebSocket消息。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// Text message (UTF-8 encoded)
+    /// 文本消息（UTF-8编码）
+    Text(String),
+
+    /// Binary message
+    /// 二进制消息
+    Binary(Vec<u8>),
+
+    /// Ping message (with optional data)
+    /// Ping消息（带可选数据）
+    Ping(Vec<u8>),
+
+    /// Pong message (with optional data)
+    /// Pong消息（带可选数据）
+    Pong(Vec<u8>),
+
+    /// Close message (with optional status code and reason)
+    /// 关闭消息（带可选状态码和原因）
+    Close(Option<CloseFrame>),
+}
+
+impl Message {
+    /// Create a text message
+    /// 创建文本消息
+    pub fn text(text: impl Into<String>) -> Self {
+        Message::Text(text.into())
+    }
+
+    /// Create a binary message
+    /// 创建二进制消息
+    pub fn binary(data: impl Into<Vec<u8>>) -> Self {
+        Message::Binary(data.into())
+    }
+
+    /// Create a ping message
+    /// 创建ping消息
+    pub fn ping(data: impl Into<Vec<u8>>) -> Self {
+        Message::Ping(data.into())
+    }
+
+    /// Create a pong message
+    /// 创建pong消息
+    pub fn pong(data: impl Into<Vec<u8>>) -> Self {
+        Message::Pong(data.into())
+    }
+
+    /// Create a close message with status code
+    /// 创建带状态码的关闭消息
+    pub fn close(code: u16, reason: impl Into<String>) -> Self {
+        Message::Close(Some(CloseFrame {
+            code,
+            reason: reason.into(),
+        }))
+    }
+
+    /// Create a close message without status code
+    /// 创建不带状态码的关闭消息
+    pub fn close_empty() -> Self {
+        Message::Close(None)
+    }
+
+    /// Check if this is a close message
+    /// 检查是否为关闭消息
+    pub fn is_close(&self) -> bool {
+        matches!(self, Message::Close(_))
+    }
+
+    /// Get the message type as a string
+    /// 获取消息类型字符串
+    pub fn type_str(&self) -> &'static str {
+        match self {
+            Message::Text(_) => "text",
+            Message::Binary(_) => "binary",
+            Message::Ping(_) => "ping",
+            Message::Pong(_) => "pong",
+            Message::Close(_) => "close",
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Text(text) => write!(f, "Text({})", text),
+            Message::Binary(data) => write!(f, "Binary({} bytes)", data.len()),
+            Message::Ping(data) => write!(f, "Ping({} bytes)", data.len()),
+            Message::Pong(data) => write!(f, "Pong({} bytes)", data.len()),
+            Message::Close(None) => write!(f, "Close"),
+            Message::Close(Some(frame)) => write!(f, "Close({}: {})", frame.code, frame.reason),
+        }
+    }
+}
+
+/// Close frame information
+/// 关闭帧信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloseFrame {
+    /// Status code
+    /// 状态码
+    pub code: u16,
+
+    /// Close reason
+    /// 关闭原因
+    pub reason: String,
+}
+
+impl CloseFrame {
+    /// Create a new close frame
+    /// 创建新的关闭帧
+    pub fn new(code: u16, reason: impl Into<String>) -> Self {
+        Self {
+            code,
+            reason: reason.into(),
+        }
+    }
+
+    /// Normal closure (1000)
+    /// 正常关闭
+    pub fn normal() -> Self {
+        Self::new(1000, "Normal Closure")
+    }
+
+    /// Endpoint going away (1001)
+    /// 端点离去
+    pub fn going_away() -> Self {
+        Self::new(1001, "Endpoint Going Away")
+    }
+
+    /// Protocol error (1002)
+    /// 协议错误
+    pub fn protocol_error() -> Self {
+        Self::new(1002, "Protocol Error")
+    }
+
+    /// Unsupported data (1003)
+    /// 不支持的数据类型
+    pub fn unsupported_data() -> Self {
+        Self::new(1003, "Unsupported Data")
+    }
+
+    /// No status code (1005)
+    /// 无状态码
+    pub fn no_status() -> Self {
+        Self::new(1005, "No Status Received")
+    }
+
+    /// Abnormal closure (1006)
+    /// 异常关闭
+    pub fn abnormal() -> Self {
+        Self::new(1006, "Abnormal Closure")
+    }
+
+    /// Invalid payload data (1007)
+    /// 无效负载数据
+    pub fn invalid_payload() -> Self {
+        Self::new(1007, "Invalid Payload Data")
+    }
+
+    /// Policy violation (1008)
+    /// 策略违规
+    pub fn policy_violation() -> Self {
+        Self::new(1008, "Policy Violation")
+    }
+
+    /// Message too big (1009)
+    /// 消息过大
+    pub fn message_too_big() -> Self {
+        Self::new(1009, "Message Too Big")
+    }
+
+    /// Extension required (1010)
+    /// 需要扩展
+    pub fn extension_required() -> Self {
+        Self::new(1010, "Extension Required")
+    }
+
+    /// Internal error (1011)
+    /// 内部错误
+    pub fn internal_error() -> Self {
+        Self::new(1011, "Internal Error")
+    }
+
+    /// Service restart (1012)
+    /// 服务重启
+    pub fn service_restart() -> Self {
+        Self::new(1012, "Service Restart")
+    }
+
+    /// Try again later (1013)
+    /// 稍后重试
+    pub fn try_again_later() -> Self {
+        Self::new(1013, "Try Again Later")
+    }
+}
+
+/// WebSocket upgrade response
+/// WebSocket升级响应
+///
+/// Response sent to complete the WebSocket handshake.
+/// 发送以完成WebSocket握手的响应。
+#[derive(Debug, Clone)]
+pub struct WebSocketUpgrade {
+    /// Accepted protocols (in order of preference)
+    /// 接受的协议（按优先级顺序）
+    protocols: Vec<String>,
+
+    /// Maximum frame size (0 = no limit)
+    /// 最大帧大小（0 = 无限制）
+    max_frame_size: usize,
+
+    /// Whether to send ping frames periodically
+    /// 是否定期发送ping帧
+    ping_enabled: bool,
+
+    /// Ping interval in seconds
+    /// Ping间隔（秒）
+    ping_interval: u64,
+
+    /// Seconds to wait for a pong (0 = no limit)
+    /// 等待pong的秒数（0 = 无限制）
+    ping_timeout: u64,
+
+    /// Maximum message size (0 = no limit)
+    /// 最大消息大小（0 = 无限制）
+    max_message_size: usize,
+
+    /// Whether to accept permessage-deflate
+    /// 是否接受 permessage-deflate
+    compression: bool,
+
+    /// Client `Sec-WebSocket-Key`
+    /// 客户端的 `Sec-WebSocket-Key`
+    key: Option<String>,
+
+    /// Protocols offered by the client
+    /// 客户端提供的协议
+    client_protocols: Vec<String>,
+
+    /// Extensions offered by the client
+    /// 客户端提供的扩展
+    client_extensions: Option<String>,
+}
+
+impl WebSocketUpgrade {
+    /// Create a new WebSocket upgrade
+    /// 创建新的WebSocket升级
+    pub fn new() -> Self {
+        Self {
+            protocols: Vec::new(),
+            max_frame_size: 64 * 1024 * 1024, // 64 MiB
+            ping_enabled: false,
+            ping_interval: 30,
+            ping_timeout: 60,
+            max_message_size: 64 * 1024 * 1024, // 64 MiB
+            compression: false,
+            key: None,
+            client_protocols: Vec::new(),
+            client_extensions: None,
+        }
+    }
+
+    /// Validate the handshake of an upgrade request (RFC 6455 Section 4.2.1)
+    /// 校验升级请求的握手（RFC 6455 第4.2.1节）
+    pub fn from_request(request: &Request) -> Result<Self, WebSocketError> {
+        if request.method() != Method::GET {
+            return Err(WebSocketError::Other("handshake must use GET".to_string()));
+        }
+        if !has_token(request, "upgrade", "websocket") {
+            return Err(WebSocketError::MissingUpgradeHeader);
+        }
+        if !has_token(request, "connection", "upgrade") {
+            return Err(WebSocketError::MissingConnectionHeader);
+        }
+        if request.header("sec-websocket-version").map(str::trim) != Some("13") {
+            return Err(WebSocketError::InvalidVersion);
+        }
+        let key = request
+            .header("sec-websocket-key")
+            .map(str::trim)
+            .filter(|key| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(key)
+                    .is_ok_and(|nonce| nonce.len() == 16)
+            })
+            .ok_or(WebSocketError::MissingKey)?;
+
+        let client_protocols = header_values(request, "sec-websocket-protocol")
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .collect();
+        let client_extensions = header_values(request, "sec-websocket-extensions")
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Ok(Self {
+            key: Some(key.to_string()),
+            client_protocols,
+            client_extensions: Some(client_extensions).filter(|e| !e.is_empty()),
+            ..Self::new()
+        })
+    }
+
+    /// Set accepted protocols
+    /// 设置接受的协议
+    pub fn with_protocols(mut self, protocols: Vec<String>) -> Self {
+        self.protocols = protocols;
+        self
+    }
+
+    /// Set maximum frame size
+    /// 设置最大帧大小
+    pub fn with_max_frame_size(mut self, size: usize) -> Self {
+        self.max_frame_size = size;
+        self
+    }
+
+    /// Enable periodic ping frames
+    /// 启用定期ping帧
+    pub fn with_ping(mut self, interval_secs: u64) -> Self {
+        self.ping_enabled = true;
+        self.ping_interval = interval_secs;
+        self
+    }
+
+    /// Set maximum message size
+    /// 设置最大消息大小
+    pub fn with_max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// Accept permessage-deflate when the client offers it
+    /// 当客户端提供时接受 permessage-deflate
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Apply a [`WebSocketConfig`]
+    /// 应用 [`WebSocketConfig`]
+    pub fn with_config(mut self, config: WebSocketConfig) -> Self {
+        let WebSocketConfig {
+            max_frame_size,
+            max_message_size,
+            ping_interval,
+            ping_timeout,
+            compression_enabled,
+        } = config;
+        self.max_frame_size = max_frame_size;
+        self.max_message_size = max_message_size;
+        self.ping_enabled = ping_interval > 0;
+        self.ping_interval = ping_interval;
+        self.ping_timeout = ping_timeout;
+        self.compression = compression_enabled;
+        self
+    }
+
+    /// Configuration the upgraded connection runs with
+    /// 升级后连接使用的配置
+    fn config(&self) -> WebSocketConfig {
+        WebSocketConfig {
+            max_frame_size: self.max_frame_size,
+            max_message_size: self.max_message_size,
+            ping_interval: if self.ping_enabled {
+                self.ping_interval
+            } else {
+                0
+            },
+            ping_timeout: self.ping_timeout,
+            compression_enabled: self.compression,
+        }
+    }
+
+    /// Build the `101` response with the negotiated protocol and extension
+    /// 构建带有协商协议和扩展的 `101` 响应
+    fn handshake(&self) -> (Response, Option<String>, Option<DeflateConfig>) {
+        let protocol = self
+            .protocols
+            .iter()
+            .find(|p| self.client_protocols.contains(p))
+            .cloned();
+        let deflate = self
+            .client_extensions
+            .as_deref()
+            .filter(|_| self.compression)
+            .and_then(DeflateConfig::negotiate);
+
+        let mut response = Response::new(StatusCode::SWITCHING_PROTOCOLS);
+        response.insert_header("connection", "Upgrade");
+        response.insert_header("upgrade", "websocket");
+        if let Some(key) = &self.key {
+            response.insert_header("sec-websocket-accept", accept_key(key));
+        }
+        if let Some(protocol) = &protocol {
+            response.insert_header("sec-websocket-protocol", protocol.clone());
+        }
+        if let Some(deflate) = &deflate {
+            response.insert_header("sec-websocket-extensions", deflate.response_header());
+        }
+        (response, protocol, deflate)
+    }
+
+    /// Generate the upgrade response
+    /// 生成升级响应
+    ///
+    /// Without [`on_upgrade`](Self::on_upgrade) the server closes the connection
+    /// after sending it.
+    /// 如果没有 [`on_upgrade`](Self::on_upgrade)，服务器在发送后关闭连接。
+    pub fn into_response(self) -> Response {
+        self.handshake().0
+    }
+
+    /// Generate the upgrade response, running `callback` on the upgraded connection
+    /// 生成升级响应，并在升级后的连接上运行 `callback`
+    pub fn on_upgrade<F, Fut>(self, callback: F) -> Response
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (mut response, protocol, deflate) = self.handshake();
+        let config = self.config();
+        response.set_upgrade(OnUpgrade::new(move |upgraded| {
+            Box::pin(callback(WebSocket::new(upgraded, config, protocol, deflate)))
+        }));
+        response
+    }
+}
+
+impl Default for WebSocketUpgrade {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<WebSocketUpgrade> for Response {
+    fn from(upgrade: WebSocketUpgrade) -> Self {
+        upgrade.into_response()
+    }
+}
+
+/// Iterate over the values of a possibly repeated header
+/// 遍历可能重复的头的值
+fn header_values<'a>(request: &'a Request, name: &str) -> impl Iterator<Item = &'a str> {
+    request
+        .headers()
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+}
+
+/// Check whether a comma-separated header contains `token`
+/// 检查逗号分隔的头是否包含 `token`
+fn has_token(request: &Request, name: &str, token: &str) -> bool {
+    header_values(request, name)
+        .flat_map(|value| value.split(','))
+        .any(|t| t.trim().eq_ignore_ascii_case(token))
+}
+
+/// WebSocket connection
+/// WebSocket连接
+///
+/// Represents an active WebSocket connection for sending/receiving messages.
+/// Incoming pings are answered automatically, fragmented messages are reassembled
+/// and, with a ping interval configured, the peer is pinged while [`recv`](Self::recv)
+/// waits and dropped if no pong arrives within the ping timeout.
+///
+/// 表示用于发送/接收消息的活动WebSocket连接。收到的ping会被自动应答，分片消息会被重组；
+/// 配置了ping间隔时，会在 [`recv`](Self::recv) 等待期间ping对端，
+/// 如果在ping超时内没有收到pong则断开连接。
+pub struct WebSocket {
+    /// The upgraded socket / 升级后的套接字
+    stream: ServerStream,
+
+    /// Received bytes not yet decoded / 已接收但尚未解码的字节
+    read_buf: BytesMut,
+
+    /// Connection limits / 连接限制
+    config: WebSocketConfig,
+
+    /// Negotiated subprotocol / 协商的子协议
+    protocol: Option<String>,
+
+    /// permessage-deflate state, when negotiated / 协商后的 permessage-deflate 状态
+    deflate: Option<Deflate>,
+
+    /// Server state, for shutdown notifications / 服务器状态，用于关闭通知
+    state: Arc<ServerState>,
+
+    /// Message being reassembled: opcode, compressed flag and payload so far
+    /// 正在重组的消息：操作码、压缩标志和目前的负载
+    partial: Option<(OpCode, bool, Vec<u8>)>,
+
+    /// When the next ping is due / 下一个ping的到期时间
+    next_ping: Option<Instant>,
+
+    /// When the peer must have answered our ping or close / 对端必须应答我们的ping或关闭的时间
+    answer_deadline: Option<Instant>,
+
+    /// Whether we sent a close frame / 是否已发送关闭帧
+    close_sent: bool,
+
+    /// Whether the peer sent a close frame / 对端是否已发送关闭帧
+    close_received: bool,
+
+    /// Whether the connection is finished / 连接是否已结束
+    closed: bool,
+
+    /// Close frame received (if any)
+    /// 收到的关闭帧（如果有）
+    close_frame: Option<CloseFrame>,
+
+    /// Whether this is the client end, which masks what it sends
+    /// 是否为客户端一端，客户端会对发送的帧加掩码
+    client: bool,
+}
+
+impl WebSocket {
+    /// Wrap a connection handed over by the server
+    /// 包装服务器移交的连接
+    fn new(
+        upgraded: Upgraded,
+        config: WebSocketConfig,
+        protocol: Option<String>,
+        deflate: Option<DeflateConfig>,
+    ) -> Self {
+        let next_ping = (config.ping_interval > 0)
+            .then(|| Instant::now() + Duration::from_secs(config.ping_interval));
+        Self {
+            stream: upgraded.stream,
+            read_buf: BytesMut::from(upgraded.buffered.as_ref()),
+            config,
+            protocol,
+            deflate: deflate.map(Deflate::new),
+            state: upgraded.state,
+            partial: None,
+            next_ping,
+            answer_deadline: None,
+            close_sent: false,
+            close_received: false,
+            closed: false,
+            close_frame: None,
+            client: false,
+        }
+    }
+
+    /// Wrap the client end of an in-memory connection
+    /// 包装内存连接的客户端一端
+    ///
+    /// The client never pings; it only answers the server's pings.
+    /// 客户端从不发送ping，只应答服务器的ping。
+    pub(crate) fn client(upgraded: Upgraded, protocol: Option<String>) -> Self {
+        let config = WebSocketConfig {
+            ping_interval: 0,
+            ..WebSocketConfig::default()
+        };
+        Self {
+            client: true,
+            ..Self::new(upgraded, config, protocol, None)
+        }
+    }
+
+    /// Check if the connection is still open
+    /// 检查连接是否仍然打开
+    pub fn is_open(&self) -> bool {
+        !self.close_sent && !self.close_received && !self.closed
+    }
+
+    /// Get the close frame (if any)
+    /// 获取关闭帧（如果有）
+    ///
+    /// A connection dropped without a closing handshake reports `1006`.
+    /// 未经关闭握手而断开的连接报告 `1006`。
+    pub fn close_frame(&self) -> Option<&CloseFrame> {
+        self.close_frame.as_ref()
+    }
+
+    /// Get the negotiated subprotocol (if any)
+    /// 获取协商的子协议（如果有）
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+
+    /// Get the connection configuration
+    /// 获取连接配置
+    pub fn config(&self) -> &WebSocketConfig {
+        &self.config
+    }
+
+    /// Send a message
+    /// 发送消息
+    ///
+    /// Text and binary messages are compressed when permessage-deflate was
+    /// negotiated and split into frames of at most `max_frame_size` bytes.
+    /// 协商了 permessage-deflate 时文本和二进制消息会被压缩，并拆分为不超过
+    /// `max_frame_size` 字节的帧。
+    pub async fn send(&mut self, message: Message) -> Result<(), WebSocketError> {
+        if self.close_sent || self.closed {
+            return Err(WebSocketError::ConnectionClosed);
+        }
+        match message {
+            Message::Text(text) => self.send_data(OpCode::Text, text.into_bytes()).await,
+            Message::Binary(data) => self.send_data(OpCode::Binary, data).await,
+            Message::Ping(data) => self.send_control(OpCode::Ping, data).await,
+            Message::Pong(data) => self.send_control(OpCode::Pong, data).await,
+            Message::Close(frame) => self.send_close(frame).await,
+        }
+    }
+
+    /// Receive the next message, `None` once the connection is closed
+    /// 接收下一条消息，连接关闭后返回 `None`
+    ///
+    /// A close frame from the peer is answered and returned as [`Message::Close`].
+    /// Protocol violations close the connection with the matching status code.
+    /// 对端的关闭帧会被应答并作为 [`Message::Close`] 返回。协议违规会以相应状态码关闭连接。
+    pub async fn recv(&mut self) -> Result<Option<Message>, WebSocketError> {
+        loop {
+            if self.closed {
+                return Ok(None);
+            }
+            let decoded = Frame::decode(&mut self.read_buf, !self.client, self.config.max_frame_size);
+            let handled = match decoded {
+                Ok(Some(frame)) => self.on_frame(frame).await,
+                Ok(None) => {
+                    self.fill().await?;
+                    continue;
+                },
+                Err(e) => Err(e),
+            };
+            match handled {
+                Ok(Some(message)) => return Ok(Some(message)),
+                Ok(None) => {},
+                Err(e) => return Err(self.fail(e).await),
+            }
+        }
+    }
+
+    /// Close the connection and wait for the peer to confirm
+    /// 关闭连接并等待对端确认
+    pub async fn close(&mut self, frame: Option<CloseFrame>) -> Result<(), WebSocketError> {
+        if !self.close_sent && !self.closed {
+            self.send_close(frame).await?;
+        }
+        while let Some(message) = self.recv().await? {
+            if message.is_close() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read more bytes, running the ping timer and watching for server shutdown
+    /// 读取更多字节，同时运行ping计时器并关注服务器关闭
+    async fn fill(&mut self) -> Result<(), WebSocketError> {
+        let deadline = [self.next_ping, self.answer_deadline]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or_else(|| Instant::now() + IDLE_WAKEUP);
+        let going_away = !self.close_sent;
+        let mut buf = [0u8; READ_CHUNK_SIZE];
+
+        let state = self.state.clone();
+        let read = timeout_at(deadline, self.stream.read(&mut buf));
+        let interrupted =
+            state.wait_until(move |s| s.is_force_closed() || (going_away && s.is_shutting_down()));
+
+        match select_two(read, interrupted).await {
+            SelectTwoOutput::First(Ok(Ok(0))) => {
+                self.finish();
+                self.close_frame.get_or_insert_with(CloseFrame::abnormal);
+                Ok(())
+            },
+            SelectTwoOutput::First(Ok(Ok(n))) => {
+                self.read_buf.extend_from_slice(&buf[..n]);
+                Ok(())
+            },
+            SelectTwoOutput::First(Ok(Err(e))) => {
+                self.finish();
+                Err(WebSocketError::Io(e.to_string()))
+            },
+            SelectTwoOutput::First(Err(_elapsed)) => self.on_timer().await,
+            SelectTwoOutput::Second(()) if state.is_force_closed() => {
+                self.finish();
+                self.close_frame.get_or_insert_with(CloseFrame::abnormal);
+                Ok(())
+            },
+            SelectTwoOutput::Second(()) => self.send_close(Some(CloseFrame::going_away())).await,
+        }
+    }
+
+    /// Send a due ping, or give up on a peer that did not answer in time
+    /// 发送到期的ping，或放弃未及时应答的对端
+    async fn on_timer(&mut self) -> Result<(), WebSocketError> {
+        let now = Instant::now();
+        if self.answer_deadline.is_some_and(|deadline| now >= deadline) {
+            self.finish();
+            self.close_frame.get_or_insert_with(CloseFrame::abnormal);
+            return Err(WebSocketError::Timeout);
+        }
+        if self.next_ping.is_some_and(|at| now >= at) {
+            self.next_ping = Some(now + Duration::from_secs(self.config.ping_interval));
+            if self.answer_deadline.is_none() {
+                self.answer_deadline = self.timeout_from(now);
+            }
+            self.write_frame(&Frame::new(true, false, OpCode::Ping, Vec::new()))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Deadline for an answer to something sent at `now`
+    /// 对 `now` 发送的内容应答的截止时间
+    fn timeout_from(&self, now: Instant) -> Option<Instant> {
+        (self.config.ping_timeout > 0).then(|| now + Duration::from_secs(self.config.ping_timeout))
+    }
+
+    /// Handle one decoded frame, returning a message once one is complete
+    /// 处理一个解码后的帧，消息完整时返回
+    async fn on_frame(&mut self, frame: Frame) -> Result<Option<Message>, WebSocketError> {
+        let Frame {
+            fin,
+            rsv1,
+            opcode,
+            payload,
+        } = frame;
+        let may_compress = matches!(opcode, OpCode::Text | OpCode::Binary);
+        if rsv1 && (self.deflate.is_none() || !may_compress) {
+            return Err(WebSocketError::Protocol("unexpected RSV1 bit".to_string()));
+        }
+
+        match opcode {
+            OpCode::Ping => {
+                if !self.close_sent {
+                    let pong = Frame::new(true, false, OpCode::Pong, payload.clone());
+                    self.write_frame(&pong).await?;
+                }
+                Ok(Some(Message::Ping(payload)))
+            },
+            OpCode::Pong => {
+                if !self.close_sent {
+                    self.answer_deadline = None;
+                }
+                Ok(Some(Message::Pong(payload)))
+            },
+            OpCode::Close => {
+                let close = parse_close(&payload)?;
+                self.close_received = true;
+                if !self.close_sent {
+                    self.send_close(close.clone()).await?;
+                }
+                self.finish();
+                self.close_frame.clone_from(&close);
+                Ok(Some(Message::Close(close)))
+            },
+            OpCode::Text | OpCode::Binary => {
+                if self.partial.is_some() {
+                    return Err(WebSocketError::Protocol(
+                        "expected a continuation frame".to_string(),
+                    ));
+                }
+                self.check_message_size(payload.len())?;
+                if fin {
+                    return self.complete(opcode, rsv1, payload).map(Some);
+                }
+                self.partial = Some((opcode, rsv1, payload));
+                Ok(None)
+            },
+            OpCode::Continuation => {
+                let Some((first, compressed, mut data)) = self.partial.take() else {
+                    return Err(WebSocketError::Protocol(
+                        "unexpected continuation frame".to_string(),
+                    ));
+                };
+                self.check_message_size(data.len() + payload.len())?;
+                data.extend_from_slice(&payload);
+                if fin {
+                    return self.complete(first, compressed, data).map(Some);
+                }
+                self.partial = Some((first, compressed, data));
+                Ok(None)
+            },
+        }
+    }
+
+    /// Turn a reassembled payload into a message
+    /// 将重组后的负载转换为消息
+    fn complete(
+        &mut self,
+        opcode: OpCode,
+        compressed: bool,
+        data: Vec<u8>,
+    ) -> Result<Message, WebSocketError> {
+        let data = match &mut self.deflate {
+            Some(deflate) if compressed => {
+                deflate.decompress(&data, self.config.max_message_size)?
+            },
+            _ => data,
+        };
+        if opcode == OpCode::Text {
+            String::from_utf8(data)
+                .map(Message::Text)
+                .map_err(|_| WebSocketError::InvalidPayload("text is not UTF-8".to_string()))
+        } else {
+            Ok(Message::Binary(data))
+        }
+    }
+
+    fn check_message_size(&self, size: usize) -> Result<(), WebSocketError> {
+        let max = self.config.max_message_size;
+        if max > 0 && size > max {
+            return Err(WebSocketError::MessageTooLarge { size, max });
+        }
+        Ok(())
+    }
+
+    /// Send a text or binary message
+    /// 发送文本或二进制消息
+    async fn send_data(&mut self, opcode: OpCode, data: Vec<u8>) -> Result<(), WebSocketError> {
+        let (payload, compressed) = match &mut self.deflate {
+            Some(deflate) => (deflate.compress(&data)?, true),
+            None => (data, false),
+        };
+
+        let max = self.config.max_frame_size;
+        let mut out = Vec::with_capacity(payload.len() + 16);
+        if max == 0 || payload.len() <= max {
+            Frame::new(true, compressed, opcode, payload).encode(&mut out, self.mask());
+        } else {
+            let count = payload.len().div_ceil(max);
+            for (i, chunk) in payload.chunks(max).enumerate() {
+                let opcode = if i == 0 { opcode } else { OpCode::Continuation };
+                Frame::new(i + 1 == count, compressed && i == 0, opcode, chunk.to_vec())
+                    .encode(&mut out, self.mask());
+            }
+        }
+        self.write(&out).await
+    }
+
+    /// Send a ping or pong
+    /// 发送ping或pong
+    async fn send_control(&mut self, opcode: OpCode, data: Vec<u8>) -> Result<(), WebSocketError> {
+        if data.len() > MAX_CONTROL_PAYLOAD {
+            return Err(WebSocketError::Protocol(format!(
+                "control payload exceeds {} bytes",
+                MAX_CONTROL_PAYLOAD
+            )));
+        }
+        self.write_frame(&Frame::new(true, false, opcode, data))
+            .await
+    }
+
+    /// Send a close frame, finishing the connection if the peer already closed
+    /// 发送关闭帧，如果对端已关闭则结束连接
+    async fn send_close(&mut self, frame: Option<CloseFrame>) -> Result<(), WebSocketError> {
+        let mut payload = Vec::new();
+        if let Some(frame) = &frame {
+            payload.extend_from_slice(&frame.code.to_be_bytes());
+            payload.extend_from_slice(frame.reason.as_bytes());
+        }
+        if payload.len() > MAX_CONTROL_PAYLOAD {
+            return Err(WebSocketError::Protocol("close reason too long".to_string()));
+        }
+
+        self.close_sent = true;
+        self.next_ping = None;
+        self.answer_deadline = self.timeout_from(Instant::now());
+        self.write_frame(&Frame::new(true, false, OpCode::Close, payload))
+            .await?;
+        if self.close_received {
+            self.finish();
+        }
+        Ok(())
+    }
+
+    /// Close the connection after a protocol error, with the matching status code
+    /// 协议错误后以相应状态码关闭连接
+    async fn fail(&mut self, error: WebSocketError) -> WebSocketError {
+        let frame = match &error {
+            WebSocketError::Protocol(_) => Some(CloseFrame::protocol_error()),
+            WebSocketError::FrameTooLarge { .. } | WebSocketError::MessageTooLarge { .. } => {
+                Some(CloseFrame::message_too_big())
+            },
+            WebSocketError::InvalidPayload(_) => Some(CloseFrame::invalid_payload()),
+            _ => None,
+        };
+        if let Some(frame) = frame
+            && !self.close_sent
+            && !self.closed
+        {
+            let _ = self.send_close(Some(frame)).await;
+        }
+        self.finish();
+        error
+    }
+
+    async fn write_frame(&mut self, frame: &Frame) -> Result<(), WebSocketError> {
+        let mut out = Vec::with_capacity(frame.payload.len() + 16);
+        frame.encode(&mut out, self.mask());
+        self.write(&out).await
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), WebSocketError> {
+        if let Err(e) = self.stream.write_all(bytes).await {
+            self.finish();
+            return Err(WebSocketError::Io(e.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Fresh masking key for a frame sent by the client end
+    /// 客户端一端发送帧时使用的新掩码密钥
+    fn mask(&self) -> Option<[u8; 4]> {
+        self.client.then(|| {
+            let random = RandomState::new().build_hasher().finish();
+            (random as u32).to_ne_bytes()
+        })
+    }
+
+    /// Mark the connection finished and close the socket
+    /// 标记连接结束并关闭套接字
+    fn finish(&mut self) {
+        if !self.closed {
+            self.closed = true;
+            let _ = self.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl fmt::Debug for WebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocket")
+            .field("config", &self.config)
+            .field("protocol", &self.protocol)
+            .field("compression", &self.deflate.is_some())
+            .field("open", &self.is_open())
+            .field("close_frame", &self.close_frame)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parse the payload of a close frame (RFC 6455 Section 5.5.1)
+/// 解析关闭帧的负载（RFC 6455 第5.5.1节）
+fn parse_close(payload: &[u8]) -> Result<Option<CloseFrame>, WebSocketError> {
+    let (code, reason) = match payload {
+        [] => return Ok(None),
+        [hi, lo, reason @ ..] => (u16::from_be_bytes([*hi, *lo]), reason),
+        [_] => return Err(WebSocketError::Protocol("truncated close code".to_string())),
+    };
+    if !matches!(code, 1000..=1003 | 1007..=1014 | 3000..=4999) {
+        return Err(WebSocketError::Protocol(format!("invalid close code {}", code)));
+    }
+    let reason = std::str::from_utf8(reason)
+        .map_err(|_| WebSocketError::InvalidPayload("close reason is not UTF-8".to_string()))?;
+    Ok(Some(CloseFrame::new(code, reason)))
+}
+
+/// WebSocket error
+/// WebSocket错误
+#[derive(Debug, Clone)]
+pub enum WebSocketError {
+    /// Missing Upgrade header
+    /// 缺少Upgrade头
+    MissingUpgradeHeader,
+
+    /// Missing Connection header
+    /// 缺少Connection头
+    MissingConnectionHeader,
+
+    /// Invalid WebSocket version
+    /// 无效的WebSocket版本
+    InvalidVersion,
+
+    /// Missing WebSocket key
+    /// 缺少WebSocket密钥
+    MissingKey,
+
+    /// Protocol not supported
+    /// 协议不支持
+    ProtocolNotSupported(String),
+
+    /// The peer violated the framing protocol (closes with 1002)
+    /// 对端违反了分帧协议（以1002关闭）
+    Protocol(String),
+
+    /// A frame exceeded `max_frame_size` (closes with 1009)
+    /// 帧超过了 `max_frame_size`（以1009关闭）
+    FrameTooLarge {
+        /// Frame payload size / 帧负载大小
+        size: usize,
+        /// Configured limit / 配置的限制
+        max: usize,
+    },
+
+    /// A message exceeded `max_message_size` (closes with 1009)
+    /// 消息超过了 `max_message_size`（以1009关闭）
+    MessageTooLarge {
+        /// Message size so far / 目前的消息大小
+        size: usize,
+        /// Configured limit / 配置的限制
+        max: usize,
+    },
+
+    /// Invalid UTF-8 text or compressed data (closes with 1007)
+    /// 无效的UTF-8文本或压缩数据（以1007关闭）
+    InvalidPayload(String),
+
+    /// The peer did not answer a ping or close in time
+    /// 对端未及时应答ping或关闭
+    Timeout,
+
+    /// The connection is closed or closing
+    /// 连接已关闭或正在关闭
+    ConnectionClosed,
+
+    /// I/O error on the socket
+    /// 套接字I/O错误
+    Io(String),
+
+    /// Other error
+    /// 其他错误
+    Other(String),
+}
+
+impl fmt::Display for WebSocketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebSocketError::MissingUpgradeHeader => write!(f, "Missing Upgrade header"),
+            WebSocketError::MissingConnectionHeader => write!(f, "Missing Connection header"),
+            WebSocketError::InvalidVersion => write!(f, "Invalid WebSocket version"),
+            WebSocketError::MissingKey => write!(f, "Missing WebSocket key"),
+            WebSocketError::ProtocolNotSupported(proto) => {
+                write!(f, "Protocol not supported: {}", proto)
+            },
+            WebSocketError::Protocol(msg) => write!(f, "WebSocket protocol error: {}", msg),
+            WebSocketError::FrameTooLarge { size, max } => {
+                write!(f, "Frame of {} bytes exceeds the limit of {}", size, max)
+            },
+            WebSocketError::MessageTooLarge { size, max } => {
+                write!(f, "Message of {} bytes exceeds the limit of {}", size, max)
+            },
+            WebSocketError::InvalidPayload(msg) => write!(f, "Invalid payload: {}", msg),
+            WebSocketError::Timeout => write!(f, "WebSocket peer did not respond in time"),
+            WebSocketError::ConnectionClosed => write!(f, "WebSocket connection closed"),
+            WebSocketError::Io(msg) => write!(f, "WebSocket I/O error: {}", msg),
+            WebSocketError::Other(msg) => write!(f, "WebSocket error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for WebSocketError {}
+
+impl From<WebSocketError> for Error {
+    fn from(err: WebSocketError) -> Self {
+        Error::InvalidRequest(err.to_string())
+    }
+}
+
+/// WebSocket configuration
+/// WebSocket配置
+///
+/// Configuration for WebSocket connections.
+/// WebSocket连接的配置。
+#[derive(Debug, Clone)]
+pub struct WebSocketConfig {
+    /// Maximum frame size in bytes
+    /// 最大帧大小（字节）
+    pub max_frame_size: usize,
+
+    /// Maximum message size in bytes
+    /// 最大消息大小（字节）
+    pub max_message_size: usize,
+
+    /// Ping interval in seconds (0 = disabled)
+    /// Ping间隔（秒，0 = 禁用）
+    pub ping_interval: u64,
+
+    /// Ping timeout in seconds
+    /// Ping超时（秒）
+    pub ping_timeout: u64,
+
+    /// Enable message compression
+    /// 启用消息压缩
+    pub compression_enabled: bool,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            max_frame_size: 64 * 1024 * 1024,   // 64 MiB
+            max_message_size: 64 * 1024 * 1024, // 64 MiB
+            ping_interval: 30,
+            ping_timeout: 60,
+            compression_enabled: false,
+        }
+    }
+}
+
+impl WebSocketConfig {
+    /// Create a new WebSocket config
+    /// 创建新的WebSocket配置
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set maximum frame size
+    /// 设置最大帧大小
+    pub fn max_frame_size(mut self, size: usize) -> Self {
+        self.max_frame_size = size;
+        self
+    }
+
+    /// Set maximum message size
+    /// 设置最大消息大小
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// Set ping interval
+    /// 设置ping间隔
+    pub fn ping_interval(mut self, interval: u64) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Enable compression
+    /// 启用压缩
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression_enabled = enabled;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_text() {
+        let msg = Message::text("Hello");
+        assert_eq!(msg.type_str(), "text");
+        assert!(!msg.is_close());
+    }
+
+    #[test]
+    fn test_message_binary() {
+        let msg = Message::binary(vec![1, 2, 3]);
+        assert_eq!(msg.type_str(), "binary");
+        assert!(!msg.is_close());
+    }
+
+    #[test]
+    fn test_message_ping() {
+        let msg = Message::ping(vec![1, 2]);
+        assert_eq!(msg.type_str(), "ping");
+        assert!(!msg.is_close());
+    }
+
+    #[test]
+    fn test_message_pong() {
+        let msg = Message::pong(vec![1, 2]);
+        assert_eq!(msg.type_str(), "pong");
+        assert!(!msg.is_close());
+    }
+
+    #[test]
+    fn test_message_close() {
+        let msg = Message::close(1000, "Normal");
+        assert!(msg.is_close());
+        assert!(matches!(msg, Message::Close(Some(_))));
+    }
+
+    #[test]
+    fn test_close_frame_normal() {
+        let frame = CloseFrame::normal();
+        assert_eq!(frame.code, 1000);
+        assert_eq!(frame.reason, "Normal Closure");
+    }
+
+    #[test]
+    fn test_close_frame_going_away() {
+        let frame = CloseFrame::going_away();
+        assert_eq!(frame.code, 1001);
+        assert_eq!(frame.reason, "Endpoint Going Away");
+    }
+
+    #[test]
+    fn test_websocket_upgrade_creation() {
+        let upgrade = WebSocketUpgrade::new();
+        assert!(upgrade.protocols.is_empty());
+        assert_eq!(upgrade.max_frame_size, 64 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_websocket_upgrade_builder() {
+        let upgrade = WebSocketUpgrade::new()
+            .with_protocols(vec!["chat".into(), "superchat".into()])
+            .with_max_frame_size(1024)
+            .with_ping(60);
+
+        assert_eq!(upgrade.protocols.len(), 2);
+        assert_eq!(upgrade.max_frame_size, 1024);
+        assert!(upgrade.ping_enabled);
+        assert_eq!(upgrade.ping_interval, 60);
+    }
+
+    #[test]
+    fn test_websocket_config_default() {
+        let config = WebSocketConfig::default();
+        assert_eq!(config.max_frame_size, 64 * 1024 * 1024);
+        assert_eq!(config.ping_interval, 30);
+        assert!(!config.compression_enabled);
+    }
+
+    #[test]
+    fn test_websocket_config_builder() {
+        let config = WebSocketConfig::new()
+            .max_frame_size(1024)
+            .max_message_size(2048)
+            .ping_interval(60)
+            .with_compression(true);
+
+        assert_eq!(config.max_frame_size, 1024);
+        assert_eq!(config.max_message_size, 2048);
+        assert_eq!(config.ping_interval, 60);
+        assert!(config.compression_enabled);
+    }
+
+    #[test]
+    fn test_websocket_error_display() {
+        let err = WebSocketError::MissingUpgradeHeader;
+        assert_eq!(err.to_string(), "Missing Upgrade header");
+
+        let err = WebSocketError::ProtocolNotSupported("chat-v2".to_string());
+        assert!(err.to_string().contains("chat-v2"));
+    }
+
+    fn upgrade_request(extra: &[(&str, &str)]) -> Request {
+        let mut builder = Request::builder()
+            .uri("/chat")
+            .header("host", "server.example.com")
+            .header("upgrade", "websocket")
+            .header("connection", "keep-alive, Upgrade")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .header("sec-websocket-version", "13");
+        for (name, value) in extra {
+            builder = builder.header(name, value);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // RFC 6455 Section 1.3
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn test_from_request_validates_handshake() {
+        assert!(WebSocketUpgrade::from_request(&upgrade_request(&[])).is_ok());
+
+        let request = Request::builder().uri("/chat").build().unwrap();
+        assert!(matches!(
+            WebSocketUpgrade::from_request(&request),
+            Err(WebSocketError::MissingUpgradeHeader)
+        ));
+
+        let request = upgrade_request(&[]);
+        let mut inner = request.into_inner();
+        inner
+            .headers_mut()
+            .insert("sec-websocket-version", "8".parse().unwrap());
+        assert!(matches!(
+            WebSocketUpgrade::from_request(&Request::new(inner)),
+            Err(WebSocketError::InvalidVersion)
+        ));
+
+        let request = upgrade_request(&[]);
+        let mut inner = request.into_inner();
+        inner
+            .headers_mut()
+            .insert("sec-websocket-key", "c2hvcnQ=".parse().unwrap());
+        assert!(matches!(
+            WebSocketUpgrade::from_request(&Request::new(inner)),
+            Err(WebSocketError::MissingKey)
+        ));
+    }
+
+    #[test]
+    fn test_upgrade_response_negotiates() {
+        let request = upgrade_request(&[
+            ("sec-websocket-protocol", "chat, superchat"),
+            ("sec-websocket-extensions", "permessage-deflate; client_max_window_bits"),
+        ]);
+        let response = WebSocketUpgrade::from_request(&request)
+            .unwrap()
+            .with_protocols(vec!["superchat".into(), "chat".into()])
+            .with_compression(true)
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(response.header("sec-websocket-accept"), Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+        assert_eq!(response.header("sec-websocket-protocol"), Some("superchat"));
+        assert_eq!(response.header("sec-websocket-extensions"), Some("permessage-deflate"));
+
+        // Compression stays off unless enabled
+        let response = WebSocketUpgrade::from_request(&request)
+            .unwrap()
+            .into_response();
+        assert_eq!(response.header("sec-websocket-extensions"), None);
+        assert_eq!(response.header("sec-websocket-protocol"), None);
+    }
+
+    #[test]
+    fn test_parse_close() {
+        assert_eq!(parse_close(&[]).unwrap(), None);
+        assert_eq!(
+            parse_close(&[0x03, 0xE8, b'o', b'k']).unwrap(),
+            Some(CloseFrame::new(1000, "ok"))
+        );
+        assert!(parse_close(&[0x03]).is_err());
+        // 1005 must never appear on the wire
+        assert!(parse_close(&[0x03, 0xED]).is_err());
+        assert!(matches!(
+            parse_close(&[0x03, 0xE8, 0xFF]),
+            Err(WebSocketError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_websocket_upgrade_into_response() {
+        let upgrade = WebSocketUpgrade::new();
+        let response = upgrade.into_response();
+
+        // Verify it compiles
+        let _ = response;
+    }
+}
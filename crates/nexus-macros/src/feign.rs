@@ -0,0 +1,520 @@
+//! Feign client macro implementation
+//! Feign客户端宏实现
+//!
+//! This module provides the #[feign_client] procedural macro, which turns a trait
+//! into a declarative HTTP client backed by `nexus_cloud::http_client::FeignClient`.
+//! 本模块提供#[feign_client]过程宏，将trait转换为由
+//! `nexus_cloud::http_client::FeignClient` 支持的声明式HTTP客户端。
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    Attribute, FnArg, Ident, ItemTrait, LitInt, LitStr, Pat, ReturnType, Token, TraitItem,
+    TraitItemFn, parse_macro_input,
+};
+
+/// #[feign_client] macro implementation
+/// #[feign_client]宏实现
+///
+/// The trait is kept, with its `async fn`s rewritten to return `Send` futures, and a
+/// `<Trait>Impl` struct implementing it is generated next to it.
+/// trait会被保留，其 `async fn` 被改写为返回 `Send` future，并在旁边生成实现它的
+/// `<Trait>Impl` 结构体。
+pub(crate) fn feign_client_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as ClientArgs);
+    let item = parse_macro_input!(item as ItemTrait);
+
+    match expand(&args, item) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+/// Arguments of #[feign_client(...)]
+/// #[feign_client(...)] 的参数
+#[derive(Default)]
+struct ClientArgs {
+    name: Option<LitStr>,
+    url: Option<LitStr>,
+    path: Option<LitStr>,
+    circuit_breaker: Option<LitStr>,
+}
+
+impl Parse for ClientArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self::default();
+        while !input.is_empty() {
+            // A bare string is the URL: #[feign_client("https://api.example.com")]
+            // 单独的字符串是URL：#[feign_client("https://api.example.com")]
+            if input.peek(LitStr) {
+                args.url = Some(input.parse()?);
+            } else {
+                let key: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                let value: LitStr = input.parse()?;
+                match key.to_string().as_str() {
+                    "name" | "value" => args.name = Some(value),
+                    "url" => args.url = Some(value),
+                    "path" => args.path = Some(value),
+                    "circuit_breaker" => args.circuit_breaker = Some(value),
+                    other => {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            format!("unknown feign_client option `{}`", other),
+                        ));
+                    },
+                }
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(args)
+    }
+}
+
+/// Options shared by the trait and its methods
+/// trait及其方法共享的选项
+#[derive(Default)]
+struct CallOptions {
+    timeout: Option<TokenStream2>,
+    retry: Option<TokenStream2>,
+    circuit_breaker: Option<LitStr>,
+    error_decoder: Option<syn::Path>,
+}
+
+/// How a parameter is sent
+/// 参数的发送方式
+enum ParamKind {
+    Path(String),
+    Query(String),
+    Header(String),
+    Body,
+}
+
+fn expand(args: &ClientArgs, mut item: ItemTrait) -> syn::Result<TokenStream2> {
+    if args.name.is_none() && args.url.is_none() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "#[feign_client] needs a `name` to discover or a `url`",
+        ));
+    }
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "#[feign_client] traits cannot be generic",
+        ));
+    }
+
+    let trait_ident = item.ident.clone();
+    let impl_ident = format_ident!("{}Impl", trait_ident);
+    let vis = item.vis.clone();
+    let options = take_call_options(&mut item.attrs)?;
+
+    let mut methods = Vec::new();
+    for trait_item in &mut item.items {
+        match trait_item {
+            TraitItem::Fn(method) => methods.push(expand_method(&trait_ident, method)?),
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "only methods are allowed in a #[feign_client] trait",
+                ));
+            },
+        }
+    }
+
+    // Builder preconfigured from the attributes
+    // 根据属性预先配置的构建器
+    let name = match &args.name {
+        Some(name) => name.value(),
+        None => trait_ident.to_string(),
+    };
+    let url = args.url.as_ref().map(|url| quote! { .url(#url) });
+    let path = args.path.as_ref().map(|path| quote! { .path(#path) });
+    let circuit_breaker = args
+        .circuit_breaker
+        .as_ref()
+        .or(options.circuit_breaker.as_ref())
+        .map(|name| quote! { .circuit_breaker(#name) });
+    let retry = options.retry.map(|policy| quote! { .retry(#policy) });
+    let timeout = options.timeout.map(|timeout| quote! { .timeout(#timeout) });
+    let error_decoder = options.error_decoder.map(
+        |decoder| quote! { .error_decoder(<#decoder as ::core::default::Default>::default()) },
+    );
+
+    let struct_doc = format!("HTTP client implementing [`{}`]", trait_ident);
+    let struct_doc_zh = format!("实现 [`{}`] 的HTTP客户端", trait_ident);
+
+    Ok(quote! {
+        #item
+
+        #[doc = #struct_doc]
+        #[doc = #struct_doc_zh]
+        #[derive(Clone, Debug)]
+        #vis struct #impl_ident {
+            client: ::nexus_cloud::http_client::FeignClient,
+        }
+
+        impl #impl_ident {
+            /// Builder preconfigured from the `#[feign_client]` attributes
+            /// 根据 `#[feign_client]` 属性预先配置的构建器
+            pub fn builder() -> ::nexus_cloud::http_client::FeignClientBuilder {
+                ::nexus_cloud::http_client::FeignClient::builder(#name)
+                    #url #path #circuit_breaker #retry #timeout #error_decoder
+            }
+
+            /// Wrap a built client
+            /// 包装已构建的客户端
+            pub fn new(client: ::nexus_cloud::http_client::FeignClient) -> Self {
+                Self { client }
+            }
+
+            /// The underlying client
+            /// 底层客户端
+            pub fn client(&self) -> &::nexus_cloud::http_client::FeignClient {
+                &self.client
+            }
+        }
+
+        impl ::core::convert::From<::nexus_cloud::http_client::FeignClient> for #impl_ident {
+            fn from(client: ::nexus_cloud::http_client::FeignClient) -> Self {
+                Self::new(client)
+            }
+        }
+
+        impl #trait_ident for #impl_ident {
+            #(#methods)*
+        }
+    })
+}
+
+/// Rewrite one trait method in place and return its implementation
+/// 就地改写一个trait方法并返回其实现
+fn expand_method(trait_ident: &Ident, method: &mut TraitItemFn) -> syn::Result<TokenStream2> {
+    let (http_method, template) = take_mapping(method)?;
+    let options = take_call_options(&mut method.attrs)?;
+    if let Some(decoder) = &options.error_decoder {
+        // The error decoder is configured once for the whole client
+        // 错误解码器为整个客户端配置一次
+        return Err(syn::Error::new_spanned(
+            decoder,
+            "#[feign_error_decoder] belongs on the #[feign_client] trait",
+        ));
+    }
+
+    if let Some(body) = &method.default {
+        return Err(syn::Error::new_spanned(
+            body,
+            "#[feign_client] methods are generated and cannot have a body",
+        ));
+    }
+    let sig = &mut method.sig;
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            sig.fn_token,
+            "#[feign_client] methods must be `async fn`",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "#[feign_client] methods cannot be generic",
+        ));
+    }
+    match sig.inputs.first() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_none() => {},
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &sig.ident,
+                "#[feign_client] methods must take `&self`",
+            ));
+        },
+    }
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) => ty.clone(),
+        ReturnType::Default => {
+            return Err(syn::Error::new_spanned(
+                &sig.ident,
+                "#[feign_client] methods must return a `Result`",
+            ));
+        },
+    };
+
+    // Turn every parameter into a request builder call
+    // 将每个参数转换为请求构建器调用
+    let placeholders = placeholders(&template.value());
+    let mut bound = Vec::new();
+    let mut has_body = false;
+    let mut calls = Vec::new();
+    for arg in sig.inputs.iter_mut().skip(1) {
+        let FnArg::Typed(arg) = arg else { continue };
+        let ident = match &*arg.pat {
+            Pat::Ident(pat) => pat.ident.clone(),
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "#[feign_client] parameters must be plain identifiers",
+                ));
+            },
+        };
+        let kind = match take_param_kind(&mut arg.attrs, &ident)? {
+            Some(kind) => kind,
+            None if placeholders.contains(&ident.to_string()) => ParamKind::Path(ident.to_string()),
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &ident,
+                    "parameter needs #[feign_path], #[feign_query], #[feign_header] or #[feign_body]",
+                ));
+            },
+        };
+        calls.push(match kind {
+            ParamKind::Path(name) => {
+                if !placeholders.contains(&name) {
+                    return Err(syn::Error::new_spanned(
+                        &ident,
+                        format!("`{}` has no `{{{}}}` in the request path", ident, name),
+                    ));
+                }
+                let call = quote! { .path_param(#name, &#ident) };
+                bound.push(name);
+                call
+            },
+            ParamKind::Query(name) => quote! { .query(#name, &#ident) },
+            ParamKind::Header(name) => quote! { .header(#name, &#ident) },
+            ParamKind::Body => {
+                if has_body {
+                    return Err(syn::Error::new_spanned(
+                        &ident,
+                        "only one parameter can be the #[feign_body]",
+                    ));
+                }
+                has_body = true;
+                quote! { .json(&#ident) }
+            },
+        });
+    }
+    if let Some(missing) = placeholders.iter().find(|name| !bound.contains(name)) {
+        return Err(syn::Error::new_spanned(
+            &template,
+            format!("path variable `{{{}}}` has no matching parameter", missing),
+        ));
+    }
+    if let Some(timeout) = options.timeout {
+        calls.push(quote! { .timeout(#timeout) });
+    }
+    if let Some(policy) = options.retry {
+        calls.push(quote! { .retry(#policy) });
+    }
+    if let Some(name) = options.circuit_breaker {
+        calls.push(quote! { .circuit_breaker(#name) });
+    }
+
+    // `async fn` becomes `fn -> impl Future + Send` so calls can be spawned
+    // `async fn` 变为 `fn -> impl Future + Send`，以便调用可以被派生
+    sig.asyncness = None;
+    sig.output = syn::parse_quote! {
+        -> impl ::core::future::Future<Output = #output> + ::core::marker::Send
+    };
+
+    let method_key = format!("{}#{}", trait_ident, sig.ident);
+    let sig = &*sig;
+    Ok(quote! {
+        #sig {
+            let request = ::nexus_cloud::http_client::FeignRequest::new(
+                ::nexus_cloud::http_client::Method::#http_method,
+                #method_key,
+                #template,
+            )
+            #(#calls)*;
+            async move {
+                let response = self.client.execute(request).await?;
+                self.client.decode(&response).map_err(::core::convert::Into::into)
+            }
+        }
+    })
+}
+
+/// Remove the request mapping attribute of a method
+/// 移除方法的请求映射属性
+fn take_mapping(method: &mut TraitItemFn) -> syn::Result<(Ident, LitStr)> {
+    let mut mapping = None;
+    let mut error = None;
+    method.attrs.retain(|attr| {
+        let http_method = match attr_name(attr).as_deref() {
+            Some("feign_get" | "get") => "GET",
+            Some("feign_post" | "post") => "POST",
+            Some("feign_put" | "put") => "PUT",
+            Some("feign_patch" | "patch") => "PATCH",
+            Some("feign_delete" | "delete") => "DELETE",
+            _ => return true,
+        };
+        let result = if mapping.is_some() {
+            Err(syn::Error::new_spanned(attr, "a method can only have one request mapping"))
+        } else {
+            attr.parse_args::<LitStr>()
+        };
+        match result {
+            Ok(path) => mapping = Some((Ident::new(http_method, Span::call_site()), path)),
+            Err(e) => error = error.take().or(Some(e)),
+        }
+        false
+    });
+    if let Some(e) = error {
+        return Err(e);
+    }
+    mapping.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &method.sig.ident,
+            "missing #[feign_get], #[feign_post], #[feign_put] or #[feign_delete]",
+        )
+    })
+}
+
+/// Remove #[feign_timeout], #[feign_retry], #[circuit_breaker_name] and
+/// #[feign_error_decoder]
+/// 移除 #[feign_timeout]、#[feign_retry]、#[circuit_breaker_name] 和
+/// #[feign_error_decoder]
+fn take_call_options(attrs: &mut Vec<Attribute>) -> syn::Result<CallOptions> {
+    let mut options = CallOptions::default();
+    let mut kept = Vec::with_capacity(attrs.len());
+    for attr in attrs.drain(..) {
+        match attr_name(&attr).as_deref() {
+            Some("feign_timeout") => options.timeout = Some(parse_timeout(&attr)?),
+            Some("feign_retry") => options.retry = Some(parse_retry(&attr)?),
+            Some("circuit_breaker_name") => options.circuit_breaker = Some(attr.parse_args()?),
+            Some("feign_error_decoder") => options.error_decoder = Some(attr.parse_args()?),
+            _ => kept.push(attr),
+        }
+    }
+    *attrs = kept;
+    Ok(options)
+}
+
+/// `#[feign_timeout(5000)]`, `#[feign_timeout(millis = 500)]` or `#[feign_timeout(secs = 5)]`
+fn parse_timeout(attr: &Attribute) -> syn::Result<TokenStream2> {
+    attr.parse_args_with(|input: ParseStream| {
+        if input.peek(LitInt) {
+            let millis: LitInt = input.parse()?;
+            return Ok(quote! { ::std::time::Duration::from_millis(#millis) });
+        }
+        let unit: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value: LitInt = input.parse()?;
+        match unit.to_string().as_str() {
+            "millis" | "ms" => Ok(quote! { ::std::time::Duration::from_millis(#value) }),
+            "secs" | "seconds" => Ok(quote! { ::std::time::Duration::from_secs(#value) }),
+            _ => Err(syn::Error::new_spanned(unit, "expected `millis` or `secs`")),
+        }
+    })
+}
+
+/// `#[feign_retry]`, `#[feign_retry(3)]` or
+/// `#[feign_retry(max_attempts = 3, delay = 100, max_delay = 2000)]` (delays in milliseconds)
+fn parse_retry(attr: &Attribute) -> syn::Result<TokenStream2> {
+    let mut policy = quote! { ::nexus_cloud::http_client::RetryPolicy::new() };
+    if matches!(attr.meta, syn::Meta::Path(_)) {
+        return Ok(policy);
+    }
+    attr.parse_args_with(|input: ParseStream| {
+        while !input.is_empty() {
+            if input.peek(LitInt) {
+                let attempts: LitInt = input.parse()?;
+                policy = quote! { #policy.with_max_attempts(#attempts) };
+            } else {
+                let key: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                let value: LitInt = input.parse()?;
+                policy = match key.to_string().as_str() {
+                    "max_attempts" => quote! { #policy.with_max_attempts(#value) },
+                    "delay" => quote! {
+                        #policy.with_initial_delay(::std::time::Duration::from_millis(#value))
+                    },
+                    "max_delay" => quote! {
+                        #policy.with_max_delay(::std::time::Duration::from_millis(#value))
+                    },
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            key,
+                            "expected `max_attempts`, `delay` or `max_delay`",
+                        ));
+                    },
+                };
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(())
+    })?;
+    Ok(policy)
+}
+
+/// Remove the parameter attribute and decide how the parameter is sent
+/// 移除参数属性并决定参数的发送方式
+fn take_param_kind(attrs: &mut Vec<Attribute>, ident: &Ident) -> syn::Result<Option<ParamKind>> {
+    let mut kind = None;
+    let mut kept = Vec::with_capacity(attrs.len());
+    for attr in attrs.drain(..) {
+        let parsed = match attr_name(&attr).as_deref() {
+            Some("feign_path" | "path") => {
+                ParamKind::Path(param_name(&attr)?.unwrap_or_else(|| ident.to_string()))
+            },
+            Some("feign_query" | "query") => {
+                ParamKind::Query(param_name(&attr)?.unwrap_or_else(|| ident.to_string()))
+            },
+            Some("feign_header" | "header") => ParamKind::Header(
+                param_name(&attr)?.unwrap_or_else(|| ident.to_string().replace('_', "-")),
+            ),
+            Some("feign_body" | "body") => ParamKind::Body,
+            _ => {
+                kept.push(attr);
+                continue;
+            },
+        };
+        if kind.is_some() {
+            return Err(syn::Error::new_spanned(attr, "a parameter can only be sent one way"));
+        }
+        kind = Some(parsed);
+    }
+    *attrs = kept;
+    Ok(kind)
+}
+
+/// Optional explicit name: `#[feign_query("page_size")]`
+/// 可选的显式名称：`#[feign_query("page_size")]`
+fn param_name(attr: &Attribute) -> syn::Result<Option<String>> {
+    match &attr.meta {
+        syn::Meta::Path(_) => Ok(None),
+        _ => Ok(Some(attr.parse_args::<LitStr>()?.value())),
+    }
+}
+
+/// Last path segment of an attribute, so `#[nexus_macros::feign_get]` also matches
+/// 属性的最后一个路径段，因此 `#[nexus_macros::feign_get]` 也能匹配
+fn attr_name(attr: &Attribute) -> Option<String> {
+    attr.path()
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+}
+
+/// Names of the `{name}` placeholders in a path template
+/// 路径模板中 `{name}` 占位符的名称
+fn placeholders(template: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        names.push(rest[start + 1..start + len].to_string());
+        rest = &rest[start + len + 1..];
+    }
+    names
+}
@@ -39,6 +39,9 @@ use syn::{
     DeriveInput, Expr, ItemFn, ItemImpl, ItemStatic, ItemStruct, ItemTrait, parse_macro_input,
 };
 
+mod feign;
+mod security;
+mod testing;
 mod transactional;
 
 // ============================================================================
@@ -884,6 +887,46 @@ pub fn transactional(attr: TokenStream, item: TokenStream) -> TokenStream {
     transactional::transactional_impl(attr, item)
 }
 
+// ============================================================================
+// Test Macro (equivalent to @SpringBootTest with virtual time)
+// 测试宏（等价于带虚拟时间的 @SpringBootTest）
+// ============================================================================
+
+/// Runs an async test on the deterministic test runtime
+/// 在确定性测试运行时上运行异步测试
+///
+/// Equivalent to Reactor's `StepVerifier.withVirtualTime`.
+/// 等价于 Reactor 的 `StepVerifier.withVirtualTime`。
+///
+/// The test body runs on a `nexus_runtime::runtime::TestRuntime`. The clock starts
+/// paused unless `start_paused = false` is given, so sleeps and timeouts finish
+/// without waiting. `seed = N` fixes the order of tasks woken together; without
+/// it the `NEXUS_TEST_SEED` environment variable or 0 is used.
+/// 测试体在 `nexus_runtime::runtime::TestRuntime` 上运行。除非指定 `start_paused = false`，
+/// 时钟以暂停状态启动，因此睡眠和超时无需等待即可完成。`seed = N` 固定同时被唤醒的任务的顺序；
+/// 未指定时使用环境变量 `NEXUS_TEST_SEED` 或0。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// use nexus_runtime::time::{self, Duration};
+///
+/// #[nexus_macros::test]
+/// async fn cache_entry_expires() {
+///     let cache = Cache::with_ttl(Duration::from_secs(60));
+///     cache.insert("key", 1);
+///     time::advance(Duration::from_secs(61)).await;
+///     assert!(cache.get("key").is_none());
+/// }
+///
+/// #[nexus_macros::test(start_paused = false, seed = 42)]
+/// async fn real_time_test() {}
+/// ```
+#[proc_macro_attribute]
+pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
+    testing::test_impl(attr, item)
+}
+
 // ============================================================================
 // Cacheable Macros (equivalent to @Cacheable, @CacheEvict, @CachePut)
 // 缓存宏（等价于 @Cacheable, @CacheEvict, @CachePut）
@@ -2169,14 +2212,32 @@ pub fn initial_delay(_attr: TokenStream, item: TokenStream) -> TokenStream {
 // 安全宏（等价于 @Secured, @PreAuthorize 等）
 // ============================================================================
 
-/// Mark a method as requiring authentication
-/// 标记方法需要认证
+/// Restrict a method to holders of any of the listed authorities
+/// 将方法限制为持有所列任一权限的用户
 ///
-/// Equivalent to Spring Security's `@Secured`.
-/// 等价于 Spring Security 的 `@Secured`。
+/// Equivalent to Spring Security's `@Secured`. Names starting with `ROLE_` are
+/// roles, others are authorities.
+/// 等价于 Spring Security 的 `@Secured`。以 `ROLE_` 开头的名称为角色，其余为权限。
+///
+/// Like all method security macros, this wraps an `async fn` returning a `Result`
+/// whose error converts from `nexus_security::SecurityError`; denials return
+/// `SecurityError::AccessDenied`, which becomes 403 Forbidden as a
+/// `nexus_http::Error`.
+/// 与所有方法安全宏一样，它包装返回 `Result` 的 `async fn`，其错误类型须可从
+/// `nexus_security::SecurityError` 转换；拒绝时返回 `SecurityError::AccessDenied`，
+/// 转换为 `nexus_http::Error` 时为403 Forbidden。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// #[secured("ROLE_ADMIN", "ROLE_MODERATOR")]
+/// async fn moderate(req: Request) -> Result<Response, nexus_http::Error> {
+///     // ...
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn secured(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    item
+pub fn secured(attr: TokenStream, item: TokenStream) -> TokenStream {
+    security::secured_impl(attr, item)
 }
 
 /// Pre-authorize method access based on expression
@@ -2184,56 +2245,107 @@ pub fn secured(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Security's `@PreAuthorize`.
 /// 等价于 Spring Security 的 `@PreAuthorize`。
+///
+/// The expression is parsed at compile time, so a malformed one fails the build.
+/// It may use `and` / `or` / `not`, comparisons, `hasRole`, `hasAnyRole`,
+/// `hasAuthority`, `hasAnyAuthority`, `hasPermission`, `isAuthenticated()`,
+/// `principal.*`, `authentication.*` and `#name`, which refers to an argument that
+/// must implement `Serialize`.
+/// 表达式在编译时解析，格式错误会导致构建失败。可使用 `and` / `or` / `not`、比较、
+/// `hasRole`、`hasAnyRole`、`hasAuthority`、`hasAnyAuthority`、`hasPermission`、
+/// `isAuthenticated()`、`principal.*`、`authentication.*` 以及 `#name`，后者引用的参数必须
+/// 实现 `Serialize`。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// use nexus_macros::pre_authorize;
+///
+/// #[pre_authorize("hasRole('ADMIN') or #user_id == principal.id")]
+/// async fn get_profile(user_id: u64) -> Result<Profile, SecurityError> {
+///     // ...
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn pre_authorize(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    item
+pub fn pre_authorize(attr: TokenStream, item: TokenStream) -> TokenStream {
+    security::pre_authorize_impl(attr, item)
 }
 
 /// Post-authorize method access based on expression
 /// 基于表达式事后授权方法访问
 ///
-/// Equivalent to Spring Security's `@PostAuthorize`.
-/// 等价于 Spring Security 的 `@PostAuthorize`。
+/// Equivalent to Spring Security's `@PostAuthorize`. The `Ok` value is
+/// `returnObject` and must implement `Serialize`.
+/// 等价于 Spring Security 的 `@PostAuthorize`。`Ok` 值即 `returnObject`，必须实现 `Serialize`。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// #[post_authorize("returnObject.owner == authentication.name")]
+/// async fn load_document(id: u64) -> Result<Document, SecurityError> {
+///     // ...
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn post_authorize(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    item
+pub fn post_authorize(attr: TokenStream, item: TokenStream) -> TokenStream {
+    security::post_authorize_impl(attr, item)
 }
 
-/// Pre-filter method access based on expression
-/// 基于表达式预先过滤方法访问
+/// Filter a collection argument based on expression
+/// 基于表达式过滤集合参数
+///
+/// Equivalent to Spring Security's `@PreFilter`. Each element is `filterObject`;
+/// with several arguments, `filter_target` names the collection.
+/// 等价于 Spring Security 的 `@PreFilter`。每个元素即 `filterObject`；有多个参数时，
+/// 由 `filter_target` 指定集合。
 ///
-/// Equivalent to Spring Security's `@PreFilter`.
-/// 等价于 Spring Security 的 `@PreFilter`。
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// #[pre_filter("filterObject.owner == authentication.name", filter_target = "docs")]
+/// async fn archive(docs: Vec<Document>, reason: String) -> Result<(), SecurityError> {
+///     // ...
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn pre_filter(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    item
+pub fn pre_filter(attr: TokenStream, item: TokenStream) -> TokenStream {
+    security::pre_filter_impl(attr, item)
 }
 
-/// Post-filter method access based on expression
-/// 基于表达式事后过滤方法访问
+/// Filter the returned collection based on expression
+/// 基于表达式过滤返回的集合
+///
+/// Equivalent to Spring Security's `@PostFilter`. Each element is `filterObject`.
+/// 等价于 Spring Security 的 `@PostFilter`。每个元素即 `filterObject`。
+///
+/// # Example / 示例
 ///
-/// Equivalent to Spring Security's `@PostFilter`.
-/// 等价于 Spring Security 的 `@PostFilter`。
+/// ```rust,no_run,ignore
+/// #[post_filter("filterObject.public or hasRole('ADMIN')")]
+/// async fn list_documents() -> Result<Vec<Document>, SecurityError> {
+///     // ...
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn post_filter(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    item
+pub fn post_filter(attr: TokenStream, item: TokenStream) -> TokenStream {
+    security::post_filter_impl(attr, item)
 }
 
-/// Define roles required for access
-/// 定义访问所需的角色
+/// Restrict a method to holders of any of the listed roles
+/// 将方法限制为持有所列任一角色的用户
 ///
-/// Equivalent to Spring Security's `@RolesAllowed`.
-/// 等价于 Spring Security 的 `@RolesAllowed`。
+/// Equivalent to Jakarta's `@RolesAllowed`; role names may omit `ROLE_`.
+/// 等价于 Jakarta 的 `@RolesAllowed`；角色名可省略 `ROLE_`。
 #[proc_macro_attribute]
-pub fn roles_allowed(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    item
+pub fn roles_allowed(attr: TokenStream, item: TokenStream) -> TokenStream {
+    security::roles_allowed_impl(attr, item, "roles_allowed")
 }
 
 /// Permit all access
 /// 允许所有访问
 ///
-/// Equivalent to Spring Security's `@PermitAll`.
-/// 等价于 Spring Security 的 `@PermitAll`。
+/// Equivalent to Jakarta's `@PermitAll`; the method is left unchecked.
+/// 等价于 Jakarta 的 `@PermitAll`；方法不做检查。
 #[proc_macro_attribute]
 pub fn permit_all(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2242,11 +2354,11 @@ pub fn permit_all(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// Deny all access
 /// 拒绝所有访问
 ///
-/// Equivalent to Spring Security's `@DenyAll`.
-/// 等价于 Spring Security 的 `@DenyAll`。
+/// Equivalent to Jakarta's `@DenyAll`; every call returns `AccessDenied`.
+/// 等价于 Jakarta 的 `@DenyAll`；每次调用都返回 `AccessDenied`。
 #[proc_macro_attribute]
 pub fn deny_all(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    item
+    security::deny_all_impl(item)
 }
 
 /// Allow anonymous access
@@ -2262,11 +2374,11 @@ pub fn anonymous(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// Require specific role for access
 /// 要求特定角色才能访问
 ///
-/// Equivalent to Spring Security's `@RolesAllowed`.
-/// 等价于 Spring Security 的 `@RolesAllowed`。
+/// Same as [`macro@roles_allowed`].
+/// 与 [`macro@roles_allowed`] 相同。
 #[proc_macro_attribute]
-pub fn require_role(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    item
+pub fn require_role(attr: TokenStream, item: TokenStream) -> TokenStream {
+    security::roles_allowed_impl(attr, item, "require_role")
 }
 
 // ============================================================================
@@ -2675,20 +2787,67 @@ pub fn delete_operation(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// Equivalent to Spring Cloud OpenFeign's `@FeignClient`.
 /// 等价于 Spring Cloud OpenFeign 的 `@FeignClient`。
 ///
+/// Generates a `<Trait>Impl` struct implementing the trait on top of
+/// `nexus_cloud::http_client::FeignClient`. Each method builds a request from its
+/// mapping attribute and parameters, serializes the `#[feign_body]` as JSON and
+/// deserializes the JSON response into the `Ok` type of its `Result`; the error type
+/// must implement `From<FeignError>`. The `async fn`s of the trait are rewritten to
+/// return `Send` futures.
+///
+/// 生成基于 `nexus_cloud::http_client::FeignClient` 实现该trait的 `<Trait>Impl` 结构体。
+/// 每个方法根据其映射属性和参数构建请求，将 `#[feign_body]` 序列化为JSON，并将JSON响应
+/// 反序列化为其 `Result` 的 `Ok` 类型；错误类型必须实现 `From<FeignError>`。trait的
+/// `async fn` 会被改写为返回 `Send` future。
+///
+/// # Attributes / 属性
+///
+/// - `name` - service id resolved through `ServiceDiscovery` / 通过 `ServiceDiscovery` 解析的服务ID
+/// - `url` - fixed base URL, takes precedence over discovery / 固定基础URL，优先于服务发现
+/// - `path` - path prefix of every method / 每个方法的路径前缀
+/// - `circuit_breaker` - circuit breaker name / 断路器名称
+///
+/// `#[feign_retry]`, `#[feign_timeout]`, `#[circuit_breaker_name]` and
+/// `#[feign_error_decoder]` below `#[feign_client]` apply to the whole client; the
+/// first three can also be put on single methods.
+/// 位于 `#[feign_client]` 之下的 `#[feign_retry]`、`#[feign_timeout]`、
+/// `#[circuit_breaker_name]` 和 `#[feign_error_decoder]` 作用于整个客户端；
+/// 前三个也可以用于单个方法。
+///
 /// # Example / 示例
 ///
 /// ```rust,no_run,ignore
+/// use nexus_cloud::http_client::FeignError;
 /// use nexus_macros::feign_client;
 ///
-/// #[feign_client("https://api.example.com")]
-/// trait ApiClient {
-///     #[get("/users/{id}")]
-///     async fn get_user(&self, #[path] id: u64) -> User;
+/// #[feign_client(name = "user-service", path = "/api", circuit_breaker = "users")]
+/// #[feign_retry(max_attempts = 3, delay = 100)]
+/// pub trait UserClient {
+///     #[feign_get("/users/{id}")]
+///     async fn get_user(&self, #[feign_path] id: u64) -> Result<User, FeignError>;
+///
+///     #[feign_get("/users")]
+///     async fn search(
+///         &self,
+///         #[feign_query] name: &str,
+///         #[feign_query("page_size")] size: Option<u32>,
+///     ) -> Result<Vec<User>, FeignError>;
+///
+///     #[feign_post("/users")]
+///     #[feign_timeout(secs = 5)]
+///     async fn create_user(
+///         &self,
+///         #[feign_header("X-Request-Id")] request_id: &str,
+///         #[feign_body] user: &NewUser,
+///     ) -> Result<User, FeignError>;
 /// }
+///
+/// let client = UserClientImpl::builder().discovery(discovery).build()?;
+/// let users = UserClientImpl::new(client);
+/// let user = users.get_user(42).await?;
 /// ```
 #[proc_macro_attribute]
-pub fn feign_client(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    item
+pub fn feign_client(attr: TokenStream, item: TokenStream) -> TokenStream {
+    feign::feign_client_impl(attr, item)
 }
 
 /// Mark a method as Feign client GET request
@@ -2696,6 +2855,12 @@ pub fn feign_client(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@GetMapping` in Feign client.
 /// 等价于 Spring Cloud OpenFeign 的 `@GetMapping`（在 Feign 客户端中）。
+///
+/// `#[feign_get("/users/{id}")]` on a `#[feign_client]` method.
+/// 在 `#[feign_client]` 方法上使用 `#[feign_get("/users/{id}")]`。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_get(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2706,6 +2871,12 @@ pub fn feign_get(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@PostMapping` in Feign client.
 /// 等价于 Spring Cloud OpenFeign 的 `@PostMapping`（在 Feign 客户端中）。
+///
+/// `#[feign_post("/users")]` on a `#[feign_client]` method.
+/// 在 `#[feign_client]` 方法上使用 `#[feign_post("/users")]`。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_post(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2716,6 +2887,12 @@ pub fn feign_post(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@PutMapping` in Feign client.
 /// 等价于 Spring Cloud OpenFeign 的 `@PutMapping`（在 Feign 客户端中）。
+///
+/// `#[feign_put("/users/{id}")]` on a `#[feign_client]` method.
+/// 在 `#[feign_client]` 方法上使用 `#[feign_put("/users/{id}")]`。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_put(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2726,6 +2903,12 @@ pub fn feign_put(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@DeleteMapping` in Feign client.
 /// 等价于 Spring Cloud OpenFeign 的 `@DeleteMapping`（在 Feign 客户端中）。
+///
+/// `#[feign_delete("/users/{id}")]` on a `#[feign_client]` method.
+/// 在 `#[feign_client]` 方法上使用 `#[feign_delete("/users/{id}")]`。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_delete(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2736,6 +2919,12 @@ pub fn feign_delete(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@PathVariable`.
 /// 等价于 Spring Cloud OpenFeign 的 `@PathVariable`。
+///
+/// Fills the `{name}` placeholder of the parameter name, or of `#[feign_path("name")]`.
+/// 填充与参数同名的 `{name}` 占位符，或 `#[feign_path("name")]` 指定的占位符。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_path(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2746,6 +2935,12 @@ pub fn feign_path(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@RequestParam`.
 /// 等价于 Spring Cloud OpenFeign 的 `@RequestParam`。
+///
+/// `None` values are skipped and sequences repeat the parameter; rename with `#[feign_query("name")]`.
+/// `None` 值会被跳过，序列会重复该参数；可用 `#[feign_query("name")]` 重命名。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_query(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2756,6 +2951,12 @@ pub fn feign_query(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@RequestHeader`.
 /// 等价于 Spring Cloud OpenFeign 的 `@RequestHeader`。
+///
+/// `#[feign_header("X-Request-Id")]`; without a name, `request_id` becomes `request-id`.
+/// `#[feign_header("X-Request-Id")]`；未指定名称时，`request_id` 变为 `request-id`。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_header(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2766,6 +2967,12 @@ pub fn feign_header(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@RequestBody`.
 /// 等价于 Spring Cloud OpenFeign 的 `@RequestBody`。
+///
+/// The parameter is serialized as the JSON request body.
+/// 参数会被序列化为JSON请求体。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_body(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2776,6 +2983,12 @@ pub fn feign_body(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@CircuitBreakerName`.
 /// 等价于 Spring Cloud OpenFeign 的 `@CircuitBreakerName`。
+///
+/// `#[circuit_breaker_name("users")]` on a `#[feign_client]` trait or method.
+/// 在 `#[feign_client]` trait或方法上使用 `#[circuit_breaker_name("users")]`。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn circuit_breaker_name(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2786,6 +2999,12 @@ pub fn circuit_breaker_name(_attr: TokenStream, item: TokenStream) -> TokenStrea
 ///
 /// Equivalent to Spring Cloud's `@Timeout`.
 /// 等价于 Spring Cloud 的 `@Timeout`。
+///
+/// `#[feign_timeout(500)]` (milliseconds), `#[feign_timeout(millis = 500)]` or `#[feign_timeout(secs = 5)]` on a `#[feign_client]` trait or method.
+/// 在 `#[feign_client]` trait或方法上使用 `#[feign_timeout(500)]`（毫秒）、`#[feign_timeout(millis = 500)]` 或 `#[feign_timeout(secs = 5)]`。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_timeout(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2796,6 +3015,12 @@ pub fn feign_timeout(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud's `@Retryable`.
 /// 等价于 Spring Cloud 的 `@Retryable`。
+///
+/// `#[feign_retry(max_attempts = 3, delay = 100, max_delay = 2000)]` (delays in milliseconds) on a `#[feign_client]` trait or method.
+/// 在 `#[feign_client]` trait或方法上使用 `#[feign_retry(max_attempts = 3, delay = 100, max_delay = 2000)]`（延迟单位为毫秒）。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_retry(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -2846,6 +3071,12 @@ pub fn feign_logger(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Equivalent to Spring Cloud OpenFeign's `@ErrorDecoder`.
 /// 等价于 Spring Cloud OpenFeign 的 `@ErrorDecoder`。
+///
+/// `#[feign_error_decoder(MyDecoder)]` on a `#[feign_client]` trait, where `MyDecoder` implements `ErrorDecoder` and `Default`.
+/// 在 `#[feign_client]` trait上使用 `#[feign_error_decoder(MyDecoder)]`，其中 `MyDecoder` 实现 `ErrorDecoder` 和 `Default`。
+///
+/// Read by `#[feign_client]`; on its own it leaves the item unchanged.
+/// 由 `#[feign_client]` 读取；单独使用时不改变被标注的项。
 #[proc_macro_attribute]
 pub fn feign_error_decoder(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
@@ -0,0 +1,519 @@
+//! Method security macro implementation
+//! 方法安全宏实现
+//!
+//! This module provides #[pre_authorize], #[post_authorize], #[pre_filter],
+//! #[post_filter], #[secured], #[roles_allowed] and #[deny_all]. They wrap async
+//! functions returning a `Result` whose error converts from
+//! `nexus_security::SecurityError`; a denial is `SecurityError::AccessDenied`, which
+//! `nexus_http::Error` turns into 403 Forbidden.
+//! 本模块提供#[pre_authorize]、#[post_authorize]、#[pre_filter]、#[post_filter]、
+//! #[secured]、#[roles_allowed]和#[deny_all]。它们包装返回 `Result` 的异步函数，其错误类型
+//! 须可从 `nexus_security::SecurityError` 转换；拒绝访问时返回
+//! `SecurityError::AccessDenied`，`nexus_http::Error` 会将其转换为403 Forbidden。
+//!
+//! Expressions are parsed while the crate compiles: a malformed one is a compile
+//! error pointing at the string, and a valid one is emitted as a ready-built
+//! `nexus_security::Expression`, so nothing is parsed at runtime. That type, and the
+//! parser behind `nexus_security::Expression::parse`, come from
+//! `nexus-security-expression`, the crate used here.
+//! 表达式在crate编译时解析：格式错误的表达式会产生指向该字符串的编译错误，有效的表达式则作为
+//! 已构建好的 `nexus_security::Expression` 生成，因此运行时无需解析。该类型以及
+//! `nexus_security::Expression::parse` 背后的解析器都来自此处使用的 `nexus-security-expression`。
+
+use nexus_security_expression::{ComparisonOperator, Expression};
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{FnArg, Ident, ItemFn, LitStr, Pat, PatIdent, ReturnType, Token, parse_macro_input};
+
+/// Identifiers bound only by the annotations that provide them
+/// 仅由提供它们的注解绑定的标识符
+const ANNOTATION_IDENTIFIERS: &[&str] = &["returnObject", "filterObject"];
+
+/// #[pre_authorize] macro implementation
+/// #[pre_authorize]宏实现
+///
+/// Checks the expression before the body runs. `#name` refers to the argument `name`.
+/// 在函数体执行前检查表达式。`#name` 引用参数 `name`。
+pub(crate) fn pre_authorize_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let expression = parse_macro_input!(attr as LitStr);
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand(|| {
+        let arguments = arguments(&function, "pre_authorize")?;
+        let (node, context) = compile(&expression, &[], &arguments)?;
+        let node = to_tokens(&node);
+        Ok(before(
+            function,
+            &quote! {
+                static __PRE_AUTHORIZE: ::std::sync::LazyLock<::nexus_security::Expression> =
+                    ::std::sync::LazyLock::new(|| #node);
+                ::nexus_security::ExpressionExt::authorize(&*__PRE_AUTHORIZE, &#context).await?;
+            },
+        ))
+    })
+}
+
+/// #[post_authorize] macro implementation
+/// #[post_authorize]宏实现
+///
+/// Checks the expression after the body has run, with its `Ok` value as `returnObject`.
+/// 在函数体执行后检查表达式，其 `Ok` 值作为 `returnObject`。
+pub(crate) fn post_authorize_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let expression = parse_macro_input!(attr as LitStr);
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand(|| {
+        let arguments = arguments(&function, "post_authorize")?;
+        let (node, context) = compile(&expression, &["returnObject"], &arguments)?;
+        let node = to_tokens(&node);
+        after(
+            function,
+            "post_authorize",
+            &context,
+            &quote! {
+                static __POST_AUTHORIZE: ::std::sync::LazyLock<::nexus_security::Expression> =
+                    ::std::sync::LazyLock::new(|| #node);
+                ::nexus_security::ExpressionExt::authorize(
+                    &*__POST_AUTHORIZE,
+                    &__context.return_object(&__value),
+                )
+                .await?;
+                ::core::result::Result::Ok(__value)
+            },
+        )
+    })
+}
+
+/// Arguments of #[pre_filter] and #[post_filter]
+/// #[pre_filter] 和 #[post_filter] 的参数
+struct FilterArgs {
+    expression: LitStr,
+    filter_target: Option<LitStr>,
+}
+
+impl Parse for FilterArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let expression = input.parse()?;
+        let mut filter_target = None;
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            if key != "filter_target" {
+                return Err(syn::Error::new_spanned(key, "expected `filter_target`"));
+            }
+            filter_target = Some(input.parse()?);
+        }
+        Ok(Self {
+            expression,
+            filter_target,
+        })
+    }
+}
+
+/// #[pre_filter] macro implementation
+/// #[pre_filter]宏实现
+///
+/// Keeps the elements of a collection argument for which the expression holds, each
+/// bound as `filterObject`. With several arguments, `filter_target` names the
+/// collection.
+/// 保留集合参数中表达式成立的元素，每个元素绑定为 `filterObject`。有多个参数时，
+/// 由 `filter_target` 指定集合。
+pub(crate) fn pre_filter_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as FilterArgs);
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand(|| {
+        let arguments = arguments(&function, "pre_filter")?;
+        let (node, context) = compile(&args.expression, &["filterObject"], &arguments)?;
+        let node = to_tokens(&node);
+
+        let target = match &args.filter_target {
+            Some(name) => arguments
+                .iter()
+                .find(|arg| arg.ident == name.value())
+                .ok_or_else(|| {
+                    syn::Error::new(name.span(), "`filter_target` does not name an argument")
+                })?,
+            None => match function.sig.inputs.iter().collect::<Vec<_>>().as_slice() {
+                [FnArg::Typed(arg)] if matches!(*arg.pat, Pat::Ident(_)) => {
+                    arguments.first().copied().ok_or_else(|| {
+                        syn::Error::new_spanned(&arg.pat, "expected an argument name")
+                    })?
+                },
+                _ => {
+                    return Err(syn::Error::new(
+                        args.expression.span(),
+                        "#[pre_filter] needs `filter_target = \"...\"` unless the function \
+                         takes a single collection argument",
+                    ));
+                },
+            },
+        };
+        let ident = &target.ident;
+        let filtered = quote! {
+            ::nexus_security::ExpressionExt::filter(&*__PRE_FILTER, #ident, &#context).await?
+        };
+        let rebind = if target.mutability.is_some() {
+            quote! { #ident = #filtered; }
+        } else {
+            quote! { let #ident = #filtered; }
+        };
+        Ok(before(
+            function,
+            &quote! {
+                static __PRE_FILTER: ::std::sync::LazyLock<::nexus_security::Expression> =
+                    ::std::sync::LazyLock::new(|| #node);
+                #rebind
+            },
+        ))
+    })
+}
+
+/// #[post_filter] macro implementation
+/// #[post_filter]宏实现
+///
+/// Keeps the elements of the returned collection for which the expression holds,
+/// each bound as `filterObject`.
+/// 保留返回集合中表达式成立的元素，每个元素绑定为 `filterObject`。
+pub(crate) fn post_filter_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let expression = parse_macro_input!(attr as LitStr);
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand(|| {
+        let arguments = arguments(&function, "post_filter")?;
+        let (node, context) = compile(&expression, &["filterObject"], &arguments)?;
+        let node = to_tokens(&node);
+        after(
+            function,
+            "post_filter",
+            &context,
+            &quote! {
+                static __POST_FILTER: ::std::sync::LazyLock<::nexus_security::Expression> =
+                    ::std::sync::LazyLock::new(|| #node);
+                ::nexus_security::ExpressionExt::filter(&*__POST_FILTER, __value, &__context)
+                    .await
+                    .map_err(::core::convert::From::from)
+            },
+        )
+    })
+}
+
+/// #[secured] macro implementation
+/// #[secured]宏实现
+///
+/// Grants access to holders of any of the listed authorities; `ROLE_` names are roles.
+/// 向持有所列任一权限的用户授予访问；`ROLE_` 开头的名称为角色。
+pub(crate) fn secured_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let names = parse_macro_input!(attr with Punctuated::<LitStr, Token![,]>::parse_terminated);
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand(|| {
+        arguments(&function, "secured")?;
+        let requirements: Vec<_> = names
+            .iter()
+            .map(|name| {
+                let value = name.value();
+                match value.strip_prefix("ROLE_") {
+                    Some(role) => quote! { .add_role(::nexus_security::Role::from_str(#role)) },
+                    None => {
+                        quote! { .add_authority(::nexus_security::Authority::permission(#value)) }
+                    },
+                }
+            })
+            .collect();
+        require_any(function, &names, &requirements, "secured")
+    })
+}
+
+/// #[roles_allowed] / #[require_role] macro implementation
+/// #[roles_allowed] / #[require_role]宏实现
+///
+/// Grants access to holders of any of the listed roles, named with or without `ROLE_`.
+/// 向持有所列任一角色的用户授予访问，角色名可带或不带 `ROLE_`。
+pub(crate) fn roles_allowed_impl(
+    attr: TokenStream,
+    item: TokenStream,
+    macro_name: &str,
+) -> TokenStream {
+    let names = parse_macro_input!(attr with Punctuated::<LitStr, Token![,]>::parse_terminated);
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand(|| {
+        arguments(&function, macro_name)?;
+        let requirements: Vec<_> = names
+            .iter()
+            .map(|name| {
+                let value = name.value();
+                let role = value.strip_prefix("ROLE_").unwrap_or(&value);
+                quote! { .add_role(::nexus_security::Role::from_str(#role)) }
+            })
+            .collect();
+        require_any(function, &names, &requirements, macro_name)
+    })
+}
+
+/// #[deny_all] macro implementation
+/// #[deny_all]宏实现
+pub(crate) fn deny_all_impl(item: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand(|| {
+        arguments(&function, "deny_all")?;
+        Ok(before(
+            function,
+            &quote! {
+                ::core::result::Result::<(), _>::Err(::nexus_security::SecurityError::AccessDenied(
+                    ::std::string::String::from("access is denied to all callers"),
+                ))?;
+            },
+        ))
+    })
+}
+
+fn expand(f: impl FnOnce() -> syn::Result<TokenStream2>) -> TokenStream {
+    match f() {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+/// Check that the function is async and collect the names its arguments bind
+/// 检查函数是否为异步，并收集其参数绑定的名称
+fn arguments<'a>(function: &'a ItemFn, macro_name: &str) -> syn::Result<Vec<&'a PatIdent>> {
+    if function.sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            function.sig.fn_token,
+            format!("#[{}] can only be applied to async functions", macro_name),
+        ));
+    }
+    let mut arguments = Vec::new();
+    for input in &function.sig.inputs {
+        if let FnArg::Typed(arg) = input {
+            bindings(&arg.pat, &mut arguments);
+        }
+    }
+    Ok(arguments)
+}
+
+/// Collect the identifiers bound by an argument pattern
+/// 收集参数模式绑定的标识符
+fn bindings<'a>(pat: &'a Pat, idents: &mut Vec<&'a PatIdent>) {
+    match pat {
+        Pat::Ident(pat) => idents.push(pat),
+        Pat::Reference(pat) => bindings(&pat.pat, idents),
+        Pat::Type(pat) => bindings(&pat.pat, idents),
+        Pat::Tuple(pat) => pat.elems.iter().for_each(|pat| bindings(pat, idents)),
+        Pat::TupleStruct(pat) => pat.elems.iter().for_each(|pat| bindings(pat, idents)),
+        Pat::Struct(pat) => pat
+            .fields
+            .iter()
+            .for_each(|field| bindings(&field.pat, idents)),
+        _ => {},
+    }
+}
+
+/// Parse an expression and build the evaluation context for it, binding the
+/// arguments it refers to
+/// 解析表达式并为其构建评估上下文，绑定其引用的参数
+fn compile(
+    expression: &LitStr,
+    available: &[&str],
+    arguments: &[&PatIdent],
+) -> syn::Result<(Expression, TokenStream2)> {
+    let node = parse(&expression.value(), available)
+        .map_err(|message| syn::Error::new(expression.span(), message))?;
+
+    let mut variables = Vec::new();
+    for name in node.variables() {
+        let Some(arg) = arguments.iter().find(|arg| arg.ident == name) else {
+            return Err(syn::Error::new(
+                expression.span(),
+                format!("`#{}` does not name an argument of this function", name),
+            ));
+        };
+        let ident = &arg.ident;
+        variables.push(quote! { .variable(#name, &#ident) });
+    }
+    let context = quote! {
+        ::nexus_security::EvaluationContext::from_context(
+            &::nexus_security::context().unwrap_or_default(),
+        )
+        .await
+        #(#variables)*
+    };
+    Ok((node, context))
+}
+
+/// Run `check` ahead of the function body
+/// 在函数体之前执行 `check`
+fn before(function: ItemFn, check: &TokenStream2) -> TokenStream2 {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = function;
+    quote! {
+        #(#attrs)*
+        #vis #sig {
+            #check
+            #block
+        }
+    }
+}
+
+/// Run the function body, then `check`, which sees its `Ok` value as `__value` and
+/// the evaluation context as `__context`
+/// 执行函数体后执行 `check`，后者可通过 `__value` 访问其 `Ok` 值，通过 `__context` 访问评估上下文
+fn after(
+    function: ItemFn,
+    macro_name: &str,
+    context: &TokenStream2,
+    check: &TokenStream2,
+) -> syn::Result<TokenStream2> {
+    let ReturnType::Type(_, output) = &function.sig.output else {
+        return Err(syn::Error::new_spanned(
+            &function.sig,
+            format!("#[{}] requires a function returning a `Result`", macro_name),
+        ));
+    };
+    let output = output.clone();
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = function;
+    // The context is built first, while the arguments are still available
+    // 先构建上下文，此时参数仍然可用
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            let __context = #context;
+            let __result: #output = async move #block.await;
+            let __value = __result?;
+            #check
+        }
+    })
+}
+
+/// Check the current authentication against any-of metadata built by `requirements`
+/// 根据 `requirements` 构建的任一满足元数据检查当前认证
+fn require_any(
+    function: ItemFn,
+    names: &Punctuated<LitStr, Token![,]>,
+    requirements: &[TokenStream2],
+    macro_name: &str,
+) -> syn::Result<TokenStream2> {
+    if names.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &function.sig.ident,
+            format!("#[{}] needs at least one name", macro_name),
+        ));
+    }
+    if let Some(empty) = names
+        .iter()
+        .find(|name| name.value().trim_start_matches("ROLE_").is_empty())
+    {
+        return Err(syn::Error::new(empty.span(), "expected a role or authority name"));
+    }
+    Ok(before(
+        function,
+        &quote! {
+            ::nexus_security::SecurityMetadata::new()
+                #(#requirements)*
+                .require_all(false)
+                .check(&::nexus_security::context().unwrap_or_default())
+                .await?;
+        },
+    ))
+}
+
+/// Parse an expression, returning the error message on failure; `available` lists
+/// the [`ANNOTATION_IDENTIFIERS`] the macro binds
+/// 解析表达式，失败时返回错误信息；`available` 列出宏绑定的 [`ANNOTATION_IDENTIFIERS`]
+fn parse(input: &str, available: &[&str]) -> Result<Expression, String> {
+    let expr = nexus_security_expression::parse(input)
+        .map_err(|e| format!("invalid security expression: {}", e))?;
+    if let Some(name) = expr
+        .identifiers()
+        .into_iter()
+        .find(|name| ANNOTATION_IDENTIFIERS.contains(name) && !available.contains(name))
+    {
+        return Err(format!(
+            "invalid security expression: `{}` is not available in this annotation",
+            name
+        ));
+    }
+    Ok(expr)
+}
+
+/// Code that builds the equivalent `nexus_security::Expression`
+/// 构建等价 `nexus_security::Expression` 的代码
+fn to_tokens(expr: &Expression) -> TokenStream2 {
+    let path = quote! { ::nexus_security::Expression };
+    let boxed = |expr: &Expression| {
+        let expr = to_tokens(expr);
+        quote! { ::std::boxed::Box::new(#expr) }
+    };
+    match expr {
+        Expression::Null => quote! { #path::Null },
+        Expression::Bool(value) => quote! { #path::Bool(#value) },
+        Expression::Number(value) => {
+            let value = Literal::f64_suffixed(*value);
+            quote! { #path::Number(#value) }
+        },
+        Expression::String(value) => {
+            quote! { #path::String(::std::string::String::from(#value)) }
+        },
+        Expression::Variable(name) => {
+            quote! { #path::Variable(::std::string::String::from(#name)) }
+        },
+        Expression::Identifier(name) => {
+            quote! { #path::Identifier(::std::string::String::from(#name)) }
+        },
+        Expression::Property(target, name) => {
+            let target = boxed(target);
+            quote! { #path::Property(#target, ::std::string::String::from(#name)) }
+        },
+        Expression::Call(name, args) => {
+            let args = args.iter().map(to_tokens);
+            quote! {
+                #path::Call(::std::string::String::from(#name), ::std::vec![#(#args),*])
+            }
+        },
+        Expression::Not(operand) => {
+            let operand = boxed(operand);
+            quote! { #path::Not(#operand) }
+        },
+        Expression::And(left, right) => {
+            let (left, right) = (boxed(left), boxed(right));
+            quote! { #path::And(#left, #right) }
+        },
+        Expression::Or(left, right) => {
+            let (left, right) = (boxed(left), boxed(right));
+            quote! { #path::Or(#left, #right) }
+        },
+        Expression::Compare(op, left, right) => {
+            let op = match op {
+                ComparisonOperator::Eq => quote! { Eq },
+                ComparisonOperator::Ne => quote! { Ne },
+                ComparisonOperator::Lt => quote! { Lt },
+                ComparisonOperator::Le => quote! { Le },
+                ComparisonOperator::Gt => quote! { Gt },
+                ComparisonOperator::Ge => quote! { Ge },
+            };
+            let (left, right) = (boxed(left), boxed(right));
+            quote! {
+                #path::Compare(::nexus_security::ComparisonOperator::#op, #left, #right)
+            }
+        },
+    }
+}
@@ -0,0 +1,97 @@
+//! Test macro implementation
+//! 测试宏实现
+//!
+//! This module provides the #[test] procedural macro that runs an async test on
+//! the deterministic `TestRuntime` of nexus-runtime.
+//! 本模块提供#[test]过程宏，在nexus-runtime的确定性 `TestRuntime` 上运行异步测试。
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{Expr, ExprLit, ItemFn, Lit, MetaNameValue, Token, parse_macro_input};
+
+/// Options of the #[test] macro
+/// #[test]宏的选项
+struct TestOptions {
+    start_paused: Option<bool>,
+    seed: Option<u64>,
+}
+
+/// Parse `start_paused = bool, seed = N`
+/// 解析 `start_paused = bool, seed = N`
+fn parse_test_options(attr: TokenStream) -> syn::Result<TestOptions> {
+    let mut options = TestOptions {
+        start_paused: None,
+        seed: None,
+    };
+    let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated.parse(attr)?;
+    for pair in pairs {
+        let Expr::Lit(ExprLit { lit, .. }) = &pair.value else {
+            return Err(syn::Error::new_spanned(&pair.value, "expected a literal"));
+        };
+        match (pair.path.get_ident().map(ToString::to_string).as_deref(), lit) {
+            (Some("start_paused"), Lit::Bool(value)) => options.start_paused = Some(value.value),
+            (Some("seed"), Lit::Int(value)) => options.seed = Some(value.base10_parse()?),
+            (Some("start_paused" | "seed"), _) => {
+                return Err(syn::Error::new_spanned(lit, "unexpected value type"));
+            },
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &pair.path,
+                    "unknown option, expected `start_paused` or `seed`",
+                ));
+            },
+        }
+    }
+    Ok(options)
+}
+
+/// #[test] macro implementation
+/// #[test]宏实现
+///
+/// The public wrapper is in `lib.rs` with the `#[proc_macro_attribute]` tag.
+/// 公共包装器在 `lib.rs` 中，带有 `#[proc_macro_attribute]` 标签。
+pub(crate) fn test_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let options = match parse_test_options(attr) {
+        Ok(options) => options,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    let function = parse_macro_input!(item as ItemFn);
+
+    if function.sig.asyncness.is_none() {
+        return syn::Error::new_spanned(function.sig.fn_token, "#[test] requires an async fn")
+            .to_compile_error()
+            .into();
+    }
+    if !function.sig.inputs.is_empty() {
+        return syn::Error::new_spanned(&function.sig.inputs, "test functions take no arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let fn_attrs = &function.attrs;
+    let fn_vis = &function.vis;
+    let fn_name = &function.sig.ident;
+    let fn_output = &function.sig.output;
+    let fn_block = &function.block;
+
+    let start_paused = options
+        .start_paused
+        .map(|paused| quote! { .start_paused(#paused) });
+    let seed = options.seed.map(|seed| quote! { .seed(#seed) });
+
+    let expanded = quote! {
+        #[::core::prelude::v1::test]
+        #(#fn_attrs)*
+        #fn_vis fn #fn_name() #fn_output {
+            ::nexus_runtime::runtime::TestRuntime::builder()
+                #start_paused
+                #seed
+                .build()
+                .block_on(async move #fn_block)
+        }
+    };
+
+    TokenStream::from(expanded)
+}
@@ -0,0 +1,41 @@
+//! Tests for the #[test] attribute
+//! #[test]属性的测试
+
+use std::sync::{Arc, Mutex};
+
+use nexus_runtime::time::{self, Duration, sleep, timeout};
+
+#[nexus_macros::test]
+async fn test_clock_starts_paused() {
+    let start = time::now();
+    let result = timeout(Duration::from_secs(600), std::future::pending::<()>()).await;
+    assert!(result.is_err());
+    assert_eq!(time::now() - start, Duration::from_secs(600));
+}
+
+#[nexus_macros::test(seed = 3)]
+async fn test_advance_wakes_spawned_sleepers() {
+    let woken = Arc::new(Mutex::new(false));
+    let flag = woken.clone();
+    let task = nexus_runtime::spawn(async move {
+        sleep(Duration::from_secs(30)).await;
+        *flag.lock().unwrap() = true;
+    });
+
+    time::advance(Duration::from_secs(29)).await;
+    assert!(!*woken.lock().unwrap());
+    time::advance(Duration::from_secs(1)).await;
+    assert!(*woken.lock().unwrap());
+    task.await.unwrap();
+}
+
+#[nexus_macros::test(start_paused = false)]
+async fn test_real_time_clock() -> Result<(), String> {
+    let start = time::now();
+    sleep(Duration::from_millis(2)).await;
+    if time::now() - start >= Duration::from_millis(2) {
+        Ok(())
+    } else {
+        Err("clock did not move".to_string())
+    }
+}
@@ -0,0 +1,182 @@
+//! Tests for the method security attributes
+//! 方法安全属性的测试
+
+use nexus_macros::{
+    deny_all, post_authorize, post_filter, pre_authorize, pre_filter, require_role, roles_allowed,
+    secured,
+};
+use nexus_security::{Authentication, Authority, Role, SecurityContext, SecurityError};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Document {
+    id: u32,
+    owner: String,
+    public: bool,
+}
+
+fn document(id: u32, owner: &str, public: bool) -> Document {
+    Document {
+        id,
+        owner: owner.to_string(),
+        public,
+    }
+}
+
+fn login(username: &str, authorities: Vec<Authority>) -> SecurityContext {
+    SecurityContext::with_authentication(
+        Authentication::new(username, "secret")
+            .set_authenticated(true)
+            .set_authorities(authorities),
+    )
+}
+
+#[secured("ROLE_ADMIN", "reports:read")]
+async fn report() -> nexus_http::Result<&'static str> {
+    Ok("report")
+}
+
+#[roles_allowed("ADMIN", "ROLE_MODERATOR")]
+async fn moderate() -> Result<(), SecurityError> {
+    Ok(())
+}
+
+#[require_role("ADMIN")]
+async fn shutdown() -> Result<(), SecurityError> {
+    Ok(())
+}
+
+#[deny_all]
+async fn legacy() -> Result<(), SecurityError> {
+    Ok(())
+}
+
+#[post_authorize("returnObject.owner == authentication.name or hasRole('ADMIN')")]
+async fn load(id: u32) -> Result<Document, SecurityError> {
+    let owner = match id {
+        1 => "alice",
+        2 => "bob",
+        _ => return Err(SecurityError::Other(format!("no document {}", id))),
+    };
+    Ok(document(id, owner, false))
+}
+
+#[pre_filter("filterObject.owner == authentication.name")]
+async fn archive(docs: Vec<Document>) -> Result<Vec<u32>, SecurityError> {
+    Ok(docs.iter().map(|doc| doc.id).collect())
+}
+
+#[pre_filter("filterObject <= #limit", filter_target = "ids")]
+async fn purge(mut ids: Vec<u32>, limit: u32) -> Result<Vec<u32>, SecurityError> {
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+#[pre_authorize("isAuthenticated()")]
+#[post_filter("filterObject.public or filterObject.owner == principal.username")]
+async fn list(docs: Vec<Document>) -> Result<Vec<Document>, SecurityError> {
+    Ok(docs)
+}
+
+#[tokio::test]
+async fn test_role_macros() {
+    let admin = login("root", vec![Authority::Role(Role::Admin)]);
+    admin
+        .scope(async {
+            assert_eq!(report().await.unwrap(), "report");
+            assert!(moderate().await.is_ok());
+            assert!(shutdown().await.is_ok());
+            assert!(matches!(legacy().await, Err(SecurityError::AccessDenied(_))));
+        })
+        .await;
+
+    let reader = login("alice", vec![Authority::permission("reports:read")]);
+    reader
+        .scope(async {
+            assert!(report().await.is_ok());
+            assert!(matches!(moderate().await, Err(SecurityError::AccessDenied(_))));
+        })
+        .await;
+
+    let moderator = login("mod", vec![Authority::Role(Role::Moderator)]);
+    moderator
+        .scope(async {
+            assert!(moderate().await.is_ok());
+            assert!(shutdown().await.is_err());
+            let denied = report().await.unwrap_err();
+            assert_eq!(denied.status_code(), 403);
+        })
+        .await;
+}
+
+#[pre_authorize("hasRole('ADMIN')")]
+async fn audit() -> Result<(), SecurityError> {
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unauthenticated_principal_is_denied() {
+    // Roles carried by an authentication that was never verified grant nothing
+    let pending = SecurityContext::with_authentication(
+        Authentication::new("root", "secret").set_authorities(vec![
+            Authority::Role(Role::Admin),
+            Authority::permission("reports:read"),
+        ]),
+    );
+    pending
+        .scope(async {
+            assert_eq!(report().await.unwrap_err().status_code(), 403);
+            assert!(matches!(moderate().await, Err(SecurityError::AccessDenied(_))));
+            assert!(audit().await.is_err());
+        })
+        .await;
+
+    login("root", vec![Authority::Role(Role::Admin)])
+        .scope(async {
+            assert!(report().await.is_ok());
+            assert!(moderate().await.is_ok());
+            assert!(audit().await.is_ok());
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_post_authorize() {
+    login("alice", vec![Authority::Role(Role::User)])
+        .scope(async {
+            assert_eq!(load(1).await.unwrap(), document(1, "alice", false));
+            assert!(matches!(load(2).await, Err(SecurityError::AccessDenied(_))));
+            assert!(matches!(load(3).await, Err(SecurityError::Other(_))));
+        })
+        .await;
+    login("root", vec![Authority::Role(Role::Admin)])
+        .scope(async { assert!(load(2).await.is_ok()) })
+        .await;
+}
+
+#[tokio::test]
+async fn test_filters() {
+    let docs = vec![
+        document(1, "alice", false),
+        document(2, "bob", true),
+        document(3, "alice", true),
+        document(4, "bob", false),
+    ];
+
+    login("alice", vec![Authority::Role(Role::User)])
+        .scope(async {
+            assert_eq!(archive(docs.clone()).await.unwrap(), [1, 3]);
+            assert_eq!(purge(vec![9, 3, 5, 1], 5).await.unwrap(), [1, 3, 5]);
+            let ids: Vec<u32> = list(docs.clone())
+                .await
+                .unwrap()
+                .iter()
+                .map(|doc| doc.id)
+                .collect();
+            assert_eq!(ids, [1, 2, 3]);
+        })
+        .await;
+
+    assert!(archive(docs.clone()).await.unwrap().is_empty());
+    assert!(list(docs).await.is_err());
+}
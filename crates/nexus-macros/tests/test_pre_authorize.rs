@@ -0,0 +1,60 @@
+//! Tests for the #[pre_authorize] attribute
+//! #[pre_authorize]属性的测试
+
+use nexus_macros::pre_authorize;
+use nexus_security::{Authentication, Authority, Role, SecurityContext, SecurityError};
+
+fn login(username: &str, authorities: Vec<Authority>) -> SecurityContext {
+    SecurityContext::with_authentication(
+        Authentication::new(username, "secret")
+            .set_authenticated(true)
+            .set_authorities(authorities),
+    )
+}
+
+#[pre_authorize("hasRole('ADMIN') or #username == authentication.name")]
+async fn profile(username: String) -> Result<String, SecurityError> {
+    Ok(format!("profile of {}", username))
+}
+
+#[pre_authorize("hasAuthority('payments:write') && (#amount <= 100 || hasRole(\"ADMIN\"))")]
+async fn pay((_from, _to): (u64, u64), amount: u32) -> Result<u32, SecurityError> {
+    Ok(amount)
+}
+
+struct Reports;
+
+impl Reports {
+    #[pre_authorize("isAuthenticated() and not isAnonymous()")]
+    async fn list(&self) -> Result<Vec<&'static str>, SecurityError> {
+        Ok(vec!["q1", "q2"])
+    }
+}
+
+#[tokio::test]
+async fn test_pre_authorize() {
+    let alice = login("alice", vec![Authority::Role(Role::User)]);
+    alice
+        .scope(async {
+            assert_eq!(profile("alice".to_string()).await.unwrap(), "profile of alice");
+            assert!(matches!(
+                profile("bob".to_string()).await,
+                Err(SecurityError::AccessDenied(_))
+            ));
+            assert_eq!(Reports.list().await.unwrap(), ["q1", "q2"]);
+        })
+        .await;
+
+    let admin = login("root", vec![Authority::Role(Role::Admin)]);
+    assert!(admin.scope(profile("bob".to_string())).await.is_ok());
+
+    let payer = login("carol", vec![Authority::permission("payments:write")]);
+    payer
+        .scope(async {
+            assert_eq!(pay((1, 2), 100).await.unwrap(), 100);
+            assert!(pay((1, 2), 101).await.is_err());
+        })
+        .await;
+
+    assert!(Reports.list().await.is_err());
+}
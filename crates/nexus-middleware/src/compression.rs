@@ -254,7 +254,9 @@ impl CompressionMiddleware {
     /// Check if response body size meets minimum size requirement
     /// 检查响应体大小是否满足最小大小要求
     fn meets_min_size(&self, body: &Body) -> bool {
-        body.data().len() >= self.min_response_size
+        // Streaming bodies are passed through untouched
+        // 流式正文原样传递
+        !body.is_stream() && body.data().len() >= self.min_response_size
     }
 
     /// Compress response body using the specified compression type
@@ -18,6 +18,14 @@
 //! let app = Router::new()
 //!     .middleware(jwt_middleware)
 //!     .get("/api/users", get_users);
+//!
+//! // Verify RS256 tokens against an identity provider's JWK Set
+//! // 使用身份提供者的JWK Set验证RS256 token
+//! let decoder = JwtDecoder::builder()
+//!     .jwk_set_uri("https://idp.example.com/.well-known/jwks.json")
+//!     .issuer("https://idp.example.com")
+//!     .build()?;
+//! let jwt_middleware = JwtAuthenticationMiddleware::new().with_decoder(decoder);
 //! ```
 
 use std::pin::Pin;
@@ -26,13 +34,20 @@ use std::future::Future;
 
 use crate::{Error, Request, Response, Result};
 use nexus_router::{Middleware, Next};
-use nexus_security::{JwtAuthentication, JwtClaims, JwtUtil, SecurityError};
+use nexus_security::{
+    Authentication, JwtAuthentication, JwtClaims, JwtDecoder, SecurityContextExt, SecurityError,
+};
 
 /// JWT authentication middleware
 /// JWT 认证中间件
 ///
-/// Extracts and validates JWT tokens from the Authorization header.
-/// 从Authorization头中提取并验证JWT token。
+/// Extracts and validates JWT tokens from the Authorization header, stores the
+/// resulting authentication in a [`SecurityContext`](nexus_security::SecurityContext)
+/// attached to the request, and runs the rest of the chain inside that context's
+/// scope so [`nexus_security::context()`] sees it.
+/// 从Authorization头中提取并验证JWT token，将得到的认证存入附加到请求的
+/// [`SecurityContext`](nexus_security::SecurityContext)，并在该上下文的作用域内运行后续链，
+/// 使 [`nexus_security::context()`] 能看到它。
 ///
 /// # Spring Equivalent / Spring等价物
 ///
@@ -79,6 +94,10 @@ pub struct JwtAuthenticationMiddleware {
     /// Skip authentication for these paths
     /// 跳过这些路径的认证
     skip_paths: Vec<String>,
+
+    /// Token decoder (default: HS256 with `JWT_SECRET`)
+    /// Token解码器（默认：使用 `JWT_SECRET` 的HS256）
+    decoder: Arc<JwtDecoder>,
 }
 
 impl JwtAuthenticationMiddleware {
@@ -99,9 +118,24 @@ impl JwtAuthenticationMiddleware {
                 "/api/auth/register".to_string(),
                 "/health".to_string(),
             ],
+            decoder: Arc::new(JwtDecoder::from_env()),
         }
     }
 
+    /// Set the decoder that verifies tokens
+    /// 设置验证token的解码器
+    ///
+    /// # Example / 示例
+    ///
+    /// ```rust,ignore
+    /// let decoder = JwtDecoder::from_properties(&JwtProperties::from_config(&config)?)?;
+    /// let middleware = JwtAuthenticationMiddleware::new().with_decoder(decoder);
+    /// ```
+    pub fn with_decoder(mut self, decoder: JwtDecoder) -> Self {
+        self.decoder = Arc::new(decoder);
+        self
+    }
+
     /// Set custom token header name
     /// 设置自定义token头名称
     ///
@@ -207,21 +241,23 @@ where
 {
     fn call(
         &self,
-        req: Request,
+        mut req: Request,
         state: Arc<S>,
         next: Next<S>,
     ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send>> {
         let token_header = self.token_header.clone();
         let token_prefix = self.token_prefix.clone();
         let skip_paths = self.skip_paths.clone();
+        let decoder = self.decoder.clone();
 
         Box::pin(async move {
-            let path = req.path();
+            let path = req.path().to_owned();
+            let ctx = req.init_security_context();
 
-            // Skip authentication for certain paths
-            if skip_paths.iter().any(|p| path == p || path.starts_with(&format!("{}/", p))) {
+            // Skip authentication for certain paths, still with a context of their own
+            if skip_paths.iter().any(|p| path == *p || path.starts_with(&format!("{}/", p))) {
                 tracing::debug!("Skipping authentication for path: {}", path);
-                return next.call(req, state).await;
+                return ctx.scope(next.call(req, state)).await;
             }
 
             // Extract JWT token from headers
@@ -242,7 +278,7 @@ where
             };
 
             // Verify and parse JWT token
-            let _claims: JwtClaims = match JwtUtil::verify_token(&token) {
+            let claims: JwtClaims = match decoder.decode(&token).await {
                 Ok(claims) => {
                     tracing::debug!("JWT verified for user: {}", claims.username);
                     claims
@@ -261,12 +297,12 @@ where
                 }
             };
 
-            // Note: JWT claims extraction successful
-            // In a full implementation, authentication would be stored in request extensions
-            let _ = _claims;
+            let jwt = JwtAuthentication::from_claims(&claims);
+            ctx.set_authentication(Authentication::from_jwt(&jwt)).await;
+            req.extensions_mut().insert(jwt);
 
-            // Continue with the request
-            next.call(req, state).await
+            // Continue with the request inside its security context
+            ctx.scope(next.call(req, state)).await
         })
     }
 }
@@ -347,6 +383,10 @@ impl JwtRequestExt for Request {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nexus_http::testing::TestClient;
+    use nexus_http::{Body, StatusCode};
+    use nexus_router::Router;
+    use nexus_security::{Authority, JwtEncoder, JwtUtil, Role};
 
     #[test]
     fn test_extract_token() {
@@ -413,4 +453,85 @@ mod tests {
         assert!(middleware.should_skip_auth("/api/docs"));
         assert!(!middleware.should_skip_auth("/api/users"));
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_authentication_is_scoped_to_request() {
+        let router = Router::new()
+            .middleware(Arc::new(JwtAuthenticationMiddleware::new()))
+            .get("/api/me", |req: Request| async move {
+                tokio::task::yield_now().await;
+                let ctx = nexus_security::context().unwrap();
+                let body = format!(
+                    "{:?} {:?} {}",
+                    ctx.get_username().await,
+                    req.get_current_username(),
+                    ctx.has_role(&Role::Admin).await
+                );
+                Ok(Response::ok().with_body(Body::from(body)))
+            })
+            .get("/health", |_req: Request| async move {
+                let authenticated = match nexus_security::context() {
+                    Some(ctx) => ctx.is_authenticated().await,
+                    None => false,
+                };
+                Ok(Response::ok().with_body(Body::from(authenticated.to_string())))
+            });
+        let client = TestClient::new(router);
+        let alice = JwtUtil::create_token("1", "alice", &[Authority::Role(Role::Admin)]).unwrap();
+        let bob = JwtUtil::create_token("2", "bob", &[Authority::Role(Role::User)]).unwrap();
+
+        for _ in 0..8 {
+            let (first, second) = tokio::join!(
+                client.get("/api/me").header("authorization", format!("Bearer {alice}")),
+                client.get("/api/me").header("authorization", format!("Bearer {bob}")),
+            );
+            first.assert_body(r#"Some("alice") Some("alice") true"#);
+            second.assert_body(r#"Some("bob") Some("bob") false"#);
+        }
+
+        client
+            .get("/health")
+            .await
+            .assert_body("false");
+        client
+            .get("/api/me")
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_with_decoder() {
+        let encoder = JwtEncoder::builder()
+            .secret("tenant-secret")
+            .issuer("https://idp.example.com")
+            .build()
+            .unwrap();
+        let decoder = JwtDecoder::builder()
+            .secret("tenant-secret")
+            .issuer("https://idp.example.com")
+            .build()
+            .unwrap();
+        let router = Router::new()
+            .middleware(Arc::new(JwtAuthenticationMiddleware::new().with_decoder(decoder)))
+            .get("/api/me", |req: Request| async move {
+                let username = req.get_current_username().unwrap_or_default().to_string();
+                Ok(Response::ok().with_body(Body::from(username)))
+            });
+        let client = TestClient::new(router);
+
+        let token = encoder.create_token("1", "alice", &[]).unwrap();
+        client
+            .get("/api/me")
+            .header("authorization", format!("Bearer {token}"))
+            .await
+            .assert_body("alice");
+
+        // Tokens of the default JWT_SECRET no longer pass
+        let token = JwtUtil::create_token("1", "alice", &[]).unwrap();
+        client
+            .get("/api/me")
+            .header("authorization", format!("Bearer {token}"))
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
 }
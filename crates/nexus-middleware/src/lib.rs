@@ -12,6 +12,7 @@
 //! - Filter, HandlerInterceptor
 //! - @CrossOrigin
 //! - OncePerRequestFilter
+//! - oauth2ResourceServer().opaqueToken()
 //! - CorsConfiguration, CORS filter
 //! - Request logging / MDC
 
@@ -28,6 +29,7 @@ pub mod cors;
 pub mod jwt_auth;
 pub mod logger;
 pub mod middleware;
+pub mod opaque_token_auth;
 pub mod static_files;
 pub mod timeout;
 
@@ -50,5 +52,6 @@ pub use cors::{CorsConfig, CorsMiddleware};
 pub use jwt_auth::{JwtAuthenticationMiddleware, JwtRequestExt};
 pub use logger::LoggerMiddleware;
 pub use middleware::MiddlewareStack;
+pub use opaque_token_auth::OpaqueTokenAuthenticationMiddleware;
 pub use static_files::StaticFiles;
 pub use timeout::TimeoutMiddleware;
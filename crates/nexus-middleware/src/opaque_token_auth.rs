@@ -0,0 +1,190 @@
+//! Opaque Token Authentication Middleware
+//! 不透明Token认证中间件
+//!
+//! # Equivalent to Spring Boot / 等价于 Spring Boot
+//!
+//! - `oauth2ResourceServer().opaqueToken()` - OAuth2 resource server with introspection
+//! - `BearerTokenAuthenticationFilter` - Bearer token filter
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_middleware::OpaqueTokenAuthenticationMiddleware;
+//! use nexus_security::{OpaqueTokenAuthenticationProvider, RemoteOpaqueTokenIntrospector};
+//! use std::sync::Arc;
+//!
+//! let provider = OpaqueTokenAuthenticationProvider::new(RemoteOpaqueTokenIntrospector::new(
+//!     "https://idp.example.com/oauth2/introspect",
+//!     "orders-api",
+//!     "secret",
+//! ));
+//!
+//! let app = Router::new()
+//!     .middleware(Arc::new(OpaqueTokenAuthenticationMiddleware::new(provider)))
+//!     .get("/api/orders", list_orders);
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::{Error, Request, Response, Result};
+use nexus_router::{Middleware, Next};
+use nexus_security::{OpaqueTokenAuthenticationProvider, SecurityContextExt, SecurityError};
+
+/// Opaque token authentication middleware
+/// 不透明token认证中间件
+///
+/// Introspects `Authorization: Bearer <token>` at the authorization server and
+/// runs the rest of the chain inside the request's
+/// [`SecurityContext`](nexus_security::SecurityContext), like
+/// [`JwtAuthenticationMiddleware`](crate::JwtAuthenticationMiddleware).
+/// 在授权服务器内省 `Authorization: Bearer <token>`，并像
+/// [`JwtAuthenticationMiddleware`](crate::JwtAuthenticationMiddleware) 一样在请求的
+/// [`SecurityContext`](nexus_security::SecurityContext) 内运行后续链。
+#[derive(Clone)]
+pub struct OpaqueTokenAuthenticationMiddleware {
+    /// Introspection-backed provider
+    /// 基于内省的提供者
+    provider: Arc<OpaqueTokenAuthenticationProvider>,
+
+    /// Skip authentication for these paths
+    /// 跳过这些路径的认证
+    skip_paths: Vec<String>,
+}
+
+impl OpaqueTokenAuthenticationMiddleware {
+    /// Create a new opaque token authentication middleware
+    /// 创建新的不透明token认证中间件
+    pub fn new(provider: OpaqueTokenAuthenticationProvider) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            skip_paths: vec!["/health".to_string()],
+        }
+    }
+
+    /// Add a path to skip authentication
+    /// 添加跳过认证的路径
+    pub fn skip_path(mut self, path: impl Into<String>) -> Self {
+        self.skip_paths.push(path.into());
+        self
+    }
+
+    /// Check if request path should skip authentication
+    /// 检查请求路径是否应该跳过认证
+    fn should_skip_auth(&self, path: &str) -> bool {
+        self.skip_paths
+            .iter()
+            .any(|skip_path| path == skip_path || path.starts_with(&format!("{}/", skip_path)))
+    }
+}
+
+impl<S> Middleware<S> for OpaqueTokenAuthenticationMiddleware
+where
+    S: Send + Sync + 'static,
+{
+    fn call(
+        &self,
+        mut req: Request,
+        state: Arc<S>,
+        next: Next<S>,
+    ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send>> {
+        let skip = self.should_skip_auth(req.path());
+        let provider = self.provider.clone();
+
+        Box::pin(async move {
+            let ctx = req.init_security_context();
+            if skip {
+                return ctx.scope(next.call(req, state)).await;
+            }
+
+            let Some(token) = req
+                .header("authorization")
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(str::to_owned)
+            else {
+                tracing::warn!("Missing bearer token for path: {}", req.path());
+                return Err(Error::unauthorized());
+            };
+
+            match provider.authenticate(&token).await {
+                Ok(auth) => {
+                    tracing::debug!("Token introspected for user: {}", auth.principal);
+                    ctx.set_authentication(auth).await;
+                },
+                Err(SecurityError::InvalidToken(msg) | SecurityError::TokenExpired(msg)) => {
+                    tracing::warn!("Rejected bearer token: {}", msg);
+                    return Err(Error::unauthorized());
+                },
+                Err(e) => {
+                    tracing::error!("Token introspection error: {:?}", e);
+                    return Err(Error::internal("Authentication error"));
+                },
+            }
+
+            ctx.scope(next.call(req, state)).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use nexus_http::testing::TestClient;
+    use nexus_http::{Body, StatusCode};
+    use nexus_router::Router;
+    use nexus_security::{IntrospectionResponse, OpaqueTokenIntrospector, SecurityResult};
+
+    struct FixedIntrospector;
+
+    #[async_trait]
+    impl OpaqueTokenIntrospector for FixedIntrospector {
+        async fn introspect(&self, token: &str) -> SecurityResult<IntrospectionResponse> {
+            match token {
+                "alice-token" => Ok(IntrospectionResponse {
+                    active: true,
+                    scope: Some("orders.read".to_string()),
+                    username: Some("alice".to_string()),
+                    ..IntrospectionResponse::default()
+                }),
+                "unreachable" => Err(SecurityError::OAuth2("connection refused".to_string())),
+                _ => Ok(IntrospectionResponse::default()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_opaque_token_authentication() {
+        let router = Router::new()
+            .middleware(Arc::new(OpaqueTokenAuthenticationMiddleware::new(
+                OpaqueTokenAuthenticationProvider::new(FixedIntrospector),
+            )))
+            .get("/api/me", |_req: Request| async move {
+                let ctx = nexus_security::context().unwrap();
+                let body = format!("{:?}", ctx.get_username().await);
+                Ok(Response::ok().with_body(Body::from(body)))
+            });
+        let client = TestClient::new(router);
+
+        client
+            .get("/api/me")
+            .header("authorization", "Bearer alice-token")
+            .await
+            .assert_body(r#"Some("alice")"#);
+        client
+            .get("/api/me")
+            .header("authorization", "Bearer revoked-token")
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        client
+            .get("/api/me")
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        client
+            .get("/api/me")
+            .header("authorization", "Bearer unreachable")
+            .await
+            .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
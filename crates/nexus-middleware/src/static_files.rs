@@ -7,7 +7,6 @@
 #![warn(missing_docs)]
 #![warn(unreachable_pub)]
 
-use std::fs;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
@@ -15,6 +14,11 @@ use std::sync::Arc;
 
 use nexus_http::{Body, Error, Request, Response, Result, StatusCode};
 use nexus_router::{Middleware, Next};
+use nexus_runtime::fs::{self, File};
+
+/// Files larger than this are sent from disk instead of read into memory
+/// 大于此大小的文件从磁盘发送，而不是读入内存
+const STREAM_THRESHOLD: u64 = 64 * 1024;
 
 /// Static file serving configuration
 /// 静态文件服务配置
@@ -142,9 +146,14 @@ impl StaticFiles {
 
     /// Serve a file
     /// 服务文件
-    fn serve_file(&self, file_path: &Path) -> Result<Response> {
-        // Read file contents
-        let contents = fs::read(file_path)
+    ///
+    /// Small files are read into memory; larger ones are sent from disk with a
+    /// `content-length` header, using `sendfile(2)` where the connection allows it.
+    /// 小文件读入内存；较大的文件带 `content-length` 头从磁盘发送，在连接允许时使用
+    /// `sendfile(2)`。
+    async fn serve_file(&self, file_path: &Path) -> Result<Response> {
+        let metadata = fs::metadata(file_path)
+            .await
             .map_err(|e| Error::internal(format!("Failed to read file: {}", e)))?;
 
         // Get content type
@@ -160,13 +169,28 @@ impl StaticFiles {
             builder = builder.header("cache-control", cache);
         }
 
-        Ok(builder.body(Body::from(contents)).unwrap())
+        let body = if metadata.len() <= STREAM_THRESHOLD {
+            // Read file contents
+            let contents = fs::read(file_path)
+                .await
+                .map_err(|e| Error::internal(format!("Failed to read file: {}", e)))?;
+            Body::from(contents)
+        } else {
+            let file = File::open(file_path)
+                .await
+                .map_err(|e| Error::internal(format!("Failed to open file: {}", e)))?;
+            builder = builder.header("content-length", metadata.len().to_string());
+            Body::from_file(file, metadata.len())
+        };
+
+        Ok(builder.body(body).unwrap())
     }
 
     /// Serve directory listing
     /// 服务目录列表
-    fn serve_listing(&self, dir_path: &Path, request_path: &str) -> Result<Response> {
+    async fn serve_listing(&self, dir_path: &Path, request_path: &str) -> Result<Response> {
         let entries = fs::read_dir(dir_path)
+            .await
             .map_err(|e| Error::internal(format!("Failed to read directory: {}", e)))?;
 
         let mut html = String::from(
@@ -201,9 +225,7 @@ impl StaticFiles {
         }
 
         // Directory entries
-        for entry_result in entries {
-            let entry = entry_result
-                .map_err(|e| Error::internal(format!("Failed to read entry: {}", e)))?;
+        for entry in entries {
             let name = entry.file_name().to_string_lossy().to_string();
             let is_dir = entry
                 .file_type()
@@ -270,15 +292,12 @@ where
             }
 
             // Check for path traversal attacks
-            if file_path
-                .canonicalize()
-                .map(|p| {
-                    !p.starts_with(
-                        &base_path
-                            .canonicalize()
-                            .unwrap_or_else(|_| base_path.clone()),
-                    )
-                })
+            let canonical_base = fs::canonicalize(&base_path)
+                .await
+                .unwrap_or_else(|_| base_path.clone());
+            if fs::canonicalize(&file_path)
+                .await
+                .map(|p| !p.starts_with(&canonical_base))
                 .unwrap_or(false)
             {
                 return Ok(Response::builder()
@@ -288,13 +307,13 @@ where
             }
 
             // Check if file exists
-            if !file_path.exists() {
+            let Ok(metadata) = fs::metadata(&file_path).await else {
                 // SPA mode: serve index.html for non-existent files
                 if spa_mode {
                     if let Some(ref index) = index_file {
                         file_path = base_path.clone();
                         file_path.push(index);
-                        if file_path.exists() {
+                        if fs::metadata(&file_path).await.is_ok() {
                             return Self {
                                 uri_prefix,
                                 base_path,
@@ -304,19 +323,20 @@ where
                                 show_listing,
                                 cache_control,
                             }
-                            .serve_file(&file_path);
+                            .serve_file(&file_path)
+                            .await;
                         }
                     }
                 }
                 return next.call(req, state).await;
-            }
+            };
 
             // Check if it's a directory
-            if file_path.is_dir() {
+            if metadata.is_dir() {
                 // Try to serve index file
                 if let Some(ref index) = index_file {
                     let index_path = file_path.join(index);
-                    if index_path.exists() {
+                    if fs::metadata(&index_path).await.is_ok() {
                         return Self {
                             uri_prefix,
                             base_path,
@@ -326,7 +346,8 @@ where
                             show_listing,
                             cache_control,
                         }
-                        .serve_file(&index_path);
+                        .serve_file(&index_path)
+                        .await;
                     }
                 }
 
@@ -341,7 +362,8 @@ where
                         show_listing,
                         cache_control,
                     }
-                    .serve_listing(&file_path, path);
+                    .serve_listing(&file_path, path)
+                    .await;
                 }
 
                 // Otherwise, try next
@@ -367,7 +389,7 @@ where
             }
 
             // Serve the file
-            this.serve_file(&file_path)
+            this.serve_file(&file_path).await
         })
     }
 }
@@ -427,4 +449,40 @@ mod tests {
         assert_eq!(StaticFiles::get_content_type(Path::new("test.json")), "application/json");
         assert_eq!(StaticFiles::get_content_type(Path::new("test.png")), "image/png");
     }
+
+    #[test]
+    fn test_serve_large_file_streams() {
+        let dir = std::env::temp_dir().join(format!("nexus-static-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("large.bin");
+        let contents: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &contents).unwrap();
+
+        let response =
+            futures::executor::block_on(StaticFiles::new("/static", &dir).serve_file(&path))
+                .unwrap();
+        assert!(response.body().is_stream());
+        assert_eq!(response.header("content-length"), Some("200000"));
+
+        let body = futures::executor::block_on(response.into_body().collect()).unwrap();
+        assert_eq!(&body[..], &contents[..]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_serve_small_file_in_memory() {
+        let dir = std::env::temp_dir().join(format!("nexus-static-small-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("index.html");
+        std::fs::write(&path, "<h1>hi</h1>").unwrap();
+
+        let response =
+            futures::executor::block_on(StaticFiles::new("/static", &dir).serve_file(&path))
+                .unwrap();
+        assert!(!response.body().is_stream());
+        assert_eq!(response.body().data().as_ref(), b"<h1>hi</h1>");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
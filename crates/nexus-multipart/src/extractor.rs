@@ -385,16 +385,19 @@ pub async fn extract_multipart(
         ));
     }
 
-    // Get body bytes directly from the request
-    // 直接从请求获取主体字节
+    // Read the body from the request
+    // 从请求读取主体
     //
-    // In nexus-http, Request::body() returns &Body (which is FullBody).
-    // FullBody has a data() method that returns &Bytes.
-    // We clone the bytes since Bytes is reference-counted (efficient clone).
-    // 在 nexus-http 中，Request::body() 返回 &Body（即 FullBody）。
-    // FullBody 有一个 data() 方法返回 &Bytes。
-    // 我们克隆字节，因为 Bytes 是引用计数的（高效克隆）。
-    let body_bytes = req.body().data().clone();
+    // The server streams bodies that did not arrive with the request head, so the
+    // body is collected here; a buffered body is returned without copying.
+    // 服务器会流式传输未随请求头一起到达的主体，因此在此收集主体；
+    // 已缓冲的主体无需复制即可返回。
+    let body_bytes = req
+        .body()
+        .clone()
+        .collect()
+        .await
+        .map_err(|e| MultipartError::InvalidRequest(format!("Failed to read body: {}", e)))?;
 
     // Create multipart with the extracted data
     // 使用提取的数据创建 multipart
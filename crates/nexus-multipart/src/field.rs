@@ -112,7 +112,7 @@ impl MultipartFile {
     /// Save the file to a path
     /// 保存文件到路径
     pub async fn save_to<P: AsRef<Path>>(&self, path: P) -> MultipartResult<()> {
-        tokio::fs::write(path, &self.data).await?;
+        nexus_runtime::fs::write(path, &self.data).await?;
         Ok(())
     }
 
@@ -217,6 +217,21 @@ mod tests {
         assert_eq!(file.text().unwrap(), "Hello, World!");
     }
 
+    #[test]
+    fn test_multipart_file_save_to() {
+        let file = MultipartFile::new(
+            "upload".to_string(),
+            Some("test.txt".to_string()),
+            Some("text/plain".to_string()),
+            Bytes::from("Hello, World!"),
+        );
+        let path = std::env::temp_dir().join(format!("nexus-multipart-{}", std::process::id()));
+
+        futures::executor::block_on(file.save_to(&path)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"Hello, World!");
+        std::fs::remove_file(path).unwrap();
+    }
+
     #[test]
     fn test_multipart_field() {
         let field = MultipartField::new("name".to_string(), None, Some("text/plain".to_string()));
@@ -13,6 +13,7 @@
 
 pub mod log;
 pub mod metrics;
+pub mod runtime;
 pub mod trace;
 
 #[cfg(feature = "nexus-format")]
@@ -0,0 +1,183 @@
+//! Runtime metrics export
+//! 运行时指标导出
+//!
+//! # Overview / 概述
+//!
+//! Copies the introspection counters of `nexus-runtime` into a
+//! [`MetricsRegistry`]. The runtime counters are cumulative, so exporting is a
+//! snapshot: call [`export_runtime_metrics`] (and [`export_worker_stats`] for
+//! each work-stealing scheduler) right before scraping.
+//!
+//! 将 `nexus-runtime` 的自省计数器复制到 [`MetricsRegistry`]。运行时计数器是累计值，
+//! 因此导出是一次快照：在抓取之前调用 [`export_runtime_metrics`]
+//! （并为每个工作窃取调度器调用 [`export_worker_stats`]）。
+//!
+//! # Equivalent to Spring Boot / 等价于 Spring Boot
+//!
+//! - Micrometer `ExecutorServiceMetrics`, `JvmThreadMetrics`
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_observability::metrics::global_registry;
+//! use nexus_observability::runtime::{export_runtime_metrics, export_worker_stats};
+//!
+//! router.get("/metrics", move || async move {
+//!     let registry = global_registry();
+//!     export_runtime_metrics(registry);
+//!     export_worker_stats(registry, &scheduler.worker_stats());
+//!     registry.export_prometheus()
+//! });
+//! ```
+
+use nexus_runtime::DriverType;
+use nexus_runtime::metrics::{self as runtime_metrics, TimerStats, WorkerStats};
+use nexus_runtime::time::global_timer;
+
+use crate::metrics::{Counter, MetricsRegistry};
+
+/// Move a counter up to a cumulative runtime value
+/// 将计数器推进到运行时的累计值
+fn sync_counter(counter: &Counter, value: u64) {
+    let current = counter.get();
+    if value < current {
+        // The source restarted / 数据源已重启
+        counter.reset();
+        counter.increment_by(value);
+    } else {
+        counter.increment_by(value - current);
+    }
+}
+
+fn labels(pairs: &[(&str, String)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(key, value)| ((*key).to_string(), value.clone()))
+        .collect()
+}
+
+/// Export per-worker statistics of a work-stealing scheduler
+/// 导出工作窃取调度器每个工作器的统计信息
+///
+/// Every series carries a `worker` label. The poll-time histogram is exported as
+/// cumulative `nexus_runtime_worker_poll_time_seconds_bucket` series with an
+/// `le` label.
+/// 每个序列都带有 `worker` 标签。轮询耗时直方图导出为带 `le` 标签的累计
+/// `nexus_runtime_worker_poll_time_seconds_bucket` 序列。
+pub fn export_worker_stats(registry: &MetricsRegistry, stats: &[WorkerStats]) {
+    for worker in stats {
+        let worker_label = labels(&[("worker", worker.worker_id.to_string())]);
+        let counter = |name: &str, value: u64| {
+            sync_counter(&registry.counter_with_labels(name, worker_label.clone()), value);
+        };
+        counter("nexus_runtime_worker_polls_total", worker.tasks_polled);
+        counter("nexus_runtime_worker_steals_total", worker.steals);
+        counter("nexus_runtime_worker_parks_total", worker.parks);
+        counter("nexus_runtime_worker_unparks_total", worker.unparks);
+        counter("nexus_runtime_worker_busy_microseconds_total", worker.busy.as_micros() as u64);
+        counter("nexus_runtime_worker_poll_time_seconds_count", worker.poll_time.count());
+
+        registry
+            .gauge_with_labels("nexus_runtime_worker_queue_depth", worker_label.clone())
+            .set(worker.queue_depth as i64);
+        registry
+            .gauge_with_labels("nexus_runtime_worker_busy_ratio_percent", worker_label.clone())
+            .set((worker.busy_ratio * 100.0).round() as i64);
+
+        for (bound, count) in worker.poll_time.cumulative() {
+            let le =
+                bound.map_or_else(|| "+Inf".to_string(), |bound| bound.as_secs_f64().to_string());
+            sync_counter(
+                &registry.counter_with_labels(
+                    "nexus_runtime_worker_poll_time_seconds_bucket",
+                    labels(&[("worker", worker.worker_id.to_string()), ("le", le)]),
+                ),
+                count,
+            );
+        }
+    }
+}
+
+/// Export timer wheel occupancy
+/// 导出时间轮占用情况
+pub fn export_timer_stats(registry: &MetricsRegistry, stats: &TimerStats) {
+    registry
+        .gauge("nexus_runtime_timers_active")
+        .set(stats.active as i64);
+    for (level, count) in stats.per_level.iter().enumerate() {
+        registry
+            .gauge_with_labels(
+                "nexus_runtime_timers_level_active",
+                labels(&[("level", level.to_string())]),
+            )
+            .set(*count as i64);
+    }
+    sync_counter(&registry.counter("nexus_runtime_timers_expired_total"), stats.expired);
+}
+
+/// Export event counts of every I/O driver backend
+/// 导出每个I/O驱动后端的事件计数
+pub fn export_driver_stats(registry: &MetricsRegistry) {
+    for (backend, name) in [
+        (DriverType::Epoll, "epoll"),
+        (DriverType::IOUring, "io_uring"),
+        (DriverType::Kqueue, "kqueue"),
+    ] {
+        let stats = runtime_metrics::driver_stats(backend);
+        let backend_label = labels(&[("backend", name.to_string())]);
+        for (metric, value) in [
+            ("nexus_runtime_driver_submissions_total", stats.submissions),
+            ("nexus_runtime_driver_waits_total", stats.waits),
+            ("nexus_runtime_driver_completions_total", stats.completions),
+        ] {
+            sync_counter(&registry.counter_with_labels(metric, backend_label.clone()), value);
+        }
+    }
+}
+
+/// Export the global timer wheel, the I/O drivers and the live task count
+/// 导出全局时间轮、I/O驱动和存活任务数
+pub fn export_runtime_metrics(registry: &MetricsRegistry) {
+    export_timer_stats(registry, &global_timer().stats());
+    export_driver_stats(registry);
+    registry
+        .gauge("nexus_runtime_tasks_live")
+        .set(runtime_metrics::live_tasks() as i64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nexus_runtime::WorkStealingConfig;
+    use nexus_runtime::time::{Duration, TimerWheel};
+
+    #[test]
+    fn test_sync_counter_follows_source() {
+        let counter = Counter::new("test_total");
+        sync_counter(&counter, 5);
+        sync_counter(&counter, 8);
+        assert_eq!(counter.get(), 8);
+        sync_counter(&counter, 2);
+        assert_eq!(counter.get(), 2);
+    }
+
+    #[test]
+    fn test_export_worker_and_timer_stats() {
+        let registry = MetricsRegistry::new();
+        let scheduler = WorkStealingConfig::new().worker_threads(2).build().unwrap();
+        export_worker_stats(&registry, &scheduler.worker_stats());
+        export_runtime_metrics(&registry);
+
+        let wheel = TimerWheel::new();
+        let _timer = wheel.insert_timer(Duration::from_millis(5));
+        export_timer_stats(&registry, &wheel.stats());
+
+        let output = registry.export_prometheus();
+        assert!(output.contains("nexus_runtime_worker_polls_total{worker=\"1\"}"));
+        assert!(output.contains("nexus_runtime_worker_queue_depth{worker=\"0\"} 0"));
+        assert!(output.contains("le=\"+Inf\""));
+        assert!(output.contains("nexus_runtime_driver_waits_total{backend=\"epoll\"}"));
+        assert!(output.contains("nexus_runtime_tasks_live"));
+        assert_eq!(registry.gauge("nexus_runtime_timers_active").get(), 1);
+    }
+}
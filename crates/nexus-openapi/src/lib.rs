@@ -60,6 +60,7 @@
 //! - [`swagger`] - Swagger UI integration / Swagger UI 集成
 //! - [`http`] - HTTP framework integration / HTTP 框架集成
 //! - [`macros`] - Re-exported utoipa macros / 重新导出的 utoipa 宏
+//! - `routing` - Paths from `nexus-router` route descriptors (`router` feature) / 从 `nexus-router` 路由描述符生成路径（`router` 特性）
 //!
 //! # Examples / 示例
 //!
@@ -78,6 +79,8 @@ pub mod openapi;
 pub mod swagger;
 pub mod http;
 pub mod macros;
+#[cfg(feature = "router")]
+pub mod routing;
 
 pub use config::{OpenApiConfig, ServerConfig, ContactConfig, LicenseConfig, InfoConfig, TagConfig, ExternalDocsConfig};
 pub use schema::{Schema, SchemaType, SchemaFormat, SchemaProperty};
@@ -87,6 +90,8 @@ pub use path::{PathItem, PathMethod, PathOperation, Components};
 pub use openapi::OpenApi;
 pub use swagger::{SwaggerUi, SwaggerConfig, ModelRendering, SyntaxHighlightTheme};
 pub use http::{OpenApiHandler, OpenApiResponse, OpenApiRoutes, OpenApiRouter};
+#[cfg(feature = "router")]
+pub use routing::paths_from_routes;
 
 /// Version of the OpenAPI module
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
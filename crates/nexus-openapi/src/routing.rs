@@ -0,0 +1,184 @@
+//! Routing table integration for OpenAPI
+//! OpenAPI 的路由表集成
+//!
+//! Turns the [`RouteDescriptor`]s of `nexus-router` into [`PathItem`]s, so the
+//! documented paths follow the real routing table. Requires the `router` feature.
+//! 将 `nexus-router` 的 [`RouteDescriptor`] 转换为 [`PathItem`]，使文档中的路径与实际路由表一致。
+//! 需要 `router` 特性。
+//!
+//! # Example / 示例
+//!
+//! ```rust,ignore
+//! use nexus_openapi::{OpenApi, OpenApiConfig};
+//!
+//! let router = Router::new().get("/users/{id:u64}", get_user);
+//! let openapi = OpenApi::new(OpenApiConfig::default()).add_routes(&router.descriptors());
+//! ```
+
+use crate::{OpenApi, Operation, Parameter, PathItem, Response, Schema, SchemaFormat};
+use nexus_router::{Method, ParamKind, RouteDescriptor};
+use std::collections::HashMap;
+
+/// Build path items from route descriptors
+/// 从路由描述符构建路径项
+///
+/// Patterns become `{name}` templates; a pattern with optional segments yields
+/// one path per combination. Constrained parameters get a matching schema.
+/// 模式转换为 `{name}` 模板；带可选段的模式为每种组合生成一个路径。带约束的参数获得对应的模式。
+pub fn paths_from_routes(routes: &[RouteDescriptor]) -> HashMap<String, PathItem> {
+    let mut paths = HashMap::new();
+    add_operations(&mut paths, routes);
+    paths
+}
+
+impl OpenApi {
+    /// Add the routes of a router, keeping operations that are already documented
+    /// 添加路由器的路由，保留已有文档的操作
+    pub fn add_routes(mut self, routes: &[RouteDescriptor]) -> Self {
+        add_operations(&mut self.paths, routes);
+        self
+    }
+}
+
+/// Add an operation for every route and template that has none yet
+/// 为每个尚无操作的路由和模板添加操作
+fn add_operations(paths: &mut HashMap<String, PathItem>, routes: &[RouteDescriptor]) {
+    for route in routes {
+        for (index, template) in route.path_templates().into_iter().enumerate() {
+            let item = paths.entry(template.clone()).or_default();
+            if let Some(slot) = operation_slot(item, route.method)
+                && slot.is_none()
+            {
+                *slot = Some(operation(route, &template, index == 0));
+            }
+        }
+    }
+}
+
+/// Get the operation of a path item for a method
+/// 获取路径项中某个方法的操作
+fn operation_slot(item: &mut PathItem, method: Method) -> Option<&mut Option<Operation>> {
+    match method {
+        Method::GET => Some(&mut item.get),
+        Method::POST => Some(&mut item.post),
+        Method::PUT => Some(&mut item.put),
+        Method::DELETE => Some(&mut item.delete),
+        Method::PATCH => Some(&mut item.patch),
+        Method::HEAD => Some(&mut item.head),
+        Method::OPTIONS => Some(&mut item.options),
+        Method::TRACE => Some(&mut item.trace),
+        Method::CONNECT => None,
+    }
+}
+
+/// Build the operation of a route for one of its templates
+/// 为路由的某个模板构建操作
+///
+/// Only the first template keeps the operation id, which must be unique.
+/// 只有第一个模板保留操作ID，因为它必须唯一。
+fn operation(route: &RouteDescriptor, template: &str, with_id: bool) -> Operation {
+    let metadata = &route.metadata;
+    let mut operation = Operation::new()
+        .tags(metadata.tags.clone())
+        .deprecated(metadata.deprecated)
+        .add_response("200", Response::ok("Success"));
+    operation.summary.clone_from(&metadata.summary);
+    operation.description.clone_from(&metadata.description);
+    if with_id {
+        operation.operation_id.clone_from(&metadata.operation_id);
+    }
+
+    for param in &route.params {
+        if template.contains(&format!("{{{}}}", param.name)) {
+            operation =
+                operation.add_parameter(Parameter::path(&param.name).schema(schema(&param.kind)));
+        }
+    }
+    operation
+}
+
+/// Schema of a path parameter
+/// 路径参数的模式
+fn schema(kind: &ParamKind) -> Schema {
+    match kind {
+        ParamKind::Type(ty) => match ty.as_str() {
+            "u8" | "u16" | "u32" | "i8" | "i16" | "i32" => Schema::integer(),
+            "u64" | "u128" | "usize" | "i64" | "i128" | "isize" => Schema::long(),
+            "f32" => Schema::float(),
+            "f64" => Schema::double(),
+            "bool" => Schema::boolean(),
+            "uuid" => Schema::string().with_format(SchemaFormat::Uuid),
+            _ => Schema::string(),
+        },
+        ParamKind::Regex(regex) => Schema {
+            pattern: Some(format!("^(?:{regex})$")),
+            ..Schema::string()
+        },
+        ParamKind::Any | ParamKind::CatchAll => Schema::string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nexus_router::{RouteMetadata, Router};
+
+    #[test]
+    fn test_paths_from_routes() {
+        let router = Router::new()
+            .get("/users/{id:u64}/posts/:page?", "Posts")
+            .with_metadata(
+                RouteMetadata::new()
+                    .summary("List posts")
+                    .operation_id("listPosts")
+                    .tag("posts"),
+            )
+            .delete("/users/{id:u64}/posts", "Deleted")
+            .get("/tags/{name:[a-z]+}", "Tag");
+        let paths = paths_from_routes(&router.descriptors());
+
+        let mut keys: Vec<_> = paths.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            [
+                "/tags/{name}",
+                "/users/{id}/posts",
+                "/users/{id}/posts/{page}"
+            ]
+        );
+
+        let full = paths["/users/{id}/posts/{page}"].get.as_ref().unwrap();
+        assert_eq!(full.summary.as_deref(), Some("List posts"));
+        assert_eq!(full.operation_id.as_deref(), Some("listPosts"));
+        assert_eq!(full.tags, ["posts"]);
+        assert_eq!(full.parameters.len(), 2);
+        assert!(full.parameters.iter().all(|param| param.required));
+
+        let short = &paths["/users/{id}/posts"];
+        let get = short.get.as_ref().unwrap();
+        assert!(get.operation_id.is_none());
+        assert_eq!(get.parameters.len(), 1);
+        let id = get.parameters[0].schema.as_ref().unwrap();
+        assert_eq!(serde_json::to_value(id).unwrap()["format"], "int64");
+        assert!(short.delete.is_some());
+
+        let name = paths["/tags/{name}"].get.as_ref().unwrap().parameters[0]
+            .schema
+            .as_ref()
+            .unwrap();
+        assert_eq!(name.pattern.as_deref(), Some("^(?:[a-z]+)$"));
+    }
+
+    #[test]
+    fn test_add_routes_keeps_documented_operations() {
+        let router = Router::new().get("/health", "ok").post("/health", "ok");
+        let openapi = OpenApi::new(crate::OpenApiConfig::default())
+            .add_path("/health", PathItem::new().get(Operation::new().summary("Documented")))
+            .add_routes(&router.descriptors());
+
+        let item = &openapi.paths["/health"];
+        assert_eq!(item.get.as_ref().unwrap().summary.as_deref(), Some("Documented"));
+        assert!(item.post.is_some());
+    }
+}
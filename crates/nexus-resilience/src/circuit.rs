@@ -49,6 +49,8 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
+use nexus_runtime::time::now;
+
 /// Circuit breaker state
 /// 熔断器状态
 ///
@@ -148,7 +150,7 @@ impl Metrics {
         Self {
             total_requests: AtomicUsize::new(0),
             failed_requests: AtomicUsize::new(0),
-            window_start: std::sync::Mutex::new(Instant::now()),
+            window_start: std::sync::Mutex::new(now()),
             window_duration: duration,
         }
     }
@@ -172,10 +174,10 @@ impl Metrics {
     /// 如果窗口过期则重置
     fn reset_if_expired(&self) {
         let mut start = self.window_start.lock().unwrap();
-        if start.elapsed() >= self.window_duration {
+        if now().duration_since(*start) >= self.window_duration {
             self.total_requests.store(0, Ordering::Relaxed);
             self.failed_requests.store(0, Ordering::Relaxed);
-            *start = Instant::now();
+            *start = now();
         }
     }
 
@@ -348,13 +350,13 @@ impl StateData {
         };
         self.state.store(value, Ordering::Release);
         if state == CircuitState::Open {
-            self.opened_at = Some(Instant::now());
+            self.opened_at = Some(now());
         }
     }
 
     fn should_attempt_reset(&self, open_duration: Duration) -> bool {
         if let Some(opened) = self.opened_at {
-            opened.elapsed() >= open_duration
+            now().duration_since(opened) >= open_duration
         } else {
             false
         }
@@ -43,6 +43,8 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
+use nexus_runtime::time::now;
+
 /// Rate limiter type
 /// 限流器类型
 ///
@@ -206,7 +208,7 @@ impl TokenBucketState {
     fn new(capacity: usize) -> Self {
         Self {
             tokens: AtomicUsize::new(capacity),
-            last_refill: std::sync::Mutex::new(Instant::now()),
+            last_refill: std::sync::Mutex::new(now()),
             capacity,
         }
     }
@@ -216,14 +218,14 @@ impl TokenBucketState {
     fn try_acquire(&self, refill_rate: u64) -> Result<()> {
         // Refill tokens based on elapsed time
         let mut last = self.last_refill.lock().unwrap();
-        let elapsed = last.elapsed();
+        let elapsed = now().duration_since(*last);
         let tokens_to_add = (elapsed.as_secs_f64() * refill_rate as f64) as usize;
 
         if tokens_to_add > 0 {
             let current = self.tokens.load(Ordering::Relaxed);
             let new_count = (current + tokens_to_add).min(self.capacity);
             self.tokens.store(new_count, Ordering::Relaxed);
-            *last = Instant::now();
+            *last = now();
         }
 
         // Try to consume a token
@@ -279,7 +281,7 @@ impl SlidingWindowState {
     /// 尝试获取许可
     fn try_acquire(&self) -> Result<()> {
         let mut timestamps = self.timestamps.lock().unwrap();
-        let now = Instant::now();
+        let now = now();
 
         // Remove timestamps outside the window
         timestamps.retain(|ts| now.duration_since(*ts) < self.window_duration);
@@ -328,7 +330,7 @@ impl FixedWindowState {
     fn new(max_requests: usize, window_duration: Duration) -> Self {
         Self {
             count: AtomicUsize::new(0),
-            window_start: std::sync::Mutex::new(Instant::now()),
+            window_start: std::sync::Mutex::new(now()),
             max_requests,
             window_duration,
         }
@@ -340,9 +342,9 @@ impl FixedWindowState {
         let mut start = self.window_start.lock().unwrap();
 
         // Check if we need to reset the window
-        if start.elapsed() >= self.window_duration {
+        if now().duration_since(*start) >= self.window_duration {
             self.count.store(0, Ordering::Relaxed);
-            *start = Instant::now();
+            *start = now();
         }
 
         // Try to increment count
@@ -354,7 +356,7 @@ impl FixedWindowState {
             self.count.fetch_sub(1, Ordering::Relaxed); // Rollback
 
             // Calculate retry after
-            let elapsed = start.elapsed();
+            let elapsed = now().duration_since(*start);
             let retry_after = self.window_duration.saturating_sub(elapsed);
             Err(RateLimitError::Exceeded { retry_after })
         }
@@ -729,4 +731,28 @@ mod tests {
         assert_eq!(metrics.available_tokens, Some(10));
         assert!(metrics.window_count.is_none());
     }
+
+    #[test]
+    fn test_fixed_window_resets_on_virtual_time() {
+        let mut runtime = nexus_runtime::runtime::TestRuntime::new();
+        runtime.block_on(async {
+            let config = RateLimiterConfig::new()
+                .with_type(RateLimiterType::FixedWindow)
+                .with_capacity(2)
+                .with_window_duration(Duration::from_secs(60));
+            let limiter = RateLimiter::new("virtual", config);
+
+            assert!(limiter.try_acquire().is_ok());
+            assert!(limiter.try_acquire().is_ok());
+            match limiter.try_acquire() {
+                Err(RateLimitError::Exceeded { retry_after }) => {
+                    assert_eq!(retry_after, Duration::from_secs(60));
+                },
+                other => panic!("expected rate limit, got {other:?}"),
+            }
+
+            nexus_runtime::time::advance(Duration::from_secs(60)).await;
+            assert!(limiter.try_acquire().is_ok());
+        });
+    }
 }
@@ -172,6 +172,12 @@ impl RetryPolicy {
         self
     }
 
+    /// Get the maximum number of attempts, the first one included
+    /// 获取最大尝试次数（包括第一次）
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
     /// Calculate delay for the given attempt
     /// 计算给定尝试的延迟
     pub fn calculate_delay(&self, attempt: usize) -> Duration {
@@ -0,0 +1,224 @@
+//! Route descriptors
+//! 路由描述符
+//!
+//! A structured view of a routing table, used by `/actuator/mappings` and the
+//! OpenAPI adapter so that both follow the real routes.
+//! 路由表的结构化视图，供 `/actuator/mappings` 和 OpenAPI 适配器使用，使两者与实际路由保持一致。
+//!
+//! # Equivalent to Spring Boot / 等价于 Spring Boot
+//!
+//! - `RequestMappingInfo` / `HandlerMethod` as listed by `/actuator/mappings`
+
+#![warn(missing_docs)]
+#![warn(unreachable_pub)]
+
+use crate::tree;
+use nexus_http::Method;
+use serde::{Serialize, Serializer};
+
+/// Description of one registered route
+/// 一个已注册路由的描述
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RouteDescriptor {
+    /// HTTP method / HTTP方法
+    #[serde(serialize_with = "serialize_method")]
+    pub method: Method,
+    /// Pattern as registered, e.g. `/users/{id:u64}` / 注册时的模式
+    pub pattern: String,
+    /// Path parameters in order / 按顺序排列的路径参数
+    pub params: Vec<ParamDescriptor>,
+    /// Handler type name / 处理程序类型名称
+    pub handler: String,
+    /// Middleware type names, outermost first / 中间件类型名称，最外层在前
+    pub middleware: Vec<String>,
+    /// Documentation metadata / 文档元数据
+    pub metadata: RouteMetadata,
+}
+
+impl RouteDescriptor {
+    /// Create a descriptor, deriving the parameters from the pattern
+    /// 创建描述符，从模式推导参数
+    pub fn new(method: Method, pattern: impl Into<String>, handler: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        Self {
+            method,
+            params: tree::param_descriptors(&pattern),
+            pattern,
+            handler: handler.into(),
+            middleware: Vec::new(),
+            metadata: RouteMetadata::default(),
+        }
+    }
+
+    /// Get the `{name}` path templates, one per combination of optional segments
+    /// 获取 `{name}` 形式的路径模板，每种可选段组合一个
+    ///
+    /// Constraints are dropped, so `/users/:id/{tab?}` gives `/users/{id}/{tab}`
+    /// and `/users/{id}`.
+    /// 约束会被去掉，因此 `/users/:id/{tab?}` 得到 `/users/{id}/{tab}` 和 `/users/{id}`。
+    pub fn path_templates(&self) -> Vec<String> {
+        tree::path_templates(&self.pattern)
+    }
+}
+
+/// Description of a path parameter
+/// 路径参数的描述
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParamDescriptor {
+    /// Parameter name / 参数名称
+    pub name: String,
+    /// What the segment accepts / 该段接受的内容
+    pub kind: ParamKind,
+    /// Whether the segment may be left out / 该段是否可以省略
+    pub optional: bool,
+}
+
+/// What a path parameter accepts
+/// 路径参数接受的内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamKind {
+    /// Any single segment / 任意单个段
+    Any,
+    /// A segment parsing as a built-in type such as `u64` or `uuid` / 可解析为内置类型的段
+    Type(String),
+    /// A segment matching a regex / 匹配正则的段
+    Regex(String),
+    /// The rest of the path / 路径的剩余部分
+    CatchAll,
+}
+
+/// Documentation metadata of a route
+/// 路由的文档元数据
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// use nexus_router::{RouteMetadata, Router};
+///
+/// let router = Router::new()
+///     .get("/users/:id", get_user)
+///     .with_metadata(RouteMetadata::new().summary("Get a user").tag("users"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RouteMetadata {
+    /// Short summary / 简短摘要
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Longer description / 详细描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Unique operation id / 唯一操作ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    /// Grouping tags / 分组标签
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Whether the route is deprecated / 路由是否已弃用
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+}
+
+impl RouteMetadata {
+    /// Create empty metadata
+    /// 创建空元数据
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the summary
+    /// 设置摘要
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Set the description
+    /// 设置描述
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the operation id
+    /// 设置操作ID
+    pub fn operation_id(mut self, operation_id: impl Into<String>) -> Self {
+        self.operation_id = Some(operation_id.into());
+        self
+    }
+
+    /// Add a tag
+    /// 添加标签
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Mark the route as deprecated
+    /// 将路由标记为已弃用
+    pub fn deprecated(mut self, deprecated: bool) -> Self {
+        self.deprecated = deprecated;
+        self
+    }
+}
+
+/// Serialize a method as its name, e.g. `"GET"`
+/// 将方法序列化为其名称，例如 `"GET"`
+fn serialize_method<S: Serializer>(method: &Method, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(method)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptor_params_and_templates() {
+        let descriptor =
+            RouteDescriptor::new(Method::GET, "/users/{id:u64}/posts/:slug?/{*rest}", "handler");
+        assert_eq!(
+            descriptor.params,
+            [
+                ParamDescriptor {
+                    name: "id".into(),
+                    kind: ParamKind::Type("u64".into()),
+                    optional: false,
+                },
+                ParamDescriptor {
+                    name: "slug".into(),
+                    kind: ParamKind::Any,
+                    optional: true,
+                },
+                ParamDescriptor {
+                    name: "rest".into(),
+                    kind: ParamKind::CatchAll,
+                    optional: false,
+                },
+            ]
+        );
+        assert_eq!(
+            descriptor.path_templates(),
+            [
+                "/users/{id}/posts/{slug}/{rest}",
+                "/users/{id}/posts/{rest}"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_descriptor_serialization() {
+        let mut descriptor = RouteDescriptor::new(Method::POST, "/items/{code:[A-Z]+}", "create");
+        descriptor.metadata = RouteMetadata::new().summary("Create").tag("items");
+        assert_eq!(
+            serde_json::to_value(&descriptor).unwrap(),
+            serde_json::json!({
+                "method": "POST",
+                "pattern": "/items/{code:[A-Z]+}",
+                "params": [{ "name": "code", "kind": { "regex": "[A-Z]+" }, "optional": false }],
+                "handler": "create",
+                "middleware": [],
+                "metadata": { "summary": "Create", "tags": ["items"] }
+            })
+        );
+    }
+}
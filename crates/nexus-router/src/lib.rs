@@ -4,9 +4,17 @@
 //! # Overview / 概述
 //!
 //! `nexus-router` provides efficient HTTP request routing with path parameters
-//! and middleware support.
+//! and middleware support. Routes are matched by a radix tree: static segments
+//! win over parameters, `{id:u64}` / `{slug:[a-z-]+}` constrain a segment,
+//! `:page?` is optional and `*rest` captures the remainder.
 //!
-//! `nexus-router` 提供高效的HTTP请求路由，支持路径参数和中间件。
+//! `nexus-router` 提供高效的HTTP请求路由，支持路径参数和中间件。路由由基数树匹配：
+//! 静态段优先于参数，`{id:u64}` / `{slug:[a-z-]+}` 约束一个段，`:page?` 为可选段，
+//! `*rest` 捕获剩余路径。
+//!
+//! Both routers list their routes as [`RouteDescriptor`]s for actuator mappings
+//! and OpenAPI.
+//! 两种路由器都能将其路由列为 [`RouteDescriptor`]，供 actuator 映射和 OpenAPI 使用。
 //!
 //! # Equivalent to Spring Boot / 等价于 Spring Boot
 //!
@@ -36,14 +44,18 @@
 #![warn(missing_docs)]
 #![warn(unreachable_pub)]
 
+pub mod descriptor;
 pub mod params;
 pub mod route;
 pub mod router;
+pub mod tree;
 pub mod trie;
 
+pub use descriptor::{ParamDescriptor, ParamKind, RouteDescriptor, RouteMetadata};
 pub use params::Path;
 pub use route::{AsyncHandlerFn, BoxedAsyncHandler, Handler as RouteHandler, Route};
 pub use router::{Handler, Middleware, Next, Router, Stateful};
+pub use tree::PatternError;
 pub use trie::TrieRouter;
 
 // Re-export from nexus-http
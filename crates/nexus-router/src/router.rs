@@ -4,12 +4,34 @@
 //! # Equivalent to Spring Boot / 等价于 Spring Boot
 //!
 //! - @RequestMapping with method, path, params, headers
-//! - Ant-style path patterns ("/user/**", "/user/{id}", "/user/?")
+//! - `PathPatternParser` patterns ("/user/{id}", "/user/{id:\d+}", "/user/{*path}")
+//!
+//! Routes are matched by a radix tree per HTTP method; see the
+//! [pattern syntax](crate::tree) for parameters, constraints and precedence.
+//! 路由按HTTP方法各用一棵基数树匹配；参数、约束和优先级见[模式语法](crate::tree)。
+//!
+//! # Composition / 组合
+//!
+//! - [`Router::nest`] mounts a router under a prefix, [`Router::merge`] combines two
+//!   routers, and both keep the other router's middleware scoped to its own routes.
+//! - [`Router::route_middleware`] wraps the routes added so far.
+//! - Unmatched paths go to the [`Router::fallback`] handler (`404 Not Found` by default).
+//! - A path registered for other methods answers `405 Method Not Allowed` with an
+//!   `Allow` header; `HEAD` falls back to `GET`, and `OPTIONS` is answered automatically.
+//!
+//! - [`Router::nest`] 将路由器挂载到前缀下，[`Router::merge`] 合并两个路由器，
+//!   两者都将另一个路由器的中间件限定在其自身路由上。
+//! - [`Router::route_middleware`] 包装已添加的路由。
+//! - 未匹配的路径交给 [`Router::fallback`] 处理程序（默认 `404 Not Found`）。
+//! - 已为其他方法注册的路径返回带 `Allow` 头的 `405 Method Not Allowed`；
+//!   `HEAD` 回退到 `GET`，`OPTIONS` 自动应答。
 
 #![warn(missing_docs)]
 #![warn(unreachable_pub)]
 
 use super::Method;
+use crate::descriptor::{RouteDescriptor, RouteMetadata};
+use crate::tree::{self, Node};
 use nexus_http::{Body, Request, Response, Result, StatusCode};
 use std::collections::HashMap;
 use std::future::Future;
@@ -38,21 +60,50 @@ pub struct Router<S = ()> {
     /// Middleware
     /// 中间件
     middleware: Vec<Arc<dyn Middleware<S>>>,
+    /// Handler for unmatched paths
+    /// 未匹配路径的处理程序
+    fallback: Option<Handler<S>>,
+    /// Method and pattern of the route added last, for `with_metadata`
+    /// 最后添加的路由的方法和模式，供 `with_metadata` 使用
+    last_route: Option<(Method, String)>,
 }
 
+/// Methods with their own route table, in `Allow` header order
+/// 拥有独立路由表的方法，按 `Allow` 头顺序排列
+const ROUTED_METHODS: [Method; 7] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::DELETE,
+    Method::PATCH,
+    Method::OPTIONS,
+];
+
 /// Routes for a specific HTTP method
 /// 特定HTTP方法的路由
 #[derive(Clone)]
 struct Routes<S> {
-    /// Path pattern -> Route mapping
-    /// 路径模式 -> 路由映射
-    patterns: HashMap<String, Route<S>>,
+    /// Radix tree of routes
+    /// 路由的基数树
+    tree: Node<Route<S>>,
 }
 
 impl<S> Default for Routes<S> {
     fn default() -> Self {
         Self {
-            patterns: HashMap::new(),
+            tree: Node::default(),
+        }
+    }
+}
+
+impl<S> Routes<S> {
+    /// Add a route, replacing one with the same pattern
+    /// 添加路由，替换具有相同模式的路由
+    fn insert(&mut self, route: Route<S>) {
+        let pattern = route.pattern.clone();
+        if let Err(error) = self.tree.insert(&pattern, route) {
+            panic!("{error}");
         }
     }
 }
@@ -69,6 +120,30 @@ struct Route<S> {
     /// Parameter names extracted from path
     /// 从路径提取的参数名称
     param_names: Vec<String>,
+    /// Middleware scoped to this route, outermost first
+    /// 限定于此路由的中间件，最外层在前
+    middleware: Vec<Arc<dyn Middleware<S>>>,
+    /// Handler type name
+    /// 处理程序类型名称
+    handler_name: &'static str,
+    /// Documentation metadata
+    /// 文档元数据
+    metadata: RouteMetadata,
+}
+
+impl<S> Route<S> {
+    /// Create a route without middleware or metadata
+    /// 创建没有中间件和元数据的路由
+    fn new(pattern: String, handler: Handler<S>, handler_name: &'static str) -> Self {
+        Self {
+            param_names: tree::param_names(&pattern),
+            pattern,
+            handler,
+            middleware: Vec::new(),
+            handler_name,
+            metadata: RouteMetadata::default(),
+        }
+    }
 }
 
 /// Manual Clone implementation for Route (doesn't require S: Clone)
@@ -79,6 +154,9 @@ impl<S> Clone for Route<S> {
             pattern: self.pattern.clone(),
             handler: self.handler.clone(),
             param_names: self.param_names.clone(),
+            middleware: self.middleware.clone(),
+            handler_name: self.handler_name,
+            metadata: self.metadata.clone(),
         }
     }
 }
@@ -115,6 +193,36 @@ impl<S> Clone for Handler<S> {
     }
 }
 
+impl<S> Handler<S>
+where
+    S: Send + Sync + 'static,
+{
+    /// Turn the handler into a callable function
+    /// 将处理程序转换为可调用函数
+    fn into_handler_fn(self) -> HandlerFn<S> {
+        match self {
+            Handler::Fn(f) => f,
+            Handler::Static(s) => Arc::new(move |_req: Request, _state: Arc<S>| {
+                Box::pin(async move {
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header("content-type", "text/plain")
+                        .body(Body::from(s))
+                        .unwrap())
+                }) as Pin<Box<dyn Future<Output = Result<Response>> + Send>>
+            }),
+            Handler::Bytes(b) => Arc::new(move |_req: Request, _state: Arc<S>| {
+                Box::pin(async move {
+                    Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .body(Body::from(Vec::from(b)))
+                        .unwrap())
+                }) as Pin<Box<dyn Future<Output = Result<Response>> + Send>>
+            }),
+        }
+    }
+}
+
 impl<S> Router<S> {
     /// Create a new router with state
     /// 创建带状态的新路由器
@@ -129,6 +237,8 @@ impl<S> Router<S> {
             options_routes: Routes::default(),
             state: Arc::new(state),
             middleware: Vec::new(),
+            fallback: None,
+            last_route: None,
         }
     }
 
@@ -151,89 +261,227 @@ impl<S> Router<S> {
 
     /// Add a GET route
     /// 添加GET路由
-    pub fn get(mut self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
-        let path = path.into();
-        let handler = handler.into();
-        let param_names = extract_param_names(&path);
-        self.get_routes.patterns.insert(
-            path.clone(),
-            Route {
-                pattern: path,
-                handler,
-                param_names,
-            },
-        );
-        self
+    ///
+    /// See the [pattern syntax](crate::tree). Adding a pattern again replaces
+    /// its handler.
+    /// 见[模式语法](crate::tree)。再次添加相同模式会替换其处理程序。
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the pattern is invalid, as do the other route methods.
+    /// 如果模式无效则恐慌，其他路由方法也是如此。
+    pub fn get(self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
+        self.add(Method::GET, path.into(), handler)
     }
 
     /// Add a POST route
     /// 添加POST路由
-    pub fn post(mut self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
-        let path = path.into();
-        let handler = handler.into();
-        let param_names = extract_param_names(&path);
-        self.post_routes.patterns.insert(
-            path.clone(),
-            Route {
-                pattern: path,
-                handler,
-                param_names,
-            },
-        );
-        self
+    pub fn post(self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
+        self.add(Method::POST, path.into(), handler)
     }
 
     /// Add a PUT route
     /// 添加PUT路由
-    pub fn put(mut self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
-        let path = path.into();
-        let handler = handler.into();
-        let param_names = extract_param_names(&path);
-        self.put_routes.patterns.insert(
-            path.clone(),
-            Route {
-                pattern: path,
-                handler,
-                param_names,
-            },
-        );
-        self
+    pub fn put(self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
+        self.add(Method::PUT, path.into(), handler)
     }
 
     /// Add a DELETE route
     /// 添加DELETE路由
-    pub fn delete(mut self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
-        let path = path.into();
-        let handler = handler.into();
-        let param_names = extract_param_names(&path);
-        self.delete_routes.patterns.insert(
-            path.clone(),
-            Route {
-                pattern: path,
-                handler,
-                param_names,
-            },
-        );
-        self
+    pub fn delete(self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
+        self.add(Method::DELETE, path.into(), handler)
     }
 
     /// Add a PATCH route
     /// 添加PATCH路由
-    pub fn patch(mut self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
-        let path = path.into();
-        let handler = handler.into();
-        let param_names = extract_param_names(&path);
-        self.patch_routes.patterns.insert(
-            path.clone(),
-            Route {
-                pattern: path,
-                handler,
-                param_names,
-            },
-        );
+    pub fn patch(self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
+        self.add(Method::PATCH, path.into(), handler)
+    }
+
+    /// Add a HEAD route, taking precedence over the automatic one from GET
+    /// 添加HEAD路由，优先于由GET自动生成的路由
+    pub fn head(self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
+        self.add(Method::HEAD, path.into(), handler)
+    }
+
+    /// Add an OPTIONS route, taking precedence over the automatic `Allow` response
+    /// 添加OPTIONS路由，优先于自动的 `Allow` 响应
+    pub fn options(self, path: impl Into<String>, handler: impl Into<Handler<S>>) -> Self {
+        self.add(Method::OPTIONS, path.into(), handler)
+    }
+
+    /// Attach documentation metadata to the route added last
+    /// 为最后添加的路由附加文档元数据
+    ///
+    /// The metadata shows up in [`Router::descriptors`], and from there in
+    /// `/actuator/mappings` and OpenAPI.
+    /// 元数据会出现在 [`Router::descriptors`] 中，并由此出现在 `/actuator/mappings` 和 OpenAPI 中。
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics unless it directly follows a route method such as [`Router::get`].
+    /// 除非紧跟在 [`Router::get`] 等路由方法之后，否则恐慌。
+    pub fn with_metadata(mut self, metadata: RouteMetadata) -> Self {
+        let Some((method, pattern)) = self.last_route.clone() else {
+            panic!("with_metadata must follow a route method such as `get`");
+        };
+        if let Some(routes) = self.routes_mut(&method) {
+            routes.tree.for_each_value_mut(&mut |route| {
+                if route.pattern == pattern {
+                    route.metadata.clone_from(&metadata);
+                }
+            });
+        }
+        self
+    }
+
+    /// Wrap every route added so far with a middleware
+    /// 用中间件包装目前已添加的所有路由
+    ///
+    /// Unlike [`Router::middleware`], it does not run for unmatched requests. Each
+    /// call wraps the previous ones, so the last call runs first.
+    /// 与 [`Router::middleware`] 不同，它不会为未匹配的请求运行。
+    /// 每次调用都会包装之前的调用，因此最后一次调用最先运行。
+    ///
+    /// # Example / 示例
+    ///
+    /// ```rust,no_run,ignore
+    /// use nexus_router::Router;
+    ///
+    /// let router = Router::new()
+    ///     .get("/admin", admin_page)
+    ///     .route_middleware(require_admin)
+    ///     .get("/", home_page);
+    /// ```
+    pub fn route_middleware(mut self, mw: Arc<dyn Middleware<S>>) -> Self {
+        for method in &ROUTED_METHODS {
+            if let Some(routes) = self.routes_mut(method) {
+                routes
+                    .tree
+                    .for_each_value_mut(&mut |route| route.middleware.insert(0, mw.clone()));
+            }
+        }
+        self
+    }
+
+    /// Set the handler for requests that match no route
+    /// 设置未匹配任何路由的请求的处理程序
+    ///
+    /// It runs inside the router middleware. Without one, such requests get
+    /// `404 Not Found`.
+    /// 它在路由器中间件内运行。未设置时，此类请求返回 `404 Not Found`。
+    pub fn fallback(mut self, handler: impl Into<Handler<S>>) -> Self {
+        self.fallback = Some(handler.into());
         self
     }
 
+    /// Mount the routes of another router under a prefix
+    /// 将另一个路由器的路由挂载到前缀下
+    ///
+    /// The nested router's middleware only wraps its own routes, inside the
+    /// middleware of this router. Its state and fallback are not used. The prefix
+    /// may contain parameters, and `/` in the nested router maps to the prefix itself.
+    /// 被嵌套路由器的中间件只包装其自身路由，并位于本路由器中间件之内。
+    /// 不使用其状态和回退处理程序。前缀可以包含参数，被嵌套路由器中的 `/` 映射到前缀本身。
+    ///
+    /// # Example / 示例
+    ///
+    /// ```rust,no_run,ignore
+    /// use nexus_router::Router;
+    ///
+    /// let api = Router::new()
+    ///     .middleware(auth)
+    ///     .get("/users/:id", get_user);
+    /// let router = Router::new().nest("/api/v1", api); // GET /api/v1/users/:id
+    /// ```
+    ///
+    /// # Panics / 恐慌
+    ///
+    /// Panics if the prefix does not start with `/` or a joined pattern is invalid.
+    /// 如果前缀不以 `/` 开头或拼接后的模式无效则恐慌。
+    pub fn nest(mut self, prefix: &str, router: Router<S>) -> Self {
+        assert!(prefix.starts_with('/'), "nest prefix `{prefix}` must start with `/`");
+        let prefix = prefix.trim_end_matches('/');
+        self.absorb(&router, |path| match path {
+            "/" if !prefix.is_empty() => prefix.to_string(),
+            _ => format!("{prefix}{path}"),
+        });
+        self
+    }
+
+    /// Add the routes of another router
+    /// 添加另一个路由器的路由
+    ///
+    /// Routes of `other` replace routes with the same method and pattern, and its
+    /// middleware only wraps its own routes. Its fallback is kept if this router
+    /// has none; its state is not used.
+    /// `other` 的路由会替换方法和模式相同的路由，其中间件只包装其自身路由。
+    /// 如果本路由器没有回退处理程序则保留其回退处理程序；不使用其状态。
+    pub fn merge(mut self, other: Router<S>) -> Self {
+        self.absorb(&other, str::to_string);
+        self.fallback = self.fallback.or(other.fallback);
+        self
+    }
+
+    /// Add a route for a method, naming it after the handler type
+    /// 为方法添加路由，以处理程序类型命名
+    fn add(mut self, method: Method, path: String, handler: impl Into<Handler<S>>) -> Self {
+        let handler_name = std::any::type_name_of_val(&handler);
+        if let Some(routes) = self.routes_mut(&method) {
+            routes.insert(Route::new(path.clone(), handler.into(), handler_name));
+        }
+        self.last_route = Some((method, path));
+        self
+    }
+
+    /// Copy the routes of `other` with the joined paths, scoping its middleware to them
+    /// 以拼接后的路径复制 `other` 的路由，并将其中间件限定于这些路由
+    fn absorb(&mut self, other: &Router<S>, join: impl Fn(&str) -> String) {
+        for method in &ROUTED_METHODS {
+            let (Some(from), Some(into)) = (other.routes(method), self.routes_mut(method)) else {
+                continue;
+            };
+            for (pattern, route) in from.tree.entries() {
+                let mut copy = Route::new(join(pattern), route.handler.clone(), route.handler_name);
+                copy.middleware = other.middleware.clone();
+                copy.middleware.extend(route.middleware.iter().cloned());
+                copy.metadata = route.metadata.clone();
+                into.insert(copy);
+            }
+        }
+        self.last_route = None;
+    }
+
+    /// Get the routes of a method
+    /// 获取某个方法的路由
+    fn routes(&self, method: &Method) -> Option<&Routes<S>> {
+        match method {
+            Method::GET => Some(&self.get_routes),
+            Method::POST => Some(&self.post_routes),
+            Method::PUT => Some(&self.put_routes),
+            Method::DELETE => Some(&self.delete_routes),
+            Method::PATCH => Some(&self.patch_routes),
+            Method::HEAD => Some(&self.head_routes),
+            Method::OPTIONS => Some(&self.options_routes),
+            Method::TRACE | Method::CONNECT => None,
+        }
+    }
+
+    /// Get the routes of a method for modification
+    /// 获取某个方法的路由以便修改
+    fn routes_mut(&mut self, method: &Method) -> Option<&mut Routes<S>> {
+        match method {
+            Method::GET => Some(&mut self.get_routes),
+            Method::POST => Some(&mut self.post_routes),
+            Method::PUT => Some(&mut self.put_routes),
+            Method::DELETE => Some(&mut self.delete_routes),
+            Method::PATCH => Some(&mut self.patch_routes),
+            Method::HEAD => Some(&mut self.head_routes),
+            Method::OPTIONS => Some(&mut self.options_routes),
+            Method::TRACE | Method::CONNECT => None,
+        }
+    }
+
     /// Match a route for the given method and path
     /// 匹配给定方法和路径的路由
     fn match_route(
@@ -241,30 +489,185 @@ impl<S> Router<S> {
         method: &Method,
         path: &str,
     ) -> Option<(Route<S>, HashMap<String, String>)> {
-        let routes = match method {
-            Method::GET => &self.get_routes,
-            Method::POST => &self.post_routes,
-            Method::PUT => &self.put_routes,
-            Method::DELETE => &self.delete_routes,
-            Method::PATCH => &self.patch_routes,
-            Method::HEAD => &self.head_routes,
-            Method::OPTIONS => &self.options_routes,
-            Method::TRACE | Method::CONNECT => return None,
-        };
+        let (route, params) = self.routes(method)?.tree.at(path)?;
+        Some((route.clone(), params.into_iter().collect()))
+    }
+
+    /// Check whether a route matches the given method and path
+    /// 检查是否有路由匹配给定方法和路径
+    fn is_routed(&self, method: &Method, path: &str) -> bool {
+        self.routes(method)
+            .is_some_and(|routes| routes.tree.at(path).is_some())
+    }
+
+    /// Get the methods a path answers to, including the automatic HEAD and OPTIONS
+    /// 获取路径可响应的方法，包括自动的HEAD和OPTIONS
+    fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        if !ROUTED_METHODS
+            .iter()
+            .any(|method| self.is_routed(method, path))
+        {
+            return Vec::new();
+        }
+        ROUTED_METHODS
+            .into_iter()
+            .filter(|method| match method {
+                Method::HEAD => {
+                    self.is_routed(&Method::HEAD, path) || self.is_routed(&Method::GET, path)
+                },
+                Method::OPTIONS => true,
+                _ => self.is_routed(method, path),
+            })
+            .collect()
+    }
 
-        // Try exact match first
-        if let Some(route) = routes.patterns.get(path) {
-            return Some((route.clone(), HashMap::new()));
+    /// Decide how to answer a request
+    /// 决定如何应答请求
+    fn dispatch(&self, method: &Method, path: &str) -> Dispatch<S> {
+        if let Some((route, params)) = self.match_route(method, path) {
+            return Dispatch::Route {
+                route,
+                params,
+                strip_body: false,
+            };
+        }
+        if *method == Method::HEAD
+            && let Some((route, params)) = self.match_route(&Method::GET, path)
+        {
+            return Dispatch::Route {
+                route,
+                params,
+                strip_body: true,
+            };
         }
 
-        // Try pattern match
-        for route in routes.patterns.values() {
-            if let Some(params) = match_path_pattern(&route.pattern, path) {
-                return Some((route.clone(), params));
+        let allowed = self.allowed_methods(path);
+        if allowed.is_empty() {
+            return Dispatch::Fallback(self.fallback.clone());
+        }
+        let allow = allowed
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if *method == Method::OPTIONS {
+            Dispatch::Allow {
+                status: StatusCode::NO_CONTENT,
+                allow,
+                body: "",
+            }
+        } else {
+            Dispatch::Allow {
+                status: StatusCode::METHOD_NOT_ALLOWED,
+                allow,
+                body: "Method Not Allowed",
             }
         }
+    }
+}
+
+/// How a request is answered
+/// 请求的应答方式
+enum Dispatch<S> {
+    /// A matched route; `strip_body` answers HEAD with a GET route
+    /// 匹配的路由；`strip_body` 表示用GET路由应答HEAD
+    Route {
+        route: Route<S>,
+        params: HashMap<String, String>,
+        strip_body: bool,
+    },
+    /// The path exists for other methods (405), or an automatic OPTIONS reply
+    /// 路径存在于其他方法（405），或自动的OPTIONS应答
+    Allow {
+        status: StatusCode,
+        allow: String,
+        body: &'static str,
+    },
+    /// No route matched the path
+    /// 没有路由匹配该路径
+    Fallback(Option<Handler<S>>),
+}
 
-        None
+/// Wrap a handler with middleware, the first one outermost
+/// 用中间件包装处理程序，第一个在最外层
+fn layered<S>(middleware: &[Arc<dyn Middleware<S>>], handler: HandlerFn<S>) -> HandlerFn<S>
+where
+    S: Send + Sync + 'static,
+{
+    middleware.iter().rev().fold(handler, |inner, mw| {
+        let mw = mw.clone();
+        let next = Next::from_arc(inner);
+        Arc::new(move |req: Request, state: Arc<S>| mw.call(req, state, next.clone()))
+    })
+}
+
+/// Answer with the headers of `handler` but no body, keeping its length
+/// 以 `handler` 的头但无body应答，保留其长度
+fn without_body<S>(handler: HandlerFn<S>) -> HandlerFn<S>
+where
+    S: Send + Sync + 'static,
+{
+    Arc::new(move |req: Request, state: Arc<S>| {
+        let response = handler(req, state);
+        Box::pin(async move {
+            let mut response = response.await?;
+            let body = response.take_body();
+            if response.header("content-length").is_none() && !body.is_stream() {
+                response.insert_header("content-length", body.data().len().to_string());
+            }
+            Ok(response)
+        }) as Pin<Box<dyn Future<Output = Result<Response>> + Send>>
+    })
+}
+
+/// Answer with a fixed status, body and optional `Allow` header
+/// 以固定状态、body和可选的 `Allow` 头应答
+fn status_handler<S>(status: StatusCode, allow: Option<String>, body: &'static str) -> HandlerFn<S>
+where
+    S: Send + Sync + 'static,
+{
+    Arc::new(move |_req: Request, _state: Arc<S>| {
+        let mut response = Response::new(status);
+        if let Some(allow) = &allow {
+            response.insert_header("allow", allow.clone());
+        }
+        if !body.is_empty() {
+            response.set_body(Body::from(body));
+        }
+        Box::pin(async move { Ok(response) })
+            as Pin<Box<dyn Future<Output = Result<Response>> + Send>>
+    })
+}
+
+impl<S> Router<S>
+where
+    S: Send + Sync + 'static,
+{
+    /// Describe every registered route, ordered by pattern
+    /// 描述每个已注册的路由，按模式排序
+    ///
+    /// Middleware lists the router middleware first, then the route's own.
+    /// 中间件先列出路由器中间件，再列出路由自身的中间件。
+    pub fn descriptors(&self) -> Vec<RouteDescriptor> {
+        let mut descriptors = Vec::new();
+        for method in ROUTED_METHODS {
+            let Some(routes) = self.routes(&method) else {
+                continue;
+            };
+            for (pattern, route) in routes.tree.entries() {
+                let mut descriptor = RouteDescriptor::new(method, pattern, route.handler_name);
+                descriptor.middleware = self
+                    .middleware
+                    .iter()
+                    .chain(&route.middleware)
+                    .map(|mw| mw.name().to_string())
+                    .collect();
+                descriptor.metadata = route.metadata.clone();
+                descriptors.push(descriptor);
+            }
+        }
+        descriptors.sort_by(|a, b| a.pattern.cmp(&b.pattern));
+        descriptors
     }
 }
 
@@ -285,38 +688,6 @@ impl Router {
     }
 }
 
-/// Extract parameter names from path pattern
-/// 从路径模式提取参数名称
-fn extract_param_names(pattern: &str) -> Vec<String> {
-    pattern
-        .split('/')
-        .filter_map(|s| s.strip_prefix(':'))
-        .map(|s| s.to_string())
-        .collect()
-}
-
-/// Match a path pattern against an actual path
-/// 匹配路径模式与实际路径
-fn match_path_pattern(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
-    let pattern_parts: Vec<&str> = pattern.split('/').collect();
-    let path_parts: Vec<&str> = path.split('/').collect();
-
-    if pattern_parts.len() != path_parts.len() {
-        return None;
-    }
-
-    let mut params = HashMap::new();
-    for (pattern_part, path_part) in pattern_parts.iter().zip(path_parts.iter()) {
-        if let Some(param_name) = pattern_part.strip_prefix(':') {
-            params.insert(param_name.to_string(), path_part.to_string());
-        } else if pattern_part != path_part {
-            return None;
-        }
-    }
-
-    Some(params)
-}
-
 /// Middleware trait
 /// 中间件trait
 pub trait Middleware<S>: Send + Sync + 'static {
@@ -328,6 +699,12 @@ pub trait Middleware<S>: Send + Sync + 'static {
         state: Arc<S>,
         next: Next<S>,
     ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send>>;
+
+    /// Name shown in route descriptors, the type name by default
+    /// 在路由描述符中显示的名称，默认为类型名称
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
 }
 
 /// Next middleware in the chain
@@ -383,77 +760,43 @@ where
     S: Send + Sync + 'static,
 {
     fn call(&self, mut req: Request) -> impl Future<Output = Result<Response>> + Send {
-        let method = req.method().clone();
-        let path = req.path().to_string();
+        let dispatch = self.dispatch(&req.method(), req.path());
         let state = self.state.clone();
         let middleware = self.middleware.clone();
-        let matched = self.match_route(&method, &path);
 
         Box::pin(async move {
-            let (route, params) = match matched {
-                Some(m) => m,
-                None => {
-                    return Ok(Response::builder()
-                        .status(StatusCode::NOT_FOUND)
-                        .body(Body::from("Not Found"))
-                        .unwrap());
+            let endpoint = match dispatch {
+                Dispatch::Route {
+                    route,
+                    params,
+                    strip_body,
+                } => {
+                    // Set path parameters on request
+                    // 在请求上设置路径参数
+                    for (name, value) in params {
+                        req.set_path_var(name, value);
+                    }
+                    let handler = layered(&route.middleware, route.handler.into_handler_fn());
+                    if strip_body {
+                        without_body(handler)
+                    } else {
+                        handler
+                    }
+                },
+                Dispatch::Allow {
+                    status,
+                    allow,
+                    body,
+                } => status_handler(status, Some(allow), body),
+                Dispatch::Fallback(Some(handler)) => handler.into_handler_fn(),
+                Dispatch::Fallback(None) => {
+                    status_handler(StatusCode::NOT_FOUND, None, "Not Found")
                 },
             };
 
-            // Set path parameters on request
-            // 在请求上设置路径参数
-            for (name, value) in params {
-                req.set_path_var(name, value);
-            }
-
-            // Build the final handler function
-            // 构建最终处理函数
-            let route_handler = route.handler.clone();
-            let route_state = state.clone();
-            let handler_fn: HandlerFn<S> =
-                Arc::new(move |req: Request, _st: Arc<S>| match route_handler.clone() {
-                    Handler::Static(s) => Box::pin(async move {
-                        Ok(Response::builder()
-                            .status(StatusCode::OK)
-                            .header("content-type", "text/plain")
-                            .body(Body::from(s))
-                            .unwrap())
-                    })
-                        as Pin<Box<dyn Future<Output = Result<Response>> + Send>>,
-                    Handler::Bytes(b) => Box::pin(async move {
-                        Ok(Response::builder()
-                            .status(StatusCode::OK)
-                            .body(Body::from(Vec::from(b)))
-                            .unwrap())
-                    })
-                        as Pin<Box<dyn Future<Output = Result<Response>> + Send>>,
-                    Handler::Fn(h) => h(req, route_state.clone()),
-                });
-
-            // Build and execute middleware chain
-            // 构建并执行中间件链
-            if middleware.is_empty() {
-                // No middleware - call handler directly
-                // 无中间件 - 直接调用处理程序
-                handler_fn(req, state).await
-            } else {
-                // Build middleware chain from inner to outer
-                // 从内到外构建中间件链
-                let mut next = Next::from_arc(handler_fn);
-
-                // Apply middleware in reverse order (first registered = outermost)
-                // 以相反顺序应用中间件（第一个注册 = 最外层）
-                for mw in middleware.iter().rev() {
-                    let mw = mw.clone();
-                    let inner = next.clone();
-                    next =
-                        Next::new(move |req: Request, st: Arc<S>| mw.call(req, st, inner.clone()));
-                }
-
-                // Execute the chain
-                // 执行链
-                next.call(req, state).await
-            }
+            // Router middleware wraps every answer, first registered = outermost
+            // 路由器中间件包装每个应答，第一个注册 = 最外层
+            layered(&middleware, endpoint)(req, state).await
         })
     }
 }
@@ -547,38 +890,333 @@ impl<S> From<&'static [u8]> for Handler<S> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nexus_http::testing::TestClient;
+    use std::sync::atomic::{AtomicU64, Ordering};
 
     #[test]
-    fn test_extract_param_names() {
-        assert_eq!(extract_param_names("/users/:id"), vec!["id"]);
-        assert_eq!(
-            extract_param_names("/users/:user_id/posts/:post_id"),
-            vec!["user_id", "post_id"]
-        );
-        assert_eq!(extract_param_names("/users"), vec![""]);
+    fn test_route_param_names() {
+        let router = Router::new()
+            .get("/users/:id", "User")
+            .get("/users/:user_id/posts/{post_id:u64}", "Post")
+            .get("/users", "Users");
+        let names = |path| {
+            router
+                .match_route(&Method::GET, path)
+                .unwrap()
+                .0
+                .param_names
+        };
+        assert_eq!(names("/users/1"), vec!["id"]);
+        assert_eq!(names("/users/1/posts/2"), vec!["user_id", "post_id"]);
+        assert!(names("/users").is_empty());
     }
 
     #[test]
-    fn test_match_path_pattern() {
+    fn test_match_route() {
+        let router = Router::new()
+            .get("/users/:id", "User")
+            .get("/users", "Users")
+            .get("/users/me", "Me")
+            .get("/users/:uid/posts/:pid", "Post");
+
         // Exact match
-        assert!(match_path_pattern("/users", "/users").is_some());
+        let (_, params) = router.match_route(&Method::GET, "/users").unwrap();
+        assert!(params.is_empty());
 
         // With parameter
-        let params = match_path_pattern("/users/:id", "/users/123").unwrap();
+        let (_, params) = router.match_route(&Method::GET, "/users/123").unwrap();
         assert_eq!(params.get("id"), Some(&"123".to_string()));
 
+        // Static segments win over parameters
+        let (route, params) = router.match_route(&Method::GET, "/users/me").unwrap();
+        assert_eq!(route.pattern, "/users/me");
+        assert!(params.is_empty());
+
         // Multiple parameters
-        let params = match_path_pattern("/users/:uid/posts/:pid", "/users/42/posts/99").unwrap();
+        let (_, params) = router
+            .match_route(&Method::GET, "/users/42/posts/99")
+            .unwrap();
         assert_eq!(params.get("uid"), Some(&"42".to_string()));
         assert_eq!(params.get("pid"), Some(&"99".to_string()));
 
         // No match
-        assert!(match_path_pattern("/users/:id", "/posts/123").is_none());
+        assert!(router.match_route(&Method::GET, "/posts/123").is_none());
+        assert!(router.match_route(&Method::POST, "/users").is_none());
     }
 
     #[test]
     fn test_router_creation() {
         let router = Router::new().get("/", "Hello");
-        assert_eq!(router.get_routes.patterns.len(), 1);
+        assert_eq!(router.get_routes.tree.patterns().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_constraint_failure_falls_through() {
+        let router = Router::new()
+            .get("/items/{id:u64}", "by id")
+            .get("/items/{slug:[a-z-]+}", "by slug")
+            .get("/files/{*path}", "file");
+        let client = TestClient::new(router);
+
+        client.get("/items/7").await.assert_body("by id");
+        client.get("/items/blue-hat").await.assert_body("by slug");
+        client
+            .get("/items/Blue")
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        client.get("/files/a/b.txt").await.assert_body("file");
+    }
+
+    #[test]
+    #[should_panic(expected = "catch-all must be the last segment")]
+    fn test_invalid_pattern_panics() {
+        let _ = Router::new().get("/{*path}/more", "Never");
+    }
+
+    struct RequireKey;
+
+    impl Middleware<AtomicU64> for RequireKey {
+        fn call(
+            &self,
+            req: Request,
+            state: Arc<AtomicU64>,
+            next: Next<AtomicU64>,
+        ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send>> {
+            Box::pin(async move {
+                if req.header("x-api-key") != Some("secret") {
+                    return Ok(Response::unauthorized());
+                }
+                let mut response = next.call(req, state).await?;
+                response.insert_header("x-checked", "true");
+                Ok(response)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_router_with_test_client() {
+        let router = Router::with_state(AtomicU64::new(0))
+            .middleware(Arc::new(RequireKey))
+            .post(
+                "/counter/:by",
+                Stateful::new(|req: Request, state: Arc<AtomicU64>| async move {
+                    let by: u64 = req.path_var("by").unwrap_or("1").parse().unwrap_or(1);
+                    let value = state.fetch_add(by, Ordering::SeqCst) + by;
+                    Ok(Response::json(&serde_json::json!({ "value": value })))
+                }),
+            );
+        let client = TestClient::new(router).with_header("x-api-key", "secret");
+
+        client
+            .post("/counter/2")
+            .await
+            .assert_status(StatusCode::OK)
+            .assert_header("x-checked", "true")
+            .assert_json(&serde_json::json!({ "value": 2 }));
+        client
+            .post("/counter/3")
+            .await
+            .assert_json(&serde_json::json!({ "value": 5 }));
+        client
+            .post("/counter/1")
+            .header("x-api-key", "wrong")
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        client
+            .get("/counter/1")
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED)
+            .assert_header("allow", "POST, OPTIONS");
+        assert_eq!(client.service().state.load(Ordering::SeqCst), 5);
+    }
+
+    /// Appends its name to the `x-tags` response header
+    struct Tag(&'static str);
+
+    impl<S: Send + Sync + 'static> Middleware<S> for Tag {
+        fn call(
+            &self,
+            req: Request,
+            state: Arc<S>,
+            next: Next<S>,
+        ) -> Pin<Box<dyn Future<Output = Result<Response>> + Send>> {
+            let name = self.0;
+            Box::pin(async move {
+                let mut response = next.call(req, state).await?;
+                let tags = match response.header("x-tags") {
+                    Some(tags) => format!("{tags},{name}"),
+                    None => name.to_string(),
+                };
+                response.insert_header("x-tags", tags);
+                Ok(response)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nest_and_merge_scope_middleware() {
+        let users = Router::new()
+            .middleware(Arc::new(Tag("users")))
+            .get("/", "list")
+            .get("/:id", "user")
+            .route_middleware(Arc::new(Tag("route")));
+        let health = Router::new()
+            .middleware(Arc::new(Tag("health")))
+            .get("/health", "ok");
+        let router = Router::new()
+            .middleware(Arc::new(Tag("global")))
+            .nest("/api/v1/users/", users)
+            .merge(health);
+        let client = TestClient::new(router);
+
+        client
+            .get("/api/v1/users")
+            .await
+            .assert_body("list")
+            .assert_header("x-tags", "route,users,global");
+        client
+            .get("/api/v1/users/7")
+            .await
+            .assert_body("user")
+            .assert_header("x-tags", "route,users,global");
+        client
+            .get("/health")
+            .await
+            .assert_body("ok")
+            .assert_header("x-tags", "health,global");
+        client
+            .get("/api/v1")
+            .await
+            .assert_status(StatusCode::NOT_FOUND)
+            .assert_header("x-tags", "global");
+    }
+
+    #[tokio::test]
+    async fn test_route_middleware_wraps_earlier_routes() {
+        let router = Router::new()
+            .get("/a", "a")
+            .route_middleware(Arc::new(Tag("inner")))
+            .get("/b", "b")
+            .route_middleware(Arc::new(Tag("outer")));
+        let client = TestClient::new(router);
+
+        client
+            .get("/a")
+            .await
+            .assert_header("x-tags", "inner,outer");
+        client.get("/b").await.assert_header("x-tags", "outer");
+    }
+
+    #[tokio::test]
+    async fn test_fallback_and_method_not_allowed() {
+        let router = Router::new()
+            .get("/items", "items")
+            .delete("/items/:id", "deleted")
+            .fallback(|_req: Request| async {
+                Ok(Response::not_found().with_body(Body::from("custom")))
+            })
+            .merge(Router::new().fallback("ignored"));
+        let client = TestClient::new(router);
+
+        client
+            .get("/missing")
+            .await
+            .assert_status(StatusCode::NOT_FOUND)
+            .assert_body("custom");
+        client
+            .post("/items")
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED)
+            .assert_header("allow", "GET, HEAD, OPTIONS");
+        client
+            .get("/items/1")
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED)
+            .assert_header("allow", "DELETE, OPTIONS");
+    }
+
+    #[tokio::test]
+    async fn test_automatic_head_and_options() {
+        let router = Router::new()
+            .get("/page", "hello")
+            .post("/page", "posted")
+            .get("/custom", "get")
+            .head("/custom", "head")
+            .options("/custom", "options");
+        let client = TestClient::new(router);
+
+        let response = client.head("/page").await;
+        response
+            .assert_status(StatusCode::OK)
+            .assert_header("content-length", "5")
+            .assert_header("content-type", "text/plain");
+        assert!(response.bytes().is_empty());
+        client
+            .options("/page")
+            .await
+            .assert_status(StatusCode::NO_CONTENT)
+            .assert_header("allow", "GET, HEAD, POST, OPTIONS");
+        client.head("/custom").await.assert_body("head");
+        client.options("/custom").await.assert_body("options");
+        client
+            .options("/nowhere")
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    async fn list_users(_req: Request) -> Result<Response> {
+        Ok(Response::ok())
+    }
+
+    #[test]
+    fn test_descriptors() {
+        let api = Router::new()
+            .middleware(Arc::new(Tag("api")))
+            .get("/users", list_users)
+            .with_metadata(RouteMetadata::new().summary("List users").tag("users"))
+            .post("/users/{id:u64}/avatar/:size?", "saved");
+        let router = Router::new()
+            .middleware(Arc::new(Tag("global")))
+            .get("/", "home")
+            .nest("/api", api);
+
+        let descriptors = router.descriptors();
+        let summary: Vec<_> = descriptors
+            .iter()
+            .map(|d| (d.method, d.pattern.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Method::GET, "/"),
+                (Method::GET, "/api/users"),
+                (Method::POST, "/api/users/{id:u64}/avatar/:size?"),
+            ]
+        );
+
+        let users = &descriptors[1];
+        assert!(users.handler.ends_with("::list_users"));
+        assert_eq!(users.middleware.len(), 2);
+        assert!(users.middleware[0].ends_with("::Tag"));
+        assert_eq!(users.metadata.summary.as_deref(), Some("List users"));
+        assert_eq!(users.metadata.tags, ["users"]);
+
+        let avatar = &descriptors[2];
+        assert_eq!(avatar.handler, "&str");
+        assert_eq!(avatar.params.len(), 2);
+        assert!(avatar.params[1].optional);
+        assert_eq!(avatar.metadata, RouteMetadata::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "must follow a route method")]
+    fn test_metadata_without_route_panics() {
+        let _ = Router::new().with_metadata(RouteMetadata::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "must start with `/`")]
+    fn test_nest_requires_leading_slash() {
+        let _ = Router::new().nest("api", Router::new());
     }
 }
@@ -0,0 +1,832 @@
+//! Radix tree shared by [`Router`](crate::Router) and [`TrieRouter`](crate::TrieRouter)
+//! [`Router`](crate::Router) 和 [`TrieRouter`](crate::TrieRouter) 共用的基数树
+//!
+//! # Pattern syntax / 模式语法
+//!
+//! | Segment / 段 | Matches / 匹配 |
+//! |--------------|----------------|
+//! | `users` | The literal text / 字面文本 |
+//! | `:id`, `{id}` | One segment / 一个段 |
+//! | `{id:u64}` | One segment parsing as the type (`u8`..`u128`, `i8`..`i128`, `usize`, `isize`, `f32`, `f64`, `bool`, `uuid`) / 可解析为该类型的一个段 |
+//! | `{slug:[a-z-]+}` | One segment matching the whole regex / 完整匹配正则的一个段 |
+//! | `:id?`, `{id?}`, `{id:u64?}` | An optional segment / 可选段 |
+//! | `*rest`, `{*rest}` | The non-empty remainder, last segment only / 非空的剩余路径，仅限最后一段 |
+//!
+//! # Precedence / 优先级
+//!
+//! Candidates are tried depth-first: static text before parameters,
+//! constrained parameters before plain ones (each in registration order), and
+//! catch-all segments last. A candidate whose constraint or remainder does not
+//! match falls through to the next one, so `/users/me` wins over `/users/:id`
+//! regardless of the order the routes were added.
+//!
+//! 候选项按深度优先尝试：静态文本优先于参数，带约束的参数优先于普通参数（各自按注册顺序），
+//! 通配段最后。约束或剩余路径不匹配的候选项会回退到下一个，因此无论添加顺序如何，
+//! `/users/me` 总是优先于 `/users/:id`。
+//!
+//! # Equivalent to Spring Boot / 等价于 Spring Boot
+//!
+//! - `PathPatternParser` - `{id}`, `{id:\d+}`, `{*path}`
+
+#![warn(missing_docs)]
+#![warn(unreachable_pub)]
+
+use crate::descriptor::{ParamDescriptor, ParamKind};
+use regex::Regex;
+
+/// Error for an invalid route pattern
+/// 无效路由模式的错误
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PatternError {
+    /// A `{` without a matching `}` / 没有匹配 `}` 的 `{`
+    #[error("unclosed `{{` in route pattern `{0}`")]
+    UnclosedBrace(String),
+    /// A parameter without a name / 没有名称的参数
+    #[error("empty parameter name in route pattern `{0}`")]
+    EmptyName(String),
+    /// A catch-all segment that is not the last one / 不是最后一段的通配段
+    #[error("catch-all must be the last segment in route pattern `{0}`")]
+    CatchAllNotLast(String),
+    /// An invalid regex constraint / 无效的正则约束
+    #[error("invalid constraint `{constraint}` in route pattern `{pattern}`: {reason}")]
+    InvalidConstraint {
+        /// The route pattern / 路由模式
+        pattern: String,
+        /// The constraint text / 约束文本
+        constraint: String,
+        /// Why the regex was rejected / 正则被拒绝的原因
+        reason: String,
+    },
+}
+
+/// Built-in segment types / 内置段类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SegmentType {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    F32,
+    F64,
+    Bool,
+    Uuid,
+}
+
+impl SegmentType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "u128" => Self::U128,
+            "usize" => Self::Usize,
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "i128" => Self::I128,
+            "isize" => Self::Isize,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "bool" => Self::Bool,
+            "uuid" => Self::Uuid,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::U128 => "u128",
+            Self::Usize => "usize",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::I128 => "i128",
+            Self::Isize => "isize",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::Bool => "bool",
+            Self::Uuid => "uuid",
+        }
+    }
+
+    fn matches(self, segment: &str) -> bool {
+        match self {
+            Self::U8 => segment.parse::<u8>().is_ok(),
+            Self::U16 => segment.parse::<u16>().is_ok(),
+            Self::U32 => segment.parse::<u32>().is_ok(),
+            Self::U64 => segment.parse::<u64>().is_ok(),
+            Self::U128 => segment.parse::<u128>().is_ok(),
+            Self::Usize => segment.parse::<usize>().is_ok(),
+            Self::I8 => segment.parse::<i8>().is_ok(),
+            Self::I16 => segment.parse::<i16>().is_ok(),
+            Self::I32 => segment.parse::<i32>().is_ok(),
+            Self::I64 => segment.parse::<i64>().is_ok(),
+            Self::I128 => segment.parse::<i128>().is_ok(),
+            Self::Isize => segment.parse::<isize>().is_ok(),
+            Self::F32 => segment.parse::<f32>().is_ok(),
+            Self::F64 => segment.parse::<f64>().is_ok(),
+            Self::Bool => segment.parse::<bool>().is_ok(),
+            Self::Uuid => is_uuid(segment),
+        }
+    }
+}
+
+/// Check for the hyphenated `8-4-4-4-12` hex form
+/// 检查带连字符的 `8-4-4-4-12` 十六进制形式
+fn is_uuid(segment: &str) -> bool {
+    let groups: Vec<&str> = segment.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Constraint on a parameter segment / 参数段的约束
+#[derive(Debug, Clone)]
+enum Constraint {
+    Type(SegmentType),
+    Regex(Regex),
+}
+
+impl Constraint {
+    fn matches(&self, segment: &str) -> bool {
+        match self {
+            Constraint::Type(ty) => ty.matches(segment),
+            Constraint::Regex(regex) => regex.is_match(segment),
+        }
+    }
+}
+
+/// One parsed piece of a route pattern / 路由模式中解析出的一部分
+#[derive(Debug, Clone)]
+enum Token {
+    /// Literal text, slashes included / 字面文本，包括斜杠
+    Static(String),
+    /// A parameter segment / 参数段
+    Param {
+        name: String,
+        /// Constraint source, used to share tree nodes / 约束源文本，用于共享树节点
+        source: Option<String>,
+        constraint: Option<Constraint>,
+        optional: bool,
+    },
+    /// The remainder of the path / 路径的剩余部分
+    CatchAll(String),
+}
+
+/// Parse a route pattern into tokens
+/// 将路由模式解析为标记
+fn tokenize(pattern: &str) -> Result<Vec<Token>, PatternError> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let segments: Vec<&str> = pattern.split('/').collect();
+
+    for (index, segment) in segments.iter().enumerate() {
+        if index > 0 {
+            text.push('/');
+        }
+        let last = index + 1 == segments.len();
+
+        let (body, braced) = match segment.strip_prefix('{') {
+            Some(inner) => match inner.strip_suffix('}') {
+                Some(inner) => (inner, true),
+                None => return Err(PatternError::UnclosedBrace(pattern.to_string())),
+            },
+            None => (*segment, false),
+        };
+
+        if let Some(name) = body
+            .strip_prefix('*')
+            .filter(|_| braced || segment.starts_with('*'))
+        {
+            if !last {
+                return Err(PatternError::CatchAllNotLast(pattern.to_string()));
+            }
+            if name.is_empty() {
+                return Err(PatternError::EmptyName(pattern.to_string()));
+            }
+            tokens.push(Token::Static(std::mem::take(&mut text)));
+            tokens.push(Token::CatchAll(name.to_string()));
+            continue;
+        }
+
+        let param = if braced {
+            Some(body)
+        } else {
+            segment.strip_prefix(':')
+        };
+        let Some(param) = param else {
+            text.push_str(segment);
+            continue;
+        };
+
+        let (param, optional) = match param.strip_suffix('?') {
+            Some(param) => (param, true),
+            None => (param, false),
+        };
+        let (name, source) = match param.split_once(':').filter(|_| braced) {
+            Some((name, source)) => (name, Some(source)),
+            None => (param, None),
+        };
+        if name.is_empty() {
+            return Err(PatternError::EmptyName(pattern.to_string()));
+        }
+        let constraint = source
+            .map(|source| match SegmentType::parse(source) {
+                Some(ty) => Ok(Constraint::Type(ty)),
+                None => Regex::new(&format!("^(?:{source})$"))
+                    .map(Constraint::Regex)
+                    .map_err(|error| PatternError::InvalidConstraint {
+                        pattern: pattern.to_string(),
+                        constraint: source.to_string(),
+                        reason: error.to_string(),
+                    }),
+            })
+            .transpose()?;
+
+        tokens.push(Token::Static(std::mem::take(&mut text)));
+        tokens.push(Token::Param {
+            name: name.to_string(),
+            source: source.map(str::to_string),
+            constraint,
+            optional,
+        });
+    }
+
+    tokens.push(Token::Static(text));
+    tokens.retain(|token| !matches!(token, Token::Static(text) if text.is_empty()));
+    Ok(tokens)
+}
+
+/// Expand optional parameters into every concrete token list
+/// 将可选参数展开为所有具体的标记列表
+///
+/// Leaving out an optional segment also drops the slash in front of it.
+/// 省略可选段时也会去掉其前面的斜杠。
+fn expand_optional(tokens: Vec<Token>) -> Vec<Vec<Token>> {
+    let mut variants = vec![Vec::new()];
+    for token in tokens {
+        match token {
+            Token::Param { optional: true, .. } => {
+                let mut without: Vec<Vec<Token>> = variants.clone();
+                for variant in &mut without {
+                    if let Some(Token::Static(text)) = variant.last_mut() {
+                        text.pop();
+                        if text.is_empty() {
+                            variant.pop();
+                        }
+                    }
+                }
+                for variant in &mut variants {
+                    variant.push(token.clone());
+                }
+                variants.extend(without);
+            },
+            Token::Static(text) => {
+                for variant in &mut variants {
+                    match variant.last_mut() {
+                        Some(Token::Static(previous)) => previous.push_str(&text),
+                        _ => variant.push(Token::Static(text.clone())),
+                    }
+                }
+            },
+            token => {
+                for variant in &mut variants {
+                    variant.push(token.clone());
+                }
+            },
+        }
+    }
+    // A route that became empty matches the root / 变为空的路由匹配根路径
+    for variant in &mut variants {
+        if variant.is_empty() {
+            variant.push(Token::Static("/".to_string()));
+        }
+    }
+    variants
+}
+
+/// Get the parameter names of a pattern, in order
+/// 按顺序获取模式的参数名称
+pub(crate) fn param_names(pattern: &str) -> Vec<String> {
+    tokenize(pattern)
+        .map(|tokens| {
+            tokens
+                .into_iter()
+                .filter_map(|token| match token {
+                    Token::Param { name, .. } | Token::CatchAll(name) => Some(name),
+                    Token::Static(_) => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Describe the parameters of a pattern, in order
+/// 按顺序描述模式的参数
+pub(crate) fn param_descriptors(pattern: &str) -> Vec<ParamDescriptor> {
+    tokenize(pattern)
+        .map(|tokens| {
+            tokens
+                .into_iter()
+                .filter_map(|token| match token {
+                    Token::Param {
+                        name,
+                        source,
+                        constraint,
+                        optional,
+                    } => {
+                        let kind = match (constraint, source) {
+                            (Some(Constraint::Type(ty)), _) => {
+                                ParamKind::Type(ty.name().to_string())
+                            },
+                            (Some(Constraint::Regex(_)), Some(source)) => ParamKind::Regex(source),
+                            _ => ParamKind::Any,
+                        };
+                        Some(ParamDescriptor {
+                            name,
+                            kind,
+                            optional,
+                        })
+                    },
+                    Token::CatchAll(name) => Some(ParamDescriptor {
+                        name,
+                        kind: ParamKind::CatchAll,
+                        optional: false,
+                    }),
+                    Token::Static(_) => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Get the `{name}` templates of a pattern, one per combination of optional segments
+/// 获取模式的 `{name}` 模板，每种可选段组合一个
+pub(crate) fn path_templates(pattern: &str) -> Vec<String> {
+    tokenize(pattern)
+        .map(|tokens| {
+            expand_optional(tokens)
+                .into_iter()
+                .map(|variant| {
+                    variant
+                        .into_iter()
+                        .map(|token| match token {
+                            Token::Static(text) => text,
+                            Token::Param { name, .. } | Token::CatchAll(name) => {
+                                format!("{{{name}}}")
+                            },
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A registered value and the pattern it was added with
+/// 已注册的值及其添加时使用的模式
+#[derive(Debug, Clone)]
+struct Endpoint<T> {
+    pattern: String,
+    value: T,
+}
+
+/// Parameter child of a node / 节点的参数子节点
+#[derive(Debug, Clone)]
+struct ParamChild<T> {
+    name: String,
+    source: Option<String>,
+    constraint: Option<Constraint>,
+    /// Subtree after the segment, with an empty prefix / 段之后的子树，前缀为空
+    node: Node<T>,
+}
+
+/// Catch-all child of a node / 节点的通配子节点
+#[derive(Debug, Clone)]
+struct CatchAllChild<T> {
+    name: String,
+    endpoint: Endpoint<T>,
+}
+
+/// Node of the radix tree
+/// 基数树的节点
+#[derive(Debug, Clone)]
+pub(crate) struct Node<T> {
+    /// Static text consumed by this node / 此节点消耗的静态文本
+    prefix: String,
+    /// Static children, each starting with a different byte / 静态子节点，各自以不同字节开头
+    statics: Vec<Node<T>>,
+    /// Parameter children in match order / 按匹配顺序排列的参数子节点
+    params: Vec<ParamChild<T>>,
+    catch_all: Option<Box<CatchAllChild<T>>>,
+    endpoint: Option<Endpoint<T>>,
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Self::with_prefix(String::new())
+    }
+}
+
+impl<T: Clone> Node<T> {
+    /// Add a route, returning the value it replaced
+    /// 添加路由，返回被替换的值
+    ///
+    /// Optional segments register one route per combination.
+    /// 可选段会为每种组合注册一个路由。
+    pub(crate) fn insert(&mut self, pattern: &str, value: T) -> Result<Option<T>, PatternError> {
+        let mut replaced = None;
+        for tokens in expand_optional(tokenize(pattern)?) {
+            let previous = self.insert_tokens(tokens, pattern, value.clone());
+            replaced = replaced.or(previous);
+        }
+        Ok(replaced)
+    }
+}
+
+impl<T> Node<T> {
+    fn with_prefix(prefix: String) -> Self {
+        Self {
+            prefix,
+            statics: Vec::new(),
+            params: Vec::new(),
+            catch_all: None,
+            endpoint: None,
+        }
+    }
+
+    fn insert_tokens(&mut self, tokens: Vec<Token>, pattern: &str, value: T) -> Option<T> {
+        let endpoint = Endpoint {
+            pattern: pattern.to_string(),
+            value,
+        };
+        let mut node = self;
+        for token in tokens {
+            match token {
+                Token::Static(text) => node = node.insert_static(&text),
+                Token::Param {
+                    name,
+                    source,
+                    constraint,
+                    ..
+                } => {
+                    let existing = node
+                        .params
+                        .iter()
+                        .position(|child| child.name == name && child.source == source);
+                    let index = if let Some(index) = existing {
+                        index
+                    } else {
+                        // Constrained parameters go before plain ones
+                        // 带约束的参数排在普通参数之前
+                        let index = if constraint.is_some() {
+                            node.params
+                                .iter()
+                                .position(|child| child.constraint.is_none())
+                                .unwrap_or(node.params.len())
+                        } else {
+                            node.params.len()
+                        };
+                        node.params.insert(
+                            index,
+                            ParamChild {
+                                name,
+                                source,
+                                constraint,
+                                node: Node::default(),
+                            },
+                        );
+                        index
+                    };
+                    node = &mut node.params[index].node;
+                },
+                Token::CatchAll(name) => {
+                    let previous = node
+                        .catch_all
+                        .replace(Box::new(CatchAllChild { name, endpoint }));
+                    return previous.map(|child| child.endpoint.value);
+                },
+            }
+        }
+        node.endpoint
+            .replace(endpoint)
+            .map(|endpoint| endpoint.value)
+    }
+
+    /// Walk static text down from this node, splitting nodes where needed
+    /// 从此节点沿静态文本向下，必要时拆分节点
+    fn insert_static(&mut self, text: &str) -> &mut Self {
+        if text.is_empty() {
+            return self;
+        }
+        let Some(index) = self
+            .statics
+            .iter()
+            .position(|child| same_first_byte(&child.prefix, text))
+        else {
+            self.statics.push(Node::with_prefix(text.to_string()));
+            let index = self.statics.len() - 1;
+            return &mut self.statics[index];
+        };
+
+        let child = &mut self.statics[index];
+        let common = common_prefix(&child.prefix, text);
+        if common < child.prefix.len() {
+            // Split the child at the end of the shared prefix
+            // 在公共前缀末尾拆分子节点
+            let suffix = child.prefix.split_off(common);
+            let mut lower = Node::with_prefix(suffix);
+            std::mem::swap(&mut lower.statics, &mut child.statics);
+            std::mem::swap(&mut lower.params, &mut child.params);
+            std::mem::swap(&mut lower.catch_all, &mut child.catch_all);
+            std::mem::swap(&mut lower.endpoint, &mut child.endpoint);
+            child.statics.push(lower);
+        }
+        child.insert_static(&text[common..])
+    }
+
+    /// Find the value for a path, collecting parameters in pattern order
+    /// 查找路径对应的值，并按模式顺序收集参数
+    pub(crate) fn at<'a>(&'a self, path: &str) -> Option<(&'a T, Vec<(String, String)>)> {
+        let mut params = Vec::new();
+        let value = self.match_children(path, &mut params)?;
+        let params = params
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Some((value, params))
+    }
+
+    fn match_node<'a, 'p>(
+        &'a self,
+        path: &'p str,
+        params: &mut Vec<(&'a str, &'p str)>,
+    ) -> Option<&'a T> {
+        let rest = path.strip_prefix(self.prefix.as_str())?;
+        self.match_children(rest, params)
+    }
+
+    fn match_children<'a, 'p>(
+        &'a self,
+        rest: &'p str,
+        params: &mut Vec<(&'a str, &'p str)>,
+    ) -> Option<&'a T> {
+        if rest.is_empty() {
+            return self.endpoint.as_ref().map(|endpoint| &endpoint.value);
+        }
+
+        if let Some(child) = self
+            .statics
+            .iter()
+            .find(|child| same_first_byte(&child.prefix, rest))
+            && let Some(value) = child.match_node(rest, params)
+        {
+            return Some(value);
+        }
+
+        let end = rest.find('/').unwrap_or(rest.len());
+        let (segment, after) = rest.split_at(end);
+        if !segment.is_empty() {
+            for child in &self.params {
+                if !child
+                    .constraint
+                    .as_ref()
+                    .is_none_or(|constraint| constraint.matches(segment))
+                {
+                    continue;
+                }
+                params.push((&child.name, segment));
+                if let Some(value) = child.node.match_children(after, params) {
+                    return Some(value);
+                }
+                params.pop();
+            }
+        }
+
+        let child = self.catch_all.as_ref()?;
+        params.push((&child.name, rest));
+        Some(&child.endpoint.value)
+    }
+
+    /// Get every registered pattern, without duplicates
+    /// 获取所有已注册的模式（去重）
+    pub(crate) fn patterns(&self) -> Vec<String> {
+        let mut patterns = Vec::new();
+        self.for_each_endpoint(&mut |endpoint| {
+            if !patterns.contains(&endpoint.pattern) {
+                patterns.push(endpoint.pattern.clone());
+            }
+        });
+        patterns
+    }
+
+    /// Get every registered pattern with its value, without duplicates
+    /// 获取所有已注册的模式及其值（去重）
+    pub(crate) fn entries(&self) -> Vec<(&str, &T)> {
+        let mut entries: Vec<(&str, &T)> = Vec::new();
+        self.for_each_endpoint(&mut |endpoint| {
+            if !entries
+                .iter()
+                .any(|(pattern, _)| *pattern == endpoint.pattern)
+            {
+                entries.push((&endpoint.pattern, &endpoint.value));
+            }
+        });
+        entries
+    }
+
+    /// Apply `f` to every stored value, including the copies made for optional segments
+    /// 对每个存储的值应用 `f`，包括为可选段生成的副本
+    pub(crate) fn for_each_value_mut(&mut self, f: &mut impl FnMut(&mut T)) {
+        if let Some(endpoint) = &mut self.endpoint {
+            f(&mut endpoint.value);
+        }
+        if let Some(child) = &mut self.catch_all {
+            f(&mut child.endpoint.value);
+        }
+        for child in &mut self.statics {
+            child.for_each_value_mut(f);
+        }
+        for child in &mut self.params {
+            child.node.for_each_value_mut(f);
+        }
+    }
+
+    fn for_each_endpoint<'a>(&'a self, f: &mut impl FnMut(&'a Endpoint<T>)) {
+        if let Some(endpoint) = &self.endpoint {
+            f(endpoint);
+        }
+        if let Some(child) = &self.catch_all {
+            f(&child.endpoint);
+        }
+        for child in &self.statics {
+            child.for_each_endpoint(f);
+        }
+        for child in &self.params {
+            child.node.for_each_endpoint(f);
+        }
+    }
+}
+
+/// Check whether both strings start with the same byte
+/// 检查两个字符串是否以相同字节开头
+fn same_first_byte(a: &str, b: &str) -> bool {
+    a.as_bytes().first() == b.as_bytes().first()
+}
+
+/// Length in bytes of the shared prefix, on a char boundary
+/// 公共前缀的字节长度（位于字符边界）
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map_or_else(|| a.len().min(b.len()), |((index, _), _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(patterns: &[&'static str]) -> Node<&'static str> {
+        let mut tree = Node::default();
+        for pattern in patterns {
+            tree.insert(pattern, *pattern).unwrap();
+        }
+        tree
+    }
+
+    fn lookup(
+        tree: &Node<&'static str>,
+        path: &str,
+    ) -> Option<(&'static str, Vec<(String, String)>)> {
+        tree.at(path).map(|(value, params)| (*value, params))
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_static_beats_param_in_any_order() {
+        for patterns in [["/users/me", "/users/:id"], ["/users/:id", "/users/me"]] {
+            let tree = build(&patterns);
+            assert_eq!(lookup(&tree, "/users/me"), Some(("/users/me", vec![])));
+            assert_eq!(
+                lookup(&tree, "/users/meow"),
+                Some(("/users/:id", params(&[("id", "meow")])))
+            );
+        }
+    }
+
+    #[test]
+    fn test_params_and_split_prefixes() {
+        let tree = build(&["/", "/user", "/users", "/users/:uid/posts/:pid", "/usage"]);
+        assert_eq!(lookup(&tree, "/").unwrap().0, "/");
+        assert_eq!(lookup(&tree, "/user").unwrap().0, "/user");
+        assert_eq!(lookup(&tree, "/usage").unwrap().0, "/usage");
+        assert_eq!(
+            lookup(&tree, "/users/42/posts/99"),
+            Some(("/users/:uid/posts/:pid", params(&[("uid", "42"), ("pid", "99")])))
+        );
+        assert!(lookup(&tree, "/users/42/posts").is_none());
+        assert!(lookup(&tree, "/users//posts/1").is_none());
+        assert!(lookup(&tree, "/posts/123").is_none());
+    }
+
+    #[test]
+    fn test_constraints_fall_through() {
+        let tree = build(&[
+            "/items/{id:u64}",
+            "/items/{slug:[a-z-]+}",
+            "/items/:other",
+            "/items/{id:u64}/raw",
+        ]);
+        assert_eq!(lookup(&tree, "/items/42").unwrap().0, "/items/{id:u64}");
+        assert_eq!(lookup(&tree, "/items/red-hat").unwrap().0, "/items/{slug:[a-z-]+}");
+        assert_eq!(lookup(&tree, "/items/Red_Hat").unwrap().0, "/items/:other");
+        assert_eq!(lookup(&tree, "/items/42/raw").unwrap().0, "/items/{id:u64}/raw");
+        assert!(lookup(&tree, "/items/x/raw").is_none());
+        assert_eq!(lookup(&tree, "/items/18446744073709551616").unwrap().0, "/items/:other");
+    }
+
+    #[test]
+    fn test_uuid_constraint() {
+        let tree = build(&["/orders/{id:uuid}"]);
+        assert!(lookup(&tree, "/orders/67e55044-10b1-426f-9247-bb680e5fe0c8").is_some());
+        assert!(lookup(&tree, "/orders/67e55044").is_none());
+    }
+
+    #[test]
+    fn test_catch_all_is_last_resort() {
+        let tree = build(&["/files/*rest", "/files/readme", "/{*path}"]);
+        assert_eq!(lookup(&tree, "/files/readme").unwrap().0, "/files/readme");
+        assert_eq!(
+            lookup(&tree, "/files/a/b.txt"),
+            Some(("/files/*rest", params(&[("rest", "a/b.txt")])))
+        );
+        assert_eq!(lookup(&tree, "/nested/path").unwrap().0, "/{*path}");
+        assert_eq!(lookup(&tree, "/files/").unwrap().0, "/{*path}");
+        assert!(lookup(&tree, "/").is_none());
+    }
+
+    #[test]
+    fn test_optional_segments() {
+        let tree = build(&["/posts/:year?/{month:u8?}"]);
+        assert_eq!(lookup(&tree, "/posts").unwrap().1, params(&[]));
+        assert_eq!(lookup(&tree, "/posts/2024").unwrap().1, params(&[("year", "2024")]));
+        assert_eq!(
+            lookup(&tree, "/posts/2024/5").unwrap().1,
+            params(&[("year", "2024"), ("month", "5")])
+        );
+        assert!(lookup(&tree, "/posts/2024/may").is_none());
+        assert_eq!(tree.patterns(), ["/posts/:year?/{month:u8?}"]);
+        assert_eq!(tree.entries().len(), 1);
+
+        let tree = build(&["/:lang?"]);
+        assert!(lookup(&tree, "/").is_some());
+        assert!(lookup(&tree, "/en").is_some());
+    }
+
+    #[test]
+    fn test_insert_replaces_and_rejects_bad_patterns() {
+        let mut tree = Node::default();
+        assert_eq!(tree.insert("/a/:id", 1), Ok(None));
+        assert_eq!(tree.insert("/a/:id", 2), Ok(Some(1)));
+        assert_eq!(tree.at("/a/x").map(|(value, _)| *value), Some(2));
+
+        assert!(matches!(tree.insert("/a/{id", 0), Err(PatternError::UnclosedBrace(_))));
+        assert!(matches!(tree.insert("/*rest/more", 0), Err(PatternError::CatchAllNotLast(_))));
+        assert!(matches!(tree.insert("/a/:", 0), Err(PatternError::EmptyName(_))));
+        assert!(matches!(
+            tree.insert("/a/{id:[}", 0),
+            Err(PatternError::InvalidConstraint { .. })
+        ));
+    }
+
+    #[test]
+    fn test_param_names() {
+        assert_eq!(param_names("/users/:id"), ["id"]);
+        assert_eq!(param_names("/u/{uid:u64}/p/:pid?/{*rest}"), ["uid", "pid", "rest"]);
+        assert!(param_names("/users").is_empty());
+    }
+}
@@ -1,15 +1,20 @@
-//! Trie-based router using matchit
-//! 使用 matchit 的基于 Trie 的路由器
+//! Trie-based router over the shared radix tree
+//! 基于共享基数树的 Trie 路由器
+//!
+//! Uses the same [pattern syntax](crate::tree) and precedence as [`Router`](crate::Router).
+//! 与 [`Router`](crate::Router) 使用相同的[模式语法](crate::tree)和优先级。
 //!
 //! # Equivalent to Spring Boot / 等价于 Spring Boot
 //!
-//! - AntPathMatcher for path pattern matching
+//! - `PathPatternParser` for path pattern matching
 //! - @PathVariable with URI templates
 
 #![warn(missing_docs)]
 #![warn(unreachable_pub)]
 
 use super::{Method, route::Handler};
+use crate::descriptor::RouteDescriptor;
+use crate::tree::{self, Node};
 use nexus_http::{Body, Request, Response, Result, StatusCode};
 use std::collections::HashMap;
 use std::future::Future;
@@ -25,13 +30,13 @@ use std::future::Future;
 pub struct TrieRouter {
     /// Per-method routers for efficient matching
     /// 每个方法一个路由器以提高效率
-    get: matchit::Router<MethodRoute>,
-    post: matchit::Router<MethodRoute>,
-    put: matchit::Router<MethodRoute>,
-    delete: matchit::Router<MethodRoute>,
-    patch: matchit::Router<MethodRoute>,
-    head: matchit::Router<MethodRoute>,
-    options: matchit::Router<MethodRoute>,
+    get: Node<MethodRoute>,
+    post: Node<MethodRoute>,
+    put: Node<MethodRoute>,
+    delete: Node<MethodRoute>,
+    patch: Node<MethodRoute>,
+    head: Node<MethodRoute>,
+    options: Node<MethodRoute>,
 }
 
 /// A route that can be called
@@ -51,13 +56,13 @@ impl TrieRouter {
     /// 创建新的 Trie 路由器
     pub fn new() -> Self {
         Self {
-            get: matchit::Router::new(),
-            post: matchit::Router::new(),
-            put: matchit::Router::new(),
-            delete: matchit::Router::new(),
-            patch: matchit::Router::new(),
-            head: matchit::Router::new(),
-            options: matchit::Router::new(),
+            get: Node::default(),
+            post: Node::default(),
+            put: Node::default(),
+            delete: Node::default(),
+            patch: Node::default(),
+            head: Node::default(),
+            options: Node::default(),
         }
     }
 
@@ -70,6 +75,9 @@ impl TrieRouter {
     /// * `method` - The HTTP method / HTTP方法
     /// * `handler` - The handler function / 处理函数
     ///
+    /// Inserting the same pattern again replaces its handler.
+    /// 再次插入相同模式会替换其处理程序。
+    ///
     /// # Example / 示例
     ///
     /// ```rust,no_run,ignore
@@ -80,24 +88,12 @@ impl TrieRouter {
     /// router.insert("/users/:id", Method::GET, get_user_handler);
     /// ```
     pub fn insert(&mut self, path: &str, method: Method, handler: Handler) -> Result<()> {
-        // Convert path to matchit format (uses :param instead of {param})
-        // matchit uses :param style which we already use
-        let router = self.router_for_method_mut(&method);
-
-        // Extract parameter names from path
-        let param_names: Vec<String> = path
-            .split('/')
-            .filter_map(|s| s.strip_prefix(':').map(|s| s.to_string()))
-            .collect();
-
-        router
-            .insert(
-                path,
-                MethodRoute {
-                    handler,
-                    param_names,
-                },
-            )
+        let route = MethodRoute {
+            handler,
+            param_names: tree::param_names(path),
+        };
+        self.router_for_method_mut(&method)
+            .insert(path, route)
             .map_err(|e| {
                 nexus_http::Error::InvalidRequest(format!("Invalid route pattern: {}", e))
             })?;
@@ -117,21 +113,13 @@ impl TrieRouter {
         method: &Method,
         path: &str,
     ) -> Option<(Handler, HashMap<String, String>)> {
-        let router = self.router_for_method(method)?;
-        let matched = router.at(path).ok()?;
-
-        let params: HashMap<String, String> = matched
-            .params
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_string()))
-            .collect();
-
-        Some((matched.value.handler.clone(), params))
+        let (route, params) = self.router_for_method(method)?.at(path)?;
+        Some((route.handler.clone(), params.into_iter().collect()))
     }
 
     /// Get the router for a specific method (mutable)
     /// 获取特定方法的路由器（可变）
-    fn router_for_method_mut(&mut self, method: &Method) -> &mut matchit::Router<MethodRoute> {
+    fn router_for_method_mut(&mut self, method: &Method) -> &mut Node<MethodRoute> {
         match method {
             Method::GET => &mut self.get,
             Method::POST => &mut self.post,
@@ -146,7 +134,7 @@ impl TrieRouter {
 
     /// Get the router for a specific method
     /// 获取特定方法的路由器
-    fn router_for_method(&self, method: &Method) -> Option<&matchit::Router<MethodRoute>> {
+    fn router_for_method(&self, method: &Method) -> Option<&Node<MethodRoute>> {
         match method {
             Method::GET => Some(&self.get),
             Method::POST => Some(&self.post),
@@ -159,15 +147,43 @@ impl TrieRouter {
         }
     }
 
-    /// Get all routes for a specific method
-    /// 获取特定方法的所有路由
+    /// Describe every registered route, ordered by pattern
+    /// 描述每个已注册的路由，按模式排序
     ///
-    /// Note: This returns an empty vector as matchit doesn't expose
-    /// the list of registered routes. Use route testing instead.
-    pub fn routes(&self, _method: &Method) -> Vec<String> {
-        // matchit Router doesn't expose a way to iterate over registered routes
-        // Users should test routes via match_request instead
-        Vec::new()
+    /// The handler is named after its kind, e.g. `Handler::Async`.
+    /// 处理程序以其种类命名，例如 `Handler::Async`。
+    pub fn descriptors(&self) -> Vec<RouteDescriptor> {
+        let mut descriptors = Vec::new();
+        for method in [
+            Method::GET,
+            Method::HEAD,
+            Method::POST,
+            Method::PUT,
+            Method::DELETE,
+            Method::PATCH,
+            Method::OPTIONS,
+        ] {
+            let Some(node) = self.router_for_method(&method) else {
+                continue;
+            };
+            for (pattern, route) in node.entries() {
+                descriptors.push(RouteDescriptor::new(
+                    method,
+                    pattern,
+                    format!("{:?}", route.handler),
+                ));
+            }
+        }
+        descriptors.sort_by(|a, b| a.pattern.cmp(&b.pattern));
+        descriptors
+    }
+
+    /// Get all route patterns for a specific method
+    /// 获取特定方法的所有路由模式
+    pub fn routes(&self, method: &Method) -> Vec<String> {
+        self.router_for_method(method)
+            .map(Node::patterns)
+            .unwrap_or_default()
     }
 }
 
@@ -283,4 +299,66 @@ mod tests {
         assert!(router.match_request(&Method::GET, "/anything").is_some());
         assert!(router.match_request(&Method::GET, "/nested/path").is_some());
     }
+
+    #[test]
+    fn test_static_param_precedence_and_routes() {
+        let mut router = TrieRouter::new();
+        router
+            .insert("/users/:id", Method::GET, Handler::Static("User"))
+            .unwrap();
+        router
+            .insert("/users/me", Method::GET, Handler::Static("Me"))
+            .unwrap();
+        router
+            .insert("/users/{id:u64}/posts", Method::GET, Handler::Static("Posts"))
+            .unwrap();
+
+        let (_, params) = router.match_request(&Method::GET, "/users/me").unwrap();
+        assert!(params.is_empty());
+        assert!(
+            router
+                .match_request(&Method::GET, "/users/abc/posts")
+                .is_none()
+        );
+        assert!(
+            router
+                .match_request(&Method::GET, "/users/7/posts")
+                .is_some()
+        );
+
+        let mut patterns = router.routes(&Method::GET);
+        patterns.sort();
+        assert_eq!(patterns, ["/users/:id", "/users/me", "/users/{id:u64}/posts"]);
+        assert!(router.routes(&Method::POST).is_empty());
+        assert!(
+            router
+                .insert("/{*a}/b", Method::GET, Handler::Static(""))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_descriptors() {
+        let mut router = TrieRouter::new();
+        router
+            .insert("/users/{id:u64}", Method::DELETE, Handler::Static("Deleted"))
+            .unwrap();
+        router
+            .insert("/users/{id:u64}", Method::GET, Handler::Unimplemented)
+            .unwrap();
+
+        let descriptors = router.descriptors();
+        let summary: Vec<_> = descriptors
+            .iter()
+            .map(|d| (d.method, d.pattern.as_str(), d.handler.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Method::GET, "/users/{id:u64}", "Handler::Unimplemented"),
+                (Method::DELETE, "/users/{id:u64}", "Handler::Static(Deleted)"),
+            ]
+        );
+        assert_eq!(descriptors[0].params[0].name, "id");
+    }
 }
@@ -13,6 +13,7 @@ use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::time::Duration;
 
 use crate::driver::{CompletionEntry, Driver, ERROR_TRANSPORT, Interest, SubmitEntry};
+use crate::metrics;
 
 /// Minimum epoll instance size / 最小epoll实例大小
 const MIN_EPOLL_SIZE: u32 = 32;
@@ -255,6 +256,9 @@ impl Driver for EpollDriver {
                 match entry.opcode {
                     crate::driver::opcode::READ => event.events |= libc::EPOLLIN as u32,
                     crate::driver::opcode::WRITE => event.events |= libc::EPOLLOUT as u32,
+                    crate::driver::opcode::POLL => {
+                        event.events |= (libc::EPOLLIN | libc::EPOLLOUT) as u32;
+                    },
                     _ => {},
                 }
 
@@ -292,6 +296,7 @@ impl Driver for EpollDriver {
         // 前进head
         self.state.submit_head.store(tail, Ordering::Release);
 
+        metrics::EPOLL.record_submit(submitted);
         Ok(submitted)
     }
 
@@ -322,11 +327,14 @@ impl Driver for EpollDriver {
             return None;
         }
 
+        self.state.submit_tail.store(next_tail, Ordering::Release);
+
         let pos = self.submit_pos(tail);
         // SAFETY: We have exclusive access to this position
         // 我们对此位置有独占访问权
         unsafe {
             let submit_queue = &mut *self.submit_queue.get();
+            submit_queue[pos] = SubmitEntry::new(-1, 0, 0);
             Some(&mut submit_queue[pos])
         }
     }
@@ -418,6 +426,7 @@ impl Driver for EpollDriver {
             crate::driver::opcode::READ
                 | crate::driver::opcode::WRITE
                 | crate::driver::opcode::CLOSE
+                | crate::driver::opcode::POLL
         )
     }
 }
@@ -437,6 +446,7 @@ impl EpollDriver {
         }
 
         let count = result as usize;
+        metrics::EPOLL.record_wait(count);
 
         // Process events into completion queue
         // 将事件处理到完成队列
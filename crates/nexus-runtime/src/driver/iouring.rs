@@ -7,176 +7,65 @@
 //!
 //! 本模块为Linux系统提供基于io_uring的I/O驱动。
 //! io_uring是Linux上最快的I/O机制，通过共享内存队列和零拷贝I/O提供卓越的性能。
+//!
+//! # Completion-based I/O / 基于完成的I/O
+//!
+//! Unlike the epoll driver, submitted entries are executed by the kernel: a
+//! `RECV` completion carries the bytes received, an `ACCEPT` completion the new
+//! file descriptor. Multishot accept and receive keep posting completions (with
+//! [`CompletionEntry::has_more`]) until cancelled, and receives can draw their
+//! buffers from a [`BufRing`] registered with
+//! [`IoUringDriver::register_buf_ring`] instead of pinning one buffer per socket.
+//!
+//! 与epoll driver不同，提交的条目由内核执行：`RECV` 完成事件携带接收的字节数，
+//! `ACCEPT` 完成事件携带新的文件描述符。多次触发的accept和receive会持续产生完成事件
+//! （带 [`CompletionEntry::has_more`]）直到被取消；接收操作可以从通过
+//! [`IoUringDriver::register_buf_ring`] 注册的 [`BufRing`] 中获取缓冲区，
+//! 而不必为每个套接字固定一个缓冲区。
+//!
+//! | Operation / 操作 | Kernel / 内核 |
+//! |------------------|---------------|
+//! | read, write, fsync, accept, send, recv, cancel | 5.6+ |
+//! | close | 5.6+ |
+//! | multishot accept / 多次触发accept | 5.19+ |
+//! | buffer rings, multishot recv / 缓冲区环、多次触发recv | 5.19+ / 6.0+ |
 
 #![cfg(target_os = "linux")]
 
+use std::alloc::{Layout, alloc_zeroed, dealloc};
 use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::ops::Deref;
 use std::os::fd::{AsRawFd, RawFd};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU16, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use crate::driver::{CompletionEntry, Driver, ERROR_TRANSPORT, Interest, SubmitEntry};
+use io_uring::{IoUring, Probe, opcode as op, squeue, types};
+
+use crate::driver::{CompletionEntry, Driver, Interest, SubmitEntry, op_flags, opcode};
+use crate::metrics;
 
 /// Minimum io_uring instance size / 最小io_uring实例大小
 const MIN_IOURING_SIZE: u32 = 32;
 
-/// Maximum entries in submission queue (for CQE overflow handling)
-/// 提交队列中的最大条目数（用于CQE溢出处理）
-const MAX_CQES: u32 = 256;
-
-/// io_uring setup flags / io_uring设置标志
-#[repr(C)]
-#[derive(Clone, Copy, Debug, Default)]
-struct IoUringParams {
-    /// Submission queue entries / 提交队列条目数
-    sq_entries: u32,
-    /// Completion queue entries / 完成队列条目数
-    cq_entries: u32,
-    /// Flags / 标志
-    flags: u32,
-    /// Reserved fields / 保留字段
-    _resv: [u32; 5],
-    /// Submission queue ring buffer offset / 提交队列环形缓冲区偏移
-    sq_off: IoUringOffsets,
-    /// Completion queue ring buffer offset / 完成队列环形缓冲区偏移
-    cq_off: IoUringOffsets,
-}
-
-/// io_uring offsets for ring buffer access
-/// io_uring环形缓冲区访问的偏移量
-#[repr(C)]
-#[derive(Clone, Copy, Debug, Default)]
-struct IoUringOffsets {
-    /// Head index / 头索引
-    head: u32,
-    /// Tail index / 尾索引
-    tail: u32,
-    /// Ring mask / 环形掩码
-    ring_mask: u32,
-    /// Ring entries count / 环形条目数
-    ring_entries: u32,
-    /// Flags / 标志
-    flags: u32,
-    /// Dropped entries / 丢弃的条目
-    dropped: u32,
-    /// Array offset / 数组偏移
-    array: u32,
-    /// Reserved fields / 保留字段
-    _resv: [u32; 3],
-}
-
-/// io_uring submission queue entry (SQE)
-/// io_uring提交队列条目(SQE)
-#[repr(C)]
-#[derive(Clone, Copy)]
-struct SubmissionQueueEntry {
-    /// Opcode / 操作码
-    opcode: u8,
-    /// Flags / 标志
-    flags: u8,
-    /// I/O priority / I/O优先级
-    ioprio: u16,
-    /// File descriptor / 文件描述符
-    fd: i32,
-    /// Offset / 偏移量
-    offset: u64,
-    /// Address / 地址
-    addr: u64,
-    /// Length / 长度
-    len: u32,
-    /// Flags for operation / 操作标志
-    rw_flags: i32,
-    /// User data / 用户数据
-    user_data: u64,
-    /// Buffer select / 缓冲区选择
-    buf_index: u16,
-    /// Personality / 个性
-    personality: u16,
-    /// Spare fields / 备用字段
-    _spare: [u64; 3],
-}
-
-/// io_uring completion queue entry (CQE)
-/// io_uring完成队列条目(CQE)
-#[repr(C)]
-#[derive(Clone, Copy)]
-struct CompletionQueueEntry {
-    /// User data / 用户数据
-    user_data: u64,
-    /// Result / 结果
-    res: i32,
-    /// Flags / 标志
-    flags: u32,
-    /// Reserved fields / 保留字段
-    _resv: [u64; 2],
-}
-
-/// io_uring submission queue
-/// io_uring提交队列
-struct SubmissionQueue {
-    /// Head index / 头索引
-    head: *const u32,
-    /// Tail index / 尾索引
-    tail: *const u32,
-    /// Ring mask / 环形掩码
-    ring_mask: *const u32,
-    /// Ring entries / 环形条目数
-    ring_entries: *const u32,
-    /// Flags / 标志
-    flags: *const u32,
-    /// Array / 数组
-    array: *mut u32,
-    /// Submission queue entries / 提交队列条目
-    sqes: *mut SubmissionQueueEntry,
-    /// Ring mask value / 环形掩码值
-    ring_mask_value: u32,
-    /// Number of entries / 条目数
-    entries: u32,
-}
-
-// SAFETY: SubmissionQueue uses raw pointers for direct memory access
-// SubmissionQueue使用原始指针进行直接内存访问
-unsafe impl Send for SubmissionQueue {}
-unsafe impl Sync for SubmissionQueue {}
-
-/// io_uring completion queue
-/// io_uring完成队列
-struct CompletionQueue {
-    /// Head index / 头索引
-    head: *const u32,
-    /// Tail index / 尾索引
-    tail: *const u32,
-    /// Ring mask / 环形掩码
-    ring_mask: *const u32,
-    /// Ring entries / 环形条目数
-    ring_entries: *const u32,
-    /// Overflow / 溢出
-    overflow: *const u32,
-    /// Completion queue entries / 完成队列条目
-    cqes: *const CompletionQueueEntry,
-    /// Ring mask value / 环形掩码值
-    ring_mask_value: u32,
-}
+/// Upper bound for [`Driver::wait`], so an idle runtime still advances its timers
+/// [`Driver::wait`] 的上限，使空闲的运行时仍能推进定时器
+const WAIT_INTERVAL: Duration = Duration::from_secs(1);
 
-// SAFETY: CompletionQueue uses raw pointers for read-only memory access
-// CompletionQueue使用原始指针进行只读内存访问
-unsafe impl Send for CompletionQueue {}
-unsafe impl Sync for CompletionQueue {}
+/// Largest number of buffers in a buffer ring / 缓冲区环中的最大缓冲区数
+const MAX_BUF_RING_ENTRIES: u16 = 1 << 15;
 
-/// Internal state for the io_uring driver
-/// io_uring driver的内部状态
+/// Application-side queue cursors
+/// 应用层队列游标
 struct IoUringState {
-    /// Submission queue head index / 提交队列头索引
-    sq_head: AtomicU32,
-    /// Submission queue tail index / 提交队列尾索引
-    sq_tail: AtomicU32,
+    /// Entries handed out by `get_submission` / 通过 `get_submission` 分配的条目数
+    sq_len: AtomicUsize,
     /// Completion queue head index / 完成队列头索引
     cq_head: AtomicU32,
     /// Completion queue tail index / 完成队列尾索引
     cq_tail: AtomicU32,
-    /// Submission queue length / 提交队列长度
-    sq_len: AtomicUsize,
 }
 
 /// io_uring-based I/O driver for Linux
@@ -188,38 +77,40 @@ struct IoUringState {
 /// io_uring provides:
 /// io_uring提供：
 /// - Shared memory queues for reduced syscall overhead / 共享内存队列减少系统调用开销
-/// - Zero-copy I/O support / 零拷贝I/O支持
+/// - Completion-based accept, recv, send, read, write, fsync and close
+///   / 基于完成的accept、recv、send、read、write、fsync和close
+/// - Multishot accept and receive / 多次触发的accept和receive
+/// - Kernel-selected buffers from registered buffer rings / 内核从已注册缓冲区环中选择缓冲区
 /// - Batched operation submission / 批量操作提交
-/// - Efficient poll-based I/O / 高效的基于轮询的I/O
 pub struct IoUringDriver {
-    /// io_uring instance file descriptor / io_uring实例文件描述符
-    ring_fd: RawFd,
-    /// Submission queue ring buffer memory (mapped) / 提交队列环形缓冲区内存（映射）
-    sq_ring: *mut u8,
-    /// Completion queue ring buffer memory (mapped) / 完成队列环形缓冲区内存（映射）
-    cq_ring: *mut u8,
-    /// Submission queue entries memory (mapped) / 提交队列条目内存（映射）
-    sqes: *mut SubmissionQueueEntry,
-    /// Submission queue / 提交队列
-    sq: SubmissionQueue,
-    /// Completion queue / 完成队列
-    cq: CompletionQueue,
-    /// Queue capacity / 队列容量
+    /// The ring; dropped before the buffer rings it uses / 环；先于其使用的缓冲区环释放
+    ring: IoUring,
+    /// Serializes access to the kernel submission queue / 串行化对内核提交队列的访问
+    sq_lock: Mutex<()>,
+    /// Serializes access to the kernel completion queue / 串行化对内核完成队列的访问
+    cq_lock: Mutex<()>,
+    /// Submission queue capacity / 提交队列容量
     capacity: usize,
+    /// Completion queue capacity / 完成队列容量
+    cq_capacity: usize,
     /// Internal state / 内部状态
-    state: Arc<IoUringState>,
+    state: IoUringState,
     /// Submission queue / 提交队列（用于应用层）
     submit_queue: UnsafeCell<Vec<SubmitEntry>>,
     /// Completion queue / 完成队列（用于应用层）
     completion_queue: UnsafeCell<Vec<Option<CompletionEntry>>>,
+    /// Bit per [`opcode`] the kernel supports / 内核支持的每个 [`opcode`] 对应一位
+    supported: u16,
+    /// Registered buffer rings by group / 按组注册的缓冲区环
+    buf_rings: Mutex<HashMap<u16, Arc<BufRing>>>,
 }
 
-// SAFETY: IoUringDriver can be sent between threads
-// IoUringDriver可以在线程间发送
+// SAFETY: the kernel queues are only touched under `sq_lock`/`cq_lock`; the
+// application queues follow the same single-consumer contract as the other drivers
+// 安全性：内核队列仅在 `sq_lock`/`cq_lock` 下访问；应用层队列遵循与其他driver相同的单消费者约定
 unsafe impl Send for IoUringDriver {}
 
-// SAFETY: IoUringDriver can be shared between threads (uses atomic operations and memory barriers)
-// IoUringDriver可以在线程间共享（使用原子操作和内存屏障）
+// SAFETY: see `Send` / 安全性：见 `Send`
 unsafe impl Sync for IoUringDriver {}
 
 impl IoUringDriver {
@@ -241,253 +132,241 @@ impl IoUringDriver {
     ///
     /// Returns an error if:
     /// 返回错误如果：
-    /// - The configuration is invalid / 配置无效
     /// - io_uring setup fails / io_uring设置失败
     /// - Memory mapping fails / 内存映射失败
     pub fn with_config(config: crate::driver::DriverConfig) -> std::io::Result<Self> {
         let entries = config.entries.max(MIN_IOURING_SIZE);
-
-        // Setup io_uring parameters
-        // 设置io_uring参数
-        let mut params = IoUringParams {
-            sq_entries: entries,
-            cq_entries: entries,
-            flags: 0, // No flags for now / 目前无标志
-            ..Default::default()
-        };
-
-        // Create io_uring instance
-        // 创建io_uring实例
-        let ring_fd = unsafe {
-            libc::syscall(
-                425, // __NR_io_uring_setup
-                entries as libc::c_long,
-                &mut params as *mut _ as libc::c_long,
-            ) as RawFd
-        };
-
-        if ring_fd < 0 {
-            return Err(std::io::Error::last_os_error());
-        }
-
-        // Calculate ring buffer sizes
-        // 计算环形缓冲区大小
-        let sq_ring_size = unsafe {
-            // Size = sq_off.array + sq_entries * sizeof(u32)
-            ((params.sq_off.array as usize) + (params.sq_entries as usize) * 4)
-        };
-
-        let cq_ring_size = unsafe {
-            // Size = cq_off.cqes + cq_entries * sizeof(cqe)
-            ((params.cq_off.array as usize) + (params.cq_entries as usize) * 16)
-        };
-
-        let sqes_size = (params.sq_entries as usize) * std::mem::size_of::<SubmissionQueueEntry>();
-
-        // Map memory regions
-        // 映射内存区域
-        let sq_ring = unsafe {
-            libc::mmap(
-                std::ptr::null_mut(),
-                sq_ring_size,
-                libc::PROT_READ | libc::PROT_WRITE,
-                libc::MAP_SHARED | libc::MAP_POPULATE,
-                ring_fd,
-                0, // Submission queue ring is at offset 0
-            )
-        };
-
-        if sq_ring == libc::MAP_FAILED {
-            unsafe { libc::close(ring_fd) };
-            return Err(std::io::Error::last_os_error());
-        }
-
-        let cq_ring = unsafe {
-            libc::mmap(
-                std::ptr::null_mut(),
-                cq_ring_size,
-                libc::PROT_READ | libc::PROT_WRITE,
-                libc::MAP_SHARED | libc::MAP_POPULATE,
-                ring_fd,
-                sq_ring_size as libc::off_t, // Completion queue ring is after submission queue
-            )
-        };
-
-        if cq_ring == libc::MAP_FAILED {
-            unsafe {
-                libc::munmap(sq_ring, sq_ring_size);
-                libc::close(ring_fd);
-            }
-            return Err(std::io::Error::last_os_error());
-        }
-
-        let sqes = unsafe {
-            libc::mmap(
-                std::ptr::null_mut(),
-                sqes_size,
-                libc::PROT_READ | libc::PROT_WRITE,
-                libc::MAP_SHARED | libc::MAP_POPULATE,
-                ring_fd,
-                0x8000000_usize as libc::off_t, // SQEs are at this offset (IORING_OFF_SQES)
-            )
-        };
-
-        if sqes == libc::MAP_FAILED {
-            unsafe {
-                libc::munmap(sq_ring, sq_ring_size);
-                libc::munmap(cq_ring, cq_ring_size);
-                libc::close(ring_fd);
-            }
-            return Err(std::io::Error::last_os_error());
-        }
-
-        // Setup submission queue
-        // 设置提交队列
-        let sq = unsafe {
-            let sq_ptr = sq_ring as *const u8;
-
-            SubmissionQueue {
-                head: sq_ptr.add(params.sq_off.head as usize) as *const u32,
-                tail: sq_ptr.add(params.sq_off.tail as usize) as *const u32,
-                ring_mask: sq_ptr.add(params.sq_off.ring_mask as usize) as *const u32,
-                ring_entries: sq_ptr.add(params.sq_off.ring_entries as usize) as *const u32,
-                flags: sq_ptr.add(params.sq_off.flags as usize) as *const u32,
-                array: sq_ptr.add(params.sq_off.array as usize) as *mut u32,
-                sqes: sqes as *mut SubmissionQueueEntry,
-                ring_mask_value: *(sq_ptr.add(params.sq_off.ring_mask as usize) as *const u32),
-                entries: params.sq_entries,
-            }
-        };
-
-        // Setup completion queue
-        // 设置完成队列
-        let cq = unsafe {
-            let cq_ptr = cq_ring as *const u8;
-
-            CompletionQueue {
-                head: cq_ptr.add(params.cq_off.head as usize) as *const u32,
-                tail: cq_ptr.add(params.cq_off.tail as usize) as *const u32,
-                ring_mask: cq_ptr.add(params.cq_off.ring_mask as usize) as *const u32,
-                ring_entries: cq_ptr.add(params.cq_off.ring_entries as usize) as *const u32,
-                overflow: cq_ptr.add(params.cq_off.overflow as usize) as *const u32,
-                cqes: cq_ptr.add(params.cq_off.cqes as usize) as *const CompletionQueueEntry,
-                ring_mask_value: *(cq_ptr.add(params.cq_off.ring_mask as usize) as *const u32),
-            }
-        };
-
-        let capacity = entries as usize;
+        let ring = IoUring::new(entries)?;
+
+        let mut probe = Probe::new();
+        ring.submitter().register_probe(&mut probe)?;
+        let supported = [
+            (opcode::READ, op::Read::CODE),
+            (opcode::WRITE, op::Write::CODE),
+            (opcode::FSYNC, op::Fsync::CODE),
+            (opcode::CLOSE, op::Close::CODE),
+            (opcode::ACCEPT, op::Accept::CODE),
+            (opcode::RECV, op::Recv::CODE),
+            (opcode::SEND, op::Send::CODE),
+            (opcode::CANCEL, op::AsyncCancel::CODE),
+        ]
+        .into_iter()
+        .filter(|&(_, code)| probe.is_supported(code))
+        .fold(0u16, |bits, (ours, _)| bits | (1 << ours));
+
+        let capacity = ring.params().sq_entries() as usize;
+        let cq_capacity = ring.params().cq_entries() as usize;
 
         Ok(Self {
-            ring_fd,
-            sq_ring,
-            cq_ring,
-            sqes,
-            sq,
-            cq,
+            ring,
+            sq_lock: Mutex::new(()),
+            cq_lock: Mutex::new(()),
             capacity,
-            state: Arc::new(IoUringState {
-                sq_head: AtomicU32::new(0),
-                sq_tail: AtomicU32::new(0),
+            cq_capacity,
+            state: IoUringState {
+                sq_len: AtomicUsize::new(0),
                 cq_head: AtomicU32::new(0),
                 cq_tail: AtomicU32::new(0),
-                sq_len: AtomicUsize::new(0),
-            }),
+            },
             submit_queue: UnsafeCell::new(vec![SubmitEntry::new(-1, 0, 0); capacity]),
-            completion_queue: UnsafeCell::new(vec![None; capacity]),
+            completion_queue: UnsafeCell::new(vec![None; cq_capacity]),
+            supported,
+            buf_rings: Mutex::new(HashMap::new()),
         })
     }
 
-    /// Get the current submission queue position
-    /// 获取当前提交队列位置
-    #[inline]
-    fn sq_pos(&self, index: u32) -> u32 {
-        index & self.sq.ring_mask_value
+    /// Register a ring of `entries` buffers of `buf_size` bytes as buffer `group`
+    /// 将 `entries` 个 `buf_size` 字节的缓冲区注册为缓冲区组 `group`
+    ///
+    /// Receives submitted with [`SubmitEntry::with_buffer_group`] then take a buffer
+    /// from the ring; get it back with [`BufRing::take`].
+    /// 使用 [`SubmitEntry::with_buffer_group`] 提交的接收操作会从环中取用缓冲区；
+    /// 通过 [`BufRing::take`] 取回。
+    ///
+    /// # Errors / 错误
+    ///
+    /// Fails if `entries` is not a power of two up to 32768, the group is already
+    /// registered, or the kernel does not support buffer rings (before 5.19).
+    /// 如果 `entries` 不是不超过32768的2的幂、该组已注册或内核不支持缓冲区环（5.19之前）则失败。
+    pub fn register_buf_ring(
+        &self,
+        group: u16,
+        entries: u16,
+        buf_size: usize,
+    ) -> std::io::Result<Arc<BufRing>> {
+        let mut buf_rings = self.buf_rings.lock().unwrap();
+        if buf_rings.contains_key(&group) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Buffer group {} already registered", group),
+            ));
+        }
+
+        let buf_ring = Arc::new(BufRing::new(group, entries, buf_size)?);
+        // SAFETY: the ring memory is page aligned and outlives the registration,
+        // because the driver keeps the `Arc` until unregistering and drops the ring first
+        // 安全性：环内存按页对齐且比注册存活更久，因为driver在注销前一直持有 `Arc`，并且先释放环
+        unsafe {
+            self.ring.submitter().register_buf_ring_with_flags(
+                buf_ring.ring.as_ptr() as u64,
+                entries,
+                group,
+                0,
+            )?;
+        }
+        buf_rings.insert(group, buf_ring.clone());
+        Ok(buf_ring)
+    }
+
+    /// Unregister buffer `group`
+    /// 注销缓冲区组 `group`
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns the kernel error if the group is not registered.
+    /// 如果该组未注册则返回内核错误。
+    pub fn unregister_buf_ring(&self, group: u16) -> std::io::Result<()> {
+        let mut buf_rings = self.buf_rings.lock().unwrap();
+        self.ring.submitter().unregister_buf_ring(group)?;
+        buf_rings.remove(&group);
+        Ok(())
+    }
+
+    /// Get the buffer ring registered as `group`
+    /// 获取注册为 `group` 的缓冲区环
+    #[must_use]
+    pub fn buf_ring(&self, group: u16) -> Option<Arc<BufRing>> {
+        self.buf_rings.lock().unwrap().get(&group).cloned()
     }
 
     /// Get the current completion queue position
     /// 获取当前完成队列位置
     #[inline]
-    fn cq_pos(&self, index: u32) -> u32 {
-        index & self.cq.ring_mask_value
+    fn cq_pos(&self, index: u32) -> usize {
+        index as usize % self.cq_capacity
     }
 
-    /// Submit operations to the kernel
-    /// 向内核提交操作
-    fn submit_to_kernel(&self) -> std::io::Result<usize> {
-        let head = unsafe { *self.sq.head };
-        let tail = self.state.sq_tail.load(Ordering::Acquire);
-        let to_submit = tail - head;
-
-        if to_submit == 0 {
-            return Ok(0);
+    /// Push an SQE, flushing the kernel queue to make room when it is full
+    /// 推入SQE，队列已满时刷新内核队列以腾出空间
+    fn push_sqe(&self, sqe: &squeue::Entry) -> std::io::Result<()> {
+        let _guard = self.sq_lock.lock().unwrap();
+        // SAFETY: `sq_lock` gives exclusive access to the submission queue
+        // 安全性：`sq_lock` 提供对提交队列的独占访问
+        let mut sq = unsafe { self.ring.submission_shared() };
+        // SAFETY: buffers referenced by the entry are kept valid by the caller of
+        // the `unsafe` constructor that created it
+        // 安全性：条目引用的缓冲区由创建它的 `unsafe` 构造函数的调用者保证有效
+        while unsafe { sq.push(sqe) }.is_err() {
+            sq.sync();
+            self.ring.submit()?;
+            sq.sync();
         }
+        sq.sync();
+        Ok(())
+    }
 
-        // Set submission queue tail
-        // 设置提交队列尾
-        unsafe {
-            *(self.sq.tail as *mut u32) = tail;
+    /// Append a completion produced without the kernel
+    /// 追加一个不经过内核产生的完成事件
+    fn push_completion(&self, entry: CompletionEntry) -> bool {
+        let _guard = self.cq_lock.lock().unwrap();
+        let head = self.state.cq_head.load(Ordering::Acquire);
+        let tail = self.state.cq_tail.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) as usize >= self.cq_capacity {
+            return false;
         }
-
-        // Use IORING_ENTER_GETEVENTS flag to also wait for completions
-        // 使用IORING_ENTER_GETEVENTS标志同时等待完成
-        let result = unsafe {
-            libc::syscall(
-                426, // __NR_io_uring_enter
-                self.ring_fd as libc::c_long,
-                to_submit as libc::c_long,
-                0, // min_complete
-                1, // flags: IORING_ENTER_GETEVENTS
-                std::ptr::null_mut::<libc::sigset_t>(),
-            ) as libc::c_long
-        };
-
-        if result < 0 {
-            Err(std::io::Error::last_os_error())
-        } else {
-            Ok(result as usize)
+        unsafe {
+            let completion_queue = &mut *self.completion_queue.get();
+            completion_queue[self.cq_pos(tail)] = Some(entry);
         }
+        self.state
+            .cq_tail
+            .store(tail.wrapping_add(1), Ordering::Release);
+        true
     }
 
-    /// Get a free submission queue entry
-    /// 获取一个空闲的提交队列条目
-    fn get_free_sqe(&self) -> Option<*mut SubmissionQueueEntry> {
-        let head = unsafe { *self.sq.head };
-        let tail = self.state.sq_tail.load(Ordering::Acquire);
-        let next_tail = tail + 1;
+    /// Move kernel completions into the application completion queue
+    /// 将内核完成事件移入应用层完成队列
+    fn reap(&self) -> usize {
+        let _guard = self.cq_lock.lock().unwrap();
+        // SAFETY: `cq_lock` gives exclusive access to the completion queue
+        // 安全性：`cq_lock` 提供对完成队列的独占访问
+        let mut cq = unsafe { self.ring.completion_shared() };
+        let head = self.state.cq_head.load(Ordering::Acquire);
+        let mut tail = self.state.cq_tail.load(Ordering::Acquire);
+        let mut reaped = 0;
 
-        // Check if queue is full
-        // 检查队列是否已满
-        if next_tail - head >= self.sq.entries {
-            return None;
+        // Completions that do not fit stay in the kernel queue for the next round
+        // 放不下的完成事件留在内核队列中等待下一轮
+        while (tail.wrapping_sub(head) as usize) < self.cq_capacity {
+            let Some(cqe) = cq.next() else { break };
+            unsafe {
+                let completion_queue = &mut *self.completion_queue.get();
+                completion_queue[self.cq_pos(tail)] = Some(CompletionEntry {
+                    user_data: cqe.user_data(),
+                    result: cqe.result(),
+                    flags: cqe.flags(),
+                });
+            }
+            tail = tail.wrapping_add(1);
+            reaped += 1;
         }
 
-        let index = self.sq_pos(tail);
-        unsafe { Some(self.sq.sqes.add(index as usize)) }
+        self.state.cq_tail.store(tail, Ordering::Release);
+        reaped
     }
-}
 
-impl Drop for IoUringDriver {
-    fn drop(&mut self) {
-        let sq_ring_size = unsafe {
-            // Approximate size for munmap
-            // 大小估算用于munmap
-            0x1000
-        };
-        let cq_ring_size = 0x1000;
-        let sqes_size = self.capacity * std::mem::size_of::<SubmissionQueueEntry>();
-
-        unsafe {
-            libc::munmap(self.sq_ring, sq_ring_size);
-            libc::munmap(self.cq_ring, cq_ring_size);
-            libc::munmap(self.sqes as *mut _, sqes_size);
-            libc::close(self.ring_fd);
-        }
+    /// Number of completions waiting in the application queue
+    /// 应用层队列中等待的完成事件数
+    fn pending_completions(&self) -> usize {
+        let head = self.state.cq_head.load(Ordering::Acquire);
+        let tail = self.state.cq_tail.load(Ordering::Acquire);
+        tail.wrapping_sub(head) as usize
     }
 }
 
+/// Translate a submission entry into an io_uring SQE
+/// 将提交条目转换为io_uring SQE
+fn build_sqe(entry: &SubmitEntry) -> Option<squeue::Entry> {
+    let fd = types::Fd(entry.fd);
+    let buf = entry.buf_ptr.map_or(std::ptr::null_mut(), NonNull::as_ptr);
+    let multishot = entry.flags & op_flags::MULTISHOT != 0;
+
+    let sqe = match entry.opcode {
+        opcode::READ => op::Read::new(fd, buf, entry.buf_len)
+            .offset(entry.offset)
+            .buf_group(entry.buf_group)
+            .build(),
+        opcode::WRITE => op::Write::new(fd, buf, entry.buf_len)
+            .offset(entry.offset)
+            .build(),
+        opcode::FSYNC => op::Fsync::new(fd).build(),
+        opcode::CLOSE => op::Close::new(fd).build(),
+        opcode::ACCEPT if multishot => op::AcceptMulti::new(fd).flags(libc::SOCK_CLOEXEC).build(),
+        opcode::ACCEPT => op::Accept::new(fd, std::ptr::null_mut(), std::ptr::null_mut())
+            .flags(libc::SOCK_CLOEXEC)
+            .build(),
+        opcode::RECV if multishot => op::RecvMulti::new(fd, entry.buf_group).build(),
+        opcode::RECV => op::Recv::new(fd, buf, entry.buf_len)
+            .buf_group(entry.buf_group)
+            .build(),
+        opcode::SEND => op::Send::new(fd, buf, entry.buf_len)
+            .flags(libc::MSG_NOSIGNAL)
+            .build(),
+        opcode::CANCEL => op::AsyncCancel::new(entry.offset).build(),
+        _ => return None,
+    };
+
+    let sqe = if entry.flags & op_flags::BUFFER_SELECT != 0 {
+        sqe.flags(squeue::Flags::BUFFER_SELECT)
+    } else {
+        sqe
+    };
+    Some(sqe.user_data(entry.user_data))
+}
+
 impl AsRawFd for IoUringDriver {
     fn as_raw_fd(&self) -> RawFd {
-        self.ring_fd
+        self.ring.as_raw_fd()
     }
 }
 
@@ -498,38 +377,21 @@ impl Driver for IoUringDriver {
         // Process all pending submissions from our internal queue
         // 处理内部队列中所有挂起的提交
         let len = self.state.sq_len.load(Ordering::Acquire);
-        for i in 0..len {
-            let submit_queue = unsafe { &*self.submit_queue.get() };
-            let entry = &submit_queue[i];
-
-            if entry.fd >= 0 {
-                if let Some(sqe) = self.get_free_sqe() {
-                    unsafe {
-                        (*sqe).opcode = entry.opcode;
-                        (*sqe).flags = 0;
-                        (*sqe).ioprio = 0;
-                        (*sqe).fd = entry.fd;
-                        (*sqe).offset = entry.offset as u64;
-                        (*sqe).addr = entry.addr as u64;
-                        (*sqe).len = entry.len as u32;
-                        (*sqe).rw_flags = 0;
-                        (*sqe).user_data = entry.user_data;
-                        (*sqe).buf_index = 0;
-                        (*sqe).personality = 0;
-
-                        // Set array index
-                        // 设置数组索引
-                        let tail = self.state.sq_tail.load(Ordering::Acquire);
-                        let index = self.sq_pos(tail);
-                        *self.sq.array.add(index as usize) = index;
-
-                        // Advance tail
-                        // 前进尾指针
-                        self.state.sq_tail.store(tail + 1, Ordering::Release);
-                    }
-
+        let submit_queue = unsafe { &*self.submit_queue.get() };
+        for entry in &submit_queue[..len] {
+            if entry.fd < 0 && entry.opcode != opcode::CANCEL {
+                continue;
+            }
+            match build_sqe(entry) {
+                Some(sqe) => {
+                    self.push_sqe(&sqe)?;
                     submitted += 1;
-                }
+                },
+                None => {
+                    // Unknown opcodes fail like the kernel would
+                    // 未知操作码像内核一样失败
+                    self.push_completion(CompletionEntry::new(entry.user_data, -libc::EINVAL, 0));
+                },
             }
         }
 
@@ -539,78 +401,36 @@ impl Driver for IoUringDriver {
 
         // Submit to kernel
         // 提交到内核
-        let kernel_submitted = self.submit_to_kernel()?;
+        self.ring.submit()?;
 
+        metrics::IO_URING.record_submit(submitted);
         Ok(submitted)
     }
 
     fn wait(&self) -> std::io::Result<usize> {
-        self.wait_timeout(Duration::from_secs(1)).map(|(n, _)| n)
+        self.wait_timeout(WAIT_INTERVAL).map(|(n, _)| n)
     }
 
     fn wait_timeout(&self, duration: Duration) -> std::io::Result<(usize, bool)> {
-        // Convert duration to timespec
-        // 转换持续时间为timespec
-        let ts = libc::timespec {
-            tv_sec: duration.as_secs() as libc::time_t,
-            tv_nsec: duration.subsec_nanos() as libc::c_long,
-        };
-
-        let result = unsafe {
-            libc::syscall(
-                426, // __NR_io_uring_enter
-                self.ring_fd as libc::c_long,
-                0, // to_submit
-                1, // min_complete
-                2, // flags: IORING_ENTER_GETEVENTS | IORING_ENTER_TIMEOUT
-                &ts as *const _ as *const libc::sigset_t,
-            ) as libc::c_long
-        };
-
-        if result < 0 {
-            return Err(std::io::Error::last_os_error());
+        // Don't block while completions are already waiting to be consumed
+        // 已有待消费的完成事件时不阻塞
+        let want = usize::from(self.pending_completions() == 0);
+        let ts = types::Timespec::from(duration);
+        let args = types::SubmitArgs::new().timespec(&ts);
+
+        match self.ring.submitter().submit_with_args(want, &args) {
+            Ok(_) => {},
+            // Timed out, interrupted, or completion queue overflow: reap what is there
+            // 超时、被中断或完成队列溢出：收取已有的完成事件
+            Err(err)
+                if matches!(err.raw_os_error(), Some(libc::ETIME | libc::EINTR | libc::EBUSY)) => {
+            },
+            Err(err) => return Err(err),
         }
 
-        // Process completion queue
-        // 处理完成队列
-        let mut completed = 0;
-        let head = self.state.cq_head.load(Ordering::Acquire);
-        let tail = unsafe { *self.cq.tail };
-
-        while head != tail {
-            let index = self.cq_pos(head);
-            let cqe = unsafe { &*self.cq.cqes.add(index as usize) };
-
-            // Store in completion queue
-            // 存储到完成队列
-            unsafe {
-                let completion_queue = &mut *self.completion_queue.get();
-                let pos = self.state.cq_tail.load(Ordering::Acquire) as usize % self.capacity;
-                completion_queue[pos] = Some(CompletionEntry {
-                    user_data: (*cqe).user_data,
-                    result: if (*cqe).res < 0 {
-                        ERROR_TRANSPORT
-                    } else {
-                        (*cqe).res
-                    },
-                    flags: (*cqe).flags,
-                });
-                self.state.cq_tail.fetch_add(1, Ordering::Release);
-            }
-
-            completed += 1;
-            unsafe {
-                *(self.cq.head as *mut u32) = head + 1;
-            }
-        }
-
-        self.state.cq_head.store(tail, Ordering::Release);
-
-        // Check if we timed out
-        // 检查是否超时
-        let timed_out = completed == 0;
-
-        Ok((completed, timed_out))
+        let completed = self.reap();
+        metrics::IO_URING.record_wait(completed);
+        Ok((completed, completed == 0 && want == 1))
     }
 
     fn get_submission(&self) -> Option<&mut SubmitEntry> {
@@ -624,6 +444,7 @@ impl Driver for IoUringDriver {
 
         unsafe {
             let submit_queue = &mut *self.submit_queue.get();
+            submit_queue[len] = SubmitEntry::new(-1, 0, 0);
             Some(&mut submit_queue[len])
         }
     }
@@ -638,8 +459,7 @@ impl Driver for IoUringDriver {
 
         unsafe {
             let completion_queue = &*self.completion_queue.get();
-            let pos = head as usize % self.capacity;
-            completion_queue[pos].as_ref()
+            completion_queue[self.cq_pos(head)].as_ref()
         }
     }
 
@@ -650,72 +470,36 @@ impl Driver for IoUringDriver {
         if head != tail {
             unsafe {
                 let completion_queue = &mut *self.completion_queue.get();
-                let pos = head as usize % self.capacity;
-                completion_queue[pos] = None;
+                completion_queue[self.cq_pos(head)] = None;
             }
 
-            self.state.cq_head.fetch_add(1, Ordering::Release);
+            self.state
+                .cq_head
+                .store(head.wrapping_add(1), Ordering::Release);
         }
     }
 
     fn register(&self, fd: RawFd, interest: Interest) -> std::io::Result<()> {
-        // io_uring uses POLL_ADD or POLL_REMOVE for registration
-        // For now, we'll use a simple approach with poll operation
-        // io_uring使用POLL_ADD或POLL_REMOVE进行注册
-        // 目前，我们使用简单的poll操作
-
-        let mut events = 0i16;
+        // Readiness interest maps to a poll operation keyed by the descriptor
+        // 就绪兴趣映射为以描述符为键的poll操作
+        let mut events = 0u32;
         if interest.readable {
-            events |= libc::POLLIN as i16;
+            events |= libc::POLLIN as u32;
         }
         if interest.writable {
-            events |= libc::POLLOUT as i16;
-        }
-
-        // Get a free SQE
-        // 获取一个空闲的SQE
-        let sqe = self.get_free_sqe().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::WouldBlock, "Submission queue full")
-        })?;
-
-        unsafe {
-            (*sqe).opcode = 6; // IORING_OP_POLL_ADD
-            (*sqe).fd = fd;
-            (*sqe).addr = events as u64;
-            (*sqe).len = 0;
-            (*sqe).user_data = fd as u64;
-
-            // Set array index and advance tail
-            // 设置数组索引并前进尾指针
-            let tail = self.state.sq_tail.load(Ordering::Acquire);
-            let index = self.sq_pos(tail);
-            *self.sq.array.add(index as usize) = index;
-            self.state.sq_tail.store(tail + 1, Ordering::Release);
+            events |= libc::POLLOUT as u32;
         }
 
-        Ok(())
+        let sqe = op::PollAdd::new(types::Fd(fd), events)
+            .multi(!interest.oneshot)
+            .build()
+            .user_data(fd as u64);
+        self.push_sqe(&sqe)
     }
 
     fn deregister(&self, fd: RawFd) -> std::io::Result<()> {
-        // Use POLL_REMOVE to deregister
-        // 使用POLL_REMOVE注销
-        let sqe = self.get_free_sqe().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::WouldBlock, "Submission queue full")
-        })?;
-
-        unsafe {
-            (*sqe).opcode = 7; // IORING_OP_POLL_REMOVE
-            (*sqe).fd = -1;
-            (*sqe).addr = fd as u64;
-            (*sqe).user_data = fd as u64;
-
-            let tail = self.state.sq_tail.load(Ordering::Acquire);
-            let index = self.sq_pos(tail);
-            *self.sq.array.add(index as usize) = index;
-            self.state.sq_tail.store(tail + 1, Ordering::Release);
-        }
-
-        Ok(())
+        let sqe = op::PollRemove::new(fd as u64).build().user_data(fd as u64);
+        self.push_sqe(&sqe)
     }
 
     fn modify(&self, fd: RawFd, interest: Interest) -> std::io::Result<()> {
@@ -730,48 +514,395 @@ impl Driver for IoUringDriver {
     }
 
     fn completion_capacity(&self) -> usize {
-        self.capacity
+        self.cq_capacity
     }
 
     fn supports_operation(&self, opcode: u8) -> bool {
-        // io_uring supports all operations
-        // io_uring支持所有操作
-        matches!(
-            opcode,
-            crate::driver::opcode::READ
-                | crate::driver::opcode::WRITE
-                | crate::driver::opcode::FSYNC
-                | crate::driver::opcode::CLOSE
-        )
+        opcode < 16 && self.supported & (1 << opcode) != 0
+    }
+
+    fn is_completion_based(&self) -> bool {
+        true
+    }
+
+    fn register_buf_ring(
+        &self,
+        group: u16,
+        entries: u16,
+        buf_size: usize,
+    ) -> std::io::Result<Arc<BufRing>> {
+        // The inherent method / 固有方法
+        IoUringDriver::register_buf_ring(self, group, entries, buf_size)
+    }
+}
+
+/// A ring of provided buffers the kernel picks from for receives
+/// 内核在接收时从中选择缓冲区的提供缓冲区环
+///
+/// Created by [`IoUringDriver::register_buf_ring`]. A completion that used a
+/// buffer reports its ID; [`BufRing::take`] returns the filled part and hands the
+/// buffer back to the kernel when dropped.
+/// 由 [`IoUringDriver::register_buf_ring`] 创建。使用了缓冲区的完成事件会报告其ID；
+/// [`BufRing::take`] 返回已填充的部分，并在释放时将缓冲区交还给内核。
+pub struct BufRing {
+    /// Buffer group ID / 缓冲区组ID
+    group: u16,
+    /// Number of buffers, a power of two / 缓冲区数量，为2的幂
+    entries: u16,
+    /// Size of each buffer / 每个缓冲区的大小
+    buf_size: usize,
+    /// Page-aligned ring shared with the kernel / 与内核共享的按页对齐的环
+    ring: NonNull<types::BufRingEntry>,
+    /// Backing memory for all buffers / 所有缓冲区的底层内存
+    buffers: NonNull<u8>,
+    /// Next ring tail to publish / 下一个要发布的环尾
+    tail: Mutex<u16>,
+}
+
+// SAFETY: the ring tail is only advanced under `tail`, and each buffer is owned
+// either by the kernel or by exactly one `ProvidedBuf`
+// 安全性：环尾仅在 `tail` 锁下推进，每个缓冲区要么归内核所有，要么只归一个 `ProvidedBuf` 所有
+unsafe impl Send for BufRing {}
+
+// SAFETY: see `Send` / 安全性：见 `Send`
+unsafe impl Sync for BufRing {}
+
+impl BufRing {
+    /// Allocate a ring with every buffer handed to the kernel
+    /// 分配一个环，并将所有缓冲区交给内核
+    fn new(group: u16, entries: u16, buf_size: usize) -> std::io::Result<Self> {
+        if !entries.is_power_of_two() || entries > MAX_BUF_RING_ENTRIES || buf_size == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Buffer ring needs a power-of-two entry count up to 32768 and non-empty buffers",
+            ));
+        }
+
+        let (ring_layout, buffers_layout) = Self::layouts(entries, buf_size)?;
+        // SAFETY: both layouts have a non-zero size
+        // 安全性：两个布局的大小都不为零
+        let ring = NonNull::new(unsafe { alloc_zeroed(ring_layout) })
+            .ok_or(std::io::ErrorKind::OutOfMemory)?;
+        let Some(buffers) = NonNull::new(unsafe { alloc_zeroed(buffers_layout) }) else {
+            unsafe { dealloc(ring.as_ptr(), ring_layout) };
+            return Err(std::io::ErrorKind::OutOfMemory.into());
+        };
+
+        let buf_ring = Self {
+            group,
+            entries,
+            buf_size,
+            ring: ring.cast(),
+            buffers,
+            tail: Mutex::new(0),
+        };
+        let mut tail = buf_ring.tail.lock().unwrap();
+        for bid in 0..entries {
+            buf_ring.push(&mut tail, bid);
+        }
+        buf_ring.publish(*tail);
+        drop(tail);
+        Ok(buf_ring)
+    }
+
+    /// Memory layouts of the ring and of the buffers
+    /// 环和缓冲区的内存布局
+    fn layouts(entries: u16, buf_size: usize) -> std::io::Result<(Layout, Layout)> {
+        let invalid = |_| std::io::Error::from(std::io::ErrorKind::InvalidInput);
+        let ring =
+            Layout::from_size_align(usize::from(entries) * size_of::<types::BufRingEntry>(), 4096)
+                .map_err(invalid)?;
+        let total = buf_size
+            .checked_mul(usize::from(entries))
+            .ok_or(std::io::ErrorKind::InvalidInput)?;
+        let buffers = Layout::from_size_align(total, 64).map_err(invalid)?;
+        Ok((ring, buffers))
+    }
+
+    /// Get the buffer group ID
+    /// 获取缓冲区组ID
+    #[must_use]
+    pub fn group(&self) -> u16 {
+        self.group
+    }
+
+    /// Get the number of buffers
+    /// 获取缓冲区数量
+    #[must_use]
+    pub fn entries(&self) -> u16 {
+        self.entries
+    }
+
+    /// Get the size of each buffer
+    /// 获取每个缓冲区的大小
+    #[must_use]
+    pub fn buffer_size(&self) -> usize {
+        self.buf_size
+    }
+
+    /// Take the buffer a completion was delivered in
+    /// 取出完成事件所用的缓冲区
+    ///
+    /// Returns `None` if the completion did not use a buffer from this ring, e.g.
+    /// because it failed. Drop the returned buffer promptly: until then the kernel
+    /// cannot reuse it.
+    /// 如果完成事件没有使用此环中的缓冲区（例如因为失败）则返回 `None`。
+    /// 应尽快释放返回的缓冲区：在此之前内核无法重用它。
+    #[must_use]
+    pub fn take(&self, completion: &CompletionEntry) -> Option<ProvidedBuf<'_>> {
+        let bid = completion.buffer_id()?;
+        if bid >= self.entries {
+            return None;
+        }
+        // A failed operation doesn't consume a buffer; a zero-length one does
+        // 失败的操作不会消耗缓冲区；长度为零的操作会
+        let len = completion.bytes_transferred().unwrap_or(0) as usize;
+        Some(ProvidedBuf {
+            ring: self,
+            bid,
+            len: len.min(self.buf_size),
+        })
+    }
+
+    /// Write buffer `bid` into the ring slot at `tail` and advance it
+    /// 将缓冲区 `bid` 写入 `tail` 处的环槽位并推进
+    fn push(&self, tail: &mut u16, bid: u16) {
+        let slot = usize::from(*tail & (self.entries - 1));
+        // SAFETY: `slot` is within the ring, and slots past the published tail
+        // are not read by the kernel
+        // 安全性：`slot` 位于环内，且内核不会读取已发布尾部之后的槽位
+        unsafe {
+            let entry = &mut *self.ring.as_ptr().add(slot);
+            entry.set_addr(self.buffers.as_ptr().add(usize::from(bid) * self.buf_size) as u64);
+            entry.set_len(self.buf_size as u32);
+            entry.set_bid(bid);
+        }
+        *tail = tail.wrapping_add(1);
+    }
+
+    /// Make the slots up to `tail` visible to the kernel
+    /// 使直到 `tail` 的槽位对内核可见
+    fn publish(&self, tail: u16) {
+        // SAFETY: the tail field lives in the first ring entry and is only
+        // written here, under the `tail` lock
+        // 安全性：尾字段位于第一个环条目中，且仅在此处、在 `tail` 锁下写入
+        unsafe {
+            let field = types::BufRingEntry::tail(self.ring.as_ptr()) as *const AtomicU16;
+            (*field).store(tail, Ordering::Release);
+        }
+    }
+
+    /// Hand buffer `bid` back to the kernel
+    /// 将缓冲区 `bid` 交还给内核
+    fn recycle(&self, bid: u16) {
+        let mut tail = self.tail.lock().unwrap();
+        self.push(&mut tail, bid);
+        self.publish(*tail);
+    }
+}
+
+impl Drop for BufRing {
+    fn drop(&mut self) {
+        if let Ok((ring_layout, buffers_layout)) = Self::layouts(self.entries, self.buf_size) {
+            unsafe {
+                dealloc(self.ring.as_ptr().cast(), ring_layout);
+                dealloc(self.buffers.as_ptr(), buffers_layout);
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for BufRing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufRing")
+            .field("group", &self.group)
+            .field("entries", &self.entries)
+            .field("buf_size", &self.buf_size)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Filled buffer taken from a [`BufRing`]; returned to the kernel on drop
+/// 从 [`BufRing`] 取出的已填充缓冲区；释放时交还给内核
+pub struct ProvidedBuf<'a> {
+    ring: &'a BufRing,
+    bid: u16,
+    len: usize,
+}
+
+impl ProvidedBuf<'_> {
+    /// Get the buffer ID
+    /// 获取缓冲区ID
+    #[must_use]
+    pub fn id(&self) -> u16 {
+        self.bid
+    }
+}
+
+impl Deref for ProvidedBuf<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: the kernel filled `len` bytes and won't touch the buffer until
+        // it is recycled on drop
+        // 安全性：内核填充了 `len` 字节，并且在释放时回收之前不会再访问该缓冲区
+        unsafe {
+            std::slice::from_raw_parts(
+                self.ring
+                    .buffers
+                    .as_ptr()
+                    .add(usize::from(self.bid) * self.ring.buf_size),
+                self.len,
+            )
+        }
+    }
+}
+
+impl Drop for ProvidedBuf<'_> {
+    fn drop(&mut self) {
+        self.ring.recycle(self.bid);
+    }
+}
+
+impl std::fmt::Debug for ProvidedBuf<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProvidedBuf")
+            .field("id", &self.bid)
+            .field("len", &self.len)
+            .finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    /// Create a driver, or skip the test where io_uring is unavailable
+    /// 创建driver；在io_uring不可用的环境中跳过测试
+    fn driver() -> Option<IoUringDriver> {
+        IoUringDriver::new().ok()
+    }
+
+    fn submit(driver: &IoUringDriver, entry: SubmitEntry) {
+        *driver.get_submission().unwrap() = entry;
+        driver.submit().unwrap();
+    }
+
+    /// Wait until `count` completions arrived
+    /// 等待直到收到 `count` 个完成事件
+    fn complete(driver: &IoUringDriver, count: usize) -> Vec<CompletionEntry> {
+        let mut completions = Vec::new();
+        for _ in 0..50 {
+            driver.wait_timeout(Duration::from_millis(100)).unwrap();
+            while let Some(completion) = driver.get_completion() {
+                completions.push(*completion);
+                driver.advance_completion();
+            }
+            if completions.len() >= count {
+                break;
+            }
+        }
+        completions
+    }
 
     #[test]
     fn test_iouring_driver_creation() {
-        // This test may fail on systems without io_uring support
-        // 此测试可能在没有io_uring支持的系统上失败
-        let driver = IoUringDriver::new();
-        // Allow test to pass if io_uring is not available
-        // 如果io_uring不可用，允许测试通过
-        let _ = driver;
+        let Some(driver) = driver() else { return };
+        assert!(driver.is_completion_based());
+        assert!(driver.supports_operation(opcode::RECV));
+        assert!(!driver.supports_operation(0xff));
     }
 
     #[test]
-    fn test_iouring_params_size() {
-        assert_eq!(std::mem::size_of::<IoUringParams>(), 40);
+    fn test_send_and_recv_complete_with_byte_counts() {
+        let Some(driver) = driver() else { return };
+        let (a, b) = UnixStream::pair().unwrap();
+        let message = b"hello uring";
+        let mut buf = [0u8; 64];
+
+        submit(&driver, unsafe {
+            SubmitEntry::send(a.as_raw_fd(), message.as_ptr(), message.len() as u32, 1)
+        });
+        submit(&driver, unsafe {
+            SubmitEntry::recv(b.as_raw_fd(), buf.as_mut_ptr(), buf.len() as u32, 2)
+        });
+
+        let completions = complete(&driver, 2);
+        assert_eq!(completions.len(), 2);
+        for completion in &completions {
+            assert_eq!(completion.result, message.len() as i32);
+        }
+        assert_eq!(&buf[..message.len()], message);
     }
 
     #[test]
-    fn test_submission_queue_entry_size() {
-        assert_eq!(std::mem::size_of::<SubmissionQueueEntry>(), 64);
+    fn test_multishot_accept() {
+        let Some(driver) = driver() else { return };
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        submit(&driver, SubmitEntry::accept(listener.as_raw_fd(), 7).multishot());
+
+        let clients: Vec<_> = (0..2)
+            .map(|_| std::net::TcpStream::connect(addr).unwrap())
+            .collect();
+        let completions = complete(&driver, 2);
+        if completions
+            .first()
+            .is_some_and(|c| c.result == -libc::EINVAL)
+        {
+            // Kernel predates multishot accept / 内核早于多次触发accept
+            return;
+        }
+        assert_eq!(completions.len(), clients.len());
+        for completion in completions {
+            assert_eq!(completion.user_data, 7);
+            assert!(completion.has_more());
+            let fd = completion.into_result().unwrap() as RawFd;
+            unsafe { libc::close(fd) };
+        }
+
+        submit(&driver, SubmitEntry::cancel(7, 8));
+        let completions = complete(&driver, 2);
+        assert!(completions.iter().any(|c| c.user_data == 8));
+    }
+
+    #[test]
+    fn test_recv_into_buffer_ring() {
+        let Some(driver) = driver() else { return };
+        let Ok(buf_ring) = driver.register_buf_ring(3, 4, 128) else {
+            return;
+        };
+        assert!(driver.register_buf_ring(3, 4, 128).is_err());
+
+        let (mut a, b) = UnixStream::pair().unwrap();
+        for message in [&b"first"[..], b"second"] {
+            a.write_all(message).unwrap();
+            // The kernel picks the buffer, so none is passed
+            // 由内核选择缓冲区，因此不传入缓冲区
+            let entry = unsafe { SubmitEntry::recv(b.as_raw_fd(), std::ptr::null_mut(), 128, 9) };
+            submit(&driver, entry.with_buffer_group(3));
+            let completions = complete(&driver, 1);
+            let buf = buf_ring.take(&completions[0]).unwrap();
+            assert_eq!(&*buf, message);
+        }
+
+        driver.unregister_buf_ring(3).unwrap();
+        assert!(driver.buf_ring(3).is_none());
     }
 
     #[test]
-    fn test_completion_queue_entry_size() {
-        assert_eq!(std::mem::size_of::<CompletionQueueEntry>(), 16);
+    fn test_fsync_and_close_file() {
+        let Some(driver) = driver() else { return };
+        let path = std::env::temp_dir().join(format!("nexus-iouring-{}", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let fd = std::os::fd::IntoRawFd::into_raw_fd(file);
+
+        submit(&driver, SubmitEntry::fsync(fd, 1));
+        assert_eq!(complete(&driver, 1)[0].result, 0);
+        submit(&driver, SubmitEntry::close(fd, 2));
+        assert_eq!(complete(&driver, 1)[0].result, 0);
+        std::fs::remove_file(path).unwrap();
     }
 }
@@ -19,6 +19,7 @@ use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::time::Duration;
 
 use crate::driver::{CompletionEntry, Driver, ERROR_TRANSPORT, Interest, SubmitEntry};
+use crate::metrics;
 
 /// Minimum kqueue instance size / 最小kqueue实例大小
 const MIN_KQUEUE_SIZE: u32 = 32;
@@ -299,6 +300,7 @@ impl KqueueDriver {
         }
 
         let count = result as usize;
+        metrics::KQUEUE.record_wait(count);
 
         // Process events into completion queue
         // 将事件处理到完成队列
@@ -374,57 +376,61 @@ impl Driver for KqueueDriver {
             if entry.fd >= 0 {
                 // Convert submit entry to kevent change
                 // 将提交条目转换为kevent change
-                let (filter, flags) = match entry.opcode {
-                    crate::driver::opcode::READ => {
-                        (libc::EVFILT_READ, libc::EV_ADD | libc::EV_ONESHOT)
+                // A poll arms both filters with the same user data
+                // poll使用相同的用户数据设置两个过滤器
+                let oneshot = libc::EV_ADD | libc::EV_ONESHOT;
+                let (filters, flags): (&[i16], u16) = match entry.opcode {
+                    crate::driver::opcode::READ => (&[libc::EVFILT_READ], oneshot),
+                    crate::driver::opcode::WRITE => (&[libc::EVFILT_WRITE], oneshot),
+                    crate::driver::opcode::POLL => {
+                        (&[libc::EVFILT_READ, libc::EVFILT_WRITE], oneshot)
                     },
-                    crate::driver::opcode::WRITE => {
-                        (libc::EVFILT_WRITE, libc::EV_ADD | libc::EV_ONESHOT)
-                    },
-                    _ => (0, 0),
-                };
-
-                let mut change = libc::kevent {
-                    ident: entry.fd as libc::uintptr_t,
-                    filter,
-                    flags,
-                    fflags: 0,
-                    data: 0,
-                    udata: entry.user_data as *mut _,
-                };
-
-                let result = unsafe {
-                    libc::kevent(
-                        self.kqueue_fd,
-                        &change,
-                        1,
-                        std::ptr::null_mut(),
-                        0,
-                        std::ptr::null_mut(),
-                    )
+                    _ => (&[0], 0),
                 };
 
-                if result < 0 {
-                    let err = std::io::Error::last_os_error();
-                    // ENOENT means FD not found, but kqueue handles this differently
-                    // Try with EV_ADD instead
-                    if err.kind() == std::io::ErrorKind::NotFound {
-                        change.flags = libc::EV_ADD | libc::EV_ONESHOT;
-                        let add_result = unsafe {
-                            libc::kevent(
-                                self.kqueue_fd,
-                                &change,
-                                1,
-                                std::ptr::null_mut(),
-                                0,
-                                std::ptr::null_mut(),
-                            )
-                        };
-                        if add_result < 0 {
+                for &filter in filters {
+                    let mut change = libc::kevent {
+                        ident: entry.fd as libc::uintptr_t,
+                        filter,
+                        flags,
+                        fflags: 0,
+                        data: 0,
+                        udata: entry.user_data as *mut _,
+                    };
+
+                    let result = unsafe {
+                        libc::kevent(
+                            self.kqueue_fd,
+                            &change,
+                            1,
+                            std::ptr::null_mut(),
+                            0,
+                            std::ptr::null_mut(),
+                        )
+                    };
+
+                    if result < 0 {
+                        let err = std::io::Error::last_os_error();
+                        // ENOENT means FD not found, but kqueue handles this differently
+                        // Try with EV_ADD instead
+                        if err.kind() == std::io::ErrorKind::NotFound {
+                            change.flags = libc::EV_ADD | libc::EV_ONESHOT;
+                            let add_result = unsafe {
+                                libc::kevent(
+                                    self.kqueue_fd,
+                                    &change,
+                                    1,
+                                    std::ptr::null_mut(),
+                                    0,
+                                    std::ptr::null_mut(),
+                                )
+                            };
+                            if add_result < 0 {
+                                return Err(err);
+                            }
+                        } else {
                             return Err(err);
                         }
-                    } else {
-                        return Err(err);
                     }
                 }
 
@@ -438,6 +444,7 @@ impl Driver for KqueueDriver {
         // 前进head
         self.state.submit_head.store(tail, Ordering::Release);
 
+        metrics::KQUEUE.record_submit(submitted);
         Ok(submitted)
     }
 
@@ -468,11 +475,14 @@ impl Driver for KqueueDriver {
             return None;
         }
 
+        self.state.submit_tail.store(next_tail, Ordering::Release);
+
         let pos = self.submit_pos(tail);
         // SAFETY: We have exclusive access to this position
         // 我们对此位置有独占访问权
         unsafe {
             let submit_queue = &mut *self.submit_queue.get();
+            submit_queue[pos] = SubmitEntry::new(-1, 0, 0);
             Some(&mut submit_queue[pos])
         }
     }
@@ -575,6 +585,7 @@ impl Driver for KqueueDriver {
             crate::driver::opcode::READ
                 | crate::driver::opcode::WRITE
                 | crate::driver::opcode::CLOSE
+                | crate::driver::opcode::POLL
         )
     }
 }
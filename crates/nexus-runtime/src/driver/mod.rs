@@ -10,6 +10,15 @@
 //! - io-uring (Linux 5.1+)
 //! - epoll (Linux)
 //! - kqueue (macOS/BSD)
+//!
+//! Readiness drivers (epoll, kqueue) complete an entry once its file descriptor is
+//! ready and leave the syscall to the caller. Completion drivers (io-uring) perform
+//! the operation itself and report the bytes transferred; check
+//! [`Driver::is_completion_based`] before interpreting a result.
+//!
+//! 就绪型driver（epoll、kqueue）在文件描述符就绪时完成条目，系统调用由调用方执行。
+//! 完成型driver（io-uring）自行执行操作并报告传输的字节数；解释结果前请检查
+//! [`Driver::is_completion_based`]。
 
 pub mod config;
 pub mod epoll;
@@ -18,6 +27,7 @@ pub mod interest;
 pub mod iouring;
 pub mod kqueue;
 pub mod queue;
+pub(crate) mod reactor;
 
 pub use config::{DriverConfig, DriverConfigBuilder, DriverFactory, DriverType};
 pub use interest::Interest;
@@ -100,6 +110,28 @@ pub trait Driver: Send + Sync + AsRawFd {
     /// Check if the driver supports the specified operation
     /// 检查driver是否支持指定操作
     fn supports_operation(&self, opcode: u8) -> bool;
+
+    /// Check whether completions carry the operation result rather than readiness
+    /// 检查完成事件携带的是操作结果而不是就绪状态
+    fn is_completion_based(&self) -> bool {
+        false
+    }
+
+    /// Register a ring of `entries` provided buffers of `buf_size` bytes as `group`
+    /// 将 `entries` 个 `buf_size` 字节的提供缓冲区组成的环注册为 `group`
+    ///
+    /// Drivers without buffer rings return [`Unsupported`](std::io::ErrorKind::Unsupported).
+    /// 不支持缓冲区环的driver返回 [`Unsupported`](std::io::ErrorKind::Unsupported)。
+    #[cfg(target_os = "linux")]
+    fn register_buf_ring(
+        &self,
+        group: u16,
+        entries: u16,
+        buf_size: usize,
+    ) -> std::io::Result<std::sync::Arc<iouring::BufRing>> {
+        let _ = (group, entries, buf_size);
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
 }
 
 /// Raw file descriptor type
@@ -121,4 +153,39 @@ pub mod opcode {
     pub const FSYNC: u8 = 2;
     /// Close operation / 关闭操作
     pub const CLOSE: u8 = 4;
+    /// Accept a connection on a listening socket / 在监听套接字上接受连接
+    pub const ACCEPT: u8 = 5;
+    /// Receive from a socket / 从套接字接收
+    pub const RECV: u8 = 6;
+    /// Send on a socket / 在套接字上发送
+    pub const SEND: u8 = 7;
+    /// Cancel an in-flight operation by its user data / 按用户数据取消进行中的操作
+    pub const CANCEL: u8 = 8;
+    /// Wait until a descriptor is readable or writable (readiness drivers)
+    /// 等待描述符可读或可写（就绪型driver）
+    pub const POLL: u8 = 9;
+}
+
+/// Submission flags for [`SubmitEntry::flags`]
+/// [`SubmitEntry::flags`] 的提交标志
+pub mod op_flags {
+    /// Keep the operation armed and post a completion per event (accept, recv)
+    /// 保持操作有效，每个事件产生一个完成事件（accept、recv）
+    pub const MULTISHOT: u16 = 1 << 0;
+    /// Let the kernel pick a buffer from the entry's buffer group
+    /// 由内核从条目的缓冲区组中选择缓冲区
+    pub const BUFFER_SELECT: u16 = 1 << 1;
+}
+
+/// Completion flags found in [`CompletionEntry::flags`] of completion drivers
+/// 完成型driver的 [`CompletionEntry::flags`] 中的完成标志
+pub mod cqe_flags {
+    /// A provided buffer was used; its ID is in the upper 16 bits
+    /// 使用了提供的缓冲区；其ID位于高16位
+    pub const BUFFER: u32 = 1 << 0;
+    /// A multishot operation stays armed and will post more completions
+    /// 多次触发操作仍然有效，并将产生更多完成事件
+    pub const MORE: u32 = 1 << 1;
+    /// Shift of the buffer ID within the flags / 缓冲区ID在标志中的位移
+    pub const BUFFER_SHIFT: u32 = 16;
 }
@@ -80,10 +80,14 @@ pub struct SubmitEntry {
     pub buf_ptr: Option<NonNull<u8>>,
     /// Buffer length in bytes / 缓冲区长度（字节）
     pub buf_len: u32,
-    /// Offset for file operations / 文件操作的偏移量
+    /// Offset for file operations, or the target user data for a cancel
+    /// 文件操作的偏移量，或取消操作的目标用户数据
     pub offset: u64,
     /// Address for connect/accept operations / 连接/接受操作的地址
     pub addr: Option<SockAddr>,
+    /// Buffer group used with [`BUFFER_SELECT`](super::op_flags::BUFFER_SELECT)
+    /// 与 [`BUFFER_SELECT`](super::op_flags::BUFFER_SELECT) 一起使用的缓冲区组
+    pub buf_group: u16,
 }
 
 /// Socket address storage for connection operations
@@ -123,6 +127,7 @@ impl SubmitEntry {
             buf_len: 0,
             offset: 0,
             addr: None,
+            buf_group: 0,
         }
     }
 
@@ -144,6 +149,7 @@ impl SubmitEntry {
             buf_len,
             offset: 0,
             addr: None,
+            buf_group: 0,
         }
     }
 
@@ -165,9 +171,79 @@ impl SubmitEntry {
             buf_len,
             offset: 0,
             addr: None,
+            buf_group: 0,
         }
     }
 
+    /// Create an accept operation entry
+    /// 创建accept操作条目
+    #[must_use]
+    pub const fn accept(fd: i32, user_data: u64) -> Self {
+        Self::new(fd, super::opcode::ACCEPT, user_data)
+    }
+
+    /// Create a receive operation entry
+    /// 创建接收操作条目
+    ///
+    /// # Safety / 安全性
+    ///
+    /// `buf` must be valid for writes and remain valid until completion.
+    /// `buf` 必须对写入有效并在完成前保持有效。
+    #[must_use]
+    pub unsafe fn recv(fd: i32, buf: *mut u8, buf_len: u32, user_data: u64) -> Self {
+        Self::new(fd, super::opcode::RECV, user_data).with_buffer(buf, buf_len)
+    }
+
+    /// Create a send operation entry
+    /// 创建发送操作条目
+    ///
+    /// # Safety / 安全性
+    ///
+    /// `buf` must be valid for reads and remain valid until completion.
+    /// `buf` 必须对读取有效并在完成前保持有效。
+    #[must_use]
+    pub unsafe fn send(fd: i32, buf: *const u8, buf_len: u32, user_data: u64) -> Self {
+        Self::new(fd, super::opcode::SEND, user_data).with_buffer(buf.cast_mut(), buf_len)
+    }
+
+    /// Create an fsync operation entry
+    /// 创建fsync操作条目
+    #[must_use]
+    pub const fn fsync(fd: i32, user_data: u64) -> Self {
+        Self::new(fd, super::opcode::FSYNC, user_data)
+    }
+
+    /// Create a close operation entry
+    /// 创建关闭操作条目
+    #[must_use]
+    pub const fn close(fd: i32, user_data: u64) -> Self {
+        Self::new(fd, super::opcode::CLOSE, user_data)
+    }
+
+    /// Create an entry cancelling the operation submitted with `target` user data
+    /// 创建取消以 `target` 用户数据提交的操作的条目
+    #[must_use]
+    pub const fn cancel(target: u64, user_data: u64) -> Self {
+        Self::new(-1, super::opcode::CANCEL, user_data).with_offset(target)
+    }
+
+    /// Keep the operation armed, posting a completion per event
+    /// 保持操作有效，每个事件产生一个完成事件
+    #[must_use]
+    pub const fn multishot(mut self) -> Self {
+        self.flags |= super::op_flags::MULTISHOT;
+        self
+    }
+
+    /// Let the kernel pick the buffer from a registered buffer group
+    /// 由内核从已注册的缓冲区组中选择缓冲区
+    #[must_use]
+    pub const fn with_buffer_group(mut self, group: u16) -> Self {
+        self.flags |= super::op_flags::BUFFER_SELECT;
+        self.buf_group = group;
+        self
+    }
+
     /// Set the buffer for this operation
     /// 为此操作设置缓冲区
     ///
@@ -306,6 +382,24 @@ impl CompletionEntry {
         }
     }
 
+    /// Get the ID of the provided buffer the kernel picked, if any
+    /// 获取内核选择的提供缓冲区的ID（如果有）
+    #[must_use]
+    pub const fn buffer_id(self) -> Option<u16> {
+        if self.flags & super::cqe_flags::BUFFER != 0 {
+            Some((self.flags >> super::cqe_flags::BUFFER_SHIFT) as u16)
+        } else {
+            None
+        }
+    }
+
+    /// Check whether a multishot operation will post more completions
+    /// 检查多次触发操作是否还会产生更多完成事件
+    #[must_use]
+    pub const fn has_more(self) -> bool {
+        self.flags & super::cqe_flags::MORE != 0
+    }
+
     /// Convert the result to a `std::io::Result`
     /// 将结果转换为 `std::io::Result`
     ///
@@ -0,0 +1,787 @@
+//! Reactor connecting a runtime's driver to the tasks waiting on it
+//! 将运行时的driver连接到等待它的任务的反应器
+//!
+//! # Overview / 概述
+//!
+//! A [`Runtime`](crate::Runtime) wraps its driver in a [`Reactor`] and makes it the
+//! current reactor of the threads running its tasks. I/O futures submit through
+//! [`Reactor::current`]; the runtime's event loop hands every completion back through
+//! [`Reactor::dispatch`], which stores the result and wakes the waiting task.
+//!
+//! With a completion driver an operation owns its buffer. The buffer stays in the
+//! in-flight table until the kernel reports the operation done, so dropping a
+//! [`Completion`] while the kernel still works on it is safe. With a readiness
+//! driver the reactor only reports that a descriptor is ready, see
+//! [`Reactor::retry_when_ready`], and the caller repeats the syscall. Readiness
+//! waits of tasks polled outside a runtime go to a shared reactor with its own thread.
+//!
+//! [`Runtime`](crate::Runtime) 将其driver包装在 [`Reactor`] 中，并使其成为运行其任务的
+//! 线程的当前反应器。I/O future通过 [`Reactor::current`] 提交；运行时的事件循环通过
+//! [`Reactor::dispatch`] 交回每个完成事件，反应器保存结果并唤醒等待的任务。
+//!
+//! 使用完成型driver时，操作拥有自己的缓冲区。缓冲区保存在进行中表里，直到内核报告操作
+//! 完成，因此在内核仍在处理时丢弃 [`Completion`] 是安全的。使用就绪型driver时，反应器只
+//! 报告描述符已就绪（参见 [`Reactor::retry_when_ready`]），由调用方重新执行系统调用。
+//! 在运行时之外轮询的任务的就绪等待交给拥有自己线程的共享反应器。
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::os::fd::RawFd;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+
+#[cfg(target_os = "linux")]
+use super::iouring::BufRing;
+use super::{CompletionEntry, Driver, DriverFactory, DriverType, SubmitEntry, op_flags, opcode};
+
+/// Largest single transfer handed to the kernel / 单次交给内核的最大传输长度
+pub(crate) const MAX_IO_LEN: usize = 1 << 30;
+
+/// User data bit marking an operation of the reactor / 标记反应器操作的用户数据位
+const OP_BIT: u64 = 1 << 63;
+
+/// User data bit marking a readiness wait; the low 32 bits hold the descriptor
+/// 标记就绪等待的用户数据位；低32位保存描述符
+const READY_BIT: u64 = 1 << 62;
+
+/// Readiness wait bit: the wait serves the reader of the descriptor
+/// 就绪等待位：该等待服务于描述符的读取方
+const READ_WAIT: u64 = 1 << 61;
+
+/// Readiness wait bit: the wait serves the writer of the descriptor
+/// 就绪等待位：该等待服务于描述符的写入方
+const WRITE_WAIT: u64 = 1 << 60;
+
+/// Buffer group of the ring socket receives draw from / 套接字接收所用环的缓冲区组
+#[cfg(target_os = "linux")]
+pub(crate) const RECV_GROUP: u16 = u16::MAX;
+
+/// Buffers in the receive ring / 接收环中的缓冲区数量
+#[cfg(target_os = "linux")]
+const RECV_BUFFERS: u16 = 256;
+
+/// Size of each receive buffer / 每个接收缓冲区的大小
+#[cfg(target_os = "linux")]
+const RECV_BUFFER_SIZE: usize = 4096;
+
+/// User data of cancellation requests, which are never collected
+/// 取消请求的用户数据，这些请求的结果不会被收取
+const CANCEL_ID: u64 = OP_BIT;
+
+thread_local! {
+    /// Reactor of the runtime whose tasks run on this thread
+    /// 其任务在此线程上运行的运行时的反应器
+    static CURRENT: RefCell<Option<Arc<Reactor>>> = const { RefCell::new(None) };
+}
+
+/// An operation the kernel has not completed or its owner has not collected
+/// 内核尚未完成或其所有者尚未取走的操作
+struct InFlight {
+    /// Operation code, to clean up after abandoned operations / 操作码，用于清理被放弃的操作
+    opcode: u8,
+    /// Buffer the kernel reads from or writes into / 内核读取或写入的缓冲区
+    buf: Vec<u8>,
+    /// Keeps resources such as the file open until completion / 在完成前保持文件等资源存活
+    _hold: Option<Box<dyn Any + Send>>,
+    /// Kernel result once completed / 完成后的内核结果
+    result: Option<i32>,
+    /// Task waiting for the result / 等待结果的任务
+    waker: Option<Waker>,
+    /// The owner went away; discard on completion / 所有者已离开；完成时丢弃
+    abandoned: bool,
+    /// Multishot operations post many completions / 多次触发操作会产生多个完成事件
+    multishot: bool,
+    /// Completions of a multishot operation not yet collected
+    /// 多次触发操作尚未取走的完成事件
+    events: VecDeque<CompletionEntry>,
+}
+
+/// Tasks waiting for a descriptor, one per direction
+/// 等待描述符的任务，每个方向一个
+#[derive(Default)]
+struct Waiters {
+    read: Option<Waker>,
+    write: Option<Waker>,
+}
+
+/// A runtime's driver together with the operations and tasks waiting on it
+/// 运行时的driver，以及等待它的操作和任务
+pub(crate) struct Reactor {
+    driver: Arc<dyn Driver>,
+    /// Serializes `get_submission` + `submit` / 串行化 `get_submission` + `submit`
+    submit_lock: Mutex<()>,
+    ops: Mutex<HashMap<u64, InFlight>>,
+    /// Tasks waiting for readiness, by descriptor / 按描述符等待就绪的任务
+    waiters: Mutex<HashMap<RawFd, Waiters>>,
+    next_id: AtomicU64,
+    /// Cleared once the kernel rejects a multishot operation
+    /// 内核拒绝多次触发操作后清除
+    multishot: AtomicBool,
+    /// Ring socket receives take their buffers from, registered on first use
+    /// 套接字接收从中获取缓冲区的环，首次使用时注册
+    #[cfg(target_os = "linux")]
+    recv_ring: OnceLock<Option<Arc<BufRing>>>,
+}
+
+/// Restores the previous current reactor when dropped
+/// 丢弃时恢复之前的当前反应器
+pub(crate) struct EnterGuard {
+    previous: Option<Arc<Reactor>>,
+}
+
+impl Drop for EnterGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+impl Reactor {
+    /// Wrap a runtime's driver
+    /// 包装运行时的driver
+    pub(crate) fn new(driver: Arc<dyn Driver>) -> Self {
+        Self {
+            driver,
+            submit_lock: Mutex::new(()),
+            ops: Mutex::new(HashMap::new()),
+            waiters: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            multishot: AtomicBool::new(true),
+            #[cfg(target_os = "linux")]
+            recv_ring: OnceLock::new(),
+        }
+    }
+
+    /// Get the reactor of the runtime running the current task
+    /// 获取运行当前任务的运行时的反应器
+    pub(crate) fn current() -> Option<Arc<Self>> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Get the current reactor if its driver performs all of `ops` itself
+    /// 如果当前反应器的driver能自行执行 `ops` 中的所有操作，则返回它
+    pub(crate) fn completion(ops: &[u8]) -> Option<Arc<Self>> {
+        Self::current().filter(|reactor| {
+            reactor.driver.is_completion_based()
+                && ops.iter().all(|&op| reactor.driver.supports_operation(op))
+        })
+    }
+
+    /// Make this the current reactor of the thread until the guard is dropped
+    /// 在守卫被丢弃之前，使其成为线程的当前反应器
+    pub(crate) fn enter(self: &Arc<Self>) -> EnterGuard {
+        let previous = CURRENT.with(|current| current.borrow_mut().replace(self.clone()));
+        EnterGuard { previous }
+    }
+
+    /// Arrange for the task to be polled again once `fd` may be ready for `op`
+    /// 安排在 `fd` 可能已为 `op` 就绪时再次轮询任务
+    ///
+    /// `op` is [`opcode::READ`] or [`opcode::WRITE`]. Outside a runtime, or on a
+    /// completion runtime, the wait goes to a shared readiness reactor whose thread
+    /// wakes the task; an error means no readiness driver is available.
+    /// `op` 为 [`opcode::READ`] 或 [`opcode::WRITE`]。在运行时之外或完成型运行时上，等待
+    /// 交给共享的就绪型反应器，由其线程唤醒任务；返回错误表示没有可用的就绪型driver。
+    pub(crate) fn retry_when_ready(fd: RawFd, op: u8, cx: &Context<'_>) -> io::Result<()> {
+        let reactor = match Self::current().filter(|reactor| !reactor.driver.is_completion_based())
+        {
+            Some(reactor) => reactor,
+            None => Self::fallback()?,
+        };
+        reactor.wait_ready(fd, op, cx.waker())
+    }
+
+    /// Get the shared readiness reactor, starting its thread on first use
+    /// 获取共享的就绪型反应器，首次使用时启动其线程
+    fn fallback() -> io::Result<Arc<Self>> {
+        static FALLBACK: OnceLock<Option<Arc<Reactor>>> = OnceLock::new();
+
+        FALLBACK
+            .get_or_init(|| {
+                #[cfg(target_os = "linux")]
+                let driver_type = DriverType::Epoll;
+                #[cfg(not(target_os = "linux"))]
+                let driver_type = DriverType::Kqueue;
+
+                let reactor = Arc::new(Self::new(DriverFactory::create(driver_type).ok()?));
+                let events = reactor.clone();
+                std::thread::Builder::new()
+                    .name("nexus-reactor".into())
+                    .spawn(move || events.run_events())
+                    .ok()?;
+                Some(reactor)
+            })
+            .clone()
+            .ok_or_else(|| io::Error::other("no readiness driver available"))
+    }
+
+    /// Event loop of the shared readiness reactor
+    /// 共享就绪型反应器的事件循环
+    fn run_events(&self) {
+        loop {
+            // Interrupted waits are simply repeated / 被中断的等待直接重试
+            let _ = self.driver.wait();
+            while let Some(&completion) = self.driver.get_completion() {
+                self.driver.advance_completion();
+                self.dispatch(completion);
+            }
+        }
+    }
+
+    /// Arm a one-shot readiness wait for `fd`
+    /// 为 `fd` 设置一次性就绪等待
+    fn wait_ready(&self, fd: RawFd, op: u8, waker: &Waker) -> io::Result<()> {
+        let mut waiters = self.waiters.lock().unwrap();
+        let slots = waiters.entry(fd).or_default();
+        let slot = if op == opcode::WRITE {
+            &mut slots.write
+        } else {
+            &mut slots.read
+        };
+        // Only a task of the same direction is displaced; it polls again
+        // 只会替换同一方向的任务；被替换的任务会再次轮询
+        let displaced = slot
+            .replace(waker.clone())
+            .filter(|previous| !previous.will_wake(waker));
+
+        // Readiness drivers keep one registration per descriptor, so a reader and a
+        // writer waiting together arm both directions
+        // 就绪型driver每个描述符只保留一个注册，因此读取方和写入方同时等待时设置两个方向
+        let (arm, interest) = match (slots.read.is_some(), slots.write.is_some()) {
+            (true, true) => (opcode::POLL, READ_WAIT | WRITE_WAIT),
+            (false, true) => (opcode::WRITE, WRITE_WAIT),
+            _ => (opcode::READ, READ_WAIT),
+        };
+        let result = self.push(SubmitEntry::new(fd, arm, READY_BIT | interest | fd as u32 as u64));
+        if result.is_err() {
+            let slots = waiters.get_mut(&fd).unwrap();
+            let slot = if op == opcode::WRITE {
+                &mut slots.write
+            } else {
+                &mut slots.read
+            };
+            slot.take();
+        }
+        drop(waiters);
+
+        if let Some(previous) = displaced {
+            previous.wake();
+        }
+        result
+    }
+
+    /// Submit the entries queued on the driver
+    /// 提交driver上排队的条目
+    pub(crate) fn flush(&self) -> io::Result<usize> {
+        let _guard = self.submit_lock.lock().unwrap();
+        self.driver.submit()
+    }
+
+    /// Start an operation, moving `buf` and `hold` into the in-flight table
+    /// 启动操作，将 `buf` 和 `hold` 移入进行中表
+    ///
+    /// # Safety / 安全性
+    ///
+    /// A buffer `entry` points to must be the heap allocation of `buf`.
+    /// `entry` 指向的缓冲区必须是 `buf` 的堆内存。
+    pub(crate) unsafe fn submit(
+        self: &Arc<Self>,
+        entry: SubmitEntry,
+        buf: Vec<u8>,
+        hold: Option<Box<dyn Any + Send>>,
+    ) -> io::Result<Completion> {
+        // SAFETY: forwarded from the caller / 安全性：由调用方保证
+        let id = unsafe { self.start(entry, buf, hold) }?;
+        Ok(Completion {
+            reactor: self.clone(),
+            id,
+        })
+    }
+
+    /// Enter an operation into the in-flight table and queue it, returning its ID
+    /// 将操作登记到进行中表并排队，返回其ID
+    ///
+    /// # Safety / 安全性
+    ///
+    /// See [`submit`](Self::submit). / 见 [`submit`](Self::submit)。
+    unsafe fn start(
+        &self,
+        mut entry: SubmitEntry,
+        buf: Vec<u8>,
+        hold: Option<Box<dyn Any + Send>>,
+    ) -> io::Result<u64> {
+        let id = OP_BIT | self.next_id.fetch_add(1, Ordering::Relaxed);
+        entry.user_data = id;
+        self.ops.lock().unwrap().insert(
+            id,
+            InFlight {
+                opcode: entry.opcode,
+                buf,
+                _hold: hold,
+                result: None,
+                waker: None,
+                abandoned: false,
+                multishot: entry.flags & op_flags::MULTISHOT != 0,
+                events: VecDeque::new(),
+            },
+        );
+        if let Err(err) = self.push(entry) {
+            self.ops.lock().unwrap().remove(&id);
+            return Err(err);
+        }
+        Ok(id)
+    }
+
+    /// Start a multishot operation whose completions queue up until collected
+    /// 启动多次触发操作，其完成事件排队等待取走
+    ///
+    /// The entry must not point to a buffer; receives take theirs from
+    /// [`recv_ring`](Self::recv_ring).
+    /// 条目不能指向缓冲区；接收操作从 [`recv_ring`](Self::recv_ring) 获取缓冲区。
+    pub(crate) fn submit_multishot(self: &Arc<Self>, entry: SubmitEntry) -> io::Result<Multishot> {
+        debug_assert!(entry.buf_ptr.is_none());
+        // SAFETY: the entry points to no buffer / 安全性：条目不指向任何缓冲区
+        let id = unsafe { self.start(entry.multishot(), Vec::new(), None) }?;
+        Ok(Multishot {
+            reactor: self.clone(),
+            id,
+        })
+    }
+
+    /// Check whether multishot operations are worth trying
+    /// 检查是否值得尝试多次触发操作
+    pub(crate) fn multishot(&self) -> bool {
+        self.multishot.load(Ordering::Relaxed)
+    }
+
+    /// Remember that the kernel rejected a multishot operation, e.g. before 5.19
+    /// 记录内核拒绝了多次触发操作，例如5.19之前的内核
+    pub(crate) fn disable_multishot(&self) {
+        self.multishot.store(false, Ordering::Relaxed);
+    }
+
+    /// Get the ring socket receives draw their buffers from, registering it on first use
+    /// 获取套接字接收所用的缓冲区环，首次使用时注册
+    #[cfg(target_os = "linux")]
+    pub(crate) fn recv_ring(&self) -> Option<&Arc<BufRing>> {
+        if !self.multishot() {
+            return None;
+        }
+        self.recv_ring
+            .get_or_init(|| {
+                self.driver
+                    .register_buf_ring(RECV_GROUP, RECV_BUFFERS, RECV_BUFFER_SIZE)
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    /// Give back what an uncollected completion holds: a connection or a ring buffer
+    /// 归还未取走的完成事件所持有的资源：连接或环缓冲区
+    fn release(&self, opcode: u8, completion: &CompletionEntry) {
+        if opcode == opcode::ACCEPT && completion.result >= 0 {
+            unsafe { libc::close(completion.result) };
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(Some(ring)) = self.recv_ring.get() {
+            drop(ring.take(completion));
+        }
+    }
+
+    /// Queue one entry and hand it to the kernel
+    /// 排入一个条目并交给内核
+    fn push(&self, entry: SubmitEntry) -> io::Result<()> {
+        let _guard = self.submit_lock.lock().unwrap();
+        match self.driver.get_submission() {
+            Some(slot) => {
+                *slot = entry;
+                self.driver.submit().map(drop)
+            },
+            None => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+        }
+    }
+
+    /// Hand a completion to the operation or task it belongs to
+    /// 将完成事件交给其所属的操作或任务
+    ///
+    /// Returns `false` for completions the reactor did not submit.
+    /// 对于不是由反应器提交的完成事件返回 `false`。
+    pub(crate) fn dispatch(&self, completion: CompletionEntry) -> bool {
+        let CompletionEntry {
+            user_data, result, ..
+        } = completion;
+        if user_data & OP_BIT != 0 {
+            let mut ops = self.ops.lock().unwrap();
+            let Some(op) = ops.get_mut(&user_data) else {
+                return true;
+            };
+            if op.multishot {
+                let finished = !completion.has_more();
+                if op.abandoned {
+                    let opcode = op.opcode;
+                    if finished {
+                        ops.remove(&user_data);
+                    }
+                    drop(ops);
+                    self.release(opcode, &completion);
+                    return true;
+                }
+                op.events.push_back(completion);
+                if finished {
+                    op.result = Some(result);
+                }
+                let waker = op.waker.take();
+                drop(ops);
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+                return true;
+            }
+            if op.abandoned {
+                let op = ops.remove(&user_data).unwrap();
+                drop(ops);
+                // A connection accepted for nobody / 无人接收的已接受连接
+                if op.opcode == opcode::ACCEPT && result >= 0 {
+                    unsafe { libc::close(result) };
+                }
+                return true;
+            }
+            op.result = Some(result);
+            let waker = op.waker.take();
+            drop(ops);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+            true
+        } else if user_data & READY_BIT != 0 {
+            let fd = user_data as u32 as RawFd;
+            let mut waiters = self.waiters.lock().unwrap();
+            let Some(slots) = waiters.get_mut(&fd) else {
+                return true;
+            };
+            let read = slots.read.take_if(|_| user_data & READ_WAIT != 0);
+            let write = slots.write.take_if(|_| user_data & WRITE_WAIT != 0);
+            if slots.read.is_none() && slots.write.is_none() {
+                waiters.remove(&fd);
+            }
+            drop(waiters);
+            for waker in read.into_iter().chain(write) {
+                waker.wake();
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A submitted operation, resolving to its result and buffer
+/// 已提交的操作，解析为其结果和缓冲区
+///
+/// Dropping it before the operation completes leaves the buffer with the reactor
+/// until the kernel is done with it.
+/// 在操作完成前丢弃它时，缓冲区留在反应器中，直到内核用完为止。
+pub(crate) struct Completion {
+    reactor: Arc<Reactor>,
+    id: u64,
+}
+
+impl Completion {
+    /// Ask the kernel to stop the operation early; it still completes
+    /// 请求内核提前停止操作；操作仍会完成
+    pub(crate) fn cancel(&self) {
+        let _ = self.reactor.push(SubmitEntry::cancel(self.id, CANCEL_ID));
+    }
+}
+
+impl Future for Completion {
+    type Output = io::Result<(usize, Vec<u8>)>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut ops = self.reactor.ops.lock().unwrap();
+        let Some(op) = ops.get_mut(&self.id) else {
+            return Poll::Ready(Err(io::Error::other("I/O operation already collected")));
+        };
+        let Some(result) = op.result else {
+            op.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        };
+        let op = ops.remove(&self.id).unwrap();
+        Poll::Ready(if result < 0 {
+            Err(io::Error::from_raw_os_error(-result))
+        } else {
+            Ok((result as usize, op.buf))
+        })
+    }
+}
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        let mut ops = self.reactor.ops.lock().unwrap();
+        let Some(op) = ops.get_mut(&self.id) else {
+            return;
+        };
+        let Some(result) = op.result else {
+            // The kernel may still use the buffer / 内核可能仍在使用缓冲区
+            op.abandoned = true;
+            op.waker = None;
+            return;
+        };
+        let op = ops.remove(&self.id).unwrap();
+        drop(ops);
+        if op.opcode == opcode::ACCEPT && result >= 0 {
+            unsafe { libc::close(result) };
+        }
+    }
+}
+
+impl fmt::Debug for Completion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Completion")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A submitted multishot operation, yielding completions until the kernel posts
+/// no more
+/// 已提交的多次触发操作，产生完成事件直到内核不再产生
+///
+/// Dropping it cancels the operation; completions still on their way give their
+/// connection or buffer back.
+/// 丢弃它会取消该操作；仍在途中的完成事件会归还其连接或缓冲区。
+pub(crate) struct Multishot {
+    reactor: Arc<Reactor>,
+    id: u64,
+}
+
+impl Multishot {
+    /// Take the next completion, or `None` once the operation has finished
+    /// 取出下一个完成事件，操作结束后返回 `None`
+    pub(crate) fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<CompletionEntry>> {
+        let mut ops = self.reactor.ops.lock().unwrap();
+        let Some(op) = ops.get_mut(&self.id) else {
+            return Poll::Ready(None);
+        };
+        if let Some(event) = op.events.pop_front() {
+            return Poll::Ready(Some(event));
+        }
+        if op.result.is_some() {
+            ops.remove(&self.id);
+            return Poll::Ready(None);
+        }
+        op.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Multishot {
+    fn drop(&mut self) {
+        let mut ops = self.reactor.ops.lock().unwrap();
+        let Some(op) = ops.get_mut(&self.id) else {
+            return;
+        };
+        let opcode = op.opcode;
+        let events = std::mem::take(&mut op.events);
+        let finished = op.result.is_some();
+        if finished {
+            ops.remove(&self.id);
+        } else {
+            op.abandoned = true;
+            op.waker = None;
+        }
+        drop(ops);
+
+        for event in &events {
+            self.reactor.release(opcode, event);
+        }
+        if !finished {
+            let _ = self.reactor.push(SubmitEntry::cancel(self.id, CANCEL_ID));
+        }
+    }
+}
+
+impl fmt::Debug for Multishot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Multishot")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{DriverFactory, DriverType};
+    use std::io::Write;
+    use std::os::fd::AsRawFd;
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    /// Run the event loop of `reactor` until `future` is ready
+    /// 运行 `reactor` 的事件循环直到 `future` 就绪
+    fn run<F: Future>(reactor: &Reactor, future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        for _ in 0..100 {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            reactor
+                .driver
+                .wait_timeout(Duration::from_millis(50))
+                .unwrap();
+            while let Some(completion) = reactor.driver.get_completion() {
+                let completion = *completion;
+                reactor.driver.advance_completion();
+                assert!(reactor.dispatch(completion));
+            }
+        }
+        panic!("operation did not complete");
+    }
+
+    #[test]
+    fn test_completion_recv() {
+        let Ok(driver) = DriverFactory::create(DriverType::IOUring) else {
+            return;
+        };
+        let reactor = Arc::new(Reactor::new(driver));
+        let (mut tx, rx) = UnixStream::pair().unwrap();
+        tx.write_all(b"ping").unwrap();
+
+        let mut buf = vec![0u8; 16];
+        // SAFETY: the entry points into `buf`, which moves into the reactor
+        let entry =
+            unsafe { SubmitEntry::recv(rx.as_raw_fd(), buf.as_mut_ptr(), buf.len() as u32, 0) };
+        let completion = unsafe { reactor.submit(entry, buf, None) }.unwrap();
+        let (n, buf) = run(&reactor, completion).unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert!(reactor.ops.lock().unwrap().is_empty());
+
+        // Abandoned, then cancelled: the table forgets it once the kernel is done
+        // 被放弃后取消：内核完成后表中不再保留它
+        let mut buf = vec![0u8; 16];
+        let entry =
+            unsafe { SubmitEntry::recv(rx.as_raw_fd(), buf.as_mut_ptr(), buf.len() as u32, 0) };
+        let completion = unsafe { reactor.submit(entry, buf, None) }.unwrap();
+        completion.cancel();
+        drop(completion);
+        run(
+            &reactor,
+            std::future::poll_fn(|_| {
+                if reactor.ops.lock().unwrap().is_empty() {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            }),
+        );
+    }
+
+    /// Waker raising a flag / 设置标志的waker
+    struct Flag(Arc<AtomicBool>);
+
+    impl std::task::Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_readiness_wakes_waiter() {
+        let Ok(driver) = DriverFactory::create(DriverType::Epoll) else {
+            return;
+        };
+        let reactor = Arc::new(Reactor::new(driver));
+        let _enter = reactor.enter();
+        let (mut tx, rx) = UnixStream::pair().unwrap();
+
+        let woken = Arc::new(AtomicBool::new(false));
+        let waker = Waker::from(Arc::new(Flag(woken.clone())));
+        Reactor::retry_when_ready(rx.as_raw_fd(), opcode::READ, &Context::from_waker(&waker))
+            .unwrap();
+        assert!(!woken.load(Ordering::SeqCst));
+
+        tx.write_all(b"x").unwrap();
+        run(
+            &reactor,
+            std::future::poll_fn(|_| {
+                if woken.load(Ordering::SeqCst) {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            }),
+        );
+    }
+
+    #[test]
+    fn test_reader_and_writer_wait_together() {
+        let Ok(driver) = DriverFactory::create(DriverType::Epoll) else {
+            return;
+        };
+        let reactor = Arc::new(Reactor::new(driver));
+        let _enter = reactor.enter();
+        let (mut tx, mut rx) = UnixStream::pair().unwrap();
+
+        // Fill the socket buffer so `rx` is not writable
+        // 填满套接字缓冲区，使 `rx` 不可写
+        rx.set_nonblocking(true).unwrap();
+        while rx.write(&[0u8; 4096]).is_ok() {}
+
+        let reader = Arc::new(AtomicBool::new(false));
+        let writer = Arc::new(AtomicBool::new(false));
+        let read_waker = Waker::from(Arc::new(Flag(reader.clone())));
+        let write_waker = Waker::from(Arc::new(Flag(writer.clone())));
+        Reactor::retry_when_ready(rx.as_raw_fd(), opcode::READ, &Context::from_waker(&read_waker))
+            .unwrap();
+        Reactor::retry_when_ready(
+            rx.as_raw_fd(),
+            opcode::WRITE,
+            &Context::from_waker(&write_waker),
+        )
+        .unwrap();
+        assert!(!reader.load(Ordering::SeqCst), "the writer displaced the reader");
+
+        tx.write_all(b"x").unwrap();
+        run(
+            &reactor,
+            std::future::poll_fn(|_| {
+                if reader.load(Ordering::SeqCst) {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            }),
+        );
+    }
+
+    #[test]
+    fn test_readiness_outside_runtime() {
+        let (mut tx, rx) = UnixStream::pair().unwrap();
+        let woken = Arc::new(AtomicBool::new(false));
+        let waker = Waker::from(Arc::new(Flag(woken.clone())));
+
+        // No reactor entered: the shared reactor waits instead of waking right away
+        // 未进入反应器：由共享反应器等待，而不是立即唤醒
+        Reactor::retry_when_ready(rx.as_raw_fd(), opcode::READ, &Context::from_waker(&waker))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!woken.load(Ordering::SeqCst));
+
+        tx.write_all(b"x").unwrap();
+        for _ in 0..100 {
+            if woken.load(Ordering::SeqCst) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("shared reactor did not wake the task");
+    }
+}
@@ -0,0 +1,616 @@
+//! Asynchronous filesystem operations
+//! 异步文件系统操作
+//!
+//! # Overview / 概述
+//!
+//! Inside a [`Runtime`](crate::Runtime) with a completion driver (io_uring), [`File`]
+//! reads, writes and syncs are submitted to the runtime's own driver, so no thread
+//! blocks on disk I/O. With a readiness driver, outside a runtime, and for path
+//! operations without a driver counterpart (opening, metadata, directory listing,
+//! renaming), the work runs on the [blocking pool](crate::task::spawn_blocking).
+//!
+//! 在使用完成型driver（io_uring）的 [`Runtime`](crate::Runtime) 中，[`File`] 的读取、
+//! 写入和同步被提交到运行时自己的driver，因此没有线程会阻塞在磁盘I/O上。使用就绪型
+//! driver时、在运行时之外，以及对于没有driver对应操作的路径操作（打开、元数据、目录
+//! 列举、重命名），工作在[阻塞线程池](crate::task::spawn_blocking)上运行。
+//!
+//! [`sendfile`] copies a file region to a [`TcpStream`] inside the kernel, without
+//! passing the bytes through user space.
+//!
+//! [`sendfile`] 在内核中将文件区域复制到 [`TcpStream`]，数据无需经过用户空间。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::fs::{self, File};
+//!
+//! async fn copy_upload() -> std::io::Result<()> {
+//!     let data = fs::read("/tmp/upload.bin").await?;
+//!
+//!     let mut file = File::create("/var/data/upload.bin").await?;
+//!     file.write_all(&data).await?;
+//!     file.sync_all().await?;
+//!
+//!     fs::rename("/var/data/upload.bin", "/var/data/final.bin").await
+//! }
+//! ```
+
+#[cfg(unix)]
+mod completion;
+
+use std::fs as std_fs;
+use std::io::{self, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::io::TcpStream;
+use crate::task::spawn_blocking;
+
+pub use std::fs::{DirEntry, Metadata};
+
+/// Chunk size for reading whole files / 读取整个文件时的块大小
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Run `f` on the blocking pool
+/// 在阻塞线程池上运行 `f`
+async fn asyncify<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    spawn_blocking(f)
+        .await
+        .unwrap_or_else(|err| Err(io::Error::other(err.to_string())))
+}
+
+/// An open file
+/// 已打开的文件
+///
+/// Reads and writes are positional: the file keeps its own cursor, moved by
+/// [`File::seek`], so a file opened in append mode should be written through
+/// [`std::fs::File`] instead.
+/// 读写是基于位置的：文件维护自己的游标，通过 [`File::seek`] 移动，因此以追加模式打开的
+/// 文件应改用 [`std::fs::File`] 写入。
+#[derive(Debug)]
+pub struct File {
+    /// Shared with in-flight operations / 与进行中的操作共享
+    std: Arc<std_fs::File>,
+    /// Cursor position / 游标位置
+    pos: u64,
+}
+
+impl File {
+    /// Open a file for reading
+    /// 以只读方式打开文件
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns an error if the file does not exist or cannot be opened.
+    /// 如果文件不存在或无法打开则返回错误。
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        asyncify(move || std_fs::File::open(path))
+            .await
+            .map(Self::from_std)
+    }
+
+    /// Create or truncate a file for writing
+    /// 以写入方式创建或截断文件
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns an error if the file cannot be created.
+    /// 如果无法创建文件则返回错误。
+    pub async fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        asyncify(move || std_fs::File::create(path))
+            .await
+            .map(Self::from_std)
+    }
+
+    /// Wrap a file opened with [`std::fs::OpenOptions`]; the cursor starts at 0
+    /// 包装通过 [`std::fs::OpenOptions`] 打开的文件；游标从0开始
+    #[must_use]
+    pub fn from_std(file: std_fs::File) -> Self {
+        Self {
+            std: Arc::new(file),
+            pos: 0,
+        }
+    }
+
+    /// Read some bytes at the cursor, returning how many were read (0 at the end)
+    /// 在游标处读取一些字节，返回读取的字节数（到达末尾时为0）
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns the error reported by the read.
+    /// 返回读取操作报告的错误。
+    pub async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let data = read_at(&self.std, self.pos, buf.len()).await?;
+        buf[..data.len()].copy_from_slice(&data);
+        self.pos += data.len() as u64;
+        Ok(data.len())
+    }
+
+    /// Read some bytes at `pos` without moving the cursor, returning how many were read
+    /// 在 `pos` 处读取一些字节而不移动游标，返回读取的字节数
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns the error reported by the read.
+    /// 返回读取操作报告的错误。
+    pub async fn read_at(&self, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+        let data = read_at(&self.std, pos, buf.len()).await?;
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    /// Read from the cursor to the end of the file, appending to `buf`
+    /// 从游标读取到文件末尾，追加到 `buf`
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns the error reported by a read.
+    /// 返回读取操作报告的错误。
+    pub async fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let start = buf.len();
+        loop {
+            let data = read_at(&self.std, self.pos, READ_CHUNK).await?;
+            if data.is_empty() {
+                return Ok(buf.len() - start);
+            }
+            self.pos += data.len() as u64;
+            buf.extend_from_slice(&data);
+        }
+    }
+
+    /// Write some bytes at the cursor, returning how many were written
+    /// 在游标处写入一些字节，返回写入的字节数
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns the error reported by the write.
+    /// 返回写入操作报告的错误。
+    pub async fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = write_at(&self.std, self.pos, buf).await?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    /// Write all of `buf` at the cursor
+    /// 在游标处写入整个 `buf`
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns the error reported by a write, or [`io::ErrorKind::WriteZero`].
+    /// 返回写入操作报告的错误，或 [`io::ErrorKind::WriteZero`]。
+    pub async fn write_all(&mut self, mut buf: &[u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.write(buf).await? {
+                0 => return Err(io::Error::new(io::ErrorKind::WriteZero, "write zero byte")),
+                n => buf = &buf[n..],
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the cursor, returning the new position
+    /// 移动游标，返回新位置
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns [`io::ErrorKind::InvalidInput`] for a position before the start.
+    /// 位置在文件开头之前时返回 [`io::ErrorKind::InvalidInput`]。
+    pub async fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, delta) = match pos {
+            SeekFrom::Start(pos) => (pos, 0),
+            SeekFrom::Current(delta) => (self.pos, delta),
+            SeekFrom::End(delta) => (self.metadata().await?.len(), delta),
+        };
+        self.pos = base.checked_add_signed(delta).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative position")
+        })?;
+        Ok(self.pos)
+    }
+
+    /// Flush data and metadata to disk
+    /// 将数据和元数据刷新到磁盘
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns the error reported by the sync.
+    /// 返回同步操作报告的错误。
+    pub async fn sync_all(&self) -> io::Result<()> {
+        #[cfg(unix)]
+        if let Some(reactor) = completion::reactor() {
+            return completion::fsync(&reactor, self.std.clone()).await;
+        }
+        let file = self.std.clone();
+        asyncify(move || file.sync_all()).await
+    }
+
+    /// Query the file's metadata
+    /// 查询文件元数据
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns the error reported by the system.
+    /// 返回系统报告的错误。
+    pub async fn metadata(&self) -> io::Result<Metadata> {
+        let file = self.std.clone();
+        asyncify(move || file.metadata()).await
+    }
+}
+
+#[cfg(unix)]
+impl std::os::fd::AsRawFd for File {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.std.as_raw_fd()
+    }
+}
+
+/// Read up to `len` bytes at `pos`
+/// 在 `pos` 处读取最多 `len` 字节
+async fn read_at(file: &Arc<std_fs::File>, pos: u64, len: usize) -> io::Result<Vec<u8>> {
+    #[cfg(unix)]
+    if let Some(reactor) = completion::reactor() {
+        return completion::read_at(&reactor, file.clone(), pos, len).await;
+    }
+    blocking_read_at(file.clone(), pos, len).await
+}
+
+/// Write `data` at `pos`
+/// 在 `pos` 处写入 `data`
+async fn write_at(file: &Arc<std_fs::File>, pos: u64, data: &[u8]) -> io::Result<usize> {
+    #[cfg(unix)]
+    if let Some(reactor) = completion::reactor() {
+        return completion::write_at(&reactor, file.clone(), pos, data).await;
+    }
+    blocking_write_at(file.clone(), pos, data.to_vec()).await
+}
+
+/// [`read_at`] on the blocking pool / 在阻塞线程池上执行的 [`read_at`]
+async fn blocking_read_at(file: Arc<std_fs::File>, pos: u64, len: usize) -> io::Result<Vec<u8>> {
+    asyncify(move || {
+        let mut buf = vec![0u8; len];
+        let n = positional::read_at(&file, &mut buf, pos)?;
+        buf.truncate(n);
+        Ok(buf)
+    })
+    .await
+}
+
+/// [`write_at`] on the blocking pool / 在阻塞线程池上执行的 [`write_at`]
+async fn blocking_write_at(file: Arc<std_fs::File>, pos: u64, data: Vec<u8>) -> io::Result<usize> {
+    asyncify(move || positional::write_at(&file, &data, pos)).await
+}
+
+/// Positional I/O on a shared file handle
+/// 共享文件句柄上的定位I/O
+mod positional {
+    use std::fs::File;
+    use std::io;
+
+    #[cfg(unix)]
+    pub(super) fn read_at(file: &File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(file, buf, pos)
+    }
+
+    #[cfg(unix)]
+    pub(super) fn write_at(file: &File, buf: &[u8], pos: u64) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::write_at(file, buf, pos)
+    }
+
+    #[cfg(windows)]
+    pub(super) fn read_at(file: &File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(file, buf, pos)
+    }
+
+    #[cfg(windows)]
+    pub(super) fn write_at(file: &File, buf: &[u8], pos: u64) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_write(file, buf, pos)
+    }
+}
+
+/// Read the whole file at `path`
+/// 读取 `path` 处的整个文件
+///
+/// # Errors / 错误
+///
+/// Returns an error if the file cannot be opened or read.
+/// 如果无法打开或读取文件则返回错误。
+pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path).await?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Create or truncate the file at `path` and write `contents` to it
+/// 创建或截断 `path` 处的文件并写入 `contents`
+///
+/// # Errors / 错误
+///
+/// Returns an error if the file cannot be created or written.
+/// 如果无法创建或写入文件则返回错误。
+pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut file = File::create(path).await?;
+    file.write_all(contents.as_ref()).await
+}
+
+/// List the entries of the directory at `path`
+/// 列出 `path` 处目录的条目
+///
+/// # Errors / 错误
+///
+/// Returns an error if the directory cannot be read.
+/// 如果无法读取目录则返回错误。
+pub async fn read_dir(path: impl AsRef<Path>) -> io::Result<Vec<DirEntry>> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || std_fs::read_dir(path)?.collect()).await
+}
+
+/// Query the metadata of the file at `path`, following symlinks
+/// 查询 `path` 处文件的元数据，跟随符号链接
+///
+/// # Errors / 错误
+///
+/// Returns an error if the path does not exist.
+/// 如果路径不存在则返回错误。
+pub async fn metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || std_fs::metadata(path)).await
+}
+
+/// Resolve `path` to an absolute path with all symlinks followed
+/// 将 `path` 解析为跟随所有符号链接后的绝对路径
+///
+/// # Errors / 错误
+///
+/// Returns an error if the path does not exist.
+/// 如果路径不存在则返回错误。
+pub async fn canonicalize(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || std_fs::canonicalize(path)).await
+}
+
+/// Rename `from` to `to`, replacing `to` if it exists
+/// 将 `from` 重命名为 `to`，如果 `to` 存在则替换
+///
+/// # Errors / 错误
+///
+/// Returns an error if `from` does not exist or the rename crosses filesystems.
+/// 如果 `from` 不存在或重命名跨越文件系统则返回错误。
+pub async fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    let (from, to) = (from.as_ref().to_owned(), to.as_ref().to_owned());
+    asyncify(move || std_fs::rename(from, to)).await
+}
+
+/// Remove the file at `path`
+/// 删除 `path` 处的文件
+///
+/// # Errors / 错误
+///
+/// Returns an error if the file does not exist or is a directory.
+/// 如果文件不存在或是目录则返回错误。
+pub async fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || std_fs::remove_file(path)).await
+}
+
+/// Send `len` bytes of `file` starting at `offset` to `stream`
+/// 将 `file` 从 `offset` 开始的 `len` 字节发送到 `stream`
+///
+/// On Linux the bytes move inside the kernel with `sendfile(2)`; elsewhere they
+/// are read in chunks and written to the stream. Returns the number of bytes sent,
+/// which is less than `len` if the file ends first. The file cursor is not moved.
+/// 在Linux上数据通过 `sendfile(2)` 在内核中移动；在其他平台上分块读取后写入流。
+/// 返回发送的字节数，如果文件先结束则小于 `len`。不移动文件游标。
+///
+/// # Errors / 错误
+///
+/// Returns the error reported by the transfer.
+/// 返回传输报告的错误。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// use nexus_runtime::fs::{self, File};
+/// use nexus_runtime::io::TcpStream;
+///
+/// async fn serve(stream: &mut TcpStream) -> std::io::Result<()> {
+///     let file = File::open("static/index.html").await?;
+///     let len = file.metadata().await?.len();
+///     fs::sendfile(&file, stream, 0, len).await?;
+///     Ok(())
+/// }
+/// ```
+pub async fn sendfile(
+    file: &File,
+    stream: &mut TcpStream,
+    offset: u64,
+    len: u64,
+) -> io::Result<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        use std::task::Poll;
+
+        use crate::driver::opcode;
+        use crate::driver::reactor::Reactor;
+
+        let mut sent = 0;
+        std::future::poll_fn(|cx| {
+            if stream.poll_write_idle(cx).is_pending() {
+                return Poll::Pending;
+            }
+            while sent < len {
+                let mut off = (offset + sent) as libc::off_t;
+                let count = usize::try_from(len - sent)
+                    .unwrap_or(usize::MAX)
+                    .min(1 << 30);
+                let n = unsafe {
+                    libc::sendfile(stream.as_raw_fd(), file.as_raw_fd(), &mut off, count)
+                };
+                if n < 0 {
+                    let err = io::Error::last_os_error();
+                    match err.kind() {
+                        // Socket buffer full / 套接字缓冲区已满
+                        io::ErrorKind::WouldBlock => {
+                            Reactor::retry_when_ready(stream.as_raw_fd(), opcode::WRITE, cx)?;
+                            return Poll::Pending;
+                        },
+                        io::ErrorKind::Interrupted => continue,
+                        _ => return Poll::Ready(Err(err)),
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+                sent += n as u64;
+            }
+            Poll::Ready(Ok(sent))
+        })
+        .await
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let mut sent = 0;
+        while sent < len {
+            let want = usize::try_from(len - sent)
+                .unwrap_or(usize::MAX)
+                .min(READ_CHUNK);
+            let data = read_at(&file.std, offset + sent, want).await?;
+            if data.is_empty() {
+                break;
+            }
+            stream.write_all(&data).await?;
+            sent += data.len() as u64;
+        }
+        Ok(sent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::block_on;
+
+    /// Fresh scratch directory for a test / 测试用的全新临时目录
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nexus-fs-{}-{}", name, std::process::id()));
+        let _ = std_fs::remove_dir_all(&dir);
+        std_fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_write_seek_read() {
+        let dir = scratch_dir("file");
+        let path = dir.join("data.txt");
+        block_on(async move {
+            let mut file = File::create(&path).await.unwrap();
+            file.write_all(b"hello world").await.unwrap();
+            file.sync_all().await.unwrap();
+
+            let mut file = File::open(&path).await.unwrap();
+            assert_eq!(file.seek(SeekFrom::Start(6)).await.unwrap(), 6);
+            let mut buf = [0u8; 16];
+            let n = file.read(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"world");
+            assert_eq!(file.read(&mut buf).await.unwrap(), 0);
+            assert_eq!(file.read_at(&mut buf[..5], 0).await.unwrap(), 5);
+            assert_eq!(&buf[..5], b"hello");
+
+            assert_eq!(file.seek(SeekFrom::End(-5)).await.unwrap(), 6);
+            assert!(file.seek(SeekFrom::Current(-7)).await.is_err());
+        });
+        std_fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_path_operations() {
+        let dir = scratch_dir("paths");
+        let root = dir.clone();
+        block_on(async move {
+            let contents = vec![7u8; READ_CHUNK * 2 + 3];
+            write(dir.join("a.bin"), &contents).await.unwrap();
+            assert_eq!(read(dir.join("a.bin")).await.unwrap(), contents);
+            assert_eq!(metadata(dir.join("a.bin")).await.unwrap().len(), contents.len() as u64);
+
+            rename(dir.join("a.bin"), dir.join("b.bin")).await.unwrap();
+            let names: Vec<_> = read_dir(&dir)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.file_name())
+                .collect();
+            assert_eq!(names, ["b.bin"]);
+
+            remove_file(dir.join("b.bin")).await.unwrap();
+            let err = metadata(dir.join("b.bin")).await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        });
+        std_fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_file_on_runtime_driver() {
+        let Ok(mut runtime) = crate::Runtime::builder()
+            .driver_type(crate::DriverType::IOUring)
+            .build()
+        else {
+            return;
+        };
+        let dir = scratch_dir("driver");
+        let path = dir.join("data.bin");
+        runtime
+            .block_on(async move {
+                assert!(completion::reactor().is_some());
+                let mut file = File::create(&path).await.unwrap();
+                file.write_all(b"through the ring").await.unwrap();
+                file.sync_all().await.unwrap();
+                assert_eq!(read(&path).await.unwrap(), b"through the ring");
+            })
+            .unwrap();
+        std_fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_blocking_fallback() {
+        let dir = scratch_dir("blocking");
+        let file = Arc::new(std_fs::File::create_new(dir.join("f")).unwrap());
+        let data = block_on(async {
+            blocking_write_at(file.clone(), 2, b"xyz".to_vec())
+                .await
+                .unwrap();
+            blocking_read_at(file, 0, 16).await.unwrap()
+        });
+        assert_eq!(data, b"\0\0xyz");
+        std_fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_sendfile_to_tcp_stream() {
+        let dir = scratch_dir("sendfile");
+        let path = dir.join("page.html");
+        std_fs::write(&path, b"<html>nexus</html>").unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let file_path = path.clone();
+        let sent = block_on(async move {
+            let mut stream = TcpStream::connect(&addr).await.unwrap();
+            let file = File::open(file_path).await.unwrap();
+            sendfile(&file, &mut stream, 6, 100).await.unwrap()
+        });
+        assert_eq!(sent, 12);
+
+        let (mut peer, _) = listener.accept().unwrap();
+        let mut received = String::new();
+        io::Read::read_to_string(&mut peer, &mut received).unwrap();
+        assert_eq!(received, "nexus</html>");
+        std_fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,76 @@
+//! File operations through the runtime's completion driver
+//! 通过运行时的完成型driver进行的文件操作
+//!
+//! # Overview / 概述
+//!
+//! Inside a [`Runtime`](crate::Runtime) whose driver is completion based (io_uring),
+//! reads, writes and syncs are submitted to that driver and complete on the runtime's
+//! event loop. Each operation owns its buffer and a reference to the file, so dropping
+//! the future while the kernel still works on it is safe. Elsewhere [`reactor`]
+//! returns `None` and the caller falls back to the blocking pool.
+//!
+//! 在driver为完成型（io_uring）的 [`Runtime`](crate::Runtime) 中，读取、写入和同步被提交到
+//! 该driver，并在运行时的事件循环上完成。每个操作拥有自己的缓冲区和对文件的引用，因此在
+//! 内核仍在处理时丢弃future是安全的。在其他环境中 [`reactor`] 返回 `None`，调用方回退到
+//! 阻塞线程池。
+
+use std::fs;
+use std::io;
+use std::os::fd::AsRawFd;
+use std::sync::Arc;
+
+use crate::driver::reactor::{MAX_IO_LEN, Reactor};
+use crate::driver::{SubmitEntry, opcode};
+
+/// The current runtime's reactor, if its driver completes file operations
+/// 当前运行时的反应器（如果其driver能完成文件操作）
+pub(super) fn reactor() -> Option<Arc<Reactor>> {
+    Reactor::completion(&[opcode::READ, opcode::WRITE, opcode::FSYNC])
+}
+
+/// Read up to `len` bytes at `pos`
+/// 在 `pos` 处读取最多 `len` 字节
+pub(super) async fn read_at(
+    reactor: &Arc<Reactor>,
+    file: Arc<fs::File>,
+    pos: u64,
+    len: usize,
+) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; len.min(MAX_IO_LEN)];
+    // SAFETY: the buffer moves into the reactor, which keeps its heap allocation
+    // and the file alive until the completion arrives
+    // 安全性：缓冲区移入反应器，反应器在完成事件到达前保持其堆内存和文件存活
+    let mut entry =
+        unsafe { SubmitEntry::read(file.as_raw_fd(), buf.as_mut_ptr(), buf.len() as u32, 0) };
+    entry.offset = pos;
+    let (n, mut buf) = unsafe { reactor.submit(entry, buf, Some(Box::new(file))) }?.await?;
+    buf.truncate(n);
+    Ok(buf)
+}
+
+/// Write `data` at `pos`, returning the number of bytes written
+/// 在 `pos` 处写入 `data`，返回写入的字节数
+pub(super) async fn write_at(
+    reactor: &Arc<Reactor>,
+    file: Arc<fs::File>,
+    pos: u64,
+    data: &[u8],
+) -> io::Result<usize> {
+    let buf = data[..data.len().min(MAX_IO_LEN)].to_vec();
+    // SAFETY: see `read_at` / 安全性：见 `read_at`
+    let mut entry =
+        unsafe { SubmitEntry::write(file.as_raw_fd(), buf.as_ptr(), buf.len() as u32, 0) };
+    entry.offset = pos;
+    let (n, _) = unsafe { reactor.submit(entry, buf, Some(Box::new(file))) }?.await?;
+    Ok(n)
+}
+
+/// Flush data and metadata to disk
+/// 将数据和元数据刷新到磁盘
+pub(super) async fn fsync(reactor: &Arc<Reactor>, file: Arc<fs::File>) -> io::Result<()> {
+    let entry = SubmitEntry::fsync(file.as_raw_fd(), 0);
+    // SAFETY: fsync uses no buffer / 安全性：fsync不使用缓冲区
+    unsafe { reactor.submit(entry, Vec::new(), Some(Box::new(file))) }?
+        .await
+        .map(drop)
+}
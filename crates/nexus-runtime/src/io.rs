@@ -3,15 +3,28 @@
 //!
 //! # Overview / 概述
 //!
-//! This module provides async I/O primitives for TCP and UDP networking.
-//! 本模块提供用于TCP和UDP网络的异步I/O原语。
+//! This module provides async I/O primitives for TCP, UDP and Unix domain networking.
+//! 本模块提供用于TCP、UDP和Unix域网络的异步I/O原语。
 //!
 //! # Features / 功能
 //!
 //! - Async TCP stream with connect/read/write / 带有connect/read/write的异步TCP流
 //! - Async TCP listener for accepting connections / 用于接受连接的异步TCP监听器
+//! - Unix domain streams and listeners / Unix域流和监听器
+//! - Sockets inherited through `LISTEN_FDS` / 通过 `LISTEN_FDS` 继承的套接字
 //! - Zero-copy ready operations / 零拷贝就绪操作
 //!
+//! Socket I/O goes through the driver of the runtime running the task. With a
+//! completion driver (io_uring) the kernel performs accept, receive and send itself;
+//! accepts and receives stay armed once started, and receives draw their buffers
+//! from a ring shared by all sockets. With a readiness driver (epoll, kqueue) a task
+//! that would block is woken once its socket is ready; outside a runtime a shared
+//! reactor thread waits for it.
+//! 套接字I/O通过运行该任务的运行时的driver进行。使用完成型driver（io_uring）时，
+//! 由内核自行执行accept、接收和发送；accept和接收一旦启动便保持有效，接收从所有套接字
+//! 共享的环中获取缓冲区。使用就绪型driver（epoll、kqueue）时，会阻塞的任务在其套接字
+//! 就绪时被唤醒；在运行时之外由共享的反应器线程代为等待。
+//!
 //! # Example / 示例
 //!
 //! ```rust,no_run,ignore
@@ -32,19 +45,36 @@
 
 #![allow(private_interfaces)]
 
+#[cfg(unix)]
+mod listen_fds;
+#[cfg(unix)]
+mod unix;
+
+#[cfg(unix)]
+pub use listen_fds::{LISTEN_FDS_START, ListenFd, listen_fds};
+#[cfg(unix)]
+pub use unix::{UnixAcceptFuture, UnixListener, UnixStream};
+
 use std::future::Future;
 use std::io;
-use std::net::{Shutdown, SocketAddr};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::os::fd::{AsRawFd, FromRawFd, RawFd};
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
+use crate::driver::SubmitEntry;
+use crate::driver::opcode;
+use crate::driver::reactor::{Completion, MAX_IO_LEN, Multishot, Reactor};
+
 /// A TCP stream between a local and a remote socket
 /// 本地套接字和远程套接字之间的TCP流
 ///
 /// Provides async read/write operations with the underlying driver.
 /// 使用底层驱动提供异步读/写操作。
 pub struct TcpStream {
+    /// Operations in flight, cancelled before the descriptor closes
+    /// 进行中的操作，在描述符关闭前取消
+    ops: SocketOps,
     /// The raw file descriptor / 原始文件描述符
     fd: std::os::fd::OwnedFd,
     /// Whether this stream is in non-blocking mode / 此流是否处于非阻塞模式
@@ -75,6 +105,7 @@ impl TcpStream {
         }
 
         Ok(Self {
+            ops: SocketOps::default(),
             // SAFETY: Caller guarantees ownership
             // 安全性：调用者保证所有权
             fd: unsafe { std::os::fd::OwnedFd::from_raw_fd(fd) },
@@ -122,9 +153,9 @@ impl TcpStream {
     /// 返回读取的字节数。如果流已关闭，可能返回0。
     pub fn read<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> ReadFuture<'a, 'b> {
         ReadFuture {
-            stream: Some(self),
+            fd: self.fd.as_raw_fd(),
+            ops: &mut self.ops,
             buf,
-            pos: 0,
         }
     }
 
@@ -135,12 +166,23 @@ impl TcpStream {
     /// 将持续写入，直到所有字节都已写入或发生错误。
     pub fn write_all<'a, 'b>(&'a mut self, buf: &'b [u8]) -> WriteAllFuture<'a, 'b> {
         WriteAllFuture {
-            stream: Some(self),
+            fd: self.fd.as_raw_fd(),
+            ops: &mut self.ops,
             buf,
             pos: 0,
+            sending: false,
         }
     }
 
+    /// Wait until a send left by a dropped [`write_all`](Self::write_all) has finished
+    /// 等待被丢弃的 [`write_all`](Self::write_all) 留下的发送完成
+    ///
+    /// Writes that bypass [`WriteAllFuture`] call this first to keep the byte order.
+    /// 绕过 [`WriteAllFuture`] 的写入需先调用此方法以保持字节顺序。
+    pub(crate) fn poll_write_idle(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.ops.poll_write_idle(cx)
+    }
+
     /// Split the stream into read and write halves
     /// 将流拆分为读写两半
     ///
@@ -180,6 +222,20 @@ impl TcpStream {
         }
         Ok(())
     }
+
+    /// Get the local address
+    /// 获取本地地址
+    #[cfg(unix)]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        socket_name(self.as_raw_fd(), libc::getsockname)
+    }
+
+    /// Get the peer address
+    /// 获取对端地址
+    #[cfg(unix)]
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        socket_name(self.as_raw_fd(), libc::getpeername)
+    }
 }
 
 impl AsRawFd for TcpStream {
@@ -209,7 +265,7 @@ struct ConnectingState {
 impl Future for ConnectFuture {
     type Output = io::Result<TcpStream>;
 
-    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         match &mut *self {
             ConnectFuture::Error(e) => {
                 let e = std::mem::replace(e, io::Error::new(io::ErrorKind::Other, ""));
@@ -234,23 +290,41 @@ impl Future for ConnectFuture {
 
                     if result < 0 {
                         let err = io::Error::last_os_error();
-                        if err.kind() != io::ErrorKind::WouldBlock {
+                        let in_progress = err.kind() == io::ErrorKind::WouldBlock
+                            || err.raw_os_error() == Some(libc::EINPROGRESS);
+                        if !in_progress {
                             unsafe { libc::close(fd) };
                             return Poll::Ready(Err(err));
                         }
                         // Async connect in progress
                         // 异步connect进行中
                         state.fd = Some(fd);
+                        Reactor::retry_when_ready(fd, opcode::WRITE, cx)?;
                         return Poll::Pending;
                     }
 
                     // Connected immediately
                     // 立即连接
                     state.fd = Some(fd);
+                } else if let Some(fd) = state.fd {
+                    // Check whether the connect in progress has finished
+                    // 检查进行中的connect是否已完成
+                    match connect_result(fd) {
+                        Ok(true) => {},
+                        Ok(false) => {
+                            Reactor::retry_when_ready(fd, opcode::WRITE, cx)?;
+                            return Poll::Pending;
+                        },
+                        Err(e) => {
+                            state.fd = None;
+                            unsafe { libc::close(fd) };
+                            return Poll::Ready(Err(e));
+                        },
+                    }
                 }
 
-                // Check if connected
-                // 检查是否已连接
+                // Connected
+                // 已连接
                 if let Some(fd) = state.fd.take() {
                     // SAFETY: fd is valid and owned
                     // 安全性：fd有效且拥有所有权
@@ -268,6 +342,104 @@ impl Future for ConnectFuture {
     }
 }
 
+impl Drop for ConnectingState {
+    fn drop(&mut self) {
+        // Close the socket of a connect that was abandoned, e.g. on timeout
+        // 关闭被放弃的connect的套接字，例如超时
+        if let Some(fd) = self.fd.take() {
+            unsafe { libc::close(fd) };
+        }
+    }
+}
+
+/// Check whether a non-blocking connect has finished, returning its error if it failed
+/// 检查非阻塞connect是否已完成，失败时返回其错误
+#[cfg(unix)]
+fn connect_result(fd: RawFd) -> io::Result<bool> {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLOUT,
+        revents: 0,
+    };
+    let ready = unsafe { libc::poll(&mut pollfd, 1, 0) };
+    if ready < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if ready == 0 {
+        return Ok(false);
+    }
+
+    let mut error: libc::c_int = 0;
+    let mut len = size_of::<libc::c_int>() as libc::socklen_t;
+    let result = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_ERROR,
+            &mut error as *mut libc::c_int as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if error != 0 {
+        return Err(io::Error::from_raw_os_error(error));
+    }
+    Ok(true)
+}
+
+/// Convert a socket address the kernel filled in
+/// 转换内核填写的套接字地址
+#[cfg(unix)]
+fn to_socket_addr(addr: &libc::sockaddr_storage, len: libc::socklen_t) -> io::Result<SocketAddr> {
+    let len = len as usize;
+    match addr.ss_family as libc::c_int {
+        libc::AF_INET if len >= size_of::<libc::sockaddr_in>() => {
+            // SAFETY: the family and length say the storage holds a sockaddr_in
+            // 安全性：地址族和长度表明存储中是sockaddr_in
+            let v4 =
+                unsafe { &*(addr as *const libc::sockaddr_storage as *const libc::sockaddr_in) };
+            Ok(SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::from(u32::from_be(v4.sin_addr.s_addr)),
+                u16::from_be(v4.sin_port),
+            )))
+        },
+        libc::AF_INET6 if len >= size_of::<libc::sockaddr_in6>() => {
+            // SAFETY: the family and length say the storage holds a sockaddr_in6
+            // 安全性：地址族和长度表明存储中是sockaddr_in6
+            let v6 =
+                unsafe { &*(addr as *const libc::sockaddr_storage as *const libc::sockaddr_in6) };
+            Ok(SocketAddr::V6(SocketAddrV6::new(
+                Ipv6Addr::from(v6.sin6_addr.s6_addr),
+                u16::from_be(v6.sin6_port),
+                v6.sin6_flowinfo,
+                v6.sin6_scope_id,
+            )))
+        },
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "unsupported address family")),
+    }
+}
+
+/// Ask the kernel for an address of a socket, `getsockname` or `getpeername`
+/// 向内核查询套接字的地址，`getsockname` 或 `getpeername`
+#[cfg(unix)]
+fn socket_name(
+    fd: RawFd,
+    query: unsafe extern "C" fn(
+        libc::c_int,
+        *mut libc::sockaddr,
+        *mut libc::socklen_t,
+    ) -> libc::c_int,
+) -> io::Result<SocketAddr> {
+    let mut addr: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+    let mut len = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    if unsafe { query(fd, &mut addr as *mut _ as *mut libc::sockaddr, &mut len) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    to_socket_addr(&addr, len)
+}
+
 /// Helper to create a non-blocking socket
 /// 创建非阻塞套接字的辅助函数
 #[cfg(unix)]
@@ -378,96 +550,198 @@ fn do_connect(fd: RawFd, addr: SocketAddr) -> i32 {
     }
 }
 
-/// Future for reading from a TcpStream
-/// 从TcpStream读取的future
-pub struct ReadFuture<'a, 'b> {
-    stream: Option<&'a mut TcpStream>,
-    buf: &'b mut [u8],
-    pos: usize,
+/// Completion-based operations a socket has in flight
+/// 套接字进行中的基于完成的操作
+///
+/// They belong to the socket rather than to the future that started them: a read
+/// future dropped mid-flight, e.g. by a timeout, leaves its bytes to the next read.
+/// 它们属于套接字而不是启动它们的future：中途被丢弃（例如因超时）的读取future会把
+/// 其字节留给下一次读取。
+#[derive(Debug, Default)]
+pub(crate) struct SocketOps {
+    /// Pending receive / 挂起的接收
+    read: Option<Completion>,
+    /// Received bytes the last read had no room for / 上次读取放不下的已接收字节
+    unread: Vec<u8>,
+    /// Pending send / 挂起的发送
+    write: Option<Completion>,
+    /// Pending accept / 挂起的accept
+    accept: Option<Completion>,
+    /// Receive that stays armed, drawing buffers from the reactor's ring
+    /// 保持有效的接收，从反应器的环中获取缓冲区
+    recv: Option<Multishot>,
+    /// Accept that stays armed, queueing connections / 保持有效的accept，将连接排队
+    accepts: Option<Multishot>,
 }
 
-impl Future for ReadFuture<'_, '_> {
-    type Output = io::Result<usize>;
+impl SocketOps {
+    /// Read into `buf`, through the completion driver when one is active
+    /// 读取到 `buf`，有活动的完成型driver时通过它进行
+    fn poll_read(
+        &mut self,
+        fd: RawFd,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if !self.unread.is_empty() {
+            let n = buf.len().min(self.unread.len());
+            buf[..n].copy_from_slice(&self.unread[..n]);
+            self.unread.drain(..n);
+            return Poll::Ready(Ok(n));
+        }
 
-    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
-        // Extract all needed values upfront to avoid borrow issues
-        // 提前提取所有需要的值以避免借用问题
-        let stream_fd;
-        let buf_ptr;
-        let buf_len;
+        #[cfg(target_os = "linux")]
+        if self.read.is_none()
+            && !buf.is_empty()
+            && let Some(reactor) = Reactor::completion(&[opcode::RECV])
+            && let Some(ring) = reactor.recv_ring()
+        {
+            loop {
+                if self.recv.is_none() {
+                    // SAFETY: the kernel picks the buffer from the ring
+                    // 安全性：内核从环中选择缓冲区
+                    let entry = unsafe { SubmitEntry::recv(fd, std::ptr::null_mut(), 0, 0) }
+                        .with_buffer_group(crate::driver::reactor::RECV_GROUP);
+                    self.recv = Some(reactor.submit_multishot(entry)?);
+                }
+                let Some(recv) = &mut self.recv else {
+                    continue;
+                };
+                let Poll::Ready(next) = recv.poll_next(cx) else {
+                    return Poll::Pending;
+                };
+                let Some(completion) = next else {
+                    self.recv = None;
+                    continue;
+                };
+                if !completion.has_more() {
+                    self.recv = None;
+                }
+                if completion.result < 0 {
+                    match -completion.result {
+                        // Ring exhausted: this read brings its own buffer
+                        // 环已耗尽：本次读取使用自己的缓冲区
+                        libc::ENOBUFS => break,
+                        // Kernel without multishot receive / 内核不支持多次触发接收
+                        libc::EINVAL => {
+                            reactor.disable_multishot();
+                            break;
+                        },
+                        errno => return Poll::Ready(Err(io::Error::from_raw_os_error(errno))),
+                    }
+                }
+                let Some(data) = ring.take(&completion) else {
+                    return Poll::Ready(Ok(0));
+                };
+                let n = data.len().min(buf.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                self.unread.extend_from_slice(&data[n..]);
+                return Poll::Ready(Ok(n));
+            }
+        }
 
+        if self.read.is_none()
+            && !buf.is_empty()
+            && let Some(reactor) = Reactor::completion(&[opcode::RECV])
         {
-            let stream = self.stream.as_mut().unwrap();
-            stream_fd = stream.as_raw_fd();
-            let pos = self.pos;
-            buf_ptr = self.buf[pos..].as_mut_ptr();
-            buf_len = self.buf[pos..].len();
+            let mut data = vec![0u8; buf.len().min(MAX_IO_LEN)];
+            // SAFETY: the entry points into `data`, which moves into the reactor
+            // 安全性：条目指向 `data`，而 `data` 被移入反应器
+            let entry = unsafe { SubmitEntry::recv(fd, data.as_mut_ptr(), data.len() as u32, 0) };
+            self.read = Some(unsafe { reactor.submit(entry, data, None) }?);
+        }
+        if let Some(op) = &mut self.read {
+            let Poll::Ready(result) = Pin::new(op).poll(cx) else {
+                return Poll::Pending;
+            };
+            self.read = None;
+            let (n, mut data) = result?;
+            data.truncate(n);
+            let copied = n.min(buf.len());
+            buf[..copied].copy_from_slice(&data[..copied]);
+            data.drain(..copied);
+            self.unread = data;
+            return Poll::Ready(Ok(copied));
         }
 
         #[cfg(unix)]
         {
-            let result = unsafe { libc::read(stream_fd, buf_ptr as *mut _, buf_len) };
+            let result = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut _, buf.len()) };
 
             if result < 0 {
                 let err = io::Error::last_os_error();
                 if err.kind() == io::ErrorKind::WouldBlock {
-                    // Would block - should register with driver
-                    // 会阻塞 - 应该向驱动注册
-                    // For now, just return Pending
-                    // 目前只返回Pending
+                    Reactor::retry_when_ready(fd, opcode::READ, cx)?;
                     return Poll::Pending;
                 }
                 return Poll::Ready(Err(err));
             }
 
-            let n = result as usize;
-            if n == 0 {
-                return Poll::Ready(Ok(0)); // EOF
-            }
-
-            self.pos += n;
-            Poll::Ready(Ok(n))
+            Poll::Ready(Ok(result as usize))
         }
 
         #[cfg(not(unix))]
         {
-            let _ = (stream_fd, buf_ptr, buf_len);
             Poll::Ready(Err(io::Error::new(
                 io::ErrorKind::Unsupported,
                 "TCP read not yet implemented on this platform",
             )))
         }
     }
-}
 
-/// Future for writing all bytes to a TcpStream
-/// 向TcpStream写入所有字节的future
-pub struct WriteAllFuture<'a, 'b> {
-    stream: Option<&'a mut TcpStream>,
-    buf: &'b [u8],
-    pos: usize,
-}
-
-impl Future for WriteAllFuture<'_, '_> {
-    type Output = io::Result<()>;
+    /// Write all of `buf` from `pos` on; `sending` tells whether the pending send is
+    /// the caller's own rather than one left by a dropped write
+    /// 从 `pos` 开始写入 `buf` 的全部内容；`sending` 表示挂起的发送是否属于调用者自己，
+    /// 而不是被丢弃的写入留下的
+    fn poll_write_all(
+        &mut self,
+        fd: RawFd,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        pos: &mut usize,
+        sending: &mut bool,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            if let Some(op) = &mut self.write {
+                let Poll::Ready(result) = Pin::new(op).poll(cx) else {
+                    return Poll::Pending;
+                };
+                self.write = None;
+                if !std::mem::take(sending) {
+                    continue;
+                }
+                let (n, _) = result?;
+                if n == 0 {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "write zero byte",
+                    )));
+                }
+                *pos += n;
+                continue;
+            }
+            if *pos >= buf.len() {
+                return Poll::Ready(Ok(()));
+            }
 
-    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
-        while self.pos < self.buf.len() {
-            let stream = self.stream.as_mut().unwrap();
+            if let Some(reactor) = Reactor::completion(&[opcode::SEND]) {
+                let data = buf[*pos..buf.len().min(*pos + MAX_IO_LEN)].to_vec();
+                // SAFETY: see `poll_read` / 安全性：见 `poll_read`
+                let entry = unsafe { SubmitEntry::send(fd, data.as_ptr(), data.len() as u32, 0) };
+                self.write = Some(unsafe { reactor.submit(entry, data, None) }?);
+                *sending = true;
+                continue;
+            }
 
             #[cfg(unix)]
             {
-                let result = unsafe {
-                    libc::write(
-                        stream.as_raw_fd(),
-                        self.buf[self.pos..].as_ptr() as *const _,
-                        self.buf[self.pos..].len(),
-                    )
-                };
+                let result =
+                    unsafe { libc::write(fd, buf[*pos..].as_ptr() as *const _, buf[*pos..].len()) };
 
                 if result < 0 {
                     let err = io::Error::last_os_error();
                     if err.kind() == io::ErrorKind::WouldBlock {
+                        Reactor::retry_when_ready(fd, opcode::WRITE, cx)?;
                         return Poll::Pending;
                     }
                     return Poll::Ready(Err(err));
@@ -481,20 +755,78 @@ impl Future for WriteAllFuture<'_, '_> {
                     )));
                 }
 
-                self.pos += n;
+                *pos += n;
             }
 
             #[cfg(not(unix))]
             {
-                let _ = stream;
                 return Poll::Ready(Err(io::Error::new(
                     io::ErrorKind::Unsupported,
                     "TCP write not yet implemented on this platform",
                 )));
             }
         }
+    }
 
-        Poll::Ready(Ok(()))
+    /// Wait until a send left by a dropped write has finished
+    /// 等待被丢弃的写入留下的发送完成
+    fn poll_write_idle(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(op) = &mut self.write {
+            if Pin::new(op).poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            self.write = None;
+        }
+        Poll::Ready(())
+    }
+}
+
+impl Drop for SocketOps {
+    fn drop(&mut self) {
+        for op in [&self.read, &self.write, &self.accept]
+            .into_iter()
+            .flatten()
+        {
+            op.cancel();
+        }
+    }
+}
+
+/// Future for reading from a TcpStream or UnixStream
+/// 从TcpStream或UnixStream读取的future
+pub struct ReadFuture<'a, 'b> {
+    fd: RawFd,
+    ops: &'a mut SocketOps,
+    buf: &'b mut [u8],
+}
+
+impl Future for ReadFuture<'_, '_> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.ops.poll_read(this.fd, cx, this.buf)
+    }
+}
+
+/// Future for writing all bytes to a TcpStream or UnixStream
+/// 向TcpStream或UnixStream写入所有字节的future
+pub struct WriteAllFuture<'a, 'b> {
+    fd: RawFd,
+    ops: &'a mut SocketOps,
+    buf: &'b [u8],
+    pos: usize,
+    /// The pending send is this future's / 挂起的发送属于此future
+    sending: bool,
+}
+
+impl Future for WriteAllFuture<'_, '_> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.ops
+            .poll_write_all(this.fd, cx, this.buf, &mut this.pos, &mut this.sending)
     }
 }
 
@@ -516,6 +848,9 @@ pub struct WriteHalf<'a> {
 /// Listens for incoming connections on a specific address.
 /// 在特定地址上监听传入的连接。
 pub struct TcpListener {
+    /// Accept in flight, cancelled before the descriptor closes
+    /// 进行中的accept，在描述符关闭前取消
+    ops: SocketOps,
     /// The raw file descriptor / 原始文件描述符
     fd: std::os::fd::OwnedFd,
 }
@@ -549,6 +884,20 @@ impl TcpListener {
         BindFuture::Binding(BindingState { addr })
     }
 
+    /// Wrap a standard library listener, switching it to non-blocking mode
+    /// 包装标准库监听器，并将其切换为非阻塞模式
+    ///
+    /// Use this for a socket that is already listening, such as one inherited from
+    /// the parent process (see [`listen_fds`]).
+    /// 用于已处于监听状态的套接字，例如从父进程继承的套接字（参见 [`listen_fds`]）。
+    pub fn from_std(listener: std::net::TcpListener) -> io::Result<Self> {
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            ops: SocketOps::default(),
+            fd: listener.into(),
+        })
+    }
+
     /// Accept a new connection
     /// 接受新连接
     pub fn accept(&mut self) -> AcceptFuture<'_> {
@@ -559,22 +908,8 @@ impl TcpListener {
     /// 获取本地地址
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
         #[cfg(unix)]
-        unsafe {
-            let mut addr: libc::sockaddr_storage = std::mem::zeroed();
-            let mut len = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
-
-            if libc::getsockname(
-                self.as_raw_fd(),
-                &mut addr as *mut _ as *mut libc::sockaddr,
-                &mut len,
-            ) < 0
-            {
-                return Err(io::Error::last_os_error());
-            }
-
-            // Convert to SocketAddr (simplified)
-            // 转换为SocketAddr（简化版）
-            Ok("0.0.0.0:0".parse().unwrap())
+        {
+            socket_name(self.as_raw_fd(), libc::getsockname)
         }
 
         #[cfg(not(unix))]
@@ -662,6 +997,7 @@ impl Future for BindFuture {
                     }
 
                     let listener = TcpListener {
+                        ops: SocketOps::default(),
                         // SAFETY: fd is valid and owned
                         fd: std::os::fd::OwnedFd::from_raw_fd(fd),
                     };
@@ -765,7 +1101,60 @@ impl Future for AcceptFuture<'_> {
     type Output = io::Result<(TcpStream, SocketAddr)>;
 
     #[allow(unused_mut)]
-    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let listener = &mut *self.listener;
+        if listener.ops.accept.is_none()
+            && let Some(reactor) = Reactor::completion(&[opcode::ACCEPT])
+            && reactor.multishot()
+        {
+            let fd = listener.as_raw_fd();
+            loop {
+                if listener.ops.accepts.is_none() {
+                    let accepts = reactor.submit_multishot(SubmitEntry::accept(fd, 0))?;
+                    listener.ops.accepts = Some(accepts);
+                }
+                let Some(accepts) = &mut listener.ops.accepts else {
+                    continue;
+                };
+                let Poll::Ready(next) = accepts.poll_next(cx) else {
+                    return Poll::Pending;
+                };
+                let Some(completion) = next else {
+                    listener.ops.accepts = None;
+                    continue;
+                };
+                if !completion.has_more() {
+                    listener.ops.accepts = None;
+                    // Kernel without multishot accept / 内核不支持多次触发accept
+                    if completion.result == -libc::EINVAL {
+                        reactor.disable_multishot();
+                        break;
+                    }
+                }
+                let fd = completion.into_result()?;
+                let stream = unsafe { TcpStream::from_raw_fd(fd as RawFd) }?;
+                let peer_addr = stream.peer_addr()?;
+                return Poll::Ready(Ok((stream, peer_addr)));
+            }
+        }
+        if listener.ops.accept.is_none()
+            && let Some(reactor) = Reactor::completion(&[opcode::ACCEPT])
+        {
+            let entry = SubmitEntry::accept(listener.as_raw_fd(), 0);
+            // SAFETY: accept uses no buffer / 安全性：accept不使用缓冲区
+            listener.ops.accept = Some(unsafe { reactor.submit(entry, Vec::new(), None) }?);
+        }
+        if let Some(op) = &mut listener.ops.accept {
+            let Poll::Ready(result) = Pin::new(op).poll(cx) else {
+                return Poll::Pending;
+            };
+            listener.ops.accept = None;
+            let (fd, _) = result?;
+            let stream = unsafe { TcpStream::from_raw_fd(fd as RawFd) }?;
+            let peer_addr = stream.peer_addr()?;
+            return Poll::Ready(Ok((stream, peer_addr)));
+        }
+
         #[cfg(unix)]
         {
             let mut addr: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
@@ -813,6 +1202,7 @@ impl Future for AcceptFuture<'_> {
             if fd < 0 {
                 let err = io::Error::last_os_error();
                 if err.kind() == io::ErrorKind::WouldBlock {
+                    Reactor::retry_when_ready(self.listener.as_raw_fd(), opcode::READ, cx)?;
                     return Poll::Pending;
                 }
                 return Poll::Ready(Err(err));
@@ -823,11 +1213,9 @@ impl Future for AcceptFuture<'_> {
                 Err(e) => return Poll::Ready(Err(e)),
             };
 
-            // Parse peer address (simplified)
-            // 解析对端地址（简化版）
-            let peer_addr = match self.listener.local_addr() {
-                Ok(_) => "0.0.0.0:0".parse().unwrap(),
-                Err(_) => return Poll::Ready(Err(io::Error::last_os_error())),
+            let peer_addr = match to_socket_addr(&addr, len) {
+                Ok(addr) => addr,
+                Err(e) => return Poll::Ready(Err(e)),
             };
 
             Poll::Ready(Ok((stream, peer_addr)))
@@ -1117,7 +1505,7 @@ pub struct RecvFromFuture<'a, 'b> {
 impl Future for RecvFromFuture<'_, '_> {
     type Output = io::Result<(usize, SocketAddr)>;
 
-    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         // Extract all needed values upfront to avoid borrow issues
         // 提前提取所有需要的值以避免借用问题
         let stream_fd;
@@ -1150,6 +1538,7 @@ impl Future for RecvFromFuture<'_, '_> {
             if result < 0 {
                 let err = io::Error::last_os_error();
                 if err.kind() == io::ErrorKind::WouldBlock {
+                    Reactor::retry_when_ready(stream_fd, opcode::READ, cx)?;
                     return Poll::Pending;
                 }
                 return Poll::Ready(Err(err));
@@ -1157,12 +1546,10 @@ impl Future for RecvFromFuture<'_, '_> {
 
             let n = result as usize;
 
-            // Parse peer address (simplified)
-            // 解析对端地址（简化版）
-            let peer_addr = SocketAddr::V4(std::net::SocketAddrV4::new(
-                std::net::Ipv4Addr::new(127, 0, 0, 1),
-                0,
-            ));
+            let peer_addr = match to_socket_addr(&addr, addr_len) {
+                Ok(addr) => addr,
+                Err(e) => return Poll::Ready(Err(e)),
+            };
 
             Poll::Ready(Ok((n, peer_addr)))
         }
@@ -1188,7 +1575,7 @@ pub struct SendToFuture<'a, 'b> {
 impl Future for SendToFuture<'_, '_> {
     type Output = io::Result<usize>;
 
-    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let stream = self.stream.as_mut().unwrap();
         let stream_fd = stream.as_raw_fd();
 
@@ -1202,6 +1589,7 @@ impl Future for SendToFuture<'_, '_> {
             if result < 0 {
                 let err = io::Error::last_os_error();
                 if err.kind() == io::ErrorKind::WouldBlock {
+                    Reactor::retry_when_ready(stream_fd, opcode::WRITE, cx)?;
                     return Poll::Pending;
                 }
                 return Poll::Ready(Err(err));
@@ -1355,4 +1743,157 @@ mod tests {
             _ => panic!("Expected Error future for invalid address"),
         }
     }
+
+    #[test]
+    fn test_connect_reports_outcome() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let connected = crate::task::block_on(async move { TcpStream::connect(&addr).await });
+        assert!(connected.is_ok());
+
+        // Nothing listens on the port once the listener is gone
+        // 监听器关闭后该端口上没有任何监听
+        let addr = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        let refused = crate::task::block_on(async move { TcpStream::connect(&addr).await });
+        assert!(refused.is_err());
+    }
+
+    /// Accept one connection on `driver`, read "hello world" and echo it back
+    /// 在 `driver` 上接受一个连接，读取 "hello world" 并回显
+    fn echo_on(driver: crate::DriverType) {
+        use std::io::{Read, Write};
+
+        let Ok(mut runtime) = crate::Runtime::builder().driver_type(driver).build() else {
+            return;
+        };
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = std_listener.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream.write_all(b"hello world").unwrap();
+            let mut echoed = [0u8; 11];
+            stream.read_exact(&mut echoed).unwrap();
+            echoed
+        });
+
+        let mut listener = TcpListener::from_std(std_listener).unwrap();
+        runtime
+            .block_on(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+
+                // A read dropped before completing leaves its bytes to the next reads
+                // 完成前被丢弃的读取会把其字节留给后续读取
+                let mut received = Vec::new();
+                let mut buf = [0u8; 64];
+                let first = {
+                    let mut read = stream.read(&mut buf);
+                    std::future::poll_fn(|cx| Poll::Ready(Pin::new(&mut read).poll(cx))).await
+                };
+                if let Poll::Ready(n) = first {
+                    received.extend_from_slice(&buf[..n.unwrap()]);
+                }
+
+                while received.len() < 11 {
+                    let mut chunk = [0u8; 5];
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    assert!(n > 0);
+                    received.extend_from_slice(&chunk[..n]);
+                }
+                assert_eq!(received, b"hello world");
+                stream.write_all(&received).await.unwrap();
+            })
+            .unwrap();
+        assert_eq!(&client.join().unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn test_completion_driver_echo() {
+        echo_on(crate::DriverType::IOUring);
+    }
+
+    #[test]
+    fn test_readiness_driver_echo() {
+        echo_on(crate::DriverType::Epoll);
+    }
+
+    /// Accept one connection on `driver` and check the addresses it reports
+    /// 在 `driver` 上接受一个连接并检查其报告的地址
+    fn addresses_on(driver: crate::DriverType) {
+        let Ok(mut runtime) = crate::Runtime::builder().driver_type(driver).build() else {
+            return;
+        };
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = std_listener.local_addr().unwrap();
+        let mut listener = TcpListener::from_std(std_listener).unwrap();
+        assert_eq!(listener.local_addr().unwrap(), addr);
+
+        let client = std::net::TcpStream::connect(addr).unwrap();
+        runtime
+            .block_on(async move {
+                let (stream, peer_addr) = listener.accept().await.unwrap();
+                assert_eq!(peer_addr, client.local_addr().unwrap());
+                assert_eq!(stream.peer_addr().unwrap(), peer_addr);
+                assert_eq!(stream.local_addr().unwrap(), addr);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_completion_driver_addresses() {
+        addresses_on(crate::DriverType::IOUring);
+    }
+
+    #[test]
+    fn test_readiness_driver_addresses() {
+        addresses_on(crate::DriverType::Epoll);
+    }
+
+    #[test]
+    fn test_completion_driver_multishot() {
+        use std::io::Write;
+
+        let Ok(mut runtime) = crate::Runtime::builder()
+            .driver_type(crate::DriverType::IOUring)
+            .build()
+        else {
+            return;
+        };
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = std_listener.local_addr().unwrap();
+        let payload: Vec<u8> = (0..20_000u32).map(|i| i as u8).collect();
+        let sent = payload.clone();
+        let client = std::thread::spawn(move || {
+            let mut first = std::net::TcpStream::connect(addr).unwrap();
+            let second = std::net::TcpStream::connect(addr).unwrap();
+            first.write_all(&sent).unwrap();
+            second
+        });
+
+        let mut listener = TcpListener::from_std(std_listener).unwrap();
+        runtime
+            .block_on(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                if listener.ops.accepts.is_none() {
+                    // Kernel predates multishot accept / 内核早于多次触发accept
+                    return;
+                }
+                // The second connection comes from the same armed accept
+                // 第二个连接来自同一个保持有效的accept
+                listener.accept().await.unwrap();
+                assert!(listener.ops.accepts.is_some());
+
+                let mut received = Vec::new();
+                let mut buf = [0u8; 1000];
+                while received.len() < payload.len() {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    assert!(n > 0);
+                    received.extend_from_slice(&buf[..n]);
+                }
+                assert_eq!(received, payload);
+                assert!(stream.ops.recv.is_some(), "receive did not stay armed");
+            })
+            .unwrap();
+        drop(client.join().unwrap());
+    }
 }
@@ -0,0 +1,220 @@
+//! Inherited listening sockets (systemd socket activation)
+//! 继承的监听套接字（systemd套接字激活）
+//!
+//! # Overview / 概述
+//!
+//! A service manager such as systemd, or a parent process handing over its sockets
+//! during a zero-downtime restart, opens the listening sockets itself and passes
+//! them to the new process as file descriptors starting at 3. The environment
+//! describes them:
+//!
+//! - `LISTEN_PID` - the process the sockets are meant for
+//! - `LISTEN_FDS` - how many descriptors were passed
+//! - `LISTEN_FDNAMES` - optional colon-separated names
+//!
+//! systemd等服务管理器，或在零停机重启期间移交套接字的父进程，会自行打开监听套接字，
+//! 并将其作为从3开始的文件描述符传给新进程。环境变量描述了这些套接字：
+//!
+//! - `LISTEN_PID` - 套接字所属的进程
+//! - `LISTEN_FDS` - 传入的描述符数量
+//! - `LISTEN_FDNAMES` - 可选的以冒号分隔的名称
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::io::listen_fds;
+//!
+//! for fd in listen_fds()? {
+//!     if fd.is_unix()? {
+//!         let listener = fd.into_unix_listener()?;
+//!     } else {
+//!         let listener = fd.into_tcp_listener()?;
+//!     }
+//! }
+//! ```
+
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::TcpListener;
+use super::unix::UnixListener;
+
+/// First inherited file descriptor (`SD_LISTEN_FDS_START`)
+/// 第一个继承的文件描述符（`SD_LISTEN_FDS_START`）
+pub const LISTEN_FDS_START: RawFd = 3;
+
+/// Set once the inherited descriptors have been claimed
+/// 继承的描述符被认领后设置
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// A listening socket inherited from the parent process
+/// 从父进程继承的监听套接字
+#[derive(Debug)]
+pub struct ListenFd {
+    /// The inherited descriptor / 继承的描述符
+    fd: OwnedFd,
+    /// Name from `LISTEN_FDNAMES` / 来自 `LISTEN_FDNAMES` 的名称
+    name: Option<String>,
+}
+
+impl ListenFd {
+    /// Name given to the socket, e.g. by `FileDescriptorName=` in the socket unit
+    /// 套接字的名称，例如socket单元中 `FileDescriptorName=` 设置的名称
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Check whether this is a Unix domain socket rather than a TCP one
+    /// 检查这是Unix域套接字而不是TCP套接字
+    pub fn is_unix(&self) -> io::Result<bool> {
+        let mut addr: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+        let mut len = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        // SAFETY: addr and len describe a valid, writable sockaddr buffer
+        // 安全性：addr和len描述了一个有效的可写sockaddr缓冲区
+        let result = unsafe {
+            libc::getsockname(
+                self.fd.as_raw_fd(),
+                &mut addr as *mut _ as *mut libc::sockaddr,
+                &mut len,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(i32::from(addr.ss_family) == libc::AF_UNIX)
+    }
+
+    /// Use the socket as a TCP listener
+    /// 将套接字用作TCP监听器
+    pub fn into_tcp_listener(self) -> io::Result<TcpListener> {
+        TcpListener::from_std(std::net::TcpListener::from(self.fd))
+    }
+
+    /// Use the socket as a Unix domain listener
+    /// 将套接字用作Unix域监听器
+    pub fn into_unix_listener(self) -> io::Result<UnixListener> {
+        UnixListener::from_std(std::os::unix::net::UnixListener::from(self.fd))
+    }
+
+    /// Take the raw descriptor, e.g. to pass it on to a child process
+    /// 取出原始描述符，例如将其传给子进程
+    pub fn into_owned_fd(self) -> OwnedFd {
+        self.fd
+    }
+}
+
+impl From<OwnedFd> for ListenFd {
+    fn from(fd: OwnedFd) -> Self {
+        Self { fd, name: None }
+    }
+}
+
+impl AsRawFd for ListenFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+/// Claim the listening sockets passed to this process
+/// 认领传给此进程的监听套接字
+///
+/// Returns an empty list when none were passed or when `LISTEN_PID` names another
+/// process (the variables were inherited from an ancestor). The descriptors are
+/// owned by the caller, so only the first call returns them; later calls return an
+/// empty list. The descriptors are marked close-on-exec.
+///
+/// 未传入套接字或 `LISTEN_PID` 指向其他进程（变量继承自祖先进程）时返回空列表。
+/// 描述符归调用者所有，因此只有第一次调用会返回它们；之后的调用返回空列表。
+/// 描述符会被标记为exec时关闭。
+pub fn listen_fds() -> io::Result<Vec<ListenFd>> {
+    let pid = std::env::var("LISTEN_PID").ok();
+    let fds = std::env::var("LISTEN_FDS").ok();
+    let names = std::env::var("LISTEN_FDNAMES").ok();
+    let passed = parse_env(pid.as_deref(), fds.as_deref(), names.as_deref(), std::process::id())?;
+    if passed.is_empty() || TAKEN.swap(true, Ordering::AcqRel) {
+        return Ok(Vec::new());
+    }
+
+    let mut inherited = Vec::with_capacity(passed.len());
+    for (fd, name) in (LISTEN_FDS_START..).zip(passed) {
+        // SAFETY: the service manager passed these descriptors to this process and
+        // TAKEN ensures they are only wrapped once
+        // 安全性：服务管理器将这些描述符传给了此进程，且TAKEN保证它们只被包装一次
+        unsafe {
+            if libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            inherited.push(ListenFd {
+                fd: OwnedFd::from_raw_fd(fd),
+                name,
+            });
+        }
+    }
+    Ok(inherited)
+}
+
+/// Work out the names of the descriptors passed to process `own_pid`
+/// 计算传给进程 `own_pid` 的描述符名称
+fn parse_env(
+    pid: Option<&str>,
+    fds: Option<&str>,
+    names: Option<&str>,
+    own_pid: u32,
+) -> io::Result<Vec<Option<String>>> {
+    let (Some(pid), Some(fds)) = (pid, fds) else {
+        return Ok(Vec::new());
+    };
+    let invalid = |var: &str, value: &str| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("invalid {}: {:?}", var, value))
+    };
+    let pid: u32 = pid.trim().parse().map_err(|_| invalid("LISTEN_PID", pid))?;
+    if pid != own_pid {
+        return Ok(Vec::new());
+    }
+    let count: usize = fds.trim().parse().map_err(|_| invalid("LISTEN_FDS", fds))?;
+
+    let mut names = names.map(|names| names.split(':'));
+    Ok((0..count)
+        .map(|_| {
+            names
+                .as_mut()
+                .and_then(Iterator::next)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env() {
+        assert!(parse_env(None, Some("2"), None, 7).unwrap().is_empty());
+        assert!(parse_env(Some("8"), Some("2"), None, 7).unwrap().is_empty());
+        assert_eq!(parse_env(Some("7"), Some("2"), None, 7).unwrap(), vec![None, None]);
+        assert_eq!(
+            parse_env(Some("7"), Some("2"), Some("http:"), 7).unwrap(),
+            vec![Some("http".to_string()), None]
+        );
+        assert!(parse_env(Some("7"), Some("two"), None, 7).is_err());
+    }
+
+    #[test]
+    fn test_listen_fd_kind() {
+        let tcp = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let fd = ListenFd::from(OwnedFd::from(tcp));
+        assert!(!fd.is_unix().unwrap());
+        assert!(fd.into_tcp_listener().is_ok());
+
+        let (unix, _) = std::os::unix::net::UnixStream::pair().unwrap();
+        let fd = ListenFd {
+            fd: unix.into(),
+            name: Some("sock".to_string()),
+        };
+        assert!(fd.is_unix().unwrap());
+        assert_eq!(fd.name(), Some("sock"));
+    }
+}
@@ -0,0 +1,364 @@
+//! Unix domain sockets
+//! Unix域套接字
+//!
+//! # Overview / 概述
+//!
+//! [`UnixListener`] and [`UnixStream`] follow the same I/O model
+//! as [`TcpListener`](super::TcpListener) and [`TcpStream`](super::TcpStream): reads
+//! and writes use the same [`ReadFuture`] and [`WriteAllFuture`], so code written
+//! for one stream type works for the other. They are typically used to talk to a
+//! local sidecar proxy without going through the TCP stack.
+//!
+//! [`UnixListener`] 和 [`UnixStream`] 采用与 [`TcpListener`](super::TcpListener) 和
+//! [`TcpStream`](super::TcpStream) 相同的I/O模型：读写使用相同的 [`ReadFuture`]
+//! 和 [`WriteAllFuture`]，因此为一种流类型编写的代码也适用于另一种。它们通常用于
+//! 与本地sidecar代理通信，而无需经过TCP协议栈。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::io::{UnixListener, UnixStream};
+//!
+//! async fn echo() -> std::io::Result<()> {
+//!     let mut listener = UnixListener::bind("/run/app.sock")?;
+//!     let (mut stream, _addr) = listener.accept().await?;
+//!
+//!     let mut buf = [0u8; 1024];
+//!     let n = stream.read(&mut buf).await?;
+//!     stream.write_all(&buf[..n]).await
+//! }
+//! ```
+
+use std::future::Future;
+use std::io;
+use std::net::Shutdown;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use super::{ReadFuture, SocketOps, WriteAllFuture};
+use crate::driver::opcode;
+use crate::driver::reactor::Reactor;
+
+/// A Unix domain stream socket
+/// Unix域流套接字
+#[derive(Debug)]
+pub struct UnixStream {
+    /// Operations in flight, cancelled before the socket closes
+    /// 进行中的操作，在套接字关闭前取消
+    ops: SocketOps,
+    /// The non-blocking socket / 非阻塞套接字
+    inner: net::UnixStream,
+}
+
+impl UnixStream {
+    /// Connect to the socket at `path`
+    /// 连接到 `path` 处的套接字
+    ///
+    /// The connect is non-blocking: while the listener's backlog is full the task
+    /// waits for writability through the reactor instead of stalling the thread.
+    /// 连接是非阻塞的：监听者的积压队列已满时，任务通过反应器等待可写，
+    /// 而不会阻塞线程。
+    pub async fn connect(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (addr, len) = sockaddr_un(path.as_ref())?;
+        let socket = create_socket()?;
+        let fd = socket.as_raw_fd();
+
+        let mut in_progress = false;
+        std::future::poll_fn(|cx| {
+            if in_progress {
+                return match super::connect_result(fd) {
+                    Ok(true) => Poll::Ready(Ok(())),
+                    Ok(false) => {
+                        Reactor::retry_when_ready(fd, opcode::WRITE, cx)?;
+                        Poll::Pending
+                    },
+                    Err(e) => Poll::Ready(Err(e)),
+                };
+            }
+
+            let result = unsafe {
+                libc::connect(fd, &addr as *const libc::sockaddr_un as *const libc::sockaddr, len)
+            };
+            if result == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::EINPROGRESS) => {
+                    in_progress = true;
+                    Reactor::retry_when_ready(fd, opcode::WRITE, cx)?;
+                    Poll::Pending
+                },
+                // Backlog full: Unix sockets never finish the connect on their own
+                // 积压队列已满：Unix套接字不会自行完成连接，需重试
+                Some(libc::EAGAIN | libc::EINTR) => {
+                    Reactor::retry_when_ready(fd, opcode::WRITE, cx)?;
+                    Poll::Pending
+                },
+                _ => Poll::Ready(Err(err)),
+            }
+        })
+        .await?;
+
+        Self::from_std(net::UnixStream::from(socket))
+    }
+
+    /// Create a connected pair of sockets
+    /// 创建一对相互连接的套接字
+    pub fn pair() -> io::Result<(Self, Self)> {
+        let (a, b) = net::UnixStream::pair()?;
+        Ok((Self::from_std(a)?, Self::from_std(b)?))
+    }
+
+    /// Wrap a standard library socket, switching it to non-blocking mode
+    /// 包装标准库套接字，并将其切换为非阻塞模式
+    pub fn from_std(stream: net::UnixStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        Ok(Self {
+            ops: SocketOps::default(),
+            inner: stream,
+        })
+    }
+
+    /// Read some bytes from the stream
+    /// 从流中读取一些字节
+    ///
+    /// Returns the number of bytes read, 0 once the peer has closed the connection.
+    /// 返回读取的字节数，对端关闭连接后返回0。
+    pub fn read<'a, 'b>(&'a mut self, buf: &'b mut [u8]) -> ReadFuture<'a, 'b> {
+        ReadFuture {
+            fd: self.inner.as_raw_fd(),
+            ops: &mut self.ops,
+            buf,
+        }
+    }
+
+    /// Write all bytes to the stream
+    /// 将所有字节写入流
+    pub fn write_all<'a, 'b>(&'a mut self, buf: &'b [u8]) -> WriteAllFuture<'a, 'b> {
+        WriteAllFuture {
+            fd: self.inner.as_raw_fd(),
+            ops: &mut self.ops,
+            buf,
+            pos: 0,
+            sending: false,
+        }
+    }
+
+    /// Shut down the read, write or both halves of the connection
+    /// 关闭连接的读、写或两个方向
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.inner.shutdown(how)
+    }
+
+    /// Get the local address
+    /// 获取本地地址
+    pub fn local_addr(&self) -> io::Result<net::SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Get the peer address
+    /// 获取对端地址
+    pub fn peer_addr(&self) -> io::Result<net::SocketAddr> {
+        self.inner.peer_addr()
+    }
+}
+
+impl AsRawFd for UnixStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+/// A Unix domain socket listener
+/// Unix域套接字监听器
+#[derive(Debug)]
+pub struct UnixListener {
+    /// The non-blocking listening socket / 非阻塞监听套接字
+    inner: net::UnixListener,
+}
+
+impl UnixListener {
+    /// Create a listener bound to `path`
+    /// 创建绑定到 `path` 的监听器
+    ///
+    /// Binding a Unix socket never blocks, so unlike
+    /// [`TcpListener::bind`](super::TcpListener::bind) this is not a future. The
+    /// socket file must not exist yet.
+    /// 绑定Unix套接字永远不会阻塞，因此与 [`TcpListener::bind`](super::TcpListener::bind)
+    /// 不同，这不是一个future。套接字文件必须尚不存在。
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_std(net::UnixListener::bind(path)?)
+    }
+
+    /// Wrap a standard library listener, switching it to non-blocking mode
+    /// 包装标准库监听器，并将其切换为非阻塞模式
+    pub fn from_std(listener: net::UnixListener) -> io::Result<Self> {
+        listener.set_nonblocking(true)?;
+        Ok(Self { inner: listener })
+    }
+
+    /// Accept a new connection
+    /// 接受新连接
+    pub fn accept(&mut self) -> UnixAcceptFuture<'_> {
+        UnixAcceptFuture { listener: self }
+    }
+
+    /// Get the local address
+    /// 获取本地地址
+    pub fn local_addr(&self) -> io::Result<net::SocketAddr> {
+        self.inner.local_addr()
+    }
+}
+
+impl AsRawFd for UnixListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+/// Future for accepting a Unix domain connection
+/// 接受Unix域连接的future
+pub struct UnixAcceptFuture<'a> {
+    listener: &'a mut UnixListener,
+}
+
+impl Future for UnixAcceptFuture<'_> {
+    type Output = io::Result<(UnixStream, net::SocketAddr)>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.listener.inner.accept() {
+            Ok((stream, addr)) => Poll::Ready(UnixStream::from_std(stream).map(|s| (s, addr))),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Reactor::retry_when_ready(self.listener.as_raw_fd(), opcode::READ, cx)?;
+                Poll::Pending
+            },
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+/// Encode `path` as a `sockaddr_un`
+/// 将 `path` 编码为 `sockaddr_un`
+fn sockaddr_un(path: &Path) -> io::Result<(libc::sockaddr_un, libc::socklen_t)> {
+    let mut addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+    let bytes = path.as_os_str().as_bytes();
+    if bytes.contains(&0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "socket path must not contain null bytes",
+        ));
+    }
+    if bytes.len() >= addr.sun_path.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "socket path is too long"));
+    }
+    for (dst, src) in addr.sun_path.iter_mut().zip(bytes) {
+        *dst = *src as libc::c_char;
+    }
+
+    let len = std::mem::offset_of!(libc::sockaddr_un, sun_path) + bytes.len() + 1;
+    Ok((addr, len as libc::socklen_t))
+}
+
+/// Create a non-blocking, close-on-exec Unix stream socket
+/// 创建非阻塞、close-on-exec的Unix流套接字
+fn create_socket() -> io::Result<OwnedFd> {
+    unsafe {
+        #[cfg(target_os = "linux")]
+        let fd = libc::socket(
+            libc::AF_UNIX,
+            libc::SOCK_STREAM | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+            0,
+        );
+
+        #[cfg(not(target_os = "linux"))]
+        let fd = libc::socket(libc::AF_UNIX, libc::SOCK_STREAM, 0);
+
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let socket = OwnedFd::from_raw_fd(fd);
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            // Set close-on-exec and non-blocking for macOS/BSD
+            if libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let flags = libc::fcntl(fd, libc::F_GETFL);
+            if libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(socket)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::block_on;
+
+    #[test]
+    fn test_unix_listener_echo() {
+        let dir = std::env::temp_dir().join(format!("nexus-unix-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("echo.sock");
+        let _ = std::fs::remove_file(&path);
+
+        let mut listener = UnixListener::bind(&path).unwrap();
+        let client_path = path.clone();
+        let echoed = block_on(async move {
+            let server = crate::task::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 16];
+                let n = stream.read(&mut buf).await.unwrap();
+                stream.write_all(&buf[..n]).await.unwrap();
+            });
+
+            let mut client = UnixStream::connect(&client_path).await.unwrap();
+            client.write_all(b"ping").await.unwrap();
+            let mut buf = [0u8; 16];
+            let n = client.read(&mut buf).await.unwrap();
+            server.wait().await.unwrap();
+            buf[..n].to_vec()
+        });
+        assert_eq!(echoed, b"ping");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unix_connect_missing_path() {
+        let path =
+            std::env::temp_dir().join(format!("nexus-unix-missing-{}.sock", std::process::id()));
+        let err = block_on(async move { UnixStream::connect(&path).await.unwrap_err() });
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_unix_connect_path_too_long() {
+        let path = "x".repeat(4096);
+        let err = block_on(async move { UnixStream::connect(&path).await.unwrap_err() });
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_unix_pair_shutdown() {
+        block_on(async {
+            let (mut a, mut b) = UnixStream::pair().unwrap();
+            a.write_all(b"x").await.unwrap();
+            a.shutdown(Shutdown::Write).unwrap();
+
+            let mut buf = [0u8; 4];
+            assert_eq!(b.read(&mut buf).await.unwrap(), 1);
+            assert_eq!(b.read(&mut buf).await.unwrap(), 0);
+        });
+    }
+}
@@ -79,17 +79,21 @@
 // Public modules / 公共模块
 pub mod channel;
 pub mod driver;
+pub mod fs;
 pub mod io;
+pub mod metrics;
 pub mod runtime;
 pub mod scheduler;
 pub mod select;
+pub mod signal;
+pub mod sync;
 pub mod task;
 pub mod time;
 
 // Re-exports / 重新导出
 pub use channel::{Receiver, RecvError, SendError, Sender, bounded, unbounded};
 pub use driver::{Driver, DriverConfig, DriverConfigBuilder, DriverFactory, DriverType};
-pub use runtime::{Runtime, RuntimeBuilder, RuntimeConfig};
+pub use runtime::{Runtime, RuntimeBuilder, RuntimeConfig, TestRuntime, TestRuntimeBuilder};
 pub use scheduler::{
     Scheduler, SchedulerConfig, SchedulerHandle, WorkStealingConfig, WorkStealingHandle,
     WorkStealingScheduler, gen_task_id,
@@ -97,5 +101,5 @@ pub use scheduler::{
 pub use select::{
     SelectMultiple, SelectMultipleOutput, SelectTwo, SelectTwoOutput, select_multiple, select_two,
 };
-pub use task::{JoinError, JoinHandle, spawn};
-pub use time::{Duration, Instant, sleep, sleep_until};
+pub use task::{JoinError, JoinHandle, JoinSet, spawn, spawn_blocking};
+pub use time::{Duration, Elapsed, Instant, sleep, sleep_until, timeout, timeout_at};
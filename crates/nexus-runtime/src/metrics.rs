@@ -0,0 +1,460 @@
+//! Runtime introspection
+//! 运行时自省
+//!
+//! # Overview / 概述
+//!
+//! The runtime keeps cheap atomic counters for its moving parts. This module
+//! holds the snapshot types and the entry points for reading them:
+//!
+//! - [`WorkerStats`] - per worker of a [`WorkStealingScheduler`](crate::WorkStealingScheduler),
+//!   from [`worker_stats`](crate::WorkStealingScheduler::worker_stats)
+//! - [`TimerStats`] - timer wheel occupancy, from [`TimerWheel::stats`](crate::time::TimerWheel::stats)
+//! - [`DriverStats`] - I/O driver event counts, from [`driver_stats`]
+//! - [`TaskDump`] - live tasks with their spawn location and age, from [`dump_tasks`]
+//!
+//! 运行时为其各个部件维护低开销的原子计数器。本模块包含快照类型及读取它们的入口：
+//! 每个工作器的 [`WorkerStats`]、时间轮占用 [`TimerStats`]、I/O驱动事件计数 [`DriverStats`]，
+//! 以及带有生成位置和存活时长的存活任务 [`TaskDump`]。
+//!
+//! # Equivalent to Spring / 等价于 Spring
+//!
+//! - Micrometer `ExecutorServiceMetrics`
+//! - Spring Boot Actuator `/actuator/threaddump`
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::metrics;
+//!
+//! // Tasks alive for more than 30 seconds are likely stuck
+//! // 存活超过30秒的任务很可能卡住了
+//! for task in metrics::dump_tasks() {
+//!     if task.age > Duration::from_secs(30) {
+//!         tracing::warn!("{task}");
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::driver::DriverType;
+use crate::scheduler::TaskId;
+
+/// Upper bounds of the poll-time histogram buckets, in microseconds
+/// 轮询耗时直方图各桶的上界（微秒）
+///
+/// Polls slower than the last bound land in an extra overflow bucket.
+/// 比最后一个上界更慢的轮询落入额外的溢出桶。
+pub const POLL_TIME_BUCKETS_US: [u64; 8] = [10, 25, 100, 250, 1_000, 10_000, 100_000, 1_000_000];
+
+/// Lock-free histogram of task poll durations
+/// 任务轮询耗时的无锁直方图
+pub(crate) struct PollTimeHistogram {
+    /// One count per bucket plus the overflow bucket / 每个桶一个计数，外加溢出桶
+    counts: [AtomicU64; POLL_TIME_BUCKETS_US.len() + 1],
+    /// Total observed time in nanoseconds / 观察到的总时间（纳秒）
+    sum_ns: AtomicU64,
+}
+
+impl PollTimeHistogram {
+    pub(crate) const fn new() -> Self {
+        Self {
+            counts: [const { AtomicU64::new(0) }; POLL_TIME_BUCKETS_US.len() + 1],
+            sum_ns: AtomicU64::new(0),
+        }
+    }
+
+    /// Record one poll / 记录一次轮询
+    pub(crate) fn record(&self, elapsed: Duration) {
+        let us = elapsed.as_micros() as u64;
+        let bucket = POLL_TIME_BUCKETS_US.partition_point(|&bound| bound < us);
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_ns
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> PollTimeStats {
+        PollTimeStats {
+            counts: self
+                .counts
+                .iter()
+                .map(|count| count.load(Ordering::Relaxed))
+                .collect(),
+            sum: Duration::from_nanos(self.sum_ns.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Snapshot of a poll-time histogram
+/// 轮询耗时直方图的快照
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PollTimeStats {
+    /// Polls per bucket of [`POLL_TIME_BUCKETS_US`], followed by the overflow bucket
+    /// [`POLL_TIME_BUCKETS_US`] 每个桶的轮询次数，最后是溢出桶
+    pub counts: Vec<u64>,
+    /// Total time spent polling / 轮询花费的总时间
+    pub sum: Duration,
+}
+
+impl PollTimeStats {
+    /// Total number of polls / 轮询总次数
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Cumulative counts per upper bound, ending with `None` for +Inf
+    /// 按上界累计的计数，最后以 `None` 表示 +Inf
+    pub fn cumulative(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        let bounds = POLL_TIME_BUCKETS_US
+            .iter()
+            .map(|&us| Some(Duration::from_micros(us)))
+            .chain([None]);
+        bounds.zip(self.counts.iter().scan(0, |total, &count| {
+            *total += count;
+            Some(*total)
+        }))
+    }
+}
+
+/// Live counters of one scheduler worker
+/// 单个调度器工作器的实时计数器
+pub(crate) struct WorkerMetrics {
+    started: Instant,
+    tasks_polled: AtomicU64,
+    steals: AtomicU64,
+    parks: AtomicU64,
+    unparks: AtomicU64,
+    busy_ns: AtomicU64,
+    poll_time: PollTimeHistogram,
+}
+
+impl WorkerMetrics {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            tasks_polled: AtomicU64::new(0),
+            steals: AtomicU64::new(0),
+            parks: AtomicU64::new(0),
+            unparks: AtomicU64::new(0),
+            busy_ns: AtomicU64::new(0),
+            poll_time: PollTimeHistogram::new(),
+        }
+    }
+
+    /// Record a task poll and its duration / 记录一次任务轮询及其耗时
+    pub(crate) fn record_poll(&self, elapsed: Duration) {
+        self.tasks_polled.fetch_add(1, Ordering::Relaxed);
+        self.busy_ns
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.poll_time.record(elapsed);
+    }
+
+    /// Record a task taken from another worker / 记录从其他工作器窃取的任务
+    pub(crate) fn record_steal(&self) {
+        self.steals.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the worker going idle / 记录工作器进入空闲
+    pub(crate) fn record_park(&self) {
+        self.parks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the worker resuming after idling / 记录工作器在空闲后恢复
+    pub(crate) fn record_unpark(&self) {
+        self.unparks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self, worker_id: usize, queue_depth: usize) -> WorkerStats {
+        let busy = Duration::from_nanos(self.busy_ns.load(Ordering::Relaxed));
+        let uptime = self.started.elapsed();
+        WorkerStats {
+            worker_id,
+            tasks_polled: self.tasks_polled.load(Ordering::Relaxed),
+            steals: self.steals.load(Ordering::Relaxed),
+            parks: self.parks.load(Ordering::Relaxed),
+            unparks: self.unparks.load(Ordering::Relaxed),
+            queue_depth,
+            busy,
+            busy_ratio: if uptime.is_zero() {
+                0.0
+            } else {
+                (busy.as_secs_f64() / uptime.as_secs_f64()).min(1.0)
+            },
+            poll_time: self.poll_time.snapshot(),
+        }
+    }
+}
+
+/// Statistics of one scheduler worker
+/// 单个调度器工作器的统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerStats {
+    /// Worker index / 工作器索引
+    pub worker_id: usize,
+    /// Tasks polled / 已轮询的任务数
+    pub tasks_polled: u64,
+    /// Tasks stolen from other workers / 从其他工作器窃取的任务数
+    pub steals: u64,
+    /// Times the worker went idle / 工作器进入空闲的次数
+    pub parks: u64,
+    /// Times the worker resumed with work after idling / 工作器空闲后有任务而恢复的次数
+    pub unparks: u64,
+    /// Tasks waiting in the local queue / 本地队列中等待的任务数
+    pub queue_depth: usize,
+    /// Time spent polling tasks / 轮询任务花费的时间
+    pub busy: Duration,
+    /// Share of the worker's lifetime spent polling, from 0.0 to 1.0
+    /// 工作器生命周期中用于轮询的比例，范围0.0到1.0
+    pub busy_ratio: f64,
+    /// Distribution of poll durations / 轮询耗时分布
+    pub poll_time: PollTimeStats,
+}
+
+/// Occupancy of a timer wheel
+/// 时间轮的占用情况
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimerStats {
+    /// Pending timers / 挂起的定时器数
+    pub active: usize,
+    /// Pending timers per wheel level, finest first / 每层轮挂起的定时器数，从最细开始
+    pub per_level: [usize; 4],
+    /// Timers that fired so far / 迄今已触发的定时器数
+    pub expired: u64,
+    /// Current wheel time in ticks / 当前轮时间（滴答）
+    pub ticks: u64,
+}
+
+/// Event counters of one I/O driver backend
+/// 单个I/O驱动后端的事件计数器
+pub(crate) struct DriverCounters {
+    submissions: AtomicU64,
+    waits: AtomicU64,
+    completions: AtomicU64,
+}
+
+impl DriverCounters {
+    const fn new() -> Self {
+        Self {
+            submissions: AtomicU64::new(0),
+            waits: AtomicU64::new(0),
+            completions: AtomicU64::new(0),
+        }
+    }
+
+    /// Record entries handed to the kernel / 记录交给内核的条目
+    pub(crate) fn record_submit(&self, entries: usize) {
+        self.submissions
+            .fetch_add(entries as u64, Ordering::Relaxed);
+    }
+
+    /// Record a wait and the completions it produced / 记录一次等待及其产生的完成事件
+    pub(crate) fn record_wait(&self, completions: usize) {
+        self.waits.fetch_add(1, Ordering::Relaxed);
+        self.completions
+            .fetch_add(completions as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> DriverStats {
+        DriverStats {
+            submissions: self.submissions.load(Ordering::Relaxed),
+            waits: self.waits.load(Ordering::Relaxed),
+            completions: self.completions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counters of every epoll driver / 所有epoll驱动的计数器
+pub(crate) static EPOLL: DriverCounters = DriverCounters::new();
+/// Counters of every io_uring driver / 所有io_uring驱动的计数器
+pub(crate) static IO_URING: DriverCounters = DriverCounters::new();
+/// Counters of every kqueue driver / 所有kqueue驱动的计数器
+pub(crate) static KQUEUE: DriverCounters = DriverCounters::new();
+
+/// Process-wide event counts of an I/O driver backend
+/// I/O驱动后端的进程级事件计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DriverStats {
+    /// Entries submitted to the kernel / 提交给内核的条目数
+    pub submissions: u64,
+    /// Calls waiting for events / 等待事件的调用次数
+    pub waits: u64,
+    /// Completions or readiness events received / 收到的完成或就绪事件数
+    pub completions: u64,
+}
+
+/// Get the event counts of all drivers of a backend since process start
+/// 获取某个后端所有驱动自进程启动以来的事件计数
+///
+/// [`DriverType::Auto`] sums all backends.
+/// [`DriverType::Auto`] 汇总所有后端。
+#[must_use]
+pub fn driver_stats(backend: DriverType) -> DriverStats {
+    match backend {
+        DriverType::Epoll => EPOLL.snapshot(),
+        DriverType::IOUring => IO_URING.snapshot(),
+        DriverType::Kqueue => KQUEUE.snapshot(),
+        DriverType::Auto => [&EPOLL, &IO_URING, &KQUEUE]
+            .into_iter()
+            .map(DriverCounters::snapshot)
+            .fold(DriverStats::default(), |total, stats| DriverStats {
+                submissions: total.submissions + stats.submissions,
+                waits: total.waits + stats.waits,
+                completions: total.completions + stats.completions,
+            }),
+    }
+}
+
+/// How a task runs
+/// 任务的运行方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskKind {
+    /// Future started with [`spawn`](crate::spawn) / 由 [`spawn`](crate::spawn) 启动的future
+    Async,
+    /// Closure started with [`spawn_blocking`](crate::spawn_blocking)
+    /// 由 [`spawn_blocking`](crate::spawn_blocking) 启动的闭包
+    Blocking,
+}
+
+impl std::fmt::Display for TaskKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Async => "async",
+            Self::Blocking => "blocking",
+        })
+    }
+}
+
+/// Registry entry of a live task / 存活任务的注册表条目
+struct LiveTask {
+    kind: TaskKind,
+    location: &'static Location<'static>,
+    spawned: Instant,
+}
+
+/// Tasks spawned and not yet finished / 已生成但尚未完成的任务
+static LIVE_TASKS: LazyLock<Mutex<HashMap<TaskId, LiveTask>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Add a task to the live task registry / 将任务加入存活任务注册表
+pub(crate) fn track_task(id: TaskId, kind: TaskKind, location: &'static Location<'static>) {
+    LIVE_TASKS.lock().unwrap().insert(
+        id,
+        LiveTask {
+            kind,
+            location,
+            spawned: Instant::now(),
+        },
+    );
+}
+
+/// Remove a finished task from the live task registry / 从存活任务注册表中移除已完成的任务
+pub(crate) fn untrack_task(id: TaskId) {
+    LIVE_TASKS.lock().unwrap().remove(&id);
+}
+
+/// A live task, as listed by [`dump_tasks`]
+/// 由 [`dump_tasks`] 列出的存活任务
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskDump {
+    /// Task ID / 任务ID
+    pub id: TaskId,
+    /// How the task runs / 任务的运行方式
+    pub kind: TaskKind,
+    /// Source location of the spawn call / spawn调用的源码位置
+    pub location: &'static Location<'static>,
+    /// Time since the task was spawned / 任务生成以来的时间
+    pub age: Duration,
+}
+
+impl std::fmt::Display for TaskDump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "task {} ({}) spawned at {}, alive for {:?}",
+            self.id, self.kind, self.location, self.age
+        )
+    }
+}
+
+/// List the tasks that have not finished yet, oldest first
+/// 列出尚未完成的任务，最早的在前
+///
+/// Long-lived entries point at stuck requests or leaked background tasks.
+/// 长期存在的条目往往意味着卡住的请求或泄漏的后台任务。
+#[must_use]
+pub fn dump_tasks() -> Vec<TaskDump> {
+    let now = Instant::now();
+    let mut tasks: Vec<_> = LIVE_TASKS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(&id, task)| TaskDump {
+            id,
+            kind: task.kind,
+            location: task.location,
+            age: now.saturating_duration_since(task.spawned),
+        })
+        .collect();
+    tasks.sort_by(|a, b| b.age.cmp(&a.age).then(a.id.cmp(&b.id)));
+    tasks
+}
+
+/// Number of tasks that have not finished yet
+/// 尚未完成的任务数
+#[must_use]
+pub fn live_tasks() -> usize {
+    LIVE_TASKS.lock().unwrap().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{block_on, spawn, spawn_blocking};
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_poll_time_buckets() {
+        let histogram = PollTimeHistogram::new();
+        histogram.record(Duration::from_micros(5));
+        histogram.record(Duration::from_micros(10));
+        histogram.record(Duration::from_micros(300));
+        histogram.record(Duration::from_secs(2));
+
+        let stats = histogram.snapshot();
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.counts[0], 2);
+        assert_eq!(stats.counts[4], 1);
+        assert_eq!(stats.counts[POLL_TIME_BUCKETS_US.len()], 1);
+        let cumulative: Vec<_> = stats.cumulative().collect();
+        assert_eq!(cumulative[3], (Some(Duration::from_micros(250)), 2));
+        assert_eq!(cumulative.last(), Some(&(None, 4)));
+    }
+
+    #[test]
+    fn test_dump_tasks_lists_live_tasks() {
+        let (release, blocked) = mpsc::channel::<()>();
+        let line = line!() + 1;
+        let handle = spawn_blocking(move || blocked.recv().unwrap());
+        let id = handle.id();
+
+        let dump = dump_tasks().into_iter().find(|task| task.id == id).unwrap();
+        assert_eq!(dump.kind, TaskKind::Blocking);
+        assert_eq!(dump.location.file(), file!());
+        assert_eq!(dump.location.line(), line);
+        assert!(dump.to_string().contains(file!()));
+
+        release.send(()).unwrap();
+        block_on(async move { handle.await.unwrap() });
+        assert!(dump_tasks().iter().all(|task| task.id != id));
+
+        let handle = spawn(async { 1 });
+        let id = handle.id();
+        block_on(async move { handle.await.unwrap() });
+        assert!(dump_tasks().iter().all(|task| task.id != id));
+    }
+}
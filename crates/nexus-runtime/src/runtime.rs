@@ -28,10 +28,15 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll, Waker};
 
+use crate::driver::reactor::Reactor;
 use crate::driver::{Driver, DriverFactory, DriverType};
 use crate::scheduler::{Scheduler, SchedulerConfig, SchedulerHandle};
 use crate::time::{Duration, Instant};
 
+mod test_runtime;
+pub(crate) use test_runtime::{Executor, LocalTask};
+pub use test_runtime::{TestRuntime, TestRuntimeBuilder};
+
 /// Runtime configuration / 运行时配置
 ///
 /// Configuration for the async runtime including scheduler and driver settings.
@@ -182,6 +187,8 @@ pub struct Runtime {
     scheduler: Scheduler,
     /// The driver / 驱动
     driver: Arc<dyn Driver>,
+    /// Hands the driver's completions to waiting tasks / 将driver的完成事件交给等待的任务
+    reactor: Arc<Reactor>,
     /// Runtime configuration / 运行时配置
     config: RuntimeConfig,
     /// Waker for the main task / 主任务的waker
@@ -230,6 +237,7 @@ impl Runtime {
 
         Ok(Self {
             scheduler,
+            reactor: Arc::new(Reactor::new(driver.clone())),
             driver,
             config,
             main_waker: None,
@@ -270,6 +278,10 @@ impl Runtime {
         let mut context = Context::from_waker(&waker);
         self.main_waker = Some(waker.clone());
 
+        // I/O of this thread and the tasks it spawns goes through this runtime's driver
+        // 此线程及其生成的任务的I/O通过此运行时的driver进行
+        let _enter = self.reactor.enter();
+
         // Run the event loop
         // 运行事件循环
         loop {
@@ -296,7 +308,7 @@ impl Runtime {
     fn run_once(&mut self) -> io::Result<()> {
         // Submit any pending I/O operations
         // 提交任何挂起的I/O操作
-        let _ = self.driver.submit();
+        let _ = self.reactor.flush();
 
         // Wait for events with timeout
         // 带超时等待事件
@@ -330,13 +342,16 @@ impl Runtime {
     /// Process completion events from the driver
     /// 处理来自driver的完成事件
     fn process_completions(&mut self) {
-        while let Some(completion) = self.driver.get_completion() {
+        while let Some(&completion) = self.driver.get_completion() {
+            self.driver.advance_completion();
+            if self.reactor.dispatch(completion) {
+                continue;
+            }
             // Notify the task associated with this completion
             // 通知与此完成关联的任务
             if let Some(waker) = self.scheduler.get_task_waker(completion.user_data) {
                 waker.wake();
             }
-            self.driver.advance_completion();
         }
     }
 
@@ -363,7 +378,7 @@ impl Runtime {
     fn flush_events(&mut self) -> io::Result<()> {
         // Submit pending operations
         // 提交挂起的操作
-        let _ = self.driver.submit();
+        let _ = self.reactor.flush();
 
         // Process any remaining completions without blocking
         // 不阻塞地处理任何剩余的完成事件
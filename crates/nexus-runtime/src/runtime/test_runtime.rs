@@ -0,0 +1,552 @@
+//! Deterministic single-threaded runtime for tests
+//! 用于测试的确定性单线程运行时
+//!
+//! # Overview / 概述
+//!
+//! [`TestRuntime`] runs the future passed to [`TestRuntime::block_on`] and every
+//! task it [`spawn`](crate::spawn)s on the calling thread:
+//!
+//! - Time is virtual. With a paused clock, timers only fire through
+//!   [`time::advance`](crate::time::advance) or when every task is idle, in which
+//!   case the clock jumps straight to the next timer. A test waiting on a
+//!   one-hour timeout finishes in microseconds.
+//! - Tasks woken in the same round run in an order drawn from a seeded random
+//!   generator. The same seed gives the same interleaving, a different seed
+//!   explores another one. The seed comes from [`TestRuntimeBuilder::seed`] or the
+//!   `NEXUS_TEST_SEED` environment variable.
+//!
+//! [`TestRuntime`] 在调用线程上运行传给 [`TestRuntime::block_on`] 的future及其
+//! [`spawn`](crate::spawn) 的所有任务：
+//!
+//! - 时间是虚拟的。时钟暂停时，定时器只会通过 [`time::advance`](crate::time::advance) 触发，
+//!   或在所有任务都空闲时触发，此时时钟直接跳到下一个定时器。等待一小时超时的测试
+//!   只需几微秒即可完成。
+//! - 同一轮中被唤醒的任务按种子随机数生成器决定的顺序运行。相同的种子产生相同的交错，
+//!   不同的种子探索另一种交错。种子来自 [`TestRuntimeBuilder::seed`] 或环境变量
+//!   `NEXUS_TEST_SEED`。
+//!
+//! Work on the [blocking pool](crate::spawn_blocking) keeps running in real time;
+//! the clock does not skip ahead while such work started by the runtime is in
+//! flight.
+//! [阻塞线程池](crate::spawn_blocking)上的工作仍按真实时间运行；运行时启动的此类工作
+//! 进行期间，时钟不会向前跳跃。
+//!
+//! # Equivalent to Spring / 等价于 Spring
+//!
+//! - Reactor `VirtualTimeScheduler` / `StepVerifier.withVirtualTime`
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::runtime::TestRuntime;
+//! use nexus_runtime::time::{self, Duration, timeout};
+//!
+//! let mut runtime = TestRuntime::builder().seed(7).build();
+//! runtime.block_on(async {
+//!     let result = timeout(Duration::from_secs(3600), std::future::pending::<()>()).await;
+//!     assert!(result.is_err());
+//! });
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
+use std::pin::{Pin, pin};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+use crate::time::{Duration, TimeContext};
+
+/// Task ID of the future passed to `block_on` / 传给 `block_on` 的future的任务ID
+const MAIN: u64 = 0;
+
+/// Longest real-time wait while idle / 空闲时最长的真实时间等待
+const IDLE_WAIT: Duration = Duration::from_millis(1);
+
+/// Environment variable providing the default seed / 提供默认种子的环境变量
+const SEED_ENV: &str = "NEXUS_TEST_SEED";
+
+/// A spawned task driven by the executor / 由执行器驱动的已生成任务
+pub(crate) type LocalTask = Pin<Box<dyn Future<Output = ()>>>;
+
+/// IDs of woken tasks, shared with wakers on any thread
+/// 被唤醒任务的ID，与任意线程上的waker共享
+struct ReadyQueue {
+    ids: Mutex<BTreeSet<u64>>,
+    condvar: Condvar,
+}
+
+impl ReadyQueue {
+    fn push(&self, id: u64) {
+        self.ids.lock().unwrap().insert(id);
+        self.condvar.notify_one();
+    }
+
+    fn take(&self) -> Vec<u64> {
+        std::mem::take(&mut *self.ids.lock().unwrap())
+            .into_iter()
+            .collect()
+    }
+
+    /// Block until a task is woken or `timeout` passes
+    /// 阻塞直到有任务被唤醒或 `timeout` 到期
+    fn wait(&self, timeout: Duration) {
+        let ids = self.ids.lock().unwrap();
+        if ids.is_empty() {
+            drop(self.condvar.wait_timeout(ids, timeout).unwrap());
+        }
+    }
+}
+
+struct TaskWaker {
+    id: u64,
+    ready: Arc<ReadyQueue>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.ready.push(self.id);
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.ready.push(self.id);
+    }
+}
+
+/// Task storage of a test runtime
+/// 测试运行时的任务存储
+pub(crate) struct Executor {
+    tasks: RefCell<HashMap<u64, (LocalTask, Waker)>>,
+    next_id: Cell<u64>,
+    ready: Arc<ReadyQueue>,
+    /// Blocking pool jobs started by tasks of this runtime / 此运行时的任务启动的阻塞池作业
+    blocking: Arc<AtomicUsize>,
+}
+
+thread_local! {
+    /// Executor of the test runtime running on this thread
+    /// 在此线程上运行的测试运行时的执行器
+    static CURRENT: RefCell<Option<Rc<Executor>>> = const { RefCell::new(None) };
+}
+
+impl Executor {
+    fn new() -> Self {
+        Self {
+            tasks: RefCell::new(HashMap::new()),
+            next_id: Cell::new(MAIN + 1),
+            ready: Arc::new(ReadyQueue {
+                ids: Mutex::new(BTreeSet::new()),
+                condvar: Condvar::new(),
+            }),
+            blocking: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Get the executor of the test runtime on this thread
+    /// 获取此线程上测试运行时的执行器
+    pub(crate) fn current() -> Option<Rc<Self>> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    fn waker(&self, id: u64) -> Waker {
+        Waker::from(Arc::new(TaskWaker {
+            id,
+            ready: self.ready.clone(),
+        }))
+    }
+
+    /// Add a task and schedule its first poll
+    /// 添加任务并安排其首次轮询
+    pub(crate) fn spawn(&self, task: LocalTask) {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let waker = self.waker(id);
+        self.tasks.borrow_mut().insert(id, (task, waker));
+        self.ready.push(id);
+    }
+
+    /// Wrap a blocking pool job so the clock does not skip ahead while it runs
+    /// 包装阻塞池作业，使时钟在其运行期间不会向前跳跃
+    pub(crate) fn track_blocking<F, T>(&self, f: F) -> impl FnOnce() -> T + Send + 'static
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        /// Decrements the counter even if the job panics / 即使作业恐慌也会递减计数器
+        struct Running(Arc<AtomicUsize>);
+
+        impl Drop for Running {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::AcqRel);
+            }
+        }
+
+        self.blocking.fetch_add(1, Ordering::AcqRel);
+        let running = Running(self.blocking.clone());
+        move || {
+            let _running = running;
+            f()
+        }
+    }
+
+    /// Poll a spawned task once, dropping it when done
+    /// 轮询已生成的任务一次，完成时将其丢弃
+    fn poll_task(&self, id: u64) {
+        // Taken out so the task can spawn while being polled
+        // 取出任务，使其在被轮询时可以生成新任务
+        let Some((mut task, waker)) = self.tasks.borrow_mut().remove(&id) else {
+            return;
+        };
+        if task
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker))
+            .is_pending()
+        {
+            self.tasks.borrow_mut().insert(id, (task, waker));
+        }
+    }
+
+    fn task_ids(&self) -> Vec<u64> {
+        let mut ids: Vec<_> = self.tasks.borrow().keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+/// Installs an executor on the current thread until dropped
+/// 在当前线程上安装执行器，直到被丢弃
+struct EnterGuard {
+    previous: Option<Rc<Executor>>,
+}
+
+impl EnterGuard {
+    fn new(executor: &Rc<Executor>) -> Self {
+        let previous = CURRENT.with(|current| current.borrow_mut().replace(executor.clone()));
+        Self { previous }
+    }
+}
+
+impl Drop for EnterGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Seeded generator for the scheduling order (SplitMix64)
+/// 用于调度顺序的种子生成器（SplitMix64）
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates shuffle / Fisher-Yates洗牌
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Builder for [`TestRuntime`]
+/// [`TestRuntime`] 的构建器
+#[derive(Debug, Clone)]
+pub struct TestRuntimeBuilder {
+    start_paused: bool,
+    seed: Option<u64>,
+}
+
+impl TestRuntimeBuilder {
+    /// Create a builder for a runtime with a paused clock
+    /// 为时钟暂停的运行时创建构建器
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            start_paused: true,
+            seed: None,
+        }
+    }
+
+    /// Start with the clock paused (default) or following real time
+    /// 以暂停的时钟（默认）或跟随真实时间的时钟启动
+    #[must_use]
+    pub fn start_paused(mut self, paused: bool) -> Self {
+        self.start_paused = paused;
+        self
+    }
+
+    /// Set the seed of the scheduling order
+    /// 设置调度顺序的种子
+    ///
+    /// Defaults to `NEXUS_TEST_SEED` from the environment, or 0.
+    /// 默认为环境变量 `NEXUS_TEST_SEED`，否则为0。
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Build the runtime
+    /// 构建运行时
+    #[must_use]
+    pub fn build(self) -> TestRuntime {
+        let seed = self.seed.unwrap_or_else(|| {
+            std::env::var(SEED_ENV)
+                .ok()
+                .and_then(|seed| seed.trim().parse().ok())
+                .unwrap_or(0)
+        });
+        TestRuntime {
+            executor: Rc::new(Executor::new()),
+            time: TimeContext::new(self.start_paused),
+            rng: SplitMix64(seed),
+            seed,
+        }
+    }
+}
+
+impl Default for TestRuntimeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Deterministic single-threaded runtime with virtual time
+/// 带虚拟时间的确定性单线程运行时
+///
+/// See the [module documentation](self) for the scheduling and time rules.
+/// 调度和时间规则见[模块文档](self)。
+pub struct TestRuntime {
+    executor: Rc<Executor>,
+    time: Arc<TimeContext>,
+    rng: SplitMix64,
+    seed: u64,
+}
+
+impl TestRuntime {
+    /// Create a runtime with a paused clock and the default seed
+    /// 创建时钟暂停、使用默认种子的运行时
+    #[must_use]
+    pub fn new() -> Self {
+        TestRuntimeBuilder::new().build()
+    }
+
+    /// Create a runtime builder
+    /// 创建运行时构建器
+    #[must_use]
+    pub fn builder() -> TestRuntimeBuilder {
+        TestRuntimeBuilder::new()
+    }
+
+    /// Get the seed of the scheduling order
+    /// 获取调度顺序的种子
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Run a future and the tasks it spawns until the future completes
+    /// 运行future及其生成的任务，直到该future完成
+    ///
+    /// Tasks still pending when the future completes stay in the runtime and
+    /// continue in the next `block_on` call.
+    /// future完成时仍挂起的任务保留在运行时中，并在下一次 `block_on` 调用中继续。
+    pub fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        let _time = self.time.enter();
+        let _executor = EnterGuard::new(&self.executor);
+        let executor = &*self.executor;
+
+        let mut future = pin!(future);
+        let main_waker = executor.waker(MAIN);
+        executor.ready.push(MAIN);
+        let mut swept = false;
+
+        loop {
+            self.time.fire_due();
+            let mut batch = executor.ready.take();
+
+            if batch.is_empty() {
+                if swept {
+                    self.idle();
+                    swept = false;
+                    continue;
+                }
+                // Futures that never wake are polled once per idle round
+                // 从不唤醒的future在每个空闲轮次中轮询一次
+                swept = true;
+                batch = executor.task_ids();
+                batch.push(MAIN);
+            } else {
+                swept = false;
+            }
+
+            // Spawned tasks in seeded order, then the main future
+            // 已生成的任务按种子顺序运行，然后是主future
+            let main = batch.first() == Some(&MAIN);
+            let tasks = if main {
+                &mut batch[1..]
+            } else {
+                &mut batch[..]
+            };
+            self.rng.shuffle(tasks);
+            for &id in tasks.iter() {
+                executor.poll_task(id);
+            }
+            if main
+                && let Poll::Ready(output) =
+                    future.as_mut().poll(&mut Context::from_waker(&main_waker))
+            {
+                return output;
+            }
+        }
+    }
+
+    /// Nothing is runnable: skip the paused clock ahead or wait for a wake-up
+    /// 没有可运行的任务：让暂停的时钟向前跳跃或等待唤醒
+    fn idle(&self) {
+        let next_timer = self.time.next_timer();
+        let blocking = self.executor.blocking.load(Ordering::Acquire) > 0;
+        let elapsed = self.time.elapsed();
+
+        if self.time.is_paused() {
+            if let (Some(next), false) = (next_timer, blocking) {
+                self.time.advance(next.saturating_sub(elapsed));
+                return;
+            }
+            self.executor.ready.wait(IDLE_WAIT);
+        } else {
+            let until_timer = next_timer.map_or(IDLE_WAIT, |next| next.saturating_sub(elapsed));
+            self.executor.ready.wait(until_timer.min(IDLE_WAIT));
+        }
+    }
+}
+
+impl Default for TestRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for TestRuntime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestRuntime")
+            .field("seed", &self.seed)
+            .field("paused", &self.time.is_paused())
+            .field("elapsed", &self.time.elapsed())
+            .field("tasks", &self.executor.tasks.borrow().len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{spawn, spawn_blocking};
+    use crate::time::{self, Instant, advance, interval, sleep, timeout};
+
+    #[test]
+    fn test_auto_advance_skips_idle_time() {
+        let started = Instant::now();
+        let mut runtime = TestRuntime::new();
+        let virtual_elapsed = runtime.block_on(async {
+            let before = time::now();
+            let result = timeout(Duration::from_secs(3600), std::future::pending::<()>()).await;
+            assert!(result.is_err());
+            time::now() - before
+        });
+        assert_eq!(virtual_elapsed, Duration::from_secs(3600));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_advance_fires_timers_in_order() {
+        let mut runtime = TestRuntime::new();
+        runtime.block_on(async {
+            let fired = Arc::new(Mutex::new(Vec::new()));
+            for ms in [30, 10, 20] {
+                let fired = fired.clone();
+                spawn(async move {
+                    sleep(Duration::from_millis(ms)).await;
+                    fired.lock().unwrap().push(ms);
+                });
+            }
+
+            advance(Duration::from_millis(15)).await;
+            assert_eq!(*fired.lock().unwrap(), [10]);
+            advance(Duration::from_millis(15)).await;
+            assert_eq!(*fired.lock().unwrap(), [10, 20, 30]);
+        });
+    }
+
+    #[test]
+    fn test_interval_ticks_on_virtual_time() {
+        let mut runtime = TestRuntime::new();
+        runtime.block_on(async {
+            let start = time::now();
+            let mut ticker = interval(Duration::from_secs(60));
+            for _ in 0..3 {
+                ticker.tick().await;
+            }
+            assert_eq!(time::now() - start, Duration::from_secs(180));
+        });
+    }
+
+    #[test]
+    fn test_same_seed_same_order() {
+        fn run(seed: u64) -> Vec<usize> {
+            let mut runtime = TestRuntime::builder().seed(seed).build();
+            runtime.block_on(async {
+                let order = Arc::new(Mutex::new(Vec::new()));
+                let handles: Vec<_> = (0..8)
+                    .map(|i| {
+                        let order = order.clone();
+                        spawn(async move { order.lock().unwrap().push(i) })
+                    })
+                    .collect();
+                for handle in handles {
+                    handle.await.unwrap();
+                }
+                Arc::try_unwrap(order).unwrap().into_inner().unwrap()
+            })
+        }
+
+        assert_eq!(run(1), run(1));
+        let orders: BTreeSet<_> = (0..8).map(run).collect();
+        assert!(orders.len() > 1);
+    }
+
+    #[test]
+    fn test_blocking_work_holds_the_clock() {
+        let mut runtime = TestRuntime::new();
+        runtime.block_on(async {
+            let work = spawn_blocking(|| std::thread::sleep(Duration::from_millis(20)));
+            let result = timeout(Duration::from_secs(5), work).await;
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    fn test_resume_follows_real_time() {
+        let mut runtime = TestRuntime::builder().start_paused(false).build();
+        runtime.block_on(async {
+            let start = time::now();
+            sleep(Duration::from_millis(5)).await;
+            assert!(time::now() - start >= Duration::from_millis(5));
+            time::pause();
+            let paused_at = time::now();
+            sleep(Duration::from_secs(10)).await;
+            // Timers have millisecond resolution / 定时器精度为毫秒
+            let slept = time::now() - paused_at;
+            assert!(slept >= Duration::from_secs(10));
+            assert!(slept <= Duration::from_millis(10_001));
+        });
+    }
+}
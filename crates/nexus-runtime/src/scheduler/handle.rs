@@ -254,9 +254,10 @@ static VTABLE: std::task::RawWakerVTable =
     std::task::RawWakerVTable::new(clone_waker, wake, wake_by_ref, drop_waker);
 
 unsafe fn clone_waker(data: *const ()) -> RawWaker {
-    let handle = Arc::from_raw(data as *const SchedulerHandle);
-    let ptr = Arc::into_raw(handle.clone()) as *const ();
-    RawWaker::new(ptr, &VTABLE)
+    // The clone shares the handle; the original waker keeps its own reference
+    // 克隆共享句柄；原waker保留自己的引用
+    Arc::increment_strong_count(data as *const SchedulerHandle);
+    RawWaker::new(data, &VTABLE)
 }
 
 unsafe fn wake(data: *const ()) {
@@ -3,9 +3,10 @@
 
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use super::{RawTask, handle::WakeChannel, queue::LocalQueue};
+use crate::metrics::{WorkerMetrics, WorkerStats};
 
 /// Work-stealing scheduler
 /// 工作窃取调度器
@@ -30,6 +31,9 @@ struct WorkerContext {
     /// Local task queue
     /// 本地任务队列
     queue: Arc<LocalQueue>,
+    /// Worker counters
+    /// 工作器计数器
+    metrics: Arc<WorkerMetrics>,
     /// Thread handle
     /// 线程句柄
     thread_handle: Option<JoinHandle<()>>,
@@ -73,16 +77,19 @@ impl WorkStealingScheduler {
         for worker_id in 0..num_workers {
             let (queue, _wake) = &worker_queues[worker_id];
             let queues: Vec<_> = worker_queues.iter().map(|(q, _)| q.clone()).collect();
+            let metrics = Arc::new(WorkerMetrics::new());
 
             let state_clone = state.clone();
+            let metrics_clone = metrics.clone();
             let thread_name = format!("{}-{}", thread_name, worker_id);
 
             let thread_handle = thread::Builder::new().name(thread_name).spawn(move || {
-                Self::run_worker(worker_id, queues, state_clone);
+                Self::run_worker(worker_id, queues, state_clone, &metrics_clone);
             })?;
 
             workers.push(WorkerContext {
                 queue: queue.clone(),
+                metrics,
                 thread_handle: Some(thread_handle),
             });
         }
@@ -96,9 +103,11 @@ impl WorkStealingScheduler {
         worker_id: usize,
         queues: Vec<Arc<LocalQueue>>,
         state: Arc<std::sync::atomic::AtomicU8>,
+        metrics: &WorkerMetrics,
     ) {
         let my_queue = &queues[worker_id];
         let num_workers = queues.len();
+        let mut parked = false;
 
         while state.load(std::sync::atomic::Ordering::Relaxed) == STATE_RUNNING {
             // Try to get a task from local queue first
@@ -109,6 +118,7 @@ impl WorkStealingScheduler {
                 for i in 1..num_workers {
                     let target = (worker_id + i) % num_workers;
                     if let Some(task) = queues[target].pop() {
+                        metrics.record_steal();
                         return Some(task);
                     }
                 }
@@ -116,14 +126,23 @@ impl WorkStealingScheduler {
             });
 
             if let Some(task) = task {
+                if std::mem::take(&mut parked) {
+                    metrics.record_unpark();
+                }
+
                 // Execute the task
                 // 执行任务
                 // TODO: Actually execute the future (Phase 1: placeholder)
                 // TODO: 实际执行 future（第1阶段：占位符）
+                let started = Instant::now();
                 let _ = task;
+                metrics.record_poll(started.elapsed());
             } else {
                 // No tasks available, park briefly
                 // 没有可用任务，短暂暂停
+                if !std::mem::replace(&mut parked, true) {
+                    metrics.record_park();
+                }
                 thread::sleep(Duration::from_millis(1));
             }
         }
@@ -180,6 +199,17 @@ impl WorkStealingScheduler {
     pub const fn num_workers(&self) -> usize {
         self.workers.len()
     }
+
+    /// Get a snapshot of every worker's statistics
+    /// 获取每个工作器统计信息的快照
+    #[must_use]
+    pub fn worker_stats(&self) -> Vec<WorkerStats> {
+        self.workers
+            .iter()
+            .enumerate()
+            .map(|(worker_id, worker)| worker.metrics.snapshot(worker_id, worker.queue.len()))
+            .collect()
+    }
 }
 
 impl Drop for WorkStealingScheduler {
@@ -325,4 +355,39 @@ mod tests {
         assert_eq!(config.num_workers, 0); // 0 means auto / 0表示自动
         assert_eq!(config.queue_size, 256);
     }
+
+    #[test]
+    fn test_worker_stats() {
+        let scheduler = WorkStealingConfig::new().worker_threads(2).build().unwrap();
+        for _ in 0..10 {
+            scheduler.submit(std::ptr::null()).unwrap();
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let stats = loop {
+            let stats = scheduler.worker_stats();
+            let polled: u64 = stats.iter().map(|worker| worker.tasks_polled).sum();
+            if polled == 10 || Instant::now() > deadline {
+                break stats;
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats.iter().map(|worker| worker.tasks_polled).sum::<u64>(), 10);
+        assert_eq!(
+            stats
+                .iter()
+                .map(|worker| worker.poll_time.count())
+                .sum::<u64>(),
+            10
+        );
+        assert!(stats.iter().all(|worker| worker.queue_depth == 0));
+        assert!(stats.iter().all(|worker| worker.parks >= 1));
+        assert!(
+            stats
+                .iter()
+                .all(|worker| (0.0..=1.0).contains(&worker.busy_ratio))
+        );
+    }
 }
@@ -0,0 +1,372 @@
+//! Unix signals and coordinated shutdown
+//! Unix信号与协调关闭
+//!
+//! # Overview / 概述
+//!
+//! [`signal`] turns a process signal into a stream of events: every delivery of
+//! the signal after the stream was created makes [`Signal::recv`] complete once
+//! (deliveries arriving close together may be merged). [`ctrl_c`] waits for the
+//! next SIGINT and [`shutdown_signal`] for SIGINT or SIGTERM, which is what
+//! container orchestrators send to stop an application.
+//!
+//! [`signal`] 将进程信号转换为事件流：流创建之后每次信号送达都会使 [`Signal::recv`]
+//! 完成一次（时间上接近的多次送达可能会合并）。[`ctrl_c`] 等待下一个SIGINT，
+//! [`shutdown_signal`] 等待SIGINT或SIGTERM，即容器编排器用于停止应用的信号。
+//!
+//! Signal handlers write to a self-pipe whose read end is registered with an I/O
+//! driver on a dispatcher thread, which wakes the waiting tasks. Once a signal is
+//! listened for, its default action (such as terminating the process) no longer
+//! applies.
+//!
+//! 信号处理函数写入一个自管道，其读端在分发线程上注册到I/O driver，由该线程唤醒等待的任务。
+//! 一旦开始监听某个信号，其默认动作（例如终止进程）将不再生效。
+//!
+//! [`ShutdownToken`] coordinates the shutdown of framework components in phases.
+//!
+//! [`ShutdownToken`] 按阶段协调框架组件的关闭。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::signal::{self, ShutdownToken, SignalKind};
+//!
+//! async fn run(token: ShutdownToken) -> std::io::Result<()> {
+//!     let mut hangup = signal::signal(SignalKind::Hangup)?;
+//!     nexus_runtime::spawn(async move {
+//!         loop {
+//!             hangup.recv().await;
+//!             reload_config();
+//!         }
+//!     });
+//!
+//!     token.shutdown_on_signal().await?;
+//!     Ok(())
+//! }
+//! ```
+
+mod shutdown;
+
+pub use shutdown::{ShutdownListener, ShutdownToken};
+
+use std::io;
+use std::task::{Context, Poll};
+
+/// A kind of process signal that can be listened for
+/// 可以监听的进程信号种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignalKind {
+    /// SIGINT, sent by Ctrl+C / SIGINT，由Ctrl+C发送
+    Interrupt,
+    /// SIGTERM, the polite stop request / SIGTERM，礼貌的停止请求
+    Terminate,
+    /// SIGHUP, conventionally "reload configuration" / SIGHUP，通常表示“重新加载配置”
+    Hangup,
+    /// SIGQUIT / SIGQUIT
+    Quit,
+    /// SIGUSR1 / SIGUSR1
+    User1,
+    /// SIGUSR2 / SIGUSR2
+    User2,
+}
+
+impl SignalKind {
+    /// Get the platform signal number
+    /// 获取平台信号编号
+    #[cfg(unix)]
+    #[must_use]
+    pub const fn as_raw(self) -> i32 {
+        match self {
+            Self::Interrupt => libc::SIGINT,
+            Self::Terminate => libc::SIGTERM,
+            Self::Hangup => libc::SIGHUP,
+            Self::Quit => libc::SIGQUIT,
+            Self::User1 => libc::SIGUSR1,
+            Self::User2 => libc::SIGUSR2,
+        }
+    }
+}
+
+impl std::fmt::Display for SignalKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Interrupt => "SIGINT",
+            Self::Terminate => "SIGTERM",
+            Self::Hangup => "SIGHUP",
+            Self::Quit => "SIGQUIT",
+            Self::User1 => "SIGUSR1",
+            Self::User2 => "SIGUSR2",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Stream of deliveries of one signal, created by [`signal`]
+/// 单个信号的送达事件流，由 [`signal`] 创建
+#[derive(Debug)]
+pub struct Signal {
+    kind: SignalKind,
+    /// Deliveries already observed / 已观察到的送达次数
+    seen: u64,
+}
+
+impl Signal {
+    /// Get the signal this stream listens for
+    /// 获取此流监听的信号
+    #[must_use]
+    pub fn kind(&self) -> SignalKind {
+        self.kind
+    }
+
+    /// Wait for the next delivery of the signal
+    /// 等待信号的下一次送达
+    pub async fn recv(&mut self) {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await;
+    }
+
+    /// Poll for the next delivery of the signal
+    /// 轮询信号的下一次送达
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        imp::poll(self.kind, &mut self.seen, cx)
+    }
+}
+
+/// Listen for `kind`
+/// 监听 `kind`
+///
+/// # Errors / 错误
+///
+/// Returns an error if the signal handler or the dispatcher thread cannot be set
+/// up, or on platforms without Unix signals.
+/// 如果无法设置信号处理函数或分发线程，或在没有Unix信号的平台上，则返回错误。
+pub fn signal(kind: SignalKind) -> io::Result<Signal> {
+    let seen = imp::register(kind)?;
+    Ok(Signal { kind, seen })
+}
+
+/// Wait for the next Ctrl+C (SIGINT)
+/// 等待下一次Ctrl+C（SIGINT）
+///
+/// # Errors / 错误
+///
+/// See [`signal`]. / 见 [`signal`]。
+pub async fn ctrl_c() -> io::Result<()> {
+    signal(SignalKind::Interrupt)?.recv().await;
+    Ok(())
+}
+
+/// Wait for SIGINT or SIGTERM, returning the one received
+/// 等待SIGINT或SIGTERM，返回收到的信号
+///
+/// # Errors / 错误
+///
+/// See [`signal`]. / 见 [`signal`]。
+pub async fn shutdown_signal() -> io::Result<SignalKind> {
+    let mut interrupt = signal(SignalKind::Interrupt)?;
+    let mut terminate = signal(SignalKind::Terminate)?;
+    Ok(std::future::poll_fn(|cx| {
+        if interrupt.poll_recv(cx).is_ready() {
+            Poll::Ready(SignalKind::Interrupt)
+        } else if terminate.poll_recv(cx).is_ready() {
+            Poll::Ready(SignalKind::Terminate)
+        } else {
+            Poll::Pending
+        }
+    })
+    .await)
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::io;
+    use std::os::fd::RawFd;
+    use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
+    use std::sync::{Mutex, OnceLock};
+    use std::task::{Context, Poll, Waker};
+    use std::thread;
+
+    use super::SignalKind;
+    use crate::driver::{DriverFactory, DriverType, Interest};
+
+    /// Signal numbers below this are supported / 支持小于此值的信号编号
+    const MAX_SIGNAL: usize = 32;
+
+    /// Deliveries per signal number, bumped by the handler
+    /// 每个信号编号的送达次数，由处理函数递增
+    static DELIVERIES: [AtomicU64; MAX_SIGNAL] = [const { AtomicU64::new(0) }; MAX_SIGNAL];
+
+    /// Write end of the self-pipe, read by the handler
+    /// 自管道的写端，供处理函数读取
+    static PIPE_WRITE: AtomicI32 = AtomicI32::new(-1);
+
+    /// Process-wide signal state
+    /// 进程级信号状态
+    struct Globals {
+        /// Signals whose handler is installed / 已安装处理函数的信号
+        installed: Mutex<[bool; MAX_SIGNAL]>,
+        /// Tasks waiting for any signal / 等待任意信号的任务
+        wakers: Mutex<Vec<Waker>>,
+    }
+
+    static GLOBALS: OnceLock<Result<Globals, String>> = OnceLock::new();
+
+    /// Signal handler: only async-signal-safe operations
+    /// 信号处理函数：仅包含异步信号安全的操作
+    extern "C" fn on_signal(signum: libc::c_int) {
+        if let Some(count) = usize::try_from(signum).ok().and_then(|n| DELIVERIES.get(n)) {
+            count.fetch_add(1, Ordering::SeqCst);
+        }
+        let fd = PIPE_WRITE.load(Ordering::SeqCst);
+        if fd >= 0 {
+            // A full pipe already guarantees a wake-up / 管道已满时已保证会唤醒
+            unsafe { libc::write(fd, [1u8].as_ptr().cast(), 1) };
+        }
+    }
+
+    /// Create the self-pipe and start the dispatcher thread
+    /// 创建自管道并启动分发线程
+    fn init() -> io::Result<Globals> {
+        let mut fds = [0 as RawFd; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        for fd in fds {
+            unsafe {
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+                let flags = libc::fcntl(fd, libc::F_GETFL);
+                libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+            }
+        }
+        let [read_fd, write_fd] = fds;
+
+        #[cfg(target_os = "linux")]
+        let driver_type = DriverType::Epoll;
+        #[cfg(not(target_os = "linux"))]
+        let driver_type = DriverType::Auto;
+        let driver = DriverFactory::create(driver_type)?;
+        driver.register(read_fd, Interest::readable())?;
+
+        thread::Builder::new()
+            .name("nexus-signal".to_string())
+            .spawn(move || {
+                let mut buf = [0u8; 64];
+                loop {
+                    if driver.wait().is_err() {
+                        thread::yield_now();
+                    }
+                    while driver.get_completion().is_some() {
+                        driver.advance_completion();
+                    }
+                    while unsafe { libc::read(read_fd, buf.as_mut_ptr().cast(), buf.len()) } > 0 {}
+                    wake_all();
+                }
+            })?;
+
+        PIPE_WRITE.store(write_fd, Ordering::SeqCst);
+        Ok(Globals {
+            installed: Mutex::new([false; MAX_SIGNAL]),
+            wakers: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn globals() -> io::Result<&'static Globals> {
+        GLOBALS
+            .get_or_init(|| init().map_err(|err| err.to_string()))
+            .as_ref()
+            .map_err(|err| io::Error::other(format!("signal dispatcher unavailable: {err}")))
+    }
+
+    /// Wake every task waiting for a signal; each checks its own counter
+    /// 唤醒所有等待信号的任务；各自检查自己的计数
+    fn wake_all() {
+        let Ok(globals) = globals() else { return };
+        let wakers = std::mem::take(&mut *globals.wakers.lock().unwrap());
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Install the handler for `kind` once, returning the current delivery count
+    /// 为 `kind` 安装一次处理函数，返回当前送达次数
+    pub(super) fn register(kind: SignalKind) -> io::Result<u64> {
+        let globals = globals()?;
+        let signum = kind.as_raw() as usize;
+        let mut installed = globals.installed.lock().unwrap();
+        if !installed[signum] {
+            unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as usize;
+                action.sa_flags = libc::SA_RESTART;
+                libc::sigemptyset(&mut action.sa_mask);
+                if libc::sigaction(kind.as_raw(), &action, std::ptr::null_mut()) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            installed[signum] = true;
+        }
+        Ok(DELIVERIES[signum].load(Ordering::SeqCst))
+    }
+
+    pub(super) fn poll(kind: SignalKind, seen: &mut u64, cx: &mut Context<'_>) -> Poll<()> {
+        let Ok(globals) = globals() else {
+            return Poll::Pending;
+        };
+        // Checked under the lock so a wake-up between check and registration is not lost
+        // 在锁内检查，避免检查与注册之间的唤醒丢失
+        let mut wakers = globals.wakers.lock().unwrap();
+        let count = DELIVERIES[kind.as_raw() as usize].load(Ordering::SeqCst);
+        if count != *seen {
+            *seen = count;
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::io;
+    use std::task::{Context, Poll};
+
+    use super::SignalKind;
+
+    pub(super) fn register(_kind: SignalKind) -> io::Result<u64> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Signals are not yet implemented on this platform",
+        ))
+    }
+
+    pub(super) fn poll(_kind: SignalKind, _seen: &mut u64, _cx: &mut Context<'_>) -> Poll<()> {
+        Poll::Pending
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::task::block_on;
+
+    #[test]
+    fn test_signal_stream_receives_deliveries() {
+        let mut user1 = signal(SignalKind::User1).unwrap();
+        assert_eq!(user1.kind(), SignalKind::User1);
+
+        unsafe { libc::raise(libc::SIGUSR1) };
+        block_on(async move {
+            user1.recv().await;
+            let waker = std::task::Waker::noop();
+            assert!(
+                user1
+                    .poll_recv(&mut Context::from_waker(waker))
+                    .is_pending()
+            );
+        });
+    }
+
+    #[test]
+    fn test_signal_kind_display() {
+        assert_eq!(SignalKind::Terminate.to_string(), "SIGTERM");
+        assert_eq!(SignalKind::Hangup.as_raw(), libc::SIGHUP);
+    }
+}
@@ -0,0 +1,353 @@
+//! Coordinated, phased shutdown
+//! 协调的分阶段关闭
+//!
+//! # Overview / 概述
+//!
+//! A [`ShutdownToken`] is shared by every component that has to stop with the
+//! application. Simple components await [`ShutdownToken::cancelled`]. Components
+//! whose stop order matters [`subscribe`](ShutdownToken::subscribe) with a phase:
+//! [`ShutdownToken::shutdown`] stops phases from the highest to the lowest, and a
+//! phase starts only after every listener of the previous one has been dropped.
+//!
+//! [`ShutdownToken`] 由所有需要随应用一起停止的组件共享。简单的组件等待
+//! [`ShutdownToken::cancelled`]。对停止顺序有要求的组件以某个阶段
+//! [`订阅`](ShutdownToken::subscribe)：[`ShutdownToken::shutdown`] 按从高到低的顺序停止各阶段，
+//! 只有上一阶段的所有监听器都被丢弃后，下一阶段才会开始。
+//!
+//! # Equivalent to Spring / 等价于 Spring
+//!
+//! - `SmartLifecycle#getPhase` - higher phases stop first / 阶段越高越先停止
+//! - `ConfigurableApplicationContext#registerShutdownHook`
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::signal::ShutdownToken;
+//!
+//! async fn main_loop(server: Server, scheduler: TaskScheduler) {
+//!     let token = ShutdownToken::new();
+//!
+//!     // The server drains first / 服务器最先排空
+//!     let listener = token.subscribe(100);
+//!     nexus_runtime::spawn(server.run_with_shutdown(handler, async move {
+//!         listener.wait().await;
+//!     }));
+//!
+//!     // Then the scheduler stops / 然后调度器停止
+//!     let listener = token.subscribe(50);
+//!     nexus_runtime::spawn(async move {
+//!         listener.wait().await;
+//!         scheduler.shutdown().await;
+//!         drop(listener);
+//!     });
+//!
+//!     token.shutdown_on_signal().await.unwrap();
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use crate::sync::Notify;
+
+/// Shutdown progress guarded by a lock
+/// 由锁保护的关闭进度
+#[derive(Default)]
+struct State {
+    /// Shutdown was requested / 已请求关闭
+    triggered: bool,
+    /// Phase currently stopping; `i32::MIN` once finished / 当前正在停止的阶段；完成后为 `i32::MIN`
+    stopping: Option<i32>,
+    /// Live listeners per phase / 每个阶段存活的监听器数
+    listeners: BTreeMap<i32, usize>,
+}
+
+struct Inner {
+    state: Mutex<State>,
+    /// Notified on every state change / 每次状态变化时通知
+    changed: Notify,
+}
+
+impl Inner {
+    /// Wait until `done` holds for the state
+    /// 等待直到状态满足 `done`
+    async fn wait_until(&self, done: impl Fn(&State) -> bool) {
+        loop {
+            // Created before the check so a change in between is not missed
+            // 在检查之前创建，以免错过其间的变化
+            let changed = self.changed.notified();
+            if done(&self.state.lock().unwrap()) {
+                return;
+            }
+            changed.await;
+        }
+    }
+}
+
+/// Handle for requesting and observing application shutdown
+/// 用于请求和观察应用关闭的句柄
+///
+/// Cloning the token yields another handle to the same shutdown.
+/// 克隆令牌会得到同一关闭过程的另一个句柄。
+#[derive(Clone)]
+pub struct ShutdownToken {
+    inner: Arc<Inner>,
+}
+
+impl ShutdownToken {
+    /// Create a token for a new shutdown
+    /// 为新的关闭过程创建令牌
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                state: Mutex::new(State::default()),
+                changed: Notify::new(),
+            }),
+        }
+    }
+
+    /// Check whether shutdown has been requested
+    /// 检查是否已请求关闭
+    #[must_use]
+    pub fn is_shutdown(&self) -> bool {
+        self.inner.state.lock().unwrap().triggered
+    }
+
+    /// Wait until shutdown is requested
+    /// 等待直到请求关闭
+    pub async fn cancelled(&self) {
+        self.inner.wait_until(|state| state.triggered).await;
+    }
+
+    /// Register a component that stops in `phase`
+    /// 注册一个在 `phase` 阶段停止的组件
+    ///
+    /// Higher phases stop first. Drop the listener once the component has
+    /// stopped; until then lower phases wait. A listener created after its phase
+    /// already passed is told to stop immediately.
+    /// 阶段越高越先停止。组件停止后丢弃监听器；在此之前较低的阶段会等待。
+    /// 在其阶段已经过去之后创建的监听器会立即收到停止通知。
+    #[must_use]
+    pub fn subscribe(&self, phase: i32) -> ShutdownListener {
+        *self
+            .inner
+            .state
+            .lock()
+            .unwrap()
+            .listeners
+            .entry(phase)
+            .or_default() += 1;
+        ShutdownListener {
+            inner: self.inner.clone(),
+            phase,
+        }
+    }
+
+    /// Request shutdown and stop every phase in order
+    /// 请求关闭并按顺序停止每个阶段
+    ///
+    /// Returns once the last phase has stopped. Concurrent callers all wait for
+    /// the same shutdown. Wrap the call in [`crate::time::timeout`] to bound it.
+    /// 最后一个阶段停止后返回。并发调用者都等待同一关闭过程。
+    /// 可用 [`crate::time::timeout`] 包装调用以限制时长。
+    pub async fn shutdown(&self) {
+        let first = {
+            let mut state = self.inner.state.lock().unwrap();
+            !std::mem::replace(&mut state.triggered, true)
+        };
+        if !first {
+            self.inner
+                .wait_until(|state| state.stopping == Some(i32::MIN))
+                .await;
+            return;
+        }
+        self.inner.changed.notify_waiters();
+        tracing::info!("Shutdown requested");
+
+        let mut below = i32::MAX;
+        loop {
+            let next = {
+                let mut state = self.inner.state.lock().unwrap();
+                let next = state
+                    .listeners
+                    .range(..=below)
+                    .next_back()
+                    .map(|(&phase, _)| phase);
+                state.stopping = Some(next.unwrap_or(i32::MIN));
+                next
+            };
+            self.inner.changed.notify_waiters();
+            let Some(phase) = next else { break };
+
+            tracing::debug!(phase, "Stopping shutdown phase");
+            self.inner
+                .wait_until(|state| !state.listeners.contains_key(&phase))
+                .await;
+            match phase.checked_sub(1) {
+                Some(lower) => below = lower,
+                None => break,
+            }
+        }
+
+        let mut state = self.inner.state.lock().unwrap();
+        state.stopping = Some(i32::MIN);
+        drop(state);
+        self.inner.changed.notify_waiters();
+        tracing::info!("Shutdown complete");
+    }
+
+    /// Wait for SIGINT or SIGTERM, then run [`ShutdownToken::shutdown`]
+    /// 等待SIGINT或SIGTERM，然后执行 [`ShutdownToken::shutdown`]
+    ///
+    /// Also returns early, with `None`, if shutdown was requested in another way.
+    /// 如果通过其他方式请求了关闭，也会提前返回 `None`。
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns an error if the signals cannot be listened for.
+    /// 如果无法监听信号则返回错误。
+    pub async fn shutdown_on_signal(&self) -> std::io::Result<Option<super::SignalKind>> {
+        let signal = super::shutdown_signal();
+        let mut signal = std::pin::pin!(signal);
+        let mut cancelled = std::pin::pin!(self.cancelled());
+        let received = std::future::poll_fn(|cx| {
+            if let std::task::Poll::Ready(result) = signal.as_mut().poll(cx) {
+                return std::task::Poll::Ready(result.map(Some));
+            }
+            cancelled.as_mut().poll(cx).map(|()| Ok(None))
+        })
+        .await?;
+        if let Some(kind) = received {
+            tracing::info!(signal = %kind, "Received shutdown signal");
+        }
+        self.shutdown().await;
+        Ok(received)
+    }
+}
+
+impl Default for ShutdownToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ShutdownToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.inner.state.lock().unwrap();
+        f.debug_struct("ShutdownToken")
+            .field("triggered", &state.triggered)
+            .field("stopping", &state.stopping)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A component's registration in a phase, created by [`ShutdownToken::subscribe`]
+/// 组件在某个阶段的注册，由 [`ShutdownToken::subscribe`] 创建
+///
+/// Dropping it reports that the component has stopped.
+/// 丢弃它表示组件已经停止。
+pub struct ShutdownListener {
+    inner: Arc<Inner>,
+    phase: i32,
+}
+
+impl ShutdownListener {
+    /// Get the phase this listener stops in
+    /// 获取此监听器停止所在的阶段
+    #[must_use]
+    pub fn phase(&self) -> i32 {
+        self.phase
+    }
+
+    /// Wait until this listener's phase starts stopping
+    /// 等待直到此监听器的阶段开始停止
+    pub async fn wait(&self) {
+        let phase = self.phase;
+        self.inner
+            .wait_until(|state| state.stopping.is_some_and(|stopping| stopping <= phase))
+            .await;
+    }
+}
+
+impl Drop for ShutdownListener {
+    fn drop(&mut self) {
+        let mut state = self.inner.state.lock().unwrap();
+        if let Some(count) = state.listeners.get_mut(&self.phase) {
+            *count -= 1;
+            if *count == 0 {
+                state.listeners.remove(&self.phase);
+            }
+        }
+        drop(state);
+        self.inner.changed.notify_waiters();
+    }
+}
+
+impl std::fmt::Debug for ShutdownListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShutdownListener")
+            .field("phase", &self.phase)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{block_on, spawn};
+    use crate::time::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn test_phases_stop_highest_first() {
+        let order = block_on(async {
+            let token = ShutdownToken::new();
+            let order = Arc::new(Mutex::new(Vec::new()));
+            let mut tasks = Vec::new();
+            for (phase, work_ms) in [(0, 1), (100, 30), (50, 10)] {
+                let listener = token.subscribe(phase);
+                let order = order.clone();
+                tasks.push(spawn(async move {
+                    listener.wait().await;
+                    order.lock().unwrap().push(format!("start {phase}"));
+                    sleep(Duration::from_millis(work_ms)).await;
+                    order.lock().unwrap().push(format!("stop {phase}"));
+                }));
+            }
+            assert!(!token.is_shutdown());
+            token.shutdown().await;
+            assert!(token.is_shutdown());
+            for task in tasks {
+                task.await.unwrap();
+            }
+            order.lock().unwrap().clone()
+        });
+        assert_eq!(
+            order,
+            [
+                "start 100",
+                "stop 100",
+                "start 50",
+                "stop 50",
+                "start 0",
+                "stop 0"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cancelled_and_late_listeners() {
+        block_on(async {
+            let token = ShutdownToken::new();
+            let watcher = token.clone();
+            let cancelled = spawn(async move { watcher.cancelled().await });
+            token.shutdown().await;
+            cancelled.await.unwrap();
+
+            // Shutdown already finished / 关闭已经完成
+            token.subscribe(i32::MAX).wait().await;
+            token.shutdown().await;
+        });
+    }
+}
@@ -0,0 +1,399 @@
+//! Multi-producer, multi-consumer broadcast channel
+//! 多生产者、多消费者广播通道
+//!
+//! # Overview / 概述
+//!
+//! Every [`Receiver`] sees every value sent after it subscribed. The channel
+//! keeps the last `capacity` values; a receiver that falls further behind skips
+//! to the oldest value still kept and is told how many it missed through
+//! [`RecvError::Lagged`].
+//!
+//! 每个 [`Receiver`] 都能看到其订阅之后发送的所有值。通道保留最近的 `capacity` 个值；
+//! 落后更多的接收器会跳到仍保留的最早值，并通过 [`RecvError::Lagged`] 得知错过了多少个。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::sync::broadcast;
+//!
+//! async fn example() {
+//!     let (tx, mut rx1) = broadcast::channel(16);
+//!     let mut rx2 = tx.subscribe();
+//!
+//!     tx.send("event").unwrap();
+//!     assert_eq!(rx1.recv().await, Ok("event"));
+//!     assert_eq!(rx2.recv().await, Ok("event"));
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+/// Error returned by [`Sender::send`] when there are no receivers
+/// 没有接收器时 [`Sender::send`] 返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> std::fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Channel closed")
+    }
+}
+
+impl<T: std::fmt::Debug> std::error::Error for SendError<T> {}
+
+/// Error returned by [`Receiver::recv`]
+/// [`Receiver::recv`] 返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// All senders are gone and every kept value was received
+    /// 所有发送器已不存在，且所有保留的值都已接收
+    Closed,
+    /// The receiver fell behind and skipped this many values
+    /// 接收器落后并跳过了这么多个值
+    Lagged(u64),
+}
+
+impl std::fmt::Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecvError::Closed => write!(f, "Channel closed"),
+            RecvError::Lagged(n) => write!(f, "Receiver lagged by {} values", n),
+        }
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Error returned by [`Receiver::try_recv`]
+/// [`Receiver::try_recv`] 返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No new value is available
+    /// 没有新值可用
+    Empty,
+    /// All senders are gone and every kept value was received
+    /// 所有发送器已不存在，且所有保留的值都已接收
+    Closed,
+    /// The receiver fell behind and skipped this many values
+    /// 接收器落后并跳过了这么多个值
+    Lagged(u64),
+}
+
+impl std::fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "Channel empty"),
+            TryRecvError::Closed => write!(f, "Channel closed"),
+            TryRecvError::Lagged(n) => write!(f, "Receiver lagged by {} values", n),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+/// Channel state guarded by a lock
+/// 由锁保护的通道状态
+struct State<T> {
+    /// Kept values, oldest first / 保留的值，最早的在前
+    buffer: VecDeque<T>,
+    /// Sequence number of `buffer[0]` / `buffer[0]` 的序号
+    head: u64,
+    /// Values kept at most / 最多保留的值数量
+    capacity: usize,
+    /// Live senders / 存活的发送器数量
+    senders: usize,
+    /// Live receivers / 存活的接收器数量
+    receivers: usize,
+    /// Next receiver ID / 下一个接收器ID
+    next_id: u64,
+    /// Receivers waiting for a value / 等待值的接收器
+    wakers: Vec<(u64, Waker)>,
+}
+
+impl<T> State<T> {
+    /// Sequence number the next value will get
+    /// 下一个值将获得的序号
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+
+    fn new_receiver(&mut self, shared: Arc<Mutex<State<T>>>) -> Receiver<T> {
+        self.receivers += 1;
+        let id = self.next_id;
+        self.next_id += 1;
+        Receiver {
+            shared,
+            id,
+            next: self.tail(),
+        }
+    }
+}
+
+/// Create a broadcast channel keeping up to `capacity` values
+/// 创建最多保留 `capacity` 个值的广播通道
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0.
+/// 如果 `capacity` 为0则恐慌。
+#[must_use]
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "broadcast channel capacity must be positive");
+    let shared = Arc::new(Mutex::new(State {
+        buffer: VecDeque::with_capacity(capacity),
+        head: 0,
+        capacity,
+        senders: 1,
+        receivers: 0,
+        next_id: 0,
+        wakers: Vec::new(),
+    }));
+    let receiver = shared.lock().unwrap().new_receiver(shared.clone());
+    (Sender { shared }, receiver)
+}
+
+/// Sending half of a broadcast channel
+/// 广播通道的发送端
+pub struct Sender<T> {
+    shared: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send a value to every receiver, returning how many there are
+    /// 向所有接收器发送值，返回接收器数量
+    ///
+    /// # Errors
+    ///
+    /// Returns the value back if there are no receivers.
+    /// 如果没有接收器则原样返回该值。
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let mut state = self.shared.lock().unwrap();
+        if state.receivers == 0 {
+            return Err(SendError(value));
+        }
+        if state.buffer.len() == state.capacity {
+            state.buffer.pop_front();
+            state.head += 1;
+        }
+        state.buffer.push_back(value);
+        let receivers = state.receivers;
+        let wakers = std::mem::take(&mut state.wakers);
+        drop(state);
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+        Ok(receivers)
+    }
+
+    /// Create a receiver that sees values sent from now on
+    /// 创建一个接收从现在起发送的值的接收器
+    #[must_use]
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.shared
+            .lock()
+            .unwrap()
+            .new_receiver(self.shared.clone())
+    }
+
+    /// Get the number of live receivers
+    /// 获取存活的接收器数量
+    #[must_use]
+    pub fn receiver_count(&self) -> usize {
+        self.shared.lock().unwrap().receivers
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().unwrap().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock().unwrap();
+        state.senders -= 1;
+        let wakers = if state.senders == 0 {
+            std::mem::take(&mut state.wakers)
+        } else {
+            Vec::new()
+        };
+        drop(state);
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// Receiving half of a broadcast channel
+/// 广播通道的接收端
+pub struct Receiver<T> {
+    shared: Arc<Mutex<State<T>>>,
+    /// Receiver ID for its waker slot / 用于waker槽位的接收器ID
+    id: u64,
+    /// Sequence number of the next value to receive / 下一个要接收的值的序号
+    next: u64,
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receive the next value
+    /// 接收下一个值
+    ///
+    /// Cancellation safe: dropping the future never loses a value.
+    /// 取消安全：丢弃该future永远不会丢失值。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError::Lagged`] after falling behind, then continues with the
+    /// oldest kept value; returns [`RecvError::Closed`] once all senders are gone.
+    /// 落后时返回 [`RecvError::Lagged`]，之后从最早保留的值继续；
+    /// 所有发送器都不存在后返回 [`RecvError::Closed`]。
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        std::future::poll_fn(|cx| match self.try_recv() {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError::Closed)),
+            Err(TryRecvError::Lagged(n)) => Poll::Ready(Err(RecvError::Lagged(n))),
+            Err(TryRecvError::Empty) => {
+                let mut state = self.shared.lock().unwrap();
+                // A value may have arrived since `try_recv` released the lock
+                // `try_recv` 释放锁之后可能已有值到达
+                if state.tail() > self.next || state.senders == 0 {
+                    drop(state);
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                match state.wakers.iter_mut().find(|(id, _)| *id == self.id) {
+                    Some((_, waker)) if waker.will_wake(cx.waker()) => {},
+                    Some((_, waker)) => waker.clone_from(cx.waker()),
+                    None => state.wakers.push((self.id, cx.waker().clone())),
+                }
+                Poll::Pending
+            },
+        })
+        .await
+    }
+
+    /// Receive the next value if one is available
+    /// 如果有可用值则接收下一个值
+    ///
+    /// # Errors
+    ///
+    /// See [`TryRecvError`].
+    /// 参见 [`TryRecvError`]。
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let state = self.shared.lock().unwrap();
+        if self.next < state.head {
+            let missed = state.head - self.next;
+            self.next = state.head;
+            return Err(TryRecvError::Lagged(missed));
+        }
+        match state.buffer.get((self.next - state.head) as usize) {
+            Some(value) => {
+                self.next += 1;
+                Ok(value.clone())
+            },
+            None if state.senders == 0 => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Create another receiver at the same position
+    /// 创建位于相同位置的另一个接收器
+    #[must_use]
+    pub fn resubscribe(&self) -> Self {
+        let mut receiver = self
+            .shared
+            .lock()
+            .unwrap()
+            .new_receiver(self.shared.clone());
+        receiver.next = self.next;
+        receiver
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock().unwrap();
+        state.receivers -= 1;
+        state.wakers.retain(|(id, _)| *id != self.id);
+    }
+}
+
+impl<T> std::fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver")
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{block_on, spawn};
+
+    #[test]
+    fn test_every_receiver_gets_every_value() {
+        let (tx, mut a) = channel(4);
+        let mut b = tx.subscribe();
+        assert_eq!(tx.send(1).unwrap(), 2);
+        assert_eq!(tx.send(2).unwrap(), 2);
+        assert_eq!(a.try_recv(), Ok(1));
+        assert_eq!(a.try_recv(), Ok(2));
+        assert_eq!(a.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(b.try_recv(), Ok(1));
+
+        drop(tx);
+        assert_eq!(b.try_recv(), Ok(2));
+        assert_eq!(b.try_recv(), Err(TryRecvError::Closed));
+    }
+
+    #[test]
+    fn test_lagging_receiver_skips_ahead() {
+        let (tx, mut rx) = channel(2);
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Lagged(3)));
+        assert_eq!(rx.try_recv(), Ok(3));
+        assert_eq!(rx.try_recv(), Ok(4));
+    }
+
+    #[test]
+    fn test_recv_waits_for_sender() {
+        let received = block_on(async {
+            let (tx, mut rx) = channel(8);
+            spawn(async move {
+                for word in ["a", "b"] {
+                    tx.send(word).unwrap();
+                }
+            });
+            let mut received = Vec::new();
+            while let Ok(word) = rx.recv().await {
+                received.push(word);
+            }
+            received
+        });
+        assert_eq!(received, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_send_without_receivers_fails() {
+        let (tx, rx) = channel(1);
+        drop(rx);
+        assert_eq!(tx.send(7), Err(SendError(7)));
+    }
+}
@@ -0,0 +1,61 @@
+//! Async synchronization primitives
+//! 异步同步原语
+//!
+//! # Overview / 概述
+//!
+//! Locks, a semaphore, notifications and channels whose waiting operations
+//! suspend the task instead of blocking the thread:
+//!
+//! - [`Mutex`] / [`RwLock`] - locks whose guards may be held across `.await`
+//! - [`Semaphore`] - counting semaphore with fair FIFO queueing
+//! - [`Notify`] - wake one or all waiting tasks
+//! - [`oneshot`] - send a single value
+//! - [`broadcast`] - every receiver sees every value
+//! - [`watch`] - receivers observe the latest value
+//!
+//! 等待操作会挂起任务而不是阻塞线程的锁、信号量、通知和通道：
+//!
+//! - [`Mutex`] / [`RwLock`] - 守卫可以跨越 `.await` 持有的锁
+//! - [`Semaphore`] - 具有公平FIFO排队的计数信号量
+//! - [`Notify`] - 唤醒一个或全部等待中的任务
+//! - [`oneshot`] - 发送单个值
+//! - [`broadcast`] - 每个接收器都能看到每个值
+//! - [`watch`] - 接收器观察最新的值
+//!
+//! Every waiting future is cancellation safe: dropping it before completion
+//! leaves the primitive as if the call had never been made. Multi-producer
+//! queues are in [`crate::channel`].
+//!
+//! 所有等待中的future都是取消安全的：在完成前丢弃它，原语的状态就如同从未调用过一样。
+//! 多生产者队列位于 [`crate::channel`]。
+//!
+//! # Equivalent to Spring / 等价于 Spring
+//!
+//! - `java.util.concurrent.locks.ReentrantLock` / `ReentrantReadWriteLock`
+//! - `java.util.concurrent.Semaphore` (fair mode)
+//! - `java.util.concurrent.CompletableFuture` (oneshot)
+
+mod mutex;
+mod notify;
+mod rwlock;
+mod semaphore;
+
+pub mod broadcast;
+pub mod oneshot;
+pub mod watch;
+
+pub use mutex::{Mutex, MutexGuard, OwnedMutexGuard, TryLockError};
+pub use notify::{Notified, Notify};
+pub use rwlock::{
+    OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+pub use semaphore::{
+    Acquire, AcquireError, OwnedSemaphorePermit, Semaphore, SemaphorePermit, TryAcquireError,
+};
+
+/// Poll a future once with a no-op waker
+/// 使用无操作waker轮询future一次
+#[cfg(test)]
+pub(crate) fn poll_once<F: Future>(future: std::pin::Pin<&mut F>) -> std::task::Poll<F::Output> {
+    future.poll(&mut std::task::Context::from_waker(std::task::Waker::noop()))
+}
@@ -0,0 +1,276 @@
+//! Async mutual exclusion lock
+//! 异步互斥锁
+//!
+//! # Overview / 概述
+//!
+//! Unlike `std::sync::Mutex`, the guard may be held across `.await` points:
+//! tasks waiting for the lock are suspended instead of blocking their thread,
+//! and are granted the lock in the order they asked for it.
+//!
+//! 与 `std::sync::Mutex` 不同，守卫可以跨越 `.await` 持有：等待锁的任务会被挂起
+//! 而不是阻塞线程，并按请求顺序获得锁。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::sync::Mutex;
+//! use std::sync::Arc;
+//!
+//! async fn increment(counter: Arc<Mutex<u64>>) {
+//!     let mut value = counter.lock().await;
+//!     *value += 1;
+//! }
+//! ```
+
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use super::semaphore::Semaphore;
+
+/// Error returned when a lock cannot be taken without waiting
+/// 无法在不等待的情况下获取锁时返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryLockError;
+
+impl std::fmt::Display for TryLockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Lock is held by another task")
+    }
+}
+
+impl std::error::Error for TryLockError {}
+
+/// An async mutex
+/// 异步互斥锁
+pub struct Mutex<T: ?Sized> {
+    /// One permit, held by the owner of the lock / 一个许可，由锁的持有者占有
+    semaphore: Semaphore,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: the semaphore hands out access to `data` to one task at a time
+// 安全性：信号量每次只把 `data` 的访问权交给一个任务
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    /// Create a new unlocked mutex
+    /// 创建新的未加锁互斥锁
+    pub const fn new(value: T) -> Self {
+        Self {
+            semaphore: Semaphore::new(1),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consume the mutex, returning the value
+    /// 消耗互斥锁并返回其中的值
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Lock the mutex, waiting until it is free
+    /// 锁定互斥锁，等待直到其空闲
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        match self.semaphore.acquire().await {
+            Ok(permit) => permit.forget(),
+            Err(_) => unreachable!("mutex semaphore is never closed"),
+        }
+        MutexGuard { lock: self }
+    }
+
+    /// Lock a mutex held through an `Arc`
+    /// 锁定通过 `Arc` 持有的互斥锁
+    pub async fn lock_owned(self: Arc<Self>) -> OwnedMutexGuard<T> {
+        self.lock().await.forget();
+        OwnedMutexGuard { lock: self }
+    }
+
+    /// Try to lock the mutex without waiting
+    /// 尝试锁定互斥锁而不等待
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError`] if the lock is held or other tasks are queued for it.
+    /// 如果锁已被持有或有其他任务在排队则返回 [`TryLockError`]。
+    pub fn try_lock(&self) -> Result<MutexGuard<'_, T>, TryLockError> {
+        let permit = self.semaphore.try_acquire().map_err(|_| TryLockError)?;
+        permit.forget();
+        Ok(MutexGuard { lock: self })
+    }
+
+    /// Get mutable access without locking, since the borrow is exclusive
+    /// 无需加锁即可获得可变访问，因为借用是独占的
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + std::fmt::Debug> std::fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut d = f.debug_struct("Mutex");
+        match self.try_lock() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(_) => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+/// Access to the value of a locked [`Mutex`], unlocking it when dropped
+/// 已锁定 [`Mutex`] 中值的访问权，释放时解锁
+#[must_use = "the mutex is unlocked as soon as the guard is dropped"]
+pub struct MutexGuard<'a, T: ?Sized> {
+    lock: &'a Mutex<T>,
+}
+
+impl<T: ?Sized> MutexGuard<'_, T> {
+    /// Keep the mutex locked after the guard is gone
+    /// 守卫消失后保持互斥锁处于锁定状态
+    fn forget(self) {
+        std::mem::forget(self);
+    }
+}
+
+// SAFETY: the guard only hands out `&T` when shared
+// 安全性：共享时守卫只提供 `&T`
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: holding the guard means holding the only permit
+        // 安全性：持有守卫即持有唯一的许可
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: holding the guard means holding the only permit
+        // 安全性：持有守卫即持有唯一的许可
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.release(1);
+    }
+}
+
+impl<T: ?Sized + std::fmt::Debug> std::fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Access to the value of a [`Mutex`] locked through an `Arc`
+/// 通过 `Arc` 锁定的 [`Mutex`] 中值的访问权
+#[must_use = "the mutex is unlocked as soon as the guard is dropped"]
+pub struct OwnedMutexGuard<T: ?Sized> {
+    lock: Arc<Mutex<T>>,
+}
+
+impl<T: ?Sized> OwnedMutexGuard<T> {
+    /// Get the mutex this guard belongs to
+    /// 获取此守卫所属的互斥锁
+    #[must_use]
+    pub fn mutex(&self) -> &Arc<Mutex<T>> {
+        &self.lock
+    }
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for OwnedMutexGuard<T> {}
+
+impl<T: ?Sized> Deref for OwnedMutexGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: holding the guard means holding the only permit
+        // 安全性：持有守卫即持有唯一的许可
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for OwnedMutexGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: holding the guard means holding the only permit
+        // 安全性：持有守卫即持有唯一的许可
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for OwnedMutexGuard<T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.release(1);
+    }
+}
+
+impl<T: ?Sized + std::fmt::Debug> std::fmt::Debug for OwnedMutexGuard<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::poll_once;
+    use crate::task::{block_on, spawn};
+    use crate::time::{Duration, sleep};
+
+    #[test]
+    fn test_lock_across_tasks() {
+        let counter = Arc::new(Mutex::new(0u64));
+        let total = block_on(async move {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let counter = counter.clone();
+                    spawn(async move {
+                        for _ in 0..100 {
+                            let mut value = counter.lock().await;
+                            let read = *value;
+                            sleep(Duration::ZERO).await;
+                            *value = read + 1;
+                        }
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.wait().await.unwrap();
+            }
+            *counter.lock().await
+        });
+        assert_eq!(total, 400);
+    }
+
+    #[test]
+    fn test_try_lock_and_cancelled_lock() {
+        let mutex = Mutex::new(1);
+        let guard = mutex.try_lock().unwrap();
+        assert_eq!(mutex.try_lock().unwrap_err(), TryLockError);
+
+        let mut waiting = Box::pin(mutex.lock());
+        assert!(poll_once(waiting.as_mut()).is_pending());
+        drop(guard);
+        drop(waiting);
+        assert_eq!(*mutex.try_lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,276 @@
+//! Task notification
+//! 任务通知
+//!
+//! # Overview / 概述
+//!
+//! [`Notify`] wakes tasks without passing data. [`Notify::notify_one`] wakes the
+//! longest waiting task, or stores a permit for the next call to
+//! [`Notify::notified`] when nobody waits, so a notification sent just before a
+//! task starts waiting is not lost. [`Notify::notify_waiters`] wakes every task
+//! currently waiting.
+//!
+//! [`Notify`] 在不传递数据的情况下唤醒任务。[`Notify::notify_one`] 唤醒等待最久的任务，
+//! 无人等待时为下一次 [`Notify::notified`] 保存一个许可，因此在任务开始等待之前发送的
+//! 通知不会丢失。[`Notify::notify_waiters`] 唤醒当前所有等待的任务。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::sync::Notify;
+//! use std::sync::Arc;
+//!
+//! async fn example(notify: Arc<Notify>) {
+//!     let waiter = notify.clone();
+//!     nexus_runtime::spawn(async move {
+//!         waiter.notified().await;
+//!         println!("notified");
+//!     });
+//!     notify.notify_one();
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+
+/// Notify state guarded by a lock
+/// 由锁保护的通知状态
+struct State {
+    /// A `notify_one` arrived while nobody waited / 无人等待时收到了 `notify_one`
+    permit: bool,
+    /// Incremented by every `notify_waiters` / 每次 `notify_waiters` 递增
+    generation: u64,
+    /// Next waiter ID / 下一个等待者ID
+    next_id: u64,
+    /// Waiters in arrival order / 按到达顺序排列的等待者
+    waiters: VecDeque<(u64, Waker)>,
+    /// Waiters picked by `notify_one` that have not seen it yet
+    /// 被 `notify_one` 选中但尚未察觉的等待者
+    notified: Vec<u64>,
+}
+
+impl State {
+    /// Hand one notification to the oldest waiter or store it as a permit
+    /// 将一次通知交给最早的等待者，或保存为许可
+    fn notify_one(&mut self) -> Option<Waker> {
+        let Some((id, waker)) = self.waiters.pop_front() else {
+            self.permit = true;
+            return None;
+        };
+        self.notified.push(id);
+        Some(waker)
+    }
+}
+
+/// Notifies one or all waiting tasks
+/// 通知一个或全部等待中的任务
+pub struct Notify {
+    state: Mutex<State>,
+}
+
+impl Notify {
+    /// Create a new `Notify` without a stored permit
+    /// 创建不带已保存许可的新 `Notify`
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                permit: false,
+                generation: 0,
+                next_id: 0,
+                waiters: VecDeque::new(),
+                notified: Vec::new(),
+            }),
+        }
+    }
+
+    /// Wait for a notification
+    /// 等待通知
+    ///
+    /// A [`Notify::notify_waiters`] call made after this method returns wakes the
+    /// future even if it has not been polled yet.
+    /// 此方法返回后发生的 [`Notify::notify_waiters`] 调用会唤醒该future，即使它尚未被轮询。
+    pub fn notified(&self) -> Notified<'_> {
+        let generation = self.state.lock().unwrap().generation;
+        Notified {
+            notify: self,
+            generation,
+            waiting: None,
+            done: false,
+        }
+    }
+
+    /// Wake the longest waiting task, or let the next waiter through at once
+    /// 唤醒等待最久的任务，或让下一个等待者立即通过
+    pub fn notify_one(&self) {
+        let waker = self.state.lock().unwrap().notify_one();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Wake every task currently waiting, without storing a permit
+    /// 唤醒当前所有等待中的任务，不保存许可
+    pub fn notify_waiters(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        let waiters = std::mem::take(&mut state.waiters);
+        drop(state);
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Notify {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("Notify")
+            .field("permit", &state.permit)
+            .field("waiters", &state.waiters.len())
+            .finish()
+    }
+}
+
+/// Future returned by [`Notify::notified`]
+/// [`Notify::notified`] 返回的future
+pub struct Notified<'a> {
+    notify: &'a Notify,
+    /// `notify_waiters` generation when created / 创建时的 `notify_waiters` 代数
+    generation: u64,
+    /// Waiter ID, once queued / 排队后的等待者ID
+    waiting: Option<u64>,
+    /// Set once the notification was received / 收到通知后设置
+    done: bool,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.done {
+            return Poll::Ready(());
+        }
+        let mut state = self.notify.state.lock().unwrap();
+
+        let mut forward = None;
+        let notified = if state.generation != self.generation {
+            // Woken by `notify_waiters`, which already emptied the queue; a
+            // `notify_one` picking this waiter before that goes to the next one
+            // 被 `notify_waiters` 唤醒，它已清空队列；在此之前选中该等待者的
+            // `notify_one` 转交给下一个等待者
+            if let Some(pos) = self
+                .waiting
+                .and_then(|id| state.notified.iter().position(|&n| n == id))
+            {
+                state.notified.swap_remove(pos);
+                forward = state.notify_one();
+            }
+            true
+        } else if let Some(id) = self.waiting {
+            if let Some(pos) = state.notified.iter().position(|&n| n == id) {
+                state.notified.swap_remove(pos);
+                true
+            } else {
+                if let Some((_, waker)) = state.waiters.iter_mut().find(|(w, _)| *w == id)
+                    && !waker.will_wake(cx.waker())
+                {
+                    waker.clone_from(cx.waker());
+                }
+                false
+            }
+        } else if state.permit {
+            state.permit = false;
+            true
+        } else {
+            let id = state.next_id;
+            state.next_id += 1;
+            state.waiters.push_back((id, cx.waker().clone()));
+            self.waiting = Some(id);
+            false
+        };
+
+        drop(state);
+        if let Some(waker) = forward {
+            waker.wake();
+        }
+        if notified {
+            self.waiting = None;
+            self.done = true;
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.waiting else {
+            return;
+        };
+        let mut state = self.notify.state.lock().unwrap();
+        let waker = if let Some(pos) = state.notified.iter().position(|&n| n == id) {
+            // Picked by `notify_one` but cancelled: pass the notification on
+            // 被 `notify_one` 选中但已取消：将通知转交下去
+            state.notified.swap_remove(pos);
+            state.notify_one()
+        } else {
+            state.waiters.retain(|(w, _)| *w != id);
+            None
+        };
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::poll_once;
+
+    #[test]
+    fn test_notify_one_stores_permit() {
+        let notify = Notify::new();
+        notify.notify_one();
+        notify.notify_one();
+        assert!(poll_once(Box::pin(notify.notified()).as_mut()).is_ready());
+        assert!(poll_once(Box::pin(notify.notified()).as_mut()).is_pending());
+    }
+
+    #[test]
+    fn test_notify_waiters_wakes_created_futures() {
+        let notify = Notify::new();
+        let mut a = Box::pin(notify.notified());
+        let mut b = Box::pin(notify.notified());
+        assert!(poll_once(a.as_mut()).is_pending());
+        notify.notify_waiters();
+        assert!(poll_once(a.as_mut()).is_ready());
+        assert!(poll_once(b.as_mut()).is_ready());
+        // No permit is left behind / 不会留下许可
+        assert!(poll_once(Box::pin(notify.notified()).as_mut()).is_pending());
+    }
+
+    #[test]
+    fn test_cancelled_waiter_passes_notification_on() {
+        let notify = Notify::new();
+        let mut first = Box::pin(notify.notified());
+        let mut second = Box::pin(notify.notified());
+        assert!(poll_once(first.as_mut()).is_pending());
+        assert!(poll_once(second.as_mut()).is_pending());
+
+        notify.notify_one();
+        drop(first);
+        assert!(poll_once(second.as_mut()).is_ready());
+    }
+}
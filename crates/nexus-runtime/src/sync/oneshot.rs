@@ -0,0 +1,274 @@
+//! Single-value channel
+//! 单值通道
+//!
+//! # Overview / 概述
+//!
+//! A oneshot channel carries exactly one value from a [`Sender`] to a
+//! [`Receiver`]. The receiver is itself a future; it fails with [`RecvError`]
+//! when the sender is dropped without sending.
+//!
+//! oneshot通道将恰好一个值从 [`Sender`] 传递给 [`Receiver`]。接收器本身就是一个future；
+//! 当发送器未发送就被丢弃时，它以 [`RecvError`] 失败。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::sync::oneshot;
+//!
+//! async fn example() {
+//!     let (tx, rx) = oneshot::channel();
+//!     nexus_runtime::spawn(async move {
+//!         let _ = tx.send(42);
+//!     });
+//!     assert_eq!(rx.await, Ok(42));
+//! }
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Error returned when the sender was dropped without sending
+/// 发送器未发送就被丢弃时返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl std::fmt::Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Channel closed")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Error returned by [`Receiver::try_recv`]
+/// [`Receiver::try_recv`] 返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No value was sent yet
+    /// 尚未发送值
+    Empty,
+    /// The sender was dropped without sending, or the value was already taken
+    /// 发送器未发送就被丢弃，或值已被取走
+    Closed,
+}
+
+impl std::fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "Channel empty"),
+            TryRecvError::Closed => write!(f, "Channel closed"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+/// Channel state guarded by a lock
+/// 由锁保护的通道状态
+struct State<T> {
+    /// The sent value, until received / 已发送的值，直到被接收
+    value: Option<T>,
+    /// The sender is gone / 发送器已不存在
+    sender_gone: bool,
+    /// The receiver is gone or closed / 接收器已不存在或已关闭
+    receiver_gone: bool,
+    /// Receiver waiting for the value / 等待值的接收器
+    rx_waker: Option<Waker>,
+    /// Sender waiting in [`Sender::closed`] / 在 [`Sender::closed`] 中等待的发送器
+    tx_waker: Option<Waker>,
+}
+
+/// Create a oneshot channel
+/// 创建oneshot通道
+#[must_use]
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Mutex::new(State {
+        value: None,
+        sender_gone: false,
+        receiver_gone: false,
+        rx_waker: None,
+        tx_waker: None,
+    }));
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared },
+    )
+}
+
+/// Sending half of a oneshot channel
+/// oneshot通道的发送端
+pub struct Sender<T> {
+    shared: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send the value, consuming the sender
+    /// 发送值并消耗发送器
+    ///
+    /// # Errors
+    ///
+    /// Returns the value back if the receiver is gone.
+    /// 如果接收器已不存在则原样返回该值。
+    pub fn send(self, value: T) -> Result<(), T> {
+        let mut state = self.shared.lock().unwrap();
+        if state.receiver_gone {
+            return Err(value);
+        }
+        state.value = Some(value);
+        let waker = state.rx_waker.take();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Check whether the receiver is gone
+    /// 检查接收器是否已不存在
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.shared.lock().unwrap().receiver_gone
+    }
+
+    /// Wait until the receiver is gone, e.g. to stop computing an unwanted value
+    /// 等待直到接收器不存在，例如用于停止计算不再需要的值
+    pub async fn closed(&mut self) {
+        std::future::poll_fn(|cx| {
+            let mut state = self.shared.lock().unwrap();
+            if state.receiver_gone {
+                return Poll::Ready(());
+            }
+            match &mut state.tx_waker {
+                Some(waker) if waker.will_wake(cx.waker()) => {},
+                waker => *waker = Some(cx.waker().clone()),
+            }
+            Poll::Pending
+        })
+        .await;
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock().unwrap();
+        state.sender_gone = true;
+        let waker = state.rx_waker.take();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// Receiving half of a oneshot channel, awaited to get the value
+/// oneshot通道的接收端，await它以获取值
+pub struct Receiver<T> {
+    shared: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Take the value if it has been sent
+    /// 如果值已发送则取走它
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryRecvError::Empty`] if nothing was sent yet and
+    /// [`TryRecvError::Closed`] if nothing will be.
+    /// 尚未发送时返回 [`TryRecvError::Empty`]，不会再发送时返回 [`TryRecvError::Closed`]。
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut state = self.shared.lock().unwrap();
+        match state.value.take() {
+            Some(value) => Ok(value),
+            None if state.sender_gone => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Refuse the value; a later [`Sender::send`] fails
+    /// 拒绝接收值；之后的 [`Sender::send`] 会失败
+    ///
+    /// A value sent before closing can still be received.
+    /// 关闭前已发送的值仍可被接收。
+    pub fn close(&mut self) {
+        let mut state = self.shared.lock().unwrap();
+        state.receiver_gone = true;
+        let waker = state.tx_waker.take();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.lock().unwrap();
+        if let Some(value) = state.value.take() {
+            return Poll::Ready(Ok(value));
+        }
+        if state.sender_gone {
+            return Poll::Ready(Err(RecvError));
+        }
+        match &mut state.rx_waker {
+            Some(waker) if waker.will_wake(cx.waker()) => {},
+            waker => *waker = Some(cx.waker().clone()),
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<T> std::fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{block_on, spawn};
+
+    #[test]
+    fn test_send_and_receive_across_tasks() {
+        let value = block_on(async {
+            let (tx, rx) = channel();
+            spawn(async move {
+                let _ = tx.send("hello");
+            });
+            rx.await
+        });
+        assert_eq!(value, Ok("hello"));
+    }
+
+    #[test]
+    fn test_dropped_sender_and_receiver() {
+        let (tx, mut rx) = channel::<u8>();
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        drop(tx);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+
+        let (tx, rx) = channel::<u8>();
+        assert!(!tx.is_closed());
+        drop(rx);
+        assert!(tx.is_closed());
+        assert_eq!(tx.send(1), Err(1));
+    }
+}
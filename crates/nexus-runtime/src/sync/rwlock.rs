@@ -0,0 +1,329 @@
+//! Async reader-writer lock
+//! 异步读写锁
+//!
+//! # Overview / 概述
+//!
+//! Any number of readers or a single writer may hold the lock. Requests are
+//! served in arrival order, so a waiting writer holds back readers that arrive
+//! after it and cannot be starved.
+//!
+//! 锁可以由任意数量的读者或单个写者持有。请求按到达顺序获得服务，因此等待中的写者
+//! 会挡住在其之后到达的读者，不会被饿死。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::sync::RwLock;
+//!
+//! async fn example(config: &RwLock<String>) {
+//!     println!("{}", *config.read().await);
+//!     config.write().await.push_str("!");
+//! }
+//! ```
+
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use super::mutex::TryLockError;
+use super::semaphore::Semaphore;
+
+/// Permits taken by a writer; each reader takes one
+/// 写者占用的许可数；每个读者占用一个
+const MAX_READS: u32 = u32::MAX >> 3;
+
+/// An async reader-writer lock
+/// 异步读写锁
+pub struct RwLock<T: ?Sized> {
+    /// `MAX_READS` permits, one per reader or all for a writer
+    /// `MAX_READS` 个许可，每个读者一个，写者占用全部
+    semaphore: Semaphore,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: the semaphore admits either shared readers or one writer
+// 安全性：信号量只允许共享的读者或单个写者进入
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+impl<T> RwLock<T> {
+    /// Create a new unlocked lock
+    /// 创建新的未加锁读写锁
+    pub const fn new(value: T) -> Self {
+        Self {
+            semaphore: Semaphore::new(MAX_READS as usize),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consume the lock, returning the value
+    /// 消耗锁并返回其中的值
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Lock for shared reading
+    /// 获取共享读锁
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.acquire(1).await;
+        RwLockReadGuard { lock: self }
+    }
+
+    /// Lock for exclusive writing
+    /// 获取独占写锁
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.acquire(MAX_READS).await;
+        RwLockWriteGuard { lock: self }
+    }
+
+    /// Lock an `Arc`-held lock for shared reading
+    /// 获取通过 `Arc` 持有的锁的共享读锁
+    pub async fn read_owned(self: Arc<Self>) -> OwnedRwLockReadGuard<T> {
+        self.acquire(1).await;
+        OwnedRwLockReadGuard { lock: self }
+    }
+
+    /// Lock an `Arc`-held lock for exclusive writing
+    /// 获取通过 `Arc` 持有的锁的独占写锁
+    pub async fn write_owned(self: Arc<Self>) -> OwnedRwLockWriteGuard<T> {
+        self.acquire(MAX_READS).await;
+        OwnedRwLockWriteGuard { lock: self }
+    }
+
+    /// Try to lock for reading without waiting
+    /// 尝试获取读锁而不等待
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError`] if a writer holds or is waiting for the lock.
+    /// 如果有写者持有或正在等待锁则返回 [`TryLockError`]。
+    pub fn try_read(&self) -> Result<RwLockReadGuard<'_, T>, TryLockError> {
+        self.semaphore
+            .try_acquire()
+            .map_err(|_| TryLockError)?
+            .forget();
+        Ok(RwLockReadGuard { lock: self })
+    }
+
+    /// Try to lock for writing without waiting
+    /// 尝试获取写锁而不等待
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError`] if the lock is held or other tasks are queued for it.
+    /// 如果锁已被持有或有其他任务在排队则返回 [`TryLockError`]。
+    pub fn try_write(&self) -> Result<RwLockWriteGuard<'_, T>, TryLockError> {
+        self.semaphore
+            .try_acquire_many(MAX_READS)
+            .map_err(|_| TryLockError)?
+            .forget();
+        Ok(RwLockWriteGuard { lock: self })
+    }
+
+    /// Get mutable access without locking, since the borrow is exclusive
+    /// 无需加锁即可获得可变访问，因为借用是独占的
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Take `permits`, keeping them until a guard releases them
+    /// 获取 `permits` 个许可，并保留到守卫释放它们
+    async fn acquire(&self, permits: u32) {
+        match self.semaphore.acquire_many(permits).await {
+            Ok(permit) => permit.forget(),
+            Err(_) => unreachable!("rwlock semaphore is never closed"),
+        }
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + std::fmt::Debug> std::fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(_) => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+/// Shared access to the value of a read-locked [`RwLock`]
+/// 已加读锁的 [`RwLock`] 中值的共享访问权
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: no writer can hold the lock while a read permit is out
+        // 安全性：读许可未归还时写者无法持有锁
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.release(1);
+    }
+}
+
+impl<T: ?Sized + std::fmt::Debug> std::fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Exclusive access to the value of a write-locked [`RwLock`]
+/// 已加写锁的 [`RwLock`] 中值的独占访问权
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
+    /// Turn the write lock into a read lock without letting another writer in
+    /// 将写锁降级为读锁，期间不让其他写者进入
+    pub fn downgrade(self) -> RwLockReadGuard<'a, T> {
+        let lock = self.lock;
+        std::mem::forget(self);
+        lock.semaphore.release(MAX_READS as usize - 1);
+        RwLockReadGuard { lock }
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the writer holds every permit
+        // 安全性：写者持有全部许可
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the writer holds every permit
+        // 安全性：写者持有全部许可
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.release(MAX_READS as usize);
+    }
+}
+
+impl<T: ?Sized + std::fmt::Debug> std::fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Shared access to an `Arc`-held [`RwLock`]
+/// 通过 `Arc` 持有的 [`RwLock`] 的共享访问权
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct OwnedRwLockReadGuard<T: ?Sized> {
+    lock: Arc<RwLock<T>>,
+}
+
+impl<T: ?Sized> Deref for OwnedRwLockReadGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: no writer can hold the lock while a read permit is out
+        // 安全性：读许可未归还时写者无法持有锁
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for OwnedRwLockReadGuard<T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.release(1);
+    }
+}
+
+/// Exclusive access to an `Arc`-held [`RwLock`]
+/// 通过 `Arc` 持有的 [`RwLock`] 的独占访问权
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct OwnedRwLockWriteGuard<T: ?Sized> {
+    lock: Arc<RwLock<T>>,
+}
+
+impl<T: ?Sized> Deref for OwnedRwLockWriteGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the writer holds every permit
+        // 安全性：写者持有全部许可
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for OwnedRwLockWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the writer holds every permit
+        // 安全性：写者持有全部许可
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for OwnedRwLockWriteGuard<T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.release(MAX_READS as usize);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::poll_once;
+
+    #[test]
+    fn test_readers_share_writer_excludes() {
+        let lock = RwLock::new(5);
+        let a = lock.try_read().unwrap();
+        let b = lock.try_read().unwrap();
+        assert_eq!(*a + *b, 10);
+        assert!(lock.try_write().is_err());
+        drop((a, b));
+
+        let mut writer = lock.try_write().unwrap();
+        *writer = 6;
+        assert!(lock.try_read().is_err());
+        let reader = writer.downgrade();
+        assert_eq!(*reader, 6);
+        assert!(lock.try_read().is_ok());
+        assert!(lock.try_write().is_err());
+    }
+
+    #[test]
+    fn test_waiting_writer_holds_back_new_readers() {
+        let lock = RwLock::new(());
+        let reader = lock.try_read().unwrap();
+        let mut writer = Box::pin(lock.write());
+        assert!(poll_once(writer.as_mut()).is_pending());
+        assert!(lock.try_read().is_err());
+
+        drop(reader);
+        assert!(poll_once(writer.as_mut()).is_ready());
+    }
+}
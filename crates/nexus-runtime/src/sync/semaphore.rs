@@ -0,0 +1,546 @@
+//! Fair counting semaphore
+//! 公平计数信号量
+//!
+//! # Overview / 概述
+//!
+//! Waiters are served strictly in arrival order: once a task is queued, later
+//! acquires wait behind it even if enough permits are free for them, so a large
+//! request (such as a write lock) cannot be starved by a stream of small ones.
+//! Dropping an [`Acquire`] future leaves the queue and hands back any permits it
+//! was granted in the meantime, which makes acquiring cancellation safe.
+//!
+//! 等待者严格按到达顺序获得服务：一旦有任务排队，之后的获取操作即使有足够的空闲许可
+//! 也会排在其后，因此大请求（如写锁）不会被源源不断的小请求饿死。丢弃 [`Acquire`]
+//! future会离开队列，并归还期间已分配给它的许可，因此获取操作是取消安全的。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::sync::Semaphore;
+//!
+//! static DB_CONNECTIONS: Semaphore = Semaphore::new(10);
+//!
+//! async fn query() {
+//!     let _permit = DB_CONNECTIONS.acquire().await.unwrap();
+//!     // at most 10 queries run at once / 最多同时运行10个查询
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Error returned when acquiring from a closed semaphore
+/// 从已关闭的信号量获取时返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcquireError;
+
+impl std::fmt::Display for AcquireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Semaphore closed")
+    }
+}
+
+impl std::error::Error for AcquireError {}
+
+/// Error returned by the `try_acquire` methods
+/// `try_acquire` 系列方法返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryAcquireError {
+    /// The semaphore is closed
+    /// 信号量已关闭
+    Closed,
+    /// Not enough permits are free, or other tasks are queued
+    /// 空闲许可不足，或有其他任务在排队
+    NoPermits,
+}
+
+impl std::fmt::Display for TryAcquireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TryAcquireError::Closed => write!(f, "Semaphore closed"),
+            TryAcquireError::NoPermits => write!(f, "No permits available"),
+        }
+    }
+}
+
+impl std::error::Error for TryAcquireError {}
+
+/// A queued acquire
+/// 排队中的获取操作
+struct Waiter {
+    /// Waiter ID / 等待者ID
+    id: u64,
+    /// Permits requested / 请求的许可数
+    needed: usize,
+    /// Task to wake once granted / 分配后要唤醒的任务
+    waker: Option<Waker>,
+}
+
+/// Semaphore state guarded by a lock
+/// 由锁保护的信号量状态
+struct State {
+    /// Free permits / 空闲许可数
+    permits: usize,
+    /// Set once closed / 关闭后设置
+    closed: bool,
+    /// Next waiter ID / 下一个等待者ID
+    next_id: u64,
+    /// Waiters in arrival order / 按到达顺序排列的等待者
+    queue: VecDeque<Waiter>,
+    /// Waiters that were granted their permits but have not seen it yet
+    /// 已分配许可但尚未察觉的等待者
+    granted: Vec<u64>,
+}
+
+impl State {
+    /// Hand free permits to the waiters at the head of the queue
+    /// 将空闲许可分配给队首的等待者
+    fn grant(&mut self) -> Vec<Waker> {
+        let mut wakers = Vec::new();
+        while let Some(front) = self.queue.front() {
+            if front.needed > self.permits {
+                break;
+            }
+            let waiter = self.queue.pop_front().unwrap();
+            self.permits -= waiter.needed;
+            self.granted.push(waiter.id);
+            wakers.extend(waiter.waker);
+        }
+        wakers
+    }
+}
+
+/// A counting semaphore with fair (FIFO) queueing
+/// 具有公平（FIFO）排队的计数信号量
+pub struct Semaphore {
+    state: Mutex<State>,
+}
+
+impl Semaphore {
+    /// Largest number of permits a semaphore can hold
+    /// 信号量可容纳的最大许可数
+    pub const MAX_PERMITS: usize = usize::MAX >> 3;
+
+    /// Create a semaphore with the given number of permits
+    /// 创建具有给定许可数的信号量
+    ///
+    /// # Panics
+    ///
+    /// Panics if `permits` exceeds [`Semaphore::MAX_PERMITS`].
+    /// 如果 `permits` 超过 [`Semaphore::MAX_PERMITS`] 则恐慌。
+    #[must_use]
+    pub const fn new(permits: usize) -> Self {
+        assert!(permits <= Self::MAX_PERMITS, "too many permits");
+        Self {
+            state: Mutex::new(State {
+                permits,
+                closed: false,
+                next_id: 0,
+                queue: VecDeque::new(),
+                granted: Vec::new(),
+            }),
+        }
+    }
+
+    /// Get the number of free permits
+    /// 获取空闲许可数
+    #[must_use]
+    pub fn available_permits(&self) -> usize {
+        self.state.lock().unwrap().permits
+    }
+
+    /// Add permits, waking queued tasks they satisfy
+    /// 增加许可，并唤醒因此得到满足的排队任务
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total would exceed [`Semaphore::MAX_PERMITS`].
+    /// 如果总数超过 [`Semaphore::MAX_PERMITS`] 则恐慌。
+    pub fn add_permits(&self, n: usize) {
+        let mut state = self.state.lock().unwrap();
+        assert!(n <= Self::MAX_PERMITS - state.permits, "too many permits");
+        state.permits += n;
+        let wakers = state.grant();
+        drop(state);
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Close the semaphore, failing all queued and future acquires
+    /// 关闭信号量，使所有排队中和之后的获取操作失败
+    ///
+    /// Permits that are already held stay valid.
+    /// 已持有的许可仍然有效。
+    pub fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        let wakers: Vec<Waker> = state.queue.drain(..).filter_map(|w| w.waker).collect();
+        drop(state);
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Check whether the semaphore has been closed
+    /// 检查信号量是否已关闭
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    /// Acquire one permit
+    /// 获取一个许可
+    pub fn acquire(&self) -> Acquire<'_> {
+        self.acquire_many(1)
+    }
+
+    /// Acquire `n` permits at once
+    /// 一次获取 `n` 个许可
+    pub fn acquire_many(&self, n: u32) -> Acquire<'_> {
+        Acquire {
+            semaphore: self,
+            needed: n as usize,
+            waiting: None,
+            done: false,
+        }
+    }
+
+    /// Acquire one permit held through an `Arc`
+    /// 通过 `Arc` 获取一个许可
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcquireError`] if the semaphore is closed.
+    /// 如果信号量已关闭则返回 [`AcquireError`]。
+    pub async fn acquire_owned(self: Arc<Self>) -> Result<OwnedSemaphorePermit, AcquireError> {
+        self.acquire_many_owned(1).await
+    }
+
+    /// Acquire `n` permits held through an `Arc`
+    /// 通过 `Arc` 获取 `n` 个许可
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AcquireError`] if the semaphore is closed.
+    /// 如果信号量已关闭则返回 [`AcquireError`]。
+    pub async fn acquire_many_owned(
+        self: Arc<Self>,
+        n: u32,
+    ) -> Result<OwnedSemaphorePermit, AcquireError> {
+        self.acquire_many(n).await?.forget();
+        Ok(OwnedSemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Try to acquire one permit without waiting
+    /// 尝试获取一个许可而不等待
+    ///
+    /// # Errors
+    ///
+    /// Fails if the semaphore is closed or the permit is not free right away.
+    /// 如果信号量已关闭或许可无法立即获得则失败。
+    pub fn try_acquire(&self) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.try_acquire_many(1)
+    }
+
+    /// Try to acquire `n` permits without waiting
+    /// 尝试获取 `n` 个许可而不等待
+    ///
+    /// # Errors
+    ///
+    /// Fails if the semaphore is closed or the permits are not free right away.
+    /// 如果信号量已关闭或许可无法立即获得则失败。
+    pub fn try_acquire_many(&self, n: u32) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.try_take(n as usize)?;
+        Ok(SemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Try to acquire one permit held through an `Arc` without waiting
+    /// 尝试通过 `Arc` 获取一个许可而不等待
+    ///
+    /// # Errors
+    ///
+    /// Fails if the semaphore is closed or the permit is not free right away.
+    /// 如果信号量已关闭或许可无法立即获得则失败。
+    pub fn try_acquire_owned(self: Arc<Self>) -> Result<OwnedSemaphorePermit, TryAcquireError> {
+        self.try_take(1)?;
+        Ok(OwnedSemaphorePermit {
+            semaphore: self,
+            permits: 1,
+        })
+    }
+
+    /// Take permits if they are free and nobody is queued
+    /// 如果许可空闲且无人排队则取走许可
+    fn try_take(&self, n: usize) -> Result<(), TryAcquireError> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            Err(TryAcquireError::Closed)
+        } else if state.queue.is_empty() && state.permits >= n {
+            state.permits -= n;
+            Ok(())
+        } else {
+            Err(TryAcquireError::NoPermits)
+        }
+    }
+
+    /// Return permits to the semaphore
+    /// 将许可归还给信号量
+    pub(crate) fn release(&self, n: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.permits += n;
+        let wakers = state.grant();
+        drop(state);
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl std::fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("Semaphore")
+            .field("permits", &state.permits)
+            .field("queued", &state.queue.len())
+            .field("closed", &state.closed)
+            .finish()
+    }
+}
+
+/// Future returned by [`Semaphore::acquire`] and [`Semaphore::acquire_many`]
+/// [`Semaphore::acquire`] 和 [`Semaphore::acquire_many`] 返回的future
+pub struct Acquire<'a> {
+    semaphore: &'a Semaphore,
+    /// Permits requested / 请求的许可数
+    needed: usize,
+    /// Queue position, once queued / 排队后的队列位置
+    waiting: Option<u64>,
+    /// Set once the output was returned / 返回输出后设置
+    done: bool,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = Result<SemaphorePermit<'a>, AcquireError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        assert!(!self.done, "Acquire polled after completion");
+        let semaphore = self.semaphore;
+        let needed = self.needed;
+        let mut state = semaphore.state.lock().unwrap();
+
+        let acquired = match self.waiting {
+            None if state.closed => Err(AcquireError),
+            None if state.queue.is_empty() && state.permits >= needed => {
+                state.permits -= needed;
+                Ok(())
+            },
+            None => {
+                let id = state.next_id;
+                state.next_id += 1;
+                state.queue.push_back(Waiter {
+                    id,
+                    needed,
+                    waker: Some(cx.waker().clone()),
+                });
+                self.waiting = Some(id);
+                return Poll::Pending;
+            },
+            Some(id) => {
+                if let Some(pos) = state.granted.iter().position(|&g| g == id) {
+                    state.granted.swap_remove(pos);
+                    Ok(())
+                } else if let Some(waiter) = state.queue.iter_mut().find(|w| w.id == id) {
+                    match &mut waiter.waker {
+                        Some(waker) if waker.will_wake(cx.waker()) => {},
+                        waker => *waker = Some(cx.waker().clone()),
+                    }
+                    return Poll::Pending;
+                } else {
+                    // Dropped from the queue by `close`
+                    // 被 `close` 移出队列
+                    Err(AcquireError)
+                }
+            },
+        };
+
+        drop(state);
+        self.waiting = None;
+        self.done = true;
+        Poll::Ready(acquired.map(|()| SemaphorePermit {
+            semaphore,
+            permits: needed as u32,
+        }))
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.waiting else {
+            return;
+        };
+        let mut state = self.semaphore.state.lock().unwrap();
+        if let Some(pos) = state.granted.iter().position(|&g| g == id) {
+            // Granted but never observed: give the permits back
+            // 已分配但未被察觉：归还许可
+            state.granted.swap_remove(pos);
+            state.permits += self.needed;
+        } else if let Some(pos) = state.queue.iter().position(|w| w.id == id) {
+            state.queue.remove(pos);
+        }
+        // Leaving may unblock the waiters queued behind us
+        // 离开队列可能使排在后面的等待者不再阻塞
+        let wakers = state.grant();
+        drop(state);
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Permits borrowed from a [`Semaphore`], released when dropped
+/// 从 [`Semaphore`] 借用的许可，释放时归还
+#[must_use = "the permits are released as soon as this is dropped"]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: u32,
+}
+
+impl SemaphorePermit<'_> {
+    /// Get the number of permits held
+    /// 获取持有的许可数
+    #[must_use]
+    pub fn num_permits(&self) -> u32 {
+        self.permits
+    }
+
+    /// Keep the permits out of the semaphore for good
+    /// 永久保留这些许可，不再归还信号量
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.release(self.permits as usize);
+        }
+    }
+}
+
+impl std::fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SemaphorePermit")
+            .field("permits", &self.permits)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Permits held through an `Arc<Semaphore>`, released when dropped
+/// 通过 `Arc<Semaphore>` 持有的许可，释放时归还
+#[must_use = "the permits are released as soon as this is dropped"]
+pub struct OwnedSemaphorePermit {
+    semaphore: Arc<Semaphore>,
+    permits: u32,
+}
+
+impl OwnedSemaphorePermit {
+    /// Get the number of permits held
+    /// 获取持有的许可数
+    #[must_use]
+    pub fn num_permits(&self) -> u32 {
+        self.permits
+    }
+
+    /// Get the semaphore the permits belong to
+    /// 获取许可所属的信号量
+    #[must_use]
+    pub fn semaphore(&self) -> &Arc<Semaphore> {
+        &self.semaphore
+    }
+
+    /// Keep the permits out of the semaphore for good
+    /// 永久保留这些许可，不再归还信号量
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for OwnedSemaphorePermit {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.release(self.permits as usize);
+        }
+    }
+}
+
+impl std::fmt::Debug for OwnedSemaphorePermit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OwnedSemaphorePermit")
+            .field("permits", &self.permits)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::poll_once;
+
+    #[test]
+    fn test_try_acquire_and_release() {
+        let semaphore = Semaphore::new(2);
+        let a = semaphore.try_acquire().unwrap();
+        let b = semaphore.try_acquire().unwrap();
+        assert_eq!(semaphore.try_acquire().unwrap_err(), TryAcquireError::NoPermits);
+        drop(a);
+        assert_eq!(semaphore.available_permits(), 1);
+        b.forget();
+        assert_eq!(semaphore.available_permits(), 1);
+    }
+
+    #[test]
+    fn test_fifo_queue_blocks_barging() {
+        let semaphore = Semaphore::new(1);
+        let held = semaphore.try_acquire().unwrap();
+
+        // A large request queues first, then a small one behind it
+        // 大请求先排队，小请求排在其后
+        let mut big = Box::pin(semaphore.acquire_many(2));
+        assert!(poll_once(big.as_mut()).is_pending());
+        semaphore.add_permits(1);
+        assert_eq!(semaphore.try_acquire().unwrap_err(), TryAcquireError::NoPermits);
+
+        drop(held);
+        let permit = poll_once(big.as_mut()).map(Result::unwrap);
+        assert!(matches!(permit, Poll::Ready(ref p) if p.num_permits() == 2));
+    }
+
+    #[test]
+    fn test_cancelled_acquire_returns_granted_permits() {
+        let semaphore = Semaphore::new(1);
+        let held = semaphore.try_acquire().unwrap();
+        let mut first = Box::pin(semaphore.acquire());
+        let mut second = Box::pin(semaphore.acquire());
+        assert!(poll_once(first.as_mut()).is_pending());
+        assert!(poll_once(second.as_mut()).is_pending());
+
+        // The permit goes to `first`, which is dropped before it notices
+        // 许可分配给 `first`，但它在察觉之前就被丢弃
+        drop(held);
+        drop(first);
+        assert!(poll_once(second.as_mut()).is_ready());
+    }
+
+    #[test]
+    fn test_close_fails_waiters() {
+        let semaphore = Arc::new(Semaphore::new(0));
+        let mut waiter = Box::pin(semaphore.clone().acquire_owned());
+        assert!(poll_once(waiter.as_mut()).is_pending());
+        semaphore.close();
+        assert!(matches!(poll_once(waiter.as_mut()), Poll::Ready(Err(AcquireError))));
+        assert_eq!(semaphore.try_acquire().unwrap_err(), TryAcquireError::Closed);
+    }
+}
@@ -0,0 +1,383 @@
+//! Single-producer, multi-consumer channel that keeps only the latest value
+//! 只保留最新值的单生产者、多消费者通道
+//!
+//! # Overview / 概述
+//!
+//! A watch channel holds one value. Receivers can read it at any time with
+//! [`Receiver::borrow`] and wait for the next change with [`Receiver::changed`];
+//! intermediate values sent while a receiver was not looking are skipped. It
+//! suits configuration reloads and state such as "shutting down".
+//!
+//! watch通道持有一个值。接收器可以随时通过 [`Receiver::borrow`] 读取它，并通过
+//! [`Receiver::changed`] 等待下一次变化；接收器未关注期间发送的中间值会被跳过。
+//! 它适用于配置重载以及“正在关闭”之类的状态。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::sync::watch;
+//!
+//! async fn example() {
+//!     let (tx, mut rx) = watch::channel("v1");
+//!     nexus_runtime::spawn(async move {
+//!         tx.send("v2").unwrap();
+//!     });
+//!     rx.changed().await.unwrap();
+//!     assert_eq!(*rx.borrow_and_update(), "v2");
+//! }
+//! ```
+
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::task::{Poll, Waker};
+
+/// Error returned by [`Sender::send`] when there are no receivers
+/// 没有接收器时 [`Sender::send`] 返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> std::fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Channel closed")
+    }
+}
+
+impl<T: std::fmt::Debug> std::error::Error for SendError<T> {}
+
+/// Error returned by [`Receiver::changed`] once the sender is gone
+/// 发送器不存在后 [`Receiver::changed`] 返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl std::fmt::Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Channel closed")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Bookkeeping guarded by a lock
+/// 由锁保护的簿记信息
+struct State {
+    /// Incremented on every send / 每次发送时递增
+    version: u64,
+    /// The sender is gone / 发送器已不存在
+    closed: bool,
+    /// Live receivers / 存活的接收器数量
+    receivers: usize,
+    /// Next receiver ID / 下一个接收器ID
+    next_id: u64,
+    /// Receivers waiting for a change / 等待变化的接收器
+    wakers: Vec<(u64, Waker)>,
+}
+
+/// State shared by both halves
+/// 两端共享的状态
+struct Shared<T> {
+    value: RwLock<T>,
+    state: Mutex<State>,
+}
+
+impl<T> Shared<T> {
+    fn new_receiver(self: &Arc<Self>, seen: u64) -> Receiver<T> {
+        let mut state = self.state.lock().unwrap();
+        state.receivers += 1;
+        let id = state.next_id;
+        state.next_id += 1;
+        Receiver {
+            shared: self.clone(),
+            id,
+            seen,
+        }
+    }
+
+    /// Record a change and wake the waiting receivers
+    /// 记录一次变化并唤醒等待中的接收器
+    fn changed(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.version += 1;
+        let wakers = std::mem::take(&mut state.wakers);
+        drop(state);
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// Create a watch channel holding `init`
+/// 创建持有 `init` 的watch通道
+#[must_use]
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        value: RwLock::new(init),
+        state: Mutex::new(State {
+            version: 0,
+            closed: false,
+            receivers: 0,
+            next_id: 0,
+            wakers: Vec::new(),
+        }),
+    });
+    let receiver = shared.new_receiver(0);
+    (Sender { shared }, receiver)
+}
+
+/// Read access to the current value; keep it short, it blocks the sender
+/// 对当前值的读访问；应尽快释放，因为它会阻塞发送器
+pub struct Ref<'a, T> {
+    guard: RwLockReadGuard<'a, T>,
+}
+
+impl<T> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Ref<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Sending half of a watch channel
+/// watch通道的发送端
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Replace the value and notify the receivers
+    /// 替换值并通知接收器
+    ///
+    /// # Errors
+    ///
+    /// Returns the value back if there are no receivers; the stored value is left
+    /// unchanged.
+    /// 如果没有接收器则原样返回该值；存储的值保持不变。
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.receiver_count() == 0 {
+            return Err(SendError(value));
+        }
+        self.send_replace(value);
+        Ok(())
+    }
+
+    /// Replace the value even without receivers, returning the previous one
+    /// 即使没有接收器也替换值，并返回之前的值
+    pub fn send_replace(&self, value: T) -> T {
+        let old = std::mem::replace(&mut *self.shared.value.write().unwrap(), value);
+        self.shared.changed();
+        old
+    }
+
+    /// Modify the value in place and notify the receivers
+    /// 就地修改值并通知接收器
+    pub fn send_modify<F: FnOnce(&mut T)>(&self, modify: F) {
+        modify(&mut self.shared.value.write().unwrap());
+        self.shared.changed();
+    }
+
+    /// Read the current value
+    /// 读取当前值
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref {
+            guard: self.shared.value.read().unwrap(),
+        }
+    }
+
+    /// Create a receiver that has seen the current value
+    /// 创建一个已看过当前值的接收器
+    #[must_use]
+    pub fn subscribe(&self) -> Receiver<T> {
+        let version = self.shared.state.lock().unwrap().version;
+        self.shared.new_receiver(version)
+    }
+
+    /// Get the number of live receivers
+    /// 获取存活的接收器数量
+    #[must_use]
+    pub fn receiver_count(&self) -> usize {
+        self.shared.state.lock().unwrap().receivers
+    }
+
+    /// Check whether every receiver is gone
+    /// 检查是否所有接收器都已不存在
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.receiver_count() == 0
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.closed = true;
+        let wakers = std::mem::take(&mut state.wakers);
+        drop(state);
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender")
+            .field("value", &*self.borrow())
+            .finish()
+    }
+}
+
+/// Receiving half of a watch channel
+/// watch通道的接收端
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    /// Receiver ID for its waker slot / 用于waker槽位的接收器ID
+    id: u64,
+    /// Version last marked as seen / 最近标记为已看过的版本
+    seen: u64,
+}
+
+impl<T> Receiver<T> {
+    /// Read the current value without marking it as seen
+    /// 读取当前值，但不将其标记为已看过
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref {
+            guard: self.shared.value.read().unwrap(),
+        }
+    }
+
+    /// Read the current value and mark it as seen
+    /// 读取当前值并将其标记为已看过
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        let guard = self.shared.value.read().unwrap();
+        // Read the version while holding the value so both match
+        // 持有值时读取版本，使两者一致
+        self.seen = self.shared.state.lock().unwrap().version;
+        Ref { guard }
+    }
+
+    /// Check whether a value was sent since the last one seen
+    /// 检查自上次看过之后是否发送了新值
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError`] if the sender is gone.
+    /// 如果发送器已不存在则返回 [`RecvError`]。
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let state = self.shared.state.lock().unwrap();
+        if state.closed {
+            return Err(RecvError);
+        }
+        Ok(state.version != self.seen)
+    }
+
+    /// Wait for a value that has not been seen yet, then mark it as seen
+    /// 等待尚未看过的值，然后将其标记为已看过
+    ///
+    /// Cancellation safe.
+    /// 取消安全。
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError`] once the sender is gone and nothing new remains.
+    /// 发送器已不存在且没有新值时返回 [`RecvError`]。
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        std::future::poll_fn(|cx| {
+            let mut state = self.shared.state.lock().unwrap();
+            if state.version != self.seen {
+                self.seen = state.version;
+                return Poll::Ready(Ok(()));
+            }
+            if state.closed {
+                return Poll::Ready(Err(RecvError));
+            }
+            match state.wakers.iter_mut().find(|(id, _)| *id == self.id) {
+                Some((_, waker)) if waker.will_wake(cx.waker()) => {},
+                Some((_, waker)) => waker.clone_from(cx.waker()),
+                None => state.wakers.push((self.id, cx.waker().clone())),
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Mark the current value as seen
+    /// 将当前值标记为已看过
+    pub fn mark_unchanged(&mut self) {
+        self.seen = self.shared.state.lock().unwrap().version;
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.new_receiver(self.seen)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.receivers -= 1;
+        state.wakers.retain(|(id, _)| *id != self.id);
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver")
+            .field("value", &*self.borrow())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{block_on, spawn};
+
+    #[test]
+    fn test_latest_value_and_change_tracking() {
+        let (tx, mut rx) = channel(1);
+        assert_eq!(rx.has_changed(), Ok(false));
+        tx.send(2).unwrap();
+        tx.send_modify(|v| *v += 1);
+        assert_eq!(rx.has_changed(), Ok(true));
+        assert_eq!(*rx.borrow_and_update(), 3);
+        assert_eq!(rx.has_changed(), Ok(false));
+
+        let late = tx.subscribe();
+        assert_eq!(late.has_changed(), Ok(false));
+        drop(tx);
+        assert_eq!(rx.has_changed(), Err(RecvError));
+    }
+
+    #[test]
+    fn test_changed_wakes_and_reports_close() {
+        let seen = block_on(async {
+            let (tx, mut rx) = channel(0);
+            spawn(async move {
+                tx.send(5).unwrap();
+            });
+            let mut seen = Vec::new();
+            while rx.changed().await.is_ok() {
+                seen.push(*rx.borrow());
+            }
+            seen
+        });
+        assert_eq!(seen, [5]);
+    }
+
+    #[test]
+    fn test_send_without_receivers() {
+        let (tx, rx) = channel("a");
+        drop(rx);
+        assert_eq!(tx.send("b"), Err(SendError("b")));
+        assert_eq!(tx.send_replace("c"), "a");
+        assert_eq!(*tx.borrow(), "c");
+    }
+}
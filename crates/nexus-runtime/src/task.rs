@@ -6,23 +6,38 @@
 //! This module provides task spawning and management with support for:
 //! - Task lifecycle tracking (Running, Completed, Cancelled)
 //! - Wake-up notifications for async polling
-//! - Join handles for awaiting task completion
+//! - Join handles for awaiting task completion and aborting tasks
+//! - A bounded thread pool for blocking work ([`spawn_blocking`])
+//! - Task-local values ([`task_local!`](crate::task_local))
+//! - Structured groups of tasks ([`JoinSet`])
 //!
 //! 本模块提供任务生成和管理，支持：
 //! - 任务生命周期跟踪（运行中、已完成、已取消）
 //! - 异步轮询的唤醒通知
-//! - 等待任务完成的join句柄
+//! - 等待任务完成和中止任务的join句柄
+//! - 用于阻塞工作的有界线程池（[`spawn_blocking`]）
+//! - 任务本地值（[`task_local!`](crate::task_local)）
+//! - 结构化的任务组（[`JoinSet`]）
 
 #![allow(private_interfaces)]
 
+mod blocking;
+mod join_set;
+mod local;
+
 use std::future::Future;
-use std::panic;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
 
+use crate::metrics::TaskKind;
 use crate::scheduler::{RawTask, SchedulerHandle};
 
+pub use blocking::{MAX_BLOCKING_THREADS, spawn_blocking};
+pub use join_set::JoinSet;
+pub use local::{AccessError, LocalKey, TaskLocalFuture};
+
 /// Task ID type
 /// 任务ID类型
 pub use crate::scheduler::TaskId;
@@ -84,6 +99,90 @@ struct TaskInner<T> {
     raw_task: AtomicUsize,
     /// Task output (available when completed) / 任务输出（完成时可用）
     output: lock::OptionalCell<T>,
+    /// Abort requested through the join handle / 通过join句柄请求了中止
+    abort: AtomicBool,
+    /// Waker of the task awaiting the join handle / 等待join句柄的任务的waker
+    join_waker: Mutex<Option<Waker>>,
+}
+
+impl<T> TaskInner<T> {
+    /// Create the shared data for a new running task
+    /// 为新的运行中任务创建共享数据
+    fn new(id: TaskId, ref_count: usize, scheduler: SchedulerHandle) -> Self {
+        Self {
+            id,
+            state: AtomicU8::new(TaskState::Running as u8),
+            ref_count: AtomicUsize::new(ref_count),
+            scheduler,
+            raw_task: AtomicUsize::new(0),
+            output: lock::OptionalCell::new(),
+            abort: AtomicBool::new(false),
+            join_waker: Mutex::new(None),
+        }
+    }
+
+    /// Check whether an abort was requested
+    /// 检查是否请求了中止
+    fn is_aborted(&self) -> bool {
+        self.abort.load(Ordering::Acquire)
+    }
+
+    /// Store the final state and wake the joiner
+    /// 保存最终状态并唤醒等待者
+    fn finish(&self, state: TaskState) {
+        crate::metrics::untrack_task(self.id);
+        self.state.store(state as u8, Ordering::Release);
+        if let Some(waker) = self.join_waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+
+    /// Run a blocking closure to completion, recording its outcome
+    /// 运行阻塞闭包直到完成，并记录其结果
+    fn run<F: FnOnce() -> T>(&self, f: F) {
+        if self.is_aborted() {
+            self.finish(TaskState::Cancelled);
+            return;
+        }
+        match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(value) => {
+                self.output.set(value);
+                self.finish(TaskState::Completed);
+            },
+            Err(_) => self.finish(TaskState::Panicked),
+        }
+    }
+
+    /// Poll for the task outcome, registering the waker while it runs
+    /// 轮询任务结果，运行期间注册waker
+    fn poll_join(&self, cx: &mut Context<'_>) -> Poll<Result<T, JoinError>> {
+        let state = TaskState::from_u8(self.state.load(Ordering::Acquire));
+        if let Some(TaskState::Running | TaskState::Waiting) = state {
+            // Register first, then re-check so a finish in between is not missed
+            // 先注册再重新检查，避免错过两者之间的完成
+            let mut join_waker = self.join_waker.lock().unwrap();
+            match &mut *join_waker {
+                Some(waker) if waker.will_wake(cx.waker()) => {},
+                waker => *waker = Some(cx.waker().clone()),
+            }
+            drop(join_waker);
+            if !TaskState::from_u8(self.state.load(Ordering::Acquire))
+                .is_some_and(TaskState::is_finished)
+            {
+                return Poll::Pending;
+            }
+        }
+
+        match TaskState::from_u8(self.state.load(Ordering::Acquire)) {
+            // The output is gone if the handle was already polled to completion
+            // 如果句柄已被轮询完成，输出将不存在
+            Some(TaskState::Completed) => {
+                Poll::Ready(self.output.take().ok_or(JoinError::Cancelled))
+            },
+            Some(TaskState::Panicked) => Poll::Ready(Err(JoinError::TaskPanic)),
+            _ => Poll::Ready(Err(JoinError::Cancelled)),
+        }
+    }
 }
 
 /// Lock-free cell for optional task output
@@ -114,12 +213,15 @@ mod lock {
             self.initialized.store(1, Ordering::Release);
         }
 
-        #[allow(dead_code)]
-        pub(super) unsafe fn get(&self) -> Option<T> {
-            if self.initialized.load(Ordering::Acquire) == 1 {
-                let inner = self.inner.lock().unwrap();
-                // Read the MaybeUninit value and assume it's initialized
-                Some(inner.assume_init_read())
+        /// Move the value out, leaving the cell empty
+        /// 移出值，使单元变为空
+        pub(super) fn take(&self) -> Option<T> {
+            let inner = self.inner.lock().unwrap();
+            if self.initialized.swap(0, Ordering::AcqRel) == 1 {
+                // SAFETY: the flag was set, so the value is initialized, and
+                // clearing it means nobody reads or drops it again
+                // 安全性：标志已设置，因此值已初始化；清除标志意味着不会再次读取或释放它
+                Some(unsafe { inner.assume_init_read() })
             } else {
                 None
             }
@@ -163,14 +265,7 @@ impl<T> Task<T> {
         F: Future<Output = T> + Send + 'static,
         T: Send + 'static,
     {
-        let inner = Arc::new(TaskInner {
-            id,
-            state: AtomicU8::new(TaskState::Running as u8),
-            ref_count: AtomicUsize::new(2), // Task + waker
-            scheduler,
-            raw_task: AtomicUsize::new(0),
-            output: lock::OptionalCell::new(),
-        });
+        let inner = Arc::new(TaskInner::new(id, 2, scheduler)); // Task + waker
 
         let raw_task = Arc::into_raw(inner.clone()) as RawTask;
         inner.raw_task.store(raw_task as usize, Ordering::Release);
@@ -320,72 +415,44 @@ impl<T> JoinHandle<T> {
         }
     }
 
+    /// Request cancellation of the task
+    /// 请求取消任务
+    ///
+    /// The future is dropped before its next poll and joining then yields
+    /// [`JoinError::Cancelled`]. A task that already finished keeps its result, and
+    /// a blocking closure that has started runs to completion.
+    /// future会在下一次轮询前被丢弃，之后join会得到 [`JoinError::Cancelled`]。
+    /// 已完成的任务保留其结果，已开始的阻塞闭包会运行到完成。
+    pub fn abort(&self) {
+        self.inner.abort.store(true, Ordering::Release);
+    }
+
     /// Wait for the task to complete
     /// 等待任务完成
+    ///
+    /// The handle can also be awaited directly.
+    /// 也可以直接await该句柄。
     pub async fn wait(self) -> Result<T, JoinError> {
-        WaitForTask::new(self.inner).await
-    }
-}
-
-/// Future for waiting on task completion
-/// 等待任务完成的future
-struct WaitForTask<T> {
-    inner: Option<Arc<TaskInner<T>>>,
-}
-
-impl<T> WaitForTask<T> {
-    fn new(inner: Arc<TaskInner<T>>) -> Self {
-        Self { inner: Some(inner) }
+        self.await
     }
 }
 
-impl<T> Future for WaitForTask<T> {
+/// Dropping a join handle detaches the task; it keeps running in the background.
+/// 丢弃join句柄会分离任务；任务继续在后台运行。
+impl<T> Future for JoinHandle<T> {
     type Output = Result<T, JoinError>;
 
-    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let inner = self.inner.as_ref().unwrap();
-
-        // Check current state
-        // 检查当前状态
-        let state = TaskState::from_u8(inner.state.load(Ordering::Acquire));
-
-        match state {
-            Some(TaskState::Completed) => {
-                // Get the output
-                // 获取输出
-                let output = unsafe { inner.output.get() };
-                if let Some(result) = output {
-                    self.inner = None;
-                    Poll::Ready(Ok(result))
-                } else {
-                    // Should not happen
-                    // 不应该发生
-                    Poll::Ready(Err(JoinError::TaskCancelled))
-                }
-            },
-            Some(TaskState::Cancelled) => {
-                self.inner = None;
-                Poll::Ready(Err(JoinError::TaskCancelled))
-            },
-            Some(TaskState::Panicked) => {
-                self.inner = None;
-                Poll::Ready(Err(JoinError::TaskPanic))
-            },
-            Some(TaskState::Running | TaskState::Waiting) => {
-                // Task still running, park this future
-                // 任务仍在运行，暂停此future
-                Poll::Pending
-            },
-            None => Poll::Ready(Err(JoinError::TaskCancelled)),
-        }
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.poll_join(cx)
     }
 }
 
-impl<T> Drop for WaitForTask<T> {
-    fn drop(&mut self) {
-        // Clear inner to prevent holding reference
-        // 清除inner以防止持有引用
-        self.inner = None;
+impl<T> std::fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("id", &self.id())
+            .field("finished", &self.is_finished())
+            .finish()
     }
 }
 
@@ -393,16 +460,34 @@ impl<T> Drop for WaitForTask<T> {
 /// 加入任务的错误
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum JoinError {
-    /// Task was cancelled
-    TaskCancelled,
+    /// Task was aborted before it finished
+    /// 任务在完成前被中止
+    Cancelled,
     /// Task panicked
+    /// 任务发生panic
     TaskPanic,
 }
 
+impl JoinError {
+    /// Check whether the task was aborted
+    /// 检查任务是否被中止
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled)
+    }
+
+    /// Check whether the task panicked
+    /// 检查任务是否发生panic
+    #[must_use]
+    pub fn is_panic(&self) -> bool {
+        matches!(self, Self::TaskPanic)
+    }
+}
+
 impl std::fmt::Display for JoinError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::TaskCancelled => write!(f, "Task was cancelled"),
+            Self::Cancelled => write!(f, "Task was cancelled"),
             Self::TaskPanic => write!(f, "Task panicked"),
         }
     }
@@ -438,6 +523,7 @@ impl std::error::Error for JoinError {}
 /// Full integration with the runtime scheduler will be added in Phase 3.
 /// 注意：这是第2阶段的简化实现。
 /// 与运行时调度器的完全集成将在第3阶段添加。
+#[track_caller]
 pub fn spawn<F, T>(future: F) -> JoinHandle<T>
 where
     F: Future<Output = T> + Send + 'static,
@@ -450,21 +536,26 @@ where
     // 第2阶段，我们使用简单的基于线程的执行器
     // 每个生成的任务都有自己的线程来运行future到完成
 
-    let id = gen_task_id();
-    let inner = Arc::new(TaskInner {
-        id,
-        state: AtomicU8::new(TaskState::Running as u8),
-        ref_count: AtomicUsize::new(1),
-        scheduler: SchedulerHandle::new_default(),
-        raw_task: AtomicUsize::new(0),
-        output: lock::OptionalCell::new(),
-    });
+    let inner = Arc::new(TaskInner::new(gen_task_id(), 1, SchedulerHandle::new_default()));
+    crate::metrics::track_task(inner.id, TaskKind::Async, Location::caller());
+
+    // Inside a test runtime the task runs on its executor
+    // 在测试运行时内，任务在其执行器上运行
+    if let Some(executor) = crate::runtime::Executor::current() {
+        executor.spawn(local_task(inner.clone(), future));
+        return JoinHandle { inner };
+    }
 
     let inner_clone = inner.clone();
+    let reactor = crate::driver::reactor::Reactor::current();
 
     // Spawn a thread to run the future
     // 生成一个线程来运行future
     thread::spawn(move || {
+        // The task keeps doing I/O through its runtime's driver
+        // 任务继续通过其运行时的driver进行I/O
+        let _enter = reactor.as_ref().map(crate::driver::reactor::Reactor::enter);
+
         // Pin the future and poll it to completion
         // Pin future并轮询它到完成
         let mut future = Box::pin(future);
@@ -479,27 +570,61 @@ where
         // Phase 3 will integrate with the runtime scheduler
         // 第3阶段将与运行时调度器集成
         let result = loop {
-            match Pin::new(&mut future).poll(&mut context) {
-                Poll::Ready(value) => break value,
-                Poll::Pending => {
+            // An aborted task drops its future without polling it again
+            // 被中止的任务丢弃其future，不再轮询
+            if inner_clone.is_aborted() {
+                drop(future);
+                inner_clone.finish(TaskState::Cancelled);
+                return;
+            }
+            match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(&mut context))) {
+                Ok(Poll::Ready(value)) => break value,
+                Ok(Poll::Pending) => {
                     // For Phase 2, just yield briefly
                     // Phase 2暂时只需要短暂yield
                     thread::sleep(std::time::Duration::from_millis(1));
                 },
+                Err(_) => {
+                    inner_clone.finish(TaskState::Panicked);
+                    return;
+                },
             }
         };
 
         // Store the result
         // 存储结果
         inner_clone.output.set(result);
-        inner_clone
-            .state
-            .store(TaskState::Completed as u8, Ordering::Release);
+        inner_clone.finish(TaskState::Completed);
     });
 
     JoinHandle { inner }
 }
 
+/// Wrap a future for a test runtime executor, recording its outcome
+/// 为测试运行时执行器包装future，并记录其结果
+fn local_task<F, T>(inner: Arc<TaskInner<T>>, future: F) -> crate::runtime::LocalTask
+where
+    F: Future<Output = T> + 'static,
+    T: 'static,
+{
+    let mut future = Box::pin(future);
+    Box::pin(std::future::poll_fn(move |cx| {
+        if inner.is_aborted() {
+            inner.finish(TaskState::Cancelled);
+            return Poll::Ready(());
+        }
+        match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+            Ok(Poll::Ready(value)) => {
+                inner.output.set(value);
+                inner.finish(TaskState::Completed);
+            },
+            Ok(Poll::Pending) => return Poll::Pending,
+            Err(_) => inner.finish(TaskState::Panicked),
+        }
+        Poll::Ready(())
+    }))
+}
+
 /// Block on a future to completion
 /// 阻塞等待future完成
 ///
@@ -600,7 +725,36 @@ mod tests {
 
     #[test]
     fn test_join_error_display() {
-        assert_eq!(format!("{}", JoinError::TaskCancelled), "Task was cancelled");
+        assert_eq!(format!("{}", JoinError::Cancelled), "Task was cancelled");
         assert_eq!(format!("{}", JoinError::TaskPanic), "Task panicked");
     }
+
+    #[test]
+    fn test_join_handle_is_awaitable() {
+        let value = block_on(async { spawn(async { 6 * 7 }).await });
+        assert_eq!(value, Ok(42));
+    }
+
+    #[test]
+    fn test_abort_cancels_running_task() {
+        let result = block_on(async {
+            let handle = spawn(async {
+                crate::time::sleep(std::time::Duration::from_mins(1)).await;
+            });
+            handle.abort();
+            handle.await
+        });
+        assert_eq!(result, Err(JoinError::Cancelled));
+    }
+
+    #[test]
+    fn test_panicking_task_reports_panic() {
+        let result = block_on(async {
+            spawn(async {
+                panic!("boom");
+            })
+            .await
+        });
+        assert!(result.unwrap_err().is_panic());
+    }
 }
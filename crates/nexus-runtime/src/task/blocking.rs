@@ -0,0 +1,192 @@
+//! Thread pool for blocking work
+//! 用于阻塞工作的线程池
+//!
+//! # Overview / 概述
+//!
+//! CPU-heavy or blocking calls (password hashing, synchronous file or database
+//! access) must not run on the threads that poll futures. [`spawn_blocking`] hands
+//! them to a shared pool that grows on demand up to [`MAX_BLOCKING_THREADS`]
+//! threads; further jobs queue until a thread frees up. Idle threads exit after
+//! a few seconds.
+//!
+//! CPU密集型或阻塞调用（密码哈希、同步文件或数据库访问）不能在轮询future的线程上运行。
+//! [`spawn_blocking`] 将它们交给一个共享线程池，线程池按需增长，最多
+//! [`MAX_BLOCKING_THREADS`] 个线程；更多的任务会排队等待空闲线程。空闲线程在几秒后退出。
+
+use std::collections::VecDeque;
+use std::panic::Location;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::{JoinHandle, TaskInner, gen_task_id};
+use crate::metrics::{TaskKind, track_task};
+use crate::scheduler::SchedulerHandle;
+
+/// Upper bound on the number of blocking pool threads
+/// 阻塞线程池的线程数上限
+pub const MAX_BLOCKING_THREADS: usize = 512;
+
+/// How long an idle pool thread waits for work before exiting
+/// 空闲线程在退出前等待工作的时长
+const KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+/// A queued blocking job
+/// 排队中的阻塞任务
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Pool bookkeeping guarded by a lock
+/// 由锁保护的线程池簿记信息
+struct PoolState {
+    /// Jobs waiting for a thread / 等待线程的任务
+    queue: VecDeque<Job>,
+    /// Live pool threads / 存活的线程数
+    threads: usize,
+    /// Threads waiting for work / 等待工作的线程数
+    idle: usize,
+    /// Idle threads already woken for a queued job / 已为排队任务唤醒的空闲线程数
+    notified: usize,
+}
+
+/// Shared blocking pool
+/// 共享的阻塞线程池
+struct Pool {
+    state: Mutex<PoolState>,
+    condvar: Condvar,
+}
+
+static POOL: Pool = Pool {
+    state: Mutex::new(PoolState {
+        queue: VecDeque::new(),
+        threads: 0,
+        idle: 0,
+        notified: 0,
+    }),
+    condvar: Condvar::new(),
+};
+
+impl Pool {
+    /// Queue a job, waking an idle thread or starting a new one
+    /// 将任务排队，唤醒空闲线程或启动新线程
+    fn execute(&'static self, job: Job) {
+        let mut state = self.state.lock().unwrap();
+        state.queue.push_back(job);
+        if state.idle > state.notified {
+            state.notified += 1;
+            self.condvar.notify_one();
+        } else if state.threads < MAX_BLOCKING_THREADS {
+            state.threads += 1;
+            drop(state);
+            let spawned = thread::Builder::new()
+                .name("nexus-blocking".to_string())
+                .spawn(|| self.work());
+            if spawned.is_err() {
+                // The job stays queued for the existing threads
+                // 任务留在队列中，由现有线程处理
+                self.state.lock().unwrap().threads -= 1;
+            }
+        }
+    }
+
+    /// Pool thread main loop
+    /// 线程池线程主循环
+    fn work(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(job) = state.queue.pop_front() {
+                drop(state);
+                job();
+                state = self.state.lock().unwrap();
+                continue;
+            }
+
+            state.idle += 1;
+            let (next, timeout) = self.condvar.wait_timeout(state, KEEP_ALIVE).unwrap();
+            state = next;
+            state.idle -= 1;
+            if state.notified > 0 {
+                state.notified -= 1;
+            } else if timeout.timed_out() && state.queue.is_empty() {
+                state.threads -= 1;
+                return;
+            }
+        }
+    }
+}
+
+/// Run a blocking closure on the blocking thread pool
+/// 在阻塞线程池上运行阻塞闭包
+///
+/// Aborting the returned handle only prevents the closure from starting; once
+/// running it cannot be interrupted.
+/// 中止返回的句柄只能阻止闭包启动；闭包一旦运行就无法被中断。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// use nexus_runtime::task::spawn_blocking;
+///
+/// async fn hash(password: String) -> String {
+///     spawn_blocking(move || bcrypt::hash(password, 12).unwrap())
+///         .await
+///         .unwrap()
+/// }
+/// ```
+#[track_caller]
+pub fn spawn_blocking<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let inner = Arc::new(TaskInner::new(gen_task_id(), 1, SchedulerHandle::new_default()));
+    track_task(inner.id, TaskKind::Blocking, Location::caller());
+    let task = inner.clone();
+    let job = move || task.run(f);
+    match crate::runtime::Executor::current() {
+        Some(executor) => POOL.execute(Box::new(executor.track_blocking(job))),
+        None => POOL.execute(Box::new(job)),
+    }
+    JoinHandle { inner }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{JoinError, block_on};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_spawn_blocking_returns_value() {
+        let value = block_on(async { spawn_blocking(|| 6 * 7).await });
+        assert_eq!(value, Ok(42));
+    }
+
+    #[test]
+    fn test_spawn_blocking_runs_concurrently() {
+        static RUNNING: AtomicUsize = AtomicUsize::new(0);
+        let peak = block_on(async {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    spawn_blocking(|| {
+                        let now = RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+                        thread::sleep(Duration::from_millis(50));
+                        RUNNING.fetch_sub(1, Ordering::SeqCst);
+                        now
+                    })
+                })
+                .collect();
+            let mut peak = 0;
+            for handle in handles {
+                peak = peak.max(handle.await.unwrap());
+            }
+            peak
+        });
+        assert!(peak > 1);
+    }
+
+    #[test]
+    fn test_spawn_blocking_panic() {
+        let result = block_on(async { spawn_blocking(|| -> u8 { panic!("boom") }).await });
+        assert_eq!(result, Err(JoinError::TaskPanic));
+    }
+}
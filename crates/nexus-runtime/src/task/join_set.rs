@@ -0,0 +1,205 @@
+//! Structured groups of tasks
+//! 结构化的任务组
+//!
+//! # Overview / 概述
+//!
+//! A [`JoinSet`] owns the tasks spawned through it. Results are collected in
+//! completion order with [`JoinSet::join_next`], and dropping the set aborts
+//! every task that is still running, so no task outlives the scope that
+//! started it.
+//!
+//! [`JoinSet`] 拥有通过它生成的任务。通过 [`JoinSet::join_next`] 按完成顺序收集结果，
+//! 丢弃任务集会中止所有仍在运行的任务，因此没有任务会比启动它的作用域活得更久。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::task::JoinSet;
+//!
+//! async fn fetch_all(urls: Vec<String>) -> Vec<usize> {
+//!     let mut set = JoinSet::new();
+//!     for url in urls {
+//!         set.spawn(async move { url.len() });
+//!     }
+//!     let mut sizes = Vec::new();
+//!     while let Some(result) = set.join_next().await {
+//!         sizes.push(result.unwrap());
+//!     }
+//!     sizes
+//! }
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+use super::{JoinError, JoinHandle, TaskId, spawn, spawn_blocking};
+
+/// A set of tasks that are aborted together when the set is dropped
+/// 丢弃时会一起中止的任务集
+pub struct JoinSet<T> {
+    tasks: Vec<JoinHandle<T>>,
+}
+
+impl<T> JoinSet<T> {
+    /// Create an empty set
+    /// 创建空的任务集
+    #[must_use]
+    pub fn new() -> Self {
+        Self { tasks: Vec::new() }
+    }
+
+    /// Get the number of tasks not yet joined
+    /// 获取尚未join的任务数
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Check whether the set has no tasks left to join
+    /// 检查任务集是否没有待join的任务
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Spawn a task into the set
+    /// 在任务集中生成任务
+    #[track_caller]
+    pub fn spawn<F>(&mut self, future: F) -> TaskId
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.push(spawn(future))
+    }
+
+    /// Run a blocking closure on the blocking pool as part of the set
+    /// 作为任务集的一部分在阻塞线程池上运行阻塞闭包
+    #[track_caller]
+    pub fn spawn_blocking<F>(&mut self, f: F) -> TaskId
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.push(spawn_blocking(f))
+    }
+
+    fn push(&mut self, handle: JoinHandle<T>) -> TaskId {
+        let id = handle.id();
+        self.tasks.push(handle);
+        id
+    }
+
+    /// Wait for the next task to finish and take its result
+    /// 等待下一个任务完成并取得其结果
+    ///
+    /// Returns `None` once the set is empty. Cancellation safe: a task whose result
+    /// was not returned stays in the set.
+    /// 任务集为空时返回 `None`。取消安全：结果未被返回的任务仍留在任务集中。
+    pub async fn join_next(&mut self) -> Option<Result<T, JoinError>> {
+        std::future::poll_fn(|cx| {
+            if self.tasks.is_empty() {
+                return Poll::Ready(None);
+            }
+            for index in 0..self.tasks.len() {
+                if let Poll::Ready(result) = Pin::new(&mut self.tasks[index]).poll(cx) {
+                    self.tasks.swap_remove(index);
+                    return Poll::Ready(Some(result));
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Take the result of a finished task without waiting
+    /// 不等待地取得一个已完成任务的结果
+    pub fn try_join_next(&mut self) -> Option<Result<T, JoinError>> {
+        let index = self.tasks.iter().position(JoinHandle::is_finished)?;
+        let handle = self.tasks.swap_remove(index);
+        match handle
+            .inner
+            .poll_join(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(result) => Some(result),
+            Poll::Pending => None,
+        }
+    }
+
+    /// Request cancellation of every task; they still have to be joined
+    /// 请求取消所有任务；仍需join它们
+    pub fn abort_all(&self) {
+        self.tasks.iter().for_each(JoinHandle::abort);
+    }
+
+    /// Abort every task and wait until all of them have stopped
+    /// 中止所有任务并等待它们全部停止
+    pub async fn shutdown(&mut self) {
+        self.abort_all();
+        while self.join_next().await.is_some() {}
+    }
+
+    /// Remove every task from the set without aborting it
+    /// 从任务集中移除所有任务而不中止它们
+    pub fn detach_all(&mut self) {
+        self.tasks.clear();
+    }
+}
+
+impl<T> Default for JoinSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for JoinSet<T> {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
+
+impl<T> std::fmt::Debug for JoinSet<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JoinSet").field("len", &self.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::block_on;
+    use crate::time::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn test_join_next_in_completion_order() {
+        let order = block_on(async {
+            let mut set = JoinSet::new();
+            for delay in [90_u64, 10, 50] {
+                set.spawn(async move {
+                    sleep(Duration::from_millis(delay)).await;
+                    delay
+                });
+            }
+            let mut order = Vec::new();
+            while let Some(result) = set.join_next().await {
+                order.push(result.unwrap());
+            }
+            order
+        });
+        assert_eq!(order, [10, 50, 90]);
+    }
+
+    #[test]
+    fn test_shutdown_aborts_tasks() {
+        let remaining = block_on(async {
+            let mut set = JoinSet::new();
+            set.spawn(async { sleep(Duration::from_mins(1)).await });
+            set.spawn_blocking(|| ());
+            set.shutdown().await;
+            set.len()
+        });
+        assert_eq!(remaining, 0);
+    }
+}
@@ -0,0 +1,281 @@
+//! Task-local storage
+//! 任务本地存储
+//!
+//! # Overview / 概述
+//!
+//! A task-local value is bound to a future with [`LocalKey::scope`] and is
+//! visible to that future, and everything it awaits, whichever thread polls it.
+//! It suits request-scoped context such as the security context or MDC fields.
+//!
+//! 任务本地值通过 [`LocalKey::scope`] 绑定到一个future，无论由哪个线程轮询，该future
+//! 及其await的所有内容都能看到它。它适用于请求范围的上下文，如安全上下文或MDC字段。
+//!
+//! Spawned tasks start without any task-local values. Wrap the spawned future with
+//! [`LocalKey::inherit`] to carry the current value over.
+//!
+//! 生成的任务启动时不带任何任务本地值。用 [`LocalKey::inherit`] 包装要生成的future
+//! 以传递当前值。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! use nexus_runtime::{spawn, task_local};
+//!
+//! task_local! {
+//!     static REQUEST_ID: String;
+//! }
+//!
+//! async fn handle() {
+//!     REQUEST_ID
+//!         .scope("req-1".to_string(), async {
+//!             assert_eq!(REQUEST_ID.get(), "req-1");
+//!             spawn(REQUEST_ID.inherit(async {
+//!                 assert_eq!(REQUEST_ID.get(), "req-1");
+//!             }));
+//!         })
+//!         .await;
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+
+/// Declare task-local keys of type [`LocalKey`]
+/// 声明 [`LocalKey`] 类型的任务本地键
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// nexus_runtime::task_local! {
+///     pub static USER: String;
+///     static TRACE_ID: u64;
+/// }
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+        $crate::task_local!($($rest)*);
+    };
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __task_local_inner {
+    ($(#[$attr:meta])* $vis:vis $name:ident, $t:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::task::LocalKey<$t> = {
+            std::thread_local! {
+                static __KEY: std::cell::RefCell<Option<$t>> =
+                    const { std::cell::RefCell::new(None) };
+            }
+
+            $crate::task::LocalKey { inner: __KEY }
+        };
+    };
+}
+
+/// Error returned when a task-local value is not set
+/// 任务本地值未设置时返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessError;
+
+impl std::fmt::Display for AccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Task-local value not set")
+    }
+}
+
+impl std::error::Error for AccessError {}
+
+/// Key for a task-local value, declared with [`task_local!`](crate::task_local)
+/// 任务本地值的键，通过 [`task_local!`](crate::task_local) 声明
+pub struct LocalKey<T: 'static> {
+    #[doc(hidden)]
+    pub inner: thread::LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    /// Bind `value` to the key while `future` runs
+    /// 在 `future` 运行期间将 `value` 绑定到此键
+    pub fn scope<F: Future>(&'static self, value: T, future: F) -> TaskLocalFuture<T, F> {
+        TaskLocalFuture {
+            key: self,
+            slot: Some(value),
+            future,
+        }
+    }
+
+    /// Bind `value` to the key while the closure `f` runs
+    /// 在闭包 `f` 运行期间将 `value` 绑定到此键
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is borrowed through [`LocalKey::with`] at the time.
+    /// 如果此时该键正通过 [`LocalKey::with`] 被借用则恐慌。
+    pub fn sync_scope<F: FnOnce() -> R, R>(&'static self, value: T, f: F) -> R {
+        let mut slot = Some(value);
+        self.enter(&mut slot, f)
+    }
+
+    /// Access the current value
+    /// 访问当前值
+    ///
+    /// # Panics
+    ///
+    /// Panics if no value is set for the current task.
+    /// 如果当前任务未设置值则恐慌。
+    pub fn with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> R {
+        self.try_with(f)
+            .unwrap_or_else(|_| panic!("cannot access a task-local value outside of its scope"))
+    }
+
+    /// Access the current value if one is set
+    /// 如果设置了值则访问当前值
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError`] if no value is set for the current task.
+    /// 如果当前任务未设置值则返回 [`AccessError`]。
+    pub fn try_with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> Result<R, AccessError> {
+        self.inner
+            .try_with(|cell| cell.borrow().as_ref().map(f))
+            .ok()
+            .flatten()
+            .ok_or(AccessError)
+    }
+
+    /// Make `future` see the current value of this key, e.g. before spawning it
+    /// 使 `future` 能看到此键的当前值，例如在生成任务之前
+    ///
+    /// If no value is set the future runs without one.
+    /// 如果未设置值，future将在没有值的情况下运行。
+    pub fn inherit<F: Future>(&'static self, future: F) -> TaskLocalFuture<T, F>
+    where
+        T: Clone,
+    {
+        TaskLocalFuture {
+            key: self,
+            slot: self.try_with(T::clone).ok(),
+            future,
+        }
+    }
+
+    /// Swap `slot` into the thread-local for the duration of `f`
+    /// 在 `f` 执行期间将 `slot` 换入线程本地存储
+    fn enter<F: FnOnce() -> R, R>(&'static self, slot: &mut Option<T>, f: F) -> R {
+        /// Swaps the values back even if `f` panics
+        /// 即使 `f` 发生panic也会把值换回
+        struct Guard<'a, T: 'static> {
+            key: &'static LocalKey<T>,
+            slot: &'a mut Option<T>,
+        }
+
+        impl<T: 'static> Drop for Guard<'_, T> {
+            fn drop(&mut self) {
+                self.key
+                    .inner
+                    .with(|cell| std::mem::swap(self.slot, &mut *cell.borrow_mut()));
+            }
+        }
+
+        self.inner.with(|cell| {
+            let mut current = cell
+                .try_borrow_mut()
+                .unwrap_or_else(|_| panic!("task-local value entered while borrowed"));
+            std::mem::swap(slot, &mut *current);
+        });
+        let _guard = Guard { key: self, slot };
+        f()
+    }
+}
+
+impl<T: Clone + 'static> LocalKey<T> {
+    /// Get a copy of the current value
+    /// 获取当前值的副本
+    ///
+    /// # Panics
+    ///
+    /// Panics if no value is set for the current task.
+    /// 如果当前任务未设置值则恐慌。
+    pub fn get(&'static self) -> T {
+        self.with(T::clone)
+    }
+}
+
+impl<T: 'static> std::fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalKey").finish_non_exhaustive()
+    }
+}
+
+/// Future that runs with a task-local value set, created by [`LocalKey::scope`]
+/// 在设置了任务本地值的情况下运行的future，由 [`LocalKey::scope`] 创建
+pub struct TaskLocalFuture<T: 'static, F> {
+    key: &'static LocalKey<T>,
+    /// The value while the future is not being polled / future未被轮询时的值
+    slot: Option<T>,
+    future: F,
+}
+
+impl<T: 'static, F: Future> Future for TaskLocalFuture<T, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // SAFETY: `future` is structurally pinned and never moved; `slot` and
+        // `key` are not pinned
+        // 安全性：`future` 是结构性固定的且从不移动；`slot` 和 `key` 不固定
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        this.key.enter(&mut this.slot, || future.poll(cx))
+    }
+}
+
+impl<T: 'static, F> std::fmt::Debug for TaskLocalFuture<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskLocalFuture").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{block_on, spawn};
+
+    crate::task_local! {
+        static NAME: &'static str;
+        static DEPTH: u32;
+    }
+
+    #[test]
+    fn test_scope_and_nesting() {
+        assert_eq!(NAME.try_with(|n| *n), Err(AccessError));
+        let seen = block_on(NAME.scope("outer", async {
+            let inner = NAME.scope("inner", async { NAME.get() }).await;
+            (inner, NAME.get())
+        }));
+        assert_eq!(seen, ("inner", "outer"));
+        assert_eq!(DEPTH.sync_scope(3, || DEPTH.get() + 1), 4);
+        assert!(DEPTH.try_with(|_| ()).is_err());
+    }
+
+    #[test]
+    fn test_inherit_through_spawn() {
+        let (plain, inherited) = block_on(NAME.scope("request", async {
+            let plain = spawn(async { NAME.try_with(|n| *n) }).await.unwrap();
+            let inherited = spawn(NAME.inherit(async { NAME.get() })).await.unwrap();
+            (plain, inherited)
+        }));
+        assert_eq!(plain, Err(AccessError));
+        assert_eq!(inherited, "request");
+    }
+}
@@ -23,6 +23,15 @@
 //! - 轮2：16.384s分辨率，64个槽（1048.576s范围）
 //! - 轮3：1048.576s分辨率，64个槽（67108.864s范围）
 //!
+//! # Virtual Time / 虚拟时间
+//!
+//! Inside a [`TestRuntime`](crate::runtime::TestRuntime) the timers in this module
+//! run on a virtual clock. [`pause`], [`resume`] and [`advance`] control it, and
+//! [`now`] reads it.
+//!
+//! 在 [`TestRuntime`](crate::runtime::TestRuntime) 内部，本模块的定时器运行在虚拟时钟上。
+//! 用 [`pause`]、[`resume`] 和 [`advance`] 控制它，用 [`now`] 读取它。
+//!
 //! # Example / 示例
 //!
 //! ```rust,no_run,ignore
@@ -38,11 +47,16 @@ use std::cell::UnsafeCell;
 use std::collections::{HashMap, LinkedList};
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Mutex;
-use std::sync::OnceLock;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::task::{Context, Poll, Waker};
 
+use crate::metrics::TimerStats;
+
+mod clock;
+
+pub(crate) use clock::TimeContext;
+
 /// Standard library duration re-export
 /// 标准库Duration重新导出
 pub use std::time::Duration;
@@ -170,6 +184,9 @@ pub struct TimerWheel {
     /// Active timer registry for cancellation (ID -> slot index)
     /// 活跃定时器注册表用于取消（ID -> 槽索引）
     timer_registry: Mutex<HashMap<u64, TimerLocation>>,
+    /// Number of timers that fired
+    /// 已触发的定时器数量
+    expired: AtomicU64,
 }
 
 /// Location of a timer in the wheel
@@ -177,11 +194,12 @@ pub struct TimerWheel {
 #[derive(Clone, Copy, Debug)]
 struct TimerLocation {
     /// Wheel level (0-3)
-    #[allow(dead_code)]
     wheel_level: u8,
     /// Slot index within the wheel
     #[allow(dead_code)]
     slot_index: usize,
+    /// Expiration tick
+    expiration: u64,
 }
 
 // SAFETY: TimerWheel uses atomic operations and interior mutability
@@ -221,6 +239,7 @@ impl TimerWheel {
                 .unwrap(),
             next_id: AtomicU64::new(1),
             timer_registry: Mutex::new(HashMap::new()),
+            expired: AtomicU64::new(0),
         }
     }
 
@@ -347,6 +366,7 @@ impl TimerWheel {
             }
         }
 
+        self.expired.fetch_add(expired as u64, Ordering::Relaxed);
         expired
     }
 
@@ -357,7 +377,7 @@ impl TimerWheel {
         let expiration = timer.expiration_ms / TICK_MS;
         let id = timer.id;
 
-        if expiration <= current {
+        if expiration < current {
             // Already expired, wake immediately
             // 已到期，立即唤醒
             if let Some(waker) = timer.waker {
@@ -396,6 +416,7 @@ impl TimerWheel {
                 TimerLocation {
                     wheel_level,
                     slot_index: pos,
+                    expiration,
                 },
             );
         }
@@ -454,16 +475,50 @@ impl TimerWheel {
         TimerHandle::new(id, self)
     }
 
+    /// Get a snapshot of the wheel occupancy
+    /// 获取时间轮占用情况的快照
+    #[must_use]
+    pub fn stats(&self) -> TimerStats {
+        let registry = self.timer_registry.lock().unwrap();
+        let mut per_level = [0; 4];
+        for location in registry.values() {
+            per_level[usize::from(location.wheel_level)] += 1;
+        }
+        TimerStats {
+            active: registry.len(),
+            per_level,
+            expired: self.expired.load(Ordering::Relaxed),
+            ticks: self.current_ticks(),
+        }
+    }
+
     /// Get the next timer expiration time in milliseconds
     /// 获取下一个定时器到期时间（毫秒）
     ///
     /// Returns `None` if there are no active timers.
     /// 如果没有活动定时器则返回 `None`。
     pub fn next_expiration(&self) -> Option<u64> {
-        // This is a simplified implementation
-        // A full implementation would scan all wheels
-        // 这是简化实现，完整实现会扫描所有轮
-        None
+        let registry = self.timer_registry.lock().unwrap();
+        registry
+            .values()
+            .map(|location| location.expiration * TICK_MS)
+            .min()
+    }
+
+    /// Insert a timer expiring at an absolute time in milliseconds
+    /// 插入在绝对时间（毫秒）到期的定时器
+    ///
+    /// Returns the timer ID for [`TimerWheel::cancel_timer`].
+    /// 返回用于 [`TimerWheel::cancel_timer`] 的定时器ID。
+    pub(crate) fn insert_timer_at(&self, expiration_ms: u64, waker: Waker) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.insert_timer_inner(TimerEntry {
+            id,
+            expiration_ms,
+            waker: Some(waker),
+            canceled: Mutex::new(false),
+        });
+        id
     }
 }
 
@@ -522,6 +577,114 @@ pub fn global_timer() -> &'static TimerWheel {
     GLOBAL_TIMER.get_or_init(|| TimerWheel::new())
 }
 
+/// Get the current time
+/// 获取当前时间
+///
+/// Inside a [`TestRuntime`](crate::runtime::TestRuntime) this is the virtual
+/// time, elsewhere it is [`Instant::now`]. Code that measures time and should
+/// be testable with [`advance`] reads the clock through this function.
+/// 在 [`TestRuntime`](crate::runtime::TestRuntime) 内部返回虚拟时间，其他地方等同于
+/// [`Instant::now`]。需要用 [`advance`] 测试的计时代码应通过此函数读取时钟。
+#[must_use]
+pub fn now() -> Instant {
+    TimeContext::current().map_or_else(Instant::now, |context| context.now())
+}
+
+/// Get the test runtime time context, panicking outside of one
+/// 获取测试运行时的时间上下文，不在其中时恐慌
+fn test_context(caller: &str) -> Arc<TimeContext> {
+    TimeContext::current()
+        .unwrap_or_else(|| panic!("time::{caller} must be called inside a TestRuntime"))
+}
+
+/// Pause the virtual clock of the current test runtime
+/// 暂停当前测试运行时的虚拟时钟
+///
+/// While paused, time only moves through [`advance`] or when every task is idle
+/// and the runtime skips ahead to the next timer.
+/// 暂停期间，时间只能通过 [`advance`] 前进，或在所有任务都空闲时由运行时跳到下一个定时器。
+///
+/// # Panics / 恐慌
+///
+/// Panics outside of a [`TestRuntime`](crate::runtime::TestRuntime).
+/// 在 [`TestRuntime`](crate::runtime::TestRuntime) 之外调用时恐慌。
+pub fn pause() {
+    test_context("pause").pause();
+}
+
+/// Let the virtual clock of the current test runtime follow real time again
+/// 让当前测试运行时的虚拟时钟重新跟随真实时间
+///
+/// # Panics / 恐慌
+///
+/// Panics outside of a [`TestRuntime`](crate::runtime::TestRuntime).
+/// 在 [`TestRuntime`](crate::runtime::TestRuntime) 之外调用时恐慌。
+pub fn resume() {
+    test_context("resume").resume();
+}
+
+/// Move the paused clock forward by `duration`
+/// 将暂停的时钟向前推进 `duration`
+///
+/// Timers fire in deadline order and the tasks they wake run before the clock
+/// moves on to the next one.
+/// 定时器按截止时间顺序触发，被唤醒的任务在时钟推进到下一个定时器之前运行。
+///
+/// # Panics / 恐慌
+///
+/// Panics outside of a [`TestRuntime`](crate::runtime::TestRuntime) or when the
+/// clock is not paused.
+/// 在 [`TestRuntime`](crate::runtime::TestRuntime) 之外调用或时钟未暂停时恐慌。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// #[nexus_macros::test]
+/// async fn retries_after_backoff() {
+///     let task = nexus_runtime::spawn(client.get_with_retry("/flaky"));
+///     time::advance(Duration::from_secs(30)).await;
+///     assert!(task.is_finished());
+/// }
+/// ```
+pub async fn advance(duration: Duration) {
+    let context = test_context("advance");
+    assert!(context.is_paused(), "time::advance requires a paused clock");
+
+    let target = context.elapsed() + duration;
+    // Let woken tasks reach their timers first
+    // 先让被唤醒的任务到达其定时器
+    YieldNow(false).await;
+    loop {
+        let elapsed = context.elapsed();
+        let step = match context.next_timer() {
+            Some(next) if next > elapsed && next < target => next.saturating_sub(elapsed),
+            _ => target.saturating_sub(elapsed),
+        };
+        context.advance(step);
+        YieldNow(false).await;
+        if context.elapsed() >= target {
+            break;
+        }
+    }
+}
+
+/// Future that is pending exactly once
+/// 恰好挂起一次的future
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if std::mem::replace(&mut self.0, true) {
+            Poll::Ready(())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
 /// Sleep future that completes after the specified duration
 /// 在指定持续时间后完成的sleep future
 pub struct Sleep {
@@ -532,6 +695,9 @@ pub struct Sleep {
     registered: bool,
     /// Start time / 开始时间
     start: Option<Instant>,
+    /// Timer on the virtual clock of a test runtime, with the waker it wakes
+    /// 测试运行时虚拟时钟上的定时器，及其唤醒的waker
+    virtual_timer: Option<(Arc<TimeContext>, u64, Waker)>,
 }
 
 impl Sleep {
@@ -542,14 +708,55 @@ impl Sleep {
             duration,
             registered: false,
             start: None,
+            virtual_timer: None,
+        }
+    }
+
+    /// Poll against the virtual clock of a test runtime
+    /// 基于测试运行时的虚拟时钟轮询
+    fn poll_virtual(&mut self, context: Arc<TimeContext>, cx: &mut Context<'_>) -> Poll<()> {
+        let now = context.now();
+        let start = *self.start.get_or_insert(now);
+        if now.duration_since(start) >= self.duration {
+            self.cancel_virtual();
+            return Poll::Ready(());
+        }
+
+        // Register again only when polled by a different task
+        // 仅在被不同任务轮询时重新注册
+        let registered = self
+            .virtual_timer
+            .as_ref()
+            .is_some_and(|(_, _, waker)| waker.will_wake(cx.waker()));
+        if !registered {
+            self.cancel_virtual();
+            let id = context.register(start + self.duration, cx.waker().clone());
+            self.virtual_timer = Some((context, id, cx.waker().clone()));
+        }
+        Poll::Pending
+    }
+
+    fn cancel_virtual(&mut self) {
+        if let Some((context, id, _)) = self.virtual_timer.take() {
+            context.cancel(id);
         }
     }
 }
 
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        self.cancel_virtual();
+    }
+}
+
 impl Future for Sleep {
     type Output = ();
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(context) = TimeContext::current() {
+            return self.poll_virtual(context, cx);
+        }
+
         if !self.registered {
             // First poll: register the timer
             // 第一次轮询：注册定时器
@@ -608,7 +815,7 @@ pub fn sleep(duration: Duration) -> Sleep {
 /// }
 /// ```
 pub fn sleep_until(instant: Instant) -> SleepUntil {
-    let now = Instant::now();
+    let now = now();
     let duration = if instant > now {
         instant.duration_since(now)
     } else {
@@ -634,6 +841,103 @@ impl Future for SleepUntil {
     }
 }
 
+/// Require a future to complete before the specified duration has elapsed
+/// 要求future在指定持续时间内完成
+///
+/// If the future completes first its output is returned, otherwise the future
+/// is dropped and [`Elapsed`] is returned.
+/// 如果future先完成则返回其输出，否则丢弃future并返回 [`Elapsed`]。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// use nexus_runtime::time::{timeout, Duration};
+///
+/// async fn example() {
+///     match timeout(Duration::from_secs(1), slow_operation()).await {
+///         Ok(value) => println!("Completed: {:?}", value),
+///         Err(_) => println!("Timed out"),
+///     }
+/// }
+/// ```
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    Timeout {
+        future,
+        sleep: sleep(duration),
+    }
+}
+
+/// Require a future to complete before the specified instant
+/// 要求future在指定时刻之前完成
+pub fn timeout_at<F: Future>(deadline: Instant, future: F) -> Timeout<F> {
+    Timeout {
+        future,
+        sleep: sleep_until(deadline).sleep,
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Future returned by [`timeout`] and [`timeout_at`]
+    /// [`timeout`] 和 [`timeout_at`] 返回的future
+    pub struct Timeout<F> {
+        #[pin]
+        future: F,
+        sleep: Sleep,
+    }
+}
+
+impl<F> Timeout<F> {
+    /// Get a reference to the inner future
+    /// 获取内部future的引用
+    pub fn get_ref(&self) -> &F {
+        &self.future
+    }
+
+    /// Consume the timeout, returning the inner future
+    /// 消费超时，返回内部future
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        // Always give the inner future a chance first
+        // 总是先给内部future一次机会
+        if let Poll::Ready(value) = this.future.poll(cx) {
+            return Poll::Ready(Ok(value));
+        }
+
+        match Pin::new(this.sleep).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(Elapsed(()))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Error returned when a [`timeout`] elapses
+/// [`timeout`] 到期时返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed(());
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+impl From<Elapsed> for std::io::Error {
+    fn from(_: Elapsed) -> Self {
+        std::io::Error::new(std::io::ErrorKind::TimedOut, "deadline has elapsed")
+    }
+}
+
 /// Interval timer that yields at regular intervals
 /// 以固定间隔产生的间隔定时器
 ///
@@ -653,7 +957,7 @@ impl Future for SleepUntil {
 pub fn interval(duration: Duration) -> Interval {
     Interval {
         duration,
-        next: Instant::now(),
+        next: now(),
     }
 }
 
@@ -668,7 +972,7 @@ impl Interval {
     /// Wait for the next tick
     /// 等待下一个滴答
     pub async fn tick(&mut self) -> Instant {
-        let now = Instant::now();
+        let now = now();
         if now >= self.next {
             self.next = now + self.duration;
         }
@@ -697,12 +1001,47 @@ mod tests {
         assert_eq!(WHEEL3_SIZE, 64);
     }
 
+    #[test]
+    fn test_timer_wheel_stats() {
+        let wheel = TimerWheel::new();
+        let _short = wheel.insert_timer(Duration::from_millis(10));
+        let medium = wheel.insert_timer(Duration::from_secs(1));
+        let _long = wheel.insert_timer(Duration::from_secs(100));
+        let stats = wheel.stats();
+        assert_eq!(stats.active, 3);
+        assert_eq!(stats.per_level, [1, 1, 1, 0]);
+
+        medium.cancel();
+        assert_eq!(wheel.advance(20), 1);
+        let stats = wheel.stats();
+        assert_eq!(stats.active, 1);
+        assert_eq!(stats.per_level, [0, 0, 1, 0]);
+        assert_eq!(stats.expired, 1);
+        assert_eq!(stats.ticks, 20);
+    }
+
     #[test]
     fn test_global_timer() {
         let timer = global_timer();
         assert_eq!(timer.current_ticks(), 0);
     }
 
+    #[test]
+    fn test_timeout_completes_before_deadline() {
+        let result = crate::task::block_on(timeout(Duration::from_secs(5), async { 42 }));
+        assert_eq!(result, Ok(42));
+    }
+
+    #[test]
+    fn test_timeout_elapses() {
+        let result = crate::task::block_on(timeout(
+            Duration::from_millis(10),
+            sleep(Duration::from_secs(5)),
+        ));
+        assert_eq!(result, Err(Elapsed(())));
+        assert_eq!(std::io::Error::from(Elapsed(())).kind(), std::io::ErrorKind::TimedOut);
+    }
+
     #[test]
     fn test_max_timeout() {
         // Maximum timeout should be about 18.6 hours
@@ -0,0 +1,158 @@
+//! Virtual clock of the test runtime
+//! 测试运行时的虚拟时钟
+//!
+//! # Overview / 概述
+//!
+//! Inside a [`TestRuntime`](crate::runtime::TestRuntime) time comes from a
+//! [`TimeContext`] instead of the system clock. The context owns its own
+//! [`TimerWheel`] with one tick per millisecond of virtual time. While the clock
+//! is paused it only moves through [`advance`](super::advance) or when the
+//! runtime has nothing left to run and jumps to the next timer.
+//!
+//! 在 [`TestRuntime`](crate::runtime::TestRuntime) 内部，时间来自 [`TimeContext`] 而不是
+//! 系统时钟。上下文拥有自己的 [`TimerWheel`]，每毫秒虚拟时间一个滴答。时钟暂停时，
+//! 只有通过 [`advance`](super::advance) 或运行时无事可做并跳到下一个定时器时才会前进。
+
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+
+use super::{Duration, Instant, TimerWheel};
+
+/// Clock position / 时钟位置
+struct ClockState {
+    /// Virtual time at the last pause or resume / 上次暂停或恢复时的虚拟时间
+    base: Duration,
+    /// Real time of the last resume; `None` while paused / 上次恢复的真实时间；暂停时为 `None`
+    resumed_at: Option<Instant>,
+}
+
+/// Time source and timers of one test runtime
+/// 单个测试运行时的时间源和定时器
+pub(crate) struct TimeContext {
+    /// Real time when the runtime was created / 运行时创建时的真实时间
+    origin: Instant,
+    state: Mutex<ClockState>,
+    wheel: TimerWheel,
+}
+
+thread_local! {
+    /// Time context of the test runtime running on this thread
+    /// 在此线程上运行的测试运行时的时间上下文
+    static CURRENT: RefCell<Option<Arc<TimeContext>>> = const { RefCell::new(None) };
+}
+
+impl TimeContext {
+    pub(crate) fn new(paused: bool) -> Arc<Self> {
+        let origin = Instant::now();
+        Arc::new(Self {
+            origin,
+            state: Mutex::new(ClockState {
+                base: Duration::ZERO,
+                resumed_at: (!paused).then_some(origin),
+            }),
+            wheel: TimerWheel::new(),
+        })
+    }
+
+    /// Get the context of the current thread, if any
+    /// 获取当前线程的上下文（如果有）
+    pub(crate) fn current() -> Option<Arc<Self>> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Install this context on the current thread until the guard drops
+    /// 在当前线程上安装此上下文，直到守卫被丢弃
+    pub(crate) fn enter(self: &Arc<Self>) -> EnterGuard {
+        let previous = CURRENT.with(|current| current.borrow_mut().replace(self.clone()));
+        EnterGuard { previous }
+    }
+
+    /// Virtual time since the runtime was created
+    /// 自运行时创建以来的虚拟时间
+    pub(crate) fn elapsed(&self) -> Duration {
+        let state = self.state.lock().unwrap();
+        match state.resumed_at {
+            Some(resumed_at) => state.base + resumed_at.elapsed(),
+            None => state.base,
+        }
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.state.lock().unwrap().resumed_at.is_none()
+    }
+
+    pub(crate) fn pause(&self) {
+        let elapsed = self.elapsed();
+        let mut state = self.state.lock().unwrap();
+        state.base = elapsed;
+        state.resumed_at = None;
+    }
+
+    pub(crate) fn resume(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.resumed_at.is_none() {
+            state.resumed_at = Some(Instant::now());
+        }
+    }
+
+    /// Move a paused clock forward and fire the timers that became due
+    /// 推进暂停的时钟并触发到期的定时器
+    pub(crate) fn advance(&self, duration: Duration) {
+        self.state.lock().unwrap().base += duration;
+        self.fire_due();
+    }
+
+    /// Fire every timer due at the current virtual time
+    /// 触发当前虚拟时间到期的所有定时器
+    ///
+    /// After this the wheel has processed every tick up to and including now.
+    /// 此后时间轮已处理到当前时刻（含）为止的所有滴答。
+    pub(crate) fn fire_due(&self) -> usize {
+        let target = self.elapsed().as_millis() as u64 + 1;
+        let current = self.wheel.current_ticks();
+        if target > current {
+            self.wheel.advance(target - current)
+        } else {
+            0
+        }
+    }
+
+    /// Virtual time of the earliest pending timer
+    /// 最早挂起定时器的虚拟时间
+    pub(crate) fn next_timer(&self) -> Option<Duration> {
+        self.wheel.next_expiration().map(Duration::from_millis)
+    }
+
+    /// Register a waker for the given virtual deadline, returning the timer ID
+    /// 为给定的虚拟截止时间注册waker，返回定时器ID
+    pub(crate) fn register(&self, deadline: Instant, waker: Waker) -> u64 {
+        let deadline = deadline.saturating_duration_since(self.origin);
+        // Round up so the timer never fires before the deadline
+        // 向上取整，使定时器不会在截止时间之前触发
+        let ms = deadline.as_millis() as u64
+            + u64::from(!deadline.subsec_nanos().is_multiple_of(1_000_000));
+        self.wheel.insert_timer_at(ms, waker)
+    }
+
+    pub(crate) fn cancel(&self, id: u64) {
+        self.wheel.cancel_timer(id);
+    }
+}
+
+/// Restores the previous context on drop
+/// 丢弃时恢复先前的上下文
+pub(crate) struct EnterGuard {
+    previous: Option<Arc<TimeContext>>,
+}
+
+impl Drop for EnterGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
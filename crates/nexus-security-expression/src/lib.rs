@@ -0,0 +1,597 @@
+//! Security expression parser
+//! 安全表达式解析器
+//!
+//! Parses the `SpEL` subset used by `@PreAuthorize` rules: boolean operators,
+//! parentheses, comparisons, the Spring Security functions, `principal` /
+//! `authentication` property access and `#name` method-argument references.
+//! `nexus-security` evaluates the resulting tree at runtime and `nexus-macros`
+//! checks it while the annotated crate compiles, so both accept exactly the same
+//! expressions.
+//!
+//! 解析 `@PreAuthorize` 规则所用的SpEL子集：布尔运算符、括号、比较、Spring Security函数、
+//! `principal` / `authentication` 属性访问以及 `#name` 方法参数引用。`nexus-security`
+//! 在运行时评估生成的语法树，`nexus-macros` 在被注解的crate编译时检查它，因此两者接受的
+//! 表达式完全相同。
+//!
+//! # Grammar / 语法
+//!
+//! ```text
+//! or         := and (("or" | "||") and)*
+//! and        := not (("and" | "&&") not)*
+//! not        := ("not" | "!") not | comparison
+//! comparison := operand (("==" | "!=" | "<" | "<=" | ">" | ">=") operand)?
+//! operand    := primary ("." identifier)*
+//! primary    := literal | "#" identifier | identifier ["(" arguments ")"] | "(" or ")"
+//! ```
+//!
+//! Operator words (`and`, `or`, `not`, `eq`, `ne`, `lt`, `le`, `gt`, `ge`) are
+//! case-insensitive, and strings may use single or double quotes, with a doubled
+//! quote standing for itself.
+//! 运算符单词（`and`、`or`、`not`、`eq`、`ne`、`lt`、`le`、`gt`、`ge`）不区分大小写，
+//! 字符串可以使用单引号或双引号，连续两个引号表示引号本身。
+//!
+//! # Example / 示例
+//!
+//! ```rust
+//! use nexus_security_expression::{Expression, parse};
+//!
+//! let expr = parse("hasRole('ADMIN') or #userId == principal.id").unwrap();
+//! assert_eq!(expr.variables(), ["userId"]);
+//! assert!(matches!(expr, Expression::Or(..)));
+//! ```
+
+#![warn(missing_docs)]
+#![warn(unreachable_pub)]
+
+use std::fmt;
+
+/// Functions callable from an expression, with their accepted argument counts
+/// 表达式中可调用的函数及其接受的参数个数
+const FUNCTIONS: &[(&str, usize, usize)] = &[
+    ("hasRole", 1, 1),
+    ("hasAnyRole", 1, usize::MAX),
+    ("hasAuthority", 1, 1),
+    ("hasAnyAuthority", 1, usize::MAX),
+    ("hasPermission", 2, 3),
+    ("isAuthenticated", 0, 0),
+    ("isFullyAuthenticated", 0, 0),
+    ("isAnonymous", 0, 0),
+    ("permitAll", 0, 0),
+    ("denyAll", 0, 0),
+];
+
+/// Names usable as bare identifiers
+/// 可作为裸标识符使用的名称
+const IDENTIFIERS: &[&str] = &[
+    "authentication",
+    "principal",
+    "permitAll",
+    "denyAll",
+    "returnObject",
+    "filterObject",
+];
+
+/// Comparison operator
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOperator {
+    /// `==` / `eq`
+    Eq,
+    /// `!=` / `ne`
+    Ne,
+    /// `<` / `lt`
+    Lt,
+    /// `<=` / `le`
+    Le,
+    /// `>` / `gt`
+    Gt,
+    /// `>=` / `ge`
+    Ge,
+}
+
+impl ComparisonOperator {
+    /// Get the operator symbol
+    /// 获取运算符符号
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ComparisonOperator::Eq => "==",
+            ComparisonOperator::Ne => "!=",
+            ComparisonOperator::Lt => "<",
+            ComparisonOperator::Le => "<=",
+            ComparisonOperator::Gt => ">",
+            ComparisonOperator::Ge => ">=",
+        }
+    }
+}
+
+/// Syntax tree of a security expression
+/// 安全表达式的语法树
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    /// `null`
+    Null,
+
+    /// `true` / `false`
+    Bool(bool),
+
+    /// Number literal
+    /// 数字字面量
+    Number(f64),
+
+    /// String literal
+    /// 字符串字面量
+    String(String),
+
+    /// Method argument reference, `#name`
+    /// 方法参数引用，`#name`
+    Variable(String),
+
+    /// Root identifier, e.g. `principal`
+    /// 根标识符，例如 `principal`
+    Identifier(String),
+
+    /// Property access, `target.name`
+    /// 属性访问，`target.name`
+    Property(Box<Expression>, String),
+
+    /// Function call, e.g. `hasRole('ADMIN')`
+    /// 函数调用，例如 `hasRole('ADMIN')`
+    Call(String, Vec<Expression>),
+
+    /// `not` / `!`
+    Not(Box<Expression>),
+
+    /// `and` / `&&`
+    And(Box<Expression>, Box<Expression>),
+
+    /// `or` / `||`
+    Or(Box<Expression>, Box<Expression>),
+
+    /// Comparison
+    /// 比较
+    Compare(ComparisonOperator, Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    /// Names of the `#name` arguments the expression refers to
+    /// 表达式引用的 `#name` 参数名称
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.visit(&mut |expr| {
+            if let Expression::Variable(name) = expr
+                && !names.contains(&name.as_str())
+            {
+                names.push(name);
+            }
+        });
+        names
+    }
+
+    /// Root identifiers the expression refers to, e.g. `principal`
+    /// 表达式引用的根标识符，例如 `principal`
+    pub fn identifiers(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.visit(&mut |expr| {
+            if let Expression::Identifier(name) = expr
+                && !names.contains(&name.as_str())
+            {
+                names.push(name);
+            }
+        });
+        names
+    }
+
+    /// Call `f` on this node and every node below it, left to right
+    /// 对此节点及其下的每个节点从左到右调用 `f`
+    fn visit<'a>(&'a self, f: &mut impl FnMut(&'a Expression)) {
+        f(self);
+        match self {
+            Expression::Property(target, _) | Expression::Not(target) => target.visit(f),
+            Expression::Call(_, args) => args.iter().for_each(|arg| arg.visit(f)),
+            Expression::And(left, right)
+            | Expression::Or(left, right)
+            | Expression::Compare(_, left, right) => {
+                left.visit(f);
+                right.visit(f);
+            },
+            _ => {},
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    /// Format as expression source that parses back to the same tree
+    /// 格式化为可解析回相同语法树的表达式源码
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Null => write!(f, "null"),
+            Expression::Bool(value) => write!(f, "{}", value),
+            Expression::Number(value) => write!(f, "{}", value),
+            Expression::String(value) => write!(f, "'{}'", value.replace('\'', "''")),
+            Expression::Variable(name) => write!(f, "#{}", name),
+            Expression::Identifier(name) => write!(f, "{}", name),
+            Expression::Property(target, name) => write!(f, "{}.{}", target, name),
+            Expression::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            },
+            Expression::Not(operand) => write!(f, "not {}", operand),
+            Expression::And(left, right) => write!(f, "({} and {})", left, right),
+            Expression::Or(left, right) => write!(f, "({} or {})", left, right),
+            Expression::Compare(op, left, right) => {
+                write!(f, "{} {} {}", left, op.symbol(), right)
+            },
+        }
+    }
+}
+
+impl std::str::FromStr for Expression {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+/// Error for an expression that does not parse
+/// 无法解析的表达式的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: String,
+    position: usize,
+}
+
+impl ParseError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+
+    /// What is wrong, without the position
+    /// 错误内容，不含位置
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Byte offset in the input where the problem was found
+    /// 在输入中发现问题的字节偏移
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse an expression
+/// 解析表达式
+///
+/// # Errors / 错误
+///
+/// Returns a [`ParseError`] for malformed input, unknown functions or
+/// identifiers and wrong argument counts.
+/// 对格式错误的输入、未知函数或标识符以及错误的参数个数返回 [`ParseError`]。
+pub fn parse(input: &str) -> Result<Expression, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        end: input.len(),
+        tokens,
+        pos: 0,
+    };
+    let expr = parser.or()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(expr),
+        Some((at, _)) => Err(ParseError::new(*at, "unexpected trailing input")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Variable(String),
+    String(String),
+    Number(f64),
+    Minus,
+    LeftParen,
+    RightParen,
+    Comma,
+    Dot,
+    Not,
+    And,
+    Or,
+    Compare(ComparisonOperator),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((at, c)) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if(|&(_, c)| c == expected).is_some();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            ',' => Token::Comma,
+            '.' => Token::Dot,
+            '-' => Token::Minus,
+            '&' if next_is('&') => Token::And,
+            '|' if next_is('|') => Token::Or,
+            '=' if next_is('=') => Token::Compare(ComparisonOperator::Eq),
+            '!' if next_is('=') => Token::Compare(ComparisonOperator::Ne),
+            '!' => Token::Not,
+            '<' if next_is('=') => Token::Compare(ComparisonOperator::Le),
+            '<' => Token::Compare(ComparisonOperator::Lt),
+            '>' if next_is('=') => Token::Compare(ComparisonOperator::Ge),
+            '>' => Token::Compare(ComparisonOperator::Gt),
+            '\'' | '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, ch)) if ch == c => {
+                            if chars.next_if(|&(_, ch)| ch == c).is_none() {
+                                break;
+                            }
+                            value.push(c);
+                        },
+                        Some((_, ch)) => value.push(ch),
+                        None => return Err(ParseError::new(at, "unterminated string")),
+                    }
+                }
+                Token::String(value)
+            },
+            c if c.is_ascii_digit() => {
+                let mut end = at + c.len_utf8();
+                while let Some((i, ch)) =
+                    chars.next_if(|&(_, ch)| ch.is_ascii_digit() || ch == '.' || ch == '_')
+                {
+                    end = i + ch.len_utf8();
+                }
+                let literal = input.get(at..end).unwrap_or_default().replace('_', "");
+                let invalid = || ParseError::new(at, format!("invalid number `{}`", literal));
+                Token::Number(literal.parse().map_err(|_| invalid())?)
+            },
+            '#' | 'a'..='z' | 'A'..='Z' | '_' | '$' => {
+                let start = if c == '#' { at + 1 } else { at };
+                let mut end = at + c.len_utf8();
+                while let Some((i, ch)) =
+                    chars.next_if(|&(_, ch)| ch.is_alphanumeric() || ch == '_' || ch == '$')
+                {
+                    end = i + ch.len_utf8();
+                }
+                let word = input.get(start..end).unwrap_or_default().to_string();
+                if c == '#' {
+                    if word.is_empty() {
+                        return Err(ParseError::new(at, "expected an argument name after `#`"));
+                    }
+                    Token::Variable(word)
+                } else {
+                    match word.to_ascii_lowercase().as_str() {
+                        "and" => Token::And,
+                        "or" => Token::Or,
+                        "not" => Token::Not,
+                        "eq" => Token::Compare(ComparisonOperator::Eq),
+                        "ne" => Token::Compare(ComparisonOperator::Ne),
+                        "lt" => Token::Compare(ComparisonOperator::Lt),
+                        "le" => Token::Compare(ComparisonOperator::Le),
+                        "gt" => Token::Compare(ComparisonOperator::Gt),
+                        "ge" => Token::Compare(ComparisonOperator::Ge),
+                        _ => Token::Identifier(word),
+                    }
+                }
+            },
+            _ => return Err(ParseError::new(at, format!("unexpected character `{}`", c))),
+        };
+        tokens.push((at, token));
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser over the token list
+/// 基于token列表的递归下降解析器
+struct Parser {
+    /// Length of the input, where "end of input" errors point
+    end: usize,
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn next(&mut self, expected: &str) -> Result<(usize, Token), ParseError> {
+        let token = self.tokens.get(self.pos).cloned().ok_or_else(|| {
+            ParseError::new(self.end, format!("expected {}, found end of input", expected))
+        })?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn or(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expression::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.not()?;
+        while self.eat(&Token::And) {
+            expr = Expression::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expression, ParseError> {
+        if self.eat(&Token::Not) {
+            return Ok(Expression::Not(Box::new(self.not()?)));
+        }
+        let left = self.operand()?;
+        if let Some(Token::Compare(op)) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            let right = self.operand()?;
+            return Ok(Expression::Compare(op, Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn operand(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.primary()?;
+        while self.eat(&Token::Dot) {
+            match self.next("a property name")? {
+                (_, Token::Identifier(name)) => expr = Expression::Property(Box::new(expr), name),
+                (at, _) => return Err(ParseError::new(at, "expected a property name")),
+            }
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expression, ParseError> {
+        let (at, token) = self.next("an operand")?;
+        Ok(match token {
+            Token::String(value) => Expression::String(value),
+            Token::Number(value) => Expression::Number(value),
+            Token::Minus => match self.next("a number")? {
+                (_, Token::Number(value)) => Expression::Number(-value),
+                (at, _) => return Err(ParseError::new(at, "expected a number")),
+            },
+            Token::Variable(name) => Expression::Variable(name),
+            Token::LeftParen => {
+                let expr = self.or()?;
+                match self.next("`)`")? {
+                    (_, Token::RightParen) => expr,
+                    (at, _) => return Err(ParseError::new(at, "expected `)`")),
+                }
+            },
+            Token::Identifier(name) if self.eat(&Token::LeftParen) => {
+                let mut args = Vec::new();
+                if !self.eat(&Token::RightParen) {
+                    loop {
+                        args.push(self.or()?);
+                        match self.next("`,` or `)`")? {
+                            (_, Token::Comma) => {},
+                            (_, Token::RightParen) => break,
+                            (at, _) => return Err(ParseError::new(at, "expected `,` or `)`")),
+                        }
+                    }
+                }
+                match FUNCTIONS.iter().find(|(function, ..)| *function == name) {
+                    Some((_, min, max)) if (*min..=*max).contains(&args.len()) => {},
+                    Some(_) => {
+                        return Err(ParseError::new(
+                            at,
+                            format!("`{}` does not take {} argument(s)", name, args.len()),
+                        ));
+                    },
+                    None => {
+                        return Err(ParseError::new(at, format!("unknown function `{}`", name)));
+                    },
+                }
+                Expression::Call(name, args)
+            },
+            Token::Identifier(name) => match name.as_str() {
+                "true" => Expression::Bool(true),
+                "false" => Expression::Bool(false),
+                "null" => Expression::Null,
+                _ if IDENTIFIERS.contains(&name.as_str()) => Expression::Identifier(name),
+                _ => return Err(ParseError::new(at, format!("unknown identifier `{}`", name))),
+            },
+            _ => return Err(ParseError::new(at, "expected an operand")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(value: &str) -> Expression {
+        Expression::String(value.to_string())
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("#doc.owner eq 'O''Brien' AND NOT hasAnyRole(\"it's\", 'B')").unwrap(),
+            Expression::And(
+                Box::new(Expression::Compare(
+                    ComparisonOperator::Eq,
+                    Box::new(Expression::Property(
+                        Box::new(Expression::Variable("doc".to_string())),
+                        "owner".to_string()
+                    )),
+                    Box::new(string("O'Brien")),
+                )),
+                Box::new(Expression::Not(Box::new(Expression::Call(
+                    "hasAnyRole".to_string(),
+                    vec![string("it's"), string("B")]
+                )))),
+            )
+        );
+        assert_eq!(parse("-1_000.5 <= 2").unwrap(), parse("(-1000.5) le (2)").unwrap());
+
+        let expr = parse("#a == principal.id or #b != #a and returnObject != null").unwrap();
+        assert_eq!(expr.variables(), ["a", "b"]);
+        assert_eq!(expr.identifiers(), ["principal", "returnObject"]);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for input in [
+            "hasRole('ADMIN') or not isAnonymous() && #id == 42",
+            "#doc.owner eq 'O''Brien' AND hasPermission(#doc, 'write')",
+            "returnObject.size >= -1.5 or null != filterObject",
+        ] {
+            let expr: Expression = input.parse().unwrap();
+            assert_eq!(parse(&expr.to_string()).unwrap(), expr, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (input, message, position) in [
+            ("", "expected an operand, found end of input", 0),
+            ("hasRole('ADMIN'", "expected `,` or `)`, found end of input", 15),
+            ("hasRole('ADMIN)", "unterminated string", 8),
+            ("hasRol('ADMIN')", "unknown function `hasRol`", 0),
+            ("hasRole()", "`hasRole` does not take 0 argument(s)", 0),
+            ("user.name", "unknown identifier `user`", 0),
+            ("#id = 1", "unexpected character `=`", 4),
+            ("principal.", "expected a property name, found end of input", 10),
+            ("permitAll denyAll", "unexpected trailing input", 10),
+            ("1.2.3 == 1", "invalid number `1.2.3`", 0),
+        ] {
+            let error = parse(input).unwrap_err();
+            assert_eq!((error.message(), error.position()), (message, position), "{input:?}");
+        }
+        assert_eq!(
+            parse("#").unwrap_err().to_string(),
+            "expected an argument name after `#` at position 0"
+        );
+    }
+}
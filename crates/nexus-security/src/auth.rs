@@ -95,6 +95,19 @@ impl Authentication {
         }
     }
 
+    /// Create authenticated from a verified JWT
+    /// 从已验证的JWT创建已认证
+    pub fn from_jwt(jwt: &crate::JwtAuthentication) -> Self {
+        Self {
+            principal: jwt.username.clone(),
+            credentials: None,
+            authorities: jwt.authorities.clone(),
+            authenticated: true,
+            details: Some(AuthDetails::new().auth_type("Bearer")),
+            login_time: Utc::now(),
+        }
+    }
+
     /// Set authenticated
     /// 设置认证
     pub fn set_authenticated(mut self, authenticated: bool) -> Self {
@@ -1,14 +1,41 @@
 //! Security context module
 //! 安全上下文模块
+//!
+//! A [`SecurityContext`] is a handle owned by one request. The authentication
+//! middleware creates it, stores it in the request extensions and runs the rest
+//! of the chain inside [`SecurityContext::scope`], so [`context()`] and the helper
+//! functions below see the authentication of the request being handled and never
+//! that of a concurrent one.
+//!
+//! [`SecurityContext`] 是单个请求拥有的句柄。认证中间件创建它，将其存入请求扩展，
+//! 并在 [`SecurityContext::scope`] 中运行后续链，因此 [`context()`] 和下面的辅助
+//! 函数看到的是当前请求的认证，而不会是并发请求的认证。
+//!
+//! # Spring Equivalent / Spring等价物
+//!
+//! ```java
+//! SecurityContextHolder.setStrategyName(SecurityContextHolder.MODE_INHERITABLETHREADLOCAL);
+//! SecurityContext context = SecurityContextHolder.getContext();
+//! ```
 
 use crate::Authentication;
+use nexus_runtime::JoinHandle;
+use nexus_runtime::task::TaskLocalFuture;
+use std::future::Future;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+nexus_runtime::task_local! {
+    /// Context of the task being polled / 正在轮询的任务的上下文
+    static CURRENT: SecurityContext;
+}
 
 /// Security context
 /// 安全上下文
 ///
-/// Holds the current authentication and security information.
-/// 保存当前认证和安全信息。
+/// Holds the authentication of one request. Clones share the same state, so a
+/// clone taken from the request extensions sees what the middleware set.
+/// 保存单个请求的认证。克隆共享同一状态，因此从请求扩展中取得的克隆能看到中间件设置的内容。
 ///
 /// Equivalent to Spring's SecurityContext.
 /// 等价于Spring的SecurityContext。
@@ -19,6 +46,7 @@ use std::sync::Arc;
 /// SecurityContext context = SecurityContextHolder.getContext();
 /// Authentication auth = context.getAuthentication();
 /// ```
+#[derive(Clone, Default)]
 pub struct SecurityContext {
     /// Current authentication
     /// 当前认证
@@ -34,6 +62,36 @@ impl SecurityContext {
         }
     }
 
+    /// Create a security context holding an authentication
+    /// 创建持有认证的安全上下文
+    pub fn with_authentication(auth: Authentication) -> Self {
+        Self {
+            authentication: Arc::new(tokio::sync::RwLock::new(Some(auth))),
+        }
+    }
+
+    /// Get the context of the current task, if one is in scope
+    /// 获取当前任务的上下文（如果在作用域内）
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Self::clone).ok()
+    }
+
+    /// Run `future` with this context as the current one
+    /// 以此上下文作为当前上下文运行 `future`
+    ///
+    /// # Example / 示例
+    ///
+    /// ```rust,no_run,ignore
+    /// let ctx = SecurityContext::with_authentication(auth);
+    /// ctx.scope(async {
+    ///     assert!(nexus_security::context().unwrap().is_authenticated().await);
+    /// })
+    /// .await;
+    /// ```
+    pub fn scope<F: Future>(self, future: F) -> TaskLocalFuture<Self, F> {
+        CURRENT.scope(self, future)
+    }
+
     /// Get current authentication
     /// 获取当前认证
     pub async fn get_authentication(&self) -> Option<Authentication> {
@@ -77,78 +135,184 @@ impl SecurityContext {
 
     /// Check if user has authority
     /// 检查用户是否有权限
+    ///
+    /// An authentication that has not been verified yet grants nothing.
+    /// 尚未验证的认证不授予任何权限。
     pub async fn has_authority(&self, authority: &crate::Authority) -> bool {
         self.authentication
             .read()
             .await
             .as_ref()
-            .map(|a| a.has_authority(authority))
+            .map(|a| a.authenticated && a.has_authority(authority))
             .unwrap_or(false)
     }
 
     /// Check if user has role
     /// 检查用户是否有角色
+    ///
+    /// Like [`has_authority`](Self::has_authority), only an authenticated
+    /// principal has roles.
+    /// 与 [`has_authority`](Self::has_authority) 一样，只有已认证的主体才有角色。
     pub async fn has_role(&self, role: &crate::Role) -> bool {
         self.authentication
             .read()
             .await
             .as_ref()
-            .map(|a| a.has_role(role))
+            .map(|a| a.authenticated && a.has_role(role))
             .unwrap_or(false)
     }
 }
 
-/// Global security context
-/// 全局安全上下文
-static GLOBAL_CONTEXT: once_cell::sync::Lazy<SecurityContext> =
-    once_cell::sync::Lazy::new(SecurityContext::new);
+/// How spawned tasks obtain their security context
+/// 生成的任务如何获得安全上下文
+///
+/// Applies to futures passed through [`propagate_context`] or
+/// [`spawn_with_context`].
+/// 适用于通过 [`propagate_context`] 或 [`spawn_with_context`] 传递的future。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SecurityContextStrategy {
+    /// Spawned tasks start with an empty context (`MODE_THREADLOCAL`)
+    /// 生成的任务以空上下文启动（`MODE_THREADLOCAL`）
+    #[default]
+    TaskLocal,
+    /// Spawned tasks share the context of their parent (`MODE_INHERITABLETHREADLOCAL`)
+    /// 生成的任务共享父任务的上下文（`MODE_INHERITABLETHREADLOCAL`）
+    InheritableTaskLocal,
+}
+
+/// Current strategy, as the discriminant of [`SecurityContextStrategy`]
+/// 当前策略，存储为 [`SecurityContextStrategy`] 的判别值
+static STRATEGY: AtomicU8 = AtomicU8::new(SecurityContextStrategy::TaskLocal as u8);
+
+/// Set the strategy for spawned tasks
+/// 设置生成任务的策略
+pub fn set_context_strategy(strategy: SecurityContextStrategy) {
+    STRATEGY.store(strategy as u8, Ordering::Relaxed);
+}
+
+/// Get the strategy for spawned tasks
+/// 获取生成任务的策略
+pub fn context_strategy() -> SecurityContextStrategy {
+    if STRATEGY.load(Ordering::Relaxed) == SecurityContextStrategy::InheritableTaskLocal as u8 {
+        SecurityContextStrategy::InheritableTaskLocal
+    } else {
+        SecurityContextStrategy::TaskLocal
+    }
+}
+
+/// Prepare `future` to run as a child task of the current one
+/// 准备 `future` 作为当前任务的子任务运行
+///
+/// Under [`SecurityContextStrategy::InheritableTaskLocal`] the future shares the
+/// current context; otherwise it gets an empty one.
+/// 在 [`SecurityContextStrategy::InheritableTaskLocal`] 下，future共享当前上下文；
+/// 否则获得一个空上下文。
+pub fn propagate_context<F: Future>(future: F) -> TaskLocalFuture<SecurityContext, F> {
+    propagate_context_with(context_strategy(), future)
+}
 
-/// Get global security context
-/// 获取全局安全上下文
-pub fn context() -> &'static SecurityContext {
-    &GLOBAL_CONTEXT
+/// Prepare `future` to run as a child task of the current one under `strategy`
+/// 按 `strategy` 准备 `future` 作为当前任务的子任务运行
+///
+/// Like [`propagate_context`], but ignores the global strategy.
+/// 与 [`propagate_context`] 相同，但忽略全局策略。
+pub fn propagate_context_with<F: Future>(
+    strategy: SecurityContextStrategy,
+    future: F,
+) -> TaskLocalFuture<SecurityContext, F> {
+    match strategy {
+        SecurityContextStrategy::InheritableTaskLocal => CURRENT.inherit(future),
+        SecurityContextStrategy::TaskLocal => CURRENT.scope(SecurityContext::new(), future),
+    }
 }
 
-/// Get current authentication from global context
-/// 从全局上下文获取当前认证
+/// Spawn a task whose security context follows the current strategy
+/// 生成一个安全上下文遵循当前策略的任务
+pub fn spawn_with_context<F, T>(future: F) -> JoinHandle<T>
+where
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    nexus_runtime::spawn(propagate_context(future))
+}
+
+/// Get the security context of the current task
+/// 获取当前任务的安全上下文
+///
+/// Returns `None` outside of a [`SecurityContext::scope`], e.g. in a task spawned
+/// without [`spawn_with_context`]. The helper functions below then read an
+/// unauthenticated context, and [`set_authentication`] and [`clear_context`] log a
+/// warning instead of changing anything.
+/// 在 [`SecurityContext::scope`] 之外返回 `None`，例如在未通过 [`spawn_with_context`]
+/// 生成的任务中。此时下面的辅助函数读取的是未认证的上下文，[`set_authentication`] 和
+/// [`clear_context`] 会记录警告而不做任何修改。
+pub fn context() -> Option<SecurityContext> {
+    SecurityContext::current()
+}
+
+/// Get current authentication from current context
+/// 从当前上下文获取当前认证
 pub async fn get_authentication() -> Option<Authentication> {
-    context().get_authentication().await
+    context()?.get_authentication().await
 }
 
-/// Set authentication in global context
-/// 在全局上下文中设置认证
+/// Set authentication in current context
+/// 在当前上下文中设置认证
+///
+/// Outside of a [`SecurityContext::scope`] there is nothing to set; a warning is logged.
+/// 在 [`SecurityContext::scope`] 之外没有可设置的上下文；会记录警告。
 pub async fn set_authentication(auth: Authentication) {
-    context().set_authentication(auth).await;
+    if let Some(context) = context() {
+        context.set_authentication(auth).await;
+    } else {
+        tracing::warn!("No security context in scope; authentication not set");
+    }
 }
 
-/// Clear global context
-/// 清除全局上下文
+/// Clear the current context
+/// 清除当前上下文
+///
+/// Outside of a [`SecurityContext::scope`] there is nothing to clear; a warning is logged.
+/// 在 [`SecurityContext::scope`] 之外没有可清除的上下文；会记录警告。
 pub async fn clear_context() {
-    context().clear().await;
+    if let Some(context) = context() {
+        context.clear().await;
+    } else {
+        tracing::warn!("No security context in scope; nothing to clear");
+    }
 }
 
 /// Check if current user is authenticated
 /// 检查当前用户是否已认证
 pub async fn is_authenticated() -> bool {
-    context().is_authenticated().await
+    match context() {
+        Some(context) => context.is_authenticated().await,
+        None => false,
+    }
 }
 
 /// Get current username
 /// 获取当前用户名
 pub async fn get_username() -> Option<String> {
-    context().get_username().await
+    context()?.get_username().await
 }
 
 /// Check if current user has authority
 /// 检查当前用户是否有权限
 pub async fn has_authority(authority: &crate::Authority) -> bool {
-    context().has_authority(authority).await
+    match context() {
+        Some(context) => context.has_authority(authority).await,
+        None => false,
+    }
 }
 
 /// Check if current user has role
 /// 检查当前用户是否有角色
 pub async fn has_role(role: &crate::Role) -> bool {
-    context().has_role(role).await
+    match context() {
+        Some(context) => context.has_role(role).await,
+        None => false,
+    }
 }
 
 #[cfg(test)]
@@ -176,4 +340,102 @@ mod tests {
         assert!(context.is_authenticated().await);
         assert_eq!(context.get_username().await, Some("john".to_string()));
     }
+
+    fn user(name: &str, role: crate::Role) -> Authentication {
+        Authentication::new(name, "")
+            .set_authenticated(true)
+            .set_authorities(vec![crate::Authority::Role(role)])
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_has_no_authorities() {
+        let authority = crate::Authority::permission("reports:read");
+        let pending = Authentication::new("john", "secret").set_authorities(vec![
+            crate::Authority::Role(crate::Role::Admin),
+            authority.clone(),
+        ]);
+        let context = SecurityContext::with_authentication(pending);
+        assert!(!context.has_role(&crate::Role::Admin).await);
+        assert!(!context.has_authority(&authority).await);
+
+        context
+            .set_authentication(user("john", crate::Role::Admin))
+            .await;
+        assert!(context.has_role(&crate::Role::Admin).await);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_requests_do_not_leak() {
+        let tasks: Vec<_> = (0..32)
+            .map(|i| {
+                let (name, role) = if i % 2 == 0 {
+                    (format!("admin{i}"), crate::Role::Admin)
+                } else {
+                    (format!("user{i}"), crate::Role::User)
+                };
+                tokio::spawn(SecurityContext::new().scope(async move {
+                    set_authentication(user(&name, role.clone())).await;
+                    for _ in 0..10 {
+                        tokio::task::yield_now().await;
+                        assert_eq!(get_username().await.as_deref(), Some(name.as_str()));
+                        assert!(has_role(&role).await);
+                    }
+                    has_role(&crate::Role::Admin).await
+                }))
+            })
+            .collect();
+
+        for (i, task) in tasks.into_iter().enumerate() {
+            assert_eq!(task.await.unwrap(), i % 2 == 0);
+        }
+        assert!(!is_authenticated().await);
+    }
+
+    #[tokio::test]
+    async fn test_context_outside_scope_is_detached() {
+        assert!(context().is_none());
+        set_authentication(user("ghost", crate::Role::Admin)).await;
+        assert!(get_authentication().await.is_none());
+        assert!(!has_role(&crate::Role::Admin).await);
+    }
+
+    #[tokio::test]
+    async fn test_strategy_for_spawned_tasks() {
+        let ctx = SecurityContext::with_authentication(user("john", crate::Role::User));
+        ctx.clone()
+            .scope(async {
+                let child =
+                    propagate_context_with(SecurityContextStrategy::TaskLocal, get_username());
+                assert_eq!(tokio::spawn(child).await.unwrap(), None);
+
+                let child =
+                    propagate_context_with(SecurityContextStrategy::InheritableTaskLocal, async {
+                        let name = get_username().await;
+                        clear_context().await;
+                        name
+                    });
+                assert_eq!(tokio::spawn(child).await.unwrap().as_deref(), Some("john"));
+            })
+            .await;
+        // The child shared the parent's context, so its clear is visible here
+        assert!(ctx.get_authentication().await.is_none());
+    }
+
+    /// Puts the global strategy back when dropped, even if the test panics
+    struct RestoreStrategy(SecurityContextStrategy);
+
+    impl Drop for RestoreStrategy {
+        fn drop(&mut self) {
+            set_context_strategy(self.0);
+        }
+    }
+
+    #[test]
+    fn test_set_context_strategy() {
+        let _restore = RestoreStrategy(context_strategy());
+        set_context_strategy(SecurityContextStrategy::InheritableTaskLocal);
+        assert_eq!(context_strategy(), SecurityContextStrategy::InheritableTaskLocal);
+        set_context_strategy(SecurityContextStrategy::TaskLocal);
+        assert_eq!(context_strategy(), SecurityContextStrategy::TaskLocal);
+    }
 }
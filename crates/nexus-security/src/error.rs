@@ -93,6 +93,16 @@ pub enum SecurityError {
     #[error("JWT error: {0}")]
     Jwt(String),
 
+    /// OAuth2 / OIDC error, e.g. an authorization server failure
+    /// OAuth2 / OIDC错误，例如授权服务器故障
+    #[error("OAuth2 error: {0}")]
+    OAuth2(String),
+
+    /// Malformed security expression, or one that cannot be evaluated
+    /// 格式错误或无法评估的安全表达式
+    #[error("Invalid security expression: {0}")]
+    InvalidExpression(String),
+
     /// IO error
     /// IO错误
     #[error("IO error: {0}")]
@@ -108,6 +118,31 @@ pub enum SecurityError {
 /// 安全结果类型
 pub type SecurityResult<T> = Result<T, SecurityError>;
 
+/// Map security failures to HTTP errors: denials are 403, authentication failures 401
+/// 将安全失败映射为HTTP错误：拒绝访问为403，认证失败为401
+impl From<SecurityError> for nexus_http::Error {
+    fn from(e: SecurityError) -> Self {
+        match e {
+            SecurityError::AccessDenied(_)
+            | SecurityError::InsufficientPermissions { .. }
+            | SecurityError::CsrfValidationFailed(_) => nexus_http::Error::Forbidden,
+            SecurityError::AuthenticationFailed(_)
+            | SecurityError::InvalidCredentials(_)
+            | SecurityError::UserNotFound(_)
+            | SecurityError::Disabled(_)
+            | SecurityError::AccountExpired(_)
+            | SecurityError::Locked(_)
+            | SecurityError::CredentialsExpired(_)
+            | SecurityError::InvalidToken(_)
+            | SecurityError::ExpiredToken(_)
+            | SecurityError::TokenError(_)
+            | SecurityError::TokenExpired(_)
+            | SecurityError::Jwt(_) => nexus_http::Error::Unauthorized,
+            _ => nexus_http::Error::Internal(e.to_string()),
+        }
+    }
+}
+
 /// Access denied exception
 /// 访问被拒绝异常
 ///
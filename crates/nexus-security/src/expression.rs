@@ -0,0 +1,734 @@
+//! Security expression language
+//! 安全表达式语言
+//!
+//! The SpEL subset used by `@PreAuthorize` rules: boolean operators, parentheses,
+//! comparisons, the Spring Security functions, `principal` / `authentication`
+//! property access and `#name` method-argument references.
+//!
+//! `@PreAuthorize` 规则所用的SpEL子集：布尔运算符、括号、比较、Spring Security函数、
+//! `principal` / `authentication` 属性访问以及 `#name` 方法参数引用。
+//!
+//! The grammar and the [`Expression`] tree are defined by `nexus-security-expression`,
+//! which `nexus-macros` shares to check expressions at compile time; this module
+//! evaluates the tree through [`ExpressionExt`].
+//! 语法和 [`Expression`] 语法树由 `nexus-security-expression` 定义，`nexus-macros`
+//! 共用它在编译时检查表达式；本模块通过 [`ExpressionExt`] 评估该语法树。
+//!
+//! # Spring Equivalent / Spring等价物
+//!
+//! ```java
+//! @PreAuthorize("hasAnyRole('ADMIN', 'AUDITOR') or #userId == principal.id")
+//! @PreAuthorize("isAuthenticated() and hasPermission(#doc, 'write')")
+//! ```
+
+use crate::{Authentication, Authority, SecurityContext, SecurityError, SecurityResult};
+use async_trait::async_trait;
+use futures::future::{BoxFuture, FutureExt};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+pub use nexus_security_expression::{ComparisonOperator, Expression};
+
+/// Parsing and evaluation of a security [`Expression`]
+/// 安全 [`Expression`] 的解析与评估
+///
+/// The syntax tree comes from `nexus-security-expression`; this trait evaluates it
+/// against an [`EvaluationContext`], as Spring Security evaluates SpEL's `Expression`.
+/// 语法树来自 `nexus-security-expression`；本trait针对 [`EvaluationContext`] 评估它，
+/// 如同Spring Security评估SpEL的 `Expression`。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// use nexus_security::{Expression, ExpressionExt};
+///
+/// let expr = Expression::parse("hasRole('ADMIN') or #userId == principal.id")?;
+/// let context = EvaluationContext::from_context(&nexus_security::context().unwrap_or_default())
+///     .await
+///     .variable("userId", user_id);
+/// if expr.evaluate(&context).await? { /* ... */ }
+/// ```
+pub trait ExpressionExt: Sized {
+    /// Parse an expression
+    /// 解析表达式
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns [`SecurityError::InvalidExpression`] for malformed input, unknown
+    /// functions or identifiers and wrong argument counts.
+    /// 对格式错误的输入、未知函数或标识符以及错误的参数个数返回
+    /// [`SecurityError::InvalidExpression`]。
+    fn parse(input: &str) -> SecurityResult<Self>;
+
+    /// Evaluate the expression to a boolean
+    /// 将表达式评估为布尔值
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns [`SecurityError::InvalidExpression`] if an operand has the wrong
+    /// type, e.g. `not 'x'`, or the result is not a boolean.
+    /// 操作数类型错误（例如 `not 'x'`）或结果不是布尔值时返回
+    /// [`SecurityError::InvalidExpression`]。
+    fn evaluate(
+        &self,
+        context: &EvaluationContext,
+    ) -> impl Future<Output = SecurityResult<bool>> + Send;
+
+    /// Evaluate the expression and fail with `AccessDenied` if it is false
+    /// 评估表达式，为false时以 `AccessDenied` 失败
+    fn authorize(
+        &self,
+        context: &EvaluationContext,
+    ) -> impl Future<Output = SecurityResult<()>> + Send;
+
+    /// Keep the elements for which the expression holds, each bound as `filterObject`
+    /// 保留表达式成立的元素，每个元素绑定为 `filterObject`
+    ///
+    /// Equivalent to Spring's `@PreFilter` / `@PostFilter`.
+    /// 等价于Spring的 `@PreFilter` / `@PostFilter`。
+    fn filter<C, T>(
+        &self,
+        items: C,
+        context: &EvaluationContext,
+    ) -> impl Future<Output = SecurityResult<C>> + Send
+    where
+        C: IntoIterator<Item = T> + FromIterator<T> + Send,
+        C::IntoIter: Send,
+        T: Serialize + Send;
+}
+
+impl ExpressionExt for Expression {
+    fn parse(input: &str) -> SecurityResult<Self> {
+        nexus_security_expression::parse(input)
+            .map_err(|e| invalid(format!("{} in `{}`", e, input)))
+    }
+
+    async fn evaluate(&self, context: &EvaluationContext) -> SecurityResult<bool> {
+        value(self, context)
+            .await
+            .and_then(|value| boolean(self, &value))
+    }
+
+    async fn authorize(&self, context: &EvaluationContext) -> SecurityResult<()> {
+        if self.evaluate(context).await? {
+            Ok(())
+        } else {
+            Err(SecurityError::AccessDenied(format!("`{}` evaluated to false", self)))
+        }
+    }
+
+    async fn filter<C, T>(&self, items: C, context: &EvaluationContext) -> SecurityResult<C>
+    where
+        C: IntoIterator<Item = T> + FromIterator<T> + Send,
+        C::IntoIter: Send,
+        T: Serialize + Send,
+    {
+        let mut context = context.clone();
+        let mut kept = Vec::new();
+        for item in items {
+            context.filter_object = Some(serde_json::to_value(&item).unwrap_or(Value::Null));
+            if self.evaluate(&context).await? {
+                kept.push(item);
+            }
+        }
+        Ok(kept.into_iter().collect())
+    }
+}
+
+/// Value of an expression node
+/// 表达式节点的值
+fn value<'a>(
+    expr: &'a Expression,
+    context: &'a EvaluationContext,
+) -> BoxFuture<'a, SecurityResult<Value>> {
+    async move {
+        Ok(match expr {
+            Expression::Null => Value::Null,
+            Expression::Bool(value) => Value::Bool(*value),
+            Expression::Number(value) => Value::from(*value),
+            Expression::String(value) => Value::String(value.clone()),
+            Expression::Variable(name) => {
+                context.variables.get(name).cloned().unwrap_or(Value::Null)
+            },
+            Expression::Identifier(name) => match name.as_str() {
+                "authentication" => context.authentication_value(),
+                "principal" => context.principal_value(),
+                "permitAll" => Value::Bool(true),
+                "denyAll" => Value::Bool(false),
+                "returnObject" => context.return_object.clone().unwrap_or(Value::Null),
+                "filterObject" => context.filter_object.clone().unwrap_or(Value::Null),
+                _ => return Err(invalid(format!("Unknown identifier `{}`", name))),
+            },
+            Expression::Property(target, name) => match value(target, context).await? {
+                Value::Object(mut object) => object.remove(name).unwrap_or(Value::Null),
+                Value::Null => Value::Null,
+                other => {
+                    return Err(invalid(format!("`{}` has no property `{}`", other, name)));
+                },
+            },
+            Expression::Call(name, args) => {
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(value(arg, context).await?);
+                }
+                Value::Bool(context.call(name, &values).await?)
+            },
+            Expression::Not(operand) => {
+                Value::Bool(!boolean(operand, &value(operand, context).await?)?)
+            },
+            Expression::And(left, right) => Value::Bool(
+                boolean(left, &value(left, context).await?)?
+                    && boolean(right, &value(right, context).await?)?,
+            ),
+            Expression::Or(left, right) => Value::Bool(
+                boolean(left, &value(left, context).await?)?
+                    || boolean(right, &value(right, context).await?)?,
+            ),
+            Expression::Compare(op, left, right) => {
+                let left = value(left, context).await?;
+                let right = value(right, context).await?;
+                Value::Bool(compare(*op, &left, &right)?)
+            },
+        })
+    }
+    .boxed()
+}
+
+/// Decides `hasPermission(...)` checks
+/// 决定 `hasPermission(...)` 检查
+///
+/// Targets are method arguments or literals converted to JSON.
+/// 目标是转换为JSON的方法参数或字面量。
+///
+/// Equivalent to Spring's `PermissionEvaluator`.
+/// 等价于Spring的 `PermissionEvaluator`。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// struct DocumentPermissions;
+///
+/// #[async_trait]
+/// impl PermissionEvaluator for DocumentPermissions {
+///     async fn has_permission(&self, auth: &Authentication, target: &Value, permission: &str) -> bool {
+///         permission == "write" && target["owner"] == auth.name()
+///     }
+/// }
+///
+/// nexus_security::set_permission_evaluator(DocumentPermissions);
+/// ```
+#[async_trait]
+pub trait PermissionEvaluator: Send + Sync {
+    /// `hasPermission(target, permission)`
+    async fn has_permission(
+        &self,
+        authentication: &Authentication,
+        target: &Value,
+        permission: &str,
+    ) -> bool;
+
+    /// `hasPermission(targetId, targetType, permission)`; denies by default
+    /// `hasPermission(targetId, targetType, permission)`；默认拒绝
+    async fn has_permission_by_id(
+        &self,
+        authentication: &Authentication,
+        target_id: &Value,
+        target_type: &str,
+        permission: &str,
+    ) -> bool {
+        let _ = (authentication, target_id, target_type, permission);
+        false
+    }
+}
+
+/// Default permission evaluator
+/// 默认许可评估器
+///
+/// Grants `hasPermission('document', 'write')` when the authentication holds the
+/// `document:write` authority; object targets and lookups by id are denied.
+/// 当认证持有 `document:write` 权限时授予 `hasPermission('document', 'write')`；
+/// 对象目标和按ID查找均被拒绝。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuthorityPermissionEvaluator;
+
+#[async_trait]
+impl PermissionEvaluator for AuthorityPermissionEvaluator {
+    async fn has_permission(
+        &self,
+        authentication: &Authentication,
+        target: &Value,
+        permission: &str,
+    ) -> bool {
+        let Some(target) = target.as_str() else {
+            return false;
+        };
+        let required = format!("{}:{}", target, permission);
+        authentication
+            .authorities
+            .iter()
+            .any(|authority| authority.authority() == required)
+    }
+}
+
+/// Permission evaluator used by new evaluation contexts
+/// 新评估上下文使用的许可评估器
+static PERMISSION_EVALUATOR: LazyLock<RwLock<Arc<dyn PermissionEvaluator>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(AuthorityPermissionEvaluator)));
+
+/// Set the permission evaluator for `hasPermission(...)`
+/// 设置 `hasPermission(...)` 的许可评估器
+pub fn set_permission_evaluator(evaluator: impl PermissionEvaluator + 'static) {
+    *PERMISSION_EVALUATOR
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Arc::new(evaluator);
+}
+
+/// Get the permission evaluator for `hasPermission(...)`
+/// 获取 `hasPermission(...)` 的许可评估器
+pub fn permission_evaluator() -> Arc<dyn PermissionEvaluator> {
+    PERMISSION_EVALUATOR
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+}
+
+/// What an expression is evaluated against
+/// 表达式的评估对象
+///
+/// Holds the authentication, the `#name` arguments, `returnObject` and the
+/// permission evaluator.
+/// `principal` defaults to `{ "username": name, "name": name }`; set it to the
+/// application's user object to reach fields such as `principal.id`.
+/// 包含认证、`#name` 参数、`returnObject` 和许可评估器。`principal` 默认为
+/// `{ "username": name, "name": name }`；将其设置为应用的用户对象即可访问
+/// `principal.id` 等字段。
+///
+/// Equivalent to Spring's `MethodSecurityEvaluationContext`.
+/// 等价于Spring的 `MethodSecurityEvaluationContext`。
+#[derive(Clone)]
+pub struct EvaluationContext {
+    authentication: Option<Authentication>,
+    principal: Option<Value>,
+    variables: HashMap<String, Value>,
+    return_object: Option<Value>,
+    filter_object: Option<Value>,
+    permission_evaluator: Arc<dyn PermissionEvaluator>,
+}
+
+impl EvaluationContext {
+    /// Create a context for an authentication, or for an anonymous caller
+    /// 为认证或匿名调用者创建上下文
+    pub fn new(authentication: Option<Authentication>) -> Self {
+        Self {
+            authentication,
+            principal: None,
+            variables: HashMap::new(),
+            return_object: None,
+            filter_object: None,
+            permission_evaluator: permission_evaluator(),
+        }
+    }
+
+    /// Create a context for the authentication of a security context
+    /// 为安全上下文的认证创建上下文
+    pub async fn from_context(context: &SecurityContext) -> Self {
+        Self::new(context.get_authentication().await)
+    }
+
+    /// Bind a method argument, referenced as `#name`
+    /// 绑定方法参数，以 `#name` 引用
+    pub fn variable(mut self, name: impl Into<String>, value: impl Serialize) -> Self {
+        let name = name.into();
+        let value = serde_json::to_value(value).unwrap_or_else(|e| {
+            tracing::warn!("Cannot bind security expression variable #{}: {}", name, e);
+            Value::Null
+        });
+        self.variables.insert(name, value);
+        self
+    }
+
+    /// Set the object `principal` refers to
+    /// 设置 `principal` 所指的对象
+    pub fn principal(mut self, principal: impl Serialize) -> Self {
+        self.principal = Some(serde_json::to_value(principal).unwrap_or(Value::Null));
+        self
+    }
+
+    /// Set the value of a method's result, `returnObject`
+    /// 设置方法结果的值，`returnObject`
+    pub fn return_object(mut self, value: impl Serialize) -> Self {
+        self.return_object = Some(serde_json::to_value(value).unwrap_or(Value::Null));
+        self
+    }
+
+    /// Use a permission evaluator other than the global one
+    /// 使用全局评估器之外的许可评估器
+    pub fn permission_evaluator(mut self, evaluator: impl PermissionEvaluator + 'static) -> Self {
+        self.permission_evaluator = Arc::new(evaluator);
+        self
+    }
+
+    /// Get the authentication
+    /// 获取认证
+    pub fn authentication(&self) -> Option<&Authentication> {
+        self.authentication.as_ref()
+    }
+
+    /// The authentication, if the caller is authenticated
+    /// 调用者已认证时的认证
+    fn authenticated(&self) -> Option<&Authentication> {
+        self.authentication
+            .as_ref()
+            .filter(|auth| auth.authenticated)
+    }
+
+    fn principal_value(&self) -> Value {
+        if let Some(principal) = &self.principal {
+            return principal.clone();
+        }
+        let Some(auth) = &self.authentication else {
+            return Value::Null;
+        };
+        let mut principal = Map::new();
+        principal.insert("username".to_string(), Value::from(auth.name()));
+        principal.insert("name".to_string(), Value::from(auth.name()));
+        Value::Object(principal)
+    }
+
+    fn authentication_value(&self) -> Value {
+        let Some(auth) = &self.authentication else {
+            return Value::Null;
+        };
+        let mut value = Map::new();
+        value.insert("name".to_string(), Value::from(auth.name()));
+        value.insert("principal".to_string(), self.principal_value());
+        value.insert("authenticated".to_string(), Value::Bool(auth.authenticated));
+        value.insert(
+            "authorities".to_string(),
+            auth.authorities.iter().map(Authority::authority).collect(),
+        );
+        value.insert(
+            "details".to_string(),
+            serde_json::to_value(&auth.details).unwrap_or(Value::Null),
+        );
+        Value::Object(value)
+    }
+
+    fn has_authority(&self, authority: &str) -> bool {
+        self.authenticated().is_some_and(|auth| {
+            auth.authorities
+                .iter()
+                .any(|granted| granted.authority() == authority)
+        })
+    }
+
+    fn has_role(&self, role: &str) -> bool {
+        let role = role
+            .strip_prefix(crate::DEFAULT_ROLE_PREFIX)
+            .unwrap_or(role);
+        self.has_authority(&crate::Role::from_str(role).with_prefix())
+    }
+
+    async fn call(&self, name: &str, args: &[Value]) -> SecurityResult<bool> {
+        Ok(match name {
+            "hasRole" => self.has_role(string_arg(name, args, 0)?),
+            "hasAnyRole" => {
+                let mut granted = false;
+                for i in 0..args.len() {
+                    granted |= self.has_role(string_arg(name, args, i)?);
+                }
+                granted
+            },
+            "hasAuthority" => self.has_authority(string_arg(name, args, 0)?),
+            "hasAnyAuthority" => {
+                let mut granted = false;
+                for i in 0..args.len() {
+                    granted |= self.has_authority(string_arg(name, args, i)?);
+                }
+                granted
+            },
+            "hasPermission" => {
+                let Some(auth) = self.authenticated() else {
+                    return Ok(false);
+                };
+                match args {
+                    [target, _] => {
+                        let permission = string_arg(name, args, 1)?;
+                        self.permission_evaluator
+                            .has_permission(auth, target, permission)
+                            .await
+                    },
+                    [target_id, _, _] => {
+                        let target_type = string_arg(name, args, 1)?;
+                        let permission = string_arg(name, args, 2)?;
+                        self.permission_evaluator
+                            .has_permission_by_id(auth, target_id, target_type, permission)
+                            .await
+                    },
+                    _ => return Err(arity_error(name, args.len())),
+                }
+            },
+            // Remember-me logins are not told apart, so fully authenticated == authenticated
+            // 未区分记住我登录，因此完全认证 == 已认证
+            "isAuthenticated" | "isFullyAuthenticated" => self.authenticated().is_some(),
+            "isAnonymous" => self.authenticated().is_none(),
+            "permitAll" => true,
+            "denyAll" => false,
+            _ => return Err(invalid(format!("Unknown function `{}`", name))),
+        })
+    }
+}
+
+fn invalid(message: impl Into<String>) -> SecurityError {
+    SecurityError::InvalidExpression(message.into())
+}
+
+fn arity_error(name: &str, count: usize) -> SecurityError {
+    invalid(format!("`{}` does not take {} argument(s)", name, count))
+}
+
+fn string_arg<'a>(function: &str, args: &'a [Value], index: usize) -> SecurityResult<&'a str> {
+    match args.get(index) {
+        Some(Value::String(value)) => Ok(value),
+        Some(other) => Err(invalid(format!(
+            "Argument {} of `{}` must be a string, got {}",
+            index + 1,
+            function,
+            other
+        ))),
+        None => Err(arity_error(function, args.len())),
+    }
+}
+
+fn boolean(expr: &Expression, value: &Value) -> SecurityResult<bool> {
+    value
+        .as_bool()
+        .ok_or_else(|| invalid(format!("`{}` is {} rather than a boolean", expr, value)))
+}
+
+/// Numeric view of a value; numeric strings count, so path parameters compare with ids
+/// 值的数字视图；数字字符串也算在内，以便路径参数可以与ID比较
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn compare(op: ComparisonOperator, left: &Value, right: &Value) -> SecurityResult<bool> {
+    let numeric = match (left, right) {
+        (Value::Number(_), Value::Number(_) | Value::String(_))
+        | (Value::String(_), Value::Number(_)) => number(left).zip(number(right)),
+        _ => None,
+    };
+    let ordering = match (numeric, left, right) {
+        (Some((l, r)), _, _) => l.partial_cmp(&r),
+        (None, Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    };
+    Ok(match op {
+        ComparisonOperator::Eq => ordering.map_or(left == right, Ordering::is_eq),
+        ComparisonOperator::Ne => ordering.map_or(left != right, Ordering::is_ne),
+        _ => {
+            let Some(ordering) = ordering else {
+                return Err(invalid(format!("Cannot compare {} {} {}", left, op.symbol(), right)));
+            };
+            match op {
+                ComparisonOperator::Lt => ordering.is_lt(),
+                ComparisonOperator::Le => ordering.is_le(),
+                ComparisonOperator::Gt => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Role;
+    use serde_json::json;
+
+    fn alice() -> Authentication {
+        Authentication::new("alice", "secret")
+            .set_authenticated(true)
+            .set_authorities(vec![
+                Authority::Role(Role::User),
+                Authority::Permission("USER:READ".to_string()),
+                Authority::Permission("report:export".to_string()),
+            ])
+    }
+
+    async fn eval(expr: &str, context: &EvaluationContext) -> bool {
+        Expression::parse(expr)
+            .unwrap()
+            .evaluate(context)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let expr = Expression::parse("hasRole('ADMIN') or not isAnonymous() && #id == 42").unwrap();
+        assert_eq!(
+            expr,
+            Expression::Or(
+                Box::new(Expression::Call(
+                    "hasRole".to_string(),
+                    vec![Expression::String("ADMIN".to_string())]
+                )),
+                Box::new(Expression::And(
+                    Box::new(Expression::Not(Box::new(Expression::Call(
+                        "isAnonymous".to_string(),
+                        vec![]
+                    )))),
+                    Box::new(Expression::Compare(
+                        ComparisonOperator::Eq,
+                        Box::new(Expression::Variable("id".to_string())),
+                        Box::new(Expression::Number(42.0))
+                    )),
+                )),
+            )
+        );
+        assert_eq!(expr.to_string(), "(hasRole('ADMIN') or (not isAnonymous() and #id == 42))");
+        assert_eq!(Expression::parse(&expr.to_string()).unwrap(), expr);
+        assert_eq!(
+            Expression::parse(r#"hasRole("it's") AND #doc.owner eq 'O''Brien'"#)
+                .unwrap()
+                .variables(),
+            ["doc"]
+        );
+
+        for malformed in [
+            "",
+            "hasRole('ADMIN'",
+            "hasRole('ADMIN') or",
+            "hasRole('ADMIN)",
+            "hasRol('ADMIN')",
+            "hasRole()",
+            "isAuthenticated(1)",
+            "user.name",
+            "#",
+            "#id = 1",
+            "principal.",
+        ] {
+            assert!(
+                matches!(Expression::parse(malformed), Err(SecurityError::InvalidExpression(_))),
+                "{malformed:?} should not parse"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluate() {
+        let context = EvaluationContext::new(Some(alice()))
+            .variable("userId", 7)
+            .variable("username", "alice")
+            .variable("ids", vec![1, 2]);
+
+        assert!(eval("hasRole('USER') and hasAuthority('USER:READ')", &context).await);
+        assert!(eval("hasRole('ROLE_USER')", &context).await);
+        assert!(eval("hasAnyRole('ADMIN', 'USER')", &context).await);
+        assert!(!eval("hasAnyAuthority('USER:WRITE', 'USER:DELETE')", &context).await);
+        assert!(eval("!(hasRole('ADMIN') || denyAll) and permitAll", &context).await);
+        assert!(eval("isAuthenticated() and not isAnonymous()", &context).await);
+        assert!(eval("#username == authentication.name", &context).await);
+        assert!(eval("#username == principal.username", &context).await);
+        assert!(eval("authentication.principal.name == 'alice'", &context).await);
+        assert!(eval("#userId >= 7 and #userId lt 7.5 and #userId != -7", &context).await);
+        assert!(eval("#missing == null and #missing.field == null", &context).await);
+        assert!(eval("hasPermission('report', 'export')", &context).await);
+        assert!(!eval("hasPermission('report', 'delete')", &context).await);
+
+        let context = context.principal(json!({ "id": "7", "name": "alice" }));
+        assert!(eval("#userId == principal.id", &context).await);
+
+        let anonymous = EvaluationContext::new(None);
+        assert!(eval("isAnonymous() and !hasRole('USER')", &anonymous).await);
+        assert!(!eval("authentication.name == 'alice'", &anonymous).await);
+
+        for wrong_type in ["#username", "not 'x'", "#ids > 1", "#username.length == 5"] {
+            let expr = Expression::parse(wrong_type).unwrap();
+            assert!(
+                matches!(expr.evaluate(&context).await, Err(SecurityError::InvalidExpression(_))),
+                "{wrong_type:?} should not evaluate"
+            );
+        }
+        assert!(matches!(
+            Expression::parse("hasRole('ADMIN')")
+                .unwrap()
+                .authorize(&context)
+                .await,
+            Err(SecurityError::AccessDenied(_))
+        ));
+    }
+
+    struct OwnerPermissions;
+
+    #[async_trait]
+    impl PermissionEvaluator for OwnerPermissions {
+        async fn has_permission(
+            &self,
+            authentication: &Authentication,
+            target: &Value,
+            permission: &str,
+        ) -> bool {
+            permission == "write" && target["owner"] == authentication.name()
+        }
+
+        async fn has_permission_by_id(
+            &self,
+            _authentication: &Authentication,
+            target_id: &Value,
+            target_type: &str,
+            permission: &str,
+        ) -> bool {
+            target_type == "Document" && permission == "read" && target_id == 1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_permission_evaluator() {
+        let context = EvaluationContext::new(Some(alice()))
+            .permission_evaluator(OwnerPermissions)
+            .variable("mine", json!({ "id": 1, "owner": "alice" }))
+            .variable("theirs", json!({ "id": 2, "owner": "bob" }));
+
+        assert!(eval("hasPermission(#mine, 'write')", &context).await);
+        assert!(!eval("hasPermission(#theirs, 'write')", &context).await);
+        assert!(eval("hasPermission(#mine.id, 'Document', 'read')", &context).await);
+        assert!(!eval("hasPermission(#theirs.id, 'Document', 'read')", &context).await);
+
+        let anonymous = EvaluationContext::new(None).permission_evaluator(OwnerPermissions);
+        assert!(!eval("hasPermission(#mine, 'write')", &anonymous).await);
+    }
+
+    #[tokio::test]
+    async fn test_return_and_filter_objects() {
+        let context = EvaluationContext::new(Some(alice())).variable("min", 2);
+
+        let own = context.clone().return_object(json!({ "owner": "alice" }));
+        assert!(eval("returnObject.owner == authentication.name", &own).await);
+        assert!(!eval("returnObject.owner == authentication.name", &context).await);
+
+        let expr = Expression::parse("filterObject >= #min").unwrap();
+        assert_eq!(expr.filter(vec![1, 2, 3], &context).await.unwrap(), [2, 3]);
+        let names = std::collections::BTreeSet::from(["alice", "bob"]);
+        let expr = Expression::parse("filterObject == principal.name").unwrap();
+        assert_eq!(
+            expr.filter(names, &context).await.unwrap(),
+            std::collections::BTreeSet::from(["alice"])
+        );
+        assert!(
+            Expression::parse("filterObject")
+                .unwrap()
+                .filter(vec!["x"], &context)
+                .await
+                .is_err()
+        );
+    }
+}
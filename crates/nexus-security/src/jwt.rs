@@ -51,16 +51,19 @@ pub struct JwtClaims {
     /// 主体（用户ID）
     pub sub: String,
 
-    /// Username
-    /// 用户名
+    /// Username, falling back to `preferred_username` as issued by OIDC providers
+    /// 用户名，缺省时取OIDC提供方签发的 `preferred_username`
+    #[serde(default, alias = "preferred_username")]
     pub username: String,
 
     /// Authorities/roles
     /// 权限/角色
+    #[serde(default)]
     pub authorities: Vec<String>,
 
     /// Issued at
     /// 签发时间
+    #[serde(default)]
     pub iat: i64,
 
     /// Expiration
@@ -70,6 +73,43 @@ pub struct JwtClaims {
     /// Issuer
     /// 签发者
     pub iss: Option<String>,
+
+    /// Audience
+    /// 受众
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "one_or_many"
+    )]
+    pub aud: Vec<String>,
+
+    /// Not before
+    /// 生效时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+
+    /// Token id, used for revocation
+    /// Token ID，用于吊销
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+/// Deserialize a claim that is either a string or an array of strings
+/// 反序列化为字符串或字符串数组的声明
+pub(crate) fn one_or_many<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(one) => vec![one],
+        OneOrMany::Many(many) => many,
+    })
 }
 
 impl JwtClaims {
@@ -91,6 +131,9 @@ impl JwtClaims {
             iat: now.timestamp(),
             exp: expiration.timestamp(),
             iss: Some("nexus-security".to_string()),
+            aud: Vec::new(),
+            nbf: None,
+            jti: None,
         }
     }
 
@@ -150,14 +193,14 @@ pub struct JwtUtil;
 impl JwtUtil {
     /// Get JWT secret key from environment or use default
     /// 从环境变量获取JWT密钥或使用默认值
-    fn get_secret() -> String {
+    pub(crate) fn get_secret() -> String {
         env::var("JWT_SECRET")
             .unwrap_or_else(|_| "nexus-jwt-secret-key-change-in-production-2024".to_string())
     }
 
     /// Get default token expiration in hours
     /// 获取默认token过期时间（小时）
-    fn get_default_expiration() -> i64 {
+    pub(crate) fn get_default_expiration() -> i64 {
         env::var("JWT_EXPIRATION_HOURS")
             .ok()
             .and_then(|s| s.parse().ok())
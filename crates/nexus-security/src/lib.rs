@@ -4,12 +4,17 @@
 //! # Equivalent to Spring Boot / 等价于 Spring Boot
 //!
 //! - `@PreAuthorize` - PreAuthorize
+//! - SpEL security expressions / `PermissionEvaluator` - `Expression` + `ExpressionExt` / `PermissionEvaluator`
 //! - `@Secured` - Secured
 //! - `@RolesAllowed` - RolesAllowed
 //! - `UserDetails` - User
 //! - `GrantedAuthority` - Permission/Role
 //! - `Authentication` - Auth
 //! - `SecurityContext` - SecurityContext
+//! - `SecurityContextHolder` - `context()` / `SecurityContext::scope`
+//! - `JwtDecoder` / `JwtEncoder` - `JwtDecoder` / `JwtEncoder`
+//! - `oauth2ResourceServer().opaqueToken()` - `OpaqueTokenAuthenticationProvider`
+//! - `oauth2Login()` - `OidcClient`
 //!
 //! # Example / 示例
 //!
@@ -51,7 +56,11 @@ mod authority;
 mod context;
 mod encoder;
 mod error;
+mod expression;
+mod jwks;
 mod jwt;
+mod jwt_codec;
+mod oauth2;
 mod pre_authorize;
 mod rbac;
 mod request_ext;
@@ -61,29 +70,56 @@ mod user;
 
 pub use auth::{Authentication, AuthenticationManager};
 pub use authority::{Authority, GrantedAuthority};
-pub use context::SecurityContext;
+pub use context::{
+    SecurityContext, SecurityContextStrategy, context, context_strategy, propagate_context,
+    propagate_context_with, set_context_strategy, spawn_with_context,
+};
 pub use encoder::{BcryptPasswordEncoder, NoOpPasswordEncoder, PasswordEncoder, Pbkdf2PasswordEncoder, StandardPasswordEncoder};
 pub use error::{SecurityError, SecurityResult};
+pub use expression::{
+    AuthorityPermissionEvaluator, ComparisonOperator, EvaluationContext, Expression, ExpressionExt,
+    PermissionEvaluator, permission_evaluator, set_permission_evaluator,
+};
+pub use jwks::{JwkSetCache, JwkSetSource, RemoteJwkSet};
 pub use jwt::{JwtAuthentication, JwtClaims, JwtTokenProvider, JwtUtil};
-pub use pre_authorize::{PreAuthorize, SecurityExpression};
+pub use jwt_codec::{
+    DEFAULT_LEEWAY, InMemoryRevocationList, JWT_PROPERTIES_PREFIX, JwtDecoder, JwtDecoderBuilder,
+    JwtEncoder, JwtEncoderBuilder, JwtProperties, RevocationChecker,
+};
+pub use oauth2::{
+    AuthorizationRequest, ClaimsAuthenticationConverter, DEFAULT_INTROSPECTION_CACHE_CAPACITY,
+    DEFAULT_INTROSPECTION_CACHE_TTL, DEFAULT_LOGIN_TIMEOUT, DEFAULT_OIDC_SCOPES,
+    IntrospectionResponse, OidcClient, OidcClientBuilder, OidcLogin, OidcProviderMetadata,
+    OidcTokenResponse, OpaqueTokenAuthenticationProvider, OpaqueTokenIntrospector,
+    RemoteOpaqueTokenIntrospector,
+};
+pub use pre_authorize::{
+    PreAuthorize, PreAuthorizeOptions, SecurityExpression, check_pre_authorize,
+};
 pub use rbac::{
     AuditLog, AuditLogger, ConsoleAuditLogger, PermissionEntry, RbacConfig, RbacManager,
     RolePermission, UserRole,
 };
-pub use request_ext::{SecurityContextExt, get_authentication_from_request};
+pub use request_ext::{
+    SecurityContextExt, get_authentication_from_request, set_authentication_to_request,
+};
 pub use role::{Permission, Role, Role as RoleEnum, Roles};
 pub use secured::{Secured, SecuredHelper, SecurityMetadata};
 pub use user::{InMemoryUserService, User, UserDetails, UserService};
 
+// Re-export JWT key types / 重新导出JWT密钥类型
+pub use jsonwebtoken::Algorithm as JwtAlgorithm;
+pub use jsonwebtoken::jwk::{Jwk, JwkSet};
+
 /// Re-exports of commonly used types
 /// 常用类型的重新导出
 pub mod prelude {
     pub use super::{
         AuditLogger, Authentication, AuthenticationManager, Authority, ConsoleAuditLogger,
-        GrantedAuthority, JwtAuthentication, JwtClaims, JwtTokenProvider, JwtUtil, PasswordEncoder,
-        Permission, PermissionEntry, PreAuthorize, RbacConfig, RbacManager, RoleEnum,
-        RolePermission, Roles, Secured, SecurityContext, SecurityExpression, User, UserDetails,
-        UserRole, UserService,
+        GrantedAuthority, JwtAuthentication, JwtClaims, JwtDecoder, JwtEncoder, JwtTokenProvider,
+        JwtUtil, PasswordEncoder, Permission, PermissionEntry, PreAuthorize, RbacConfig,
+        RbacManager, RoleEnum, RolePermission, Roles, Secured, SecurityContext, SecurityContextExt,
+        SecurityExpression, User, UserDetails, UserRole, UserService,
     };
 }
 
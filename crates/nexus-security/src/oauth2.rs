@@ -0,0 +1,302 @@
+//! OAuth2 module
+//! OAuth2 模块
+//!
+//! OAuth2 resource server support for opaque tokens (RFC 7662 introspection) and
+//! the OpenID Connect authorization-code + PKCE login flow for browser apps. Both
+//! turn the claims they receive into an [`Authentication`] through a
+//! [`ClaimsAuthenticationConverter`].
+//!
+//! 不透明token的OAuth2资源服务器支持（RFC 7662内省），以及面向浏览器应用的OpenID Connect
+//! 授权码 + PKCE 登录流程。两者都通过 [`ClaimsAuthenticationConverter`] 将收到的声明转换为
+//! [`Authentication`]。
+//!
+//! # Equivalent to Spring Boot / 等价于 Spring Boot
+//!
+//! - `oauth2ResourceServer().opaqueToken()` - [`OpaqueTokenAuthenticationProvider`]
+//! - `OpaqueTokenIntrospector` - [`OpaqueTokenIntrospector`]
+//! - `oauth2Login()` - [`OidcClient`]
+//! - `JwtAuthenticationConverter` - [`ClaimsAuthenticationConverter`]
+
+mod introspection;
+mod oidc;
+
+pub use introspection::{
+    DEFAULT_INTROSPECTION_CACHE_CAPACITY, DEFAULT_INTROSPECTION_CACHE_TTL, IntrospectionResponse,
+    OpaqueTokenAuthenticationProvider, OpaqueTokenIntrospector, RemoteOpaqueTokenIntrospector,
+};
+pub use oidc::{
+    AuthorizationRequest, DEFAULT_LOGIN_TIMEOUT, DEFAULT_OIDC_SCOPES, OidcClient,
+    OidcClientBuilder, OidcLogin, OidcProviderMetadata, OidcTokenResponse,
+};
+
+use crate::auth::AuthDetails;
+use crate::{Authentication, Authority, SecurityError, SecurityResult};
+use chrono::Utc;
+use serde_json::{Map, Value};
+
+/// Converts verified claims into an [`Authentication`]
+/// 将已验证的声明转换为 [`Authentication`]
+///
+/// The principal is the first present claim of `preferred_username`, `username`
+/// and `sub`. Authorities come from `scope` and `scp` with the `SCOPE_` prefix;
+/// more claims can be mapped, e.g. `realm_access.roles` with `ROLE_`. Claim
+/// values may be space-separated strings or arrays.
+///
+/// 主体取 `preferred_username`、`username`、`sub` 中第一个存在的声明。权限来自带 `SCOPE_`
+/// 前缀的 `scope` 和 `scp`；还可以映射更多声明，例如带 `ROLE_` 的 `realm_access.roles`。
+/// 声明值可以是空格分隔的字符串或数组。
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// let converter = ClaimsAuthenticationConverter::new()
+///     .principal_claim("email")
+///     .authorities_claim("realm_access.roles", "ROLE_");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimsAuthenticationConverter {
+    principal_claims: Vec<String>,
+    authorities_claims: Vec<(String, String)>,
+}
+
+impl Default for ClaimsAuthenticationConverter {
+    fn default() -> Self {
+        Self {
+            principal_claims: vec![
+                "preferred_username".to_string(),
+                "username".to_string(),
+                "sub".to_string(),
+            ],
+            authorities_claims: vec![
+                ("scope".to_string(), "SCOPE_".to_string()),
+                ("scp".to_string(), "SCOPE_".to_string()),
+            ],
+        }
+    }
+}
+
+impl ClaimsAuthenticationConverter {
+    /// Create a converter with the default claims
+    /// 使用默认声明创建转换器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the principal from this claim only
+    /// 仅从此声明获取主体
+    pub fn principal_claim(mut self, claim: impl Into<String>) -> Self {
+        self.principal_claims = vec![claim.into()];
+        self
+    }
+
+    /// Also map a claim to authorities, prefixing every value; nested claims use dots
+    /// 同时将一个声明映射为权限，为每个值添加前缀；嵌套声明使用点号
+    pub fn authorities_claim(
+        mut self,
+        claim: impl Into<String>,
+        prefix: impl Into<String>,
+    ) -> Self {
+        self.authorities_claims.push((claim.into(), prefix.into()));
+        self
+    }
+
+    /// Get the principal named by the claims
+    /// 获取声明所指定的主体
+    pub fn principal(&self, claims: &Map<String, Value>) -> Option<String> {
+        self.principal_claims
+            .iter()
+            .find_map(|name| claim(claims, name)?.as_str().map(String::from))
+    }
+
+    /// Get the authorities granted by the claims
+    /// 获取声明所授予的权限
+    pub fn authorities(&self, claims: &Map<String, Value>) -> Vec<Authority> {
+        let mut authorities = Vec::new();
+        for (name, prefix) in &self.authorities_claims {
+            let values: Vec<&str> = match claim(claims, name) {
+                Some(Value::String(value)) => value.split_whitespace().collect(),
+                Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+                _ => continue,
+            };
+            for value in values {
+                if let Some(authority) = Authority::from_string(&format!("{}{}", prefix, value))
+                    && !authorities.contains(&authority)
+                {
+                    authorities.push(authority);
+                }
+            }
+        }
+        authorities
+    }
+
+    /// Build the authentication for verified claims
+    /// 为已验证的声明构建认证
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns an error if no principal claim is present.
+    /// 不存在主体声明时返回错误。
+    pub fn convert(
+        &self,
+        claims: &Map<String, Value>,
+        auth_type: &str,
+    ) -> SecurityResult<Authentication> {
+        let principal = self.principal(claims).ok_or_else(|| {
+            SecurityError::InvalidToken(format!(
+                "None of the claims {:?} names the principal",
+                self.principal_claims
+            ))
+        })?;
+        Ok(Authentication {
+            principal,
+            credentials: None,
+            authorities: self.authorities(claims),
+            authenticated: true,
+            details: Some(AuthDetails::new().auth_type(auth_type)),
+            login_time: Utc::now(),
+        })
+    }
+}
+
+/// Look up a claim, following dots into nested objects
+/// 查找声明，按点号进入嵌套对象
+fn claim<'a>(claims: &'a Map<String, Value>, name: &str) -> Option<&'a Value> {
+    if let Some(value) = claims.get(name) {
+        return Some(value);
+    }
+    let (first, rest) = name.split_once('.')?;
+    claim(claims.get(first)?.as_object()?, rest)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::Role;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    /// A request received by [`AuthorizationServerStub`]
+    pub(crate) struct StubRequest {
+        pub(crate) method: String,
+        pub(crate) path: String,
+        pub(crate) query: HashMap<String, String>,
+        pub(crate) headers: HashMap<String, String>,
+        pub(crate) form: HashMap<String, String>,
+    }
+
+    /// Local stand-in for an authorization server; the handler returns the
+    /// status and JSON body of each response
+    pub(crate) struct AuthorizationServerStub {
+        addr: String,
+    }
+
+    impl AuthorizationServerStub {
+        pub(crate) fn start<F>(handler: F) -> Self
+        where
+            F: Fn(&StubRequest) -> (u16, String) + Send + Sync + 'static,
+        {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+            let handler = Arc::new(handler);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else { return };
+                    let handler = handler.clone();
+                    std::thread::spawn(move || {
+                        let mut reader = BufReader::new(stream);
+                        while let Some(request) = read_request(&mut reader) {
+                            let (status, body) = handler(&request);
+                            let response = format!(
+                                "HTTP/1.1 {} Stub\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                                status,
+                                body.len(),
+                                body
+                            );
+                            if reader.get_mut().write_all(response.as_bytes()).is_err() {
+                                return;
+                            }
+                        }
+                    });
+                }
+            });
+            Self { addr }
+        }
+
+        pub(crate) fn url(&self, path: &str) -> String {
+            format!("http://{}{}", self.addr, path)
+        }
+    }
+
+    fn read_request(reader: &mut BufReader<std::net::TcpStream>) -> Option<StubRequest> {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let mut parts = line.split_whitespace();
+        let method = parts.next()?.to_string();
+        let target = parts.next()?.to_string();
+
+        let mut headers = HashMap::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).ok()?;
+            let Some((name, value)) = line.trim_end().split_once(':') else {
+                break;
+            };
+            headers.insert(name.to_ascii_lowercase(), value.trim().to_string());
+        }
+        let mut body = vec![
+            0u8;
+            headers
+                .get("content-length")
+                .map_or(Ok(0), |len| len.parse())
+                .ok()?
+        ];
+        reader.read_exact(&mut body).ok()?;
+
+        let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+        Some(StubRequest {
+            method,
+            path: path.to_string(),
+            query: serde_urlencoded::from_str(query).ok()?,
+            headers,
+            form: serde_urlencoded::from_bytes(&body).ok()?,
+        })
+    }
+
+    #[test]
+    fn test_claims_authentication_converter() {
+        let claims = json!({
+            "sub": "248289761001",
+            "preferred_username": "alice",
+            "scope": "orders.read orders.write",
+            "realm_access": { "roles": ["admin", "auditor"] },
+        });
+        let claims = claims.as_object().unwrap();
+
+        let converter =
+            ClaimsAuthenticationConverter::new().authorities_claim("realm_access.roles", "ROLE_");
+        let auth = converter.convert(claims, "Bearer").unwrap();
+        assert_eq!(auth.principal, "alice");
+        assert!(auth.authenticated);
+        assert_eq!(
+            auth.authorities,
+            [
+                Authority::Permission("SCOPE_orders.read".to_string()),
+                Authority::Permission("SCOPE_orders.write".to_string()),
+                Authority::Role(Role::Admin),
+                Authority::Role(Role::Custom("AUDITOR".to_string())),
+            ]
+        );
+
+        let by_sub = ClaimsAuthenticationConverter::new().principal_claim("sub");
+        assert_eq!(by_sub.principal(claims).as_deref(), Some("248289761001"));
+        assert!(
+            ClaimsAuthenticationConverter::new()
+                .principal_claim("email")
+                .convert(claims, "Bearer")
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! Opaque token introspection (RFC 7662)
+//! 不透明token内省（RFC 7662）
+//!
+//! # Spring Equivalent / Spring等价物
+//!
+//! ```java
+//! http.oauth2ResourceServer(oauth2 -> oauth2.opaqueToken(token -> token
+//!     .introspectionUri("https://idp.example.com/oauth2/introspect")
+//!     .introspectionClientCredentials("client", "secret")));
+//! ```
+
+use super::ClaimsAuthenticationConverter;
+use crate::jwt::one_or_many;
+use crate::{Authentication, SecurityError, SecurityResult};
+use async_trait::async_trait;
+use nexus_client::{HttpClient, ResponseExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Default time an introspection result is reused
+/// 内省结果的默认复用时间
+pub const DEFAULT_INTROSPECTION_CACHE_TTL: Duration = Duration::from_mins(1);
+
+/// Default number of cached introspection results
+/// 默认缓存的内省结果数量
+pub const DEFAULT_INTROSPECTION_CACHE_CAPACITY: usize = 10_000;
+
+/// Introspection response
+/// 内省响应
+///
+/// Members other than the standard ones are kept in `extra`.
+/// 标准成员以外的成员保存在 `extra` 中。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntrospectionResponse {
+    /// Whether the token is active / token是否有效
+    pub active: bool,
+    /// Space-separated scopes / 空格分隔的作用域
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Client the token was issued to / token签发给的客户端
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Resource owner / 资源所有者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Token type / token类型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    /// Expiration time / 过期时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    /// Issued at / 签发时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    /// Not before / 生效时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    /// Subject / 主题
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Audiences / 受众
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "one_or_many"
+    )]
+    pub aud: Vec<String>,
+    /// Issuer / 签发者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Token ID / Token ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// Other members / 其他成员
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl IntrospectionResponse {
+    /// Get the scopes
+    /// 获取作用域
+    pub fn scopes(&self) -> Vec<&str> {
+        self.scope
+            .as_deref()
+            .map(|scope| scope.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get all members as claims
+    /// 以声明形式获取所有成员
+    pub fn claims(&self) -> Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(claims)) => claims,
+            _ => Map::new(),
+        }
+    }
+
+    /// Seconds until `exp`, if the response has one
+    /// 距离 `exp` 的秒数（如果响应包含）
+    fn expires_in(&self) -> Option<i64> {
+        self.exp.map(|exp| exp - chrono::Utc::now().timestamp())
+    }
+}
+
+/// Asks the authorization server about an opaque token
+/// 向授权服务器查询不透明token
+#[async_trait]
+pub trait OpaqueTokenIntrospector: Send + Sync {
+    /// Introspect a token; an unknown token is reported as inactive, not as an error
+    /// 内省token；未知token报告为无效，而不是错误
+    async fn introspect(&self, token: &str) -> SecurityResult<IntrospectionResponse>;
+}
+
+/// A cached introspection result
+/// 缓存的内省结果
+struct Cached {
+    response: IntrospectionResponse,
+    until: Instant,
+}
+
+/// Introspector calling an RFC 7662 endpoint, with a response cache
+/// 调用RFC 7662端点的内省器，带响应缓存
+///
+/// Results are cached by a SHA-256 hash of the token, never longer than the
+/// token lives. A zero TTL turns the cache off.
+/// 结果按token的SHA-256哈希缓存，缓存时间不超过token的有效期。TTL为零时关闭缓存。
+pub struct RemoteOpaqueTokenIntrospector {
+    uri: String,
+    client_id: String,
+    client_secret: String,
+    client: HttpClient,
+    cache_ttl: Duration,
+    cache_capacity: usize,
+    cache: RwLock<HashMap<String, Cached>>,
+}
+
+impl RemoteOpaqueTokenIntrospector {
+    /// Create an introspector authenticating with client credentials (HTTP basic)
+    /// 创建使用客户端凭据（HTTP基本认证）认证的内省器
+    pub fn new(
+        uri: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            uri: uri.into(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            client: HttpClient::new(),
+            cache_ttl: DEFAULT_INTROSPECTION_CACHE_TTL,
+            cache_capacity: DEFAULT_INTROSPECTION_CACHE_CAPACITY,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Use a configured HTTP client (timeouts, TLS roots, interceptors)
+    /// 使用已配置的HTTP客户端（超时、TLS根证书、拦截器）
+    pub fn with_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set how long results are reused; zero disables caching
+    /// 设置结果的复用时间；为零时禁用缓存
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Set the maximum number of cached results
+    /// 设置缓存结果的最大数量
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    /// Get the introspection endpoint
+    /// 获取内省端点
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Call the endpoint
+    /// 调用端点
+    async fn fetch(&self, token: &str) -> SecurityResult<IntrospectionResponse> {
+        self.client
+            .post(&self.uri)
+            .basic_auth(&self.client_id, Some(self.client_secret.as_str()))
+            .header("accept", "application/json")
+            .form(&[("token", token), ("token_type_hint", "access_token")])
+            .send()
+            .await
+            .and_then(ResponseExt::error_for_status)
+            .and_then(|response| response.json())
+            .map_err(|e| {
+                SecurityError::OAuth2(format!("Token introspection at {} failed: {}", self.uri, e))
+            })
+    }
+
+    /// Cache a result for the TTL, or until the token expires if sooner
+    /// 在TTL内缓存结果；如果token更早过期则缓存到过期为止
+    fn store(&self, key: String, response: &IntrospectionResponse) {
+        let mut ttl = self.cache_ttl;
+        if response.active
+            && let Some(expires_in) = response.expires_in()
+        {
+            ttl = ttl.min(Duration::from_secs(u64::try_from(expires_in).unwrap_or(0)));
+        }
+        if ttl.is_zero() {
+            return;
+        }
+
+        let Ok(mut cache) = self.cache.write() else {
+            return;
+        };
+        let now = Instant::now();
+        if cache.len() >= self.cache_capacity {
+            cache.retain(|_, cached| cached.until > now);
+        }
+        if cache.len() < self.cache_capacity {
+            cache.insert(
+                key,
+                Cached {
+                    response: response.clone(),
+                    until: now + ttl,
+                },
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl OpaqueTokenIntrospector for RemoteOpaqueTokenIntrospector {
+    async fn introspect(&self, token: &str) -> SecurityResult<IntrospectionResponse> {
+        let key = hex::encode(Sha256::digest(token.as_bytes()));
+        if let Ok(cache) = self.cache.read()
+            && let Some(cached) = cache.get(&key)
+            && cached.until > Instant::now()
+        {
+            return Ok(cached.response.clone());
+        }
+
+        let response = self.fetch(token).await?;
+        self.store(key, &response);
+        Ok(response)
+    }
+}
+
+impl std::fmt::Debug for RemoteOpaqueTokenIntrospector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteOpaqueTokenIntrospector")
+            .field("uri", &self.uri)
+            .field("client_id", &self.client_id)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("cache_capacity", &self.cache_capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Authenticates bearer requests with opaque tokens
+/// 使用不透明token认证Bearer请求
+///
+/// # Example / 示例
+///
+/// ```rust,no_run,ignore
+/// let provider = OpaqueTokenAuthenticationProvider::new(RemoteOpaqueTokenIntrospector::new(
+///     "https://idp.example.com/oauth2/introspect",
+///     "orders-api",
+///     "secret",
+/// ))
+/// .audience("orders-api");
+///
+/// let auth = provider.authenticate(token).await?;
+/// ```
+pub struct OpaqueTokenAuthenticationProvider {
+    introspector: Arc<dyn OpaqueTokenIntrospector>,
+    converter: ClaimsAuthenticationConverter,
+    audiences: Vec<String>,
+}
+
+impl OpaqueTokenAuthenticationProvider {
+    /// Create a provider
+    /// 创建提供者
+    pub fn new(introspector: impl OpaqueTokenIntrospector + 'static) -> Self {
+        Self {
+            introspector: Arc::new(introspector),
+            converter: ClaimsAuthenticationConverter::default(),
+            audiences: Vec::new(),
+        }
+    }
+
+    /// Set how claims become an authentication
+    /// 设置声明如何转换为认证
+    pub fn with_converter(mut self, converter: ClaimsAuthenticationConverter) -> Self {
+        self.converter = converter;
+        self
+    }
+
+    /// Require one of the accepted audiences
+    /// 要求为接受的受众之一
+    pub fn audience(mut self, audience: impl Into<String>) -> Self {
+        self.audiences.push(audience.into());
+        self
+    }
+
+    /// Introspect a token and build its authentication
+    /// 内省token并构建其认证
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns [`SecurityError::InvalidToken`] for inactive tokens or a foreign
+    /// audience, [`SecurityError::TokenExpired`] for expired ones and
+    /// [`SecurityError::OAuth2`] if the authorization server cannot be asked.
+    /// 无效token或受众不符时返回 [`SecurityError::InvalidToken`]，过期时返回
+    /// [`SecurityError::TokenExpired`]，无法访问授权服务器时返回 [`SecurityError::OAuth2`]。
+    pub async fn authenticate(&self, token: &str) -> SecurityResult<Authentication> {
+        let response = self.introspector.introspect(token).await?;
+        if !response.active {
+            return Err(SecurityError::InvalidToken("Token is not active".to_string()));
+        }
+        if response
+            .expires_in()
+            .is_some_and(|expires_in| expires_in <= 0)
+        {
+            return Err(SecurityError::TokenExpired("Token has expired".to_string()));
+        }
+        if !self.audiences.is_empty()
+            && !response.aud.iter().any(|aud| self.audiences.contains(aud))
+        {
+            return Err(SecurityError::InvalidToken(format!(
+                "Token audience {:?} is not accepted",
+                response.aud
+            )));
+        }
+        self.converter.convert(&response.claims(), "Bearer")
+    }
+}
+
+impl std::fmt::Debug for OpaqueTokenAuthenticationProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpaqueTokenAuthenticationProvider")
+            .field("converter", &self.converter)
+            .field("audiences", &self.audiences)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Authority;
+    use crate::oauth2::tests::AuthorizationServerStub;
+    use nexus_runtime::task::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn introspection_server(calls: Arc<AtomicUsize>) -> AuthorizationServerStub {
+        AuthorizationServerStub::start(move |req| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            // "orders-api:secret"
+            if req.method != "POST"
+                || req.headers.get("authorization").map(String::as_str)
+                    != Some("Basic b3JkZXJzLWFwaTpzZWNyZXQ=")
+            {
+                return (401, r#"{"error":"invalid_client"}"#.to_string());
+            }
+            let exp = chrono::Utc::now().timestamp() + 3600;
+            let body = match req.form.get("token").map(String::as_str) {
+                Some("good") => format!(
+                    r#"{{"active":true,"scope":"orders.read","username":"alice","aud":"orders-api","exp":{},"tenant":"acme"}}"#,
+                    exp
+                ),
+                Some("foreign") => {
+                    format!(r#"{{"active":true,"username":"bob","aud":["billing"],"exp":{}}}"#, exp)
+                },
+                _ => r#"{"active":false}"#.to_string(),
+            };
+            (200, body)
+        })
+    }
+
+    #[test]
+    fn test_introspection_with_cache() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let server = introspection_server(calls.clone());
+        let introspector =
+            RemoteOpaqueTokenIntrospector::new(server.url("/introspect"), "orders-api", "secret");
+
+        block_on(async move {
+            let first = introspector.introspect("good").await.unwrap();
+            assert!(first.active);
+            assert_eq!(first.scopes(), ["orders.read"]);
+            assert_eq!(first.aud, ["orders-api"]);
+            assert_eq!(first.extra["tenant"], "acme");
+
+            let second = introspector.introspect("good").await.unwrap();
+            assert_eq!(second, first);
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+            assert!(!introspector.introspect("unknown").await.unwrap().active);
+            assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+            let uncached = RemoteOpaqueTokenIntrospector::new(
+                server.url("/introspect"),
+                "orders-api",
+                "secret",
+            )
+            .with_cache_ttl(Duration::ZERO);
+            uncached.introspect("good").await.unwrap();
+            uncached.introspect("good").await.unwrap();
+            assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+            let wrong_secret = RemoteOpaqueTokenIntrospector::new(
+                server.url("/introspect"),
+                "orders-api",
+                "wrong",
+            );
+            assert!(matches!(wrong_secret.introspect("good").await, Err(SecurityError::OAuth2(_))));
+        });
+    }
+
+    #[test]
+    fn test_opaque_token_authentication_provider() {
+        let server = introspection_server(Arc::new(AtomicUsize::new(0)));
+        let provider = OpaqueTokenAuthenticationProvider::new(RemoteOpaqueTokenIntrospector::new(
+            server.url("/introspect"),
+            "orders-api",
+            "secret",
+        ))
+        .audience("orders-api");
+
+        block_on(async move {
+            let auth = provider.authenticate("good").await.unwrap();
+            assert_eq!(auth.principal, "alice");
+            assert_eq!(auth.authorities, [Authority::Permission("SCOPE_orders.read".to_string())]);
+
+            assert!(matches!(
+                provider.authenticate("unknown").await,
+                Err(SecurityError::InvalidToken(_))
+            ));
+            assert!(matches!(
+                provider.authenticate("foreign").await,
+                Err(SecurityError::InvalidToken(_))
+            ));
+        });
+    }
+}
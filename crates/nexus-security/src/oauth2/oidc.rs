@@ -0,0 +1,710 @@
+//! OpenID Connect login (authorization code + PKCE)
+//! OpenID Connect 登录（授权码 + PKCE）
+//!
+//! 1. [`OidcClient::authorization_request`] creates `state`, `nonce` and a PKCE
+//!    verifier, remembers them and returns the redirect to the provider.
+//! 2. [`OidcClient::callback`] checks `state`, exchanges the code with the
+//!    verifier, validates the ID token (signature, `iss`, `aud`, `exp`, `nonce`)
+//!    and maps its claims to an [`Authentication`].
+//!
+//! 1. [`OidcClient::authorization_request`] 生成 `state`、`nonce` 和PKCE验证码，保存它们并返回到提供方的重定向。
+//! 2. [`OidcClient::callback`] 检查 `state`，使用验证码交换授权码，验证ID token（签名、`iss`、`aud`、
+//!    `exp`、`nonce`），并将其声明映射为 [`Authentication`]。
+//!
+//! # Example / 示例
+//!
+//! ```rust,no_run,ignore
+//! let provider = OidcProviderMetadata::discover("https://idp.example.com").await?;
+//! let oidc = Arc::new(
+//!     OidcClient::builder(provider)
+//!         .client_id("web")
+//!         .client_secret("secret")
+//!         .redirect_uri("https://app.example.com/login/callback")
+//!         .build()?,
+//! );
+//!
+//! // GET /login
+//! let response = oidc.authorization_request(Some("/orders")).redirect();
+//!
+//! // GET /login/callback?code=...&state=...
+//! let login = oidc.callback(&req).await?;
+//! session.insert("auth", login.authentication);
+//! ```
+
+use super::ClaimsAuthenticationConverter;
+use crate::jwks::RemoteJwkSet;
+use crate::{Authentication, JwtDecoder, SecurityError, SecurityResult};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use nexus_client::{HttpClient, ResponseExt};
+use nexus_http::{Body, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Scopes requested when none are configured
+/// 未配置时请求的作用域
+pub const DEFAULT_OIDC_SCOPES: &[&str] = &["openid", "profile", "email"];
+
+/// Default time a user has to complete a login at the provider
+/// 用户在提供方完成登录的默认时限
+pub const DEFAULT_LOGIN_TIMEOUT: Duration = Duration::from_mins(10);
+
+/// OpenID provider metadata (`/.well-known/openid-configuration`)
+/// OpenID提供方元数据（`/.well-known/openid-configuration`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OidcProviderMetadata {
+    /// Issuer identifier / 签发者标识
+    pub issuer: String,
+    /// Authorization endpoint / 授权端点
+    pub authorization_endpoint: String,
+    /// Token endpoint / Token端点
+    pub token_endpoint: String,
+    /// JWK Set endpoint / JWK Set端点
+    pub jwks_uri: String,
+    /// UserInfo endpoint / UserInfo端点
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userinfo_endpoint: Option<String>,
+    /// Logout endpoint / 登出端点
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_session_endpoint: Option<String>,
+    /// ID token signing algorithms / ID token签名算法
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+}
+
+impl OidcProviderMetadata {
+    /// Fetch the metadata of an issuer
+    /// 获取签发者的元数据
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns an error if the document cannot be loaded or names another issuer.
+    /// 文档无法加载或指明了其他签发者时返回错误。
+    pub async fn discover(issuer: &str) -> SecurityResult<Self> {
+        Self::discover_with(&HttpClient::new(), issuer).await
+    }
+
+    /// Fetch the metadata of an issuer with a configured HTTP client
+    /// 使用已配置的HTTP客户端获取签发者的元数据
+    ///
+    /// # Errors / 错误
+    ///
+    /// Same as [`OidcProviderMetadata::discover`].
+    /// 与 [`OidcProviderMetadata::discover`] 相同。
+    pub async fn discover_with(client: &HttpClient, issuer: &str) -> SecurityResult<Self> {
+        let uri = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+        let metadata: Self = client
+            .get(&uri)
+            .header("accept", "application/json")
+            .send()
+            .await
+            .and_then(ResponseExt::error_for_status)
+            .and_then(|response| response.json())
+            .map_err(|e| {
+                SecurityError::OAuth2(format!("OIDC discovery at {} failed: {}", uri, e))
+            })?;
+
+        if metadata.issuer.trim_end_matches('/') != issuer.trim_end_matches('/') {
+            return Err(SecurityError::OAuth2(format!(
+                "Discovery for {} returned issuer {}",
+                issuer, metadata.issuer
+            )));
+        }
+        Ok(metadata)
+    }
+}
+
+/// Redirect that starts a login at the provider
+/// 在提供方开始登录的重定向
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationRequest {
+    /// Authorization URL / 授权URL
+    pub url: String,
+    /// State binding the callback to this request / 将回调绑定到此请求的state
+    pub state: String,
+}
+
+impl AuthorizationRequest {
+    /// Build the `302 Found` response to the authorization URL
+    /// 构建到授权URL的 `302 Found` 响应
+    pub fn redirect(&self) -> Response {
+        Response::with_status(StatusCode::FOUND)
+            .location(&self.url)
+            .header("cache-control", "no-store")
+            .body(Body::empty())
+    }
+}
+
+/// Token endpoint response
+/// Token端点响应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OidcTokenResponse {
+    /// Access token / 访问令牌
+    pub access_token: String,
+    /// Token type / Token类型
+    pub token_type: String,
+    /// Access token lifetime in seconds / 访问令牌有效期秒数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<u64>,
+    /// Refresh token / 刷新令牌
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// ID token / ID令牌
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+    /// Granted scopes / 授予的作用域
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// A completed login
+/// 已完成的登录
+#[derive(Debug, Clone)]
+pub struct OidcLogin {
+    /// Authentication of the user / 用户的认证
+    pub authentication: Authentication,
+    /// Verified ID token claims / 已验证的ID token声明
+    pub claims: Map<String, Value>,
+    /// Tokens issued by the provider / 提供方签发的令牌
+    pub tokens: OidcTokenResponse,
+    /// Where the user wanted to go before logging in / 登录前用户要访问的地址
+    pub return_to: Option<String>,
+}
+
+/// A login waiting for its callback
+/// 等待回调的登录
+struct PendingLogin {
+    nonce: String,
+    code_verifier: String,
+    return_to: Option<String>,
+    started: Instant,
+}
+
+/// OpenID Connect client for browser logins
+/// 用于浏览器登录的OpenID Connect客户端
+///
+/// Pending logins are kept in memory, so the callback must reach the instance
+/// that started the login.
+/// 待处理的登录保存在内存中，因此回调必须到达发起登录的实例。
+pub struct OidcClient {
+    provider: OidcProviderMetadata,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    client: HttpClient,
+    id_token_decoder: JwtDecoder,
+    converter: ClaimsAuthenticationConverter,
+    login_timeout: Duration,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+}
+
+impl OidcClient {
+    /// Create a client builder
+    /// 创建客户端构建器
+    pub fn builder(provider: OidcProviderMetadata) -> OidcClientBuilder {
+        OidcClientBuilder {
+            provider,
+            client_id: None,
+            client_secret: None,
+            redirect_uri: None,
+            scopes: Vec::new(),
+            client: None,
+            id_token_decoder: None,
+            converter: None,
+            login_timeout: DEFAULT_LOGIN_TIMEOUT,
+        }
+    }
+
+    /// Get the provider metadata
+    /// 获取提供方元数据
+    pub fn provider(&self) -> &OidcProviderMetadata {
+        &self.provider
+    }
+
+    /// Start a login, remembering where to return afterwards
+    /// 开始登录，并记住之后返回的地址
+    pub fn authorization_request(&self, return_to: Option<&str>) -> AuthorizationRequest {
+        let state = random_token();
+        let nonce = random_token();
+        let code_verifier = random_token();
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+        let query = serde_urlencoded::to_string([
+            ("response_type", "code"),
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("scope", self.scopes.join(" ").as_str()),
+            ("state", state.as_str()),
+            ("nonce", nonce.as_str()),
+            ("code_challenge", code_challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ])
+        .unwrap_or_default();
+        let separator = if self.provider.authorization_endpoint.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        let url = format!("{}{}{}", self.provider.authorization_endpoint, separator, query);
+
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.retain(|_, login| login.started.elapsed() < self.login_timeout);
+            pending.insert(
+                state.clone(),
+                PendingLogin {
+                    nonce,
+                    code_verifier,
+                    return_to: return_to.map(String::from),
+                    started: Instant::now(),
+                },
+            );
+        }
+        AuthorizationRequest { url, state }
+    }
+
+    /// Complete a login from the callback request
+    /// 从回调请求完成登录
+    ///
+    /// # Errors / 错误
+    ///
+    /// Same as [`OidcClient::handle_callback`].
+    /// 与 [`OidcClient::handle_callback`] 相同。
+    pub async fn callback(&self, req: &Request) -> SecurityResult<OidcLogin> {
+        self.handle_callback(req.params()).await
+    }
+
+    /// Complete a login from the callback query parameters
+    /// 从回调查询参数完成登录
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns [`SecurityError::AuthenticationFailed`] if the provider reports an
+    /// error or `state` is unknown, [`SecurityError::InvalidToken`] if the ID token
+    /// is rejected and [`SecurityError::OAuth2`] if the code exchange fails.
+    /// 提供方报告错误或 `state` 未知时返回 [`SecurityError::AuthenticationFailed`]，ID token
+    /// 被拒绝时返回 [`SecurityError::InvalidToken`]，授权码交换失败时返回 [`SecurityError::OAuth2`]。
+    pub async fn handle_callback(
+        &self,
+        params: &HashMap<String, String>,
+    ) -> SecurityResult<OidcLogin> {
+        // The state is single-use, even when the provider reports an error
+        let login = params
+            .get("state")
+            .and_then(|state| self.pending.lock().ok()?.remove(state))
+            .filter(|login| login.started.elapsed() < self.login_timeout)
+            .ok_or_else(|| {
+                SecurityError::AuthenticationFailed("Unknown or expired login state".to_string())
+            })?;
+        if let Some(error) = params.get("error") {
+            return Err(SecurityError::AuthenticationFailed(format!(
+                "Provider returned {}: {}",
+                error,
+                params.get("error_description").map_or("", String::as_str)
+            )));
+        }
+        let code = params.get("code").ok_or_else(|| {
+            SecurityError::AuthenticationFailed("Callback without authorization code".to_string())
+        })?;
+
+        let tokens = self.exchange_code(code, &login.code_verifier).await?;
+        let id_token = tokens
+            .id_token
+            .as_deref()
+            .ok_or_else(|| SecurityError::OAuth2("Token response without id_token".to_string()))?;
+        let claims = self.validate_id_token(id_token, &login.nonce).await?;
+        let authentication = self.converter.convert(&claims, "OIDC")?;
+
+        Ok(OidcLogin {
+            authentication,
+            claims,
+            tokens,
+            return_to: login.return_to,
+        })
+    }
+
+    /// Exchange the authorization code at the token endpoint
+    /// 在Token端点交换授权码
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> SecurityResult<OidcTokenResponse> {
+        let mut request = self
+            .client
+            .post(&self.provider.token_endpoint)
+            .header("accept", "application/json");
+        if let Some(secret) = &self.client_secret {
+            request = request.basic_auth(&self.client_id, Some(secret.as_str()));
+        }
+        let response = request
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("client_id", self.client_id.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .map_err(|e| SecurityError::OAuth2(format!("Token request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let error: Map<String, Value> = response.json().unwrap_or_default();
+            return Err(SecurityError::OAuth2(format!(
+                "Token endpoint returned {}: {}",
+                response.status(),
+                error
+                    .get("error")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+            )));
+        }
+        response
+            .json()
+            .map_err(|e| SecurityError::OAuth2(format!("Invalid token response: {}", e)))
+    }
+
+    /// Validate the ID token and return its claims
+    /// 验证ID token并返回其声明
+    async fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> SecurityResult<Map<String, Value>> {
+        let claims: Map<String, Value> = self.id_token_decoder.decode_as(id_token).await?;
+        let invalid =
+            |reason: &str| Err(SecurityError::InvalidToken(format!("ID token {}", reason)));
+
+        if !claims.get("sub").is_some_and(Value::is_string) {
+            return invalid("has no subject");
+        }
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            return invalid("nonce does not match the login");
+        }
+        // A token for several audiences must name this client as authorized party
+        let audiences = claims
+            .get("aud")
+            .and_then(Value::as_array)
+            .map_or(1, Vec::len);
+        let azp = claims.get("azp").and_then(Value::as_str);
+        if (audiences > 1 || azp.is_some()) && azp != Some(self.client_id.as_str()) {
+            return invalid("was issued to another party");
+        }
+        Ok(claims)
+    }
+}
+
+impl std::fmt::Debug for OidcClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OidcClient")
+            .field("issuer", &self.provider.issuer)
+            .field("client_id", &self.client_id)
+            .field("redirect_uri", &self.redirect_uri)
+            .field("scopes", &self.scopes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builder for [`OidcClient`]
+/// [`OidcClient`] 的构建器
+pub struct OidcClientBuilder {
+    provider: OidcProviderMetadata,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    redirect_uri: Option<String>,
+    scopes: Vec<String>,
+    client: Option<HttpClient>,
+    id_token_decoder: Option<JwtDecoder>,
+    converter: Option<ClaimsAuthenticationConverter>,
+    login_timeout: Duration,
+}
+
+impl OidcClientBuilder {
+    /// Set the client ID (required)
+    /// 设置客户端ID（必需）
+    pub fn client_id(mut self, client_id: impl Into<String>) -> Self {
+        self.client_id = Some(client_id.into());
+        self
+    }
+
+    /// Set the client secret; public clients rely on PKCE alone
+    /// 设置客户端密钥；公共客户端仅依赖PKCE
+    pub fn client_secret(mut self, client_secret: impl Into<String>) -> Self {
+        self.client_secret = Some(client_secret.into());
+        self
+    }
+
+    /// Set the callback URL registered at the provider (required)
+    /// 设置在提供方注册的回调URL（必需）
+    pub fn redirect_uri(mut self, redirect_uri: impl Into<String>) -> Self {
+        self.redirect_uri = Some(redirect_uri.into());
+        self
+    }
+
+    /// Request a scope; `openid` is always requested
+    /// 请求一个作用域；始终请求 `openid`
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+
+    /// Use a configured HTTP client
+    /// 使用已配置的HTTP客户端
+    pub fn http_client(mut self, client: HttpClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Verify ID tokens with this decoder instead of the provider's JWK Set
+    /// 使用此解码器而不是提供方的JWK Set验证ID token
+    pub fn id_token_decoder(mut self, decoder: JwtDecoder) -> Self {
+        self.id_token_decoder = Some(decoder);
+        self
+    }
+
+    /// Set how ID token claims become an authentication
+    /// 设置ID token声明如何转换为认证
+    pub fn converter(mut self, converter: ClaimsAuthenticationConverter) -> Self {
+        self.converter = Some(converter);
+        self
+    }
+
+    /// Set how long a user has to complete a login
+    /// 设置用户完成登录的时限
+    pub fn login_timeout(mut self, timeout: Duration) -> Self {
+        self.login_timeout = timeout;
+        self
+    }
+
+    /// Build the client
+    /// 构建客户端
+    ///
+    /// # Errors / 错误
+    ///
+    /// Returns an error if the client ID or redirect URI is missing.
+    /// 缺少客户端ID或重定向URI时返回错误。
+    pub fn build(self) -> SecurityResult<OidcClient> {
+        let client_id = self
+            .client_id
+            .ok_or_else(|| SecurityError::OAuth2("OidcClient needs a client_id".to_string()))?;
+        let redirect_uri = self
+            .redirect_uri
+            .ok_or_else(|| SecurityError::OAuth2("OidcClient needs a redirect_uri".to_string()))?;
+        let client = self.client.unwrap_or_default();
+
+        let mut scopes = self.scopes;
+        if scopes.is_empty() {
+            scopes = DEFAULT_OIDC_SCOPES
+                .iter()
+                .map(|scope| (*scope).to_string())
+                .collect();
+        } else if !scopes.iter().any(|scope| scope == "openid") {
+            scopes.insert(0, "openid".to_string());
+        }
+
+        let id_token_decoder = match self.id_token_decoder {
+            Some(decoder) => decoder,
+            None => {
+                let mut builder = JwtDecoder::builder()
+                    .jwk_set_source(RemoteJwkSet::with_client(
+                        &self.provider.jwks_uri,
+                        client.clone(),
+                    ))
+                    .issuer(&self.provider.issuer)
+                    .audience(&client_id);
+                for algorithm in &self.provider.id_token_signing_alg_values_supported {
+                    if let Ok(algorithm) = algorithm.parse() {
+                        builder = builder.algorithm(algorithm);
+                    }
+                }
+                builder.build()?
+            },
+        };
+
+        Ok(OidcClient {
+            provider: self.provider,
+            client_id,
+            client_secret: self.client_secret,
+            redirect_uri,
+            scopes,
+            client,
+            id_token_decoder,
+            converter: self.converter.unwrap_or_default(),
+            login_timeout: self.login_timeout,
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+/// A random URL-safe token with 256 bits of entropy
+/// 具有256位熵的随机URL安全token
+fn random_token() -> String {
+    URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwks::tests::EC_KEY_A;
+    use crate::oauth2::tests::AuthorizationServerStub;
+    use crate::{Authority, JwtEncoder, Role};
+    use nexus_runtime::task::block_on;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    /// Stand-in provider; `nonce` overrides the nonce put in ID tokens
+    fn provider_stub(nonce: Option<&'static str>) -> AuthorizationServerStub {
+        let encoder = Arc::new(
+            JwtEncoder::builder()
+                .private_key_pem(EC_KEY_A.as_bytes())
+                .key_id("k1")
+                .build()
+                .unwrap(),
+        );
+        let jwks = json!({ "keys": [encoder.public_jwk().unwrap()] }).to_string();
+        // The authorization endpoint is not called: codes carry "<challenge>|<nonce>"
+        let issuer = Arc::new(Mutex::new(String::new()));
+
+        let base = issuer.clone();
+        let stub = AuthorizationServerStub::start(move |req| {
+            let issuer = base.lock().unwrap().clone();
+            match (req.method.as_str(), req.path.as_str()) {
+                ("GET", "/.well-known/openid-configuration") => (
+                    200,
+                    json!({
+                        "issuer": issuer,
+                        "authorization_endpoint": format!("{issuer}/authorize"),
+                        "token_endpoint": format!("{issuer}/token"),
+                        "jwks_uri": format!("{issuer}/jwks"),
+                        "id_token_signing_alg_values_supported": ["ES256"],
+                    })
+                    .to_string(),
+                ),
+                ("GET", "/jwks") => (200, jwks.clone()),
+                ("POST", "/token") => {
+                    let (challenge, login_nonce) = req.form["code"].split_once('|').unwrap();
+                    let verifier = &req.form["code_verifier"];
+                    let authorized = req.headers.get("authorization").map(String::as_str)
+                        == Some("Basic d2ViOndlYi1zZWNyZXQ=");
+                    if !authorized
+                        || URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())) != challenge
+                    {
+                        return (400, r#"{"error":"invalid_grant"}"#.to_string());
+                    }
+                    let now = chrono::Utc::now().timestamp();
+                    let id_token = encoder
+                        .encode(&json!({
+                            "iss": issuer,
+                            "aud": "web",
+                            "sub": "u-1",
+                            "preferred_username": "alice",
+                            "roles": ["admin"],
+                            "nonce": nonce.unwrap_or(login_nonce),
+                            "iat": now,
+                            "exp": now + 300,
+                        }))
+                        .unwrap();
+                    (
+                        200,
+                        json!({
+                            "access_token": "at-1",
+                            "token_type": "Bearer",
+                            "expires_in": 300,
+                            "id_token": id_token,
+                        })
+                        .to_string(),
+                    )
+                },
+                _ => (404, r#"{"error":"not_found"}"#.to_string()),
+            }
+        });
+        *issuer.lock().unwrap() = stub.url("");
+        stub
+    }
+
+    async fn oidc_client(stub: &AuthorizationServerStub) -> OidcClient {
+        let provider = OidcProviderMetadata::discover(&stub.url("")).await.unwrap();
+        OidcClient::builder(provider)
+            .client_id("web")
+            .client_secret("web-secret")
+            .redirect_uri("https://app.example.com/callback")
+            .converter(ClaimsAuthenticationConverter::new().authorities_claim("roles", "ROLE_"))
+            .build()
+            .unwrap()
+    }
+
+    /// What the provider would redirect back with after the user logged in
+    fn callback_params(request: &AuthorizationRequest) -> HashMap<String, String> {
+        let query = request.url.split_once('?').unwrap().1;
+        let params: HashMap<String, String> = serde_urlencoded::from_str(query).unwrap();
+        HashMap::from([
+            ("code".to_string(), format!("{}|{}", params["code_challenge"], params["nonce"])),
+            ("state".to_string(), params["state"].clone()),
+        ])
+    }
+
+    #[test]
+    fn test_authorization_code_login() {
+        let stub = provider_stub(None);
+        block_on(async move {
+            let oidc = oidc_client(&stub).await;
+            let request = oidc.authorization_request(Some("/orders"));
+            assert!(
+                request
+                    .url
+                    .starts_with(&stub.url("/authorize?response_type=code&client_id=web"))
+            );
+            assert!(request.url.contains("scope=openid+profile+email"));
+            assert!(request.url.contains("code_challenge_method=S256"));
+            let redirect = request.redirect();
+            assert_eq!(redirect.status(), StatusCode::FOUND);
+            assert_eq!(redirect.header("location"), Some(request.url.as_str()));
+
+            let params = callback_params(&request);
+            let login = oidc.handle_callback(&params).await.unwrap();
+            assert_eq!(login.authentication.principal, "alice");
+            assert_eq!(login.authentication.authorities, [Authority::Role(Role::Admin)]);
+            assert_eq!(login.claims["sub"], "u-1");
+            assert_eq!(login.tokens.access_token, "at-1");
+            assert_eq!(login.return_to.as_deref(), Some("/orders"));
+
+            // The state is single-use
+            assert!(matches!(
+                oidc.handle_callback(&params).await,
+                Err(SecurityError::AuthenticationFailed(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_callback_rejections() {
+        let stub = provider_stub(Some("replayed-nonce"));
+        block_on(async move {
+            let oidc = oidc_client(&stub).await;
+
+            let params = callback_params(&oidc.authorization_request(None));
+            assert!(matches!(
+                oidc.handle_callback(&params).await,
+                Err(SecurityError::InvalidToken(_))
+            ));
+
+            // A verifier from another login fails PKCE at the token endpoint
+            let first = callback_params(&oidc.authorization_request(None));
+            let mut second = callback_params(&oidc.authorization_request(None));
+            second.insert("code".to_string(), first["code"].clone());
+            assert!(matches!(oidc.handle_callback(&second).await, Err(SecurityError::OAuth2(_))));
+
+            let mut denied = callback_params(&oidc.authorization_request(None));
+            denied.insert("error".to_string(), "access_denied".to_string());
+            assert!(matches!(
+                oidc.handle_callback(&denied).await,
+                Err(SecurityError::AuthenticationFailed(_))
+            ));
+        });
+    }
+}
@@ -1,7 +1,7 @@
 //! PreAuthorize module
 //! PreAuthorize模块（@PreAuthorize等价物）
 
-use crate::{Authority, SecurityContext};
+use crate::{EvaluationContext, Expression, ExpressionExt, SecurityContext, SecurityResult};
 use std::future::Future;
 use std::pin::Pin;
 
@@ -44,8 +44,12 @@ pub enum SecurityExpression {
     /// 对对象有许可
     HasPermission(String, String),
 
-    /// Custom expression
-    /// 自定义表达式
+    /// Parsed expression
+    /// 已解析的表达式
+    Parsed(Expression),
+
+    /// Custom expression, parsed when evaluated
+    /// 自定义表达式，在评估时解析
     Custom(String),
 }
 
@@ -53,73 +57,95 @@ impl SecurityExpression {
     /// Evaluate the expression
     /// 评估表达式
     pub async fn evaluate(&self, context: &SecurityContext) -> bool {
-        match self {
-            SecurityExpression::IsAuthenticated => context.is_authenticated().await,
-            SecurityExpression::IsAnonymous => !context.is_authenticated().await,
-            SecurityExpression::IsFullyAuthenticated => {
-                // For now, same as authenticated
-                context.is_authenticated().await
-            },
-            SecurityExpression::HasRole(role) => {
-                let role = crate::Role::from_str(role);
-                context.has_role(&role).await
-            },
-            SecurityExpression::HasAuthority(auth) => {
-                let authority = Authority::Permission(auth.clone());
-                context.has_authority(&authority).await
-            },
+        self.evaluate_with(&EvaluationContext::from_context(context).await)
+            .await
+    }
+
+    /// Evaluate the expression with method arguments and a permission evaluator
+    /// 使用方法参数和许可评估器评估表达式
+    ///
+    /// Malformed expressions and evaluation errors deny access.
+    /// 格式错误的表达式和评估错误都会拒绝访问。
+    pub async fn evaluate_with(&self, context: &EvaluationContext) -> bool {
+        let result = match self.to_expression() {
+            Ok(expr) => expr.evaluate(context).await,
+            Err(e) => Err(e),
+        };
+        result.unwrap_or_else(|e| {
+            tracing::warn!("Denying access: {}", e);
+            false
+        })
+    }
+
+    /// Get the equivalent parsed expression
+    /// 获取等价的已解析表达式
+    pub fn to_expression(&self) -> SecurityResult<Expression> {
+        let call = |name: &str, args: &[&str]| {
+            Expression::Call(
+                name.to_string(),
+                args.iter()
+                    .map(|arg| Expression::String((*arg).to_string()))
+                    .collect(),
+            )
+        };
+        Ok(match self {
+            SecurityExpression::HasRole(role) => call("hasRole", &[role]),
+            SecurityExpression::HasAuthority(auth) => call("hasAuthority", &[auth]),
+            SecurityExpression::IsAuthenticated => call("isAuthenticated", &[]),
+            SecurityExpression::IsAnonymous => call("isAnonymous", &[]),
+            SecurityExpression::IsFullyAuthenticated => call("isFullyAuthenticated", &[]),
             SecurityExpression::HasPermission(target, permission) => {
-                let auth = Authority::Permission(format!("{}:{}", target, permission));
-                context.has_authority(&auth).await
+                call("hasPermission", &[target, permission])
             },
-            SecurityExpression::Custom(expr) => {
-                // Custom expressions would need a full expression parser
-                // For now, return false
-                tracing::warn!("Custom security expression not implemented: {}", expr);
-                false
-            },
-        }
+            SecurityExpression::Parsed(expr) => expr.clone(),
+            SecurityExpression::Custom(expr) => Expression::parse(expr)?,
+        })
     }
 
     /// Parse expression from string
     /// 从字符串解析表达式
+    ///
+    /// Returns a single expression: one of the simple variants when the input is a
+    /// lone `hasRole(...)`, `isAuthenticated()`, etc., [`SecurityExpression::Parsed`]
+    /// otherwise, or [`SecurityExpression::Custom`] if the input does not parse, which
+    /// then denies access when evaluated.
+    /// 返回单个表达式：当输入仅为 `hasRole(...)`、`isAuthenticated()` 等时返回对应的简单变体，
+    /// 否则返回 [`SecurityExpression::Parsed`]；输入无法解析时返回
+    /// [`SecurityExpression::Custom`]，评估时将拒绝访问。
     pub fn parse(input: &str) -> Vec<Self> {
-        let mut expressions = Vec::new();
-
-        // Simple parser for common patterns
-        // In a full implementation, this would use a proper expression language
-
-        if input.contains("hasRole(") {
-            if let Some(start) = input.find("hasRole(\"") {
-                if let Some(end) = input[start..].find("\")") {
-                    let role = &input[start + 9..start + end];
-                    expressions.push(SecurityExpression::HasRole(role.to_string()));
-                }
-            }
-        }
-
-        if input.contains("hasAuthority(") {
-            if let Some(start) = input.find("hasAuthority(\"") {
-                if let Some(end) = input[start..].find("\")") {
-                    let auth = &input[start + 14..start + end];
-                    expressions.push(SecurityExpression::HasAuthority(auth.to_string()));
-                }
-            }
-        }
-
-        if input.contains("isAuthenticated()") {
-            expressions.push(SecurityExpression::IsAuthenticated);
-        }
-
-        if input.contains("isAnonymous()") {
-            expressions.push(SecurityExpression::IsAnonymous);
+        match Expression::parse(input) {
+            Ok(expr) => vec![Self::from(expr)],
+            Err(e) => {
+                tracing::warn!("{}", e);
+                vec![SecurityExpression::Custom(input.to_string())]
+            },
         }
+    }
+}
 
-        if expressions.is_empty() {
-            expressions.push(SecurityExpression::Custom(input.to_string()));
+impl From<Expression> for SecurityExpression {
+    fn from(expr: Expression) -> Self {
+        let Expression::Call(name, args) = &expr else {
+            return SecurityExpression::Parsed(expr);
+        };
+        let args: Vec<&str> = args
+            .iter()
+            .map_while(|arg| match arg {
+                Expression::String(value) => Some(value.as_str()),
+                _ => None,
+            })
+            .collect();
+        match (name.as_str(), args.as_slice()) {
+            ("hasRole", [role]) => SecurityExpression::HasRole((*role).to_string()),
+            ("hasAuthority", [auth]) => SecurityExpression::HasAuthority((*auth).to_string()),
+            ("isAuthenticated", []) => SecurityExpression::IsAuthenticated,
+            ("isAnonymous", []) => SecurityExpression::IsAnonymous,
+            ("isFullyAuthenticated", []) => SecurityExpression::IsFullyAuthenticated,
+            ("hasPermission", [target, permission]) => {
+                SecurityExpression::HasPermission((*target).to_string(), (*permission).to_string())
+            },
+            _ => SecurityExpression::Parsed(expr),
         }
-
-        expressions
     }
 }
 
@@ -195,6 +221,13 @@ impl PreAuthorizeOptions {
     /// Evaluate all expressions
     /// 评估所有表达式
     pub async fn evaluate(&self, context: &SecurityContext) -> bool {
+        self.evaluate_with(&EvaluationContext::from_context(context).await)
+            .await
+    }
+
+    /// Evaluate all expressions with method arguments and a permission evaluator
+    /// 使用方法参数和许可评估器评估所有表达式
+    pub async fn evaluate_with(&self, context: &EvaluationContext) -> bool {
         if self.expressions.is_empty() {
             return true;
         }
@@ -202,7 +235,7 @@ impl PreAuthorizeOptions {
         if self.require_all {
             // All must pass
             for expr in &self.expressions {
-                if !expr.evaluate(context).await {
+                if !expr.evaluate_with(context).await {
                     return false;
                 }
             }
@@ -210,7 +243,7 @@ impl PreAuthorizeOptions {
         } else {
             // Any can pass
             for expr in &self.expressions {
-                if expr.evaluate(context).await {
+                if expr.evaluate_with(context).await {
                     return true;
                 }
             }
@@ -227,12 +260,20 @@ impl Default for PreAuthorizeOptions {
 
 /// Helper function to check pre-authorize
 /// 检查pre-authorize的助手函数
+///
+/// # Errors / 错误
+///
+/// Returns [`SecurityError::InvalidExpression`](crate::SecurityError::InvalidExpression)
+/// if the expression is malformed or cannot be evaluated.
+/// 表达式格式错误或无法评估时返回
+/// [`SecurityError::InvalidExpression`](crate::SecurityError::InvalidExpression)。
 pub async fn check_pre_authorize(
     context: &SecurityContext,
     expression: &str,
 ) -> Result<bool, crate::SecurityError> {
-    let options = PreAuthorizeOptions::new().add_expression_string(expression);
-    Ok(options.evaluate(context).await)
+    Expression::parse(expression)?
+        .evaluate(&EvaluationContext::from_context(context).await)
+        .await
 }
 
 /// Common security expressions
@@ -299,4 +340,50 @@ mod tests {
         // Should return false because context is empty (no auth)
         assert!(!options.evaluate(&context).await);
     }
+
+    #[tokio::test]
+    async fn test_compound_expression() {
+        let exprs = SecurityExpression::parse(
+            "hasAnyRole('ADMIN', 'USER') and #username == authentication.name",
+        );
+        assert!(matches!(exprs.as_slice(), [SecurityExpression::Parsed(_)]));
+
+        let auth = crate::Authentication::new("alice", "secret")
+            .set_authenticated(true)
+            .set_authorities(vec![crate::Authority::Role(crate::Role::User)]);
+        let context = SecurityContext::with_authentication(auth);
+        let options = PreAuthorizeOptions {
+            expressions: exprs,
+            require_all: true,
+        };
+        let own = EvaluationContext::from_context(&context)
+            .await
+            .variable("username", "alice");
+        let other = EvaluationContext::from_context(&context)
+            .await
+            .variable("username", "bob");
+        assert!(options.evaluate_with(&own).await);
+        assert!(!options.evaluate_with(&other).await);
+
+        assert!(
+            SecurityExpression::Custom("isAuthenticated() and not hasRole('ADMIN')".into())
+                .evaluate(&context)
+                .await
+        );
+        assert!(
+            !SecurityExpression::Custom("hasRole('USER'".into())
+                .evaluate(&context)
+                .await
+        );
+        assert!(
+            check_pre_authorize(&context, "hasRole(\"USER\")")
+                .await
+                .unwrap()
+        );
+        assert!(
+            check_pre_authorize(&context, "hasRole('USER'")
+                .await
+                .is_err()
+        );
+    }
 }
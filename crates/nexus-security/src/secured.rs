@@ -103,11 +103,10 @@ impl SecurityMetadata {
                 });
             }
         } else {
-            // Must have at least one of roles OR authorities
-            if !has_roles
-                && !has_authorities
-                && (!self.roles.is_empty() || !self.authorities.is_empty())
-            {
+            // Must have at least one of roles OR authorities; an empty list grants nothing
+            let granted = (!self.roles.is_empty() && has_roles)
+                || (!self.authorities.is_empty() && has_authorities);
+            if !granted && (!self.roles.is_empty() || !self.authorities.is_empty()) {
                 return Err(crate::SecurityError::AccessDenied(
                     "Access denied: insufficient permissions".to_string(),
                 ));
@@ -204,7 +203,7 @@ impl<T> Secured for T where T: ?Sized {}
 /// ```rust,no_run,ignore
 /// use nexus_security::SecuredHelper;
 ///
-/// #[secured(roles = ["ADMIN"])]
+/// #[secured("ROLE_ADMIN")]
 /// async fn delete_user(id: u64) -> Result<(), Error> {
 ///     // ...
 /// }
@@ -302,4 +301,44 @@ mod tests {
         let permit = Constraints::permit_all();
         assert!(permit.roles.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_require_any() {
+        let metadata = SecurityMetadata::new()
+            .add_role(Role::Admin)
+            .add_authority(Authority::permission("USER:DELETE"))
+            .require_all(false);
+        let context = |authorities| {
+            SecurityContext::with_authentication(
+                crate::Authentication::new("alice", "secret")
+                    .set_authenticated(true)
+                    .set_authorities(authorities),
+            )
+        };
+
+        assert!(
+            metadata
+                .check(&context(vec![Authority::Role(Role::Admin)]))
+                .await
+                .is_ok()
+        );
+        assert!(
+            metadata
+                .check(&context(vec![Authority::permission("USER:DELETE")]))
+                .await
+                .is_ok()
+        );
+        assert!(matches!(
+            metadata
+                .check(&context(vec![Authority::Role(Role::User)]))
+                .await,
+            Err(crate::SecurityError::AccessDenied(_))
+        ));
+        assert!(
+            Constraints::user_or_admin()
+                .check(&SecurityContext::new())
+                .await
+                .is_err()
+        );
+    }
 }